@@ -0,0 +1,195 @@
+//! Compute units consumed by the program's hot paths, measured on the SBF
+//! build in solana-program-test. Every case prints its count next to its
+//! budget in `BUDGETS`, and the test fails once any case goes over, so a
+//! regression shows up as a failure and an optimization as a budget that
+//! can be lowered.
+//!
+//! Build the program first, then run from `gadder_gold_token/cu-bench/`:
+//! `cargo build-sbf --manifest-path ../Cargo.toml && SBF_OUT_DIR=../target/deploy cargo test -- --nocapture`
+
+use gadder_gold_token::{
+    events::VoteChoice,
+    instruction,
+    test_utils::{
+        ballot_page_data, governance_authority_address, governance_config, program_config_with, proposal, rewards_vault_authority,
+        stake_address, stake_data, stake_data_with_rewards, stake_pool_authority, staking_pool_data, token_account_data,
+    },
+    TokenContract,
+};
+use solana_program::{
+    clock::Clock,
+    instruction::{AccountMeta, Instruction},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction, system_program,
+};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use spl_token::state::Mint;
+
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([0x47; 32]);
+const NOW: i64 = 1_700_000_000;
+const STAKE: u64 = 1_000_000_000_000;
+/// Ballots on a full page, `BALLOTS_PER_PAGE` in the governance module.
+const FULL_PAGE: usize = 32;
+
+/// Most compute units each case may consume. Lower a budget when an
+/// optimization lands; raising one needs a reason in the commit that does it.
+const BUDGETS: &[(&str, u64)] = &[
+    ("transfer", 40_000),
+    ("stake", 60_000),
+    ("claim_rewards", 60_000),
+    ("vote_on_proposal", 80_000),
+    ("tally_ballot_page", 80_000),
+    ("finalize_proposal", 60_000),
+    ("execute_proposal", 80_000),
+];
+
+fn add_account(program_test: &mut ProgramTest, key: Pubkey, owner: Pubkey, data: Vec<u8>) {
+    let lamports = Rent::default().minimum_balance(data.len());
+    program_test.add_account(key, Account { lamports, data, owner, executable: false, rent_epoch: 0 });
+}
+
+fn add_wallet(program_test: &mut ProgramTest, key: Pubkey) {
+    program_test.add_account(key, Account { lamports: 10_000_000_000, data: Vec::new(), owner: system_program::id(), executable: false, rent_epoch: 0 });
+}
+
+struct Bench {
+    context: ProgramTestContext,
+    report: Vec<(&'static str, u64)>,
+}
+
+impl Bench {
+    /// Sends `ix` alone in a transaction and records the units it consumed.
+    async fn run(&mut self, name: &'static str, ix: Instruction, signers: &[&Keypair]) {
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let payer = &self.context.payer;
+        let signers: Vec<&Keypair> = std::iter::once(payer).chain(signers.iter().copied()).collect();
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &signers, blockhash);
+        let outcome = self.context.banks_client.process_transaction_with_metadata(tx).await.unwrap();
+        let metadata = outcome.metadata.expect("transaction was not processed");
+        if let Err(err) = outcome.result {
+            panic!("{} failed: {}\n{}", name, err, metadata.log_messages.join("\n"));
+        }
+        self.report.push((name, metadata.compute_units_consumed));
+    }
+}
+
+#[tokio::test]
+async fn compute_units_stay_within_budget() {
+    let mut program_test = ProgramTest::new("gadder_gold_token", PROGRAM_ID, None);
+    program_test.prefer_bpf(true);
+
+    let (governance_signer, alice, bob) = (Keypair::new(), Keypair::new(), Keypair::new());
+    let mint = TokenContract::mint_address(&PROGRAM_ID).0;
+    let mut mint_data = vec![0u8; Mint::LEN];
+    let mint_state = Mint { mint_authority: COption::None, supply: 4 * STAKE, decimals: 9, is_initialized: true, freeze_authority: COption::None };
+    Mint::pack(mint_state, &mut mint_data).unwrap();
+    add_account(&mut program_test, mint, spl_token::id(), mint_data);
+    add_wallet(&mut program_test, alice.pubkey());
+    add_wallet(&mut program_test, bob.pubkey());
+
+    // A transfer paying the protocol fee into the fee vault.
+    let (config, config_data) = program_config_with(&PROGRAM_ID, &governance_signer.pubkey(), 25);
+    add_account(&mut program_test, config, PROGRAM_ID, config_data);
+    let (alice_token, bob_token, fee_vault) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    add_account(&mut program_test, alice_token, spl_token::id(), token_account_data(&mint, &alice.pubkey(), 2 * STAKE));
+    add_account(&mut program_test, bob_token, spl_token::id(), token_account_data(&mint, &bob.pubkey(), 0));
+    let fee_vault_authority = TokenContract::fee_vault_authority(&PROGRAM_ID).0;
+    add_account(&mut program_test, fee_vault, spl_token::id(), token_account_data(&mint, &fee_vault_authority, 0));
+
+    // Alice opens a stake position; Bob's has been open since before the
+    // proposals and has rewards waiting in the rewards vault.
+    let (stake_pool, pool_state) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let stake_pool_authority = stake_pool_authority(&PROGRAM_ID, &mint).0;
+    add_account(&mut program_test, stake_pool, spl_token::id(), token_account_data(&mint, &stake_pool_authority, STAKE));
+    add_account(&mut program_test, pool_state, PROGRAM_ID, staking_pool_data(STAKE));
+    add_account(&mut program_test, stake_address(&PROGRAM_ID, &alice.pubkey(), &mint).0, PROGRAM_ID, stake_data(0, 0));
+    let bob_stake = stake_address(&PROGRAM_ID, &bob.pubkey(), &mint).0;
+    add_account(&mut program_test, bob_stake, PROGRAM_ID, stake_data_with_rewards(STAKE, 0, STAKE / 100));
+    let rewards_vault = Pubkey::new_unique();
+    let rewards_vault_authority = rewards_vault_authority(&PROGRAM_ID).0;
+    add_account(&mut program_test, rewards_vault, spl_token::id(), token_account_data(&mint, &rewards_vault_authority, STAKE / 100));
+
+    // Proposal 1 is open for votes. Proposal 2 has closed with a full ballot
+    // page voting yes, and moves lamports out of the governance authority once executed.
+    let (governance, governance_data) = governance_config(&PROGRAM_ID, &pool_state, 2_000, 5_000);
+    add_account(&mut program_test, governance, PROGRAM_ID, governance_data);
+    let (open_proposal, open_data) = proposal(&PROGRAM_ID, 1, 1, NOW + 86_400, 0, &[]);
+    add_account(&mut program_test, open_proposal, PROGRAM_ID, open_data);
+    let governance_authority = governance_authority_address(&PROGRAM_ID).0;
+    add_wallet(&mut program_test, governance_authority);
+    let recipient = Pubkey::new_unique();
+    let payout = system_instruction::transfer(&governance_authority, &recipient, 1_000_000_000);
+    let (closed_proposal, closed_data) = proposal(&PROGRAM_ID, 2, 1, NOW - 1, 1, &[payout]);
+    add_account(&mut program_test, closed_proposal, PROGRAM_ID, closed_data);
+    let ballots: Vec<(Pubkey, u64)> = (0..FULL_PAGE).map(|_| (Pubkey::new_unique(), STAKE / FULL_PAGE as u64)).collect();
+    let page = Pubkey::new_unique();
+    add_account(&mut program_test, page, PROGRAM_ID, ballot_page_data(&closed_proposal, 0, &ballots, u32::MAX));
+
+    let mut context = program_test.start_with_context().await;
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    context.set_sysvar(&Clock { unix_timestamp: NOW, ..clock });
+    let mut bench = Bench { context, report: Vec::new() };
+
+    let token_program = spl_token::id();
+    bench
+        .run(
+            "transfer",
+            instruction::transfer(&PROGRAM_ID, &alice_token, &bob_token, &alice.pubkey(), &mint, &fee_vault, &token_program, STAKE / 2),
+            &[&alice],
+        )
+        .await;
+    bench
+        .run(
+            "stake",
+            instruction::stake(&PROGRAM_ID, &alice.pubkey(), &alice_token, &stake_pool, &pool_state, &mint, &token_program, STAKE, 30),
+            &[&alice],
+        )
+        .await;
+    bench
+        .run(
+            "claim_rewards",
+            instruction::claim_rewards(&PROGRAM_ID, &bob.pubkey(), &bob_token, &rewards_vault, &pool_state, &mint, &token_program),
+            &[&bob],
+        )
+        .await;
+    bench
+        .run(
+            "vote_on_proposal",
+            instruction::vote_on_proposal(&PROGRAM_ID, 1, &bob.pubkey(), &bob_stake, &mint, None, &[], VoteChoice::Yes),
+            &[&bob],
+        )
+        .await;
+    bench.run("tally_ballot_page", instruction::tally_ballot_page(&PROGRAM_ID, 2, &page, 0), &[]).await;
+    bench.run("finalize_proposal", instruction::finalize_proposal(&PROGRAM_ID, 2, &pool_state, None), &[]).await;
+    let execute_accounts = [
+        AccountMeta::new(governance_authority, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(recipient, false),
+    ];
+    bench
+        .run(
+            "execute_proposal",
+            instruction::execute_proposal(&PROGRAM_ID, 2, &governance_signer.pubkey(), &execute_accounts),
+            &[&governance_signer],
+        )
+        .await;
+
+    println!("{:<20} {:>10} {:>10}", "instruction", "units", "budget");
+    let mut over_budget = Vec::new();
+    for (name, units) in &bench.report {
+        let budget = BUDGETS.iter().find(|(case, _)| case == name).map(|(_, budget)| *budget).expect("every case has a budget");
+        println!("{:<20} {:>10} {:>10}", name, units, budget);
+        if *units > budget {
+            over_budget.push(format!("{} used {} of {}", name, units, budget));
+        }
+    }
+    assert!(over_budget.is_empty(), "over budget: {}", over_budget.join(", "));
+}
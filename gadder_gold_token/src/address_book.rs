@@ -0,0 +1,337 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    keccak,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use crate::{ai_contract::recover_evm_address, error::GgtError};
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const ADDRESS_BOOK_SEED: &[u8] = b"address_book";
+pub const MAX_ADDRESS_BOOK_ENTRIES: usize = 8;
+pub const MAX_CHAIN_LEN: usize = 16;
+/// Raw destination bytes; fits EVM (20), Solana (32) and bech32-encoded addresses.
+pub const MAX_DESTINATION_LEN: usize = 64;
+
+pub fn address_book_address(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ADDRESS_BOOK_SEED, owner.as_ref()], program_id)
+}
+
+/// EIP-191 `personal_sign` digest an EVM key signs to prove it controls `destination`.
+pub fn destination_digest(owner: &Pubkey, chain: &str, destination: &[u8; 20]) -> [u8; 32] {
+    let hex: String = destination.iter().map(|b| format!("{:02x}", b)).collect();
+    let message = format!("Register 0x{} as GGT bridge destination for {} on {}", hex, owner, chain);
+    let prefix = format!("\x19Ethereum Signed Message:\n{}", message.len());
+    keccak::hashv(&[prefix.as_bytes(), message.as_bytes()]).to_bytes()
+}
+
+/// Signature and recovery id over `destination_digest`.
+pub type EvmProof = ([u8; 64], u8);
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct AddressBookEntry {
+    pub chain: String,
+    pub destination: Vec<u8>,
+    /// Whether the destination key signed `destination_digest`.
+    pub evm_verified: bool,
+}
+
+impl AddressBookEntry {
+    const LEN: usize = 83; // len (1) + chain (16) + len (1) + destination (64) + bool (1)
+}
+
+/// A user's registered bridge destinations. In `strict` mode bridge locks from
+/// the owner must target one of them.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct AddressBook {
+    pub owner: Pubkey,
+    pub strict: bool,
+    pub entries: Vec<AddressBookEntry>,
+    pub is_initialized: bool,
+}
+
+impl AddressBook {
+    pub fn contains(&self, chain: &str, destination: &[u8]) -> bool {
+        self.entries.iter().any(|e| e.chain == chain && e.destination == destination)
+    }
+}
+
+impl Sealed for AddressBook {}
+
+impl IsInitialized for AddressBook {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for AddressBook {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [112, 200, 175, 39, 249, 237, 44, 141];
+}
+
+impl Pack for AddressBook {
+    const LEN: usize = 708; // discriminator (8) + version (1) + Pubkey (32) + bool (1) + count (1) + AddressBookEntry (83) * MAX_ADDRESS_BOOK_ENTRIES + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        let mut cursor = 0;
+        dst[cursor..cursor + 32].copy_from_slice(self.owner.as_ref());
+        cursor += 32;
+        dst[cursor] = self.strict as u8;
+        cursor += 1;
+        dst[cursor] = self.entries.len() as u8;
+        cursor += 1;
+        for slot in 0..MAX_ADDRESS_BOOK_ENTRIES {
+            let entry = &mut dst[cursor..cursor + AddressBookEntry::LEN];
+            entry.fill(0);
+            if let Some(e) = self.entries.get(slot) {
+                entry[0] = e.chain.len() as u8;
+                entry[1..1 + e.chain.len()].copy_from_slice(e.chain.as_bytes());
+                entry[17] = e.destination.len() as u8;
+                entry[18..18 + e.destination.len()].copy_from_slice(&e.destination);
+                entry[82] = e.evm_verified as u8;
+            }
+            cursor += AddressBookEntry::LEN;
+        }
+        dst[cursor] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let mut cursor = 0;
+        let owner = Pubkey::new_from_array(src[cursor..cursor + 32].try_into().unwrap());
+        cursor += 32;
+        let strict = src[cursor] != 0;
+        cursor += 1;
+        let count = src[cursor] as usize;
+        cursor += 1;
+        if count > MAX_ADDRESS_BOOK_ENTRIES {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut entries = Vec::with_capacity(count);
+        for slot in 0..MAX_ADDRESS_BOOK_ENTRIES {
+            let entry = &src[cursor..cursor + AddressBookEntry::LEN];
+            if slot < count {
+                let (chain_len, destination_len) = (entry[0] as usize, entry[17] as usize);
+                if chain_len > MAX_CHAIN_LEN || destination_len > MAX_DESTINATION_LEN {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                entries.push(AddressBookEntry {
+                    chain: String::from_utf8(entry[1..1 + chain_len].to_vec()).map_err(|_| ProgramError::InvalidAccountData)?,
+                    destination: entry[18..18 + destination_len].to_vec(),
+                    evm_verified: entry[82] != 0,
+                });
+            }
+            cursor += AddressBookEntry::LEN;
+        }
+        let is_initialized = src[cursor] != 0;
+        Ok(AddressBook { owner, strict, entries, is_initialized })
+    }
+}
+
+/// Loads `owner`'s address book, or `None` if they never created one.
+pub(crate) fn load_address_book(program_id: &Pubkey, book_acc: &AccountInfo, owner: &Pubkey) -> Result<Option<AddressBook>, ProgramError> {
+    assert_pda(book_acc, address_book_address(program_id, owner))?;
+    if book_acc.lamports() == 0 {
+        return Ok(None);
+    }
+    assert_owned_by(book_acc, program_id)?;
+    AddressBook::unpack(&book_acc.try_borrow_data()?).map(Some)
+}
+
+/// Loads the signer's address book, creating it on first use.
+fn load_or_create<'a>(
+    program_id: &Pubkey,
+    book_acc: &AccountInfo<'a>,
+    owner_acc: &AccountInfo<'a>,
+    system_program_acc: &AccountInfo<'a>,
+) -> Result<AddressBook, ProgramError> {
+    if let Some(book) = load_address_book(program_id, book_acc, owner_acc.key)? {
+        return Ok(book);
+    }
+    let (_, bump) = address_book_address(program_id, owner_acc.key);
+    invoke_signed(
+        &system_instruction::create_account(
+            owner_acc.key,
+            book_acc.key,
+            Rent::get()?.minimum_balance(AddressBook::LEN),
+            AddressBook::LEN as u64,
+            program_id,
+        ),
+        &[owner_acc.clone(), book_acc.clone(), system_program_acc.clone()],
+        &[&[ADDRESS_BOOK_SEED, owner_acc.key.as_ref(), &[bump]]],
+    )?;
+    Ok(AddressBook { owner: *owner_acc.key, strict: false, entries: Vec::new(), is_initialized: true })
+}
+
+/// Accounts for `register_destination` and `set_strict_mode`.
+pub struct AddressBookAccounts<'a, 'info> {
+    pub book_acc: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> AddressBookAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            book_acc: next_account_info(account_info_iter)?,
+            owner_acc: next_account_info(account_info_iter)?,
+            system_program_acc: next_account_info(account_info_iter)?,
+        };
+        assert_signer(ctx.owner_acc)?;
+        Ok(ctx)
+    }
+}
+
+pub struct RemoveDestinationAccounts<'a, 'info> {
+    pub book_acc: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> RemoveDestinationAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self { book_acc: next_account_info(account_info_iter)?, owner_acc: next_account_info(account_info_iter)? };
+        assert_signer(ctx.owner_acc)?;
+        Ok(ctx)
+    }
+}
+
+/// Adds `destination` on `chain` to the signer's address book. With an EVM
+/// signature over `destination_digest` the entry is marked verified.
+pub fn register_destination(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    chain: String,
+    destination: Vec<u8>,
+    evm_proof: Option<EvmProof>,
+) -> ProgramResult {
+    let AddressBookAccounts { book_acc, owner_acc, system_program_acc } = AddressBookAccounts::try_from(accounts)?;
+    if chain.is_empty() || chain.len() > MAX_CHAIN_LEN || destination.is_empty() || destination.len() > MAX_DESTINATION_LEN {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let evm_verified = match evm_proof {
+        Some((signature, recovery_id)) => {
+            let evm_address: [u8; 20] = destination.as_slice().try_into().map_err(|_| GgtError::InvalidEvmSignature)?;
+            let recovered = recover_evm_address(&destination_digest(owner_acc.key, &chain, &evm_address), &signature, recovery_id)?;
+            if recovered != evm_address {
+                return Err(GgtError::InvalidEvmSignature.into());
+            }
+            true
+        }
+        None => false,
+    };
+
+    let mut book = load_or_create(program_id, book_acc, owner_acc, system_program_acc)?;
+    if let Some(entry) = book.entries.iter_mut().find(|e| e.chain == chain && e.destination == destination) {
+        entry.evm_verified |= evm_verified;
+    } else if book.entries.len() < MAX_ADDRESS_BOOK_ENTRIES {
+        book.entries.push(AddressBookEntry { chain: chain.clone(), destination, evm_verified });
+    } else {
+        return Err(GgtError::AddressBookFull.into());
+    }
+    pack_state(&book, book_acc)?;
+    msg!("Registered {} destination (verified: {})", chain, evm_verified);
+    Ok(())
+}
+
+pub fn remove_destination(program_id: &Pubkey, accounts: &[AccountInfo], chain: String, destination: Vec<u8>) -> ProgramResult {
+    let RemoveDestinationAccounts { book_acc, owner_acc } = RemoveDestinationAccounts::try_from(accounts)?;
+    let mut book = load_address_book(program_id, book_acc, owner_acc.key)?.ok_or(ProgramError::UninitializedAccount)?;
+    let before = book.entries.len();
+    book.entries.retain(|e| e.chain != chain || e.destination != destination);
+    if book.entries.len() == before {
+        return Err(ProgramError::InvalidArgument);
+    }
+    pack_state(&book, book_acc)?;
+    msg!("Removed {} destination", chain);
+    Ok(())
+}
+
+/// Turns strict mode on or off. While on, bridge locks must target a registered destination.
+pub fn set_strict_mode(program_id: &Pubkey, accounts: &[AccountInfo], strict: bool) -> ProgramResult {
+    let AddressBookAccounts { book_acc, owner_acc, system_program_acc } = AddressBookAccounts::try_from(accounts)?;
+    let mut book = load_or_create(program_id, book_acc, owner_acc, system_program_acc)?;
+    book.strict = strict;
+    pack_state(&book, book_acc)?;
+    msg!("Address book strict mode {}", if strict { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Rejects a lock from `sender` unless it is allowed by their address book.
+pub(crate) fn check_lock_destination(
+    program_id: &Pubkey,
+    book_acc: &AccountInfo,
+    sender: &Pubkey,
+    chain: &str,
+    destination: Option<&[u8]>,
+) -> ProgramResult {
+    let Some(book) = load_address_book(program_id, book_acc, sender)? else {
+        return Ok(());
+    };
+    if book.strict && !destination.is_some_and(|d| book.contains(chain, d)) {
+        msg!("Strict mode: destination is not in the address book for {}", chain);
+        return Err(GgtError::DestinationNotInAddressBook.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{account_info, SimulatedBank};
+
+    #[test]
+    fn test_register_destinations_and_enforce_strict_mode() {
+        let _bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (owner_key, system_program_id) = (Pubkey::new_unique(), Pubkey::default());
+        let (book_key, _) = address_book_address(&program_id, &owner_key);
+        let (mut l0, mut l1, mut l2) = (0u64, 10_000_000u64, 0u64);
+        let mut book_data = vec![0u8; AddressBook::LEN];
+        let (mut d1, mut d2) = (vec![], vec![]);
+        let book_acc = account_info(&book_key, false, true, &mut l0, &mut book_data, &program_id);
+        let owner_acc = account_info(&owner_key, true, true, &mut l1, &mut d1, &system_program_id);
+        let system_program_acc = account_info(&system_program_id, false, false, &mut l2, &mut d2, &system_program_id);
+        let accounts = [book_acc.clone(), owner_acc, system_program_acc];
+
+        // No address book yet: locks are unrestricted.
+        assert!(check_lock_destination(&program_id, &book_acc, &owner_key, "Ethereum", None).is_ok());
+
+        let secret_key = libsecp256k1::SecretKey::parse(&[9u8; 32]).unwrap();
+        let public_key = libsecp256k1::PublicKey::from_secret_key(&secret_key);
+        let evm_address: [u8; 20] = keccak::hash(&public_key.serialize()[1..]).to_bytes()[12..].try_into().unwrap();
+        let digest = destination_digest(&owner_key, "Ethereum", &evm_address);
+        let (signature, recovery_id) = libsecp256k1::sign(&libsecp256k1::Message::parse(&digest), &secret_key);
+        let proof = Some((signature.serialize(), recovery_id.serialize()));
+
+        assert_eq!(
+            register_destination(&program_id, &accounts, "Ethereum".to_string(), vec![0x11; 20], proof),
+            Err(GgtError::InvalidEvmSignature.into())
+        );
+        register_destination(&program_id, &accounts, "Ethereum".to_string(), evm_address.to_vec(), proof).unwrap();
+        set_strict_mode(&program_id, &accounts, true).unwrap();
+
+        let book = AddressBook::unpack(&book_acc.try_borrow_data().unwrap()).unwrap();
+        assert!(book.strict && book.entries[0].evm_verified);
+        assert!(check_lock_destination(&program_id, &book_acc, &owner_key, "Ethereum", Some(&evm_address)).is_ok());
+        for (chain, destination) in [("Ethereum", Some(&[0x22; 20][..])), ("Polygon", Some(&evm_address[..])), ("Ethereum", None)] {
+            assert_eq!(
+                check_lock_destination(&program_id, &book_acc, &owner_key, chain, destination),
+                Err(GgtError::DestinationNotInAddressBook.into())
+            );
+        }
+
+        remove_destination(&program_id, &accounts[..2], "Ethereum".to_string(), evm_address.to_vec()).unwrap();
+        assert!(AddressBook::unpack(&book_acc.try_borrow_data().unwrap()).unwrap().entries.is_empty());
+    }
+}
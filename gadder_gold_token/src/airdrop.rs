@@ -0,0 +1,420 @@
+//! Merkle airdrops. A creator funds a vault owned by the distribution PDA and
+//! commits to the full recipient list with a single Merkle root; each
+//! recipient then claims their own allocation with a proof. Claimed indices
+//! are tracked in a bitmap account so every leaf pays out once.
+//!
+//! Leaves are `hashv([0x00, index, recipient, amount])` with little-endian
+//! integers, inner nodes `hashv([0x01, a, b])` with the pair sorted, so proofs
+//! carry no left/right flags and a leaf can never pass as a node.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
+    hash::hashv,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use crate::{error::GgtError, token_program};
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer, assert_token_account_mint};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const AIRDROP_SEED: &[u8] = b"airdrop";
+pub const AIRDROP_BITMAP_SEED: &[u8] = b"airdrop_bitmap";
+/// Keeps the claimed bitmap within the 10 KiB a single CPI `create_account`
+/// may allocate: 81,920 recipients.
+pub const MAX_AIRDROP_RECIPIENTS: u32 = MAX_PERMITTED_DATA_INCREASE as u32 * 8;
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Distribution `distribution_id` funded by `creator`.
+pub fn distribution_address(program_id: &Pubkey, creator: &Pubkey, distribution_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AIRDROP_SEED, creator.as_ref(), &distribution_id.to_le_bytes()], program_id)
+}
+
+/// Claimed bitmap of `distribution`, one bit per leaf index.
+pub fn bitmap_address(program_id: &Pubkey, distribution: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AIRDROP_BITMAP_SEED, distribution.as_ref()], program_id)
+}
+
+pub fn leaf_hash(index: u64, recipient: &Pubkey, amount: u64) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, &index.to_le_bytes(), recipient.as_ref(), &amount.to_le_bytes()]).to_bytes()
+}
+
+pub fn node_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Whether `proof` leads from `leaf` to `root`.
+pub fn verify_proof(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    proof.iter().fold(leaf, |node, sibling| node_hash(&node, sibling)) == *root
+}
+
+pub struct Distribution {
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    /// Token account owned by the distribution PDA holding the unclaimed tokens.
+    pub vault: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub distribution_id: u64,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub num_recipients: u32,
+    pub is_initialized: bool,
+}
+
+impl Sealed for Distribution {}
+
+impl IsInitialized for Distribution {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for Distribution {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [176, 85, 17, 11, 13, 194, 18, 1];
+}
+
+impl Pack for Distribution {
+    const LEN: usize = 166; // discriminator (8) + version (1) + Pubkey (32) * 3 + [u8; 32] (32) + u64 (8) * 3 + u32 (4) + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..32].copy_from_slice(self.creator.as_ref());
+        dst[32..64].copy_from_slice(self.mint.as_ref());
+        dst[64..96].copy_from_slice(self.vault.as_ref());
+        dst[96..128].copy_from_slice(&self.merkle_root);
+        dst[128..136].copy_from_slice(&self.distribution_id.to_le_bytes());
+        dst[136..144].copy_from_slice(&self.total_amount.to_le_bytes());
+        dst[144..152].copy_from_slice(&self.claimed_amount.to_le_bytes());
+        dst[152..156].copy_from_slice(&self.num_recipients.to_le_bytes());
+        dst[156] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(Distribution {
+            creator: Pubkey::new_from_array(src[0..32].try_into().unwrap()),
+            mint: Pubkey::new_from_array(src[32..64].try_into().unwrap()),
+            vault: Pubkey::new_from_array(src[64..96].try_into().unwrap()),
+            merkle_root: src[96..128].try_into().unwrap(),
+            distribution_id: u64::from_le_bytes(src[128..136].try_into().unwrap()),
+            total_amount: u64::from_le_bytes(src[136..144].try_into().unwrap()),
+            claimed_amount: u64::from_le_bytes(src[144..152].try_into().unwrap()),
+            num_recipients: u32::from_le_bytes(src[152..156].try_into().unwrap()),
+            is_initialized: src[156] != 0,
+        })
+    }
+}
+
+/// The creator signs, pays for both PDAs and funds the vault, which must
+/// already exist and be owned by the distribution PDA.
+pub struct CreateDistributionAccounts<'a, 'info> {
+    pub distribution_acc: &'a AccountInfo<'info>,
+    pub bitmap_acc: &'a AccountInfo<'info>,
+    pub creator_acc: &'a AccountInfo<'info>,
+    pub creator_token_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CreateDistributionAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            distribution_acc: next_account_info(account_info_iter)?,
+            bitmap_acc: next_account_info(account_info_iter)?,
+            creator_acc: next_account_info(account_info_iter)?,
+            creator_token_acc: next_account_info(account_info_iter)?,
+            vault_acc: next_account_info(account_info_iter)?,
+            mint_acc: next_account_info(account_info_iter)?,
+            token_program_acc: next_account_info(account_info_iter)?,
+            system_program_acc: next_account_info(account_info_iter)?,
+        };
+        assert_signer(ctx.creator_acc)?;
+        token_program::check_token_program(ctx.token_program_acc, ctx.mint_acc)?;
+        if assert_token_account_mint(ctx.vault_acc, ctx.mint_acc.key)?.owner != *ctx.distribution_acc.key {
+            msg!("Airdrop vault must be owned by the distribution PDA");
+            return Err(ProgramError::IllegalOwner);
+        }
+        Ok(ctx)
+    }
+}
+
+/// The recipient signs; tokens go to a token account they own.
+pub struct ClaimAirdropAccounts<'a, 'info> {
+    pub distribution_acc: &'a AccountInfo<'info>,
+    pub bitmap_acc: &'a AccountInfo<'info>,
+    pub recipient_acc: &'a AccountInfo<'info>,
+    pub recipient_token_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ClaimAirdropAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            distribution_acc: next_account_info(account_info_iter)?,
+            bitmap_acc: next_account_info(account_info_iter)?,
+            recipient_acc: next_account_info(account_info_iter)?,
+            recipient_token_acc: next_account_info(account_info_iter)?,
+            vault_acc: next_account_info(account_info_iter)?,
+            mint_acc: next_account_info(account_info_iter)?,
+            token_program_acc: next_account_info(account_info_iter)?,
+        };
+        assert_signer(ctx.recipient_acc)?;
+        assert_owned_by(ctx.distribution_acc, program_id)?;
+        assert_owned_by(ctx.bitmap_acc, program_id)?;
+        assert_pda(ctx.bitmap_acc, bitmap_address(program_id, ctx.distribution_acc.key))?;
+        token_program::check_token_program(ctx.token_program_acc, ctx.mint_acc)?;
+        if assert_token_account_mint(ctx.recipient_token_acc, ctx.mint_acc.key)?.owner != *ctx.recipient_acc.key {
+            return Err(ProgramError::IllegalOwner);
+        }
+        Ok(ctx)
+    }
+}
+
+/// Creates distribution `distribution_id` for `num_recipients` leaves under
+/// `merkle_root` and moves `total_amount` from the creator into its vault.
+pub fn create_distribution(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    distribution_id: u64,
+    merkle_root: [u8; 32],
+    total_amount: u64,
+    num_recipients: u32,
+) -> ProgramResult {
+    let CreateDistributionAccounts {
+        distribution_acc,
+        bitmap_acc,
+        creator_acc,
+        creator_token_acc,
+        vault_acc,
+        mint_acc,
+        token_program_acc,
+        system_program_acc,
+    } = CreateDistributionAccounts::try_from(accounts)?;
+    if total_amount == 0 || !(1..=MAX_AIRDROP_RECIPIENTS).contains(&num_recipients) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let bump = assert_pda(distribution_acc, distribution_address(program_id, creator_acc.key, distribution_id))?;
+    let bitmap_bump = assert_pda(bitmap_acc, bitmap_address(program_id, distribution_acc.key))?;
+    if distribution_acc.lamports() > 0 || bitmap_acc.lamports() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            creator_acc.key,
+            distribution_acc.key,
+            rent.minimum_balance(Distribution::LEN),
+            Distribution::LEN as u64,
+            program_id,
+        ),
+        &[creator_acc.clone(), distribution_acc.clone(), system_program_acc.clone()],
+        &[&[AIRDROP_SEED, creator_acc.key.as_ref(), &distribution_id.to_le_bytes(), &[bump]]],
+    )?;
+    let bitmap_len = num_recipients.div_ceil(8) as usize;
+    invoke_signed(
+        &system_instruction::create_account(
+            creator_acc.key,
+            bitmap_acc.key,
+            rent.minimum_balance(bitmap_len),
+            bitmap_len as u64,
+            program_id,
+        ),
+        &[creator_acc.clone(), bitmap_acc.clone(), system_program_acc.clone()],
+        &[&[AIRDROP_BITMAP_SEED, distribution_acc.key.as_ref(), &[bitmap_bump]]],
+    )?;
+    token_program::transfer(token_program_acc, creator_token_acc, mint_acc, vault_acc, creator_acc, total_amount, &[])?;
+
+    let distribution = Distribution {
+        creator: *creator_acc.key,
+        mint: *mint_acc.key,
+        vault: *vault_acc.key,
+        merkle_root,
+        distribution_id,
+        total_amount,
+        claimed_amount: 0,
+        num_recipients,
+        is_initialized: true,
+    };
+    pack_state(&distribution, distribution_acc)?;
+    msg!("Created airdrop {} of {} across {} recipients", distribution_acc.key, total_amount, num_recipients);
+    Ok(())
+}
+
+/// Pays leaf `index` of the distribution to the signing recipient once `proof`
+/// checks out against the stored root.
+pub fn claim_airdrop(program_id: &Pubkey, accounts: &[AccountInfo], index: u64, amount: u64, proof: Vec<[u8; 32]>) -> ProgramResult {
+    let ClaimAirdropAccounts { distribution_acc, bitmap_acc, recipient_acc, recipient_token_acc, vault_acc, mint_acc, token_program_acc } =
+        ClaimAirdropAccounts::try_from(program_id, accounts)?;
+    let mut distribution = Distribution::unpack(&distribution_acc.try_borrow_data()?)?;
+    let bump = assert_pda(
+        distribution_acc,
+        distribution_address(program_id, &distribution.creator, distribution.distribution_id),
+    )?;
+    if *vault_acc.key != distribution.vault || *mint_acc.key != distribution.mint {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if index >= distribution.num_recipients as u64 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if !verify_proof(&distribution.merkle_root, leaf_hash(index, recipient_acc.key, amount), &proof) {
+        return Err(GgtError::InvalidMerkleProof.into());
+    }
+
+    let (byte, bit) = ((index / 8) as usize, 1u8 << (index % 8));
+    {
+        let mut bitmap = bitmap_acc.try_borrow_mut_data()?;
+        let slot = bitmap.get_mut(byte).ok_or(ProgramError::InvalidAccountData)?;
+        if *slot & bit != 0 {
+            return Err(GgtError::AirdropAlreadyClaimed.into());
+        }
+        *slot |= bit;
+    }
+    distribution.claimed_amount = distribution.claimed_amount.checked_add(amount).ok_or(GgtError::MathOverflow)?;
+    if distribution.claimed_amount > distribution.total_amount {
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    let creator = distribution.creator;
+    let id_bytes = distribution.distribution_id.to_le_bytes();
+    let signer_seeds: &[&[u8]] = &[AIRDROP_SEED, creator.as_ref(), &id_bytes, &[bump]];
+    token_program::transfer(token_program_acc, vault_acc, mint_acc, recipient_token_acc, distribution_acc, amount, &[signer_seeds])?;
+    pack_state(&distribution, distribution_acc)?;
+    msg!("Claimed airdrop leaf {} of {} for {}", index, distribution_acc.key, recipient_acc.key);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{account_info, token_account_data, SimulatedBank};
+
+    #[test]
+    fn test_claim_airdrop_checks_proof_and_pays_each_leaf_once() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_key = spl_token::id();
+        let system_program_key = solana_program::system_program::id();
+        let (creator_key, alice_key, bob_key, mint_key, creator_token_key, vault_key, alice_token_key) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let (distribution_key, _) = distribution_address(&program_id, &creator_key, 7);
+        let (bitmap_key, _) = bitmap_address(&program_id, &distribution_key);
+
+        let (alice_leaf, bob_leaf) = (leaf_hash(0, &alice_key, 300), leaf_hash(1, &bob_key, 700));
+        let root = node_hash(&alice_leaf, &bob_leaf);
+        assert!(verify_proof(&root, bob_leaf, &[alice_leaf]));
+
+        let mut lamports = [0u64, 0, 10_000_000, 0, 0, 0, 0, 0, 0];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8] = &mut lamports;
+        let (mut distribution_data, mut bitmap_data) = (vec![0u8; Distribution::LEN], vec![0u8; 1]);
+        let mut creator_token_data = token_account_data(&mint_key, &creator_key, 1_000);
+        let mut vault_data = token_account_data(&mint_key, &distribution_key, 0);
+        let mut alice_token_data = token_account_data(&mint_key, &alice_key, 0);
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() }, &mut mint_data)
+            .unwrap();
+        let (mut d0, mut d1, mut d2) = (vec![], vec![], vec![]);
+
+        let distribution_acc = account_info(&distribution_key, false, true, l0, &mut distribution_data, &program_id);
+        let bitmap_acc = account_info(&bitmap_key, false, true, l1, &mut bitmap_data, &program_id);
+        let creator_acc = account_info(&creator_key, true, true, l2, &mut d0, &system_program_key);
+        let creator_token_acc = account_info(&creator_token_key, false, true, l3, &mut creator_token_data, &token_program_key);
+        let vault_acc = account_info(&vault_key, false, true, l4, &mut vault_data, &token_program_key);
+        let mint_acc = account_info(&mint_key, false, false, l5, &mut mint_data, &token_program_key);
+        let token_program_acc = account_info(&token_program_key, false, false, l6, &mut d1, &system_program_key);
+        let system_program_acc = account_info(&system_program_key, false, false, l7, &mut d2, &system_program_key);
+        let alice_token_acc = account_info(&alice_token_key, false, true, l8, &mut alice_token_data, &token_program_key);
+        bank.set_balance(&creator_token_key, 1_000);
+
+        let create_accounts = [
+            distribution_acc.clone(),
+            bitmap_acc.clone(),
+            creator_acc.clone(),
+            creator_token_acc,
+            vault_acc.clone(),
+            mint_acc.clone(),
+            token_program_acc.clone(),
+            system_program_acc,
+        ];
+        create_distribution(&program_id, &create_accounts, 7, root, 1_000, 2).unwrap();
+        assert_eq!(bank.balance(&vault_key), 1_000);
+
+        let (mut alice_lamports, mut alice_data) = (0u64, vec![]);
+        let alice_acc = account_info(&alice_key, true, false, &mut alice_lamports, &mut alice_data, &system_program_key);
+        let claim_accounts =
+            [distribution_acc, bitmap_acc, alice_acc, alice_token_acc, vault_acc, mint_acc, token_program_acc];
+        assert_eq!(
+            claim_airdrop(&program_id, &claim_accounts, 0, 700, vec![bob_leaf]),
+            Err(GgtError::InvalidMerkleProof.into())
+        );
+        claim_airdrop(&program_id, &claim_accounts, 0, 300, vec![bob_leaf]).unwrap();
+        assert_eq!((bank.balance(&alice_token_key), bank.balance(&vault_key)), (300, 700));
+        assert_eq!(
+            claim_airdrop(&program_id, &claim_accounts, 0, 300, vec![bob_leaf]),
+            Err(GgtError::AirdropAlreadyClaimed.into())
+        );
+    }
+
+    #[test]
+    fn test_create_distribution_caps_recipients_at_one_cpi_allocation() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_key = spl_token::id();
+        let system_program_key = solana_program::system_program::id();
+        let (creator_key, mint_key, creator_token_key, vault_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (distribution_key, _) = distribution_address(&program_id, &creator_key, 1);
+        let (bitmap_key, _) = bitmap_address(&program_id, &distribution_key);
+        assert_eq!(MAX_AIRDROP_RECIPIENTS, 81_920);
+
+        let mut lamports = [0u64, 0, 1_000_000_000, 0, 0, 0, 0, 0];
+        let [l0, l1, l2, l3, l4, l5, l6, l7] = &mut lamports;
+        let mut distribution_data = vec![0u8; Distribution::LEN];
+        let mut bitmap_data = vec![0u8; MAX_PERMITTED_DATA_INCREASE];
+        let mut creator_token_data = token_account_data(&mint_key, &creator_key, 1_000);
+        let mut vault_data = token_account_data(&mint_key, &distribution_key, 0);
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() }, &mut mint_data)
+            .unwrap();
+        let (mut d0, mut d1, mut d2) = (vec![], vec![], vec![]);
+        let create_accounts = [
+            account_info(&distribution_key, false, true, l0, &mut distribution_data, &program_id),
+            account_info(&bitmap_key, false, true, l1, &mut bitmap_data, &program_id),
+            account_info(&creator_key, true, true, l2, &mut d0, &system_program_key),
+            account_info(&creator_token_key, false, true, l3, &mut creator_token_data, &token_program_key),
+            account_info(&vault_key, false, true, l4, &mut vault_data, &token_program_key),
+            account_info(&mint_key, false, false, l5, &mut mint_data, &token_program_key),
+            account_info(&token_program_key, false, false, l6, &mut d1, &system_program_key),
+            account_info(&system_program_key, false, false, l7, &mut d2, &system_program_key),
+        ];
+        bank.set_balance(&creator_token_key, 1_000);
+
+        assert_eq!(
+            create_distribution(&program_id, &create_accounts, 1, [7u8; 32], 1_000, MAX_AIRDROP_RECIPIENTS + 1),
+            Err(ProgramError::InvalidArgument)
+        );
+        create_distribution(&program_id, &create_accounts, 1, [7u8; 32], 1_000, MAX_AIRDROP_RECIPIENTS).unwrap();
+        assert_eq!(create_accounts[1].lamports(), Rent::default().minimum_balance(MAX_PERMITTED_DATA_INCREASE));
+    }
+}
@@ -0,0 +1,5 @@
+//! Prints the program IDL to stdout.
+
+fn main() {
+    println!("{}", serde_json::to_string_pretty(&gadder_gold_token::idl::idl()).expect("the IDL is valid JSON"));
+}
@@ -0,0 +1,477 @@
+//! Records of outbound bridge transfers. Every lock creates a
+//! `BridgeTransfer` PDA that stays pending until guardians attest the transfer
+//! was completed on the target chain. If that never happens, the sender can
+//! cancel it once the bridge's transfer timeout has passed and get back both
+//! the bridged amount and the relayer fee. Relayers must skip transfers whose
+//! `BridgeTransferCancelled` event they have seen.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use crate::cross_chain_bridge_contract::{BridgeMode, CrossChainBridge, BRIDGE_VAULT_SEED, RELAYER_FEE_SEED};
+use crate::{address_book, error::GgtError, events, token_program};
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer, assert_token_account_mint};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const BRIDGE_TRANSFER_SEED: &[u8] = b"bridge_transfer";
+/// Prefix of the payload guardians sign once a transfer landed on its target chain.
+pub const BRIDGE_COMPLETION_DOMAIN: &[u8] = b"ggt-bridge-complete";
+/// Transfer timeout of a newly initialized bridge config.
+pub const DEFAULT_TRANSFER_TIMEOUT_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+/// Record of the lock with `sequence`. Clients read the next sequence from
+/// `BridgeConfig::outbound_sequence` before locking.
+pub fn bridge_transfer_address(program_id: &Pubkey, sender: &Pubkey, sequence: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BRIDGE_TRANSFER_SEED, sender.as_ref(), &sequence.to_le_bytes()], program_id)
+}
+
+/// What guardians sign to mark `transfer_id` as completed on its target chain.
+pub fn completion_message(transfer_id: &[u8; 32]) -> Vec<u8> {
+    [BRIDGE_COMPLETION_DOMAIN, &transfer_id[..]].concat()
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum BridgeTransferStatus {
+    Pending,
+    Completed,
+    Cancelled,
+}
+
+impl BridgeTransferStatus {
+    fn from_u8(value: u8) -> Result<Self, ProgramError> {
+        match value {
+            0 => Ok(BridgeTransferStatus::Pending),
+            1 => Ok(BridgeTransferStatus::Completed),
+            2 => Ok(BridgeTransferStatus::Cancelled),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct BridgeTransfer {
+    pub sender: Pubkey,
+    /// Token account the lock was paid from; refunds go back to it.
+    pub sender_token: Pubkey,
+    /// Mint of the locked or burned tokens.
+    pub mint: Pubkey,
+    pub sequence: u64,
+    /// Bridged amount, after `fee`.
+    pub amount: u64,
+    pub fee: u64,
+    pub target_chain_id: u16,
+    /// Empty if the lock did not name a destination.
+    pub destination: Vec<u8>,
+    pub status: BridgeTransferStatus,
+    pub created_at: i64,
+    pub is_initialized: bool,
+}
+
+impl BridgeTransfer {
+    pub fn transfer_id(&self) -> [u8; 32] {
+        CrossChainBridge::transfer_id(&self.sender, &self.mint, self.sequence, self.target_chain_id, self.amount)
+    }
+}
+
+impl Sealed for BridgeTransfer {}
+
+impl IsInitialized for BridgeTransfer {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for BridgeTransfer {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [29, 199, 232, 66, 207, 59, 19, 226];
+}
+
+impl Pack for BridgeTransfer {
+    // discriminator (8) + version (1) + Pubkey (32) * 3 + u64 (8) * 3 + u16 (2) + len (1) + destination (64) + u8 (1) + i64 (8) + bool (1)
+    const LEN: usize = 206;
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..32].copy_from_slice(self.sender.as_ref());
+        dst[32..64].copy_from_slice(self.sender_token.as_ref());
+        dst[64..96].copy_from_slice(self.mint.as_ref());
+        dst[96..104].copy_from_slice(&self.sequence.to_le_bytes());
+        dst[104..112].copy_from_slice(&self.amount.to_le_bytes());
+        dst[112..120].copy_from_slice(&self.fee.to_le_bytes());
+        dst[120..122].copy_from_slice(&self.target_chain_id.to_le_bytes());
+        dst[122] = self.destination.len() as u8;
+        dst[123..123 + address_book::MAX_DESTINATION_LEN].fill(0);
+        dst[123..123 + self.destination.len()].copy_from_slice(&self.destination);
+        dst[187] = self.status as u8;
+        dst[188..196].copy_from_slice(&self.created_at.to_le_bytes());
+        dst[196] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let destination_len = src[122] as usize;
+        if destination_len > address_book::MAX_DESTINATION_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(BridgeTransfer {
+            sender: Pubkey::new_from_array(src[0..32].try_into().unwrap()),
+            sender_token: Pubkey::new_from_array(src[32..64].try_into().unwrap()),
+            mint: Pubkey::new_from_array(src[64..96].try_into().unwrap()),
+            sequence: u64::from_le_bytes(src[96..104].try_into().unwrap()),
+            amount: u64::from_le_bytes(src[104..112].try_into().unwrap()),
+            fee: u64::from_le_bytes(src[112..120].try_into().unwrap()),
+            target_chain_id: u16::from_le_bytes(src[120..122].try_into().unwrap()),
+            destination: src[123..123 + destination_len].to_vec(),
+            status: BridgeTransferStatus::from_u8(src[187])?,
+            created_at: i64::from_le_bytes(src[188..196].try_into().unwrap()),
+            is_initialized: src[196] != 0,
+        })
+    }
+}
+
+/// Creates the record of a lock in `transfer_acc`, funded by the sender.
+pub(crate) fn record_transfer<'a>(
+    program_id: &Pubkey,
+    transfer_acc: &AccountInfo<'a>,
+    sender_acc: &AccountInfo<'a>,
+    system_program_acc: &AccountInfo<'a>,
+    transfer: BridgeTransfer,
+) -> ProgramResult {
+    let bump = assert_pda(transfer_acc, bridge_transfer_address(program_id, &transfer.sender, transfer.sequence))?;
+    invoke_signed(
+        &system_instruction::create_account(
+            sender_acc.key,
+            transfer_acc.key,
+            Rent::get()?.minimum_balance(BridgeTransfer::LEN),
+            BridgeTransfer::LEN as u64,
+            program_id,
+        ),
+        &[sender_acc.clone(), transfer_acc.clone(), system_program_acc.clone()],
+        &[&[BRIDGE_TRANSFER_SEED, transfer.sender.as_ref(), &transfer.sequence.to_le_bytes(), &[bump]]],
+    )?;
+    pack_state(&transfer, transfer_acc)
+}
+
+fn load_pending_transfer(program_id: &Pubkey, transfer_acc: &AccountInfo) -> Result<BridgeTransfer, ProgramError> {
+    assert_owned_by(transfer_acc, program_id)?;
+    let transfer = BridgeTransfer::unpack(&transfer_acc.try_borrow_data()?)?;
+    assert_pda(transfer_acc, bridge_transfer_address(program_id, &transfer.sender, transfer.sequence))?;
+    if transfer.status != BridgeTransferStatus::Pending {
+        msg!("Bridge transfer {} of {} is {:?}", transfer.sequence, transfer.sender, transfer.status);
+        return Err(GgtError::BridgeTransferNotPending.into());
+    }
+    Ok(transfer)
+}
+
+pub struct CompleteBridgeTransferAccounts<'a, 'info> {
+    pub transfer_acc: &'a AccountInfo<'info>,
+    pub bridge_config_acc: &'a AccountInfo<'info>,
+    pub instructions_sysvar_acc: &'a AccountInfo<'info>,
+    pub registry_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CompleteBridgeTransferAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let transfer_acc = next_account_info(account_info_iter)?;
+        let bridge_config_acc = next_account_info(account_info_iter)?;
+        let instructions_sysvar_acc = next_account_info(account_info_iter)?;
+        let registry_acc = next_account_info(account_info_iter)?;
+
+        assert_owned_by(bridge_config_acc, program_id)?;
+        CrossChainBridge::check_registry(program_id, registry_acc)?;
+        Ok(Self { transfer_acc, bridge_config_acc, instructions_sysvar_acc, registry_acc })
+    }
+}
+
+/// Marks a pending transfer as completed once guardians of its target chain
+/// have signed `completion_message` earlier in the transaction. Anyone may
+/// submit the attestation.
+pub fn complete_bridge_transfer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let CompleteBridgeTransferAccounts { transfer_acc, bridge_config_acc, instructions_sysvar_acc, registry_acc } =
+        CompleteBridgeTransferAccounts::try_from(program_id, accounts)?;
+    let mut transfer = load_pending_transfer(program_id, transfer_acc)?;
+    let chain = CrossChainBridge::registered_chain(registry_acc, transfer.target_chain_id)?;
+    let config = CrossChainBridge::load_config(bridge_config_acc)?;
+    CrossChainBridge::verify_attestation(&config, &chain, instructions_sysvar_acc, &completion_message(&transfer.transfer_id()))?;
+
+    transfer.status = BridgeTransferStatus::Completed;
+    let sequence = transfer.sequence;
+    pack_state(&transfer, transfer_acc)?;
+    msg!("Bridge transfer {} completed on {}", sequence, chain.name);
+    Ok(())
+}
+
+/// The sender signer follows `transfer_acc`; it is checked against the record.
+pub struct CancelBridgeTransferAccounts<'a, 'info> {
+    pub transfer_acc: &'a AccountInfo<'info>,
+    pub sender_acc: &'a AccountInfo<'info>,
+    pub sender_token_acc: &'a AccountInfo<'info>,
+    pub bridge_vault_acc: &'a AccountInfo<'info>,
+    pub vault_authority_acc: &'a AccountInfo<'info>,
+    pub relayer_fee_vault_acc: &'a AccountInfo<'info>,
+    pub fee_authority_acc: &'a AccountInfo<'info>,
+    pub bridge_config_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub vault_authority_bump: u8,
+    pub fee_authority_bump: u8,
+}
+
+impl<'a, 'info> CancelBridgeTransferAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let transfer_acc = next_account_info(account_info_iter)?;
+        let sender_acc = next_account_info(account_info_iter)?;
+        let sender_token_acc = next_account_info(account_info_iter)?;
+        let bridge_vault_acc = next_account_info(account_info_iter)?;
+        let vault_authority_acc = next_account_info(account_info_iter)?;
+        let relayer_fee_vault_acc = next_account_info(account_info_iter)?;
+        let fee_authority_acc = next_account_info(account_info_iter)?;
+        let bridge_config_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(sender_acc)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        assert_token_account_mint(sender_token_acc, mint_acc.key)?;
+        assert_owned_by(bridge_config_acc, program_id)?;
+        CrossChainBridge::check_relayer_fee_vault(program_id, relayer_fee_vault_acc, mint_acc)?;
+        let vault_authority_bump = assert_pda(vault_authority_acc, CrossChainBridge::vault_authority(program_id))?;
+        let fee_authority_bump = assert_pda(fee_authority_acc, CrossChainBridge::relayer_fee_authority(program_id))?;
+        Ok(Self {
+            transfer_acc,
+            sender_acc,
+            sender_token_acc,
+            bridge_vault_acc,
+            vault_authority_acc,
+            relayer_fee_vault_acc,
+            fee_authority_acc,
+            bridge_config_acc,
+            mint_acc,
+            token_program_acc,
+            vault_authority_bump,
+            fee_authority_bump,
+        })
+    }
+}
+
+/// Refunds a transfer that was never attested as completed, once
+/// `BridgeConfig::transfer_timeout_seconds` have passed since the lock. The
+/// bridged amount comes back from the bridge vault, or is minted again in
+/// `BurnMint` mode, and the fee comes back from the relayer fee vault.
+pub fn cancel_bridge_transfer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let CancelBridgeTransferAccounts {
+        transfer_acc,
+        sender_acc,
+        sender_token_acc,
+        bridge_vault_acc,
+        vault_authority_acc,
+        relayer_fee_vault_acc,
+        fee_authority_acc,
+        bridge_config_acc,
+        mint_acc,
+        token_program_acc,
+        vault_authority_bump,
+        fee_authority_bump,
+    } = CancelBridgeTransferAccounts::try_from(program_id, accounts)?;
+    let mut transfer = load_pending_transfer(program_id, transfer_acc)?;
+    if transfer.sender != *sender_acc.key {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if transfer.sender_token != *sender_token_acc.key || transfer.mint != *mint_acc.key {
+        msg!("Refunds go to the token account the lock was paid from, in the mint it locked");
+        return Err(ProgramError::InvalidArgument);
+    }
+    let config = CrossChainBridge::load_config(bridge_config_acc)?;
+    CrossChainBridge::check_not_paused(&config)?;
+    let expires_at = transfer.created_at.checked_add(config.transfer_timeout_seconds).ok_or(GgtError::MathOverflow)?;
+    if Clock::get()?.unix_timestamp < expires_at {
+        msg!("Bridge transfer {} can be cancelled from {}", transfer.sequence, expires_at);
+        return Err(GgtError::BridgeTransferNotExpired.into());
+    }
+
+    let (transfer_id, sender, amount, fee, sequence) = (transfer.transfer_id(), transfer.sender, transfer.amount, transfer.fee, transfer.sequence);
+    transfer.status = BridgeTransferStatus::Cancelled;
+    pack_state(&transfer, transfer_acc)?;
+
+    let vault_seeds: &[&[u8]] = &[BRIDGE_VAULT_SEED, &[vault_authority_bump]];
+    match config.mode {
+        BridgeMode::LockRelease => {
+            CrossChainBridge::check_vault(program_id, bridge_vault_acc, mint_acc)?;
+            token_program::transfer(
+                token_program_acc,
+                bridge_vault_acc,
+                mint_acc,
+                sender_token_acc,
+                vault_authority_acc,
+                amount,
+                &[vault_seeds],
+            )?
+        }
+        BridgeMode::BurnMint => {
+            token_program::mint_to(token_program_acc, mint_acc, sender_token_acc, vault_authority_acc, amount, &[vault_seeds])?
+        }
+    }
+    if fee > 0 {
+        token_program::transfer(
+            token_program_acc,
+            relayer_fee_vault_acc,
+            mint_acc,
+            sender_token_acc,
+            fee_authority_acc,
+            fee,
+            &[&[RELAYER_FEE_SEED, &[fee_authority_bump]]],
+        )?;
+    }
+    events::emit(&events::BridgeTransferCancelled { transfer_id, sender, sequence, amount, fee });
+    msg!("Cancelled bridge transfer {} and refunded {} plus a fee of {}", sequence, amount, fee);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::new_ed25519_instruction;
+    use crate::cross_chain_bridge_contract::{AddressFormat, BridgeConfig, ChainInfo, ChainRegistry, SignatureScheme};
+    use crate::test_utils::{account_info, fund_rent_exempt, instructions_sysvar_data, token_account_data, SimulatedBank};
+    use solana_program::instruction::Instruction;
+    use spl_token::state::Mint;
+
+    #[test]
+    fn test_unfinished_transfer_is_refunded_after_the_timeout_and_completed_ones_are_not() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let guardian = Pubkey::new_unique();
+        let (sender_key, stranger_key, sender_token_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (vault_key, fee_vault_key, config_key, mint_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (vault_authority, _) = CrossChainBridge::vault_authority(&program_id);
+        let (fee_authority, _) = CrossChainBridge::relayer_fee_authority(&program_id);
+        let (registry_key, _) = CrossChainBridge::chain_registry_address(&program_id);
+        let (lost_key, _) = bridge_transfer_address(&program_id, &sender_key, 0);
+        let (delivered_key, _) = bridge_transfer_address(&program_id, &sender_key, 1);
+        let sysvar_key = solana_instructions_sysvar::ID;
+        let record = |sequence| {
+            let transfer = BridgeTransfer {
+                sender: sender_key,
+                sender_token: sender_token_key,
+                mint: mint_key,
+                sequence,
+                amount: 485,
+                fee: 15,
+                target_chain_id: 2,
+                destination: vec![0x11; 20],
+                status: BridgeTransferStatus::Pending,
+                created_at: 1_000,
+                is_initialized: true,
+            };
+            let mut data = vec![0u8; BridgeTransfer::LEN];
+            BridgeTransfer::pack(transfer, &mut data).unwrap();
+            data
+        };
+        let config = BridgeConfig {
+            guardians: vec![guardian],
+            guardian_threshold: 1,
+            evm_guardians: Vec::new(),
+            evm_guardian_threshold: 0,
+            outbound_sequence: 2,
+            mode: BridgeMode::LockRelease,
+            daily_outflow_cap: 0,
+            chain_outflow_caps: Vec::new(),
+            transfer_timeout_seconds: DEFAULT_TRANSFER_TIMEOUT_SECONDS,
+            paused: false,
+            is_initialized: true,
+        };
+        let mut config_data = vec![0u8; BridgeConfig::LEN];
+        BridgeConfig::pack(config, &mut config_data).unwrap();
+        let ethereum = ChainInfo {
+            chain_id: 2,
+            name: "Ethereum".to_string(),
+            address_format: AddressFormat::Evm,
+            decimals: 8,
+            signature_scheme: SignatureScheme::Ed25519,
+            fee_flat: 15,
+            fee_bps: 0,
+            wormhole_chain_id: 0,
+            wormhole_emitter: [0; 32],
+        };
+        let mut registry_data = vec![0u8; ChainRegistry::LEN];
+        ChainRegistry::pack(ChainRegistry { chains: vec![ethereum], is_initialized: true }, &mut registry_data).unwrap();
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { decimals: 9, is_initialized: true, ..Mint::default() }, &mut mint_data).unwrap();
+        let message = completion_message(&CrossChainBridge::transfer_id(&sender_key, &mint_key, 1, 2, 485));
+        let transaction = vec![new_ed25519_instruction(&guardian, &[0u8; 64], &message), Instruction { program_id, accounts: vec![], data: vec![] }];
+        let mut sysvar_data = instructions_sysvar_data(&transaction, 1);
+
+        let mut lamports = [0u64; 14];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10, l11, l12, l13] = &mut lamports;
+        let (mut lost_data, mut delivered_data) = (record(0), record(1));
+        let (mut sender_data, mut stranger_data, mut vault_authority_data, mut fee_authority_data, mut token_program_data) =
+            (vec![], vec![], vec![], vec![], vec![]);
+        let mut sender_token_data = token_account_data(&mint_key, &sender_key, 0);
+        let mut vault_data = token_account_data(&mint_key, &vault_authority, 970);
+        let mut fee_vault_data = token_account_data(&mint_key, &fee_authority, 30);
+        let lost_acc = account_info(&lost_key, false, true, l0, &mut lost_data, &program_id);
+        let delivered_acc = account_info(&delivered_key, false, true, l1, &mut delivered_data, &program_id);
+        fund_rent_exempt(&[&lost_acc, &delivered_acc]);
+        let sender_acc = account_info(&sender_key, true, false, l2, &mut sender_data, &program_id);
+        let stranger_acc = account_info(&stranger_key, true, false, l3, &mut stranger_data, &program_id);
+        let refund_accounts = [
+            account_info(&sender_token_key, false, true, l4, &mut sender_token_data, &token_program_id),
+            account_info(&vault_key, false, true, l5, &mut vault_data, &token_program_id),
+            account_info(&vault_authority, false, false, l6, &mut vault_authority_data, &program_id),
+            account_info(&fee_vault_key, false, true, l7, &mut fee_vault_data, &token_program_id),
+            account_info(&fee_authority, false, false, l8, &mut fee_authority_data, &program_id),
+            account_info(&config_key, false, false, l9, &mut config_data, &program_id),
+            account_info(&mint_key, false, true, l10, &mut mint_data, &token_program_id),
+            account_info(&token_program_id, false, false, l11, &mut token_program_data, &program_id),
+        ];
+        let sysvar_acc = account_info(&sysvar_key, false, false, l12, &mut sysvar_data, &program_id);
+        let registry_acc = account_info(&registry_key, false, false, l13, &mut registry_data, &program_id);
+        let config_acc = refund_accounts[5].clone();
+        let cancel_lost = [&[lost_acc.clone(), sender_acc.clone()][..], &refund_accounts].concat();
+        let cancel_delivered = [&[delivered_acc.clone(), sender_acc][..], &refund_accounts].concat();
+        bank.set_balance(&vault_key, 970);
+        bank.set_balance(&fee_vault_key, 30);
+
+        // Guardians attest the second transfer arrived; it can no longer be cancelled.
+        complete_bridge_transfer(&program_id, &[delivered_acc.clone(), config_acc.clone(), sysvar_acc.clone(), registry_acc.clone()]).unwrap();
+        assert_eq!(BridgeTransfer::unpack(&delivered_acc.try_borrow_data().unwrap()).unwrap().status, BridgeTransferStatus::Completed);
+        assert_eq!(
+            complete_bridge_transfer(&program_id, &[lost_acc.clone(), config_acc, sysvar_acc, registry_acc]),
+            Err(GgtError::InsufficientGuardianSignatures.into())
+        );
+
+        let expires_at = 1_000 + DEFAULT_TRANSFER_TIMEOUT_SECONDS;
+        bank.set_unix_timestamp(expires_at - 1);
+        assert_eq!(cancel_bridge_transfer(&program_id, &cancel_lost), Err(GgtError::BridgeTransferNotExpired.into()));
+        bank.set_unix_timestamp(expires_at);
+        let by_stranger = [&[lost_acc.clone(), stranger_acc][..], &refund_accounts].concat();
+        assert_eq!(cancel_bridge_transfer(&program_id, &by_stranger), Err(ProgramError::IllegalOwner));
+        assert_eq!(cancel_bridge_transfer(&program_id, &cancel_delivered), Err(GgtError::BridgeTransferNotPending.into()));
+
+        cancel_bridge_transfer(&program_id, &cancel_lost).unwrap();
+        assert_eq!((bank.balance(&sender_token_key), bank.balance(&vault_key), bank.balance(&fee_vault_key)), (500, 485, 15));
+        assert_eq!(BridgeTransfer::unpack(&lost_acc.try_borrow_data().unwrap()).unwrap().status, BridgeTransferStatus::Cancelled);
+        let cancelled = events::BridgeTransferCancelled {
+            transfer_id: CrossChainBridge::transfer_id(&sender_key, &mint_key, 0, 2, 485),
+            sender: sender_key,
+            sequence: 0,
+            amount: 485,
+            fee: 15,
+        };
+        assert_eq!(bank.events::<events::BridgeTransferCancelled>(), vec![cancelled]);
+        assert_eq!(cancel_bridge_transfer(&program_id, &cancel_lost), Err(GgtError::BridgeTransferNotPending.into()));
+    }
+}
@@ -0,0 +1,325 @@
+//! Off-chain helpers for clients building instruction data.
+
+use solana_program::{ed25519_program, instruction::Instruction, program_pack::Pack, pubkey::Pubkey, rent::Rent, secp256k1_program};
+use crate::{
+    address_book::AddressBook,
+    ai_contract::{ConsultantProfile, MatchRequest, OracleRegistry, PayoutSplit},
+    bridge_transfer::BridgeTransfer,
+    config::{AuditLog, ProgramConfig},
+    dispute::{ArbitrationConfig, Dispute},
+    engagement::Engagement,
+    cross_chain_bridge_contract::{BridgeConfig, BridgeReceipt, ChainRegistry, CrossChainBridge, OutflowWindow},
+    governance_contract::{BallotPage, GovernanceConfig, Proposal, Vote, MAX_URI_LEN},
+    invoice::Invoice,
+    market_maker::{FastMint, MarketMaker},
+    migration::MigrationState,
+    multisig::{AdminAction, Multisig},
+    oracle_operator::{OracleBondConfig, OracleOperator},
+    denylist::Denylist,
+    payment_receipt::PaymentReceipt,
+    reputation::Reputation,
+    staking_contract::{CoStake, CoStakingPool, Stake, StakeSummary, StakingConfig, StakingPool},
+    streaming_contract::PaymentStream,
+    treasury_contract::TreasuryAssets,
+    vesting::VestingSchedule,
+    vote_escrow::VoteEscrow,
+    GET_VERSION_TAG, INSTRUCTION_VERSION, MIN_SUPPORTED_INSTRUCTION_VERSION,
+};
+
+pub use crate::ai_contract::evm_link_digest;
+pub use crate::market_maker::reconcile_message;
+
+/// Transfer ID a bridge lock returns, for matching it against `BridgeLock` events.
+pub fn bridge_transfer_id(sender: &Pubkey, mint: &Pubkey, sequence: u64, target_chain_id: u16, amount: u64) -> [u8; 32] {
+    CrossChainBridge::transfer_id(sender, mint, sequence, target_chain_id, amount)
+}
+
+/// Versions this client build knows how to encode.
+pub const CLIENT_VERSIONS: (u8, u8) = (MIN_SUPPORTED_INSTRUCTION_VERSION, INSTRUCTION_VERSION);
+
+/// Instruction data for the `get_version` query. The program answers it for any
+/// version byte and publishes `[min_supported, current]` as return data.
+pub fn get_version_data() -> Vec<u8> {
+    vec![GET_VERSION_TAG, INSTRUCTION_VERSION]
+}
+
+/// Parses the return data of a simulated `get_version` call.
+pub fn parse_version_range(return_data: &[u8]) -> Option<(u8, u8)> {
+    match return_data {
+        [min, max, ..] if min <= max => Some((*min, *max)),
+        _ => None,
+    }
+}
+
+/// Picks the newest layout both sides understand, or `None` if the ranges don't overlap.
+pub fn negotiate_version(program: (u8, u8), client: (u8, u8)) -> Option<u8> {
+    let low = program.0.max(client.0);
+    let high = program.1.min(client.1);
+    (low <= high).then_some(high)
+}
+
+/// Prefixes `payload` with the tag and version header expected by the dispatcher.
+pub fn encode_instruction_data(tag: u8, version: u8, payload: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(payload.len() + 2);
+    data.push(tag);
+    data.push(version);
+    data.extend_from_slice(payload);
+    data
+}
+
+/// Builds an ed25519 precompile instruction carrying one guardian signature over
+/// `message`. It must precede `release_tokens_on_target_chain` in the same transaction.
+pub fn new_ed25519_instruction(guardian: &Pubkey, signature: &[u8; 64], message: &[u8]) -> Instruction {
+    const HEADER_LEN: u16 = 16; // count (1) + padding (1) + offsets (14)
+    let public_key_offset = HEADER_LEN;
+    let signature_offset = public_key_offset + 32;
+    let message_data_offset = signature_offset + 64;
+
+    let mut data = vec![1u8, 0];
+    for field in [
+        signature_offset,
+        u16::MAX,
+        public_key_offset,
+        u16::MAX,
+        message_data_offset,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(guardian.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+
+    Instruction { program_id: ed25519_program::id(), accounts: vec![], data }
+}
+
+/// Builds a secp256k1 precompile instruction carrying one EVM guardian's
+/// recoverable signature (64 bytes plus the recovery ID) over `message`. The
+/// instruction must sit at `instruction_index` in the transaction, before
+/// `release_tokens_on_target_chain`.
+pub fn new_secp256k1_instruction(instruction_index: u8, eth_address: &[u8; 20], signature: &[u8; 65], message: &[u8]) -> Instruction {
+    const HEADER_LEN: u16 = 12; // count (1) + offsets (11)
+    let eth_address_offset = HEADER_LEN;
+    let signature_offset = eth_address_offset + 20;
+    let message_data_offset = signature_offset + 65;
+
+    let mut data = vec![1u8];
+    data.extend_from_slice(&signature_offset.to_le_bytes());
+    data.push(instruction_index);
+    data.extend_from_slice(&eth_address_offset.to_le_bytes());
+    data.push(instruction_index);
+    data.extend_from_slice(&message_data_offset.to_le_bytes());
+    data.extend_from_slice(&(message.len() as u16).to_le_bytes());
+    data.push(instruction_index);
+    data.extend_from_slice(eth_address);
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+
+    Instruction { program_id: secp256k1_program::id(), accounts: vec![], data }
+}
+
+/// Accounts a client has to allocate before calling into the program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountKind {
+    StakingPool,
+    Stake,
+    StakingConfig,
+    CoStakingPool,
+    CoStake,
+    StakeSummary,
+    Proposal { uri_len: usize },
+    Vote,
+    VoteEscrow,
+    BallotPage,
+    GovernanceConfig,
+    BridgeConfig,
+    BridgeReceipt,
+    BridgeTransfer,
+    ChainRegistry,
+    OutflowWindow,
+    MarketMaker,
+    FastMint,
+    AddressBook,
+    Invoice,
+    MatchRequest,
+    OracleRegistry,
+    ConsultantProfile,
+    PayoutSplit,
+    Engagement,
+    ArbitrationConfig,
+    Dispute,
+    Reputation,
+    PaymentReceipt,
+    Denylist,
+    TreasuryAssets,
+    PaymentStream,
+    ProgramConfig,
+    AuditLog,
+    VestingSchedule,
+    MigrationState,
+    Multisig,
+    AdminAction,
+    OracleBondConfig,
+    OracleOperator,
+    /// SPL token account used as a vault (bridge, rewards, escrow).
+    TokenAccount,
+}
+
+/// Exact allocation in bytes for `kind`, or `None` if a variable-length field
+/// would not fit the on-chain layout.
+pub fn account_size(kind: AccountKind) -> Option<usize> {
+    match kind {
+        AccountKind::StakingPool => Some(StakingPool::LEN),
+        AccountKind::Stake => Some(Stake::LEN),
+        AccountKind::StakingConfig => Some(StakingConfig::LEN),
+        AccountKind::CoStakingPool => Some(CoStakingPool::LEN),
+        AccountKind::CoStake => Some(CoStake::LEN),
+        AccountKind::StakeSummary => Some(StakeSummary::LEN),
+        AccountKind::Proposal { uri_len } => (uri_len <= MAX_URI_LEN).then_some(Proposal::LEN),
+        AccountKind::Vote => Some(Vote::LEN),
+        AccountKind::VoteEscrow => Some(VoteEscrow::LEN),
+        AccountKind::BallotPage => Some(BallotPage::LEN),
+        AccountKind::GovernanceConfig => Some(GovernanceConfig::LEN),
+        AccountKind::BridgeConfig => Some(BridgeConfig::LEN),
+        AccountKind::BridgeReceipt => Some(BridgeReceipt::LEN),
+        AccountKind::BridgeTransfer => Some(BridgeTransfer::LEN),
+        AccountKind::ChainRegistry => Some(ChainRegistry::LEN),
+        AccountKind::OutflowWindow => Some(OutflowWindow::LEN),
+        AccountKind::MarketMaker => Some(MarketMaker::LEN),
+        AccountKind::FastMint => Some(FastMint::LEN),
+        AccountKind::AddressBook => Some(AddressBook::LEN),
+        AccountKind::Invoice => Some(Invoice::LEN),
+        AccountKind::MatchRequest => Some(MatchRequest::LEN),
+        AccountKind::OracleRegistry => Some(OracleRegistry::LEN),
+        AccountKind::ConsultantProfile => Some(ConsultantProfile::LEN),
+        AccountKind::PayoutSplit => Some(PayoutSplit::LEN),
+        AccountKind::Engagement => Some(Engagement::LEN),
+        AccountKind::ArbitrationConfig => Some(ArbitrationConfig::LEN),
+        AccountKind::Dispute => Some(Dispute::LEN),
+        AccountKind::Reputation => Some(Reputation::LEN),
+        AccountKind::PaymentReceipt => Some(PaymentReceipt::LEN),
+        AccountKind::Denylist => Some(Denylist::LEN),
+        AccountKind::TreasuryAssets => Some(TreasuryAssets::LEN),
+        AccountKind::PaymentStream => Some(PaymentStream::LEN),
+        AccountKind::ProgramConfig => Some(ProgramConfig::LEN),
+        AccountKind::AuditLog => Some(AuditLog::LEN),
+        AccountKind::VestingSchedule => Some(VestingSchedule::LEN),
+        AccountKind::MigrationState => Some(MigrationState::LEN),
+        AccountKind::Multisig => Some(Multisig::LEN),
+        AccountKind::AdminAction => Some(AdminAction::LEN),
+        AccountKind::OracleBondConfig => Some(OracleBondConfig::LEN),
+        AccountKind::OracleOperator => Some(OracleOperator::LEN),
+        AccountKind::TokenAccount => Some(spl_token::state::Account::LEN),
+    }
+}
+
+/// Lamports the payer must fund for `kind` to be rent exempt under `rent`.
+/// Pass `Rent::default()` for the mainnet parameters or the fetched sysvar.
+pub fn rent_exempt_lamports(kind: AccountKind, rent: &Rent) -> Option<u64> {
+    account_size(kind).map(|size| rent.minimum_balance(size))
+}
+
+/// Request body posted to the off-chain matching service.
+#[cfg(feature = "client")]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct MatchServiceRequest {
+    pub client_requirements: String,
+}
+
+/// Asks the matching service at `endpoint` for a consultant. Oracles call this
+/// after observing a pending `request_match`, then submit the answer with
+/// `fulfill_match`.
+#[cfg(feature = "client")]
+pub async fn fetch_consultant_match(endpoint: &str, requirements: &str) -> Result<String, reqwest::Error> {
+    let request = MatchServiceRequest {
+        client_requirements: requirements.to_string(),
+    };
+    reqwest::Client::new()
+        .post(endpoint)
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_version() {
+        assert_eq!(negotiate_version((1, 3), (1, 2)), Some(2));
+        assert_eq!(negotiate_version((2, 3), (1, 4)), Some(3));
+        assert_eq!(negotiate_version((3, 4), (1, 2)), None);
+        assert_eq!(negotiate_version(CLIENT_VERSIONS, CLIENT_VERSIONS), Some(INSTRUCTION_VERSION));
+    }
+
+    #[test]
+    fn test_parse_version_range() {
+        assert_eq!(parse_version_range(&[1, 2]), Some((1, 2)));
+        assert_eq!(parse_version_range(&[2, 1]), None);
+        assert_eq!(parse_version_range(&[1]), None);
+    }
+
+    #[test]
+    fn test_encode_instruction_data() {
+        assert_eq!(encode_instruction_data(1, 1, &500u64.to_le_bytes()), vec![1, 1, 244, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(get_version_data(), vec![GET_VERSION_TAG, INSTRUCTION_VERSION]);
+    }
+
+    #[test]
+    fn test_account_sizes_and_rent() {
+        assert_eq!(account_size(AccountKind::Stake), Some(115));
+        assert_eq!(account_size(AccountKind::Proposal { uri_len: MAX_URI_LEN }), Some(Proposal::LEN));
+        assert_eq!(account_size(AccountKind::Proposal { uri_len: MAX_URI_LEN + 1 }), None);
+        assert_eq!(account_size(AccountKind::TokenAccount), Some(165));
+
+        let rent = Rent::default();
+        assert_eq!(rent_exempt_lamports(AccountKind::Vote, &rent), Some(rent.minimum_balance(Vote::LEN)));
+        assert!(rent_exempt_lamports(AccountKind::MatchRequest, &rent) > rent_exempt_lamports(AccountKind::Stake, &rent));
+    }
+
+    #[cfg(feature = "client")]
+    const TEST_ENDPOINT: &str = "/match";
+
+    #[cfg(feature = "client")]
+    #[tokio::test]
+    async fn test_fetch_consultant_match() {
+        use wiremock::matchers::{body_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(TEST_ENDPOINT))
+            .and(body_json(MatchServiceRequest {
+                client_requirements: "Test requirements".to_string(),
+            }))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Consultant matched"))
+            .mount(&mock_server)
+            .await;
+
+        let endpoint = format!("{}{}", mock_server.uri(), TEST_ENDPOINT);
+        let result = fetch_consultant_match(&endpoint, "Test requirements").await.unwrap();
+        assert_eq!(result, "Consultant matched");
+    }
+
+    #[cfg(feature = "client")]
+    #[tokio::test]
+    async fn test_fetch_consultant_match_surfaces_http_errors() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&mock_server)
+            .await;
+
+        let endpoint = format!("{}{}", mock_server.uri(), TEST_ENDPOINT);
+        let large_requirements = "x".repeat(1000);
+        assert!(fetch_consultant_match(&endpoint, &large_requirements).await.is_err());
+    }
+}
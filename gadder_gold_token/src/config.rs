@@ -0,0 +1,707 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use mpl_token_metadata::{
+    accounts::Metadata,
+    instructions::{UpdateMetadataAccountV2, UpdateMetadataAccountV2InstructionArgs},
+};
+use crate::{
+    error::GgtError, governance_contract::governance_authority_address, multisig::multisig_authority_address, TokenContract, ADMIN_PUBKEY,
+    BRIDGE_ADMIN_PUBKEY, GOVERNANCE_PUBKEY,
+};
+use crate::state::{create_state_account, pack_state};
+use crate::validation::{assert_owned_by, assert_pda, assert_signer};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const PROGRAM_CONFIG_SEED: &[u8] = b"program_config";
+pub const AUDIT_LOG_SEED: &[u8] = b"audit_log";
+pub const AUDIT_LOG_CAPACITY: usize = 16;
+/// Longest a drill may keep the program paused, roughly one minute of slots.
+pub const MAX_DRILL_SLOTS: u64 = 150;
+pub const MAX_TRANSFER_FEE_BPS: u16 = 500;
+
+/// Bits of `ProgramConfig::pause_flags`; each stops one group of instructions.
+pub const PAUSE_TRANSFERS: u8 = 1 << 0;
+pub const PAUSE_STAKING: u8 = 1 << 1;
+pub const PAUSE_GOVERNANCE_EXECUTION: u8 = 1 << 2;
+pub const PAUSE_BRIDGE: u8 = 1 << 3;
+pub const PAUSE_FLAGS_ALL: u8 = PAUSE_TRANSFERS | PAUSE_STAKING | PAUSE_GOVERNANCE_EXECUTION | PAUSE_BRIDGE;
+
+/// How long a nominee has to accept a role before the nomination lapses.
+pub const NOMINATION_TTL_SECONDS: i64 = 7 * 86_400;
+
+/// Privileged roles handed over with `nominate_authority` and `accept_authority`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthorityRole {
+    Admin,
+    Governance,
+    BridgeAdmin,
+    /// Update authority of the GGT metadata, which the token metadata program tracks.
+    MetadataUpdate,
+}
+
+impl AuthorityRole {
+    pub const COUNT: usize = 4;
+}
+
+/// A role offered to `nominee`, who must accept before `expires_at`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Nomination {
+    pub nominee: Pubkey,
+    pub expires_at: i64,
+}
+
+impl Nomination {
+    const LEN: usize = 40; // Pubkey (32) + i64 (8)
+}
+
+/// Program-wide settings. Every gated instruction takes this PDA as its last account.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct ProgramConfig {
+    pub paused: bool,
+    /// Slot at which a drill pause lifts on its own; zero for an indefinite pause.
+    pub pause_until_slot: u64,
+    /// Protocol fee taken from `transfer_tokens`, in basis points.
+    pub fee_bps: u16,
+    /// Signer allowed to freeze and thaw GGT accounts; the default key disables it.
+    pub compliance_authority: Pubkey,
+    /// `PAUSE_*` bits set by governance, on top of the admin's global pause.
+    pub pause_flags: u8,
+    /// Current holders of the admin, governance and bridge admin roles. The
+    /// default key stands for the built-in key the role started with.
+    pub admin: Pubkey,
+    pub governance: Pubkey,
+    pub bridge_admin: Pubkey,
+    /// Pending nominations, indexed by `AuthorityRole`; a default nominee means none.
+    pub nominations: [Nomination; AuthorityRole::COUNT],
+    /// GGT burned out of the treasury by `burn_from_treasury` so far.
+    pub total_burned: u64,
+    pub is_initialized: bool,
+}
+
+impl ProgramConfig {
+    /// Holder of `role`, or `None` for the metadata role, which lives in the metadata account.
+    pub fn authority(&self, role: AuthorityRole) -> Option<Pubkey> {
+        let (holder, initial) = match role {
+            AuthorityRole::Admin => (self.admin, ADMIN_PUBKEY),
+            AuthorityRole::Governance => (self.governance, GOVERNANCE_PUBKEY),
+            AuthorityRole::BridgeAdmin => (self.bridge_admin, BRIDGE_ADMIN_PUBKEY),
+            AuthorityRole::MetadataUpdate => return None,
+        };
+        Some(if holder == Pubkey::default() { initial } else { holder })
+    }
+
+    /// The protocol fee `fee_bps` takes from a transfer of `amount`, rounded down.
+    pub fn transfer_fee(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_bps as u128 / 10_000) as u64
+    }
+
+    /// Whether the pause gate blocks instructions at `slot`.
+    pub fn is_paused_at(&self, slot: u64) -> bool {
+        self.paused && (self.pause_until_slot == 0 || slot < self.pause_until_slot)
+    }
+}
+
+impl Sealed for ProgramConfig {}
+
+impl IsInitialized for ProgramConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for ProgramConfig {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [196, 210, 90, 231, 144, 149, 140, 63];
+}
+
+impl Pack for ProgramConfig {
+    const LEN: usize = 318; // discriminator (8) + version (1) + bool (1) + u64 (8) + u16 (2) + Pubkey (32) + u8 (1) + Pubkey (32) * 3 + Nomination (40) * 4 + u64 (8) + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        let mut cursor = 0;
+        dst[cursor] = self.paused as u8;
+        cursor += 1;
+        dst[cursor..cursor + 8].copy_from_slice(&self.pause_until_slot.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 2].copy_from_slice(&self.fee_bps.to_le_bytes());
+        cursor += 2;
+        dst[cursor..cursor + 32].copy_from_slice(self.compliance_authority.as_ref());
+        cursor += 32;
+        dst[cursor] = self.pause_flags;
+        cursor += 1;
+        for holder in [&self.admin, &self.governance, &self.bridge_admin] {
+            dst[cursor..cursor + 32].copy_from_slice(holder.as_ref());
+            cursor += 32;
+        }
+        for nomination in &self.nominations {
+            dst[cursor..cursor + 32].copy_from_slice(nomination.nominee.as_ref());
+            dst[cursor + 32..cursor + 40].copy_from_slice(&nomination.expires_at.to_le_bytes());
+            cursor += Nomination::LEN;
+        }
+        dst[cursor..cursor + 8].copy_from_slice(&self.total_burned.to_le_bytes());
+        cursor += 8;
+        dst[cursor] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let paused = src[0] != 0;
+        let pause_until_slot = u64::from_le_bytes(src[1..9].try_into().unwrap());
+        let fee_bps = u16::from_le_bytes(src[9..11].try_into().unwrap());
+        let compliance_authority = Pubkey::new_from_array(src[11..43].try_into().unwrap());
+        let pause_flags = src[43];
+        let holder = |at: usize| Pubkey::new_from_array(src[at..at + 32].try_into().unwrap());
+        let nominations = std::array::from_fn(|role| {
+            let at = 140 + role * Nomination::LEN;
+            Nomination { nominee: holder(at), expires_at: i64::from_le_bytes(src[at + 32..at + 40].try_into().unwrap()) }
+        });
+        let total_burned = u64::from_le_bytes(src[300..308].try_into().unwrap());
+        let is_initialized = src[308] != 0;
+        Ok(ProgramConfig {
+            paused,
+            pause_until_slot,
+            fee_bps,
+            compliance_authority,
+            pause_flags,
+            admin: holder(44),
+            governance: holder(76),
+            bridge_admin: holder(108),
+            nominations,
+            total_burned,
+            is_initialized,
+        })
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AuditAction {
+    Pause,
+    Unpause,
+    DrillPause,
+    SetTransferFee,
+    SetComplianceAuthority,
+    SetPauseFlags,
+    NominateAuthority,
+    AcceptAuthority,
+}
+
+impl AuditAction {
+    fn from_u8(value: u8) -> Result<Self, ProgramError> {
+        match value {
+            0 => Ok(AuditAction::Pause),
+            1 => Ok(AuditAction::Unpause),
+            2 => Ok(AuditAction::DrillPause),
+            3 => Ok(AuditAction::SetTransferFee),
+            4 => Ok(AuditAction::SetComplianceAuthority),
+            5 => Ok(AuditAction::SetPauseFlags),
+            6 => Ok(AuditAction::NominateAuthority),
+            7 => Ok(AuditAction::AcceptAuthority),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct AuditEntry {
+    pub action: AuditAction,
+    pub actor: Pubkey,
+    pub slot: u64,
+    /// Action-specific value, e.g. the drill length in slots.
+    pub detail: u64,
+}
+
+/// Ring buffer of the most recent admin actions.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct AuditLog {
+    pub entries: Vec<AuditEntry>,
+    pub next_index: u8,
+    pub is_initialized: bool,
+}
+
+impl AuditLog {
+    pub fn record(&mut self, entry: AuditEntry) {
+        let index = self.next_index as usize;
+        if index < self.entries.len() {
+            self.entries[index] = entry;
+        } else {
+            self.entries.push(entry);
+        }
+        self.next_index = ((index + 1) % AUDIT_LOG_CAPACITY) as u8;
+    }
+}
+
+impl Sealed for AuditLog {}
+
+impl IsInitialized for AuditLog {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for AuditLog {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [230, 207, 176, 233, 170, 130, 101, 244];
+}
+
+impl Pack for AuditLog {
+    const LEN: usize = 796; // discriminator (8) + version (1) + count (1) + (u8 (1) + Pubkey (32) + u64 (8) * 2) * AUDIT_LOG_CAPACITY + u8 (1) + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        let mut cursor = 0;
+        dst[cursor] = self.entries.len() as u8;
+        cursor += 1;
+        for slot in 0..AUDIT_LOG_CAPACITY {
+            match self.entries.get(slot) {
+                Some(entry) => {
+                    dst[cursor] = entry.action as u8;
+                    dst[cursor + 1..cursor + 33].copy_from_slice(entry.actor.as_ref());
+                    dst[cursor + 33..cursor + 41].copy_from_slice(&entry.slot.to_le_bytes());
+                    dst[cursor + 41..cursor + 49].copy_from_slice(&entry.detail.to_le_bytes());
+                }
+                None => dst[cursor..cursor + 49].fill(0),
+            }
+            cursor += 49;
+        }
+        dst[cursor] = self.next_index;
+        cursor += 1;
+        dst[cursor] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let mut cursor = 0;
+        let count = src[cursor] as usize;
+        cursor += 1;
+        if count > AUDIT_LOG_CAPACITY {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut entries = Vec::with_capacity(count);
+        for slot in 0..AUDIT_LOG_CAPACITY {
+            if slot < count {
+                entries.push(AuditEntry {
+                    action: AuditAction::from_u8(src[cursor])?,
+                    actor: Pubkey::new_from_array(src[cursor + 1..cursor + 33].try_into().unwrap()),
+                    slot: u64::from_le_bytes(src[cursor + 33..cursor + 41].try_into().unwrap()),
+                    detail: u64::from_le_bytes(src[cursor + 41..cursor + 49].try_into().unwrap()),
+                });
+            }
+            cursor += 49;
+        }
+        let next_index = src[cursor];
+        cursor += 1;
+        let is_initialized = src[cursor] != 0;
+        Ok(AuditLog {
+            entries,
+            next_index,
+            is_initialized,
+        })
+    }
+}
+
+pub fn config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROGRAM_CONFIG_SEED], program_id)
+}
+
+pub fn audit_log_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUDIT_LOG_SEED], program_id)
+}
+
+/// Loads the program config, checking it lives at the config PDA.
+pub fn load_config(program_id: &Pubkey, config_acc: &AccountInfo) -> Result<ProgramConfig, ProgramError> {
+    assert_owned_by(config_acc, program_id)?;
+    assert_pda(config_acc, config_address(program_id))?;
+    ProgramConfig::unpack(&config_acc.try_borrow_data()?)
+}
+
+/// Pause gate run by the dispatcher before every gated handler, with the
+/// `PAUSE_*` bit of the handler's group in `pause_flag` (zero for none). A
+/// drill pause lifts itself once its slot has passed, without another transaction.
+pub fn check_not_paused(program_id: &Pubkey, config_acc: &AccountInfo, pause_flag: u8) -> ProgramResult {
+    let config = load_config(program_id, config_acc)?;
+    if config.is_paused_at(Clock::get()?.slot) || config.pause_flags & pause_flag != 0 {
+        return Err(GgtError::ProgramPaused.into());
+    }
+    Ok(())
+}
+
+fn record_audit(program_id: &Pubkey, audit_log_acc: &AccountInfo, action: AuditAction, actor: &Pubkey, detail: u64) -> ProgramResult {
+    if audit_log_acc.owner != program_id || *audit_log_acc.key != audit_log_address(program_id).0 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut log = AuditLog::unpack(&audit_log_acc.try_borrow_data()?)?;
+    log.record(AuditEntry {
+        action,
+        actor: *actor,
+        slot: Clock::get()?.slot,
+        detail,
+    });
+    pack_state(&log, audit_log_acc)
+}
+
+pub struct InitializeConfigAccounts<'a, 'info> {
+    pub config_acc: &'a AccountInfo<'info>,
+    pub admin_acc: &'a AccountInfo<'info>,
+    pub audit_log_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub config_bump: u8,
+    pub audit_log_bump: u8,
+}
+
+impl<'a, 'info> InitializeConfigAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let config_acc = next_account_info(account_info_iter)?;
+        let admin_acc = next_account_info(account_info_iter)?;
+        let audit_log_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        if admin_acc.key != &ADMIN_PUBKEY {
+            return Err(ProgramError::IllegalOwner);
+        }
+        assert_signer(admin_acc)?;
+        let config_bump = assert_pda(config_acc, config_address(program_id))?;
+        let audit_log_bump = assert_pda(audit_log_acc, audit_log_address(program_id))?;
+        Ok(Self { config_acc, admin_acc, audit_log_acc, system_program_acc, config_bump, audit_log_bump })
+    }
+}
+
+/// Accounts for the config setters. `authority_acc` must be `authority` and sign;
+/// the config and audit log PDAs are checked when loaded.
+pub struct ConfigUpdateAccounts<'a, 'info> {
+    pub config_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub audit_log_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ConfigUpdateAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>], authority: &Pubkey) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            config_acc: next_account_info(account_info_iter)?,
+            authority_acc: next_account_info(account_info_iter)?,
+            audit_log_acc: next_account_info(account_info_iter)?,
+        };
+        if ctx.authority_acc.key != authority {
+            return Err(ProgramError::IllegalOwner);
+        }
+        assert_signer(ctx.authority_acc)?;
+        Ok(ctx)
+    }
+
+    /// Like `try_from`, with `authority_acc` checked against the config's holder of `role`.
+    pub fn try_from_role(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], role: AuthorityRole) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            config_acc: next_account_info(account_info_iter)?,
+            authority_acc: next_account_info(account_info_iter)?,
+            audit_log_acc: next_account_info(account_info_iter)?,
+        };
+        check_authority(program_id, ctx.config_acc, &[role], ctx.authority_acc)?;
+        Ok(ctx)
+    }
+}
+
+/// Accounts for `nominate_authority` and `accept_authority`: the config, the
+/// signer and the audit log. The metadata role also takes the GGT mint's
+/// metadata account, then for `accept_authority` the outgoing update
+/// authority, which must sign too, and the token metadata program.
+pub struct AuthorityTransferAccounts<'a, 'info> {
+    pub config_acc: &'a AccountInfo<'info>,
+    pub signer_acc: &'a AccountInfo<'info>,
+    pub audit_log_acc: &'a AccountInfo<'info>,
+    pub remaining_accs: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> AuthorityTransferAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let config_acc = next_account_info(account_info_iter)?;
+        let signer_acc = next_account_info(account_info_iter)?;
+        let audit_log_acc = next_account_info(account_info_iter)?;
+        assert_signer(signer_acc)?;
+        Ok(Self { config_acc, signer_acc, audit_log_acc, remaining_accs: account_info_iter.as_slice() })
+    }
+}
+
+/// Creates the program config and audit log PDAs, paid for by the admin.
+pub fn initialize_program_config(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let InitializeConfigAccounts { config_acc, admin_acc, audit_log_acc, system_program_acc, config_bump, audit_log_bump } =
+        InitializeConfigAccounts::try_from(program_id, accounts)?;
+    if config_acc.lamports() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    create_state_account(program_id, admin_acc, config_acc, system_program_acc, ProgramConfig::LEN, &[&[PROGRAM_CONFIG_SEED, &[config_bump]]])?;
+    create_state_account(program_id, admin_acc, audit_log_acc, system_program_acc, AuditLog::LEN, &[&[AUDIT_LOG_SEED, &[audit_log_bump]]])?;
+
+    let config = ProgramConfig {
+        paused: false,
+        pause_until_slot: 0,
+        fee_bps: 0,
+        compliance_authority: Pubkey::default(),
+        pause_flags: 0,
+        admin: Pubkey::default(),
+        governance: Pubkey::default(),
+        bridge_admin: Pubkey::default(),
+        nominations: Default::default(),
+        total_burned: 0,
+        is_initialized: true,
+    };
+    pack_state(&config, config_acc)?;
+    let log = AuditLog {
+        entries: Vec::new(),
+        next_index: 0,
+        is_initialized: true,
+    };
+    pack_state(&log, audit_log_acc)?;
+    msg!("Program config initialized");
+    Ok(())
+}
+
+/// Pauses or unpauses every gated instruction until changed again.
+pub fn set_paused(program_id: &Pubkey, accounts: &[AccountInfo], paused: bool) -> ProgramResult {
+    let ConfigUpdateAccounts { config_acc, authority_acc: admin_acc, audit_log_acc } =
+        ConfigUpdateAccounts::try_from(accounts, &multisig_authority_address(program_id).0)?;
+    let mut config = load_config(program_id, config_acc)?;
+    config.paused = paused;
+    config.pause_until_slot = 0;
+    pack_state(&config, config_acc)?;
+
+    let action = if paused { AuditAction::Pause } else { AuditAction::Unpause };
+    record_audit(program_id, audit_log_acc, action, admin_acc.key, 0)?;
+    msg!("Program paused: {}", paused);
+    Ok(())
+}
+
+/// Kill-switch drill: pauses the program for `slots` slots, after which the pause
+/// gate lets instructions through again on its own.
+pub fn drill_pause(program_id: &Pubkey, accounts: &[AccountInfo], slots: u64) -> ProgramResult {
+    let ConfigUpdateAccounts { config_acc, authority_acc: admin_acc, audit_log_acc } =
+        ConfigUpdateAccounts::try_from(accounts, &multisig_authority_address(program_id).0)?;
+    if slots == 0 || slots > MAX_DRILL_SLOTS {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mut config = load_config(program_id, config_acc)?;
+    if config.is_paused_at(Clock::get()?.slot) {
+        msg!("Program is already paused");
+        return Err(GgtError::ProgramPaused.into());
+    }
+    let until = Clock::get()?.slot.checked_add(slots).ok_or(GgtError::MathOverflow)?;
+    config.paused = true;
+    config.pause_until_slot = until;
+    pack_state(&config, config_acc)?;
+
+    record_audit(program_id, audit_log_acc, AuditAction::DrillPause, admin_acc.key, slots)?;
+    msg!("Drill pause until slot {}", until);
+    Ok(())
+}
+
+/// Sets the transfer fee. Governance only, so changes go through a passed proposal.
+pub fn set_transfer_fee(program_id: &Pubkey, accounts: &[AccountInfo], fee_bps: u16) -> ProgramResult {
+    let ConfigUpdateAccounts { config_acc, authority_acc: governance_acc, audit_log_acc } =
+        ConfigUpdateAccounts::try_from_role(program_id, accounts, AuthorityRole::Governance)?;
+    if fee_bps > MAX_TRANSFER_FEE_BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mut config = load_config(program_id, config_acc)?;
+    config.fee_bps = fee_bps;
+    pack_state(&config, config_acc)?;
+
+    record_audit(program_id, audit_log_acc, AuditAction::SetTransferFee, governance_acc.key, fee_bps as u64)?;
+    msg!("Transfer fee set to {} bps", fee_bps);
+    Ok(())
+}
+
+/// Replaces the `PAUSE_*` flags. Only the governance authority PDA can sign,
+/// so flags are set and lifted by executed proposals.
+pub fn set_pause_flags(program_id: &Pubkey, accounts: &[AccountInfo], pause_flags: u8) -> ProgramResult {
+    let ConfigUpdateAccounts { config_acc, authority_acc: governance_acc, audit_log_acc } =
+        ConfigUpdateAccounts::try_from(accounts, &governance_authority_address(program_id).0)?;
+    if pause_flags & !PAUSE_FLAGS_ALL != 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mut config = load_config(program_id, config_acc)?;
+    config.pause_flags = pause_flags;
+    pack_state(&config, config_acc)?;
+
+    record_audit(program_id, audit_log_acc, AuditAction::SetPauseFlags, governance_acc.key, pause_flags as u64)?;
+    msg!("Pause flags set to {:#06b}", pause_flags);
+    Ok(())
+}
+
+/// Names the compliance authority allowed to freeze and thaw accounts. Pass the
+/// default key to disable freezing.
+pub fn set_compliance_authority(program_id: &Pubkey, accounts: &[AccountInfo], compliance_authority: Pubkey) -> ProgramResult {
+    let ConfigUpdateAccounts { config_acc, authority_acc: admin_acc, audit_log_acc } =
+        ConfigUpdateAccounts::try_from(accounts, &multisig_authority_address(program_id).0)?;
+    let mut config = load_config(program_id, config_acc)?;
+    config.compliance_authority = compliance_authority;
+    pack_state(&config, config_acc)?;
+
+    record_audit(program_id, audit_log_acc, AuditAction::SetComplianceAuthority, admin_acc.key, 0)?;
+    msg!("Compliance authority set to {}", compliance_authority);
+    Ok(())
+}
+
+/// Checks `signer_acc` holds one of `roles`, as recorded in the program config, and signed.
+pub(crate) fn check_authority(program_id: &Pubkey, config_acc: &AccountInfo, roles: &[AuthorityRole], signer_acc: &AccountInfo) -> ProgramResult {
+    let config = load_config(program_id, config_acc)?;
+    if !roles.iter().any(|role| config.authority(*role) == Some(*signer_acc.key)) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    assert_signer(signer_acc)
+}
+
+/// Checks `metadata_acc` is the token metadata account of the GGT mint, the
+/// only metadata whose update authority the metadata role covers.
+fn check_ggt_metadata(program_id: &Pubkey, metadata_acc: &AccountInfo) -> ProgramResult {
+    assert_owned_by(metadata_acc, &mpl_token_metadata::ID)?;
+    if *metadata_acc.key != Metadata::find_pda(&TokenContract::mint_address(program_id).0).0 {
+        msg!("Metadata {} does not belong to the GGT mint", metadata_acc.key);
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(())
+}
+
+/// Offers `role` to `nominee`, who has `NOMINATION_TTL_SECONDS` to accept it.
+/// Signed by the current holder; nominating the default key withdraws the offer.
+pub fn nominate_authority(program_id: &Pubkey, accounts: &[AccountInfo], role: AuthorityRole, nominee: Pubkey) -> ProgramResult {
+    let AuthorityTransferAccounts { config_acc, signer_acc, audit_log_acc, remaining_accs } = AuthorityTransferAccounts::try_from(accounts)?;
+    let mut config = load_config(program_id, config_acc)?;
+    let holder = match config.authority(role) {
+        Some(holder) => holder,
+        None => {
+            let metadata_acc = next_account_info(&mut remaining_accs.iter())?;
+            check_ggt_metadata(program_id, metadata_acc)?;
+            Metadata::safe_deserialize(&metadata_acc.try_borrow_data()?).map_err(|_| ProgramError::InvalidAccountData)?.update_authority
+        }
+    };
+    if *signer_acc.key != holder {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let expires_at = if nominee == Pubkey::default() { 0 } else { Clock::get()?.unix_timestamp + NOMINATION_TTL_SECONDS };
+    config.nominations[role as usize] = Nomination { nominee, expires_at };
+    pack_state(&config, config_acc)?;
+
+    record_audit(program_id, audit_log_acc, AuditAction::NominateAuthority, signer_acc.key, role as u64)?;
+    msg!("Nominated {} for {:?} until {}", nominee, role, expires_at);
+    Ok(())
+}
+
+/// Hands `role` to its nominee, who must sign before the nomination expires.
+pub fn accept_authority(program_id: &Pubkey, accounts: &[AccountInfo], role: AuthorityRole) -> ProgramResult {
+    let AuthorityTransferAccounts { config_acc, signer_acc, audit_log_acc, remaining_accs } = AuthorityTransferAccounts::try_from(accounts)?;
+    let mut config = load_config(program_id, config_acc)?;
+    let nomination = config.nominations[role as usize];
+    if nomination.nominee == Pubkey::default() || *signer_acc.key != nomination.nominee {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if Clock::get()?.unix_timestamp >= nomination.expires_at {
+        msg!("Nomination for {:?} expired at {}", role, nomination.expires_at);
+        return Err(GgtError::NominationExpired.into());
+    }
+    match role {
+        AuthorityRole::Admin => config.admin = nomination.nominee,
+        AuthorityRole::Governance => config.governance = nomination.nominee,
+        AuthorityRole::BridgeAdmin => config.bridge_admin = nomination.nominee,
+        AuthorityRole::MetadataUpdate => {
+            let account_info_iter = &mut remaining_accs.iter();
+            let metadata_acc = next_account_info(account_info_iter)?;
+            let update_authority_acc = next_account_info(account_info_iter)?;
+            check_ggt_metadata(program_id, metadata_acc)?;
+            let ix = UpdateMetadataAccountV2 { metadata: *metadata_acc.key, update_authority: *update_authority_acc.key }.instruction(
+                UpdateMetadataAccountV2InstructionArgs {
+                    data: None,
+                    new_update_authority: Some(nomination.nominee),
+                    primary_sale_happened: None,
+                    is_mutable: None,
+                },
+            );
+            invoke(&ix, &[metadata_acc.clone(), update_authority_acc.clone()])?;
+        }
+    }
+    config.nominations[role as usize] = Nomination::default();
+    pack_state(&config, config_acc)?;
+
+    record_audit(program_id, audit_log_acc, AuditAction::AcceptAuthority, signer_acc.key, role as u64)?;
+    msg!("{:?} role accepted by {}", role, signer_acc.key);
+    Ok(())
+}
+
+/// Checks `compliance_acc` is the configured compliance authority and signed.
+pub fn check_compliance_authority(program_id: &Pubkey, config_acc: &AccountInfo, compliance_acc: &AccountInfo) -> ProgramResult {
+    let config = load_config(program_id, config_acc)?;
+    if config.compliance_authority == Pubkey::default() || *compliance_acc.key != config.compliance_authority {
+        return Err(ProgramError::IllegalOwner);
+    }
+    assert_signer(compliance_acc)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{account_info, fund_rent_exempt, program_config, SimulatedBank};
+
+    fn metadata_data(update_authority: &Pubkey, mint: &Pubkey) -> Vec<u8> {
+        borsh::to_vec(&(
+            4u8, // Key::MetadataV1
+            *update_authority,
+            *mint,
+            "Gadder Gold",
+            "GGT",
+            "http://example.com/metadata",
+            0u16,
+            None::<u8>,
+            false,
+            true,
+            None::<u8>,
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_metadata_role_moves_only_with_the_ggt_metadata() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (ggt_mint, other_mint) = (TokenContract::mint_address(&program_id).0, Pubkey::new_unique());
+        let (holder_key, nominee_key, metadata_program_id) = (Pubkey::new_unique(), Pubkey::new_unique(), mpl_token_metadata::ID);
+        let system_program_id = Pubkey::default();
+        let (ggt_metadata_key, other_metadata_key) = (Metadata::find_pda(&ggt_mint).0, Metadata::find_pda(&other_mint).0);
+        let (config_key, mut config_data) = program_config(&program_id);
+        let (audit_log_key, _) = audit_log_address(&program_id);
+        let mut audit_log_data = vec![0u8; AuditLog::LEN];
+        AuditLog::pack(AuditLog { entries: vec![], next_index: 0, is_initialized: true }, &mut audit_log_data).unwrap();
+        let (mut ggt_metadata_data, mut other_metadata_data) = (metadata_data(&holder_key, &ggt_mint), metadata_data(&holder_key, &other_mint));
+        let mut lamports = [0u64; 6];
+        let [l0, l1, l2, l3, l4, l5] = &mut lamports;
+        let (mut d1, mut d2) = (vec![], vec![]);
+        let config_acc = account_info(&config_key, false, true, l0, &mut config_data, &program_id);
+        let holder_acc = account_info(&holder_key, true, false, l1, &mut d1, &system_program_id);
+        let nominee_acc = account_info(&nominee_key, true, false, l2, &mut d2, &system_program_id);
+        let audit_log_acc = account_info(&audit_log_key, false, true, l3, &mut audit_log_data, &program_id);
+        let ggt_metadata_acc = account_info(&ggt_metadata_key, false, true, l4, &mut ggt_metadata_data, &metadata_program_id);
+        let other_metadata_acc = account_info(&other_metadata_key, false, true, l5, &mut other_metadata_data, &metadata_program_id);
+        fund_rent_exempt(&[&config_acc, &audit_log_acc]);
+        bank.set_unix_timestamp(10);
+
+        // Another mint's metadata, though its update authority signs, does not carry the role.
+        let other = [config_acc.clone(), holder_acc.clone(), audit_log_acc.clone(), other_metadata_acc.clone()];
+        assert_eq!(nominate_authority(&program_id, &other, AuthorityRole::MetadataUpdate, nominee_key), Err(ProgramError::InvalidSeeds));
+        let nominate = [config_acc.clone(), holder_acc.clone(), audit_log_acc.clone(), ggt_metadata_acc];
+        nominate_authority(&program_id, &nominate, AuthorityRole::MetadataUpdate, nominee_key).unwrap();
+        let nomination = load_config(&program_id, &config_acc).unwrap().nominations[AuthorityRole::MetadataUpdate as usize];
+        assert_eq!(nomination.nominee, nominee_key);
+
+        let accept = [config_acc, nominee_acc, audit_log_acc, other_metadata_acc, holder_acc];
+        assert_eq!(accept_authority(&program_id, &accept, AuthorityRole::MetadataUpdate), Err(ProgramError::InvalidSeeds));
+    }
+}
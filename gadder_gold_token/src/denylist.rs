@@ -0,0 +1,241 @@
+//! Compliance denylist. The compliance authority in the program config keeps
+//! a list of addresses, wallets or token accounts, in one PDA; transfers,
+//! stakes and bridge locks touching a listed address are rejected. Until the
+//! first address is added the PDA does not exist and nothing is denied.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use crate::{config, error::GgtError, events, token_program};
+use crate::state::{create_state_account, pack_state};
+use crate::validation::{assert_owned_by, assert_pda};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const DENYLIST_SEED: &[u8] = b"denylist";
+pub const MAX_DENYLIST_ENTRIES: usize = 64;
+
+pub fn denylist_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[DENYLIST_SEED], program_id)
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct Denylist {
+    pub entries: Vec<Pubkey>,
+    pub is_initialized: bool,
+}
+
+impl Sealed for Denylist {}
+
+impl IsInitialized for Denylist {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for Denylist {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [108, 181, 129, 179, 64, 117, 203, 63];
+}
+
+impl Pack for Denylist {
+    const LEN: usize = 2059; // discriminator (8) + version (1) + count (1) + Pubkey (32) * MAX_DENYLIST_ENTRIES + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0] = self.entries.len() as u8;
+        for (slot, entry) in dst[1..1 + 32 * MAX_DENYLIST_ENTRIES].chunks_exact_mut(32).zip(&self.entries) {
+            slot.copy_from_slice(entry.as_ref());
+        }
+        dst[1 + 32 * MAX_DENYLIST_ENTRIES] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let count = src[0] as usize;
+        if count > MAX_DENYLIST_ENTRIES {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let entries = src[1..1 + 32 * count].chunks_exact(32).map(|slot| Pubkey::new_from_array(slot.try_into().unwrap())).collect();
+        Ok(Denylist { entries, is_initialized: src[1 + 32 * MAX_DENYLIST_ENTRIES] != 0 })
+    }
+}
+
+/// Rejects the instruction if any of `addresses` is on the denylist in
+/// `denylist_acc`, which must be the denylist PDA. Only a PDA that holds no
+/// lamports counts as not created yet; a funded one the program does not own
+/// fails closed until the compliance authority creates the denylist.
+pub(crate) fn check_not_denylisted(program_id: &Pubkey, denylist_acc: &AccountInfo, addresses: &[&Pubkey]) -> ProgramResult {
+    assert_pda(denylist_acc, denylist_address(program_id))?;
+    if denylist_acc.lamports() == 0 {
+        return Ok(());
+    }
+    assert_owned_by(denylist_acc, program_id)?;
+    let denylist = Denylist::unpack(&denylist_acc.try_borrow_data()?)?;
+    if let Some(denied) = addresses.iter().find(|address| denylist.entries.contains(address)) {
+        msg!("{} is on the denylist", denied);
+        return Err(GgtError::AddressDenylisted.into());
+    }
+    Ok(())
+}
+
+/// Rejects a transfer out of `source_acc` signed by `signers` if any party to
+/// it is on the denylist: the source, the signers, and each destination token
+/// account and its owner.
+pub(crate) fn check_transfer_parties(
+    program_id: &Pubkey,
+    denylist_acc: &AccountInfo,
+    source_acc: &AccountInfo,
+    signers: &[&Pubkey],
+    dest_accs: &[&AccountInfo],
+) -> ProgramResult {
+    let dest_owners =
+        dest_accs.iter().map(|dest_acc| Ok(token_program::unpack_account(dest_acc)?.owner)).collect::<Result<Vec<_>, ProgramError>>()?;
+    let mut parties = vec![source_acc.key];
+    parties.extend_from_slice(signers);
+    parties.extend(dest_accs.iter().map(|dest_acc| dest_acc.key));
+    parties.extend(&dest_owners);
+    check_not_denylisted(program_id, denylist_acc, &parties)
+}
+
+/// The compliance authority signs and pays for the denylist PDA the first
+/// time an address is added.
+pub struct UpdateDenylistAccounts<'a, 'info> {
+    pub denylist_acc: &'a AccountInfo<'info>,
+    pub compliance_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub denylist_bump: u8,
+}
+
+impl<'a, 'info> UpdateDenylistAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let denylist_acc = next_account_info(account_info_iter)?;
+        let compliance_acc = next_account_info(account_info_iter)?;
+        let config_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        config::check_compliance_authority(program_id, config_acc, compliance_acc)?;
+        let denylist_bump = assert_pda(denylist_acc, denylist_address(program_id))?;
+        Ok(Self { denylist_acc, compliance_acc, system_program_acc, denylist_bump })
+    }
+}
+
+/// Adds `address` to the denylist, creating the denylist PDA on first use,
+/// even if someone has already sent it lamports.
+pub fn add_to_denylist(program_id: &Pubkey, accounts: &[AccountInfo], address: Pubkey) -> ProgramResult {
+    let UpdateDenylistAccounts { denylist_acc, compliance_acc, system_program_acc, denylist_bump } =
+        UpdateDenylistAccounts::try_from(program_id, accounts)?;
+    let mut denylist = if denylist_acc.lamports() == 0 || denylist_acc.owner != program_id {
+        create_state_account(program_id, compliance_acc, denylist_acc, system_program_acc, Denylist::LEN, &[&[DENYLIST_SEED, &[denylist_bump]]])?;
+        Denylist { entries: Vec::new(), is_initialized: true }
+    } else {
+        assert_owned_by(denylist_acc, program_id)?;
+        Denylist::unpack(&denylist_acc.try_borrow_data()?)?
+    };
+    if denylist.entries.contains(&address) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if denylist.entries.len() >= MAX_DENYLIST_ENTRIES {
+        msg!("Denylist is full");
+        return Err(ProgramError::InvalidArgument);
+    }
+    denylist.entries.push(address);
+    pack_state(&denylist, denylist_acc)?;
+    events::emit(&events::DenylistAdded { address, authority: *compliance_acc.key });
+    msg!("Denylisted {}", address);
+    Ok(())
+}
+
+/// Takes `address` off the denylist.
+pub fn remove_from_denylist(program_id: &Pubkey, accounts: &[AccountInfo], address: Pubkey) -> ProgramResult {
+    let UpdateDenylistAccounts { denylist_acc, compliance_acc, .. } = UpdateDenylistAccounts::try_from(program_id, accounts)?;
+    assert_owned_by(denylist_acc, program_id)?;
+    let mut denylist = Denylist::unpack(&denylist_acc.try_borrow_data()?)?;
+    let slot = denylist.entries.iter().position(|entry| *entry == address).ok_or(ProgramError::InvalidArgument)?;
+    denylist.entries.swap_remove(slot);
+    pack_state(&denylist, denylist_acc)?;
+    events::emit(&events::DenylistRemoved { address, authority: *compliance_acc.key });
+    msg!("Removed {} from the denylist", address);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{config_address, ProgramConfig};
+    use crate::test_utils::{account_info, SimulatedBank};
+    use solana_program::rent::Rent;
+
+    #[test]
+    fn test_compliance_authority_adds_and_removes_denied_addresses() {
+        let _bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let system_program_id = solana_program::system_program::id();
+        let (denylist_key, _) = denylist_address(&program_id);
+        let (config_key, _) = config_address(&program_id);
+        let (compliance_key, wallet_key, other_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut config_data = vec![0u8; ProgramConfig::LEN];
+        ProgramConfig::pack(ProgramConfig { compliance_authority: compliance_key, is_initialized: true, ..Default::default() }, &mut config_data).unwrap();
+        let mut denylist_data = vec![0u8; Denylist::LEN];
+        let mut lamports = [0, 100_000_000, 0, 0, 0];
+        let [l0, l1, l2, l3, l4] = &mut lamports;
+        let (mut d1, mut d3, mut d4) = (vec![], vec![], vec![]);
+        let denylist_acc = account_info(&denylist_key, false, true, l0, &mut denylist_data, &program_id);
+        let compliance_acc = account_info(&compliance_key, true, true, l1, &mut d1, &system_program_id);
+        let config_acc = account_info(&config_key, false, false, l2, &mut config_data, &program_id);
+        let system_program_acc = account_info(&system_program_id, false, false, l3, &mut d3, &system_program_id);
+        let outsider_acc = account_info(&other_key, true, true, l4, &mut d4, &system_program_id);
+
+        // Nothing is denied before the denylist exists.
+        check_not_denylisted(&program_id, &denylist_acc, &[&wallet_key]).unwrap();
+
+        let mut accounts = [denylist_acc.clone(), outsider_acc, config_acc, system_program_acc];
+        assert_eq!(add_to_denylist(&program_id, &accounts, wallet_key), Err(ProgramError::IllegalOwner));
+        accounts[1] = compliance_acc;
+        add_to_denylist(&program_id, &accounts, wallet_key).unwrap();
+        add_to_denylist(&program_id, &accounts, other_key).unwrap();
+        assert_eq!(add_to_denylist(&program_id, &accounts, wallet_key), Err(ProgramError::InvalidArgument));
+        assert_eq!(
+            check_not_denylisted(&program_id, &denylist_acc, &[&Pubkey::new_unique(), &wallet_key]),
+            Err(GgtError::AddressDenylisted.into())
+        );
+
+        remove_from_denylist(&program_id, &accounts, wallet_key).unwrap();
+        check_not_denylisted(&program_id, &denylist_acc, &[&wallet_key]).unwrap();
+        assert_eq!(check_not_denylisted(&program_id, &denylist_acc, &[&other_key]), Err(GgtError::AddressDenylisted.into()));
+        assert_eq!(remove_from_denylist(&program_id, &accounts, wallet_key), Err(ProgramError::InvalidArgument));
+    }
+
+    #[test]
+    fn test_prefunded_denylist_fails_closed_and_can_still_be_created() {
+        let _bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let system_program_id = solana_program::system_program::id();
+        let (denylist_key, _) = denylist_address(&program_id);
+        let (config_key, _) = config_address(&program_id);
+        let (compliance_key, wallet_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut config_data = vec![0u8; ProgramConfig::LEN];
+        ProgramConfig::pack(ProgramConfig { compliance_authority: compliance_key, is_initialized: true, ..Default::default() }, &mut config_data).unwrap();
+        let mut denylist_data = vec![0u8; Denylist::LEN];
+        let mut lamports = [1, 100_000_000, 0, 0];
+        let [l0, l1, l2, l3] = &mut lamports;
+        let (mut d1, mut d3) = (vec![], vec![]);
+        // Someone sent the PDA a lamport before the compliance authority created it.
+        let denylist_acc = account_info(&denylist_key, false, true, l0, &mut denylist_data, &system_program_id);
+        let compliance_acc = account_info(&compliance_key, true, true, l1, &mut d1, &system_program_id);
+        let config_acc = account_info(&config_key, false, false, l2, &mut config_data, &program_id);
+        let system_program_acc = account_info(&system_program_id, false, false, l3, &mut d3, &system_program_id);
+
+        assert_eq!(check_not_denylisted(&program_id, &denylist_acc, &[&wallet_key]), Err(ProgramError::IncorrectProgramId));
+
+        let accounts = [denylist_acc.clone(), compliance_acc, config_acc, system_program_acc];
+        add_to_denylist(&program_id, &accounts, wallet_key).unwrap();
+        assert_eq!(denylist_acc.owner, &program_id);
+        assert_eq!(denylist_acc.lamports(), Rent::default().minimum_balance(Denylist::LEN));
+        assert_eq!(check_not_denylisted(&program_id, &denylist_acc, &[&wallet_key]), Err(GgtError::AddressDenylisted.into()));
+    }
+}
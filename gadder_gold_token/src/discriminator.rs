@@ -0,0 +1,407 @@
+//! Eight-byte type tags at the start of every program-owned account, so an
+//! account of one type cannot be passed where another is expected. Tags
+//! follow Anchor's scheme, the first eight bytes of `sha256("account:<Name>")`.
+//! A one-byte layout version follows the tag.
+//!
+//! Layouts only ever grow by appending fields, each addition bumping the
+//! type's `Discriminator::VERSION`. Unpacking accepts any version up to the
+//! current one and reads fields an older version lacks as zero, and packing
+//! writes the current version, so an account is upgraded the next time it is
+//! written. An addition that does not fit the account's allocation needs
+//! `migrate_account` to grow it first.
+//!
+//! Accounts written before tags were introduced are a header shorter than
+//! their `Pack::LEN` and fail to unpack until `tag_legacy_account` shifts
+//! their data up and writes the header in front of it. Tagged accounts
+//! written before versions were introduced are one byte short, and
+//! `migrate_account` inserts their version.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use crate::{
+    address_book::AddressBook,
+    ai_contract::{ConsultantProfile, MatchRequest, OracleRegistry, PayoutSplit},
+    airdrop::Distribution,
+    bridge_transfer::BridgeTransfer,
+    config::{self, AuditLog, ProgramConfig},
+    cross_chain_bridge_contract::{BridgeConfig, BridgeReceipt, ChainRegistry, OutflowWindow},
+    denylist::Denylist,
+    dispute::{ArbitrationConfig, Dispute},
+    engagement::Engagement,
+    error::GgtError,
+    governance_contract::{BallotPage, GovernanceConfig, Proposal, ProposalCounter, Vote},
+    invoice::Invoice,
+    market_maker::{FastMint, MarketMaker},
+    migration::MigrationState,
+    multisig::{self, AdminAction, Multisig},
+    oracle_operator::{OracleBondConfig, OracleOperator},
+    payment_receipt::PaymentReceipt,
+    reputation::Reputation,
+    staking_contract::{CoStake, CoStakingPool, Stake, StakeSummary, StakingConfig, StakingPool},
+    streaming_contract::PaymentStream,
+    treasury_contract::TreasuryAssets,
+    vesting::VestingSchedule,
+    vote_escrow::VoteEscrow,
+    wrapped_asset::WrappedAsset,
+};
+use crate::validation::{assert_owned_by, assert_pda, assert_signer};
+
+pub const DISCRIMINATOR_LEN: usize = 8;
+/// The tag and the layout version after it.
+pub const HEADER_LEN: usize = DISCRIMINATOR_LEN + 1;
+
+/// A packed account type and the header its `Pack` layout starts with.
+pub trait Discriminator {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN];
+    /// Layout version packing writes. Bump it when appending a field.
+    const VERSION: u8 = 1;
+}
+
+/// Writes `T`'s tag and current version at the start of `dst` and returns
+/// the rest for the body.
+pub fn write_discriminator<T: Discriminator>(dst: &mut [u8]) -> &mut [u8] {
+    let (header, body) = dst.split_at_mut(HEADER_LEN);
+    header[..DISCRIMINATOR_LEN].copy_from_slice(&T::DISCRIMINATOR);
+    header[DISCRIMINATOR_LEN] = T::VERSION;
+    body
+}
+
+/// Checks `src` is long enough for `T`, tagged as `T` and of a version this
+/// program reads, and returns the body after the header. An all-zero header
+/// is accepted: it is a freshly allocated account the program has not written yet.
+pub fn read_discriminator<T: Discriminator + Pack>(src: &[u8]) -> Result<&[u8], ProgramError> {
+    if src.len() < T::LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let (header, body) = src.split_at(HEADER_LEN);
+    let (tag, version) = (&header[..DISCRIMINATOR_LEN], header[DISCRIMINATOR_LEN]);
+    if tag == [0; DISCRIMINATOR_LEN] && version == 0 {
+        return Ok(body);
+    }
+    if tag != T::DISCRIMINATOR {
+        return Err(GgtError::AccountDiscriminatorMismatch.into());
+    }
+    if version == 0 || version > T::VERSION {
+        return Err(GgtError::UnsupportedAccountVersion.into());
+    }
+    Ok(body)
+}
+
+/// A tagged account type, for finding an account's layout by its tag.
+struct TaggedType {
+    discriminator: [u8; DISCRIMINATOR_LEN],
+    version: u8,
+    len: usize,
+    /// Body offset of the header of a layout embedded in this one.
+    nested: Option<usize>,
+    upgrade: fn(&mut [u8]) -> ProgramResult,
+}
+
+/// Unpacks `data` as `T`, reading fields its version lacks as zero, and packs
+/// it back at the current version.
+fn upgrade<T: Pack + IsInitialized>(data: &mut [u8]) -> ProgramResult {
+    T::pack(T::unpack(data)?, data)
+}
+
+fn tagged<T: Discriminator + Pack + IsInitialized>() -> TaggedType {
+    TaggedType { discriminator: T::DISCRIMINATOR, version: T::VERSION, len: T::LEN, nested: None, upgrade: upgrade::<T> }
+}
+
+fn tagged_types() -> [TaggedType; 43] {
+    [
+        tagged::<AddressBook>(),
+        tagged::<MatchRequest>(),
+        tagged::<OracleRegistry>(),
+        tagged::<ConsultantProfile>(),
+        tagged::<PayoutSplit>(),
+        tagged::<Distribution>(),
+        tagged::<ProgramConfig>(),
+        tagged::<AuditLog>(),
+        tagged::<BridgeConfig>(),
+        tagged::<ChainRegistry>(),
+        tagged::<BridgeReceipt>(),
+        tagged::<BridgeTransfer>(),
+        tagged::<OutflowWindow>(),
+        tagged::<Engagement>(),
+        tagged::<ArbitrationConfig>(),
+        tagged::<Dispute>(),
+        tagged::<Reputation>(),
+        tagged::<PaymentReceipt>(),
+        tagged::<Denylist>(),
+        tagged::<GovernanceConfig>(),
+        tagged::<Proposal>(),
+        tagged::<Vote>(),
+        tagged::<VoteEscrow>(),
+        tagged::<BallotPage>(),
+        tagged::<ProposalCounter>(),
+        tagged::<Invoice>(),
+        tagged::<MarketMaker>(),
+        tagged::<FastMint>(),
+        tagged::<MigrationState>(),
+        tagged::<Multisig>(),
+        tagged::<AdminAction>(),
+        tagged::<OracleBondConfig>(),
+        tagged::<OracleOperator>(),
+        tagged::<StakingConfig>(),
+        tagged::<Stake>(),
+        tagged::<StakingPool>(),
+        TaggedType { nested: Some(144), ..tagged::<CoStakingPool>() },
+        TaggedType { nested: Some(16), ..tagged::<CoStake>() },
+        tagged::<StakeSummary>(),
+        tagged::<PaymentStream>(),
+        tagged::<TreasuryAssets>(),
+        tagged::<VestingSchedule>(),
+        tagged::<WrappedAsset>(),
+    ]
+}
+
+/// Shifts an untagged layout, reallocated to its tagged length, past the
+/// header and writes `discriminator` and version 1 in front of it.
+fn retag(data: &mut [u8], discriminator: [u8; DISCRIMINATOR_LEN]) {
+    data.copy_within(..data.len() - HEADER_LEN, HEADER_LEN);
+    data[..DISCRIMINATOR_LEN].copy_from_slice(&discriminator);
+    data[DISCRIMINATOR_LEN] = 1;
+}
+
+/// Shifts a layout tagged before versions, reallocated to its versioned
+/// length, to insert version 1 after its tag and after the tag of the layout
+/// it embeds at body offset `nested`.
+fn insert_versions(data: &mut [u8], nested: Option<usize>) {
+    let mut tag_ends = vec![DISCRIMINATOR_LEN];
+    tag_ends.extend(nested.map(|offset| DISCRIMINATOR_LEN + offset + DISCRIMINATOR_LEN));
+    let mut end = data.len() - tag_ends.len();
+    for (shift, &tag_end) in tag_ends.iter().enumerate().rev() {
+        data.copy_within(tag_end..end, tag_end + shift + 1);
+        data[tag_end + shift] = 1;
+        end = tag_end;
+    }
+}
+
+/// Grows `account_acc` to `len` zeroed bytes, topping up its rent from `payer_acc`.
+fn grow<'a>(account_acc: &AccountInfo<'a>, payer_acc: &AccountInfo<'a>, system_program_acc: &AccountInfo<'a>, len: usize) -> ProgramResult {
+    let shortfall = Rent::get()?.minimum_balance(len).saturating_sub(account_acc.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer_acc.key, account_acc.key, shortfall),
+            &[payer_acc.clone(), account_acc.clone(), system_program_acc.clone()],
+        )?;
+    }
+    account_acc.realloc(len, true)
+}
+
+/// Grows `account_acc` by the header and retags it as `tagged`, which its
+/// data must then unpack as.
+fn retag_account<'a>(
+    account_acc: &AccountInfo<'a>,
+    payer_acc: &AccountInfo<'a>,
+    system_program_acc: &AccountInfo<'a>,
+    tagged: &TaggedType,
+) -> ProgramResult {
+    grow(account_acc, payer_acc, system_program_acc, tagged.len)?;
+    retag(&mut account_acc.try_borrow_mut_data()?, tagged.discriminator);
+    (tagged.upgrade)(&mut account_acc.try_borrow_mut_data()?)
+}
+
+/// Grows `account_acc`, a `tagged` account at `version`, to the current
+/// layout and repacks it at the current version. Version 0 is an account
+/// tagged before versions were introduced.
+fn migrate<'a>(
+    account_acc: &AccountInfo<'a>,
+    payer_acc: &AccountInfo<'a>,
+    system_program_acc: &AccountInfo<'a>,
+    tagged: &TaggedType,
+    version: u8,
+) -> ProgramResult {
+    if version >= tagged.version {
+        return Err(GgtError::UnsupportedAccountVersion.into());
+    }
+    if version == 0 {
+        let unversioned_len = tagged.len - 1 - usize::from(tagged.nested.is_some());
+        if account_acc.data_len() != unversioned_len {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        grow(account_acc, payer_acc, system_program_acc, tagged.len)?;
+        insert_versions(&mut account_acc.try_borrow_mut_data()?, tagged.nested);
+    } else {
+        if account_acc.try_borrow_data()?.get(DISCRIMINATOR_LEN) != Some(&version) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        grow(account_acc, payer_acc, system_program_acc, tagged.len)?;
+    }
+    (tagged.upgrade)(&mut account_acc.try_borrow_mut_data()?)
+}
+
+pub struct TagLegacyAccounts<'a, 'info> {
+    pub account_acc: &'a AccountInfo<'info>,
+    pub admin_acc: &'a AccountInfo<'info>,
+    pub config_acc: &'a AccountInfo<'info>,
+    pub payer_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> TagLegacyAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            account_acc: next_account_info(account_info_iter)?,
+            admin_acc: next_account_info(account_info_iter)?,
+            config_acc: next_account_info(account_info_iter)?,
+            payer_acc: next_account_info(account_info_iter)?,
+            system_program_acc: next_account_info(account_info_iter)?,
+        };
+        assert_signer(ctx.payer_acc)?;
+        Ok(ctx)
+    }
+}
+
+/// Retags an account written before discriminators as the type tagged
+/// `discriminator`, signed by the admin. Old layouts of different types can
+/// share a length, so the caller names the type. The program config is
+/// retagged first when it is still untagged, since the admin is read from it;
+/// this runs ungated for the same reason.
+pub fn tag_legacy_account(program_id: &Pubkey, accounts: &[AccountInfo], discriminator: [u8; DISCRIMINATOR_LEN]) -> ProgramResult {
+    let TagLegacyAccounts { account_acc, admin_acc, config_acc, payer_acc, system_program_acc } = TagLegacyAccounts::try_from(accounts)?;
+    let config_untagged = config_acc.data_len() == ProgramConfig::LEN - HEADER_LEN;
+    if config_untagged {
+        assert_owned_by(config_acc, program_id)?;
+        assert_pda(config_acc, config::config_address(program_id))?;
+        retag_account(config_acc, payer_acc, system_program_acc, &tagged::<ProgramConfig>())?;
+        msg!("Tagged program config {}", config_acc.key);
+    }
+    multisig::check_admin_signer(program_id, admin_acc)?;
+    if config_untagged && account_acc.key == config_acc.key {
+        return Ok(());
+    }
+
+    assert_owned_by(account_acc, program_id)?;
+    let tagged = tagged_types()
+        .into_iter()
+        .find(|tagged| tagged.discriminator == discriminator)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if account_acc.data_len() != tagged.len - HEADER_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    retag_account(account_acc, payer_acc, system_program_acc, &tagged)?;
+    msg!("Tagged account {}", account_acc.key);
+    Ok(())
+}
+
+/// Migrates an account of layout `version` to the current version of its
+/// type, found by its tag, growing it to the current length, signed by the
+/// admin. Accounts tagged before versions were introduced are version 0. The
+/// program config is migrated first when it is still unversioned, since the
+/// admin is read from it; this runs ungated for the same reason.
+pub fn migrate_account(program_id: &Pubkey, accounts: &[AccountInfo], version: u8) -> ProgramResult {
+    let TagLegacyAccounts { account_acc, admin_acc, config_acc, payer_acc, system_program_acc } = TagLegacyAccounts::try_from(accounts)?;
+    let config_unversioned = config_acc.data_len() == ProgramConfig::LEN - 1;
+    if config_unversioned {
+        assert_owned_by(config_acc, program_id)?;
+        assert_pda(config_acc, config::config_address(program_id))?;
+        migrate(config_acc, payer_acc, system_program_acc, &tagged::<ProgramConfig>(), 0)?;
+        msg!("Migrated program config {}", config_acc.key);
+    }
+    multisig::check_admin_signer(program_id, admin_acc)?;
+    if config_unversioned && account_acc.key == config_acc.key {
+        return Ok(());
+    }
+
+    assert_owned_by(account_acc, program_id)?;
+    let tagged = {
+        let data = account_acc.try_borrow_data()?;
+        let discriminator = data.get(..DISCRIMINATOR_LEN).ok_or(ProgramError::InvalidAccountData)?;
+        tagged_types()
+            .into_iter()
+            .find(|tagged| tagged.discriminator == discriminator)
+            .ok_or(GgtError::AccountDiscriminatorMismatch)?
+    };
+    migrate(account_acc, payer_acc, system_program_acc, &tagged, version)?;
+    msg!("Migrated account {} from version {} to {}", account_acc.key, version, tagged.version);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::hash::hashv;
+    use std::collections::HashSet;
+
+    #[test]
+    fn discriminators_are_anchor_account_hashes() {
+        let mut seen = HashSet::new();
+        for tagged in tagged_types() {
+            assert!(seen.insert(tagged.discriminator), "duplicate discriminator {:?}", tagged.discriminator);
+        }
+        fn anchor<T: Discriminator>(name: &str) {
+            assert_eq!(T::DISCRIMINATOR[..], hashv(&[b"account:", name.as_bytes()]).to_bytes()[..DISCRIMINATOR_LEN], "{name}");
+        }
+        anchor::<ProgramConfig>("ProgramConfig");
+        anchor::<Proposal>("Proposal");
+        anchor::<Vote>("Vote");
+        anchor::<ProposalCounter>("ProposalCounter");
+        anchor::<Invoice>("Invoice");
+        anchor::<Stake>("Stake");
+        anchor::<CoStakingPool>("CoStakingPool");
+        anchor::<FastMint>("FastMint");
+        anchor::<WrappedAsset>("WrappedAsset");
+        anchor::<OracleOperator>("OracleOperator");
+    }
+
+    #[test]
+    fn unpack_rejects_another_types_tag_and_retag_restores_legacy_data() {
+        let mut invoice_data = vec![0u8; Invoice::LEN];
+        write_discriminator::<Invoice>(&mut invoice_data);
+        assert_eq!(
+            read_discriminator::<Vote>(&invoice_data[..Vote::LEN]).err(),
+            Some(GgtError::AccountDiscriminatorMismatch.into())
+        );
+        assert!(read_discriminator::<Invoice>(&invoice_data).is_ok());
+        assert!(read_discriminator::<Invoice>(&[0u8; Invoice::LEN]).is_ok());
+
+        let stake = Stake { amount: 500, lock_until: 42, is_initialized: true, ..Stake::default() };
+        let mut tagged_data = vec![0u8; Stake::LEN];
+        stake.pack_into_slice(&mut tagged_data);
+        let mut legacy_data = tagged_data[HEADER_LEN..].to_vec();
+        assert_eq!(Stake::unpack(&legacy_data).err(), Some(ProgramError::InvalidAccountData));
+
+        legacy_data.resize(Stake::LEN, 0xFF);
+        retag(&mut legacy_data, Stake::DISCRIMINATOR);
+        assert_eq!(legacy_data, tagged_data);
+        assert_eq!(Stake::unpack(&legacy_data).unwrap().amount, 500);
+    }
+
+    #[test]
+    fn unpack_checks_the_version_and_insert_versions_migrates_unversioned_data() {
+        let stake = Stake { amount: 500, lock_until: 42, is_initialized: true, ..Stake::default() };
+        let mut data = vec![0u8; Stake::LEN];
+        stake.pack_into_slice(&mut data);
+        assert_eq!(data[DISCRIMINATOR_LEN], Stake::VERSION);
+        for version in [0, Stake::VERSION + 1] {
+            data[DISCRIMINATOR_LEN] = version;
+            assert_eq!(Stake::unpack(&data).err(), Some(GgtError::UnsupportedAccountVersion.into()));
+        }
+
+        let co_stake = CoStake { ggt_amount: 7, partner_amount: 9, weight: stake };
+        let mut versioned = vec![0u8; CoStake::LEN];
+        co_stake.pack_into_slice(&mut versioned);
+        // Before versions, both headers were the bare tag.
+        let nested_tag_end = HEADER_LEN + 16 + DISCRIMINATOR_LEN;
+        let mut unversioned = [&versioned[..DISCRIMINATOR_LEN], &versioned[HEADER_LEN..nested_tag_end], &versioned[nested_tag_end + 1..]].concat();
+        assert_eq!(unversioned.len(), CoStake::LEN - 2);
+
+        unversioned.resize(CoStake::LEN, 0);
+        insert_versions(&mut unversioned, Some(16));
+        assert_eq!(unversioned, versioned);
+        let migrated = CoStake::unpack_from_slice(&unversioned).unwrap();
+        assert_eq!((migrated.ggt_amount, migrated.partner_amount, migrated.weight.amount), (7, 9, 500));
+    }
+}
@@ -0,0 +1,627 @@
+//! Disputes over consultant engagements. Either party to an open engagement
+//! may open a dispute by posting a bond, a share of the escrow set by
+//! governance, into the engagement vault; the escrow is frozen from then on.
+//! A member of the governance-appointed arbitration council rules by naming
+//! the consultant's share of the escrow, and the client receives the rest.
+//! The party the ruling goes against loses if it opened the dispute: a
+//! governance-set share of its bond goes to the fee vault and the remainder is
+//! returned. An even split has no losing side.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use crate::engagement::{self, EngagementStatus, ENGAGEMENT_SEED};
+use crate::{
+    config::{self, AuthorityRole},
+    error::GgtError,
+    reputation, token_program, TokenContract,
+};
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer, assert_token_account_mint};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const ARBITRATION_CONFIG_SEED: &[u8] = b"arbitration_config";
+pub const DISPUTE_SEED: &[u8] = b"dispute";
+pub const MAX_ARBITERS: usize = 10;
+
+pub fn arbitration_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ARBITRATION_CONFIG_SEED], program_id)
+}
+
+/// Dispute over the engagement at `engagement`.
+pub fn dispute_address(program_id: &Pubkey, engagement: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[DISPUTE_SEED, engagement.as_ref()], program_id)
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ArbitrationConfig {
+    /// Signers allowed to resolve disputes.
+    pub council: Vec<Pubkey>,
+    /// Bond the disputing party posts, as a share of the escrow in basis points.
+    pub dispute_bond_bps: u16,
+    /// Share of a losing opener's bond forfeited to the fee vault, in basis points.
+    pub loser_slash_bps: u16,
+    pub is_initialized: bool,
+}
+
+impl Sealed for ArbitrationConfig {}
+
+impl IsInitialized for ArbitrationConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for ArbitrationConfig {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [141, 34, 18, 130, 195, 51, 170, 66];
+}
+
+impl Pack for ArbitrationConfig {
+    const LEN: usize = 335; // discriminator (8) + version (1) + count (1) + Pubkey (32) * MAX_ARBITERS + u16 (2) * 2 + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        let mut cursor = 0;
+        dst[cursor] = self.council.len() as u8;
+        cursor += 1;
+        for slot in 0..MAX_ARBITERS {
+            let key = self.council.get(slot).copied().unwrap_or_default();
+            dst[cursor..cursor + 32].copy_from_slice(key.as_ref());
+            cursor += 32;
+        }
+        dst[cursor..cursor + 2].copy_from_slice(&self.dispute_bond_bps.to_le_bytes());
+        dst[cursor + 2..cursor + 4].copy_from_slice(&self.loser_slash_bps.to_le_bytes());
+        dst[cursor + 4] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let count = src[0] as usize;
+        if count > MAX_ARBITERS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let council = (0..count)
+            .map(|slot| Pubkey::new_from_array(src[1 + slot * 32..33 + slot * 32].try_into().unwrap()))
+            .collect();
+        let cursor = 1 + MAX_ARBITERS * 32;
+        Ok(ArbitrationConfig {
+            council,
+            dispute_bond_bps: u16::from_le_bytes(src[cursor..cursor + 2].try_into().unwrap()),
+            loser_slash_bps: u16::from_le_bytes(src[cursor + 2..cursor + 4].try_into().unwrap()),
+            is_initialized: src[cursor + 4] != 0,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dispute {
+    pub engagement: Pubkey,
+    /// The client or consultant who opened the dispute and posted the bond.
+    pub opened_by: Pubkey,
+    /// Held in the engagement vault alongside the escrow.
+    pub bond: u64,
+    /// Hash of the off-chain statement of the complaint.
+    pub reason_hash: [u8; 32],
+    pub opened_at: i64,
+    /// The consultant's share of the escrow the council ruled, in basis points.
+    pub consultant_bps: u16,
+    pub resolved: bool,
+    pub is_initialized: bool,
+}
+
+impl Sealed for Dispute {}
+
+impl IsInitialized for Dispute {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for Dispute {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [36, 49, 241, 67, 40, 36, 241, 74];
+}
+
+impl Pack for Dispute {
+    const LEN: usize = 125; // discriminator (8) + version (1) + Pubkey (32) * 2 + u64 (8) + [u8; 32] + i64 (8) + u16 (2) + bool (1) * 2
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..32].copy_from_slice(self.engagement.as_ref());
+        dst[32..64].copy_from_slice(self.opened_by.as_ref());
+        dst[64..72].copy_from_slice(&self.bond.to_le_bytes());
+        dst[72..104].copy_from_slice(&self.reason_hash);
+        dst[104..112].copy_from_slice(&self.opened_at.to_le_bytes());
+        dst[112..114].copy_from_slice(&self.consultant_bps.to_le_bytes());
+        dst[114] = self.resolved as u8;
+        dst[115] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(Dispute {
+            engagement: Pubkey::new_from_array(src[0..32].try_into().unwrap()),
+            opened_by: Pubkey::new_from_array(src[32..64].try_into().unwrap()),
+            bond: u64::from_le_bytes(src[64..72].try_into().unwrap()),
+            reason_hash: src[72..104].try_into().unwrap(),
+            opened_at: i64::from_le_bytes(src[104..112].try_into().unwrap()),
+            consultant_bps: u16::from_le_bytes(src[112..114].try_into().unwrap()),
+            resolved: src[114] != 0,
+            is_initialized: src[115] != 0,
+        })
+    }
+}
+
+fn load_arbitration_config(program_id: &Pubkey, config_acc: &AccountInfo) -> Result<ArbitrationConfig, ProgramError> {
+    assert_owned_by(config_acc, program_id)?;
+    assert_pda(config_acc, arbitration_config_address(program_id))?;
+    ArbitrationConfig::unpack(&config_acc.try_borrow_data()?)
+}
+
+/// The governance signer follows `config_acc`, then the program config it is
+/// checked against; a separate payer funds the config on the first call.
+pub struct SetArbitrationCouncilAccounts<'a, 'info> {
+    pub config_acc: &'a AccountInfo<'info>,
+    pub payer_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub config_bump: u8,
+}
+
+impl<'a, 'info> SetArbitrationCouncilAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let config_acc = next_account_info(account_info_iter)?;
+        let governance_acc = next_account_info(account_info_iter)?;
+        let program_config_acc = next_account_info(account_info_iter)?;
+        let payer_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        config::check_authority(program_id, program_config_acc, &[AuthorityRole::Governance], governance_acc)?;
+        assert_signer(payer_acc)?;
+        let config_bump = assert_pda(config_acc, arbitration_config_address(program_id))?;
+        Ok(Self { config_acc, payer_acc, system_program_acc, config_bump })
+    }
+}
+
+/// The opener is the client or the consultant and pays for `dispute_acc`;
+/// the bond comes from a token account it owns.
+pub struct OpenDisputeAccounts<'a, 'info> {
+    pub engagement_acc: &'a AccountInfo<'info>,
+    pub dispute_acc: &'a AccountInfo<'info>,
+    pub opener_acc: &'a AccountInfo<'info>,
+    pub client_acc: &'a AccountInfo<'info>,
+    pub consultant_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub opener_token_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub config: ArbitrationConfig,
+}
+
+impl<'a, 'info> OpenDisputeAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let engagement_acc = next_account_info(account_info_iter)?;
+        let dispute_acc = next_account_info(account_info_iter)?;
+        let opener_acc = next_account_info(account_info_iter)?;
+        let client_acc = next_account_info(account_info_iter)?;
+        let consultant_acc = next_account_info(account_info_iter)?;
+        let vault_acc = next_account_info(account_info_iter)?;
+        let opener_token_acc = next_account_info(account_info_iter)?;
+        let config_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(opener_acc)?;
+        if opener_acc.key != client_acc.key && opener_acc.key != consultant_acc.key {
+            msg!("Only the client or the consultant can dispute an engagement");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let config = load_arbitration_config(program_id, config_acc)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        Ok(Self {
+            engagement_acc,
+            dispute_acc,
+            opener_acc,
+            client_acc,
+            consultant_acc,
+            vault_acc,
+            opener_token_acc,
+            mint_acc,
+            token_program_acc,
+            system_program_acc,
+            config,
+        })
+    }
+}
+
+/// An arbiter signs. Each party's share goes to a token account it owns. The
+/// arbiter pays for either party's reputation account if it does not exist yet.
+pub struct ResolveDisputeAccounts<'a, 'info> {
+    pub engagement_acc: &'a AccountInfo<'info>,
+    pub dispute_acc: &'a AccountInfo<'info>,
+    pub arbiter_acc: &'a AccountInfo<'info>,
+    pub client_acc: &'a AccountInfo<'info>,
+    pub consultant_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub client_token_acc: &'a AccountInfo<'info>,
+    pub consultant_token_acc: &'a AccountInfo<'info>,
+    pub fee_vault_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub client_reputation_acc: &'a AccountInfo<'info>,
+    pub consultant_reputation_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub config: ArbitrationConfig,
+}
+
+impl<'a, 'info> ResolveDisputeAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let engagement_acc = next_account_info(account_info_iter)?;
+        let dispute_acc = next_account_info(account_info_iter)?;
+        let arbiter_acc = next_account_info(account_info_iter)?;
+        let client_acc = next_account_info(account_info_iter)?;
+        let consultant_acc = next_account_info(account_info_iter)?;
+        let vault_acc = next_account_info(account_info_iter)?;
+        let client_token_acc = next_account_info(account_info_iter)?;
+        let consultant_token_acc = next_account_info(account_info_iter)?;
+        let fee_vault_acc = next_account_info(account_info_iter)?;
+        let config_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let client_reputation_acc = next_account_info(account_info_iter)?;
+        let consultant_reputation_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(arbiter_acc)?;
+        let config = load_arbitration_config(program_id, config_acc)?;
+        if !config.council.contains(arbiter_acc.key) {
+            return Err(GgtError::NotArbitrator.into());
+        }
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        if assert_token_account_mint(client_token_acc, mint_acc.key)?.owner != *client_acc.key
+            || assert_token_account_mint(consultant_token_acc, mint_acc.key)?.owner != *consultant_acc.key
+        {
+            return Err(ProgramError::IllegalOwner);
+        }
+        Ok(Self {
+            engagement_acc,
+            dispute_acc,
+            arbiter_acc,
+            client_acc,
+            consultant_acc,
+            vault_acc,
+            client_token_acc,
+            consultant_token_acc,
+            fee_vault_acc,
+            mint_acc,
+            token_program_acc,
+            client_reputation_acc,
+            consultant_reputation_acc,
+            system_program_acc,
+            config,
+        })
+    }
+}
+
+/// Replaces the arbitration council and the bond terms. Governance only.
+pub fn set_arbitration_council(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    council: Vec<Pubkey>,
+    dispute_bond_bps: u16,
+    loser_slash_bps: u16,
+) -> ProgramResult {
+    let SetArbitrationCouncilAccounts { config_acc, payer_acc, system_program_acc, config_bump } =
+        SetArbitrationCouncilAccounts::try_from(program_id, accounts)?;
+    if council.len() > MAX_ARBITERS || dispute_bond_bps > 10_000 || loser_slash_bps > 10_000 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if config_acc.lamports() == 0 {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_acc.key,
+                config_acc.key,
+                Rent::get()?.minimum_balance(ArbitrationConfig::LEN),
+                ArbitrationConfig::LEN as u64,
+                program_id,
+            ),
+            &[payer_acc.clone(), config_acc.clone(), system_program_acc.clone()],
+            &[&[ARBITRATION_CONFIG_SEED, &[config_bump]]],
+        )?;
+    } else {
+        assert_owned_by(config_acc, program_id)?;
+    }
+    let count = council.len();
+    let config = ArbitrationConfig { council, dispute_bond_bps, loser_slash_bps, is_initialized: true };
+    pack_state(&config, config_acc)?;
+    msg!("Arbitration council set to {} members", count);
+    Ok(())
+}
+
+/// Freezes an open engagement's escrow pending arbitration. The opener posts
+/// the configured bond into the engagement vault.
+pub fn open_dispute(program_id: &Pubkey, accounts: &[AccountInfo], reason_hash: [u8; 32]) -> ProgramResult {
+    let OpenDisputeAccounts {
+        engagement_acc,
+        dispute_acc,
+        opener_acc,
+        client_acc,
+        consultant_acc,
+        vault_acc,
+        opener_token_acc,
+        mint_acc,
+        token_program_acc,
+        system_program_acc,
+        config,
+    } = OpenDisputeAccounts::try_from(program_id, accounts)?;
+    let (mut engagement, _) = engagement::load_open_engagement(program_id, engagement_acc, client_acc.key, consultant_acc.key, vault_acc)?;
+    let dispute_bump = assert_pda(dispute_acc, dispute_address(program_id, engagement_acc.key))?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            opener_acc.key,
+            dispute_acc.key,
+            Rent::get()?.minimum_balance(Dispute::LEN),
+            Dispute::LEN as u64,
+            program_id,
+        ),
+        &[opener_acc.clone(), dispute_acc.clone(), system_program_acc.clone()],
+        &[&[DISPUTE_SEED, engagement_acc.key.as_ref(), &[dispute_bump]]],
+    )?;
+    let bond = (engagement.remaining() as u128 * config.dispute_bond_bps as u128 / 10_000) as u64;
+    if bond > 0 {
+        token_program::transfer(token_program_acc, opener_token_acc, mint_acc, vault_acc, opener_acc, bond, &[])?;
+    }
+
+    let dispute = Dispute {
+        engagement: *engagement_acc.key,
+        opened_by: *opener_acc.key,
+        bond,
+        reason_hash,
+        opened_at: Clock::get()?.unix_timestamp,
+        consultant_bps: 0,
+        resolved: false,
+        is_initialized: true,
+    };
+    pack_state(&dispute, dispute_acc)?;
+    engagement.status = EngagementStatus::Disputed;
+    let engagement_id = engagement.engagement_id;
+    pack_state(&engagement, engagement_acc)?;
+    msg!("Engagement {} disputed by {} with a bond of {}", engagement_id, opener_acc.key, bond);
+    Ok(())
+}
+
+/// Rules on a dispute: the consultant receives `consultant_bps` of the escrow
+/// and the client the rest. An opener the ruling goes against forfeits the
+/// configured share of its bond to the fee vault. The losing party, whether or
+/// not it opened the dispute, has a lost dispute added to its reputation.
+pub fn resolve_dispute(program_id: &Pubkey, accounts: &[AccountInfo], consultant_bps: u16) -> ProgramResult {
+    let ResolveDisputeAccounts {
+        engagement_acc,
+        dispute_acc,
+        arbiter_acc,
+        client_acc,
+        consultant_acc,
+        vault_acc,
+        client_token_acc,
+        consultant_token_acc,
+        fee_vault_acc,
+        mint_acc,
+        token_program_acc,
+        client_reputation_acc,
+        consultant_reputation_acc,
+        system_program_acc,
+        config,
+    } = ResolveDisputeAccounts::try_from(program_id, accounts)?;
+    if consultant_bps > 10_000 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (mut engagement, bump) = engagement::load_engagement(program_id, engagement_acc, client_acc.key, consultant_acc.key, vault_acc)?;
+    if engagement.status != EngagementStatus::Disputed {
+        return Err(GgtError::EngagementNotDisputed.into());
+    }
+    assert_owned_by(dispute_acc, program_id)?;
+    assert_pda(dispute_acc, dispute_address(program_id, engagement_acc.key))?;
+    let mut dispute = Dispute::unpack(&dispute_acc.try_borrow_data()?)?;
+
+    let escrow = engagement.remaining();
+    let consultant_share = (escrow as u128 * consultant_bps as u128 / 10_000) as u64;
+    let client_share = escrow - consultant_share;
+    let loser = match consultant_bps.cmp(&5_000) {
+        std::cmp::Ordering::Less => Some(engagement.consultant),
+        std::cmp::Ordering::Greater => Some(engagement.client),
+        std::cmp::Ordering::Equal => None,
+    };
+    let slashed = if loser == Some(dispute.opened_by) {
+        (dispute.bond as u128 * config.loser_slash_bps as u128 / 10_000) as u64
+    } else {
+        0
+    };
+    if slashed > 0 {
+        TokenContract::check_fee_vault(program_id, fee_vault_acc, mint_acc.key)?;
+    }
+    let refund = dispute.bond - slashed;
+    let (client_total, consultant_total) = if dispute.opened_by == engagement.client {
+        (client_share.checked_add(refund).ok_or(GgtError::MathOverflow)?, consultant_share)
+    } else {
+        (client_share, consultant_share.checked_add(refund).ok_or(GgtError::MathOverflow)?)
+    };
+
+    engagement.status = EngagementStatus::Resolved;
+    let (engagement_id, amount) = (engagement.engagement_id, engagement.amount);
+    pack_state(&engagement, engagement_acc)?;
+    for (reputation_acc, wallet) in [(client_reputation_acc, client_acc.key), (consultant_reputation_acc, consultant_acc.key)] {
+        reputation::update_reputation(program_id, reputation_acc, wallet, arbiter_acc, system_program_acc, |reputation, now| {
+            if loser == Some(*wallet) {
+                reputation.record_lost_dispute(amount, now)
+            } else {
+                reputation.record_settlement(amount, now)
+            }
+        })?;
+    }
+    dispute.consultant_bps = consultant_bps;
+    dispute.resolved = true;
+    pack_state(&dispute, dispute_acc)?;
+
+    let id_bytes = engagement_id.to_le_bytes();
+    let signer_seeds: &[&[u8]] = &[ENGAGEMENT_SEED, client_acc.key.as_ref(), consultant_acc.key.as_ref(), &id_bytes, &[bump]];
+    for (destination, value) in [(client_token_acc, client_total), (consultant_token_acc, consultant_total), (fee_vault_acc, slashed)] {
+        if value == 0 {
+            continue;
+        }
+        token_program::transfer(token_program_acc, vault_acc, mint_acc, destination, engagement_acc, value, &[signer_seeds])?;
+    }
+    msg!("Resolved engagement {}: consultant {}, client {}, bond slashed {}", engagement_id, consultant_total, client_total, slashed);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engagement::{cancel_engagement, engagement_address, Engagement};
+    use crate::reputation::{reputation_address, Reputation};
+    use crate::test_utils::{account_info, fund_rent_exempt, program_config, token_account_data, SimulatedBank};
+    use crate::GOVERNANCE_PUBKEY;
+    use spl_token::state::Mint;
+
+    #[test]
+    fn test_dispute_freezes_escrow_and_the_ruling_slashes_a_losing_opener() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (token_program_id, system_program_id) = (spl_token::id(), Pubkey::default());
+        let (client_key, consultant_key, arbiter_key, mint_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (client_token_key, consultant_token_key, vault_key, fee_vault_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (engagement_key, _) = engagement_address(&program_id, &client_key, &consultant_key, 1);
+        let (dispute_key, _) = dispute_address(&program_id, &engagement_key);
+        let (arbitration_key, _) = arbitration_config_address(&program_id);
+        let (fee_authority, _) = TokenContract::fee_vault_authority(&program_id);
+
+        let mut engagement_data = vec![0u8; Engagement::LEN];
+        let engagement = Engagement {
+            client: client_key,
+            consultant: consultant_key,
+            engagement_id: 1,
+            vault: vault_key,
+            amount: 400,
+            opened_at: 0,
+            deadline: 86_400,
+            released: 0,
+            milestones: vec![400],
+            milestones_approved: 0,
+            status: EngagementStatus::Open,
+            is_initialized: true,
+        };
+        Engagement::pack(engagement, &mut engagement_data).unwrap();
+        let mut client_token_data = token_account_data(&mint_key, &client_key, 0);
+        let mut consultant_token_data = token_account_data(&mint_key, &consultant_key, 100);
+        let mut vault_data = token_account_data(&mint_key, &engagement_key, 400);
+        let mut fee_vault_data = token_account_data(&mint_key, &fee_authority, 0);
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { decimals: 9, is_initialized: true, ..Mint::default() }, &mut mint_data).unwrap();
+        let (mut dispute_data, mut arbitration_data) = (vec![0u8; Dispute::LEN], vec![0u8; ArbitrationConfig::LEN]);
+        let (program_config_key, mut program_config_data) = program_config(&program_id);
+
+        let mut lamports = [0u64, 0, 0, 10_000_000, 0, 10_000_000, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10, l11, l12, l13, l14] = &mut lamports;
+        let (mut d3, mut d4, mut d5, mut d12, mut d13, mut d14) = (vec![], vec![], vec![], vec![], vec![], vec![]);
+        let engagement_acc = account_info(&engagement_key, false, true, l0, &mut engagement_data, &program_id);
+        let dispute_acc = account_info(&dispute_key, false, true, l1, &mut dispute_data, &program_id);
+        let arbitration_acc = account_info(&arbitration_key, false, true, l2, &mut arbitration_data, &program_id);
+        fund_rent_exempt(&[&engagement_acc, &arbitration_acc]);
+        let consultant_acc = account_info(&consultant_key, true, true, l3, &mut d3, &system_program_id);
+        let client_acc = account_info(&client_key, false, false, l4, &mut d4, &system_program_id);
+        let arbiter_acc = account_info(&arbiter_key, true, true, l5, &mut d5, &system_program_id);
+        let client_token_acc = account_info(&client_token_key, false, true, l6, &mut client_token_data, &token_program_id);
+        let consultant_token_acc = account_info(&consultant_token_key, false, true, l7, &mut consultant_token_data, &token_program_id);
+        let vault_acc = account_info(&vault_key, false, true, l8, &mut vault_data, &token_program_id);
+        let fee_vault_acc = account_info(&fee_vault_key, false, true, l9, &mut fee_vault_data, &token_program_id);
+        let mint_acc = account_info(&mint_key, false, false, l10, &mut mint_data, &token_program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, l11, &mut program_config_data, &program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, l12, &mut d12, &program_id);
+        let system_program_acc = account_info(&system_program_id, false, false, l13, &mut d13, &system_program_id);
+        let governance_acc = account_info(&GOVERNANCE_PUBKEY, true, false, l14, &mut d14, &system_program_id);
+        let (client_reputation_key, _) = reputation_address(&program_id, &client_key);
+        let (consultant_reputation_key, _) = reputation_address(&program_id, &consultant_key);
+        let (mut client_reputation_data, mut consultant_reputation_data) = (vec![0u8; Reputation::LEN], vec![0u8; Reputation::LEN]);
+        let (mut l15, mut l16) = (0u64, 0u64);
+        let client_reputation_acc = account_info(&client_reputation_key, false, true, &mut l15, &mut client_reputation_data, &program_id);
+        let consultant_reputation_acc =
+            account_info(&consultant_reputation_key, false, true, &mut l16, &mut consultant_reputation_data, &program_id);
+        bank.set_balance(&consultant_token_key, 100);
+        bank.set_balance(&vault_key, 400);
+
+        // A 10% bond, half of which a losing opener forfeits.
+        let council = [arbitration_acc.clone(), governance_acc, program_config_acc, consultant_acc.clone(), system_program_acc.clone()];
+        set_arbitration_council(&program_id, &council, vec![arbiter_key], 1_000, 5_000).unwrap();
+
+        let open = [
+            engagement_acc.clone(),
+            dispute_acc.clone(),
+            consultant_acc.clone(),
+            client_acc.clone(),
+            consultant_acc.clone(),
+            vault_acc.clone(),
+            consultant_token_acc.clone(),
+            arbitration_acc.clone(),
+            mint_acc.clone(),
+            token_program_acc.clone(),
+            system_program_acc.clone(),
+        ];
+        open_dispute(&program_id, &open, [9; 32]).unwrap();
+        assert_eq!((bank.balance(&consultant_token_key), bank.balance(&vault_key)), (60, 440));
+
+        let cancel = [
+            engagement_acc.clone(),
+            client_acc.clone(),
+            consultant_acc.clone(),
+            vault_acc.clone(),
+            client_token_acc.clone(),
+            mint_acc.clone(),
+            token_program_acc.clone(),
+        ];
+        assert_eq!(cancel_engagement(&program_id, &cancel), Err(GgtError::EngagementNotOpen.into()));
+
+        let mut resolve = vec![
+            engagement_acc.clone(),
+            dispute_acc.clone(),
+            consultant_acc.clone(),
+            client_acc,
+            consultant_acc,
+            vault_acc,
+            client_token_acc,
+            consultant_token_acc,
+            fee_vault_acc,
+            arbitration_acc,
+            mint_acc,
+            token_program_acc,
+            client_reputation_acc.clone(),
+            consultant_reputation_acc.clone(),
+            system_program_acc,
+        ];
+        assert_eq!(resolve_dispute(&program_id, &resolve, 2_500), Err(GgtError::NotArbitrator.into()));
+        resolve[2] = arbiter_acc;
+        resolve_dispute(&program_id, &resolve, 2_500).unwrap();
+        // The consultant opened and lost: it gets 100 of the escrow and 20 of its bond back.
+        assert_eq!(
+            (bank.balance(&client_token_key), bank.balance(&consultant_token_key), bank.balance(&fee_vault_key), bank.balance(&vault_key)),
+            (300, 180, 20, 0)
+        );
+        assert_eq!(Engagement::unpack(&engagement_acc.try_borrow_data().unwrap()).unwrap().status, EngagementStatus::Resolved);
+        let reputation = |acc: &AccountInfo| Reputation::unpack(&acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((reputation(&consultant_reputation_acc).disputes_lost, reputation(&client_reputation_acc).disputes_lost), (1, 0));
+        assert_eq!(resolve_dispute(&program_id, &resolve, 2_500), Err(GgtError::EngagementNotDisputed.into()));
+    }
+}
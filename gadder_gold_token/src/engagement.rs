@@ -0,0 +1,691 @@
+//! Escrowed payment for work agreed between a client and a registered
+//! consultant. The client deposits GGT into a vault owned by the engagement
+//! PDA, split into milestones the client approves one at a time, each paying
+//! its tranche to the consultant minus the protocol fee from the program
+//! config. When both parties sign off the vault pays out whatever is left.
+//! Before that, the consultant may cancel
+//! at any time and the client once the engagement deadline has passed, both
+//! refunding the client what has not been released. Either party may instead open a dispute,
+//! which freezes the escrow until the arbitration council rules on it.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use crate::{ai_contract, config, error::GgtError, events, reputation, token_program, TokenContract};
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer, assert_token_account_mint};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const ENGAGEMENT_SEED: &[u8] = b"engagement";
+pub const MAX_ENGAGEMENT_SECONDS: i64 = 365 * 86_400;
+pub const MAX_ENGAGEMENT_MILESTONES: usize = 8;
+
+/// Engagement `engagement_id` between `client` and `consultant`.
+pub fn engagement_address(program_id: &Pubkey, client: &Pubkey, consultant: &Pubkey, engagement_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ENGAGEMENT_SEED, client.as_ref(), consultant.as_ref(), &engagement_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum EngagementStatus {
+    Open,
+    Completed,
+    Cancelled,
+    /// Frozen until the arbitration council resolves the dispute.
+    Disputed,
+    Resolved,
+}
+
+impl EngagementStatus {
+    fn from_u8(value: u8) -> Result<Self, ProgramError> {
+        match value {
+            0 => Ok(EngagementStatus::Open),
+            1 => Ok(EngagementStatus::Completed),
+            2 => Ok(EngagementStatus::Cancelled),
+            3 => Ok(EngagementStatus::Disputed),
+            4 => Ok(EngagementStatus::Resolved),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct Engagement {
+    pub client: Pubkey,
+    pub consultant: Pubkey,
+    pub engagement_id: u64,
+    /// Token account owned by the engagement PDA holding the deposit.
+    pub vault: Pubkey,
+    /// Sum of `milestones`.
+    pub amount: u64,
+    pub opened_at: i64,
+    /// From here on the client may cancel without the consultant.
+    pub deadline: i64,
+    /// Escrow already paid out through approved milestones, fees included.
+    pub released: u64,
+    pub milestones: Vec<u64>,
+    /// Milestones approved so far; they are approved in order.
+    pub milestones_approved: u8,
+    pub status: EngagementStatus,
+    pub is_initialized: bool,
+}
+
+impl Engagement {
+    /// Escrow still held in the vault for this engagement.
+    pub fn remaining(&self) -> u64 {
+        self.amount.saturating_sub(self.released)
+    }
+}
+
+impl Sealed for Engagement {}
+
+impl IsInitialized for Engagement {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for Engagement {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [101, 251, 151, 96, 14, 185, 187, 41];
+}
+
+impl Pack for Engagement {
+    const LEN: usize = 213; // discriminator (8) + version (1) + Pubkey (32) * 3 + u64 (8) * 3 + i64 (8) * 2 + count (1) + u64 (8) * MAX_ENGAGEMENT_MILESTONES + u8 (1) * 2 + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..32].copy_from_slice(self.client.as_ref());
+        dst[32..64].copy_from_slice(self.consultant.as_ref());
+        dst[64..72].copy_from_slice(&self.engagement_id.to_le_bytes());
+        dst[72..104].copy_from_slice(self.vault.as_ref());
+        dst[104..112].copy_from_slice(&self.amount.to_le_bytes());
+        dst[112..120].copy_from_slice(&self.opened_at.to_le_bytes());
+        dst[120..128].copy_from_slice(&self.deadline.to_le_bytes());
+        dst[128..136].copy_from_slice(&self.released.to_le_bytes());
+        dst[136] = self.milestones.len() as u8;
+        for (slot, milestone) in dst[137..201].chunks_exact_mut(8).zip(&self.milestones) {
+            slot.copy_from_slice(&milestone.to_le_bytes());
+        }
+        dst[201] = self.milestones_approved;
+        dst[202] = self.status as u8;
+        dst[203] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let milestone_count = src[136] as usize;
+        if milestone_count > MAX_ENGAGEMENT_MILESTONES {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let milestones = src[137..201].chunks_exact(8).take(milestone_count).map(|slot| u64::from_le_bytes(slot.try_into().unwrap())).collect();
+        Ok(Engagement {
+            client: Pubkey::new_from_array(src[0..32].try_into().unwrap()),
+            consultant: Pubkey::new_from_array(src[32..64].try_into().unwrap()),
+            engagement_id: u64::from_le_bytes(src[64..72].try_into().unwrap()),
+            vault: Pubkey::new_from_array(src[72..104].try_into().unwrap()),
+            amount: u64::from_le_bytes(src[104..112].try_into().unwrap()),
+            opened_at: i64::from_le_bytes(src[112..120].try_into().unwrap()),
+            deadline: i64::from_le_bytes(src[120..128].try_into().unwrap()),
+            released: u64::from_le_bytes(src[128..136].try_into().unwrap()),
+            milestones,
+            milestones_approved: src[201],
+            status: EngagementStatus::from_u8(src[202])?,
+            is_initialized: src[203] != 0,
+        })
+    }
+}
+
+/// Loads the engagement in `engagement_acc` between `client` and
+/// `consultant`, checking that `vault_acc` is its vault, and returns it with
+/// the PDA bump.
+pub(crate) fn load_engagement(
+    program_id: &Pubkey,
+    engagement_acc: &AccountInfo,
+    client: &Pubkey,
+    consultant: &Pubkey,
+    vault_acc: &AccountInfo,
+) -> Result<(Engagement, u8), ProgramError> {
+    assert_owned_by(engagement_acc, program_id)?;
+    let engagement = Engagement::unpack(&engagement_acc.try_borrow_data()?)?;
+    let bump = assert_pda(engagement_acc, engagement_address(program_id, client, consultant, engagement.engagement_id))?;
+    if *vault_acc.key != engagement.vault {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok((engagement, bump))
+}
+
+/// [`load_engagement`] for an engagement that is still open.
+pub(crate) fn load_open_engagement(
+    program_id: &Pubkey,
+    engagement_acc: &AccountInfo,
+    client: &Pubkey,
+    consultant: &Pubkey,
+    vault_acc: &AccountInfo,
+) -> Result<(Engagement, u8), ProgramError> {
+    let (engagement, bump) = load_engagement(program_id, engagement_acc, client, consultant, vault_acc)?;
+    if engagement.status != EngagementStatus::Open {
+        return Err(GgtError::EngagementNotOpen.into());
+    }
+    Ok((engagement, bump))
+}
+
+/// The client signs and pays for `engagement_acc`. `consultant_acc` need not
+/// sign; its registry profile must be active.
+pub struct OpenEngagementAccounts<'a, 'info> {
+    pub engagement_acc: &'a AccountInfo<'info>,
+    pub client_acc: &'a AccountInfo<'info>,
+    pub consultant_acc: &'a AccountInfo<'info>,
+    pub client_token_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> OpenEngagementAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let engagement_acc = next_account_info(account_info_iter)?;
+        let client_acc = next_account_info(account_info_iter)?;
+        let consultant_acc = next_account_info(account_info_iter)?;
+        let profile_acc = next_account_info(account_info_iter)?;
+        let client_token_acc = next_account_info(account_info_iter)?;
+        let vault_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(client_acc)?;
+        ai_contract::load_registered_profile(program_id, profile_acc, consultant_acc.key)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        assert_token_account_mint(vault_acc, mint_acc.key)?;
+        Ok(Self {
+            engagement_acc,
+            client_acc,
+            consultant_acc,
+            client_token_acc,
+            vault_acc,
+            mint_acc,
+            token_program_acc,
+            system_program_acc,
+        })
+    }
+}
+
+/// Both parties sign. The fee vault must be owned by the fee vault PDA. The
+/// client pays for either party's reputation account if it does not exist yet.
+pub struct CompleteEngagementAccounts<'a, 'info> {
+    pub engagement_acc: &'a AccountInfo<'info>,
+    pub client_acc: &'a AccountInfo<'info>,
+    pub consultant_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub consultant_token_acc: &'a AccountInfo<'info>,
+    pub fee_vault_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub config_acc: &'a AccountInfo<'info>,
+    pub client_reputation_acc: &'a AccountInfo<'info>,
+    pub consultant_reputation_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CompleteEngagementAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            engagement_acc: next_account_info(account_info_iter)?,
+            client_acc: next_account_info(account_info_iter)?,
+            consultant_acc: next_account_info(account_info_iter)?,
+            vault_acc: next_account_info(account_info_iter)?,
+            consultant_token_acc: next_account_info(account_info_iter)?,
+            fee_vault_acc: next_account_info(account_info_iter)?,
+            mint_acc: next_account_info(account_info_iter)?,
+            token_program_acc: next_account_info(account_info_iter)?,
+            config_acc: next_account_info(account_info_iter)?,
+            client_reputation_acc: next_account_info(account_info_iter)?,
+            consultant_reputation_acc: next_account_info(account_info_iter)?,
+            system_program_acc: next_account_info(account_info_iter)?,
+        };
+        assert_signer(ctx.client_acc)?;
+        assert_signer(ctx.consultant_acc)?;
+        token_program::check_token_program(ctx.token_program_acc, ctx.mint_acc)?;
+        Ok(ctx)
+    }
+}
+
+/// The client signs. The fee vault must be owned by the fee vault PDA.
+pub struct ApproveMilestoneAccounts<'a, 'info> {
+    pub engagement_acc: &'a AccountInfo<'info>,
+    pub client_acc: &'a AccountInfo<'info>,
+    pub consultant_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub consultant_token_acc: &'a AccountInfo<'info>,
+    pub fee_vault_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub config_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ApproveMilestoneAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            engagement_acc: next_account_info(account_info_iter)?,
+            client_acc: next_account_info(account_info_iter)?,
+            consultant_acc: next_account_info(account_info_iter)?,
+            vault_acc: next_account_info(account_info_iter)?,
+            consultant_token_acc: next_account_info(account_info_iter)?,
+            fee_vault_acc: next_account_info(account_info_iter)?,
+            mint_acc: next_account_info(account_info_iter)?,
+            token_program_acc: next_account_info(account_info_iter)?,
+            config_acc: next_account_info(account_info_iter)?,
+        };
+        assert_signer(ctx.client_acc)?;
+        token_program::check_token_program(ctx.token_program_acc, ctx.mint_acc)?;
+        Ok(ctx)
+    }
+}
+
+/// Either party may sign; `cancel_engagement` decides whether that suffices.
+/// The refund goes to a token account owned by the client.
+pub struct CancelEngagementAccounts<'a, 'info> {
+    pub engagement_acc: &'a AccountInfo<'info>,
+    pub client_acc: &'a AccountInfo<'info>,
+    pub consultant_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub client_token_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CancelEngagementAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            engagement_acc: next_account_info(account_info_iter)?,
+            client_acc: next_account_info(account_info_iter)?,
+            consultant_acc: next_account_info(account_info_iter)?,
+            vault_acc: next_account_info(account_info_iter)?,
+            client_token_acc: next_account_info(account_info_iter)?,
+            mint_acc: next_account_info(account_info_iter)?,
+            token_program_acc: next_account_info(account_info_iter)?,
+        };
+        if !ctx.client_acc.is_signer && !ctx.consultant_acc.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        token_program::check_token_program(ctx.token_program_acc, ctx.mint_acc)?;
+        if assert_token_account_mint(ctx.client_token_acc, ctx.mint_acc.key)?.owner != *ctx.client_acc.key {
+            return Err(ProgramError::IllegalOwner);
+        }
+        Ok(ctx)
+    }
+}
+
+/// Pays `amount` out of the engagement vault to the consultant, less the
+/// protocol fee at the configured rate, which goes to the fee vault. Returns
+/// the net payout and the fee.
+#[allow(clippy::too_many_arguments)]
+fn pay_consultant<'info>(
+    program_id: &Pubkey,
+    engagement: &Engagement,
+    bump: u8,
+    engagement_acc: &AccountInfo<'info>,
+    vault_acc: &AccountInfo<'info>,
+    consultant_token_acc: &AccountInfo<'info>,
+    fee_vault_acc: &AccountInfo<'info>,
+    mint_acc: &AccountInfo<'info>,
+    token_program_acc: &AccountInfo<'info>,
+    config_acc: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<(u64, u64), ProgramError> {
+    let fee = config::load_config(program_id, config_acc)?.transfer_fee(amount);
+    if fee > 0 {
+        TokenContract::check_fee_vault(program_id, fee_vault_acc, mint_acc.key)?;
+    }
+    let net = amount.checked_sub(fee).ok_or(GgtError::MathOverflow)?;
+
+    let id_bytes = engagement.engagement_id.to_le_bytes();
+    let signer_seeds: &[&[u8]] = &[ENGAGEMENT_SEED, engagement.client.as_ref(), engagement.consultant.as_ref(), &id_bytes, &[bump]];
+    for (destination, value) in [(consultant_token_acc, net), (fee_vault_acc, fee)] {
+        if value == 0 {
+            continue;
+        }
+        token_program::transfer(token_program_acc, vault_acc, mint_acc, destination, engagement_acc, value, &[signer_seeds])?;
+    }
+    Ok((net, fee))
+}
+
+/// Opens engagement `engagement_id` with `consultant`, moving the sum of
+/// `milestones` from the client into a vault owned by the engagement PDA. The
+/// client may cancel alone once `duration_seconds` have passed.
+pub fn open_engagement(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    engagement_id: u64,
+    milestones: Vec<u64>,
+    duration_seconds: i64,
+) -> ProgramResult {
+    let OpenEngagementAccounts {
+        engagement_acc,
+        client_acc,
+        consultant_acc,
+        client_token_acc,
+        vault_acc,
+        mint_acc,
+        token_program_acc,
+        system_program_acc,
+    } = OpenEngagementAccounts::try_from(program_id, accounts)?;
+    if milestones.is_empty() || milestones.len() > MAX_ENGAGEMENT_MILESTONES || milestones.contains(&0) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if !(1..=MAX_ENGAGEMENT_SECONDS).contains(&duration_seconds) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let amount = milestones.iter().try_fold(0u64, |total, milestone| total.checked_add(*milestone)).ok_or(GgtError::MathOverflow)?;
+    if client_acc.key == consultant_acc.key {
+        msg!("Client and consultant must differ");
+        return Err(ProgramError::InvalidArgument);
+    }
+    let bump = assert_pda(engagement_acc, engagement_address(program_id, client_acc.key, consultant_acc.key, engagement_id))?;
+    if token_program::unpack_account(vault_acc)?.owner != *engagement_acc.key {
+        msg!("Engagement vault must be owned by the engagement PDA");
+        return Err(ProgramError::IllegalOwner);
+    }
+    if engagement_acc.lamports() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            client_acc.key,
+            engagement_acc.key,
+            Rent::get()?.minimum_balance(Engagement::LEN),
+            Engagement::LEN as u64,
+            program_id,
+        ),
+        &[client_acc.clone(), engagement_acc.clone(), system_program_acc.clone()],
+        &[&[ENGAGEMENT_SEED, client_acc.key.as_ref(), consultant_acc.key.as_ref(), &engagement_id.to_le_bytes(), &[bump]]],
+    )?;
+    token_program::transfer(token_program_acc, client_token_acc, mint_acc, vault_acc, client_acc, amount, &[])?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let engagement = Engagement {
+        client: *client_acc.key,
+        consultant: *consultant_acc.key,
+        engagement_id,
+        vault: *vault_acc.key,
+        amount,
+        opened_at: now,
+        deadline: now + duration_seconds,
+        released: 0,
+        milestones: milestones.clone(),
+        milestones_approved: 0,
+        status: EngagementStatus::Open,
+        is_initialized: true,
+    };
+    pack_state(&engagement, engagement_acc)?;
+    events::emit(&events::EngagementOpened {
+        engagement: *engagement_acc.key,
+        client: *client_acc.key,
+        consultant: *consultant_acc.key,
+        engagement_id,
+        milestones,
+    });
+    msg!("Opened engagement {} with {} escrowed for {}", engagement_id, amount, consultant_acc.key);
+    Ok(())
+}
+
+/// The client approves milestone `milestone_index`, the next one not yet
+/// approved, releasing its tranche to the consultant.
+pub fn approve_milestone(program_id: &Pubkey, accounts: &[AccountInfo], milestone_index: u8) -> ProgramResult {
+    let ApproveMilestoneAccounts {
+        engagement_acc,
+        client_acc,
+        consultant_acc,
+        vault_acc,
+        consultant_token_acc,
+        fee_vault_acc,
+        mint_acc,
+        token_program_acc,
+        config_acc,
+    } = ApproveMilestoneAccounts::try_from(accounts)?;
+    let (mut engagement, bump) = load_open_engagement(program_id, engagement_acc, client_acc.key, consultant_acc.key, vault_acc)?;
+    if milestone_index != engagement.milestones_approved {
+        msg!("Milestone {} is next to approve", engagement.milestones_approved);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let tranche = *engagement.milestones.get(milestone_index as usize).ok_or(ProgramError::InvalidArgument)?;
+
+    engagement.milestones_approved += 1;
+    engagement.released = engagement.released.checked_add(tranche).ok_or(GgtError::MathOverflow)?;
+    pack_state(&engagement.clone(), engagement_acc)?;
+    let (net, fee) = pay_consultant(
+        program_id,
+        &engagement,
+        bump,
+        engagement_acc,
+        vault_acc,
+        consultant_token_acc,
+        fee_vault_acc,
+        mint_acc,
+        token_program_acc,
+        config_acc,
+        tranche,
+    )?;
+    events::emit(&events::MilestoneApproved { engagement: *engagement_acc.key, milestone_index, amount: net, fee });
+    msg!("Approved milestone {} of engagement {}: paid {} with fee {}", milestone_index, engagement.engagement_id, net, fee);
+    Ok(())
+}
+
+/// Releases the escrow left after approved milestones to the consultant once
+/// both parties sign off and credits the engagement to both parties'
+/// reputations. The protocol fee at the configured rate goes to the fee vault.
+pub fn complete_engagement(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let CompleteEngagementAccounts {
+        engagement_acc,
+        client_acc,
+        consultant_acc,
+        vault_acc,
+        consultant_token_acc,
+        fee_vault_acc,
+        mint_acc,
+        token_program_acc,
+        config_acc,
+        client_reputation_acc,
+        consultant_reputation_acc,
+        system_program_acc,
+    } = CompleteEngagementAccounts::try_from(accounts)?;
+    let (mut engagement, bump) = load_open_engagement(program_id, engagement_acc, client_acc.key, consultant_acc.key, vault_acc)?;
+
+    let remainder = engagement.remaining();
+    engagement.released = engagement.amount;
+    engagement.status = EngagementStatus::Completed;
+    pack_state(&engagement.clone(), engagement_acc)?;
+    for (reputation_acc, wallet) in [(client_reputation_acc, client_acc.key), (consultant_reputation_acc, consultant_acc.key)] {
+        reputation::update_reputation(program_id, reputation_acc, wallet, client_acc, system_program_acc, |reputation, now| {
+            reputation.record_completion(engagement.amount, now)
+        })?;
+    }
+
+    let (net, fee) = pay_consultant(
+        program_id,
+        &engagement,
+        bump,
+        engagement_acc,
+        vault_acc,
+        consultant_token_acc,
+        fee_vault_acc,
+        mint_acc,
+        token_program_acc,
+        config_acc,
+        remainder,
+    )?;
+    events::emit(&events::EngagementCompleted { engagement: *engagement_acc.key, amount: net, fee });
+    msg!("Completed engagement {}: paid {} with fee {}", engagement.engagement_id, net, fee);
+    Ok(())
+}
+
+/// Cancels an open engagement and refunds the client the escrow no approved
+/// milestone has released. The consultant
+/// may cancel at any time; the client alone only once the deadline has passed.
+pub fn cancel_engagement(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let CancelEngagementAccounts { engagement_acc, client_acc, consultant_acc, vault_acc, client_token_acc, mint_acc, token_program_acc } =
+        CancelEngagementAccounts::try_from(accounts)?;
+    let (mut engagement, bump) = load_open_engagement(program_id, engagement_acc, client_acc.key, consultant_acc.key, vault_acc)?;
+    if !consultant_acc.is_signer && Clock::get()?.unix_timestamp < engagement.deadline {
+        return Err(GgtError::EngagementDeadlineNotReached.into());
+    }
+
+    engagement.status = EngagementStatus::Cancelled;
+    let (engagement_id, amount) = (engagement.engagement_id, engagement.remaining());
+    pack_state(&engagement, engagement_acc)?;
+
+    let id_bytes = engagement_id.to_le_bytes();
+    let signer_seeds: &[&[u8]] = &[ENGAGEMENT_SEED, client_acc.key.as_ref(), consultant_acc.key.as_ref(), &id_bytes, &[bump]];
+    token_program::transfer(token_program_acc, vault_acc, mint_acc, client_token_acc, engagement_acc, amount, &[signer_seeds])?;
+    msg!("Cancelled engagement {}, refunded {}", engagement_id, amount);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_contract::{consultant_profile_address, ConsultantProfile};
+    use crate::config::{config_address, ProgramConfig};
+    use crate::reputation::{reputation_address, Reputation};
+    use crate::test_utils::{account_info, token_account_data, SimulatedBank};
+    use spl_token::state::Mint;
+
+    #[test]
+    fn test_engagement_releases_milestones_completes_with_fee_and_cancels_with_refund() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (token_program_id, system_program_id) = (spl_token::id(), Pubkey::default());
+        let (client_key, consultant_key, mint_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (client_token_key, consultant_token_key, vault_key, fee_vault_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (engagement_key, _) = engagement_address(&program_id, &client_key, &consultant_key, 1);
+        let (profile_key, _) = consultant_profile_address(&program_id, &consultant_key);
+        let (fee_authority, _) = TokenContract::fee_vault_authority(&program_id);
+        let (config_key, _) = config_address(&program_id);
+
+        let mut engagement_data = vec![0u8; Engagement::LEN];
+        let mut profile_data = vec![0u8; ConsultantProfile::LEN];
+        let profile = ConsultantProfile { consultant: consultant_key, hourly_rate: 50, available: true, active: true, is_initialized: true, ..Default::default() };
+        ConsultantProfile::pack(profile, &mut profile_data).unwrap();
+        let mut client_token_data = token_account_data(&mint_key, &client_key, 1_000);
+        let mut consultant_token_data = token_account_data(&mint_key, &consultant_key, 0);
+        let mut vault_data = token_account_data(&mint_key, &engagement_key, 0);
+        let mut fee_vault_data = token_account_data(&mint_key, &fee_authority, 0);
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { decimals: 9, is_initialized: true, ..Mint::default() }, &mut mint_data).unwrap();
+        let mut config_data = vec![0u8; ProgramConfig::LEN];
+        let config = ProgramConfig { paused: false, pause_until_slot: 0, fee_bps: 250, compliance_authority: Pubkey::default(), pause_flags: 0, is_initialized: true, ..Default::default() };
+        ProgramConfig::pack(config, &mut config_data).unwrap();
+
+        let mut lamports = [0u64, 10_000_000, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10, l11] = &mut lamports;
+        let (mut d1, mut d2, mut d9, mut d10) = (vec![], vec![], vec![], vec![]);
+        let engagement_acc = account_info(&engagement_key, false, true, l0, &mut engagement_data, &program_id);
+        let client_acc = account_info(&client_key, true, true, l1, &mut d1, &system_program_id);
+        let consultant_acc = account_info(&consultant_key, true, false, l2, &mut d2, &system_program_id);
+        let profile_acc = account_info(&profile_key, false, false, l3, &mut profile_data, &program_id);
+        let client_token_acc = account_info(&client_token_key, false, true, l4, &mut client_token_data, &token_program_id);
+        let consultant_token_acc = account_info(&consultant_token_key, false, true, l5, &mut consultant_token_data, &token_program_id);
+        let vault_acc = account_info(&vault_key, false, true, l6, &mut vault_data, &token_program_id);
+        let fee_vault_acc = account_info(&fee_vault_key, false, true, l7, &mut fee_vault_data, &token_program_id);
+        let mint_acc = account_info(&mint_key, false, false, l8, &mut mint_data, &token_program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, l9, &mut d9, &program_id);
+        let system_program_acc = account_info(&system_program_id, false, false, l10, &mut d10, &system_program_id);
+        let config_acc = account_info(&config_key, false, false, l11, &mut config_data, &program_id);
+        let (client_reputation_key, _) = reputation_address(&program_id, &client_key);
+        let (consultant_reputation_key, _) = reputation_address(&program_id, &consultant_key);
+        let (mut client_reputation_data, mut consultant_reputation_data) = (vec![0u8; Reputation::LEN], vec![0u8; Reputation::LEN]);
+        let (mut l12, mut l13) = (0u64, 0u64);
+        let client_reputation_acc = account_info(&client_reputation_key, false, true, &mut l12, &mut client_reputation_data, &program_id);
+        let consultant_reputation_acc =
+            account_info(&consultant_reputation_key, false, true, &mut l13, &mut consultant_reputation_data, &program_id);
+        bank.set_balance(&client_token_key, 1_000);
+        bank.set_unix_timestamp(1_000);
+
+        let open = [
+            engagement_acc.clone(),
+            client_acc.clone(),
+            consultant_acc.clone(),
+            profile_acc,
+            client_token_acc.clone(),
+            vault_acc.clone(),
+            mint_acc.clone(),
+            token_program_acc.clone(),
+            system_program_acc.clone(),
+        ];
+        open_engagement(&program_id, &open, 1, vec![100, 300], 86_400).unwrap();
+        assert_eq!((bank.balance(&client_token_key), bank.balance(&vault_key)), (600, 400));
+        assert_eq!(open_engagement(&program_id, &open, 1, vec![400], 86_400), Err(ProgramError::AccountAlreadyInitialized));
+        assert_eq!(bank.events::<events::EngagementOpened>()[0].milestones, vec![100, 300]);
+
+        let mut client_only = consultant_acc.clone();
+        client_only.is_signer = false;
+        let cancel = [
+            engagement_acc.clone(),
+            client_acc.clone(),
+            client_only,
+            vault_acc.clone(),
+            client_token_acc.clone(),
+            mint_acc.clone(),
+            token_program_acc.clone(),
+        ];
+        assert_eq!(cancel_engagement(&program_id, &cancel), Err(GgtError::EngagementDeadlineNotReached.into()));
+
+        let complete = [
+            engagement_acc.clone(),
+            client_acc,
+            consultant_acc,
+            vault_acc,
+            consultant_token_acc,
+            fee_vault_acc,
+            mint_acc,
+            token_program_acc,
+            config_acc,
+            client_reputation_acc,
+            consultant_reputation_acc.clone(),
+            system_program_acc,
+        ];
+        let approve = &complete[..9];
+        assert_eq!(approve_milestone(&program_id, approve, 1), Err(ProgramError::InvalidArgument));
+        approve_milestone(&program_id, approve, 0).unwrap();
+        // 2.5% of each tranche goes to the fee vault, rounded down.
+        assert_eq!((bank.balance(&consultant_token_key), bank.balance(&fee_vault_key), bank.balance(&vault_key)), (98, 2, 300));
+        assert_eq!(bank.events::<events::MilestoneApproved>()[0], events::MilestoneApproved { engagement: engagement_key, milestone_index: 0, amount: 98, fee: 2 });
+
+        complete_engagement(&program_id, &complete).unwrap();
+        assert_eq!((bank.balance(&consultant_token_key), bank.balance(&fee_vault_key), bank.balance(&vault_key)), (391, 9, 0));
+        assert_eq!(bank.events::<events::EngagementCompleted>()[0], events::EngagementCompleted { engagement: engagement_key, amount: 293, fee: 7 });
+        let reputation = Reputation::unpack(&consultant_reputation_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((reputation.wallet, reputation.completed_engagements, reputation.total_volume), (consultant_key, 1, 400));
+        assert_eq!(complete_engagement(&program_id, &complete), Err(GgtError::EngagementNotOpen.into()));
+
+        let mut reopened = Engagement::unpack(&engagement_acc.try_borrow_data().unwrap()).unwrap();
+        reopened.status = EngagementStatus::Open;
+        reopened.released = 100;
+        Engagement::pack(reopened, &mut engagement_acc.try_borrow_mut_data().unwrap()).unwrap();
+        bank.set_balance(&vault_key, 300);
+        bank.set_unix_timestamp(1_000 + 86_400);
+        // Only the milestone not yet approved is refunded.
+        cancel_engagement(&program_id, &cancel).unwrap();
+        assert_eq!((bank.balance(&client_token_key), bank.balance(&vault_key)), (900, 0));
+        assert_eq!(Engagement::unpack(&engagement_acc.try_borrow_data().unwrap()).unwrap().status, EngagementStatus::Cancelled);
+    }
+}
@@ -0,0 +1,212 @@
+use solana_program::program_error::ProgramError;
+
+/// Program-specific errors, surfaced to clients as `ProgramError::Custom(code)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GgtError {
+    UnsupportedInstructionVersion,
+    InvalidGuardianSet,
+    InsufficientGuardianSignatures,
+    SlaNotElapsed,
+    InvalidMatchStatus,
+    UnregisteredOracle,
+    MatchExpired,
+    VotingClosed,
+    BallotPageFull,
+    PageAlreadyTallied,
+    UntalliedBallotPages,
+    InvalidEvmSignature,
+    AlreadyVoted,
+    UnregisteredTreasuryAsset,
+    TreasuryCapExceeded,
+    StreamClosed,
+    StreamSolvent,
+    VotingStillOpen,
+    ProgramPaused,
+    VestingRevoked,
+    CoStakeRatioMismatch,
+    BridgeMessageAlreadyProcessed,
+    InsufficientStakeToPropose,
+    EpochNotEnded,
+    AddressBookFull,
+    DestinationNotInAddressBook,
+    CreditLimitExceeded,
+    InvoiceOverdue,
+    TimelockNotElapsed,
+    MigrationClosed,
+    StakeLocked,
+    CooldownNotElapsed,
+    UnknownChain,
+    CollateralRatioBreached,
+    MarketMakerNotApproved,
+    EngagementNotOpen,
+    EngagementDeadlineNotReached,
+    MathOverflow,
+    RateLimited,
+    BridgePaused,
+    StakeAfterSnapshot,
+    ApprovalThresholdNotMet,
+    NominationExpired,
+    StakeReceiptOutstanding,
+    InvalidMerkleProof,
+    AirdropAlreadyClaimed,
+    ProposalExpired,
+    AccountDiscriminatorMismatch,
+    RewardsUnderfunded,
+    OracleBondTooLow,
+    OracleNotActive,
+    NotArbitrator,
+    EngagementNotDisputed,
+    BridgeFeeExceedsAmount,
+    NotRelayer,
+    RelayerFeeUnavailable,
+    BridgeTransferNotPending,
+    BridgeTransferNotExpired,
+    WormholeVaaMismatch,
+    NotVotingDelegate,
+    VoteEscrowLocked,
+    AddressDenylisted,
+    UnsupportedAccountVersion,
+}
+
+impl GgtError {
+    /// Every variant, in code order.
+    pub const ALL: &'static [GgtError] = &[
+        GgtError::UnsupportedInstructionVersion,
+        GgtError::InvalidGuardianSet,
+        GgtError::InsufficientGuardianSignatures,
+        GgtError::SlaNotElapsed,
+        GgtError::InvalidMatchStatus,
+        GgtError::UnregisteredOracle,
+        GgtError::MatchExpired,
+        GgtError::VotingClosed,
+        GgtError::BallotPageFull,
+        GgtError::PageAlreadyTallied,
+        GgtError::UntalliedBallotPages,
+        GgtError::InvalidEvmSignature,
+        GgtError::AlreadyVoted,
+        GgtError::UnregisteredTreasuryAsset,
+        GgtError::TreasuryCapExceeded,
+        GgtError::StreamClosed,
+        GgtError::StreamSolvent,
+        GgtError::VotingStillOpen,
+        GgtError::ProgramPaused,
+        GgtError::VestingRevoked,
+        GgtError::CoStakeRatioMismatch,
+        GgtError::BridgeMessageAlreadyProcessed,
+        GgtError::InsufficientStakeToPropose,
+        GgtError::EpochNotEnded,
+        GgtError::AddressBookFull,
+        GgtError::DestinationNotInAddressBook,
+        GgtError::CreditLimitExceeded,
+        GgtError::InvoiceOverdue,
+        GgtError::TimelockNotElapsed,
+        GgtError::MigrationClosed,
+        GgtError::StakeLocked,
+        GgtError::CooldownNotElapsed,
+        GgtError::UnknownChain,
+        GgtError::CollateralRatioBreached,
+        GgtError::MarketMakerNotApproved,
+        GgtError::EngagementNotOpen,
+        GgtError::EngagementDeadlineNotReached,
+        GgtError::MathOverflow,
+        GgtError::RateLimited,
+        GgtError::BridgePaused,
+        GgtError::StakeAfterSnapshot,
+        GgtError::ApprovalThresholdNotMet,
+        GgtError::NominationExpired,
+        GgtError::StakeReceiptOutstanding,
+        GgtError::InvalidMerkleProof,
+        GgtError::AirdropAlreadyClaimed,
+        GgtError::ProposalExpired,
+        GgtError::AccountDiscriminatorMismatch,
+        GgtError::RewardsUnderfunded,
+        GgtError::OracleBondTooLow,
+        GgtError::OracleNotActive,
+        GgtError::NotArbitrator,
+        GgtError::EngagementNotDisputed,
+        GgtError::BridgeFeeExceedsAmount,
+        GgtError::NotRelayer,
+        GgtError::RelayerFeeUnavailable,
+        GgtError::BridgeTransferNotPending,
+        GgtError::BridgeTransferNotExpired,
+        GgtError::WormholeVaaMismatch,
+        GgtError::NotVotingDelegate,
+        GgtError::VoteEscrowLocked,
+        GgtError::AddressDenylisted,
+        GgtError::UnsupportedAccountVersion,
+    ];
+
+    pub fn message(self) -> &'static str {
+        match self {
+            GgtError::UnsupportedInstructionVersion => "Instruction version is not supported",
+            GgtError::InvalidGuardianSet => "Guardian set is empty, too large, has duplicates or an unreachable threshold",
+            GgtError::InsufficientGuardianSignatures => "Not enough guardians signed the message",
+            GgtError::SlaNotElapsed => "Match request SLA has not elapsed",
+            GgtError::InvalidMatchStatus => "Match request is not in the expected status",
+            GgtError::UnregisteredOracle => "Signer is not a registered oracle",
+            GgtError::MatchExpired => "Match request has expired",
+            GgtError::VotingClosed => "Voting period has ended",
+            GgtError::BallotPageFull => "Ballot page is full",
+            GgtError::PageAlreadyTallied => "Ballot page was already tallied",
+            GgtError::UntalliedBallotPages => "Some ballot pages are not tallied yet",
+            GgtError::InvalidEvmSignature => "EVM signature does not recover to the expected address",
+            GgtError::AlreadyVoted => "Voter already voted on this proposal",
+            GgtError::UnregisteredTreasuryAsset => "Mint is not a registered treasury asset",
+            GgtError::TreasuryCapExceeded => "Treasury would exceed the asset holding cap",
+            GgtError::StreamClosed => "Payment stream is closed",
+            GgtError::StreamSolvent => "Payment stream is still solvent",
+            GgtError::VotingStillOpen => "Voting period has not ended",
+            GgtError::ProgramPaused => "Program is paused",
+            GgtError::VestingRevoked => "Vesting schedule was revoked",
+            GgtError::CoStakeRatioMismatch => "Partner amount does not match the co-staking ratio",
+            GgtError::BridgeMessageAlreadyProcessed => "Bridge message was already processed",
+            GgtError::InsufficientStakeToPropose => "Stake is below the minimum to propose",
+            GgtError::EpochNotEnded => "Reward epoch has not ended",
+            GgtError::AddressBookFull => "Address book is full",
+            GgtError::DestinationNotInAddressBook => "Destination is not in the address book",
+            GgtError::CreditLimitExceeded => "Invoice credit limit exceeded",
+            GgtError::InvoiceOverdue => "Invoice is overdue",
+            GgtError::TimelockNotElapsed => "Timelock has not elapsed",
+            GgtError::MigrationClosed => "Token migration is closed",
+            GgtError::StakeLocked => "Stake is still locked",
+            GgtError::CooldownNotElapsed => "Unstake cooldown has not elapsed",
+            GgtError::UnknownChain => "Chain is not in the chain registry",
+            GgtError::CollateralRatioBreached => "Collateral does not cover outstanding fast mints",
+            GgtError::MarketMakerNotApproved => "Market maker is not approved",
+            GgtError::EngagementNotOpen => "Engagement is not open",
+            GgtError::EngagementDeadlineNotReached => "Engagement deadline has not been reached",
+            GgtError::MathOverflow => "Arithmetic overflow",
+            GgtError::RateLimited => "Bridge outflow cap reached",
+            GgtError::BridgePaused => "Bridge is paused",
+            GgtError::StakeAfterSnapshot => "Stake was opened after the proposal snapshot",
+            GgtError::ApprovalThresholdNotMet => "Not enough multisig approvals",
+            GgtError::NominationExpired => "Authority nomination has expired",
+            GgtError::StakeReceiptOutstanding => "Stake receipt must be burned first",
+            GgtError::InvalidMerkleProof => "Merkle proof does not match the distribution root",
+            GgtError::AirdropAlreadyClaimed => "Airdrop was already claimed",
+            GgtError::ProposalExpired => "Proposal execution window has passed",
+            GgtError::AccountDiscriminatorMismatch => "Account holds a different account type",
+            GgtError::RewardsUnderfunded => "Reward pool cannot cover the emissions schedule",
+            GgtError::OracleBondTooLow => "Oracle bond is below the minimum",
+            GgtError::OracleNotActive => "Oracle operator is not active",
+            GgtError::NotArbitrator => "Signer is not on the arbitration council",
+            GgtError::EngagementNotDisputed => "Engagement is not under dispute",
+            GgtError::BridgeFeeExceedsAmount => "Amount does not cover the bridge fee",
+            GgtError::NotRelayer => "Signer is not the relayer of this transfer",
+            GgtError::RelayerFeeUnavailable => "Relayer fee was already claimed or none is owed",
+            GgtError::BridgeTransferNotPending => "Bridge transfer was already completed or cancelled",
+            GgtError::BridgeTransferNotExpired => "Bridge transfer timeout has not elapsed",
+            GgtError::WormholeVaaMismatch => "Posted VAA does not match the message or emitter",
+            GgtError::NotVotingDelegate => "Voter neither owns the stake nor is its voting delegate",
+            GgtError::VoteEscrowLocked => "Voting lock has not expired",
+            GgtError::AddressDenylisted => "Address is on the compliance denylist",
+            GgtError::UnsupportedAccountVersion => "Account layout version is not supported; migrate the account",
+        }
+    }
+}
+
+impl From<GgtError> for ProgramError {
+    fn from(e: GgtError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
@@ -0,0 +1,453 @@
+//! Typed program events for indexers. Each event is logged with `sol_log_data`
+//! as a single field: an 8-byte discriminator followed by the Borsh-encoded
+//! event. The discriminator is the first 8 bytes of `sha256("event:<Name>")`,
+//! the layout Anchor's `emit!` uses, so Anchor-aware explorers decode them too.
+
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use solana_program::{hash::hashv, log::sol_log_data, pubkey::Pubkey};
+pub use crate::governance_contract::VoteChoice;
+
+pub trait Event: borsh::BorshSerialize + borsh::BorshDeserialize {
+    const NAME: &'static str;
+
+    fn discriminator() -> [u8; 8] {
+        let hash = hashv(&[b"event:", Self::NAME.as_bytes()]);
+        hash.to_bytes()[..8].try_into().unwrap()
+    }
+}
+
+pub fn emit<E: Event>(event: &E) {
+    let mut data = E::discriminator().to_vec();
+    borsh::to_writer(&mut data, event).expect("serializing into a Vec cannot fail");
+    sol_log_data(&[&data]);
+}
+
+/// Decodes one `sol_log_data` field, or `None` if it holds a different event.
+pub fn decode<E: Event>(data: &[u8]) -> Option<E> {
+    let body = data.strip_prefix(&E::discriminator())?;
+    borsh::from_slice(body).ok()
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct TransferEvent {
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub authority: Pubkey,
+    /// Amount received by `destination`, after the protocol fee.
+    pub amount: u64,
+    pub fee: u64,
+}
+
+/// `owner` let `delegate` move up to `amount` from `source`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct DelegateApproved {
+    pub source: Pubkey,
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct DelegateRevoked {
+    pub source: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct StakeEvent {
+    pub staker: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub lock_until: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct UnstakeEvent {
+    pub staker: Pubkey,
+    pub mint: Pubkey,
+    /// Amount paid out, after `penalty`.
+    pub amount: u64,
+    pub penalty: u64,
+}
+
+/// Governance confiscated `amount` of a position into the penalty pool.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct StakeSlashed {
+    pub staker: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    /// Hash of the off-chain record of the misbehaviour.
+    pub reason_hash: [u8; 32],
+}
+
+/// `amount` and `unlock_at` describe the whole lock after this addition.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct VoteEscrowLocked {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub unlock_at: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct VoteEscrowWithdrawn {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+/// The default `delegate` means the staker votes with the stake again.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct VotingDelegateSet {
+    pub staker: Pubkey,
+    pub mint: Pubkey,
+    pub delegate: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct ProposalCreated {
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
+    pub voting_ends_at: i64,
+    pub content_hash: [u8; 32],
+    pub uri: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct VoteCast {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub choice: VoteChoice,
+    pub weight: u64,
+}
+
+/// A voter moved `weight` from `previous` to `choice` before voting closed.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct VoteChanged {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub previous: VoteChoice,
+    pub choice: VoteChoice,
+    pub weight: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct BridgeLock {
+    pub transfer_id: [u8; 32],
+    pub sender: Pubkey,
+    /// Mint of the locked or burned tokens.
+    pub mint: Pubkey,
+    pub sequence: u64,
+    pub target_chain_id: u16,
+    /// Destination address on `target_chain_id`; empty if the lock did not name one.
+    pub destination: Vec<u8>,
+    /// Bridged amount, after `fee`.
+    pub amount: u64,
+    /// Relayer fee moved to the relayer fee vault.
+    pub fee: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct BridgeRelease {
+    pub source_chain_id: u16,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// A lock that was never completed was refunded to its sender; relayers must
+/// not deliver `transfer_id` any more.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct BridgeTransferCancelled {
+    pub transfer_id: [u8; 32],
+    pub sender: Pubkey,
+    pub sequence: u64,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+/// The relayer that completed release `nonce` from `source_chain_id` was paid its fee.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct RelayerFeeClaimed {
+    pub source_chain_id: u16,
+    pub nonce: u64,
+    pub relayer: Pubkey,
+    pub amount: u64,
+}
+
+/// Wrapped tokens minted for an inbound transfer of `foreign_token`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct WrappedRelease {
+    pub source_chain_id: u16,
+    pub foreign_token: [u8; 32],
+    pub mint: Pubkey,
+    pub nonce: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// One `batch_transfer` call; `amounts[i]` went to `recipients[i]`, and the
+/// single `fee` was charged on top of their sum.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct BatchTransferEvent {
+    pub source: Pubkey,
+    pub authority: Pubkey,
+    pub recipients: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    pub fee: u64,
+}
+
+/// One `migrate_tokens` call, with the migration totals after it.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct TokensMigrated {
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub epoch: u64,
+    pub epoch_migrated: u64,
+    pub total_migrated: u64,
+}
+
+/// A market maker's fast mint; relayers mint on the destination chain from it
+/// without waiting for guardians.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct FastMint {
+    pub transfer_id: [u8; 32],
+    pub maker: Pubkey,
+    pub sequence: u64,
+    pub target_chain_id: u16,
+    pub destination: Vec<u8>,
+    pub amount: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct FastMintReconciled {
+    pub transfer_id: [u8; 32],
+    pub maker: Pubkey,
+    pub amount: u64,
+}
+
+/// An oracle operator bonded tokens; `bond` is its total bond afterwards.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct OracleRegistered {
+    pub operator: Pubkey,
+    pub bond: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct OracleDeregistered {
+    pub operator: Pubkey,
+    pub withdrawable_at: i64,
+}
+
+/// Governance moved `amount` of an oracle operator's bond into the treasury.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct OracleSlashed {
+    pub operator: Pubkey,
+    pub amount: u64,
+    /// Whether the operator's remaining bond still keeps it active.
+    pub active: bool,
+    pub reason_hash: [u8; 32],
+}
+
+/// An engagement opened with `milestones` escrowed, released in order.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct EngagementOpened {
+    pub engagement: Pubkey,
+    pub client: Pubkey,
+    pub consultant: Pubkey,
+    pub engagement_id: u64,
+    pub milestones: Vec<u64>,
+}
+
+/// `amount` went to the consultant and `fee` to the fee vault.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct MilestoneApproved {
+    pub engagement: Pubkey,
+    pub milestone_index: u8,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+/// The escrow left after approved milestones was paid out: `amount` to the
+/// consultant and `fee` to the fee vault.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct EngagementCompleted {
+    pub engagement: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+/// Governance burned `amount` out of a treasury vault; `total_burned` is the
+/// running total afterwards.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct TreasuryBurned {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub total_burned: u64,
+}
+
+/// A `transfer_with_memo` payment: `sender` owns the paying token account and
+/// `recipient` received `amount`, after the protocol `fee`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct PaymentReferenced {
+    pub reference: [u8; 32],
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+/// The compliance authority put `address` on the denylist.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct DenylistAdded {
+    pub address: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct DenylistRemoved {
+    pub address: Pubkey,
+    pub authority: Pubkey,
+}
+
+impl Event for TransferEvent {
+    const NAME: &'static str = "TransferEvent";
+}
+
+impl Event for BatchTransferEvent {
+    const NAME: &'static str = "BatchTransferEvent";
+}
+
+impl Event for DelegateApproved {
+    const NAME: &'static str = "DelegateApproved";
+}
+
+impl Event for DelegateRevoked {
+    const NAME: &'static str = "DelegateRevoked";
+}
+
+impl Event for StakeEvent {
+    const NAME: &'static str = "StakeEvent";
+}
+
+impl Event for UnstakeEvent {
+    const NAME: &'static str = "UnstakeEvent";
+}
+
+impl Event for StakeSlashed {
+    const NAME: &'static str = "StakeSlashed";
+}
+
+impl Event for VotingDelegateSet {
+    const NAME: &'static str = "VotingDelegateSet";
+}
+
+impl Event for VoteEscrowLocked {
+    const NAME: &'static str = "VoteEscrowLocked";
+}
+
+impl Event for VoteEscrowWithdrawn {
+    const NAME: &'static str = "VoteEscrowWithdrawn";
+}
+
+impl Event for ProposalCreated {
+    const NAME: &'static str = "ProposalCreated";
+}
+
+impl Event for VoteCast {
+    const NAME: &'static str = "VoteCast";
+}
+
+impl Event for VoteChanged {
+    const NAME: &'static str = "VoteChanged";
+}
+
+impl Event for BridgeLock {
+    const NAME: &'static str = "BridgeLock";
+}
+
+impl Event for BridgeRelease {
+    const NAME: &'static str = "BridgeRelease";
+}
+
+impl Event for BridgeTransferCancelled {
+    const NAME: &'static str = "BridgeTransferCancelled";
+}
+
+impl Event for RelayerFeeClaimed {
+    const NAME: &'static str = "RelayerFeeClaimed";
+}
+
+impl Event for WrappedRelease {
+    const NAME: &'static str = "WrappedRelease";
+}
+
+impl Event for TokensMigrated {
+    const NAME: &'static str = "TokensMigrated";
+}
+
+impl Event for FastMint {
+    const NAME: &'static str = "FastMint";
+}
+
+impl Event for FastMintReconciled {
+    const NAME: &'static str = "FastMintReconciled";
+}
+
+impl Event for OracleRegistered {
+    const NAME: &'static str = "OracleRegistered";
+}
+
+impl Event for OracleDeregistered {
+    const NAME: &'static str = "OracleDeregistered";
+}
+
+impl Event for OracleSlashed {
+    const NAME: &'static str = "OracleSlashed";
+}
+
+impl Event for EngagementOpened {
+    const NAME: &'static str = "EngagementOpened";
+}
+
+impl Event for MilestoneApproved {
+    const NAME: &'static str = "MilestoneApproved";
+}
+
+impl Event for EngagementCompleted {
+    const NAME: &'static str = "EngagementCompleted";
+}
+
+impl Event for TreasuryBurned {
+    const NAME: &'static str = "TreasuryBurned";
+}
+
+impl Event for PaymentReferenced {
+    const NAME: &'static str = "PaymentReferenced";
+}
+
+impl Event for DenylistAdded {
+    const NAME: &'static str = "DenylistAdded";
+}
+
+impl Event for DenylistRemoved {
+    const NAME: &'static str = "DenylistRemoved";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_round_trip_and_reject_other_discriminators() {
+        let event = VoteCast { proposal: Pubkey::new_unique(), voter: Pubkey::new_unique(), choice: VoteChoice::Yes, weight: 42 };
+        let mut data = VoteCast::discriminator().to_vec();
+        data.extend_from_slice(&borsh::to_vec(&event).unwrap());
+
+        assert_eq!(decode::<VoteCast>(&data), Some(event));
+        assert_eq!(decode::<StakeEvent>(&data), None);
+        assert_ne!(VoteCast::discriminator(), StakeEvent::discriminator());
+        assert_eq!(&VoteCast::discriminator()[..], &hashv(&[b"event:VoteCast"]).to_bytes()[..8]);
+    }
+}
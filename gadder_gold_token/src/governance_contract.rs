@@ -1,88 +1,734 @@
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable,
     clock::Clock,
     entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
     msg,
+    program::{invoke, invoke_signed},
     program_error::ProgramError,
     program_pack::{Pack, Sealed, IsInitialized},
     pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
     sysvar::Sysvar,
 };
 use borsh_derive::{BorshDeserialize, BorshSerialize};
-use crate::{staking_contract::StakingContract, ADMIN_PUBKEY, GOVERNANCE_PUBKEY};
+use crate::{
+    config::{self, AuthorityRole},
+    error::GgtError,
+    events,
+    cross_chain_bridge_contract::CrossChainBridge,
+    multisig,
+    staking_contract::{check_stake_account, load_staking_config, stake_address, PodStake, PodStakingPool, StakingContract},
+    token_program,
+    treasury_contract::Treasury,
+    vote_escrow, SET_PAUSE_FLAGS_TAG,
+};
+use crate::state::pack_state;
+use crate::validation::{assert_ggt_mint, assert_owned_by, assert_pda, assert_signer, assert_token_account_mint};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN, HEADER_LEN};
+use crate::zero_copy::load;
 
 
-#[derive(BorshSerialize, BorshDeserialize)]
+pub const MAX_URI_LEN: usize = Proposal::LEN - HEADER_LEN - 236 - MAX_INSTRUCTIONS_LEN; // length prefix (4) + content hash (32) + fixed fields (200)
+/// Most full-text bytes `extend_proposal_body` may append behind a proposal.
+pub const MAX_PROPOSAL_BODY_LEN: usize = 8 * 1024;
+/// Space reserved in a proposal for its Borsh-encoded execution payload.
+pub const MAX_INSTRUCTIONS_LEN: usize = 768;
+pub const BALLOTS_PER_PAGE: usize = 32;
+pub const VOTE_SEED: &[u8] = b"vote";
+pub const GOVERNANCE_CONFIG_SEED: &[u8] = b"governance_config";
+pub const GOVERNANCE_AUTHORITY_SEED: &[u8] = b"governance_authority";
+pub const PROPOSAL_DEPOSIT_SEED: &[u8] = b"proposal_deposit";
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+pub const PROPOSAL_COUNTER_SEED: &[u8] = b"proposal_counter";
+/// Delay after voting ends before a passed treasury spend may execute.
+pub const TREASURY_SPEND_TIMELOCK_SECONDS: i64 = 2 * 86_400;
+/// How long a passed proposal stays executable once its timelock ends.
+pub const EXECUTION_WINDOW_SECONDS: i64 = 14 * 86_400;
+
+/// Address of the singleton `GovernanceConfig` account.
+pub fn governance_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GOVERNANCE_CONFIG_SEED], program_id)
+}
+
+/// PDA that signs the instructions of executed proposals. Assets governance
+/// should control are held by, or delegated to, this address.
+pub fn governance_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GOVERNANCE_AUTHORITY_SEED], program_id)
+}
+
+/// Owns the token accounts that proposal deposits are escrowed in.
+pub fn proposal_deposit_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROPOSAL_DEPOSIT_SEED], program_id)
+}
+
+/// Address of the singleton `ProposalCounter` that numbers proposals.
+pub fn proposal_counter_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROPOSAL_COUNTER_SEED], program_id)
+}
+
+/// Address of the proposal numbered `proposal_id` by the counter.
+pub fn proposal_address(program_id: &Pubkey, proposal_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROPOSAL_SEED, &proposal_id.to_le_bytes()], program_id)
+}
+
+/// Weight `staking_acc` votes with on `proposal`: its stake, which must not have
+/// been opened or topped up since the proposal was created. Accounts that hold
+/// no stake vote with zero weight.
+fn snapshot_weight(proposal: &Proposal, staking_acc: &AccountInfo) -> Result<u64, ProgramError> {
+    let data = staking_acc.try_borrow_data()?;
+    let Ok(&PodStake { amount, staked_at, .. }) = load::<PodStake>(&data) else { return Ok(0) };
+    if amount > 0 && staked_at >= proposal.timestamp {
+        msg!("Stake changed at {}, after the proposal snapshot at {}", staked_at, proposal.timestamp);
+        return Err(GgtError::StakeAfterSnapshot.into());
+    }
+    Ok(amount)
+}
+
+/// Checks `voter` may vote with the stake in `staking_acc`: their own stake
+/// for `mint` while it is not delegated, or a stake delegated to them since
+/// before the proposal was created. Returns its snapshot weight.
+fn stake_weight(program_id: &Pubkey, proposal: &Proposal, staking_acc: &AccountInfo, voter: &Pubkey, mint: &Pubkey) -> Result<u64, ProgramError> {
+    assert_owned_by(staking_acc, program_id)?;
+    let (voting_delegate, delegated_at) = {
+        let data = staking_acc.try_borrow_data()?;
+        let stake = load::<PodStake>(&data)?;
+        (Pubkey::new_from_array(stake.voting_delegate), stake.delegated_at)
+    };
+    let casts_vote = if voting_delegate == Pubkey::default() {
+        *staking_acc.key == stake_address(program_id, voter, mint).0
+    } else {
+        voting_delegate == *voter
+    };
+    if !casts_vote {
+        return Err(GgtError::NotVotingDelegate.into());
+    }
+    if delegated_at >= proposal.timestamp {
+        msg!("Stake was delegated at {}, after the proposal snapshot at {}", delegated_at, proposal.timestamp);
+        return Err(GgtError::StakeAfterSnapshot.into());
+    }
+    snapshot_weight(proposal, staking_acc)
+}
+
+/// Sums the [`stake_weight`] of every stake in `staking_accs`, so a delegate
+/// votes once with all the stakes delegated to it. A stake listed twice
+/// counts once.
+fn delegated_weight<'a, 'info: 'a>(
+    program_id: &Pubkey,
+    proposal: &Proposal,
+    staking_accs: impl IntoIterator<Item = &'a AccountInfo<'info>>,
+    voter: &Pubkey,
+    mint: &Pubkey,
+) -> Result<u64, ProgramError> {
+    let mut counted: Vec<&Pubkey> = Vec::new();
+    let mut weight = 0u64;
+    for staking_acc in staking_accs {
+        if counted.contains(&staking_acc.key) {
+            continue;
+        }
+        counted.push(staking_acc.key);
+        weight = weight.checked_add(stake_weight(program_id, proposal, staking_acc, voter, mint)?).ok_or(GgtError::MathOverflow)?;
+    }
+    Ok(weight)
+}
+
+/// Whether `acc` is a vote-escrow lock rather than a stake position.
+fn is_vote_escrow(program_id: &Pubkey, acc: &AccountInfo) -> bool {
+    acc.owner == program_id && acc.try_borrow_data().is_ok_and(|data| read_discriminator::<vote_escrow::VoteEscrow>(&data).is_ok())
+}
+
+/// Splits an optional leading vote-escrow lock off the accounts trailing a vote,
+/// leaving the stakes delegated to the voter.
+fn split_vote_escrow<'a, 'info>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'info>],
+) -> (Option<&'a AccountInfo<'info>>, &'a [AccountInfo<'info>]) {
+    match accounts.split_first() {
+        Some((escrow_acc, rest)) if is_vote_escrow(program_id, escrow_acc) => (Some(escrow_acc), rest),
+        _ => (None, accounts),
+    }
+}
+
+/// Unpacks the proposal in `proposal_acc`, ignoring any body appended behind it.
+fn load_proposal(proposal_acc: &AccountInfo) -> Result<Proposal, ProgramError> {
+    let data = proposal_acc.try_borrow_data()?;
+    Proposal::unpack(data.get(..Proposal::LEN).ok_or(ProgramError::InvalidAccountData)?)
+}
+
+fn check_deposit_vault(program_id: &Pubkey, vault_acc: &AccountInfo, mint: &Pubkey) -> ProgramResult {
+    if assert_token_account_mint(vault_acc, mint)?.owner != proposal_deposit_authority(program_id).0 {
+        msg!("Proposal deposits are escrowed in a vault owned by the deposit authority");
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
+
+/// Whether the proposal in `proposal_acc` does nothing but call `set_pause_flags`.
+/// Such proposals execute even while governance execution is paused, so an
+/// executed proposal can always lift the pause again.
+pub(crate) fn only_sets_pause_flags(program_id: &Pubkey, proposal_acc: &AccountInfo) -> bool {
+    if proposal_acc.owner != program_id {
+        return false;
+    }
+    let Ok(proposal) = load_proposal(proposal_acc) else { return false };
+    proposal.kind == ProposalKind::Text
+        && !proposal.instructions.is_empty()
+        && proposal.instructions.iter().all(|ix| ix.program_id == *program_id && ix.data.first() == Some(&SET_PAUSE_FLAGS_TAG))
+}
+
+/// Governance-only instructions take the governance signer, checked against
+/// the program config in `config_acc`, or the governance authority PDA when
+/// they run as part of an executed proposal.
+pub(crate) fn check_governance_signer(program_id: &Pubkey, config_acc: &AccountInfo, governance_acc: &AccountInfo) -> ProgramResult {
+    if *governance_acc.key == governance_authority_address(program_id).0 {
+        return assert_signer(governance_acc);
+    }
+    config::check_authority(program_id, config_acc, &[AuthorityRole::Governance], governance_acc)
+}
+
+/// Vote record address for `voter` on `proposal`; one per voter and proposal.
+pub fn vote_address(program_id: &Pubkey, proposal: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VOTE_SEED, proposal.as_ref(), voter.as_ref()], program_id)
+}
+
+/// Tunable governance rules. Set once at initialization, after which only an
+/// executed `GovernanceRules` or `ParameterChange` proposal can change them.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct GovernanceParams {
+    /// Share of total staked weight that must vote, in basis points.
+    pub quorum_bps: u16,
+    /// Share of cast weight voting yes that a proposal must exceed, in basis points.
+    pub approval_threshold_bps: u16,
+    pub voting_period_seconds: i64,
+    pub min_stake_to_propose: u64,
+    /// GGT escrowed by each new proposal; refunded once it is finalized with
+    /// quorum, otherwise slashed to the treasury.
+    pub proposal_deposit: u64,
+    /// Delay between a proposal passing and becoming executable, so holders
+    /// who disagree can exit first.
+    pub timelock_seconds: i64,
+}
+
+impl GovernanceParams {
+    pub const LEN: usize = 36; // u16 (2) * 2 + i64 (8) * 2 + u64 (8) * 2
+
+    pub(crate) fn validate(&self) -> ProgramResult {
+        if self.quorum_bps > 10_000
+            || self.approval_threshold_bps >= 10_000
+            || self.voting_period_seconds <= 0
+            || self.timelock_seconds < 0
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[0..2].copy_from_slice(&self.quorum_bps.to_le_bytes());
+        dst[2..4].copy_from_slice(&self.approval_threshold_bps.to_le_bytes());
+        dst[4..12].copy_from_slice(&self.voting_period_seconds.to_le_bytes());
+        dst[12..20].copy_from_slice(&self.min_stake_to_propose.to_le_bytes());
+        dst[20..28].copy_from_slice(&self.proposal_deposit.to_le_bytes());
+        dst[28..36].copy_from_slice(&self.timelock_seconds.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Self {
+        GovernanceParams {
+            quorum_bps: u16::from_le_bytes(src[0..2].try_into().unwrap()),
+            approval_threshold_bps: u16::from_le_bytes(src[2..4].try_into().unwrap()),
+            voting_period_seconds: i64::from_le_bytes(src[4..12].try_into().unwrap()),
+            min_stake_to_propose: u64::from_le_bytes(src[12..20].try_into().unwrap()),
+            proposal_deposit: u64::from_le_bytes(src[20..28].try_into().unwrap()),
+            timelock_seconds: i64::from_le_bytes(src[28..36].try_into().unwrap()),
+        }
+    }
+
+    /// Whether enough of `total_staked` eligible weight voted, abstentions included.
+    /// The `escrow_weight` share of the tallies is vote-escrow bonus, which
+    /// `total_staked` does not include, so it is left out of the count.
+    pub fn reaches_quorum(&self, yes_weight: u64, no_weight: u64, abstain_weight: u64, escrow_weight: u64, total_staked: u64) -> bool {
+        let cast = (yes_weight as u128 + no_weight as u128 + abstain_weight as u128).saturating_sub(escrow_weight as u128);
+        cast > 0 && cast >= total_staked as u128 * self.quorum_bps as u128 / 10_000
+    }
+
+    /// Whether the tallied weights pass given `total_staked` eligible weight.
+    /// Abstentions count towards quorum but not towards the approval threshold.
+    pub fn passes(&self, yes_weight: u64, no_weight: u64, abstain_weight: u64, escrow_weight: u64, total_staked: u64) -> bool {
+        let decided = yes_weight as u128 + no_weight as u128;
+        self.reaches_quorum(yes_weight, no_weight, abstain_weight, escrow_weight, total_staked)
+            && yes_weight as u128 * 10_000 > decided * self.approval_threshold_bps as u128
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct GovernanceConfig {
+    pub params: GovernanceParams,
+    /// Staking pool whose total stake `quorum_bps` is measured against.
+    pub staking_pool: Pubkey,
+    pub is_initialized: bool,
+}
+
+impl Sealed for GovernanceConfig {}
+
+impl IsInitialized for GovernanceConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for GovernanceConfig {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [81, 63, 124, 107, 210, 100, 145, 70];
+}
+
+impl Pack for GovernanceConfig {
+    const LEN: usize = 78; // discriminator (8) + version (1) + GovernanceParams (36) + Pubkey (32) + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        self.params.pack_into_slice(&mut dst[..GovernanceParams::LEN]);
+        dst[36..68].copy_from_slice(self.staking_pool.as_ref());
+        dst[68] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(GovernanceConfig {
+            params: GovernanceParams::unpack_from_slice(&src[..GovernanceParams::LEN]),
+            staking_pool: Pubkey::new_from_array(src[36..68].try_into().unwrap()),
+            is_initialized: src[68] != 0,
+        })
+    }
+}
+
+fn load_governance_config(program_id: &Pubkey, config_acc: &AccountInfo) -> Result<GovernanceConfig, ProgramError> {
+    assert_owned_by(config_acc, program_id)?;
+    assert_pda(config_acc, governance_config_address(program_id))?;
+    GovernanceConfig::unpack(&config_acc.try_borrow_data()?)
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ProposalStatus {
+    Active,
+    Passed,
+    Rejected,
+    Executed,
+    /// Withdrawn as spam by the admin or governance before voting ended.
+    Cancelled,
+}
+
+impl ProposalStatus {
+    fn from_u8(value: u8) -> Result<Self, ProgramError> {
+        match value {
+            0 => Ok(ProposalStatus::Active),
+            1 => Ok(ProposalStatus::Passed),
+            2 => Ok(ProposalStatus::Rejected),
+            3 => Ok(ProposalStatus::Executed),
+            4 => Ok(ProposalStatus::Cancelled),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+/// A voter's choice. Abstaining counts towards quorum but not approval.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum VoteChoice {
+    No,
+    Yes,
+    Abstain,
+}
+
+impl VoteChoice {
+    fn from_u8(value: u8) -> Result<Self, ProgramError> {
+        match value {
+            0 => Ok(VoteChoice::No),
+            1 => Ok(VoteChoice::Yes),
+            2 => Ok(VoteChoice::Abstain),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+/// Payment out of a registered treasury vault made when the proposal executes.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TreasurySpend {
+    pub vault: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// Burn out of a token account held by, or delegated to, the governance
+/// authority, made when the proposal executes.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TokenBurn {
+    pub token_account: Pubkey,
+    pub amount: u64,
+}
+
+/// A setting in the governance, staking or bridge config that a
+/// parameter-change proposal can replace. Its variant index is the field id.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ConfigField {
+    GovernanceQuorumBps,
+    GovernanceApprovalThresholdBps,
+    GovernanceVotingPeriodSeconds,
+    GovernanceMinStakeToPropose,
+    GovernanceProposalDeposit,
+    GovernanceTimelockSeconds,
+    StakingPenaltyBpsOver90Days,
+    StakingPenaltyBpsOver30Days,
+    StakingPenaltyBps,
+    StakingUnstakeCooldownSeconds,
+    BridgeGuardianThreshold,
+    BridgeEvmGuardianThreshold,
+    BridgeDailyOutflowCap,
+}
+
+/// Sets one config field to `value` when the proposal executes. The value is
+/// narrowed to the field's type and the config revalidated, so an out-of-range
+/// value fails execution rather than being clamped.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ParameterChange {
+    pub field: ConfigField,
+    pub value: u64,
+}
+
+fn narrow<T: TryFrom<u64>>(value: u64) -> Result<T, ProgramError> {
+    T::try_from(value).map_err(|_| ProgramError::InvalidArgument)
+}
+
+impl ParameterChange {
+    /// Writes the change into `config_acc`, the config account `field` lives in.
+    fn apply(&self, program_id: &Pubkey, config_acc: &AccountInfo, now: i64) -> ProgramResult {
+        use ConfigField::*;
+        let value = self.value;
+        match self.field {
+            GovernanceQuorumBps
+            | GovernanceApprovalThresholdBps
+            | GovernanceVotingPeriodSeconds
+            | GovernanceMinStakeToPropose
+            | GovernanceProposalDeposit
+            | GovernanceTimelockSeconds => {
+                let mut config = load_governance_config(program_id, config_acc)?;
+                let params = &mut config.params;
+                match self.field {
+                    GovernanceQuorumBps => params.quorum_bps = narrow(value)?,
+                    GovernanceApprovalThresholdBps => params.approval_threshold_bps = narrow(value)?,
+                    GovernanceVotingPeriodSeconds => params.voting_period_seconds = narrow(value)?,
+                    GovernanceMinStakeToPropose => params.min_stake_to_propose = value,
+                    GovernanceProposalDeposit => params.proposal_deposit = value,
+                    _ => params.timelock_seconds = narrow(value)?,
+                }
+                params.validate()?;
+                pack_state(&config, config_acc)?;
+            }
+            StakingPenaltyBpsOver90Days | StakingPenaltyBpsOver30Days | StakingPenaltyBps | StakingUnstakeCooldownSeconds => {
+                let mut config = load_staking_config(program_id, config_acc)?;
+                let economics = &mut config.economics;
+                match self.field {
+                    StakingPenaltyBpsOver90Days => economics.penalty_bps_over_90_days = narrow(value)?,
+                    StakingPenaltyBpsOver30Days => economics.penalty_bps_over_30_days = narrow(value)?,
+                    StakingPenaltyBps => economics.penalty_bps = narrow(value)?,
+                    _ => economics.unstake_cooldown_seconds = narrow(value)?,
+                }
+                economics.validate()?;
+                // Opens the same penalty-free exit window as `set_staking_economics`.
+                config.last_econ_change = now;
+                pack_state(&config, config_acc)?;
+            }
+            BridgeGuardianThreshold | BridgeEvmGuardianThreshold | BridgeDailyOutflowCap => {
+                assert_owned_by(config_acc, program_id)?;
+                let mut config = CrossChainBridge::load_config(config_acc)?;
+                match self.field {
+                    BridgeGuardianThreshold => {
+                        config.guardian_threshold = narrow(value)?;
+                        CrossChainBridge::validate_guardian_set(&config.guardians, config.guardian_threshold)?;
+                    }
+                    BridgeEvmGuardianThreshold => {
+                        config.evm_guardian_threshold = narrow(value)?;
+                        CrossChainBridge::validate_guardian_set(&config.evm_guardians, config.evm_guardian_threshold)?;
+                    }
+                    _ => config.daily_outflow_cap = value,
+                }
+                pack_state(&config, config_acc)?;
+            }
+        }
+        msg!("Set {:?} to {}", self.field, value);
+        Ok(())
+    }
+}
+
+/// Upgrade of this program to the code written to `buffer`, signed by the
+/// governance authority, which must already hold the upgrade authority (see
+/// `set_upgrade_authority`). The buffer's lamports go to `spill`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct CodeUpgrade {
+    pub buffer: Pubkey,
+    pub spill: Pubkey,
+}
+
+/// What executing a proposal does, besides invoking its `instructions`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ProposalKind {
+    /// No effect of its own; a signalling vote or one that only carries instructions.
+    Text,
+    ParameterChange(ParameterChange),
+    TreasurySpend(TreasurySpend),
+    CodeUpgrade(CodeUpgrade),
+    /// Replaces every governance rule at once.
+    GovernanceRules(GovernanceParams),
+    TokenBurn(TokenBurn),
+}
+
+impl ProposalKind {
+    const LEN: usize = 73; // variant (1) + largest payload, TreasurySpend (72)
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ProposalAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// A serialized instruction a proposal invokes when executed.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct ProposalInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<ProposalAccountMeta>,
+    pub data: Vec<u8>,
+}
+
+impl From<&ProposalInstruction> for Instruction {
+    fn from(ix: &ProposalInstruction) -> Self {
+        Instruction {
+            program_id: ix.program_id,
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|meta| AccountMeta { pubkey: meta.pubkey, is_signer: meta.is_signer, is_writable: meta.is_writable })
+                .collect(),
+            data: ix.data.clone(),
+        }
+    }
+}
+
+/// What a proposal says: the hash of its full text and where that text is
+/// published. The text itself only goes on-chain through `extend_proposal_body`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct ProposalContent {
+    /// SHA-256 of the full proposal text.
+    pub content_hash: [u8; 32],
+    pub uri: String,
+}
+
+/// Return data of `get_proposal_state`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct ProposalState {
+    pub status: ProposalStatus,
+    pub voting_ends_at: i64,
+    pub yes_weight: u64,
+    pub no_weight: u64,
+    pub abstain_weight: u64,
+    /// Zero until the proposal passes.
+    pub executable_at: i64,
+}
+
+/// The packed fields are followed by the optional full-text body, appended by
+/// `extend_proposal_body`; read it with `Proposal::body`.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct Proposal {
-    pub description: String,
+    pub uri: String,
+    pub content_hash: [u8; 32],
     pub proposer: Pubkey,
-    pub active: bool,
+    pub status: ProposalStatus,
     pub timestamp: i64,
+    pub voting_ends_at: i64,
+    pub yes_weight: u64,
+    pub no_weight: u64,
+    pub abstain_weight: u64,
+    /// Vote-escrow weight included in the tallies above. It counts towards
+    /// approval but not towards quorum.
+    pub escrow_weight: u64,
+    /// Ballot pages opened for bitmap-mode voting; zero for direct votes only.
+    pub ballot_pages: u16,
+    pub pages_tallied: u16,
+    /// Applied on execution; a treasury spend also waits out `TREASURY_SPEND_TIMELOCK_SECONDS`.
+    pub kind: ProposalKind,
+    /// GGT the proposer escrowed, held in the deposit vault until finalization.
+    pub deposit: u64,
+    /// Staking pool epoch and reward index when the proposal was created. Only
+    /// stake opened or topped up before `timestamp` counts towards its votes.
+    pub snapshot_epoch: u64,
+    pub snapshot_reward_index: u128,
+    /// When a passed proposal's timelock ends; zero until it passes. It can
+    /// execute until `EXECUTION_WINDOW_SECONDS` after this.
+    pub executable_at: i64,
     pub is_initialized: bool,
+    /// Instructions invoked, signed by the governance authority, on execution.
+    pub instructions: Vec<ProposalInstruction>,
 }
 
 impl Sealed for Proposal {}
 
+impl Proposal {
+    /// The full-text body appended behind the packed proposal in `data`.
+    pub fn body(data: &[u8]) -> &[u8] {
+        data.get(Self::LEN..).unwrap_or_default()
+    }
+
+    /// The running total that votes for `choice` are added to.
+    fn tally(&mut self, choice: VoteChoice) -> &mut u64 {
+        match choice {
+            VoteChoice::No => &mut self.no_weight,
+            VoteChoice::Yes => &mut self.yes_weight,
+            VoteChoice::Abstain => &mut self.abstain_weight,
+        }
+    }
+}
+
 impl IsInitialized for Proposal {
     fn is_initialized(&self) -> bool {
         self.is_initialized
     }
 }
 
+impl Discriminator for Proposal {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [26, 94, 189, 187, 116, 136, 53, 33];
+}
+
 impl Pack for Proposal {
-    const LEN: usize = 300; // Adjust based on max description length + fields
+    const LEN: usize = 445 + MAX_INSTRUCTIONS_LEN; // discriminator (8) + version (1) + max URI length + fields
     fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
         let mut cursor = 0;
-        let desc_bytes = self.description.as_bytes();
-        let desc_len = desc_bytes.len() as u32;
-        dst[cursor..cursor + 4].copy_from_slice(&desc_len.to_le_bytes());
+        let uri_bytes = self.uri.as_bytes();
+        let uri_len = uri_bytes.len() as u32;
+        dst[cursor..cursor + 4].copy_from_slice(&uri_len.to_le_bytes());
         cursor += 4;
-        dst[cursor..cursor + desc_bytes.len()].copy_from_slice(desc_bytes);
-        cursor += desc_bytes.len();
+        dst[cursor..cursor + uri_bytes.len()].copy_from_slice(uri_bytes);
+        cursor += uri_bytes.len();
+        dst[cursor..cursor + 32].copy_from_slice(&self.content_hash);
+        cursor += 32;
         dst[cursor..cursor + 32].copy_from_slice(self.proposer.as_ref());
         cursor += 32;
-        dst[cursor] = self.active as u8;
+        dst[cursor] = self.status as u8;
         cursor += 1;
         dst[cursor..cursor + 8].copy_from_slice(&self.timestamp.to_le_bytes());
         cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.voting_ends_at.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.yes_weight.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.no_weight.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.abstain_weight.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.escrow_weight.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 2].copy_from_slice(&self.ballot_pages.to_le_bytes());
+        cursor += 2;
+        dst[cursor..cursor + 2].copy_from_slice(&self.pages_tallied.to_le_bytes());
+        cursor += 2;
+        let kind_dst = &mut dst[cursor..cursor + ProposalKind::LEN];
+        kind_dst.fill(0);
+        borsh::to_writer(kind_dst, &self.kind).expect("every proposal kind fits ProposalKind::LEN");
+        cursor += ProposalKind::LEN;
+        dst[cursor..cursor + 8].copy_from_slice(&self.deposit.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.snapshot_epoch.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 16].copy_from_slice(&self.snapshot_reward_index.to_le_bytes());
+        cursor += 16;
+        dst[cursor..cursor + 8].copy_from_slice(&self.executable_at.to_le_bytes());
+        cursor += 8;
         dst[cursor] = self.is_initialized as u8;
+        cursor += 1;
+        let instructions_dst = &mut dst[cursor..cursor + MAX_INSTRUCTIONS_LEN];
+        instructions_dst.fill(0);
+        borsh::to_writer(instructions_dst, &self.instructions).expect("proposal instructions exceed MAX_INSTRUCTIONS_LEN");
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        if src.len() < 45 {
+        let src = read_discriminator::<Self>(src)?;
+        if src.len() < 236 + MAX_INSTRUCTIONS_LEN {
             return Err(ProgramError::InvalidAccountData);
         }
         let mut cursor = 0;
-        let desc_len = u32::from_le_bytes(src[cursor..cursor + 4].try_into().unwrap()) as usize;
+        let uri_len = u32::from_le_bytes(src[cursor..cursor + 4].try_into().unwrap()) as usize;
         cursor += 4;
-        if cursor + desc_len > src.len() {
+        if cursor + uri_len + 232 + MAX_INSTRUCTIONS_LEN > src.len() {
             return Err(ProgramError::InvalidAccountData);
         }
-        let description = String::from_utf8(src[cursor..cursor + desc_len].to_vec())
+        let uri = String::from_utf8(src[cursor..cursor + uri_len].to_vec())
             .map_err(|_| ProgramError::InvalidAccountData)?;
-        cursor += desc_len;
+        cursor += uri_len;
+        let content_hash = src[cursor..cursor + 32].try_into().unwrap();
+        cursor += 32;
         let proposer = Pubkey::new_from_array(src[cursor..cursor + 32].try_into().unwrap());
         cursor += 32;
-        let active = src[cursor] != 0;
+        let status = ProposalStatus::from_u8(src[cursor])?;
         cursor += 1;
         let timestamp = i64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
         cursor += 8;
+        let voting_ends_at = i64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let yes_weight = u64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let no_weight = u64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let abstain_weight = u64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let escrow_weight = u64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let ballot_pages = u16::from_le_bytes(src[cursor..cursor + 2].try_into().unwrap());
+        cursor += 2;
+        let pages_tallied = u16::from_le_bytes(src[cursor..cursor + 2].try_into().unwrap());
+        cursor += 2;
+        let kind = borsh::BorshDeserialize::deserialize(&mut &src[cursor..cursor + ProposalKind::LEN])
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        cursor += ProposalKind::LEN;
+        let deposit = u64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let snapshot_epoch = u64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let snapshot_reward_index = u128::from_le_bytes(src[cursor..cursor + 16].try_into().unwrap());
+        cursor += 16;
+        let executable_at = i64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
         let is_initialized = src[cursor] != 0;
+        cursor += 1;
+        let instructions = borsh::BorshDeserialize::deserialize(&mut &src[cursor..cursor + MAX_INSTRUCTIONS_LEN])
+            .map_err(|_| ProgramError::InvalidAccountData)?;
         Ok(Proposal {
-            description,
+            uri,
+            content_hash,
             proposer,
-            active,
+            status,
             timestamp,
+            voting_ends_at,
+            yes_weight,
+            no_weight,
+            abstain_weight,
+            escrow_weight,
+            ballot_pages,
+            pages_tallied,
+            kind,
+            deposit,
+            snapshot_epoch,
+            snapshot_reward_index,
+            executable_at,
             is_initialized,
+            instructions,
         })
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct Vote {
     pub proposal: Pubkey,
     pub voter: Pubkey,
-    pub vote: bool,
+    pub choice: VoteChoice,
     pub weight: u64,
     pub is_initialized: bool,
 }
@@ -95,15 +741,20 @@ impl IsInitialized for Vote {
     }
 }
 
+impl Discriminator for Vote {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [96, 91, 104, 57, 145, 35, 172, 155];
+}
+
 impl Pack for Vote {
-    const LEN: usize = 73; // Pubkey (32) + Pubkey (32) + bool (1) + u64 (8) + bool (1)
+    const LEN: usize = 83; // discriminator (8) + version (1) + Pubkey (32) + Pubkey (32) + VoteChoice (1) + u64 (8) + bool (1)
     fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
         let mut cursor = 0;
         dst[cursor..cursor + 32].copy_from_slice(self.proposal.as_ref());
         cursor += 32;
         dst[cursor..cursor + 32].copy_from_slice(self.voter.as_ref());
         cursor += 32;
-        dst[cursor] = self.vote as u8;
+        dst[cursor] = self.choice as u8;
         cursor += 1;
         dst[cursor..cursor + 8].copy_from_slice(&self.weight.to_le_bytes());
         cursor += 8;
@@ -111,15 +762,13 @@ impl Pack for Vote {
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        if src.len() < Self::LEN {
-            return Err(ProgramError::InvalidAccountData);
-        }
+        let src = read_discriminator::<Self>(src)?;
         let mut cursor = 0;
         let proposal = Pubkey::new_from_array(src[cursor..cursor + 32].try_into().unwrap());
         cursor += 32;
         let voter = Pubkey::new_from_array(src[cursor..cursor + 32].try_into().unwrap());
         cursor += 32;
-        let vote = src[cursor] != 0;
+        let choice = VoteChoice::from_u8(src[cursor])?;
         cursor += 1;
         let weight = u64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
         cursor += 8;
@@ -127,90 +776,2098 @@ impl Pack for Vote {
         Ok(Vote {
             proposal,
             voter,
-            vote,
+            choice,
             weight,
             is_initialized,
         })
     }
 }
 
-pub struct GovernanceContract;
+/// Hands out proposal IDs in creation order, starting from 1.
+pub struct ProposalCounter {
+    pub next_id: u64,
+    pub is_initialized: bool,
+}
 
-impl GovernanceContract {
-    pub fn create_proposal(_program_id: &Pubkey, accounts: &[AccountInfo], description: &str) -> ProgramResult {
-        let account_info_iter = &mut accounts.iter();
-        let proposal_acc = next_account_info(account_info_iter)?;
-        let proposer_acc = next_account_info(account_info_iter)?;
+impl Sealed for ProposalCounter {}
 
-        if !proposer_acc.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
+impl IsInitialized for ProposalCounter {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
 
-        let proposal = Proposal {
-            description: description.to_string(),
-            proposer: *proposer_acc.key,
-            active: true,
-            timestamp: Clock::get()?.unix_timestamp,
-            is_initialized: true,
-        };
-        let mut proposal_data = proposal_acc.try_borrow_mut_data()?;
-        proposal.pack_into_slice(&mut proposal_data);
-        msg!("Created proposal: {}", description);
-        Ok(())
+impl Discriminator for ProposalCounter {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [110, 92, 147, 182, 142, 28, 182, 5];
+}
+
+impl Pack for ProposalCounter {
+    const LEN: usize = 18; // discriminator (8) + version (1) + u64 (8) + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[..8].copy_from_slice(&self.next_id.to_le_bytes());
+        dst[8] = self.is_initialized as u8;
     }
 
-    pub fn execute_proposal(_program_id: &Pubkey, accounts: &[AccountInfo], _proposal_id: u64) -> ProgramResult {
-        let account_info_iter = &mut accounts.iter();
-        let proposal_acc = next_account_info(account_info_iter)?;
-        let authority_acc = next_account_info(account_info_iter)?;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(ProposalCounter {
+            next_id: u64::from_le_bytes(src[..8].try_into().unwrap()),
+            is_initialized: src[8] != 0,
+        })
+    }
+}
 
-        if authority_acc.key != &ADMIN_PUBKEY && authority_acc.key != &GOVERNANCE_PUBKEY {
-            return Err(ProgramError::IllegalOwner);
+/// One page of bitmap-mode ballots. Voters are registered in slot order; bit `i`
+/// of `in_favor` holds the choice of `voters[i]`.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct BallotPage {
+    pub proposal: Pubkey,
+    pub page_index: u16,
+    pub voters: Vec<Pubkey>,
+    pub in_favor: u32,
+    pub weights: Vec<u64>,
+    /// Vote-escrow weight included in `weights`, added to the proposal's `escrow_weight` on tally.
+    pub escrow_weight: u64,
+    pub tallied: bool,
+    pub is_initialized: bool,
+}
+
+impl BallotPage {
+    fn yes_no_weights(&self) -> (u64, u64) {
+        self.weights.iter().enumerate().fold((0u64, 0u64), |(yes, no), (slot, weight)| {
+            if self.in_favor & (1 << slot) != 0 {
+                (yes.saturating_add(*weight), no)
+            } else {
+                (yes, no.saturating_add(*weight))
+            }
+        })
+    }
+}
+
+impl Sealed for BallotPage {}
+
+impl IsInitialized for BallotPage {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for BallotPage {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [32, 90, 233, 74, 96, 180, 171, 28];
+}
+
+impl Pack for BallotPage {
+    const LEN: usize = 1338; // discriminator (8) + version (1) + Pubkey (32) + u16 (2) + count (1) + Pubkey (32) * 32 + u32 (4) + u64 (8) * 33 + bool (1) * 2
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        let mut cursor = 0;
+        dst[cursor..cursor + 32].copy_from_slice(self.proposal.as_ref());
+        cursor += 32;
+        dst[cursor..cursor + 2].copy_from_slice(&self.page_index.to_le_bytes());
+        cursor += 2;
+        dst[cursor] = self.voters.len() as u8;
+        cursor += 1;
+        for slot in 0..BALLOTS_PER_PAGE {
+            let voter = self.voters.get(slot).copied().unwrap_or_default();
+            dst[cursor..cursor + 32].copy_from_slice(voter.as_ref());
+            cursor += 32;
         }
-        if !authority_acc.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+        dst[cursor..cursor + 4].copy_from_slice(&self.in_favor.to_le_bytes());
+        cursor += 4;
+        for slot in 0..BALLOTS_PER_PAGE {
+            let weight = self.weights.get(slot).copied().unwrap_or(0);
+            dst[cursor..cursor + 8].copy_from_slice(&weight.to_le_bytes());
+            cursor += 8;
         }
+        dst[cursor..cursor + 8].copy_from_slice(&self.escrow_weight.to_le_bytes());
+        cursor += 8;
+        dst[cursor] = self.tallied as u8;
+        cursor += 1;
+        dst[cursor] = self.is_initialized as u8;
+    }
 
-        let mut proposal = Proposal::unpack(&proposal_acc.try_borrow_data()?)?;
-        if !proposal.active {
-            return Err(ProgramError::InvalidArgument);
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let mut cursor = 0;
+        let proposal = Pubkey::new_from_array(src[cursor..cursor + 32].try_into().unwrap());
+        cursor += 32;
+        let page_index = u16::from_le_bytes(src[cursor..cursor + 2].try_into().unwrap());
+        cursor += 2;
+        let count = src[cursor] as usize;
+        cursor += 1;
+        if count > BALLOTS_PER_PAGE {
+            return Err(ProgramError::InvalidAccountData);
         }
-        proposal.active = false;
-        let mut proposal_data = proposal_acc.try_borrow_mut_data()?;
-        proposal.pack_into_slice(&mut proposal_data);
-        msg!("Executing proposal with ID: {}", _proposal_id);
-        Ok(())
+        let voters = (0..count)
+            .map(|slot| Pubkey::new_from_array(src[cursor + slot * 32..cursor + (slot + 1) * 32].try_into().unwrap()))
+            .collect();
+        cursor += 32 * BALLOTS_PER_PAGE;
+        let in_favor = u32::from_le_bytes(src[cursor..cursor + 4].try_into().unwrap());
+        cursor += 4;
+        let weights = (0..count)
+            .map(|slot| u64::from_le_bytes(src[cursor + slot * 8..cursor + (slot + 1) * 8].try_into().unwrap()))
+            .collect();
+        cursor += 8 * BALLOTS_PER_PAGE;
+        let escrow_weight = u64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let tallied = src[cursor] != 0;
+        cursor += 1;
+        let is_initialized = src[cursor] != 0;
+        Ok(BallotPage {
+            proposal,
+            page_index,
+            voters,
+            in_favor,
+            weights,
+            escrow_weight,
+            tallied,
+            is_initialized,
+        })
     }
+}
+
+pub struct InitializeGovernanceConfigAccounts<'a, 'info> {
+    pub config_acc: &'a AccountInfo<'info>,
+    pub admin_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub pool_state_acc: &'a AccountInfo<'info>,
+    pub config_bump: u8,
+}
 
-    pub fn vote_on_proposal(_program_id: &Pubkey, accounts: &[AccountInfo], _proposal_id: u64, vote_in_favor: bool) -> ProgramResult {
+impl<'a, 'info> InitializeGovernanceConfigAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let config_acc = next_account_info(account_info_iter)?;
+        let admin_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+        let pool_state_acc = next_account_info(account_info_iter)?;
+
+        multisig::check_admin_signer(program_id, admin_acc)?;
+        assert_owned_by(pool_state_acc, program_id)?;
+        let config_bump = assert_pda(config_acc, governance_config_address(program_id))?;
+        Ok(Self { config_acc, admin_acc, system_program_acc, pool_state_acc, config_bump })
+    }
+}
+
+/// `pool_state_acc` is the configured staking pool, snapshotted for voting.
+/// `proposal_acc` is the proposal PDA for the counter's next ID, which the
+/// proposer pays to create. When governance requires a deposit,
+/// `remaining_accs` holds the proposer's token account, the deposit vault and
+/// the token program.
+pub struct CreateProposalAccounts<'a, 'info> {
+    pub proposal_acc: &'a AccountInfo<'info>,
+    pub proposer_acc: &'a AccountInfo<'info>,
+    pub config_acc: &'a AccountInfo<'info>,
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub pool_state_acc: &'a AccountInfo<'info>,
+    pub counter_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub remaining_accs: &'a [AccountInfo<'info>],
+    pub counter_bump: u8,
+}
+
+impl<'a, 'info> CreateProposalAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
         let account_info_iter = &mut accounts.iter();
-        let vote_acc = next_account_info(account_info_iter)?;
-        let voter_acc = next_account_info(account_info_iter)?;
         let proposal_acc = next_account_info(account_info_iter)?;
+        let proposer_acc = next_account_info(account_info_iter)?;
+        let config_acc = next_account_info(account_info_iter)?;
         let staking_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let pool_state_acc = next_account_info(account_info_iter)?;
+        let counter_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
 
-        if !voter_acc.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
+        assert_signer(proposer_acc)?;
+        let counter_bump = assert_pda(counter_acc, proposal_counter_address(program_id))?;
+        Ok(Self {
+            proposal_acc,
+            proposer_acc,
+            config_acc,
+            staking_acc,
+            mint_acc,
+            pool_state_acc,
+            counter_acc,
+            system_program_acc,
+            remaining_accs: account_info_iter.as_slice(),
+            counter_bump,
+        })
+    }
+}
 
-        let proposal = Proposal::unpack(&proposal_acc.try_borrow_data()?)?;
-        if !proposal.active {
-            return Err(ProgramError::InvalidArgument);
-        }
+/// `proposer_acc` pays the rent for the appended body.
+pub struct ExtendProposalBodyAccounts<'a, 'info> {
+    pub proposal_acc: &'a AccountInfo<'info>,
+    pub proposer_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+}
 
-        let staking_contract = StakingContract::new();
-        let staked_amount = staking_contract.get_staked_amount(staking_acc).unwrap_or(0);
+impl<'a, 'info> ExtendProposalBodyAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], proposal_id: u64) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let proposal_acc = next_account_info(account_info_iter)?;
+        let proposer_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
 
+        assert_signer(proposer_acc)?;
+        assert_owned_by(proposal_acc, program_id)?;
+        assert_pda(proposal_acc, proposal_address(program_id, proposal_id))?;
+        Ok(Self { proposal_acc, proposer_acc, system_program_acc })
+    }
+}
+
+pub struct ProposalStateAccounts<'a, 'info> {
+    pub proposal_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ProposalStateAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], proposal_id: u64) -> Result<Self, ProgramError> {
+        let proposal_acc = next_account_info(&mut accounts.iter())?;
+        assert_owned_by(proposal_acc, program_id)?;
+        assert_pda(proposal_acc, proposal_address(program_id, proposal_id))?;
+        Ok(Self { proposal_acc })
+    }
+}
+
+/// The executing authority follows `proposal_acc`, then the program config it
+/// is checked against; neither is kept. Which of `remaining_accs` are expected
+/// depends on what the proposal carries.
+pub struct ExecuteProposalAccounts<'a, 'info> {
+    pub proposal_acc: &'a AccountInfo<'info>,
+    pub remaining_accs: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> ExecuteProposalAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], proposal_id: u64) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let proposal_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let program_config_acc = next_account_info(account_info_iter)?;
+
+        if multisig::check_admin_signer(program_id, authority_acc).is_err() {
+            config::check_authority(program_id, program_config_acc, &[AuthorityRole::Governance], authority_acc)?;
+        }
+        assert_owned_by(proposal_acc, program_id)?;
+        assert_pda(proposal_acc, proposal_address(program_id, proposal_id))?;
+        Ok(Self { proposal_acc, remaining_accs: account_info_iter.as_slice() })
+    }
+}
+
+/// The voter's vote-escrow lock may follow `mint_acc`; any further accounts
+/// are more stakes delegated to the voter, weighed together with `staking_acc`.
+pub struct VoteAccounts<'a, 'info> {
+    pub vote_acc: &'a AccountInfo<'info>,
+    pub voter_acc: &'a AccountInfo<'info>,
+    pub proposal_acc: &'a AccountInfo<'info>,
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub vote_escrow_acc: Option<&'a AccountInfo<'info>>,
+    pub delegated_accs: &'a [AccountInfo<'info>],
+    pub vote_bump: u8,
+}
+
+impl<'a, 'info> VoteAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], proposal_id: u64) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let vote_acc = next_account_info(account_info_iter)?;
+        let voter_acc = next_account_info(account_info_iter)?;
+        let proposal_acc = next_account_info(account_info_iter)?;
+        let staking_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let (vote_escrow_acc, delegated_accs) = split_vote_escrow(program_id, account_info_iter.as_slice());
+
+        assert_signer(voter_acc)?;
+        assert_owned_by(proposal_acc, program_id)?;
+        assert_pda(proposal_acc, proposal_address(program_id, proposal_id))?;
+        assert_ggt_mint(program_id, mint_acc)?;
+        let vote_bump = assert_pda(vote_acc, vote_address(program_id, proposal_acc.key, voter_acc.key))?;
+        Ok(Self { vote_acc, voter_acc, proposal_acc, staking_acc, system_program_acc, mint_acc, vote_escrow_acc, delegated_accs, vote_bump })
+    }
+}
+
+pub struct UpdateVoteAccounts<'a, 'info> {
+    pub vote_acc: &'a AccountInfo<'info>,
+    pub voter_acc: &'a AccountInfo<'info>,
+    pub proposal_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> UpdateVoteAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], proposal_id: u64) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let vote_acc = next_account_info(account_info_iter)?;
+        let voter_acc = next_account_info(account_info_iter)?;
+        let proposal_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(voter_acc)?;
+        assert_owned_by(proposal_acc, program_id)?;
+        assert_pda(proposal_acc, proposal_address(program_id, proposal_id))?;
+        assert_owned_by(vote_acc, program_id)?;
+        assert_pda(vote_acc, vote_address(program_id, proposal_acc.key, voter_acc.key))?;
+        Ok(Self { vote_acc, voter_acc, proposal_acc })
+    }
+}
+
+/// A proposal holding a deposit takes `DepositReleaseAccounts` in `remaining_accs`.
+pub struct FinalizeProposalAccounts<'a, 'info> {
+    pub proposal_acc: &'a AccountInfo<'info>,
+    pub config_acc: &'a AccountInfo<'info>,
+    pub pool_state_acc: &'a AccountInfo<'info>,
+    pub remaining_accs: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> FinalizeProposalAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], proposal_id: u64) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            proposal_acc: next_account_info(account_info_iter)?,
+            config_acc: next_account_info(account_info_iter)?,
+            pool_state_acc: next_account_info(account_info_iter)?,
+            remaining_accs: account_info_iter.as_slice(),
+        };
+        assert_owned_by(ctx.proposal_acc, program_id)?;
+        assert_pda(ctx.proposal_acc, proposal_address(program_id, proposal_id))?;
+        assert_owned_by(ctx.pool_state_acc, program_id)?;
+        Ok(ctx)
+    }
+}
+
+/// The cancelling authority follows `proposal_acc`, then the program config it
+/// is checked against; neither is kept. It is the admin, or the governance
+/// signer or authority PDA. A proposal holding a deposit takes
+/// `DepositReleaseAccounts` in `remaining_accs`.
+pub struct CancelProposalAccounts<'a, 'info> {
+    pub proposal_acc: &'a AccountInfo<'info>,
+    pub remaining_accs: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> CancelProposalAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let proposal_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let program_config_acc = next_account_info(account_info_iter)?;
+
+        if multisig::check_admin_signer(program_id, authority_acc).is_err() {
+            check_governance_signer(program_id, program_config_acc, authority_acc)?;
+        }
+        assert_owned_by(proposal_acc, program_id)?;
+        Ok(Self { proposal_acc, remaining_accs: account_info_iter.as_slice() })
+    }
+}
+
+/// Accounts that pay a deposit out of escrow: the deposit vault, the deposit
+/// authority PDA, the destination token account, the mint and the token program.
+pub struct DepositReleaseAccounts<'a, 'info> {
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub destination_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub authority_bump: u8,
+}
+
+impl<'a, 'info> DepositReleaseAccounts<'a, 'info> {
+    /// `destination_owner` is the proposer for a refund and the treasury
+    /// authority for a slash.
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], destination_owner: &Pubkey) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let vault_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let destination_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+
+        let authority_bump = assert_pda(authority_acc, proposal_deposit_authority(program_id))?;
+        check_deposit_vault(program_id, vault_acc, mint_acc.key)?;
+        if assert_token_account_mint(destination_acc, mint_acc.key)?.owner != *destination_owner {
+            msg!("Deposit must go to a token account owned by {}", destination_owner);
+            return Err(ProgramError::IllegalOwner);
+        }
+        Ok(Self { vault_acc, authority_acc, destination_acc, mint_acc, token_program_acc, authority_bump })
+    }
+}
+
+pub struct OpenBallotPageAccounts<'a, 'info> {
+    pub page_acc: &'a AccountInfo<'info>,
+    pub proposal_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> OpenBallotPageAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self { page_acc: next_account_info(account_info_iter)?, proposal_acc: next_account_info(account_info_iter)? };
+        assert_owned_by(ctx.page_acc, program_id)?;
+        check_proposal_account(program_id, ctx.proposal_acc)?;
+        Ok(ctx)
+    }
+}
+
+/// Checks `proposal_acc` is a proposal account of this program. Ballot
+/// instructions find the proposal through its page rather than by id.
+fn check_proposal_account(program_id: &Pubkey, proposal_acc: &AccountInfo) -> ProgramResult {
+    assert_owned_by(proposal_acc, program_id)?;
+    read_discriminator::<Proposal>(&proposal_acc.try_borrow_data()?)?;
+    Ok(())
+}
+
+/// The voter's stake is weighed as in `VoteAccounts`, and `vote_acc` is the
+/// same per-voter record, so a voter casts one vote per proposal across
+/// ballot pages and direct votes.
+/// More stakes delegated to the voter may follow `mint_acc`, weighed
+/// together with `staking_acc`.
+/// As in [`VoteAccounts`], the voter's vote-escrow lock may follow `mint_acc`,
+/// ahead of further delegated stakes.
+pub struct CastBallotAccounts<'a, 'info> {
+    pub page_acc: &'a AccountInfo<'info>,
+    pub voter_acc: &'a AccountInfo<'info>,
+    pub proposal_acc: &'a AccountInfo<'info>,
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub vote_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub vote_escrow_acc: Option<&'a AccountInfo<'info>>,
+    pub delegated_accs: &'a [AccountInfo<'info>],
+    pub vote_bump: u8,
+}
+
+impl<'a, 'info> CastBallotAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let page_acc = next_account_info(account_info_iter)?;
+        let voter_acc = next_account_info(account_info_iter)?;
+        let proposal_acc = next_account_info(account_info_iter)?;
+        let staking_acc = next_account_info(account_info_iter)?;
+        let vote_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let (vote_escrow_acc, delegated_accs) = split_vote_escrow(program_id, account_info_iter.as_slice());
+
+        assert_signer(voter_acc)?;
+        assert_owned_by(page_acc, program_id)?;
+        check_proposal_account(program_id, proposal_acc)?;
+        assert_ggt_mint(program_id, mint_acc)?;
+        let vote_bump = assert_pda(vote_acc, vote_address(program_id, proposal_acc.key, voter_acc.key))?;
+        Ok(Self {
+            page_acc,
+            voter_acc,
+            proposal_acc,
+            staking_acc,
+            vote_acc,
+            system_program_acc,
+            mint_acc,
+            vote_escrow_acc,
+            delegated_accs,
+            vote_bump,
+        })
+    }
+}
+
+pub struct TallyPageAccounts<'a, 'info> {
+    pub proposal_acc: &'a AccountInfo<'info>,
+    pub page_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> TallyPageAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self { proposal_acc: next_account_info(account_info_iter)?, page_acc: next_account_info(account_info_iter)? };
+        check_proposal_account(program_id, ctx.proposal_acc)?;
+        assert_owned_by(ctx.page_acc, program_id)?;
+        Ok(ctx)
+    }
+}
+
+pub struct SetUpgradeAuthorityAccounts<'a, 'info> {
+    pub program_data_acc: &'a AccountInfo<'info>,
+    pub current_authority_acc: &'a AccountInfo<'info>,
+    pub governance_authority_acc: &'a AccountInfo<'info>,
+    pub loader_acc: &'a AccountInfo<'info>,
+    pub governance_authority_bump: u8,
+}
+
+impl<'a, 'info> SetUpgradeAuthorityAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let program_data_acc = next_account_info(account_info_iter)?;
+        let current_authority_acc = next_account_info(account_info_iter)?;
+        let governance_authority_acc = next_account_info(account_info_iter)?;
+        let loader_acc = next_account_info(account_info_iter)?;
+        assert_signer(current_authority_acc)?;
+        if *program_data_acc.key != bpf_loader_upgradeable::get_program_data_address(program_id) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if !bpf_loader_upgradeable::check_id(loader_acc.key) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let governance_authority_bump = assert_pda(governance_authority_acc, governance_authority_address(program_id))?;
+        Ok(Self { program_data_acc, current_authority_acc, governance_authority_acc, loader_acc, governance_authority_bump })
+    }
+}
+
+pub struct GovernanceContract;
+
+impl GovernanceContract {
+    /// Creates the singleton governance config with its initial rules, binding
+    /// quorum to the staking pool passed after the system program.
+    pub fn initialize_governance_config(program_id: &Pubkey, accounts: &[AccountInfo], params: GovernanceParams) -> ProgramResult {
+        let InitializeGovernanceConfigAccounts { config_acc, admin_acc, system_program_acc, pool_state_acc, config_bump } =
+            InitializeGovernanceConfigAccounts::try_from(program_id, accounts)?;
+        if config_acc.lamports() > 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        params.validate()?;
+        load::<PodStakingPool>(&pool_state_acc.try_borrow_data()?)?;
+
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_acc.key,
+                config_acc.key,
+                Rent::get()?.minimum_balance(GovernanceConfig::LEN),
+                GovernanceConfig::LEN as u64,
+                program_id,
+            ),
+            &[admin_acc.clone(), config_acc.clone(), system_program_acc.clone()],
+            &[&[GOVERNANCE_CONFIG_SEED, &[config_bump]]],
+        )?;
+        let config = GovernanceConfig { params, staking_pool: *pool_state_acc.key, is_initialized: true };
+        pack_state(&config, config_acc)?;
+        msg!("Governance config initialized");
+        Ok(())
+    }
+
+    /// Opens a proposal voting for the configured period. The proposer must hold at
+    /// least `min_stake_to_propose` in their stake account for `mint`. Its `kind`
+    /// is applied when it executes, and any `instructions` are then invoked by
+    /// the governance authority. The proposal takes the counter's next ID and is
+    /// created at that ID's address; the counter itself is created on first use.
+    pub fn create_proposal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        content: ProposalContent,
+        kind: ProposalKind,
+        instructions: Vec<ProposalInstruction>,
+    ) -> ProgramResult {
+        let CreateProposalAccounts {
+            proposal_acc,
+            proposer_acc,
+            config_acc,
+            staking_acc,
+            mint_acc,
+            pool_state_acc,
+            counter_acc,
+            system_program_acc,
+            remaining_accs,
+            counter_bump,
+        } = CreateProposalAccounts::try_from(program_id, accounts)?;
+        if content.uri.len() > MAX_URI_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if let ProposalKind::GovernanceRules(params) = &kind {
+            params.validate()?;
+        }
+        if borsh::object_length(&instructions).map_err(|_| ProgramError::InvalidInstructionData)? > MAX_INSTRUCTIONS_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let config = load_governance_config(program_id, config_acc)?;
+        check_stake_account(program_id, staking_acc, proposer_acc.key, mint_acc.key)?;
+        let staked_amount = StakingContract::new().get_staked_amount(staking_acc)?;
+        if staked_amount < config.params.min_stake_to_propose {
+            msg!("Proposer stake {} is below the minimum {}", staked_amount, config.params.min_stake_to_propose);
+            return Err(GgtError::InsufficientStakeToPropose.into());
+        }
+        if *pool_state_acc.key != config.staking_pool {
+            msg!("Votes are snapshotted against staking pool {}", config.staking_pool);
+            return Err(ProgramError::InvalidArgument);
+        }
+        assert_owned_by(pool_state_acc, program_id)?;
+        let PodStakingPool { epoch, reward_per_token_stored, .. } = *load::<PodStakingPool>(&pool_state_acc.try_borrow_data()?)?;
+        let deposit = config.params.proposal_deposit;
+        if deposit > 0 {
+            let account_info_iter = &mut remaining_accs.iter();
+            let proposer_token_acc = next_account_info(account_info_iter)?;
+            let vault_acc = next_account_info(account_info_iter)?;
+            let token_program_acc = next_account_info(account_info_iter)?;
+            check_deposit_vault(program_id, vault_acc, mint_acc.key)?;
+            token_program::transfer(token_program_acc, proposer_token_acc, mint_acc, vault_acc, proposer_acc, deposit, &[])?;
+            msg!("Escrowed a proposal deposit of {}", deposit);
+        }
+
+        let rent = Rent::get()?;
+        if counter_acc.lamports() == 0 {
+            invoke_signed(
+                &system_instruction::create_account(
+                    proposer_acc.key,
+                    counter_acc.key,
+                    rent.minimum_balance(ProposalCounter::LEN),
+                    ProposalCounter::LEN as u64,
+                    program_id,
+                ),
+                &[proposer_acc.clone(), counter_acc.clone(), system_program_acc.clone()],
+                &[&[PROPOSAL_COUNTER_SEED, &[counter_bump]]],
+            )?;
+            pack_state(&ProposalCounter { next_id: 1, is_initialized: true }, counter_acc)?;
+        }
+        assert_owned_by(counter_acc, program_id)?;
+        let mut counter = ProposalCounter::unpack(&counter_acc.try_borrow_data()?)?;
+        let proposal_id = counter.next_id;
+        let proposal_bump = assert_pda(proposal_acc, proposal_address(program_id, proposal_id))?;
+        if proposal_acc.lamports() > 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        invoke_signed(
+            &system_instruction::create_account(
+                proposer_acc.key,
+                proposal_acc.key,
+                rent.minimum_balance(Proposal::LEN),
+                Proposal::LEN as u64,
+                program_id,
+            ),
+            &[proposer_acc.clone(), proposal_acc.clone(), system_program_acc.clone()],
+            &[&[PROPOSAL_SEED, &proposal_id.to_le_bytes(), &[proposal_bump]]],
+        )?;
+        counter.next_id = proposal_id.checked_add(1).ok_or(GgtError::MathOverflow)?;
+        pack_state(&counter, counter_acc)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = Proposal {
+            uri: content.uri,
+            content_hash: content.content_hash,
+            proposer: *proposer_acc.key,
+            status: ProposalStatus::Active,
+            timestamp: now,
+            voting_ends_at: now + config.params.voting_period_seconds,
+            yes_weight: 0,
+            no_weight: 0,
+            abstain_weight: 0,
+            escrow_weight: 0,
+            ballot_pages: 0,
+            pages_tallied: 0,
+            kind,
+            deposit,
+            snapshot_epoch: epoch,
+            snapshot_reward_index: reward_per_token_stored,
+            executable_at: 0,
+            is_initialized: true,
+            instructions,
+        };
+        pack_state(&proposal, proposal_acc)?;
+        events::emit(&events::ProposalCreated {
+            proposal: *proposal_acc.key,
+            proposer: *proposer_acc.key,
+            voting_ends_at: proposal.voting_ends_at,
+            content_hash: proposal.content_hash,
+            uri: proposal.uri,
+        });
+        msg!("Created proposal {}", proposal_id);
+        Ok(())
+    }
+
+    /// Appends `chunk` to the full text kept behind an active proposal, growing
+    /// the account and topping up its rent from the proposer. Readers check the
+    /// finished body against the proposal's `content_hash`.
+    pub fn extend_proposal_body(program_id: &Pubkey, accounts: &[AccountInfo], proposal_id: u64, chunk: &[u8]) -> ProgramResult {
+        let ExtendProposalBodyAccounts { proposal_acc, proposer_acc, system_program_acc } =
+            ExtendProposalBodyAccounts::try_from(program_id, accounts, proposal_id)?;
+        let proposal = load_proposal(proposal_acc)?;
+        if proposal.proposer != *proposer_acc.key {
+            msg!("Only proposer {} can extend proposal {}", proposal.proposer, proposal_id);
+            return Err(ProgramError::InvalidArgument);
+        }
+        if proposal.status != ProposalStatus::Active {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let body_len = Proposal::body(&proposal_acc.try_borrow_data()?).len();
+        if chunk.is_empty() || body_len + chunk.len() > MAX_PROPOSAL_BODY_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let new_len = proposal_acc.data_len() + chunk.len();
+        let shortfall = Rent::get()?.minimum_balance(new_len).saturating_sub(proposal_acc.lamports());
+        if shortfall > 0 {
+            invoke(
+                &system_instruction::transfer(proposer_acc.key, proposal_acc.key, shortfall),
+                &[proposer_acc.clone(), proposal_acc.clone(), system_program_acc.clone()],
+            )?;
+        }
+        proposal_acc.realloc(new_len, false)?;
+        proposal_acc.try_borrow_mut_data()?[new_len - chunk.len()..].copy_from_slice(chunk);
+        msg!("Proposal {} body is {} bytes", proposal_id, body_len + chunk.len());
+        Ok(())
+    }
+
+    /// Read-only query: publishes the proposal's `ProposalState` as Borsh return data.
+    pub fn get_proposal_state(program_id: &Pubkey, accounts: &[AccountInfo], proposal_id: u64) -> ProgramResult {
+        let ProposalStateAccounts { proposal_acc } = ProposalStateAccounts::try_from(program_id, accounts, proposal_id)?;
+        let proposal = load_proposal(proposal_acc)?;
+        let state = ProposalState {
+            status: proposal.status,
+            voting_ends_at: proposal.voting_ends_at,
+            yes_weight: proposal.yes_weight,
+            no_weight: proposal.no_weight,
+            abstain_weight: proposal.abstain_weight,
+            executable_at: proposal.executable_at,
+        };
+        crate::set_borsh_return_data(&state);
+        Ok(())
+    }
+
+    /// Marks a passed proposal executed, between the end of its timelock and
+    /// the close of its execution window. After the program config, the accounts
+    /// its kind applies to follow:
+    /// - `ParameterChange`: the governance, staking or bridge config holding the field.
+    /// - `TreasurySpend`: the treasury assets registry, vault, recipient, treasury
+    ///   authority and token program; the spend also waits out its own timelock.
+    /// - `CodeUpgrade`: the program data, program, buffer and spill accounts, the
+    ///   rent and clock sysvars, the governance authority PDA and the upgradeable loader.
+    /// - `GovernanceRules`: the governance config.
+    /// - `TokenBurn`: the token account, mint, governance authority PDA and token program.
+    ///
+    /// A proposal with instructions then takes the governance authority PDA,
+    /// followed by every account its instructions reference.
+    pub fn execute_proposal(program_id: &Pubkey, accounts: &[AccountInfo], proposal_id: u64) -> ProgramResult {
+        let ExecuteProposalAccounts { proposal_acc, remaining_accs } = ExecuteProposalAccounts::try_from(program_id, accounts, proposal_id)?;
+        let account_info_iter = &mut remaining_accs.iter();
+
+        let mut proposal = load_proposal(proposal_acc)?;
+        let now = Clock::get()?.unix_timestamp;
+        if now < proposal.voting_ends_at {
+            msg!("Proposal {} cannot execute before voting ends", proposal_id);
+            return Err(GgtError::VotingStillOpen.into());
+        }
+        if proposal.status != ProposalStatus::Passed {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if now < proposal.executable_at {
+            msg!("Proposal {} is timelocked until {}", proposal_id, proposal.executable_at);
+            return Err(GgtError::TimelockNotElapsed.into());
+        }
+        if now >= proposal.executable_at.saturating_add(EXECUTION_WINDOW_SECONDS) {
+            msg!("Proposal {} expired unexecuted", proposal_id);
+            return Err(GgtError::ProposalExpired.into());
+        }
+        match proposal.kind {
+            ProposalKind::Text => {}
+            ProposalKind::ParameterChange(change) => change.apply(program_id, next_account_info(account_info_iter)?, now)?,
+            ProposalKind::TreasurySpend(spend) => {
+                let unlocks_at = proposal.voting_ends_at + TREASURY_SPEND_TIMELOCK_SECONDS;
+                if now < unlocks_at {
+                    msg!("Treasury spend is timelocked until {}", unlocks_at);
+                    return Err(GgtError::TimelockNotElapsed.into());
+                }
+                let assets_acc = next_account_info(account_info_iter)?;
+                let vault_acc = next_account_info(account_info_iter)?;
+                let recipient_acc = next_account_info(account_info_iter)?;
+                let treasury_authority_acc = next_account_info(account_info_iter)?;
+                let token_program_acc = next_account_info(account_info_iter)?;
+                if *vault_acc.key != spend.vault || *recipient_acc.key != spend.recipient {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                Treasury::pay_out(program_id, assets_acc, vault_acc, recipient_acc, treasury_authority_acc, token_program_acc, spend.amount)?;
+            }
+            ProposalKind::CodeUpgrade(upgrade) => Self::upgrade_program(program_id, account_info_iter, &upgrade)?,
+            ProposalKind::GovernanceRules(params) => {
+                let config_acc = next_account_info(account_info_iter)?;
+                let mut config = load_governance_config(program_id, config_acc)?;
+                config.params = params;
+                pack_state(&config, config_acc)?;
+                msg!("Applied governance config change");
+            }
+            ProposalKind::TokenBurn(burn) => {
+                let token_acc = next_account_info(account_info_iter)?;
+                let mint_acc = next_account_info(account_info_iter)?;
+                let governance_authority_acc = next_account_info(account_info_iter)?;
+                let token_program_acc = next_account_info(account_info_iter)?;
+                if *token_acc.key != burn.token_account {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                let bump = assert_pda(governance_authority_acc, governance_authority_address(program_id))?;
+                token_program::burn(
+                    token_program_acc,
+                    token_acc,
+                    mint_acc,
+                    governance_authority_acc,
+                    burn.amount,
+                    &[&[GOVERNANCE_AUTHORITY_SEED, &[bump]]],
+                )?;
+                msg!("Burned {} tokens from {}", burn.amount, token_acc.key);
+            }
+        }
+        let governance_authority = if proposal.instructions.is_empty() {
+            None
+        } else {
+            let governance_authority_acc = next_account_info(account_info_iter)?;
+            let bump = assert_pda(governance_authority_acc, governance_authority_address(program_id))?;
+            Some((governance_authority_acc, bump))
+        };
+        proposal.status = ProposalStatus::Executed;
+        pack_state(&proposal, proposal_acc)?;
+
+        if let Some((governance_authority_acc, bump)) = governance_authority {
+            let mut account_infos = vec![governance_authority_acc.clone()];
+            account_infos.extend(account_info_iter.cloned());
+            for ix in proposal.instructions.iter() {
+                invoke_signed(&Instruction::from(ix), &account_infos, &[&[GOVERNANCE_AUTHORITY_SEED, &[bump]]])?;
+            }
+            msg!("Invoked {} proposal instructions", proposal.instructions.len());
+        }
+        msg!("Executing proposal with ID: {}", proposal_id);
+        Ok(())
+    }
+
+    /// Has the upgradeable loader replace this program's code with `upgrade.buffer`.
+    fn upgrade_program<'a, 'info: 'a>(
+        program_id: &Pubkey,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'info>>,
+        upgrade: &CodeUpgrade,
+    ) -> ProgramResult {
+        let program_data_acc = next_account_info(account_info_iter)?;
+        let program_acc = next_account_info(account_info_iter)?;
+        let buffer_acc = next_account_info(account_info_iter)?;
+        let spill_acc = next_account_info(account_info_iter)?;
+        let rent_acc = next_account_info(account_info_iter)?;
+        let clock_acc = next_account_info(account_info_iter)?;
+        let governance_authority_acc = next_account_info(account_info_iter)?;
+        let loader_acc = next_account_info(account_info_iter)?;
+        if program_acc.key != program_id || *buffer_acc.key != upgrade.buffer || *spill_acc.key != upgrade.spill {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let bump = assert_pda(governance_authority_acc, governance_authority_address(program_id))?;
+        invoke_signed(
+            &bpf_loader_upgradeable::upgrade(program_id, &upgrade.buffer, governance_authority_acc.key, &upgrade.spill),
+            &[
+                program_data_acc.clone(),
+                program_acc.clone(),
+                buffer_acc.clone(),
+                spill_acc.clone(),
+                rent_acc.clone(),
+                clock_acc.clone(),
+                governance_authority_acc.clone(),
+                loader_acc.clone(),
+            ],
+            &[&[GOVERNANCE_AUTHORITY_SEED, &[bump]]],
+        )?;
+        msg!("Upgraded program from buffer {}", upgrade.buffer);
+        Ok(())
+    }
+
+    /// Hands this program's upgrade authority from the signing current
+    /// authority to the governance authority PDA, after which only an executed
+    /// `CodeUpgrade` proposal can upgrade it. The loader's checked variant has
+    /// the PDA co-sign, so the authority cannot land on an address nobody
+    /// signs for. Handing it on again takes a proposal whose instructions call
+    /// the loader.
+    pub fn set_upgrade_authority(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let SetUpgradeAuthorityAccounts { program_data_acc, current_authority_acc, governance_authority_acc, loader_acc, governance_authority_bump } =
+            SetUpgradeAuthorityAccounts::try_from(program_id, accounts)?;
+        invoke_signed(
+            &bpf_loader_upgradeable::set_upgrade_authority_checked(program_id, current_authority_acc.key, governance_authority_acc.key),
+            &[program_data_acc.clone(), current_authority_acc.clone(), governance_authority_acc.clone(), loader_acc.clone()],
+            &[&[GOVERNANCE_AUTHORITY_SEED, &[governance_authority_bump]]],
+        )?;
+        msg!("Upgrade authority handed from {} to governance authority {}", current_authority_acc.key, governance_authority_acc.key);
+        Ok(())
+    }
+
+    /// Votes with the voter's own stake for `mint`, or with a stake whose
+    /// `voting_delegate` is the voter, plus the voter's vote-escrow lock if given.
+    pub fn vote_on_proposal(program_id: &Pubkey, accounts: &[AccountInfo], proposal_id: u64, choice: VoteChoice) -> ProgramResult {
+        let VoteAccounts {
+            vote_acc,
+            voter_acc,
+            proposal_acc,
+            staking_acc,
+            system_program_acc,
+            mint_acc,
+            vote_escrow_acc,
+            delegated_accs,
+            vote_bump,
+        } = VoteAccounts::try_from(program_id, accounts, proposal_id)?;
+        if vote_acc.lamports() > 0 {
+            return Err(GgtError::AlreadyVoted.into());
+        }
+
+        let mut proposal = load_proposal(proposal_acc)?;
+        if proposal.status != ProposalStatus::Active {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if Clock::get()?.unix_timestamp >= proposal.voting_ends_at {
+            return Err(GgtError::VotingClosed.into());
+        }
+
+        let stakes = std::iter::once(staking_acc).chain(delegated_accs);
+        let mut staked_amount = delegated_weight(program_id, &proposal, stakes, voter_acc.key, mint_acc.key)?;
+        if let Some(vote_escrow_acc) = vote_escrow_acc {
+            let escrowed = vote_escrow::snapshot_escrow_weight(program_id, vote_escrow_acc, voter_acc.key, mint_acc.key, proposal.timestamp)?;
+            staked_amount = staked_amount.saturating_add(escrowed);
+            proposal.escrow_weight = proposal.escrow_weight.saturating_add(escrowed);
+        }
+
+        let tally = proposal.tally(choice);
+        *tally = tally.saturating_add(staked_amount);
+        pack_state(&proposal, proposal_acc)?;
+
+        invoke_signed(
+            &system_instruction::create_account(
+                voter_acc.key,
+                vote_acc.key,
+                Rent::get()?.minimum_balance(Vote::LEN),
+                Vote::LEN as u64,
+                program_id,
+            ),
+            &[voter_acc.clone(), vote_acc.clone(), system_program_acc.clone()],
+            &[&[VOTE_SEED, proposal_acc.key.as_ref(), voter_acc.key.as_ref(), &[vote_bump]]],
+        )?;
         let vote_data = Vote {
             proposal: *proposal_acc.key,
             voter: *voter_acc.key,
-            vote: vote_in_favor,
+            choice,
+            weight: staked_amount,
+            is_initialized: true,
+        };
+        pack_state(&vote_data, vote_acc)?;
+        events::emit(&events::VoteCast {
+            proposal: *proposal_acc.key,
+            voter: *voter_acc.key,
+            choice,
             weight: staked_amount,
+        });
+        msg!("Voted {:?} on proposal {} with weight {}", choice, proposal_id, staked_amount);
+        Ok(())
+    }
+
+    /// Moves a vote's recorded weight from its previous choice to `choice`
+    /// while voting on the proposal is still open.
+    pub fn update_vote(program_id: &Pubkey, accounts: &[AccountInfo], proposal_id: u64, choice: VoteChoice) -> ProgramResult {
+        let UpdateVoteAccounts { vote_acc, voter_acc, proposal_acc } = UpdateVoteAccounts::try_from(program_id, accounts, proposal_id)?;
+        let mut vote = Vote::unpack(&vote_acc.try_borrow_data()?)?;
+        if vote.weight == 0 {
+            msg!("Ballot votes and votes without weight cannot be changed");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let mut proposal = load_proposal(proposal_acc)?;
+        if proposal.status != ProposalStatus::Active {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if Clock::get()?.unix_timestamp >= proposal.voting_ends_at {
+            return Err(GgtError::VotingClosed.into());
+        }
+
+        let previous = vote.choice;
+        let tally = proposal.tally(previous);
+        *tally = tally.saturating_sub(vote.weight);
+        let tally = proposal.tally(choice);
+        *tally = tally.saturating_add(vote.weight);
+        vote.choice = choice;
+        pack_state(&proposal, proposal_acc)?;
+        pack_state(&vote, vote_acc)?;
+        events::emit(&events::VoteChanged {
+            proposal: *proposal_acc.key,
+            voter: *voter_acc.key,
+            previous,
+            choice,
+            weight: vote.weight,
+        });
+        msg!("Changed vote on proposal {} from {:?} to {:?}", proposal_id, previous, choice);
+        Ok(())
+    }
+
+    /// Settles a proposal once voting has ended, against the configured quorum
+    /// (a share of the staking pool's total stake, met by staked weight only)
+    /// and approval threshold.
+    pub fn finalize_proposal(program_id: &Pubkey, accounts: &[AccountInfo], proposal_id: u64) -> ProgramResult {
+        let FinalizeProposalAccounts { proposal_acc, config_acc, pool_state_acc, remaining_accs } =
+            FinalizeProposalAccounts::try_from(program_id, accounts, proposal_id)?;
+
+        let mut proposal = load_proposal(proposal_acc)?;
+        if proposal.status != ProposalStatus::Active {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let now = Clock::get()?.unix_timestamp;
+        if now < proposal.voting_ends_at {
+            msg!("Voting on proposal {} is still open", proposal_id);
+            return Err(ProgramError::InvalidArgument);
+        }
+        if proposal.pages_tallied < proposal.ballot_pages {
+            msg!("{} of {} ballot pages tallied", proposal.pages_tallied, proposal.ballot_pages);
+            return Err(GgtError::UntalliedBallotPages.into());
+        }
+
+        let config = load_governance_config(program_id, config_acc)?;
+        if *pool_state_acc.key != config.staking_pool {
+            msg!("Quorum is measured against staking pool {}", config.staking_pool);
+            return Err(ProgramError::InvalidArgument);
+        }
+        let total_staked = load::<PodStakingPool>(&pool_state_acc.try_borrow_data()?)?.total_staked;
+        if config.params.passes(proposal.yes_weight, proposal.no_weight, proposal.abstain_weight, proposal.escrow_weight, total_staked) {
+            proposal.status = ProposalStatus::Passed;
+            proposal.executable_at = now.checked_add(config.params.timelock_seconds).ok_or(GgtError::MathOverflow)?;
+        } else {
+            proposal.status = ProposalStatus::Rejected;
+        }
+        pack_state(&proposal, proposal_acc)?;
+        let refund =
+            config.params.reaches_quorum(proposal.yes_weight, proposal.no_weight, proposal.abstain_weight, proposal.escrow_weight, total_staked);
+        Self::release_deposit(program_id, &proposal, remaining_accs, refund)?;
+        msg!(
+            "Finalized proposal {}: {:?} (yes {}, no {}, abstain {})",
+            proposal_id,
+            proposal.status,
+            proposal.yes_weight,
+            proposal.no_weight,
+            proposal.abstain_weight
+        );
+        Ok(())
+    }
+
+    /// Withdraws an active proposal as spam, slashing its deposit to the treasury.
+    pub fn cancel_proposal(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let CancelProposalAccounts { proposal_acc, remaining_accs } = CancelProposalAccounts::try_from(program_id, accounts)?;
+        let mut proposal = load_proposal(proposal_acc)?;
+        if proposal.status != ProposalStatus::Active {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        proposal.status = ProposalStatus::Cancelled;
+        pack_state(&proposal, proposal_acc)?;
+        Self::release_deposit(program_id, &proposal, remaining_accs, false)?;
+        msg!("Cancelled proposal {}", proposal_acc.key);
+        Ok(())
+    }
+
+    /// Pays a proposal's escrowed deposit back to the proposer, or to the
+    /// treasury when `refund` is false.
+    fn release_deposit(program_id: &Pubkey, proposal: &Proposal, accounts: &[AccountInfo], refund: bool) -> ProgramResult {
+        if proposal.deposit == 0 {
+            return Ok(());
+        }
+        let destination_owner = if refund { proposal.proposer } else { Treasury::treasury_authority(program_id).0 };
+        let DepositReleaseAccounts { vault_acc, authority_acc, destination_acc, mint_acc, token_program_acc, authority_bump } =
+            DepositReleaseAccounts::try_from(program_id, accounts, &destination_owner)?;
+        token_program::transfer(
+            token_program_acc,
+            vault_acc,
+            mint_acc,
+            destination_acc,
+            authority_acc,
+            proposal.deposit,
+            &[&[PROPOSAL_DEPOSIT_SEED, &[authority_bump]]],
+        )?;
+        if refund {
+            msg!("Refunded proposal deposit of {}", proposal.deposit);
+        } else {
+            msg!("Slashed proposal deposit of {} to the treasury", proposal.deposit);
+        }
+        Ok(())
+    }
+
+    /// Opens the next ballot page for bitmap-mode voting on an active proposal.
+    pub fn open_ballot_page(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let OpenBallotPageAccounts { page_acc, proposal_acc } = OpenBallotPageAccounts::try_from(program_id, accounts)?;
+        if BallotPage::unpack_unchecked(&page_acc.try_borrow_data()?)?.is_initialized {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        let mut proposal = load_proposal(proposal_acc)?;
+        if proposal.status != ProposalStatus::Active || Clock::get()?.unix_timestamp >= proposal.voting_ends_at {
+            return Err(GgtError::VotingClosed.into());
+        }
+
+        let page = BallotPage {
+            proposal: *proposal_acc.key,
+            page_index: proposal.ballot_pages,
+            voters: Vec::new(),
+            in_favor: 0,
+            weights: Vec::new(),
+            escrow_weight: 0,
+            tallied: false,
             is_initialized: true,
         };
-        let mut vote_data_mut = vote_acc.try_borrow_mut_data()?;
-        vote_data.pack_into_slice(&mut vote_data_mut);
-        msg!("Voted {} on proposal {} with weight {}", vote_in_favor, _proposal_id, staked_amount);
+        proposal.ballot_pages = proposal.ballot_pages.checked_add(1).ok_or(GgtError::MathOverflow)?;
+        pack_state(&page, page_acc)?;
+        pack_state(&proposal, proposal_acc)?;
+        msg!("Opened ballot page {}", proposal.ballot_pages - 1);
+        Ok(())
+    }
+
+    /// Registers the voter in the next free slot of a ballot page. Weights are folded
+    /// into the proposal later by `tally_page`. The voter's vote record is created
+    /// without weight, so `vote_on_proposal` and `update_vote` cannot count it again.
+    pub fn cast_ballot(program_id: &Pubkey, accounts: &[AccountInfo], vote_in_favor: bool) -> ProgramResult {
+        let CastBallotAccounts {
+            page_acc,
+            voter_acc,
+            proposal_acc,
+            staking_acc,
+            vote_acc,
+            system_program_acc,
+            mint_acc,
+            vote_escrow_acc,
+            delegated_accs,
+            vote_bump,
+        } = CastBallotAccounts::try_from(program_id, accounts)?;
+        if vote_acc.lamports() > 0 {
+            return Err(GgtError::AlreadyVoted.into());
+        }
+        let mut page = BallotPage::unpack(&page_acc.try_borrow_data()?)?;
+        if page.proposal != *proposal_acc.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let proposal = load_proposal(proposal_acc)?;
+        if proposal.status != ProposalStatus::Active || Clock::get()?.unix_timestamp >= proposal.voting_ends_at {
+            return Err(GgtError::VotingClosed.into());
+        }
+        if page.voters.len() >= BALLOTS_PER_PAGE {
+            return Err(GgtError::BallotPageFull.into());
+        }
+
+        let stakes = std::iter::once(staking_acc).chain(delegated_accs);
+        let mut staked_amount = delegated_weight(program_id, &proposal, stakes, voter_acc.key, mint_acc.key)?;
+        if let Some(vote_escrow_acc) = vote_escrow_acc {
+            let escrowed = vote_escrow::snapshot_escrow_weight(program_id, vote_escrow_acc, voter_acc.key, mint_acc.key, proposal.timestamp)?;
+            staked_amount = staked_amount.saturating_add(escrowed);
+            page.escrow_weight = page.escrow_weight.saturating_add(escrowed);
+        }
+        if vote_in_favor {
+            page.in_favor |= 1 << page.voters.len();
+        }
+        page.voters.push(*voter_acc.key);
+        page.weights.push(staked_amount);
+        pack_state(&page, page_acc)?;
+
+        invoke_signed(
+            &system_instruction::create_account(
+                voter_acc.key,
+                vote_acc.key,
+                Rent::get()?.minimum_balance(Vote::LEN),
+                Vote::LEN as u64,
+                program_id,
+            ),
+            &[voter_acc.clone(), vote_acc.clone(), system_program_acc.clone()],
+            &[&[VOTE_SEED, proposal_acc.key.as_ref(), voter_acc.key.as_ref(), &[vote_bump]]],
+        )?;
+        let choice = if vote_in_favor { VoteChoice::Yes } else { VoteChoice::No };
+        let vote_data = Vote { proposal: *proposal_acc.key, voter: *voter_acc.key, choice, weight: 0, is_initialized: true };
+        pack_state(&vote_data, vote_acc)?;
+        events::emit(&events::VoteCast {
+            proposal: *proposal_acc.key,
+            voter: *voter_acc.key,
+            choice,
+            weight: staked_amount,
+        });
+        msg!("Ballot cast {} with weight {}", vote_in_favor, staked_amount);
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Permissionless crank folding one closed ballot page into the proposal totals.
+    pub fn tally_page(program_id: &Pubkey, accounts: &[AccountInfo], page_index: u16) -> ProgramResult {
+        let TallyPageAccounts { proposal_acc, page_acc } = TallyPageAccounts::try_from(program_id, accounts)?;
+        let mut page = BallotPage::unpack(&page_acc.try_borrow_data()?)?;
+        if page.proposal != *proposal_acc.key || page.page_index != page_index {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if page.tallied {
+            return Err(GgtError::PageAlreadyTallied.into());
+        }
+        let mut proposal = load_proposal(proposal_acc)?;
+        if proposal.status != ProposalStatus::Active {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if Clock::get()?.unix_timestamp < proposal.voting_ends_at {
+            msg!("Ballot page {} is still open", page_index);
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (yes, no) = page.yes_no_weights();
+        proposal.yes_weight = proposal.yes_weight.saturating_add(yes);
+        proposal.no_weight = proposal.no_weight.saturating_add(no);
+        proposal.escrow_weight = proposal.escrow_weight.saturating_add(page.escrow_weight);
+        proposal.pages_tallied += 1;
+        page.tallied = true;
+        pack_state(&page, page_acc)?;
+        pack_state(&proposal, proposal_acc)?;
+        msg!("Tallied ballot page {} ({} of {})", page_index, proposal.pages_tallied, proposal.ballot_pages);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::staking_contract::{Stake, StakingConfig, StakingPool};
+    use crate::test_utils::{account_info, fund_rent_exempt, SimulatedBank};
+    use crate::GOVERNANCE_PUBKEY;
+
+    fn proposal(voting_ends_at: i64, yes_weight: u64, no_weight: u64) -> Vec<u8> {
+        let proposal = Proposal {
+            uri: "ipfs://raise-the-reward-rate".to_string(),
+            content_hash: [7; 32],
+            proposer: Pubkey::new_unique(),
+            status: ProposalStatus::Active,
+            timestamp: 1,
+            voting_ends_at,
+            yes_weight,
+            no_weight,
+            abstain_weight: 0,
+            escrow_weight: 0,
+            ballot_pages: 0,
+            pages_tallied: 0,
+            kind: ProposalKind::Text,
+            deposit: 0,
+            snapshot_epoch: 0,
+            snapshot_reward_index: 0,
+            executable_at: 0,
+            is_initialized: true,
+            instructions: Vec::new(),
+        };
+        let mut data = vec![0u8; Proposal::LEN];
+        proposal.pack_into_slice(&mut data);
+        data
+    }
+
+    fn content(uri: &str) -> ProposalContent {
+        ProposalContent { content_hash: [7; 32], uri: uri.to_string() }
+    }
+
+    const PARAMS: GovernanceParams = GovernanceParams {
+        quorum_bps: 1_000,
+        approval_threshold_bps: 5_000,
+        voting_period_seconds: 100,
+        min_stake_to_propose: 500,
+        proposal_deposit: 0,
+        timelock_seconds: 0,
+    };
+
+    fn governance_config_data(params: GovernanceParams, staking_pool: Pubkey) -> Vec<u8> {
+        let mut data = vec![0u8; GovernanceConfig::LEN];
+        GovernanceConfig::pack(GovernanceConfig { params, staking_pool, is_initialized: true }, &mut data).unwrap();
+        data
+    }
+
+    fn pool_data(total_staked: u64) -> Vec<u8> {
+        let mut data = vec![0u8; StakingPool::LEN];
+        StakingPool::pack(StakingPool { total_staked, ..StakingPool::new(0) }, &mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_vote_on_proposal_updates_tally() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (voter_key, proposal_key, mint_key) = (Pubkey::new_unique(), proposal_address(&program_id, 1).0, crate::TokenContract::mint_address(&program_id).0);
+        let staking_key = stake_address(&program_id, &voter_key, &mint_key).0;
+        let (vote_key, _) = vote_address(&program_id, &proposal_key, &voter_key);
+        let system_program_key = solana_program::system_program::id();
+        let (mut l0, mut l1, mut l2, mut l3, mut l4, mut l5) = (0u64, 10_000_000u64, 0u64, 0u64, 0u64, 0u64);
+        let mut vote_data = vec![0u8; Vote::LEN];
+        let mut voter_data = vec![];
+        let (mut system_program_data, mut mint_data) = (vec![], vec![]);
+        let mut proposal_data = proposal(100, 0, 0);
+        let mut staking_data = vec![0u8; Stake::LEN];
+        Stake { amount: 600, staked_at: 1, is_initialized: true, ..Default::default() }
+            .pack_into_slice(&mut staking_data);
+
+        let accounts = vec![
+            account_info(&vote_key, false, true, &mut l0, &mut vote_data, &program_id),
+            account_info(&voter_key, true, true, &mut l1, &mut voter_data, &program_id),
+            account_info(&proposal_key, false, true, &mut l2, &mut proposal_data, &program_id),
+            account_info(&staking_key, false, false, &mut l3, &mut staking_data, &program_id),
+            account_info(&system_program_key, false, false, &mut l4, &mut system_program_data, &program_id),
+            account_info(&mint_key, false, false, &mut l5, &mut mint_data, &program_id),
+        ];
+        fund_rent_exempt(&[&accounts[2], &accounts[3]]);
+        // Stake opened when the proposal was created is not part of its snapshot.
+        assert_eq!(
+            GovernanceContract::vote_on_proposal(&program_id, &accounts, 1, VoteChoice::No),
+            Err(GgtError::StakeAfterSnapshot.into())
+        );
+        Stake { amount: 600, staked_at: 0, is_initialized: true, ..Default::default() }
+            .pack_into_slice(&mut accounts[3].try_borrow_mut_data().unwrap());
+        GovernanceContract::vote_on_proposal(&program_id, &accounts, 1, VoteChoice::No).unwrap();
+
+        let tallied = Proposal::unpack_from_slice(&accounts[2].try_borrow_data().unwrap()).unwrap();
+        assert_eq!(tallied.yes_weight, 0);
+        assert_eq!(tallied.no_weight, 600);
+
+        assert_eq!(
+            GovernanceContract::vote_on_proposal(&program_id, &accounts, 1, VoteChoice::Yes),
+            Err(GgtError::AlreadyVoted.into())
+        );
+        let tallied = Proposal::unpack_from_slice(&accounts[2].try_borrow_data().unwrap()).unwrap();
+        assert_eq!((tallied.yes_weight, tallied.no_weight), (0, 600));
+
+        // Once delegated, the stake votes only through its delegate, and only on
+        // proposals created after the delegation.
+        let delegate_key = Pubkey::new_unique();
+        let (delegate_vote_key, _) = vote_address(&program_id, &proposal_key, &delegate_key);
+        let (mut l6, mut l7) = (0u64, 10_000_000u64);
+        let (mut delegate_vote_data, mut delegate_data) = (vec![0u8; Vote::LEN], vec![]);
+        let delegate_accounts = [
+            &[
+                account_info(&delegate_vote_key, false, true, &mut l6, &mut delegate_vote_data, &program_id),
+                account_info(&delegate_key, true, true, &mut l7, &mut delegate_data, &program_id),
+            ][..],
+            &accounts[2..],
+        ]
+        .concat();
+        assert_eq!(
+            GovernanceContract::vote_on_proposal(&program_id, &delegate_accounts, 1, VoteChoice::Yes),
+            Err(GgtError::NotVotingDelegate.into())
+        );
+        bank.set_unix_timestamp(3);
+        let staking_contract = StakingContract::new();
+        let delegation_accounts = [accounts[3].clone(), accounts[1].clone(), accounts[5].clone()];
+        staking_contract.set_voting_delegate(&program_id, &delegation_accounts, delegate_key).unwrap();
+        assert_eq!(
+            GovernanceContract::vote_on_proposal(&program_id, &delegate_accounts, 1, VoteChoice::Yes),
+            Err(GgtError::StakeAfterSnapshot.into())
+        );
+        let mut fresh = Proposal::unpack_from_slice(&proposal(100, 0, 0)).unwrap();
+        fresh.timestamp = 5;
+        fresh.pack_into_slice(&mut accounts[2].try_borrow_mut_data().unwrap());
+        bank.set_unix_timestamp(5);
+        // Every stake delegated to the delegate counts in its one vote, after its
+        // own vote-escrow lock; a stake listed twice counts once.
+        let other_staking_key = stake_address(&program_id, &Pubkey::new_unique(), &mint_key).0;
+        let escrow_key = vote_escrow::vote_escrow_address(&program_id, &delegate_key, &mint_key).0;
+        let (mut l8, mut l9, mut other_staking_data) = (0u64, 0u64, vec![0u8; Stake::LEN]);
+        Stake { amount: 400, voting_delegate: delegate_key, delegated_at: 3, is_initialized: true, ..Default::default() }
+            .pack_into_slice(&mut other_staking_data);
+        let mut escrow_data = vec![0u8; vote_escrow::VoteEscrow::LEN];
+        let escrow = vote_escrow::VoteEscrow { amount: 100, unlock_at: 5 + vote_escrow::SECONDS_PER_YEAR, updated_at: 3, is_initialized: true, ..Default::default() };
+        vote_escrow::VoteEscrow::pack(escrow, &mut escrow_data).unwrap();
+        let other_staking_acc = account_info(&other_staking_key, false, false, &mut l8, &mut other_staking_data, &program_id);
+        let escrow_acc = account_info(&escrow_key, false, false, &mut l9, &mut escrow_data, &program_id);
+        let all_delegated =
+            [&delegate_accounts[..], &[escrow_acc, other_staking_acc.clone(), other_staking_acc, accounts[3].clone()]].concat();
+        GovernanceContract::vote_on_proposal(&program_id, &all_delegated, 1, VoteChoice::Yes).unwrap();
+        let tallied = Proposal::unpack_from_slice(&accounts[2].try_borrow_data().unwrap()).unwrap();
+        assert_eq!((tallied.yes_weight, tallied.escrow_weight), (1_100, 100));
+        // As if the owner had not voted on the new proposal yet.
+        **accounts[0].try_borrow_mut_lamports().unwrap() = 0;
+        assert_eq!(
+            GovernanceContract::vote_on_proposal(&program_id, &accounts, 1, VoteChoice::No),
+            Err(GgtError::NotVotingDelegate.into())
+        );
+    }
+
+    #[test]
+    fn test_proposal_body_sits_behind_the_packed_fields() {
+        let program_id = Pubkey::new_unique();
+        let (proposal_key, stranger_key) = (proposal_address(&program_id, 1).0, Pubkey::new_unique());
+        let system_program_key = solana_program::system_program::id();
+        let (mut l0, mut l1, mut l2) = (0u64, 0u64, 0u64);
+        let mut proposal_data = [proposal(100, 0, 0), b"Full text".to_vec()].concat();
+        let (mut stranger_data, mut system_program_data) = (vec![], vec![]);
+        let accounts = vec![
+            account_info(&proposal_key, false, true, &mut l0, &mut proposal_data, &program_id),
+            account_info(&stranger_key, true, true, &mut l1, &mut stranger_data, &program_id),
+            account_info(&system_program_key, false, false, &mut l2, &mut system_program_data, &program_id),
+        ];
+
+        let proposal = load_proposal(&accounts[0]).unwrap();
+        assert_eq!((proposal.uri.as_str(), proposal.content_hash), ("ipfs://raise-the-reward-rate", [7; 32]));
+        assert_eq!(Proposal::body(&accounts[0].try_borrow_data().unwrap()), b"Full text");
+        assert_eq!(
+            GovernanceContract::extend_proposal_body(&program_id, &accounts, 1, b" continued"),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            GovernanceContract::extend_proposal_body(&program_id, &accounts, 2, b" continued"),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+
+    #[test]
+    fn test_update_vote_moves_weight_until_voting_ends() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (voter_key, proposal_key) = (Pubkey::new_unique(), proposal_address(&program_id, 1).0);
+        let (vote_key, _) = vote_address(&program_id, &proposal_key, &voter_key);
+        let (mut l0, mut l1, mut l2) = (0u64, 0u64, 0u64);
+        let mut vote_data = vec![0u8; Vote::LEN];
+        Vote { proposal: proposal_key, voter: voter_key, choice: VoteChoice::No, weight: 600, is_initialized: true }
+            .pack_into_slice(&mut vote_data);
+        let mut voter_data = vec![];
+        let mut proposal_data = proposal(100, 0, 600);
+        let accounts = vec![
+            account_info(&vote_key, false, true, &mut l0, &mut vote_data, &program_id),
+            account_info(&voter_key, true, false, &mut l1, &mut voter_data, &program_id),
+            account_info(&proposal_key, false, true, &mut l2, &mut proposal_data, &program_id),
+        ];
+
+fund_rent_exempt(&[&accounts[0], &accounts[2]]);
+        bank.set_unix_timestamp(50);
+        GovernanceContract::update_vote(&program_id, &accounts, 1, VoteChoice::Abstain).unwrap();
+        let tallied = Proposal::unpack_from_slice(&accounts[2].try_borrow_data().unwrap()).unwrap();
+        assert_eq!((tallied.yes_weight, tallied.no_weight, tallied.abstain_weight), (0, 0, 600));
+        // Abstentions reach quorum without approving.
+        assert!(PARAMS.reaches_quorum(0, 0, 600, 0, 1_000) && !PARAMS.passes(0, 0, 600, 0, 1_000));
+
+        GovernanceContract::update_vote(&program_id, &accounts, 1, VoteChoice::Yes).unwrap();
+        let tallied = Proposal::unpack_from_slice(&accounts[2].try_borrow_data().unwrap()).unwrap();
+        assert_eq!((tallied.yes_weight, tallied.no_weight, tallied.abstain_weight), (600, 0, 0));
+        assert_eq!(Vote::unpack(&accounts[0].try_borrow_data().unwrap()).unwrap().choice, VoteChoice::Yes);
+
+        bank.set_unix_timestamp(100);
+        assert_eq!(
+            GovernanceContract::update_vote(&program_id, &accounts, 1, VoteChoice::No),
+            Err(GgtError::VotingClosed.into())
+        );
+    }
+
+    #[test]
+    fn test_vote_rejected_after_voting_ends() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (voter_key, proposal_key, staking_key) = (Pubkey::new_unique(), proposal_address(&program_id, 1).0, Pubkey::new_unique());
+        let (vote_key, _) = vote_address(&program_id, &proposal_key, &voter_key);
+        let system_program_key = solana_program::system_program::id();
+        let mint_key = crate::TokenContract::mint_address(&program_id).0;
+        let (mut l0, mut l1, mut l2, mut l3, mut l4, mut l5) = (0u64, 10_000_000u64, 0u64, 0u64, 0u64, 0u64);
+        let mut vote_data = vec![0u8; Vote::LEN];
+        let mut proposal_data = proposal(100, 0, 0);
+        let mut staking_data = vec![0u8; Stake::LEN];
+        let (mut voter_data, mut system_program_data, mut mint_data) = (vec![], vec![], vec![]);
+        let accounts = vec![
+            account_info(&vote_key, false, true, &mut l0, &mut vote_data, &program_id),
+            account_info(&voter_key, true, false, &mut l1, &mut voter_data, &program_id),
+            account_info(&proposal_key, false, true, &mut l2, &mut proposal_data, &program_id),
+            account_info(&staking_key, false, false, &mut l3, &mut staking_data, &program_id),
+            account_info(&system_program_key, false, false, &mut l4, &mut system_program_data, &program_id),
+            account_info(&mint_key, false, false, &mut l5, &mut mint_data, &program_id),
+        ];
+
+        bank.set_unix_timestamp(100);
+        assert_eq!(
+            GovernanceContract::vote_on_proposal(&program_id, &accounts, 1, VoteChoice::Yes),
+            Err(GgtError::VotingClosed.into())
+        );
+    }
+
+    #[test]
+    fn test_finalize_proposal_passes_and_allows_execution() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let proposal_key = proposal_address(&program_id, 1).0;
+        let (config_key, _) = governance_config_address(&program_id);
+        let pool_key = Pubkey::new_unique();
+        let (mut l0, mut l1, mut l2, mut l3, mut l4) = (0u64, 0u64, 0u64, 0u64, 0u64);
+        let mut proposal_data = proposal(100, 1_500, 100);
+        let mut admin_data = vec![];
+        let mut config_data = governance_config_data(PARAMS, pool_key);
+        let mut pool_state_data = pool_data(10_000);
+        let proposal_acc = account_info(&proposal_key, false, true, &mut l0, &mut proposal_data, &program_id);
+        let (admin_key, _) = multisig::multisig_authority_address(&program_id);
+        let admin_acc = account_info(&admin_key, true, false, &mut l1, &mut admin_data, &program_id);
+        let config_acc = account_info(&config_key, false, false, &mut l2, &mut config_data, &program_id);
+        let pool_state_acc = account_info(&pool_key, false, false, &mut l3, &mut pool_state_data, &program_id);
+        let (program_config_key, mut program_config_data) = crate::test_utils::program_config(&program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, &mut l4, &mut program_config_data, &program_id);
+        fund_rent_exempt(&[&proposal_acc]);
+        let finalize_accounts = [proposal_acc.clone(), config_acc, pool_state_acc];
+        let execute_accounts = [proposal_acc.clone(), admin_acc, program_config_acc];
+
+        bank.set_unix_timestamp(50);
+        assert!(GovernanceContract::finalize_proposal(&program_id, &finalize_accounts, 1).is_err());
+        assert_eq!(
+            GovernanceContract::execute_proposal(&program_id, &execute_accounts, 1),
+            Err(GgtError::VotingStillOpen.into())
+        );
+
+        bank.set_unix_timestamp(100);
+        GovernanceContract::finalize_proposal(&program_id, &finalize_accounts, 1).unwrap();
+        let finalized = Proposal::unpack_from_slice(&proposal_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(finalized.status, ProposalStatus::Passed);
+
+        GovernanceContract::execute_proposal(&program_id, &execute_accounts, 1).unwrap();
+        let executed = Proposal::unpack_from_slice(&proposal_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(executed.status, ProposalStatus::Executed);
+    }
+
+    #[test]
+    fn test_passed_proposal_executes_only_inside_its_timelock_window() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (proposal_key, pool_key) = (proposal_address(&program_id, 1).0, Pubkey::new_unique());
+        let (config_key, _) = governance_config_address(&program_id);
+        let (mut l0, mut l1, mut l2, mut l3, mut l4) = (0u64, 0u64, 0u64, 0u64, 0u64);
+        let mut proposal_data = proposal(100, 1_500, 100);
+        let mut admin_data = vec![];
+        let mut config_data = governance_config_data(GovernanceParams { timelock_seconds: 1_000, ..PARAMS }, pool_key);
+        let mut pool_state_data = pool_data(10_000);
+        let proposal_acc = account_info(&proposal_key, false, true, &mut l0, &mut proposal_data, &program_id);
+        let (admin_key, _) = multisig::multisig_authority_address(&program_id);
+        let admin_acc = account_info(&admin_key, true, false, &mut l1, &mut admin_data, &program_id);
+        let config_acc = account_info(&config_key, false, false, &mut l2, &mut config_data, &program_id);
+        let pool_state_acc = account_info(&pool_key, false, false, &mut l3, &mut pool_state_data, &program_id);
+        let (program_config_key, mut program_config_data) = crate::test_utils::program_config(&program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, &mut l4, &mut program_config_data, &program_id);
+        fund_rent_exempt(&[&proposal_acc]);
+        let execute_accounts = [proposal_acc.clone(), admin_acc, program_config_acc];
+
+        bank.set_unix_timestamp(100);
+        GovernanceContract::finalize_proposal(&program_id, &[proposal_acc.clone(), config_acc, pool_state_acc], 1).unwrap();
+        assert_eq!(Proposal::unpack_from_slice(&proposal_acc.try_borrow_data().unwrap()).unwrap().executable_at, 1_100);
+        bank.set_unix_timestamp(1_099);
+        assert_eq!(
+            GovernanceContract::execute_proposal(&program_id, &execute_accounts, 1),
+            Err(GgtError::TimelockNotElapsed.into())
+        );
+        bank.set_unix_timestamp(1_100 + EXECUTION_WINDOW_SECONDS);
+        assert_eq!(
+            GovernanceContract::execute_proposal(&program_id, &execute_accounts, 1),
+            Err(GgtError::ProposalExpired.into())
+        );
+        bank.set_unix_timestamp(1_100);
+        GovernanceContract::execute_proposal(&program_id, &execute_accounts, 1).unwrap();
+    }
+
+    #[test]
+    fn test_finalize_proposal_rejects_without_quorum() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let proposal_key = proposal_address(&program_id, 1).0;
+        let (config_key, _) = governance_config_address(&program_id);
+        let pool_key = Pubkey::new_unique();
+        let (mut l0, mut l1, mut l2) = (0u64, 0u64, 0u64);
+        // Vote-escrow bonus counts towards approval but not towards quorum,
+        // so 1_099 cast leaves only 999 staked weight against a quorum of 1_000.
+        let mut proposal_data = proposal(100, 1_099, 0);
+        let mut escrowed = Proposal::unpack_from_slice(&proposal_data).unwrap();
+        escrowed.escrow_weight = 100;
+        escrowed.pack_into_slice(&mut proposal_data);
+        let mut config_data = governance_config_data(PARAMS, pool_key);
+        let mut pool_state_data = pool_data(10_000);
+        let proposal_acc = account_info(&proposal_key, false, true, &mut l0, &mut proposal_data, &program_id);
+        let config_acc = account_info(&config_key, false, false, &mut l1, &mut config_data, &program_id);
+        let pool_state_acc = account_info(&pool_key, false, false, &mut l2, &mut pool_state_data, &program_id);
+        let (other_pool_key, mut l3, mut other_pool_data) = (Pubkey::new_unique(), 0u64, pool_data(1_000));
+        let other_pool_acc = account_info(&other_pool_key, false, false, &mut l3, &mut other_pool_data, &program_id);
+        fund_rent_exempt(&[&proposal_acc]);
+
+        bank.set_unix_timestamp(200);
+        // A smaller pool would put the same votes over quorum, so only the configured one counts.
+        assert_eq!(
+            GovernanceContract::finalize_proposal(&program_id, &[proposal_acc.clone(), config_acc.clone(), other_pool_acc], 1),
+            Err(ProgramError::InvalidArgument)
+        );
+        GovernanceContract::finalize_proposal(&program_id, &[proposal_acc.clone(), config_acc, pool_state_acc], 1).unwrap();
+        let finalized = Proposal::unpack_from_slice(&proposal_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(finalized.status, ProposalStatus::Rejected);
+    }
+
+    #[test]
+    fn test_proposal_deposit_refunded_with_quorum_and_slashed_on_cancel() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let (proposer_key, mint_key, pool_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (first_key, second_key) = (proposal_address(&program_id, 1).0, proposal_address(&program_id, 2).0);
+        let (proposer_token_key, vault_key, treasury_vault_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (config_key, _) = governance_config_address(&program_id);
+        let (staking_key, _) = crate::staking_contract::stake_address(&program_id, &proposer_key, &mint_key);
+        let (deposit_authority, _) = proposal_deposit_authority(&program_id);
+        let treasury_authority = Treasury::treasury_authority(&program_id).0;
+        let params = GovernanceParams { proposal_deposit: 100, ..PARAMS };
+        let (counter_key, system_program_key) = (proposal_counter_address(&program_id).0, solana_program::system_program::id());
+        let mut lamports = [0u64; 17];
+        lamports[2] = 1_000_000_000;
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10, l11, l12, l13, l14, l15, l16] = &mut lamports;
+        let (program_config_key, mut program_config_data) = crate::test_utils::program_config(&program_id);
+        let (mut first_data, mut second_data) = (vec![0u8; Proposal::LEN], vec![0u8; Proposal::LEN]);
+        let (mut counter_data, mut d6) = (vec![0u8; ProposalCounter::LEN], vec![]);
+        let mut config_data = governance_config_data(params, pool_key);
+        let mut pool_state_data = pool_data(10_000);
+        let mut staking_data = vec![0u8; Stake::LEN];
+        Stake { amount: 500, is_initialized: true, ..Default::default() }.pack_into_slice(&mut staking_data);
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() }, &mut mint_data)
+            .unwrap();
+        let mut proposer_token_data = crate::test_utils::token_account_data(&mint_key, &proposer_key, 1_000);
+        let mut vault_data = crate::test_utils::token_account_data(&mint_key, &deposit_authority, 0);
+        let mut treasury_vault_data = crate::test_utils::token_account_data(&mint_key, &treasury_authority, 0);
+        let (mut d1, mut d2, mut d3, mut d4, mut d5) = (vec![], vec![], vec![], vec![], vec![]);
+        let first_acc = account_info(&first_key, false, true, l0, &mut first_data, &program_id);
+        let second_acc = account_info(&second_key, false, true, l1, &mut second_data, &program_id);
+        let proposer_acc = account_info(&proposer_key, true, true, l2, &mut d1, &program_id);
+        let config_acc = account_info(&config_key, false, false, l3, &mut config_data, &program_id);
+        let pool_state_acc = account_info(&pool_key, false, false, l4, &mut pool_state_data, &program_id);
+        let staking_acc = account_info(&staking_key, false, false, l5, &mut staking_data, &program_id);
+        let mint_acc = account_info(&mint_key, false, false, l6, &mut mint_data, &token_program_id);
+        let proposer_token_acc = account_info(&proposer_token_key, false, true, l7, &mut proposer_token_data, &token_program_id);
+        let vault_acc = account_info(&vault_key, false, true, l8, &mut vault_data, &token_program_id);
+        let treasury_vault_acc = account_info(&treasury_vault_key, false, true, l9, &mut treasury_vault_data, &token_program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, l10, &mut d2, &program_id);
+        let authority_acc = account_info(&deposit_authority, false, false, l11, &mut d3, &program_id);
+        let (admin_key, _) = multisig::multisig_authority_address(&program_id);
+        let admin_acc = account_info(&admin_key, true, false, l12, &mut d4, &program_id);
+        let stranger_acc = account_info(&proposer_key, true, false, l13, &mut d5, &program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, l14, &mut program_config_data, &program_id);
+        let counter_acc = account_info(&counter_key, false, true, l15, &mut counter_data, &program_id);
+        let system_program_acc = account_info(&system_program_key, false, false, l16, &mut d6, &program_id);
+        let deposit_from = [proposer_token_acc.clone(), vault_acc.clone(), token_program_acc.clone()];
+        let create_accounts = |proposal_acc| {
+            [
+                proposal_acc,
+                proposer_acc.clone(),
+                config_acc.clone(),
+                staking_acc.clone(),
+                mint_acc.clone(),
+                pool_state_acc.clone(),
+                counter_acc.clone(),
+                system_program_acc.clone(),
+            ]
+        };
+        let release_to = |destination_acc| [vault_acc.clone(), authority_acc.clone(), destination_acc, mint_acc.clone(), token_program_acc.clone()];
+        bank.set_balance(&proposer_token_key, 1_000);
+
+        bank.set_unix_timestamp(1_000);
+        let accounts = [create_accounts(first_acc.clone()).as_slice(), &deposit_from].concat();
+        GovernanceContract::create_proposal(&program_id, &accounts, content("ipfs://raise-the-reward-rate"), ProposalKind::Text, Vec::new()).unwrap();
+        assert_eq!((bank.balance(&proposer_token_key), bank.balance(&vault_key)), (900, 100));
+        let mut first = Proposal::unpack_from_slice(&first_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(first.deposit, 100);
+        first.no_weight = 1_500;
+        first.pack_into_slice(&mut first_acc.try_borrow_mut_data().unwrap());
+
+        // Rejected, but with quorum, so the deposit goes back to the proposer.
+        bank.set_unix_timestamp(1_100);
+        let finalize_accounts = [first_acc.clone(), config_acc.clone(), pool_state_acc.clone()];
+        let accounts = [finalize_accounts.as_slice(), &release_to(proposer_token_acc.clone())].concat();
+        GovernanceContract::finalize_proposal(&program_id, &accounts, 1).unwrap();
+        assert_eq!((bank.balance(&proposer_token_key), bank.balance(&vault_key)), (1_000, 0));
+
+        let accounts = [create_accounts(second_acc.clone()).as_slice(), &deposit_from].concat();
+        GovernanceContract::create_proposal(&program_id, &accounts, content("ipfs://raise-the-reward-rate"), ProposalKind::Text, Vec::new()).unwrap();
+        let by_stranger = [[second_acc.clone(), stranger_acc, program_config_acc.clone()].as_slice(), &release_to(treasury_vault_acc.clone())].concat();
+        assert!(GovernanceContract::cancel_proposal(&program_id, &by_stranger).is_err());
+        let by_admin = [[second_acc.clone(), admin_acc, program_config_acc].as_slice(), &release_to(treasury_vault_acc.clone())].concat();
+        GovernanceContract::cancel_proposal(&program_id, &by_admin).unwrap();
+        assert_eq!((bank.balance(&vault_key), bank.balance(&treasury_vault_key)), (0, 100));
+        let second = Proposal::unpack_from_slice(&second_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(second.status, ProposalStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_tally_pages_before_finalize() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (proposal_key, page_key, mint_key) = (proposal_address(&program_id, 1).0, Pubkey::new_unique(), crate::TokenContract::mint_address(&program_id).0);
+        let (config_key, _) = governance_config_address(&program_id);
+        let (pool_key, system_program_key) = (Pubkey::new_unique(), solana_program::system_program::id());
+        let (mut l0, mut l1, mut l2, mut l3, mut l4, mut l5) = (0u64, 0u64, 0u64, 0u64, 0u64, 0u64);
+        let mut proposal_data = proposal(100, 0, 0);
+        let mut config_data = governance_config_data(PARAMS, pool_key);
+        let mut pool_state_data = pool_data(10_000);
+        let mut page_data = vec![0u8; BallotPage::LEN];
+        let (mut system_program_data, mut mint_data) = (vec![], vec![]);
+        let proposal_acc = account_info(&proposal_key, false, true, &mut l0, &mut proposal_data, &program_id);
+        let page_acc = account_info(&page_key, false, true, &mut l1, &mut page_data, &program_id);
+        let config_acc = account_info(&config_key, false, false, &mut l2, &mut config_data, &program_id);
+        let pool_state_acc = account_info(&pool_key, false, false, &mut l3, &mut pool_state_data, &program_id);
+        let system_program_acc = account_info(&system_program_key, false, false, &mut l4, &mut system_program_data, &program_id);
+        let mint_acc = account_info(&mint_key, false, false, &mut l5, &mut mint_data, &program_id);
+        fund_rent_exempt(&[&proposal_acc, &page_acc]);
+        let finalize_accounts = [proposal_acc.clone(), config_acc, pool_state_acc];
+        let voters: Vec<Pubkey> = (0..2).map(|_| Pubkey::new_unique()).collect();
+        let stake_keys: Vec<Pubkey> = voters.iter().map(|voter| stake_address(&program_id, voter, &mint_key).0).collect();
+        let vote_keys: Vec<Pubkey> = voters.iter().map(|voter| vote_address(&program_id, &proposal_key, voter).0).collect();
+        let (mut voter_lamports, mut stake_lamports, mut vote_lamports) = ([10_000_000u64; 2], [0u64; 2], [0u64; 2]);
+        let mut voter_data = [vec![], vec![]];
+        let mut stake_data = [vec![0u8; Stake::LEN], vec![0u8; Stake::LEN]];
+        let mut vote_data = [vec![0u8; Vote::LEN], vec![0u8; Vote::LEN]];
+        for data in stake_data.iter_mut() {
+            Stake { amount: 1_200, is_initialized: true, ..Default::default() }.pack_into_slice(data);
+        }
+        let mut ballots = Vec::new();
+        for ((((voter, lamports), data), (stake_key, (stake_lamports, stake_data))), (vote_key, (vote_lamports, vote_data))) in voters
+            .iter()
+            .zip(voter_lamports.iter_mut())
+            .zip(voter_data.iter_mut())
+            .zip(stake_keys.iter().zip(stake_lamports.iter_mut().zip(stake_data.iter_mut())))
+            .zip(vote_keys.iter().zip(vote_lamports.iter_mut().zip(vote_data.iter_mut())))
+        {
+            ballots.push([
+                page_acc.clone(),
+                account_info(voter, true, true, lamports, data, &program_id),
+                proposal_acc.clone(),
+                account_info(stake_key, false, false, stake_lamports, stake_data, &program_id),
+                account_info(vote_key, false, true, vote_lamports, vote_data, &program_id),
+                system_program_acc.clone(),
+                mint_acc.clone(),
+            ]);
+        }
+
+        bank.set_unix_timestamp(10);
+        GovernanceContract::open_ballot_page(&program_id, &[page_acc.clone(), proposal_acc.clone()]).unwrap();
+        // A voter cannot vote with someone else's stake.
+        let mut borrowed = ballots[0].clone();
+        borrowed[3] = ballots[1][3].clone();
+        assert_eq!(GovernanceContract::cast_ballot(&program_id, &borrowed, true), Err(GgtError::NotVotingDelegate.into()));
+        // The second voter's vote-escrow lock adds its weight, as it would to a direct vote.
+        let escrow_key = vote_escrow::vote_escrow_address(&program_id, &voters[1], &mint_key).0;
+        let (mut l7, mut escrow_data) = (0u64, vec![0u8; vote_escrow::VoteEscrow::LEN]);
+        let escrow = vote_escrow::VoteEscrow { amount: 100, unlock_at: 1 + vote_escrow::SECONDS_PER_YEAR, is_initialized: true, ..Default::default() };
+        vote_escrow::VoteEscrow::pack(escrow, &mut escrow_data).unwrap();
+        let escrow_acc = account_info(&escrow_key, false, false, &mut l7, &mut escrow_data, &program_id);
+        GovernanceContract::cast_ballot(&program_id, &ballots[0], true).unwrap();
+        GovernanceContract::cast_ballot(&program_id, &[&ballots[1][..], &[escrow_acc]].concat(), false).unwrap();
+        // The ballot is recorded at the voter's vote address, so they cannot vote again.
+        assert_eq!(GovernanceContract::cast_ballot(&program_id, &ballots[0], true), Err(GgtError::AlreadyVoted.into()));
+        let direct_vote = [ballots[0][4].clone(), ballots[0][1].clone(), proposal_acc.clone(), ballots[0][3].clone(), system_program_acc.clone(), mint_acc.clone()];
+        assert_eq!(
+            GovernanceContract::vote_on_proposal(&program_id, &direct_vote, 1, VoteChoice::Yes),
+            Err(GgtError::AlreadyVoted.into())
+        );
+        assert_eq!(
+            GovernanceContract::update_vote(&program_id, &direct_vote[..3], 1, VoteChoice::No),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert!(GovernanceContract::tally_page(&program_id, &[proposal_acc.clone(), page_acc.clone()], 0).is_err());
+
+        bank.set_unix_timestamp(100);
+        assert_eq!(
+            GovernanceContract::finalize_proposal(&program_id, &finalize_accounts, 1),
+            Err(GgtError::UntalliedBallotPages.into())
+        );
+        // The page is only folded into a proposal account of this program.
+        let (foreign_owner, mut l6, mut foreign_data) = (Pubkey::new_unique(), 0u64, proposal(100, 0, 0));
+        let foreign_proposal = account_info(&proposal_key, false, true, &mut l6, &mut foreign_data, &foreign_owner);
+        assert_eq!(
+            GovernanceContract::tally_page(&program_id, &[foreign_proposal, page_acc.clone()], 0),
+            Err(ProgramError::IncorrectProgramId)
+        );
+        GovernanceContract::tally_page(&program_id, &[proposal_acc.clone(), page_acc.clone()], 0).unwrap();
+        assert_eq!(
+            GovernanceContract::tally_page(&program_id, &[proposal_acc.clone(), page_acc.clone()], 0),
+            Err(GgtError::PageAlreadyTallied.into())
+        );
+
+        let tallied = Proposal::unpack_from_slice(&proposal_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((tallied.yes_weight, tallied.no_weight, tallied.escrow_weight, tallied.pages_tallied), (1_200, 1_300, 100, 1));
+        GovernanceContract::finalize_proposal(&program_id, &finalize_accounts, 1).unwrap();
+        let finalized = Proposal::unpack_from_slice(&proposal_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(finalized.status, ProposalStatus::Rejected);
+    }
+
+    #[test]
+    fn test_config_change_requires_stake_and_applies_on_execution() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (proposer_key, proposal_key, mint_key) = (Pubkey::new_unique(), proposal_address(&program_id, 1).0, Pubkey::new_unique());
+        let (config_key, _) = governance_config_address(&program_id);
+        let (staking_key, _) = crate::staking_contract::stake_address(&program_id, &proposer_key, &mint_key);
+        let pool_key = Pubkey::new_unique();
+        let (counter_key, system_program_key) = (proposal_counter_address(&program_id).0, solana_program::system_program::id());
+        let (mut l0, mut l1, mut l2, mut l3, mut l4, mut l5, mut l6, mut l7) = (0u64, 1_000_000_000u64, 0u64, 0u64, 0u64, 0u64, 0u64, 0u64);
+        let (mut l8, mut l9) = (0u64, 0u64);
+        let (program_config_key, mut program_config_data) = crate::test_utils::program_config(&program_id);
+        let mut proposal_data = vec![0u8; Proposal::LEN];
+        let (mut counter_data, mut system_program_data) = (vec![0u8; ProposalCounter::LEN], vec![]);
+        let mut config_data = governance_config_data(PARAMS, pool_key);
+        let mut pool_state_data = vec![0u8; StakingPool::LEN];
+        StakingPool::pack(StakingPool { epoch: 3, reward_per_token_stored: 7, ..StakingPool::new(0) }, &mut pool_state_data).unwrap();
+        let mut staking_data = vec![0u8; Stake::LEN];
+        Stake { amount: 499, is_initialized: true, ..Default::default() }
+            .pack_into_slice(&mut staking_data);
+        let (mut proposer_data, mut mint_data, mut admin_data) = (vec![], vec![], vec![]);
+        let proposal_acc = account_info(&proposal_key, false, true, &mut l0, &mut proposal_data, &program_id);
+        let proposer_acc = account_info(&proposer_key, true, true, &mut l1, &mut proposer_data, &program_id);
+        let config_acc = account_info(&config_key, false, true, &mut l2, &mut config_data, &program_id);
+        let staking_acc = account_info(&staking_key, false, false, &mut l3, &mut staking_data, &program_id);
+        let mint_acc = account_info(&mint_key, false, false, &mut l4, &mut mint_data, &program_id);
+        let (admin_key, _) = multisig::multisig_authority_address(&program_id);
+        let admin_acc = account_info(&admin_key, true, false, &mut l5, &mut admin_data, &program_id);
+        let pool_state_acc = account_info(&pool_key, false, false, &mut l6, &mut pool_state_data, &program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, &mut l7, &mut program_config_data, &program_id);
+        let counter_acc = account_info(&counter_key, false, true, &mut l8, &mut counter_data, &program_id);
+        let system_program_acc = account_info(&system_program_key, false, false, &mut l9, &mut system_program_data, &program_id);
+        fund_rent_exempt(&[&config_acc]);
+        let accounts =
+            [proposal_acc.clone(), proposer_acc, config_acc.clone(), staking_acc.clone(), mint_acc, pool_state_acc, counter_acc.clone(), system_program_acc];
+        let change = GovernanceParams { voting_period_seconds: 3 * 86400, ..PARAMS };
+
+        bank.set_unix_timestamp(1_000);
+        assert_eq!(
+            GovernanceContract::create_proposal(&program_id, &accounts, content("ipfs://shorten-voting"), ProposalKind::GovernanceRules(change), Vec::new()),
+            Err(GgtError::InsufficientStakeToPropose.into())
+        );
+        Stake { amount: 500, is_initialized: true, ..Default::default() }
+            .pack_into_slice(&mut staking_acc.try_borrow_mut_data().unwrap());
+        GovernanceContract::create_proposal(&program_id, &accounts, content("ipfs://shorten-voting"), ProposalKind::GovernanceRules(change), Vec::new()).unwrap();
+        let mut created = Proposal::unpack_from_slice(&proposal_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((created.voting_ends_at, created.kind), (1_100, ProposalKind::GovernanceRules(change)));
+        assert_eq!((created.snapshot_epoch, created.snapshot_reward_index), (3, 7));
+        assert_eq!(ProposalCounter::unpack(&counter_acc.try_borrow_data().unwrap()).unwrap().next_id, 2);
+        // The counter has moved on to 2, so proposal 1's address no longer matches.
+        assert_eq!(
+            GovernanceContract::create_proposal(&program_id, &accounts, content("ipfs://shorten-voting"), ProposalKind::Text, Vec::new()),
+            Err(ProgramError::InvalidSeeds)
+        );
+
+        created.status = ProposalStatus::Passed;
+        created.pack_into_slice(&mut proposal_acc.try_borrow_mut_data().unwrap());
+        bank.set_unix_timestamp(1_100);
+        let execute_accounts = [proposal_acc, admin_acc, program_config_acc, config_acc.clone()];
+        assert_eq!(GovernanceContract::execute_proposal(&program_id, &execute_accounts, 2), Err(ProgramError::InvalidSeeds));
+        GovernanceContract::execute_proposal(&program_id, &execute_accounts, 1).unwrap();
+        let config = GovernanceConfig::unpack(&config_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(config.params, change);
+    }
+
+    #[test]
+    fn test_parameter_change_proposal_sets_one_staking_field() {
+        use crate::staking_contract::{staking_config_address, StakingEconomics};
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (staking_config_key, _) = staking_config_address(&program_id);
+        let economics = StakingEconomics { penalty_bps_over_90_days: 1_000, penalty_bps_over_30_days: 700, penalty_bps: 500, unstake_cooldown_seconds: 0 };
+        let mut staking_config_data = vec![0u8; StakingConfig::LEN];
+        StakingConfig::pack(StakingConfig { economics, last_econ_change: 0, is_initialized: true }, &mut staking_config_data).unwrap();
+        let (program_config_key, mut program_config_data) = crate::test_utils::program_config(&program_id);
+        let (proposal_key, mut l0, mut l1, mut l2, mut l3, mut admin_data) = (proposal_address(&program_id, 1).0, 0u64, 0u64, 0u64, 0u64, vec![]);
+        let mut proposal_data = proposal(100, 1_500, 100);
+        let proposal_acc = account_info(&proposal_key, false, true, &mut l0, &mut proposal_data, &program_id);
+        let (admin_key, _) = multisig::multisig_authority_address(&program_id);
+        let admin_acc = account_info(&admin_key, true, false, &mut l1, &mut admin_data, &program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, &mut l2, &mut program_config_data, &program_id);
+        let staking_config_acc = account_info(&staking_config_key, false, true, &mut l3, &mut staking_config_data, &program_id);
+        fund_rent_exempt(&[&proposal_acc, &staking_config_acc]);
+        let accounts = [proposal_acc.clone(), admin_acc, program_config_acc, staking_config_acc.clone()];
+        bank.set_unix_timestamp(500);
+
+        let mut passed = Proposal::unpack_from_slice(&proposal_acc.try_borrow_data().unwrap()).unwrap();
+        passed.status = ProposalStatus::Passed;
+        for (value, result) in [(70_000, Err(ProgramError::InvalidArgument)), (10_001, Err(ProgramError::InvalidArgument)), (800, Ok(()))] {
+            passed.kind = ProposalKind::ParameterChange(ParameterChange { field: ConfigField::StakingPenaltyBps, value });
+            passed.pack_into_slice(&mut proposal_acc.try_borrow_mut_data().unwrap());
+            assert_eq!(GovernanceContract::execute_proposal(&program_id, &accounts, 1), result);
+        }
+        let config = StakingConfig::unpack(&staking_config_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((config.economics, config.last_econ_change), (StakingEconomics { penalty_bps: 800, ..economics }, 500));
+        let executed = Proposal::unpack_from_slice(&proposal_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(executed.status, ProposalStatus::Executed);
+    }
+
+    #[test]
+    fn test_execute_proposal_invokes_payload_as_governance_authority() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let (governance_authority_key, _) = governance_authority_address(&program_id);
+        let (proposal_key, vault_key, recipient_key) = (proposal_address(&program_id, 1).0, Pubkey::new_unique(), Pubkey::new_unique());
+        let transfer = spl_token::instruction::transfer(&token_program_id, &vault_key, &recipient_key, &governance_authority_key, &[], 250)
+            .unwrap();
+        let payload = ProposalInstruction {
+            program_id: transfer.program_id,
+            accounts: transfer
+                .accounts
+                .iter()
+                .map(|meta| ProposalAccountMeta { pubkey: meta.pubkey, is_signer: meta.is_signer, is_writable: meta.is_writable })
+                .collect(),
+            data: transfer.data.clone(),
+        };
+        assert_eq!(Instruction::from(&payload), transfer);
+
+        let mut proposal_data = proposal(100, 1_500, 100);
+        let mut passed = Proposal::unpack_from_slice(&proposal_data).unwrap();
+        passed.status = ProposalStatus::Passed;
+        passed.instructions = vec![payload.clone()];
+        passed.pack_into_slice(&mut proposal_data);
+        let (mut l0, mut l1, mut l2, mut l3, mut l4, mut l5, mut l7) = (0u64, 0u64, 0u64, 0u64, 0u64, 0u64, 0u64);
+        let (mut d1, mut d2, mut d3, mut d4, mut d5) = (vec![], vec![], vec![], vec![], vec![]);
+        let (program_config_key, mut program_config_data) = crate::test_utils::program_config(&program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, &mut l7, &mut program_config_data, &program_id);
+        let proposal_acc = account_info(&proposal_key, false, true, &mut l0, &mut proposal_data, &program_id);
+        let (admin_key, _) = multisig::multisig_authority_address(&program_id);
+        let admin_acc = account_info(&admin_key, true, false, &mut l1, &mut d1, &program_id);
+        let governance_authority_acc = account_info(&governance_authority_key, false, false, &mut l2, &mut d2, &program_id);
+        let vault_acc = account_info(&vault_key, false, true, &mut l3, &mut d3, &token_program_id);
+        let recipient_acc = account_info(&recipient_key, false, true, &mut l4, &mut d4, &token_program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, &mut l5, &mut d5, &program_id);
+        fund_rent_exempt(&[&proposal_acc]);
+        bank.set_balance(&vault_key, 1_000);
+        bank.set_unix_timestamp(100);
+
+        let (rogue_key, mut l6, mut d6) = (Pubkey::new_unique(), 0u64, vec![]);
+        let rogue_acc = account_info(&rogue_key, false, false, &mut l6, &mut d6, &program_id);
+        assert_eq!(
+            GovernanceContract::execute_proposal(&program_id, &[proposal_acc.clone(), admin_acc.clone(), program_config_acc.clone(), rogue_acc], 1),
+            Err(ProgramError::InvalidSeeds)
+        );
+
+        let accounts = [proposal_acc.clone(), admin_acc, program_config_acc, governance_authority_acc, vault_acc, recipient_acc, token_program_acc];
+        GovernanceContract::execute_proposal(&program_id, &accounts, 1).unwrap();
+        assert_eq!(bank.balance(&vault_key), 750);
+        assert_eq!(bank.balance(&recipient_key), 250);
+        let executed = Proposal::unpack_from_slice(&proposal_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((executed.status, executed.instructions), (ProposalStatus::Executed, vec![payload]));
+    }
+
+    #[test]
+    fn test_set_upgrade_authority_hands_this_programs_authority_to_governance() {
+        let _bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let loader_id = bpf_loader_upgradeable::id();
+        let (program_data_key, other_program_data_key) =
+            (bpf_loader_upgradeable::get_program_data_address(&program_id), bpf_loader_upgradeable::get_program_data_address(&Pubkey::new_unique()));
+        let (wallet_key, governance_authority_key) = (Pubkey::new_unique(), governance_authority_address(&program_id).0);
+        let (mut l0, mut l1, mut l2, mut l3, mut l4) = (0u64, 0u64, 0u64, 0u64, 0u64);
+        let (mut d0, mut d1, mut d2, mut d3, mut d4) = (vec![], vec![], vec![], vec![], vec![]);
+        let program_data_acc = account_info(&program_data_key, false, true, &mut l0, &mut d0, &loader_id);
+        let other_program_data_acc = account_info(&other_program_data_key, false, true, &mut l1, &mut d1, &loader_id);
+        let wallet_acc = account_info(&wallet_key, true, false, &mut l2, &mut d2, &program_id);
+        let governance_authority_acc = account_info(&governance_authority_key, false, false, &mut l3, &mut d3, &program_id);
+        let loader_acc = account_info(&loader_id, false, false, &mut l4, &mut d4, &program_id);
+
+        let other_program = [other_program_data_acc, wallet_acc.clone(), governance_authority_acc.clone(), loader_acc.clone()];
+        assert_eq!(GovernanceContract::set_upgrade_authority(&program_id, &other_program), Err(ProgramError::InvalidArgument));
+        let mut unsigned_wallet = wallet_acc.clone();
+        unsigned_wallet.is_signer = false;
+        let unsigned = [program_data_acc.clone(), unsigned_wallet, governance_authority_acc.clone(), loader_acc.clone()];
+        assert_eq!(GovernanceContract::set_upgrade_authority(&program_id, &unsigned), Err(ProgramError::MissingRequiredSignature));
+        let to_wallet = [program_data_acc.clone(), wallet_acc.clone(), wallet_acc.clone(), loader_acc.clone()];
+        assert_eq!(GovernanceContract::set_upgrade_authority(&program_id, &to_wallet), Err(ProgramError::InvalidSeeds));
+
+        GovernanceContract::set_upgrade_authority(&program_id, &[program_data_acc, wallet_acc, governance_authority_acc, loader_acc]).unwrap();
+    }
+
+    #[test]
+    fn test_treasury_spend_proposal_waits_for_timelock() {
+        use crate::treasury_contract::TreasuryAssets;
+        use solana_program::program_option::COption;
+        use spl_token::state::{Account as TokenAccount, AccountState};
+
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let (treasury_authority_key, _) = Treasury::treasury_authority(&program_id);
+        let (proposal_key, assets_key, vault_key, recipient_key) =
+            (proposal_address(&program_id, 1).0, Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let spend = TreasurySpend { vault: vault_key, recipient: recipient_key, amount: 400 };
+        let mut proposal_data = proposal(100, 1_500, 100);
+        let mut passed = Proposal::unpack_from_slice(&proposal_data).unwrap();
+        passed.status = ProposalStatus::Passed;
+        passed.kind = ProposalKind::TreasurySpend(spend);
+        passed.pack_into_slice(&mut proposal_data);
+        let mut assets_data = vec![0u8; TreasuryAssets::LEN];
+        let mut vault_data = vec![0u8; TokenAccount::LEN];
+        let vault = TokenAccount {
+            mint: Pubkey::new_unique(),
+            owner: treasury_authority_key,
+            amount: 1_000,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
+        TokenAccount::pack(vault, &mut vault_data).unwrap();
+        let mut lamports = [0u64; 9];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8] = &mut lamports;
+        let (mut d1, mut d2, mut d4, mut d5, mut d6) = (vec![], vec![], vec![], vec![], vec![]);
+        let (program_config_key, mut program_config_data) = crate::test_utils::program_config(&program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, l8, &mut program_config_data, &program_id);
+        let proposal_acc = account_info(&proposal_key, false, true, l0, &mut proposal_data, &program_id);
+        let (admin_key, _) = multisig::multisig_authority_address(&program_id);
+        let admin_acc = account_info(&admin_key, true, false, l1, &mut d1, &program_id);
+        let governance_acc = account_info(&GOVERNANCE_PUBKEY, true, false, l2, &mut d2, &program_id);
+        let assets_acc = account_info(&assets_key, false, true, l3, &mut assets_data, &program_id);
+        let recipient_acc = account_info(&recipient_key, false, true, l4, &mut d4, &token_program_id);
+        let treasury_authority_acc = account_info(&treasury_authority_key, false, false, l5, &mut d5, &program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, l6, &mut d6, &program_id);
+        let vault_acc = account_info(&vault_key, false, true, l7, &mut vault_data, &token_program_id);
+        fund_rent_exempt(&[&proposal_acc, &assets_acc]);
+        bank.set_balance(&vault_key, 1_000);
+        let set_asset_accounts = [assets_acc.clone(), governance_acc, program_config_acc.clone(), vault_acc.clone()];
+        Treasury::set_treasury_asset(&program_id, &set_asset_accounts, u64::MAX).unwrap();
+
+        let accounts = [
+            proposal_acc.clone(),
+            admin_acc,
+            program_config_acc.clone(),
+            assets_acc.clone(),
+            vault_acc.clone(),
+            recipient_acc,
+            treasury_authority_acc,
+            token_program_acc,
+        ];
+        bank.set_unix_timestamp(100 + TREASURY_SPEND_TIMELOCK_SECONDS - 1);
+        assert_eq!(GovernanceContract::execute_proposal(&program_id, &accounts, 1), Err(GgtError::TimelockNotElapsed.into()));
+
+        bank.set_unix_timestamp(100 + TREASURY_SPEND_TIMELOCK_SECONDS);
+        GovernanceContract::execute_proposal(&program_id, &accounts, 1).unwrap();
+        assert_eq!((bank.balance(&vault_key), bank.balance(&recipient_key)), (600, 400));
+        let executed = Proposal::unpack_from_slice(&proposal_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((executed.status, executed.kind), (ProposalStatus::Executed, ProposalKind::TreasurySpend(spend)));
+
+        Treasury::get_treasury_balance(&program_id, &[assets_acc, vault_acc]).unwrap();
+        assert_eq!(bank.return_data(), 600u64.to_le_bytes().to_vec());
+    }
+
+    #[test]
+    fn test_burn_proposal_burns_governance_tokens_and_owners_burn_their_own() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let (governance_authority_key, _) = governance_authority_address(&program_id);
+        let (proposal_key, mint_key, held_key, holder_key) =
+            (proposal_address(&program_id, 1).0, Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let burn = TokenBurn { token_account: held_key, amount: 300 };
+        let mut proposal_data = proposal(100, 1_500, 100);
+        let mut passed = Proposal::unpack_from_slice(&proposal_data).unwrap();
+        passed.status = ProposalStatus::Passed;
+        passed.kind = ProposalKind::TokenBurn(burn);
+        passed.pack_into_slice(&mut proposal_data);
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() }, &mut mint_data)
+            .unwrap();
+        let mut held_data = crate::test_utils::token_account_data(&mint_key, &governance_authority_key, 1_000);
+        let mut lamports = [0u64; 8];
+        let [l0, l1, l2, l3, l4, l5, l6, l7] = &mut lamports;
+        let (mut d1, mut d2, mut d3, mut d4) = (vec![], vec![], vec![], vec![]);
+        let (program_config_key, mut program_config_data) = crate::test_utils::program_config(&program_id);
+        let proposal_acc = account_info(&proposal_key, false, true, l0, &mut proposal_data, &program_id);
+        let (admin_key, _) = multisig::multisig_authority_address(&program_id);
+        let admin_acc = account_info(&admin_key, true, false, l1, &mut d1, &program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, l2, &mut program_config_data, &program_id);
+        let held_acc = account_info(&held_key, false, true, l3, &mut held_data, &token_program_id);
+        let mint_acc = account_info(&mint_key, false, true, l4, &mut mint_data, &token_program_id);
+        let governance_authority_acc = account_info(&governance_authority_key, false, false, l5, &mut d2, &program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, l6, &mut d3, &program_id);
+        let holder_acc = account_info(&holder_key, true, false, l7, &mut d4, &program_id);
+        fund_rent_exempt(&[&proposal_acc, &program_config_acc]);
+        bank.set_balance(&held_key, 1_000);
+
+        // Neither the admin nor anyone but the owner can burn directly.
+        for signer_acc in [&admin_acc, &holder_acc] {
+            let burn_accounts = [held_acc.clone(), mint_acc.clone(), signer_acc.clone(), token_program_acc.clone()];
+            assert_eq!(crate::TokenContract::burn_tokens(&burn_accounts, 300), Err(ProgramError::IllegalOwner));
+        }
+
+        bank.set_unix_timestamp(100);
+        let accounts = [
+            proposal_acc.clone(),
+            admin_acc.clone(),
+            program_config_acc,
+            held_acc.clone(),
+            mint_acc.clone(),
+            governance_authority_acc,
+            token_program_acc.clone(),
+        ];
+        GovernanceContract::execute_proposal(&program_id, &accounts, 1).unwrap();
+        assert_eq!(bank.balance(&held_key), 700);
+        let executed = Proposal::unpack_from_slice(&proposal_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((executed.status, executed.kind), (ProposalStatus::Executed, ProposalKind::TokenBurn(burn)));
+
+        let mut own_data = crate::test_utils::token_account_data(&mint_key, &holder_key, 50);
+        let (own_key, mut own_lamports) = (Pubkey::new_unique(), 0);
+        let own_acc = account_info(&own_key, false, true, &mut own_lamports, &mut own_data, &token_program_id);
+        bank.set_balance(&own_key, 50);
+        crate::TokenContract::burn_tokens(&[own_acc, mint_acc, holder_acc, token_program_acc], 20).unwrap();
+        assert_eq!(bank.balance(&own_key), 30);
+    }
+}
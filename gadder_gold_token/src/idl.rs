@@ -0,0 +1,2408 @@
+//! Anchor-compatible IDL (spec 0.1.0) for generating clients with Codama or
+//! `@coral-xyz/anchor`. Built with the `idl` feature:
+//!
+//! ```text
+//! cargo run --features idl --bin idl > gadder_gold_token.json
+//! ```
+//!
+//! The program is not an Anchor program, so three things differ from what
+//! Anchor would emit: instruction discriminators are the two-byte
+//! `[tag, INSTRUCTION_VERSION]` header; account layouts are the `Pack`
+//! layouts, with fixed-capacity lists written as a count byte followed by
+//! every slot and nested accounts keeping their own discriminator and version; and every
+//! instruction outside the ungated set takes the program config PDA as its
+//! last account, listed as `pause_config`. Account discriminators, events and
+//! errors are encoded exactly as Anchor encodes them, except that the legacy
+//! `LegacyStake` layout has no discriminator.
+//! Events and errors are encoded exactly as Anchor encodes them.
+
+use serde_json::{json, Map, Value};
+use solana_program::program_pack::Pack;
+
+use crate::address_book::{AddressBook, MAX_ADDRESS_BOOK_ENTRIES, MAX_CHAIN_LEN, MAX_DESTINATION_LEN};
+use crate::ai_contract::{ConsultantProfile, MatchRequest, OracleRegistry, PayoutSplit, MAX_ORACLES, MAX_SPLIT_MEMBERS};
+use crate::airdrop::Distribution;
+use crate::config::{AuditLog, AuthorityRole, ProgramConfig, AUDIT_LOG_CAPACITY};
+use crate::cross_chain_bridge_contract::{BridgeConfig, BridgeReceipt, ChainRegistry, OutflowWindow, MAX_CHAINS, MAX_GUARDIANS, OUTFLOW_BUCKETS};
+use crate::discriminator::{Discriminator, DISCRIMINATOR_LEN};
+use crate::bridge_transfer::BridgeTransfer;
+use crate::dispute::{ArbitrationConfig, Dispute, MAX_ARBITERS};
+use crate::payment_receipt::PaymentReceipt;
+use crate::denylist::{Denylist, MAX_DENYLIST_ENTRIES};
+use crate::reputation::Reputation;
+use crate::engagement::{Engagement, MAX_ENGAGEMENT_MILESTONES};
+use crate::error::GgtError;
+use crate::events::{self, Event};
+use crate::governance_contract::{BallotPage, GovernanceConfig, ProposalCounter, Vote, BALLOTS_PER_PAGE};
+use crate::invoice::Invoice;
+use crate::market_maker::{FastMint, MarketMaker};
+use crate::migration::MigrationState;
+use crate::multisig::{Multisig, MAX_MULTISIG_OWNERS};
+use crate::oracle_operator::{OracleBondConfig, OracleOperator};
+use crate::staking_contract::{CoStake, CoStakingPool, LegacyStake, Stake, StakeSummary, StakingConfig, StakingPool};
+use crate::streaming_contract::{PaymentStream, MAX_STREAM_RECIPIENTS};
+use crate::treasury_contract::{TreasuryAssets, MAX_TREASURY_ASSETS};
+use crate::vesting::VestingSchedule;
+use crate::vote_escrow::VoteEscrow;
+use crate::wrapped_asset::WrappedAsset;
+use crate::{GET_VERSION_TAG, INSTRUCTION_VERSION, SET_PAUSE_FLAGS_TAG};
+
+/// Tags dispatched before the pause gate, which take no trailing program config.
+const UNGATED_TAGS: &[u8] = &[GET_VERSION_TAG, 33, 34, 35, 36, 48, 49, 50, 68, SET_PAUSE_FLAGS_TAG, 94, 95, 96, 97, 98, 99, 109, 120, 121, 122, 131, 143, 144, 145, 147];
+
+#[derive(Clone, Debug)]
+enum Ty {
+    U8,
+    U16,
+    U32,
+    U64,
+    I64,
+    U128,
+    I128,
+    Bool,
+    Key,
+    Str,
+    Bytes,
+    Arr(Box<Ty>, usize),
+    List(Box<Ty>),
+    Opt(Box<Ty>),
+    Def(&'static str),
+}
+
+use Ty::*;
+
+fn arr(ty: Ty, len: usize) -> Ty {
+    Arr(Box::new(ty), len)
+}
+
+fn list(ty: Ty) -> Ty {
+    List(Box::new(ty))
+}
+
+fn opt(ty: Ty) -> Ty {
+    Opt(Box::new(ty))
+}
+
+impl Ty {
+    fn to_json(&self) -> Value {
+        match self {
+            U8 => json!("u8"),
+            U16 => json!("u16"),
+            U32 => json!("u32"),
+            U64 => json!("u64"),
+            I64 => json!("i64"),
+            U128 => json!("u128"),
+            I128 => json!("i128"),
+            Bool => json!("bool"),
+            Key => json!("pubkey"),
+            Str => json!("string"),
+            Bytes => json!("bytes"),
+            Arr(ty, len) => json!({ "array": [ty.to_json(), len] }),
+            List(ty) => json!({ "vec": ty.to_json() }),
+            Opt(ty) => json!({ "option": ty.to_json() }),
+            Def(name) => json!({ "defined": { "name": name } }),
+        }
+    }
+
+    /// Encoded size, or `None` if it depends on the value.
+    #[cfg(test)]
+    fn size(&self, types: &[TypeDef]) -> Option<usize> {
+        match self {
+            U8 | Bool => Some(1),
+            U16 => Some(2),
+            U32 => Some(4),
+            U64 | I64 => Some(8),
+            U128 | I128 => Some(16),
+            Key => Some(32),
+            Str | Bytes | List(_) | Opt(_) => None,
+            Arr(ty, len) => Some(ty.size(types)? * len),
+            Def(name) => types.iter().find(|def| def.name == *name)?.size(types),
+        }
+    }
+}
+
+type Fields = Vec<(&'static str, Ty)>;
+
+enum Kind {
+    Struct(Fields),
+    Enum(&'static [&'static str]),
+    /// Variants carrying at most one unnamed field.
+    TupleEnum(Vec<(&'static str, Option<Ty>)>),
+}
+
+struct TypeDef {
+    name: &'static str,
+    kind: Kind,
+}
+
+impl TypeDef {
+    #[cfg(test)]
+    fn size(&self, types: &[TypeDef]) -> Option<usize> {
+        match &self.kind {
+            Kind::Struct(fields) => fields.iter().map(|(_, ty)| ty.size(types)).sum(),
+            Kind::Enum(_) => Some(1),
+            Kind::TupleEnum(_) => None,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let ty = match &self.kind {
+            Kind::Struct(fields) => json!({ "kind": "struct", "fields": fields_json(fields) }),
+            Kind::Enum(variants) => {
+                json!({ "kind": "enum", "variants": variants.iter().map(|name| json!({ "name": name })).collect::<Vec<_>>() })
+            }
+            Kind::TupleEnum(variants) => {
+                let variants: Vec<Value> = variants
+                    .iter()
+                    .map(|(name, field)| match field {
+                        Some(ty) => json!({ "name": name, "fields": [ty.to_json()] }),
+                        None => json!({ "name": name }),
+                    })
+                    .collect();
+                json!({ "kind": "enum", "variants": variants })
+            }
+        };
+        json!({ "name": self.name, "type": ty })
+    }
+}
+
+fn fields_json(fields: &Fields) -> Vec<Value> {
+    fields.iter().map(|(name, ty)| json!({ "name": name, "type": ty.to_json() })).collect()
+}
+
+fn strukt(name: &'static str, fields: Fields) -> TypeDef {
+    TypeDef { name, kind: Kind::Struct(fields) }
+}
+
+fn enumeration(name: &'static str, variants: &'static [&'static str]) -> TypeDef {
+    TypeDef { name, kind: Kind::Enum(variants) }
+}
+
+/// Types used in instruction arguments and nested in account layouts.
+fn arg_types() -> Vec<TypeDef> {
+    vec![
+        strukt(
+            "TokenParams",
+            vec![("decimals", U8), ("name", Str), ("symbol", Str), ("uri", Str), ("freeze_authority", opt(Key))],
+        ),
+        strukt(
+            "GovernanceParams",
+            vec![
+                ("quorum_bps", U16),
+                ("approval_threshold_bps", U16),
+                ("voting_period_seconds", I64),
+                ("min_stake_to_propose", U64),
+                ("proposal_deposit", U64),
+                ("timelock_seconds", I64),
+            ],
+        ),
+        strukt(
+            "StakingEconomics",
+            vec![
+                ("penalty_bps_over_90_days", U16),
+                ("penalty_bps_over_30_days", U16),
+                ("penalty_bps", U16),
+                ("unstake_cooldown_seconds", I64),
+            ],
+        ),
+        strukt("TreasurySpend", vec![("vault", Key), ("recipient", Key), ("amount", U64)]),
+        strukt("TokenBurn", vec![("token_account", Key), ("amount", U64)]),
+        enumeration(
+            "ConfigField",
+            &[
+                "GovernanceQuorumBps",
+                "GovernanceApprovalThresholdBps",
+                "GovernanceVotingPeriodSeconds",
+                "GovernanceMinStakeToPropose",
+                "GovernanceProposalDeposit",
+                "GovernanceTimelockSeconds",
+                "StakingPenaltyBpsOver90Days",
+                "StakingPenaltyBpsOver30Days",
+                "StakingPenaltyBps",
+                "StakingUnstakeCooldownSeconds",
+                "BridgeGuardianThreshold",
+                "BridgeEvmGuardianThreshold",
+                "BridgeDailyOutflowCap",
+            ],
+        ),
+        strukt("ProposalContent", vec![("content_hash", arr(U8, 32)), ("uri", Str)]),
+        strukt("ParameterChange", vec![("field", Def("ConfigField")), ("value", U64)]),
+        strukt("CodeUpgrade", vec![("buffer", Key), ("spill", Key)]),
+        TypeDef {
+            name: "ProposalKind",
+            kind: Kind::TupleEnum(vec![
+                ("Text", None),
+                ("ParameterChange", Some(Def("ParameterChange"))),
+                ("TreasurySpend", Some(Def("TreasurySpend"))),
+                ("CodeUpgrade", Some(Def("CodeUpgrade"))),
+                ("GovernanceRules", Some(Def("GovernanceParams"))),
+                ("TokenBurn", Some(Def("TokenBurn"))),
+            ]),
+        },
+        strukt("ProposalAccountMeta", vec![("pubkey", Key), ("is_signer", Bool), ("is_writable", Bool)]),
+        strukt(
+            "ProposalInstruction",
+            vec![("program_id", Key), ("accounts", list(Def("ProposalAccountMeta"))), ("data", Bytes)],
+        ),
+        strukt("EvmProof", vec![("signature", arr(U8, 64)), ("recovery_id", U8)]),
+        strukt("StreamRecipientArg", vec![("token_account", Key), ("rate_per_second", U64)]),
+        strukt("StreamSchedule", vec![("deposit", U64), ("starts_at", I64), ("ends_at", I64)]),
+        strukt("SplitMember", vec![("member", Key), ("share_bps", U16)]),
+        strukt("Token2022MintParams", vec![("transfer_fee_bps", U16), ("maximum_fee", U64)]),
+        strukt(
+            "ChainInfo",
+            vec![
+                ("chain_id", U16),
+                ("name", Str),
+                ("address_format", Def("AddressFormat")),
+                ("decimals", U8),
+                ("signature_scheme", Def("SignatureScheme")),
+                ("fee_flat", U64),
+                ("fee_bps", U16),
+                ("wormhole_chain_id", U16),
+                ("wormhole_emitter", arr(U8, 32)),
+            ],
+        ),
+        strukt("ChainOutflowCap", vec![("chain_id", U16), ("cap", U64)]),
+        strukt(
+            "StakeInfo",
+            vec![
+                ("amount", U64),
+                ("lock_until", I64),
+                ("claimable_rewards", U64),
+                ("pending_unstake", U64),
+                ("claimable_at", I64),
+                ("has_receipt", Bool),
+            ],
+        ),
+        strukt(
+            "ProposalState",
+            vec![
+                ("status", Def("ProposalStatus")),
+                ("voting_ends_at", I64),
+                ("yes_weight", U64),
+                ("no_weight", U64),
+                ("abstain_weight", U64),
+                ("executable_at", I64),
+            ],
+        ),
+        enumeration("MatchStatus", &["Pending", "Fulfilled", "Expired", "Refunded"]),
+        enumeration("ProposalStatus", &["Active", "Passed", "Rejected", "Executed", "Cancelled"]),
+        enumeration("VoteChoice", &["No", "Yes", "Abstain"]),
+        enumeration("EngagementStatus", &["Open", "Completed", "Cancelled", "Disputed", "Resolved"]),
+        enumeration("BridgeTransferStatus", &["Pending", "Completed", "Cancelled"]),
+        enumeration(
+            "AuditAction",
+            &["Pause", "Unpause", "DrillPause", "SetTransferFee", "SetComplianceAuthority", "SetPauseFlags", "NominateAuthority", "AcceptAuthority"],
+        ),
+        enumeration("BridgeMode", &["LockRelease", "BurnMint"]),
+        enumeration("AddressFormat", &["Evm", "Svm", "Raw"]),
+        enumeration("SignatureScheme", &["Ed25519", "Secp256k1", "Wormhole"]),
+        enumeration("AuthorityRole", &["Admin", "Governance", "BridgeAdmin", "MetadataUpdate"]),
+        // Fixed-size slots of the account layouts below.
+        strukt(
+            "AddressBookEntry",
+            vec![
+                ("chain_len", U8),
+                ("chain", arr(U8, MAX_CHAIN_LEN)),
+                ("destination_len", U8),
+                ("destination", arr(U8, MAX_DESTINATION_LEN)),
+                ("evm_verified", Bool),
+            ],
+        ),
+        strukt("Nomination", vec![("nominee", Key), ("expires_at", I64)]),
+        strukt("AuditEntry", vec![("action", Def("AuditAction")), ("actor", Key), ("slot", U64), ("detail", U64)]),
+        strukt(
+            "ChainSlot",
+            vec![
+                ("chain_id", U16),
+                ("name_len", U8),
+                ("name", arr(U8, 16)),
+                ("address_format", Def("AddressFormat")),
+                ("decimals", U8),
+                ("signature_scheme", Def("SignatureScheme")),
+                ("fee_flat", U64),
+                ("fee_bps", U16),
+                ("wormhole_chain_id", U16),
+                ("wormhole_emitter", arr(U8, 32)),
+            ],
+        ),
+        strukt("ChainOutflow", vec![("chain_id", U16), ("buckets", arr(U64, OUTFLOW_BUCKETS))]),
+        strukt("StreamRecipient", vec![("token_account", Key), ("rate_per_second", U64), ("withdrawn", U64)]),
+        strukt("TreasuryAsset", vec![("mint", Key), ("vault", Key), ("max_holding", U64)]),
+    ]
+}
+
+/// Program-owned account layouts with their allocated `Pack::LEN` and the
+/// discriminator written ahead of the layout. Tagged layouts start with
+/// their version byte.
+fn account_layouts() -> Vec<(TypeDef, usize, &'static [u8])> {
+    let layouts: Vec<(TypeDef, usize, &'static [u8])> = vec![
+        (
+            strukt(
+                "AddressBook",
+                vec![
+                    ("owner", Key),
+                    ("strict", Bool),
+                    ("entry_count", U8),
+                    ("entries", arr(Def("AddressBookEntry"), MAX_ADDRESS_BOOK_ENTRIES)),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            AddressBook::LEN,
+            &AddressBook::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "MatchRequest",
+                vec![
+                    ("requester", Key),
+                    ("requirements_hash", arr(U8, 32)),
+                    ("fee", U64),
+                    ("created_at", I64),
+                    ("sla_deadline", I64),
+                    ("status", Def("MatchStatus")),
+                    ("matched_consultant", Key),
+                    ("fulfilled_by", Key),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            MatchRequest::LEN,
+            &MatchRequest::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "OracleRegistry",
+                vec![("oracle_count", U8), ("oracles", arr(Key, MAX_ORACLES)), ("is_initialized", Bool)],
+            ),
+            OracleRegistry::LEN,
+            &OracleRegistry::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "ConsultantProfile",
+                vec![
+                    ("consultant", Key),
+                    ("evm_address", arr(U8, 20)),
+                    ("evm_linked_at", I64),
+                    ("skills_hash", arr(U8, 32)),
+                    ("hourly_rate", U64),
+                    ("available", Bool),
+                    ("active", Bool),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            ConsultantProfile::LEN,
+            &ConsultantProfile::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "PayoutSplit",
+                vec![
+                    ("profile", Key),
+                    ("member_count", U8),
+                    ("members", arr(Def("SplitMember"), MAX_SPLIT_MEMBERS)),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            PayoutSplit::LEN,
+            &PayoutSplit::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "Distribution",
+                vec![
+                    ("creator", Key),
+                    ("mint", Key),
+                    ("vault", Key),
+                    ("merkle_root", arr(U8, 32)),
+                    ("distribution_id", U64),
+                    ("total_amount", U64),
+                    ("claimed_amount", U64),
+                    ("num_recipients", U32),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            Distribution::LEN,
+            &Distribution::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "ProgramConfig",
+                vec![
+                    ("paused", Bool),
+                    ("pause_until_slot", U64),
+                    ("fee_bps", U16),
+                    ("compliance_authority", Key),
+                    ("pause_flags", U8),
+                    ("admin", Key),
+                    ("governance", Key),
+                    ("bridge_admin", Key),
+                    ("nominations", arr(Def("Nomination"), AuthorityRole::COUNT)),
+                    ("total_burned", U64),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            ProgramConfig::LEN,
+            &ProgramConfig::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "AuditLog",
+                vec![
+                    ("entry_count", U8),
+                    ("entries", arr(Def("AuditEntry"), AUDIT_LOG_CAPACITY)),
+                    ("next_index", U8),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            AuditLog::LEN,
+            &AuditLog::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "BridgeConfig",
+                vec![
+                    ("guardian_count", U8),
+                    ("guardians", arr(Key, MAX_GUARDIANS)),
+                    ("guardian_threshold", U8),
+                    ("evm_guardian_count", U8),
+                    ("evm_guardians", arr(arr(U8, 20), MAX_GUARDIANS)),
+                    ("evm_guardian_threshold", U8),
+                    ("outbound_sequence", U64),
+                    ("mode", Def("BridgeMode")),
+                    ("daily_outflow_cap", U64),
+                    ("chain_outflow_cap_count", U8),
+                    ("chain_outflow_caps", arr(Def("ChainOutflowCap"), MAX_CHAINS)),
+                    ("transfer_timeout_seconds", I64),
+                    ("paused", Bool),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            BridgeConfig::LEN,
+            &BridgeConfig::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "ChainRegistry",
+                vec![("chain_count", U8), ("chains", arr(Def("ChainSlot"), MAX_CHAINS)), ("is_initialized", Bool)],
+            ),
+            ChainRegistry::LEN,
+            &ChainRegistry::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "BridgeReceipt",
+                vec![
+                    ("source_chain_id", U16),
+                    ("nonce", U64),
+                    ("amount", U64),
+                    ("recipient", Key),
+                    ("relayer", Key),
+                    ("relayer_fee", U64),
+                    ("fee_claimed", Bool),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            BridgeReceipt::LEN,
+            &BridgeReceipt::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "BridgeTransfer",
+                vec![
+                    ("sender", Key),
+                    ("sender_token", Key),
+                    ("mint", Key),
+                    ("sequence", U64),
+                    ("amount", U64),
+                    ("fee", U64),
+                    ("target_chain_id", U16),
+                    ("destination_len", U8),
+                    ("destination", arr(U8, MAX_DESTINATION_LEN)),
+                    ("status", Def("BridgeTransferStatus")),
+                    ("created_at", I64),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            BridgeTransfer::LEN,
+            &BridgeTransfer::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "OutflowWindow",
+                vec![
+                    ("current_hour", I64),
+                    ("global", arr(U64, OUTFLOW_BUCKETS)),
+                    ("chain_count", U8),
+                    ("chains", arr(Def("ChainOutflow"), MAX_CHAINS)),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            OutflowWindow::LEN,
+            &OutflowWindow::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "Engagement",
+                vec![
+                    ("client", Key),
+                    ("consultant", Key),
+                    ("engagement_id", U64),
+                    ("vault", Key),
+                    ("amount", U64),
+                    ("opened_at", I64),
+                    ("deadline", I64),
+                    ("released", U64),
+                    ("milestone_count", U8),
+                    ("milestones", arr(U64, MAX_ENGAGEMENT_MILESTONES)),
+                    ("milestones_approved", U8),
+                    ("status", Def("EngagementStatus")),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            Engagement::LEN,
+            &Engagement::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "ArbitrationConfig",
+                vec![
+                    ("council_count", U8),
+                    ("council", arr(Key, MAX_ARBITERS)),
+                    ("dispute_bond_bps", U16),
+                    ("loser_slash_bps", U16),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            ArbitrationConfig::LEN,
+            &ArbitrationConfig::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "Dispute",
+                vec![
+                    ("engagement", Key),
+                    ("opened_by", Key),
+                    ("bond", U64),
+                    ("reason_hash", arr(U8, 32)),
+                    ("opened_at", I64),
+                    ("consultant_bps", U16),
+                    ("resolved", Bool),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            Dispute::LEN,
+            &Dispute::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "Reputation",
+                vec![
+                    ("wallet", Key),
+                    ("completed_engagements", U32),
+                    ("disputes_lost", U32),
+                    ("total_volume", U64),
+                    ("score", U16),
+                    ("updated_at", I64),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            Reputation::LEN,
+            &Reputation::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "PaymentReceipt",
+                vec![
+                    ("reference", arr(U8, 32)),
+                    ("sender", Key),
+                    ("recipient", Key),
+                    ("mint", Key),
+                    ("amount", U64),
+                    ("fee", U64),
+                    ("paid_at", I64),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            PaymentReceipt::LEN,
+            &PaymentReceipt::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "Denylist",
+                vec![("entry_count", U8), ("entries", arr(Key, MAX_DENYLIST_ENTRIES)), ("is_initialized", Bool)],
+            ),
+            Denylist::LEN,
+            &Denylist::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "GovernanceConfig",
+                vec![("params", Def("GovernanceParams")), ("staking_pool", Key), ("is_initialized", Bool)],
+            ),
+            GovernanceConfig::LEN,
+            &GovernanceConfig::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "Proposal",
+                vec![
+                    ("uri", Str),
+                    ("content_hash", arr(U8, 32)),
+                    ("proposer", Key),
+                    ("status", Def("ProposalStatus")),
+                    ("timestamp", I64),
+                    ("voting_ends_at", I64),
+                    ("yes_weight", U64),
+                    ("no_weight", U64),
+                    ("abstain_weight", U64),
+                    ("escrow_weight", U64),
+                    ("ballot_pages", U16),
+                    ("pages_tallied", U16),
+                    ("kind", Def("ProposalKind")),
+                    ("deposit", U64),
+                    ("snapshot_epoch", U64),
+                    ("snapshot_reward_index", U128),
+                    ("executable_at", I64),
+                    ("is_initialized", Bool),
+                    ("instructions", list(Def("ProposalInstruction"))),
+                ],
+            ),
+            crate::governance_contract::Proposal::LEN,
+            &crate::governance_contract::Proposal::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "Vote",
+                vec![("proposal", Key), ("voter", Key), ("choice", Def("VoteChoice")), ("weight", U64), ("is_initialized", Bool)],
+            ),
+            Vote::LEN,
+            &Vote::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "VoteEscrow",
+                vec![
+                    ("owner", Key),
+                    ("mint", Key),
+                    ("amount", U64),
+                    ("unlock_at", I64),
+                    ("updated_at", I64),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            VoteEscrow::LEN,
+            &VoteEscrow::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "BallotPage",
+                vec![
+                    ("proposal", Key),
+                    ("page_index", U16),
+                    ("voter_count", U8),
+                    ("voters", arr(Key, BALLOTS_PER_PAGE)),
+                    ("in_favor", U32),
+                    ("weights", arr(U64, BALLOTS_PER_PAGE)),
+                    ("escrow_weight", U64),
+                    ("tallied", Bool),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            BallotPage::LEN,
+            &BallotPage::DISCRIMINATOR,
+        ),
+        (strukt("ProposalCounter", vec![("next_id", U64), ("is_initialized", Bool)]), ProposalCounter::LEN, &ProposalCounter::DISCRIMINATOR),
+        (
+            strukt(
+                "Invoice",
+                vec![
+                    ("client", Key),
+                    ("credit_limit", U64),
+                    ("accrued", U64),
+                    ("outstanding", U64),
+                    ("period_ends_at", I64),
+                    ("due_at", I64),
+                    ("suspended", Bool),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            Invoice::LEN,
+            &Invoice::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "MarketMaker",
+                vec![
+                    ("maker", Key),
+                    ("collateral_vault", Key),
+                    ("collateral_ratio_bps", U16),
+                    ("outstanding", U64),
+                    ("approved", Bool),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            MarketMaker::LEN,
+            &MarketMaker::DISCRIMINATOR,
+        ),
+        // Named apart from the `FastMint` event, whose discriminator depends on its name.
+        (
+            strukt(
+                "FastMintRecord",
+                vec![
+                    ("maker", Key),
+                    ("sequence", U64),
+                    ("target_chain_id", U16),
+                    ("amount", U64),
+                    ("reconciled", Bool),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            FastMint::LEN,
+            &FastMint::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "MigrationState",
+                vec![
+                    ("legacy_mint", Key),
+                    ("new_mint", Key),
+                    ("started_at", I64),
+                    ("cutoff_at", I64),
+                    ("epoch", U64),
+                    ("epoch_migrated", U64),
+                    ("total_migrated", U64),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            MigrationState::LEN,
+            &MigrationState::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "Multisig",
+                vec![
+                    ("owner_count", U8),
+                    ("owners", arr(Key, MAX_MULTISIG_OWNERS)),
+                    ("threshold", U8),
+                    ("nonce", U64),
+                    ("owners_set_at", U64),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            Multisig::LEN,
+            &Multisig::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "AdminAction",
+                vec![
+                    ("nonce", U64),
+                    ("proposer", Key),
+                    ("approvals", U16),
+                    ("executed", Bool),
+                    ("is_initialized", Bool),
+                    ("instruction", Def("ProposalInstruction")),
+                ],
+            ),
+            crate::multisig::AdminAction::LEN,
+            &crate::multisig::AdminAction::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "OracleBondConfig",
+                vec![
+                    ("mint", Key),
+                    ("bond_vault", Key),
+                    ("min_bond", U64),
+                    ("withdrawal_delay_seconds", I64),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            OracleBondConfig::LEN,
+            &OracleBondConfig::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "OracleOperator",
+                vec![("operator", Key), ("bond", U64), ("active", Bool), ("withdrawable_at", I64), ("is_initialized", Bool)],
+            ),
+            OracleOperator::LEN,
+            &OracleOperator::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "StakingConfig",
+                vec![("economics", Def("StakingEconomics")), ("last_econ_change", I64), ("is_initialized", Bool)],
+            ),
+            StakingConfig::LEN,
+            &StakingConfig::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "Stake",
+                vec![
+                    ("amount", U64),
+                    ("lock_until", I64),
+                    ("reward_per_token_paid", U128),
+                    ("rewards_owed", U64),
+                    ("staked_at", I64),
+                    ("pending_unstake", U64),
+                    ("claimable_at", I64),
+                    ("voting_delegate", Key),
+                    ("delegated_at", I64),
+                    ("has_receipt", Bool),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            Stake::LEN,
+            &Stake::DISCRIMINATOR,
+        ),
+        (
+            strukt("LegacyStake", vec![("amount", U64), ("lock_until", I64), ("is_initialized", Bool)]),
+            LegacyStake::LEN,
+            &[],
+        ),
+        (
+            strukt(
+                "StakingPool",
+                vec![
+                    ("total_staked", U64),
+                    ("reward_pool", U64),
+                    ("penalty_pool", U64),
+                    ("reward_per_token_stored", U128),
+                    ("last_update_time", I64),
+                    ("epoch", U64),
+                    ("epoch_ends_at", I64),
+                    ("reward_rate_per_second", U64),
+                    ("rewards_end_at", I64),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            StakingPool::LEN,
+            &StakingPool::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "CoStakingPool",
+                vec![
+                    ("ggt_mint", Key),
+                    ("partner_mint", Key),
+                    ("ggt_vault", Key),
+                    ("partner_vault", Key),
+                    ("partner_ratio_bps", U64),
+                    ("boost_bps", U64),
+                    ("rewards_discriminator", arr(U8, DISCRIMINATOR_LEN)),
+                    ("rewards", Def("StakingPool")),
+                    ("rewards_vault", Key),
+                ],
+            ),
+            CoStakingPool::LEN,
+            &CoStakingPool::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "CoStake",
+                vec![
+                    ("ggt_amount", U64),
+                    ("partner_amount", U64),
+                    ("weight_discriminator", arr(U8, DISCRIMINATOR_LEN)),
+                    ("weight", Def("Stake")),
+                ],
+            ),
+            CoStake::LEN,
+            &CoStake::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "StakeSummary",
+                vec![
+                    ("owner", Key),
+                    ("total_staked", U64),
+                    ("weighted_unlock_at", I64),
+                    ("lock_weight", I128),
+                    ("pending_rewards", U64),
+                    ("positions", U32),
+                    ("updated_at", I64),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            StakeSummary::LEN,
+            &StakeSummary::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "PaymentStream",
+                vec![
+                    ("payer", Key),
+                    ("vault", Key),
+                    ("recipient_count", U8),
+                    ("recipients", arr(Def("StreamRecipient"), MAX_STREAM_RECIPIENTS)),
+                    ("started_at", I64),
+                    ("ends_at", I64),
+                    ("closed", Bool),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            PaymentStream::LEN,
+            &PaymentStream::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "TreasuryAssets",
+                vec![
+                    ("asset_count", U8),
+                    ("assets", arr(Def("TreasuryAsset"), MAX_TREASURY_ASSETS)),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            TreasuryAssets::LEN,
+            &TreasuryAssets::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "VestingSchedule",
+                vec![
+                    ("beneficiary", Key),
+                    ("vault", Key),
+                    ("total_amount", U64),
+                    ("claimed", U64),
+                    ("start", I64),
+                    ("cliff_seconds", I64),
+                    ("duration_seconds", I64),
+                    ("revoked", Bool),
+                    ("is_initialized", Bool),
+                ],
+            ),
+            VestingSchedule::LEN,
+            &VestingSchedule::DISCRIMINATOR,
+        ),
+        (
+            strukt(
+                "WrappedAsset",
+                vec![("chain_id", U16), ("foreign_token", arr(U8, 32)), ("mint", Key), ("decimals", U8), ("is_initialized", Bool)],
+            ),
+            WrappedAsset::LEN,
+            &WrappedAsset::DISCRIMINATOR,
+        ),
+    ];
+    layouts
+        .into_iter()
+        .map(|(mut def, len, discriminator)| {
+            if let (Kind::Struct(fields), false) = (&mut def.kind, discriminator.is_empty()) {
+                fields.insert(0, ("version", U8));
+            }
+            (def, len, discriminator)
+        })
+        .collect()
+}
+
+fn event<E: Event>(fields: Fields) -> ([u8; 8], TypeDef) {
+    (E::discriminator(), strukt(E::NAME, fields))
+}
+
+fn event_types() -> Vec<([u8; 8], TypeDef)> {
+    vec![
+        event::<events::TransferEvent>(vec![
+            ("source", Key),
+            ("destination", Key),
+            ("authority", Key),
+            ("amount", U64),
+            ("fee", U64),
+        ]),
+        event::<events::BatchTransferEvent>(vec![
+            ("source", Key),
+            ("authority", Key),
+            ("recipients", list(Key)),
+            ("amounts", list(U64)),
+            ("fee", U64),
+        ]),
+        event::<events::DelegateApproved>(vec![("source", Key), ("owner", Key), ("delegate", Key), ("amount", U64)]),
+        event::<events::DelegateRevoked>(vec![("source", Key), ("owner", Key)]),
+        event::<events::StakeEvent>(vec![("staker", Key), ("mint", Key), ("amount", U64), ("lock_until", I64)]),
+        event::<events::UnstakeEvent>(vec![("staker", Key), ("mint", Key), ("amount", U64), ("penalty", U64)]),
+        event::<events::StakeSlashed>(vec![("staker", Key), ("mint", Key), ("amount", U64), ("reason_hash", arr(U8, 32))]),
+        event::<events::VotingDelegateSet>(vec![("staker", Key), ("mint", Key), ("delegate", Key)]),
+        event::<events::VoteEscrowLocked>(vec![("owner", Key), ("mint", Key), ("amount", U64), ("unlock_at", I64)]),
+        event::<events::VoteEscrowWithdrawn>(vec![("owner", Key), ("mint", Key), ("amount", U64)]),
+        event::<events::EngagementOpened>(vec![
+            ("engagement", Key),
+            ("client", Key),
+            ("consultant", Key),
+            ("engagement_id", U64),
+            ("milestones", list(U64)),
+        ]),
+        event::<events::MilestoneApproved>(vec![("engagement", Key), ("milestone_index", U8), ("amount", U64), ("fee", U64)]),
+        event::<events::EngagementCompleted>(vec![("engagement", Key), ("amount", U64), ("fee", U64)]),
+        event::<events::PaymentReferenced>(vec![("reference", arr(U8, 32)), ("sender", Key), ("recipient", Key), ("amount", U64), ("fee", U64)]),
+        event::<events::TreasuryBurned>(vec![("vault", Key), ("mint", Key), ("amount", U64), ("total_burned", U64)]),
+        event::<events::DenylistAdded>(vec![("address", Key), ("authority", Key)]),
+        event::<events::DenylistRemoved>(vec![("address", Key), ("authority", Key)]),
+        event::<events::ProposalCreated>(vec![
+            ("proposal", Key),
+            ("proposer", Key),
+            ("voting_ends_at", I64),
+            ("content_hash", arr(U8, 32)),
+            ("uri", Str),
+        ]),
+        event::<events::VoteCast>(vec![("proposal", Key), ("voter", Key), ("choice", Def("VoteChoice")), ("weight", U64)]),
+        event::<events::VoteChanged>(vec![
+            ("proposal", Key),
+            ("voter", Key),
+            ("previous", Def("VoteChoice")),
+            ("choice", Def("VoteChoice")),
+            ("weight", U64),
+        ]),
+        event::<events::BridgeLock>(vec![
+            ("transfer_id", arr(U8, 32)),
+            ("sender", Key),
+            ("mint", Key),
+            ("sequence", U64),
+            ("target_chain_id", U16),
+            ("destination", Bytes),
+            ("amount", U64),
+            ("fee", U64),
+        ]),
+        event::<events::BridgeRelease>(vec![("source_chain_id", U16), ("nonce", U64), ("recipient", Key), ("amount", U64)]),
+        event::<events::BridgeTransferCancelled>(vec![
+            ("transfer_id", arr(U8, 32)),
+            ("sender", Key),
+            ("sequence", U64),
+            ("amount", U64),
+            ("fee", U64),
+        ]),
+        event::<events::RelayerFeeClaimed>(vec![("source_chain_id", U16), ("nonce", U64), ("relayer", Key), ("amount", U64)]),
+        event::<events::WrappedRelease>(vec![
+            ("source_chain_id", U16),
+            ("foreign_token", arr(U8, 32)),
+            ("mint", Key),
+            ("nonce", U64),
+            ("recipient", Key),
+            ("amount", U64),
+        ]),
+        event::<events::TokensMigrated>(vec![
+            ("holder", Key),
+            ("amount", U64),
+            ("epoch", U64),
+            ("epoch_migrated", U64),
+            ("total_migrated", U64),
+        ]),
+        event::<events::FastMint>(vec![
+            ("transfer_id", arr(U8, 32)),
+            ("maker", Key),
+            ("sequence", U64),
+            ("target_chain_id", U16),
+            ("destination", Bytes),
+            ("amount", U64),
+        ]),
+        event::<events::FastMintReconciled>(vec![("transfer_id", arr(U8, 32)), ("maker", Key), ("amount", U64)]),
+        event::<events::OracleRegistered>(vec![("operator", Key), ("bond", U64)]),
+        event::<events::OracleDeregistered>(vec![("operator", Key), ("withdrawable_at", I64)]),
+        event::<events::OracleSlashed>(vec![("operator", Key), ("amount", U64), ("active", Bool), ("reason_hash", arr(U8, 32))]),
+    ]
+}
+
+struct Ix {
+    tag: u8,
+    name: &'static str,
+    /// `name` optionally followed by `:` and any of `w` (writable), `s`
+    /// (signer) and `?` (optional).
+    accounts: &'static [&'static str],
+    args: Fields,
+    docs: &'static str,
+}
+
+fn ix(tag: u8, name: &'static str, accounts: &'static [&'static str], args: Fields, docs: &'static str) -> Ix {
+    Ix { tag, name, accounts, args, docs }
+}
+
+const CREATE_PROPOSAL_ACCOUNTS: &[&str] =
+    &["proposal:w", "proposer:ws", "governance_config", "stake", "mint", "pool_state", "proposal_counter:w", "system_program"];
+const CREATE_PROPOSAL_DOCS: &str =
+    "When governance requires a deposit, pass the proposer's token account, the deposit vault and the token program as remaining accounts.";
+const CONFIG_UPDATE_ACCOUNTS: &[&str] = &["program_config:w", "authority:s", "audit_log:w"];
+const AUTHORITY_TRANSFER_ACCOUNTS: &[&str] = &["program_config:w", "signer:s", "audit_log:w"];
+const AUTHORITY_TRANSFER_DOCS: &str = "The metadata role also takes the GGT mint's metadata account, then for accept_authority the outgoing update authority, which must sign too, and the token metadata program.";
+const TRANSFER_ACCOUNTS: &[&str] =
+    &["source:w", "destination:w", "owner", "token_program", "mint", "program_config", "fee_vault:w", "denylist", "delegate:s?"];
+const DENYLIST_ACCOUNTS: &[&str] = &["denylist:w", "compliance_authority:ws", "program_config", "system_program"];
+const STAKE_ACCOUNTS: &[&str] =
+    &["stake:w", "staker_token:w", "pool:w", "staker:s", "token_program", "pool_state:w", "mint", "denylist", "stake_summary:w?"];
+const LOCK_ACCOUNTS: &[&str] = &[
+    "sender:ws",
+    "sender_token:w",
+    "bridge_vault:w",
+    "token_program",
+    "bridge_config:w",
+    "mint:w",
+    "address_book",
+    "chain_registry",
+    "relayer_fee_vault:w",
+    "bridge_transfer:w",
+    "system_program",
+    "denylist",
+    "wormhole_bridge:w?",
+    "wormhole_message:w?",
+    "wormhole_emitter?",
+    "wormhole_sequence:w?",
+    "wormhole_fee_collector:w?",
+    "clock?",
+    "rent?",
+    "wormhole_program?",
+];
+const STREAM_PAYOUT_ACCOUNTS: &[&str] = &["stream:w", "vault:w", "destination:w", "vault_authority", "token_program"];
+const STREAM_PAYOUT_DOCS: &str = "The recipient token accounts follow as remaining accounts.";
+
+fn instructions() -> Vec<Ix> {
+    vec![
+        ix(
+            0,
+            "initialize_token",
+            &[
+                "mint:w",
+                "authority:s",
+                "token_program",
+                "metadata:w",
+                "metadata_mint",
+                "mint_authority:s",
+                "payer:ws",
+                "update_authority",
+                "system_program",
+                "rent",
+                "token_metadata_program",
+            ],
+            vec![("params", Def("TokenParams"))],
+            "The mint is the PDA of [\"ggt-mint\", program_id] and can be created once. An empty payload launches GGT with the default TokenParams.",
+        ),
+        ix(
+            1,
+            "transfer_tokens",
+            TRANSFER_ACCOUNTS,
+            vec![("amount", U64)],
+            "The delegate, when passed, signs and spends its allowance; otherwise the owner signs.",
+        ),
+        ix(
+            2,
+            "burn_tokens",
+            &["token_account:w", "mint:w", "owner:s", "token_program"],
+            vec![("amount", U64)],
+            "Only the token account's owner can burn; governance burns through a proposal carrying a TokenBurn.",
+        ),
+        ix(3, "stake_tokens", STAKE_ACCOUNTS, vec![("amount", U64), ("lock_period_in_days", U64)], "The mint must be GGT."),
+        ix(
+            4,
+            "unstake_tokens",
+            &[
+                "stake:w",
+                "pool:w",
+                "pool_authority",
+                "staker_token:w",
+                "staker:s",
+                "token_program",
+                "pool_state:w",
+                "mint",
+                "staking_config",
+                "rewards_vault:w",
+                "stake_summary:w?",
+            ],
+            vec![("amount", U64)],
+            "An early-exit penalty moves into the rewards vault and is credited to the remaining stakers.",
+        ),
+        ix(
+            5,
+            "create_proposal",
+            CREATE_PROPOSAL_ACCOUNTS,
+            vec![("content", Def("ProposalContent"))],
+            CREATE_PROPOSAL_DOCS,
+        ),
+        ix(
+            6,
+            "execute_proposal",
+            &["proposal:w", "authority:s", "authority_config"],
+            vec![("proposal_id", U64)],
+            "The proposal must be the address derived from proposal_id. Which remaining accounts are expected depends on the proposal's kind and instructions.",
+        ),
+        ix(
+            7,
+            "vote_on_proposal",
+            &["vote:w", "voter:ws", "proposal:w", "stake", "system_program", "mint", "vote_escrow?"],
+            vec![("proposal_id", U64), ("choice", Def("VoteChoice"))],
+            "The proposal must be the address derived from proposal_id. The stake is the voter's own for mint, or one delegated to the voter. The voter's vote-escrow lock for mint, if given, adds its weight, which counts towards approval but not quorum. Further stakes delegated to the voter follow as remaining accounts and add theirs, each counted once.",
+        ),
+        ix(
+            8,
+            "lock_tokens_for_bridge",
+            LOCK_ACCOUNTS,
+            vec![("amount", U64), ("target_chain_id", U16)],
+            "The target chain's relayer fee is taken out of amount. Wormhole chains also need the core bridge accounts.",
+        ),
+        ix(
+            9,
+            "release_tokens_on_target_chain",
+            &[
+                "bridge_vault:w",
+                "recipient_token:w",
+                "vault_authority",
+                "bridge_config",
+                "instructions_sysvar",
+                "token_program",
+                "receipt:w",
+                "payer:ws",
+                "system_program",
+                "mint:w",
+                "chain_registry",
+                "outflow_window:w",
+            ],
+            vec![("amount", U64), ("nonce", U64), ("source_chain_id", U16)],
+            "Guardian signatures are verified from the precompile instructions earlier in the transaction. For Wormhole chains, instructions_sysvar is the posted VAA instead.",
+        ),
+        ix(11, "initialize_pool", &["pool_state:w", "admin:s"], vec![], ""),
+        ix(
+            12,
+            "claim_rewards",
+            &[
+                "stake:w",
+                "pool_state:w",
+                "rewards_vault:w",
+                "vault_authority",
+                "staker_token:w",
+                "staker:s",
+                "token_program",
+                "mint",
+                "stake_summary:w?",
+            ],
+            vec![],
+            "",
+        ),
+        ix(
+            13,
+            "finalize_proposal",
+            &["proposal:w", "governance_config", "pool_state"],
+            vec![("proposal_id", U64)],
+            "The proposal must be the address derived from proposal_id. A proposal holding a deposit takes the deposit release accounts as remaining accounts.",
+        ),
+        ix(
+            GET_VERSION_TAG,
+            "get_version",
+            &[],
+            vec![],
+            "Accepted with any version byte. Returns [MIN_SUPPORTED_INSTRUCTION_VERSION, INSTRUCTION_VERSION] as return data.",
+        ),
+        ix(
+            15,
+            "initialize_bridge_config",
+            &["bridge_config:w", "bridge_admin:s"],
+            vec![("guardian_threshold", U8), ("guardians", list(Key))],
+            "",
+        ),
+        ix(
+            16,
+            "set_guardian_set",
+            &["bridge_config:w", "bridge_admin:s"],
+            vec![("guardian_threshold", U8), ("guardians", list(Key))],
+            "",
+        ),
+        ix(
+            17,
+            "request_match",
+            &["match_request:w", "requester:s", "requester_token:w", "escrow_vault:w", "token_program"],
+            vec![("fee", U64), ("sla_seconds", I64), ("requirements", Str)],
+            "",
+        ),
+        ix(
+            18,
+            "reclaim_match_fee",
+            &["match_request:w", "requester:s", "requester_token:w", "escrow_vault:w", "escrow_authority", "token_program"],
+            vec![],
+            "",
+        ),
+        ix(19, "expire_match_requests", &[], vec![], "Pass the match requests to expire as writable remaining accounts."),
+        ix(
+            20,
+            "fulfill_match",
+            &[
+                "match_request:w",
+                "oracle:s",
+                "oracle_registry",
+                "oracle_token:w",
+                "escrow_vault:w",
+                "escrow_authority",
+                "token_program",
+                "oracle_operator",
+            ],
+            vec![("consultant", Key)],
+            "The oracle must be registered and hold an active bond.",
+        ),
+        ix(21, "set_oracles", &["oracle_registry:w", "admin:s"], vec![("oracles", list(Key))], ""),
+        ix(22, "open_ballot_page", &["ballot_page:w", "proposal:w"], vec![], ""),
+        ix(
+            23,
+            "cast_ballot",
+            &["ballot_page:w", "voter:ws", "proposal", "stake", "vote:w", "system_program", "mint", "vote_escrow?"],
+            vec![("vote", Bool)],
+            "The mint must be GGT and the stake and optional vote-escrow lock are weighed as in vote_on_proposal, with further delegated stakes as remaining accounts. The voter is recorded at the same vote address, so each voter votes once per proposal.",
+        ),
+        ix(24, "tally_page", &["proposal:w", "ballot_page:w"], vec![("page_index", U16)], ""),
+        ix(
+            25,
+            "link_evm_address",
+            &["consultant_profile:w", "consultant:s"],
+            vec![("evm_address", arr(U8, 20)), ("signature", arr(U8, 64)), ("recovery_id", U8)],
+            "",
+        ),
+        ix(26, "init_stake_account", &["stake:w", "staker:ws", "mint", "system_program"], vec![], ""),
+        ix(
+            27,
+            "set_treasury_asset",
+            &["treasury_assets:w", "governance:s", "authority_config", "vault"],
+            vec![("max_holding", U64)],
+            "",
+        ),
+        ix(
+            28,
+            "treasury_spend",
+            &["treasury_assets", "governance:s", "authority_config", "vault:w", "recipient:w", "treasury_authority", "token_program"],
+            vec![("amount", U64)],
+            "",
+        ),
+        ix(
+            29,
+            "treasury_swap",
+            &[
+                "treasury_assets",
+                "governance:s",
+                "authority_config",
+                "vault_in:w",
+                "vault_out:w",
+                "counterparty:s",
+                "counterparty_receive:w",
+                "counterparty_pay:w",
+                "treasury_authority",
+                "token_program",
+            ],
+            vec![("amount_in", U64), ("amount_out", U64)],
+            "",
+        ),
+        ix(
+            30,
+            "create_stream",
+            &["stream:w", "payer:s", "vault:w", "payer_token?:w", "token_program?"],
+            vec![("recipients", list(Def("StreamRecipientArg"))), ("schedule", Def("StreamSchedule"))],
+            "A zero ends_at leaves the stream open-ended; otherwise the deposit must cover every recipient until then. \
+             The vault must be empty and owned by the stream's own vault authority PDA. \
+             payer_token and token_program are only needed to escrow a deposit.",
+        ),
+        ix(31, "withdraw_stream", STREAM_PAYOUT_ACCOUNTS, vec![("recipient_index", U8)], ""),
+        ix(32, "liquidate_underfunded", STREAM_PAYOUT_ACCOUNTS, vec![], STREAM_PAYOUT_DOCS),
+        ix(33, "initialize_program_config", &["program_config:w", "admin:ws", "audit_log:w", "system_program"], vec![], ""),
+        ix(34, "set_paused", CONFIG_UPDATE_ACCOUNTS, vec![("paused", Bool)], ""),
+        ix(35, "drill_pause", CONFIG_UPDATE_ACCOUNTS, vec![("slots", U64)], ""),
+        ix(36, "set_transfer_fee", CONFIG_UPDATE_ACCOUNTS, vec![("fee_bps", U16)], ""),
+        ix(
+            37,
+            "withdraw_fees",
+            &["fee_vault:w", "treasury_vault:w", "fee_authority", "token_program", "mint"],
+            vec![("amount", U64)],
+            "",
+        ),
+        ix(
+            38,
+            "create_vesting_schedule",
+            &["schedule:w", "funder:s", "funder_token:w", "vault:w", "token_program", "mint"],
+            vec![("beneficiary", Key), ("total_amount", U64), ("cliff_seconds", I64), ("duration_seconds", I64)],
+            "",
+        ),
+        ix(
+            39,
+            "claim_vested",
+            &["schedule:w", "beneficiary:s", "beneficiary_token:w", "vault:w", "vault_authority", "token_program", "mint"],
+            vec![],
+            "",
+        ),
+        ix(
+            40,
+            "revoke_vesting",
+            &[
+                "schedule:w",
+                "governance:s",
+                "authority_config",
+                "vault:w",
+                "beneficiary_token:w",
+                "treasury_vault:w",
+                "vault_authority",
+                "token_program",
+                "mint",
+            ],
+            vec![],
+            "",
+        ),
+        ix(
+            41,
+            "initialize_co_staking_pool",
+            &["co_staking_pool:w", "admin:s", "ggt_vault", "partner_vault", "ggt_mint", "rewards_vault"],
+            vec![("partner_ratio_bps", U64), ("boost_bps", U64)],
+            "The GGT vault must hold GGT, and co-stakers claim rewards only from the rewards vault given here.",
+        ),
+        ix(
+            42,
+            "co_stake",
+            &[
+                "position:w",
+                "co_staking_pool:w",
+                "staker:ws",
+                "staker_ggt:w",
+                "staker_partner:w",
+                "ggt_vault:w",
+                "partner_vault:w",
+                "token_program",
+                "system_program",
+                "ggt_mint",
+                "partner_mint",
+            ],
+            vec![("ggt_amount", U64), ("partner_amount", U64)],
+            "",
+        ),
+        ix(
+            43,
+            "co_unstake",
+            &[
+                "position:w",
+                "co_staking_pool:w",
+                "staker:s",
+                "staker_ggt:w",
+                "staker_partner:w",
+                "ggt_vault:w",
+                "partner_vault:w",
+                "vault_authority",
+                "token_program",
+                "ggt_mint",
+                "partner_mint",
+            ],
+            vec![("ggt_amount", U64)],
+            "",
+        ),
+        ix(
+            44,
+            "claim_co_stake_rewards",
+            &["position:w", "co_staking_pool:w", "staker:s", "staker_ggt:w", "rewards_vault:w", "vault_authority", "token_program", "ggt_mint"],
+            vec![],
+            "",
+        ),
+        ix(
+            45,
+            "initialize_governance_config",
+            &["governance_config:w", "admin:ws", "system_program", "pool_state"],
+            vec![("params", Def("GovernanceParams"))],
+            "",
+        ),
+        ix(
+            46,
+            "create_config_proposal",
+            CREATE_PROPOSAL_ACCOUNTS,
+            vec![("content", Def("ProposalContent")), ("params", Def("GovernanceParams"))],
+            CREATE_PROPOSAL_DOCS,
+        ),
+        ix(
+            47,
+            "convert_legacy_stake",
+            &[
+                "legacy_stake:ws",
+                "staker:ws",
+                "stake:w",
+                "mint",
+                "pool_state:w",
+                "system_program",
+                "staker_token:w",
+                "pool:w",
+                "token_program",
+                "denylist",
+            ],
+            vec![],
+            "Legacy stakes record no owner, so the legacy account's keypair signs for it. The mint must be GGT, and the \
+             staker deposits the legacy amount into the stake pool again.",
+        ),
+        ix(48, "set_compliance_authority", CONFIG_UPDATE_ACCOUNTS, vec![("compliance_authority", Key)], ""),
+        ix(
+            49,
+            "freeze_account",
+            &["token_account:w", "mint", "freeze_authority", "compliance_authority:s", "program_config", "token_program"],
+            vec![],
+            "",
+        ),
+        ix(
+            50,
+            "thaw_account",
+            &["token_account:w", "mint", "freeze_authority", "compliance_authority:s", "program_config", "token_program"],
+            vec![],
+            "",
+        ),
+        ix(51, "roll_reward_epoch", &["pool_state:w"], vec![], ""),
+        ix(
+            52,
+            "initialize_staking_config",
+            &["staking_config:w", "admin:ws", "system_program"],
+            vec![("economics", Def("StakingEconomics"))],
+            "",
+        ),
+        ix(
+            53,
+            "set_staking_economics",
+            &["staking_config:w", "governance:s", "authority_config"],
+            vec![("economics", Def("StakingEconomics"))],
+            "",
+        ),
+        ix(
+            54,
+            "register_destination",
+            &["address_book:w", "owner:ws", "system_program"],
+            vec![("chain", Str), ("destination", Bytes), ("evm_proof", opt(Def("EvmProof")))],
+            "",
+        ),
+        ix(55, "remove_destination", &["address_book:w", "owner:s"], vec![("chain", Str), ("destination", Bytes)], ""),
+        ix(56, "set_strict_mode", &["address_book:w", "owner:ws", "system_program"], vec![("strict", Bool)], ""),
+        ix(
+            57,
+            "lock_tokens_to_destination",
+            LOCK_ACCOUNTS,
+            vec![("amount", U64), ("target_chain_id", U16), ("destination", Bytes)],
+            "The target chain's relayer fee is taken out of amount. Wormhole chains also need the core bridge accounts.",
+        ),
+        ix(58, "extend_lock", &["stake:w", "staker:s", "mint", "stake_summary:w?"], vec![("lock_period_in_days", U64)], ""),
+        ix(59, "add_to_stake", STAKE_ACCOUNTS, vec![("amount", U64)], ""),
+        ix(
+            60,
+            "set_payout_split",
+            &["payout_split:w", "consultant_profile", "consultant:ws", "system_program"],
+            vec![("members", list(Def("SplitMember")))],
+            "Current split members co-sign by passing their signer accounts as remaining accounts.",
+        ),
+        ix(
+            61,
+            "distribute_payout",
+            &["payout_split", "consultant_profile", "payer_token:w", "payer:s", "token_program", "mint"],
+            vec![("amount", U64)],
+            "The member token accounts follow as writable remaining accounts.",
+        ),
+        ix(
+            62,
+            "create_instruction_proposal",
+            CREATE_PROPOSAL_ACCOUNTS,
+            vec![("content", Def("ProposalContent")), ("instructions", list(Def("ProposalInstruction")))],
+            CREATE_PROPOSAL_DOCS,
+        ),
+        ix(
+            63,
+            "set_credit_limit",
+            &["invoice:w", "governance:ws", "authority_config", "system_program"],
+            vec![("client", Key), ("credit_limit", U64)],
+            "",
+        ),
+        ix(
+            64,
+            "transfer_tokens_on_invoice",
+            &["source:w", "destination:w", "owner:s", "token_program", "mint", "program_config", "invoice:w", "denylist"],
+            vec![("amount", U64)],
+            "",
+        ),
+        ix(
+            65,
+            "pay_invoice",
+            &["invoice:w", "payer_token:w", "payer:s", "fee_vault:w", "token_program", "mint"],
+            vec![("amount", U64)],
+            "",
+        ),
+        ix(
+            66,
+            "settle_invoice",
+            &["invoice:w", "client_token:w", "fee_vault:w", "fee_authority", "token_program", "mint"],
+            vec![],
+            "",
+        ),
+        ix(
+            67,
+            "create_treasury_proposal",
+            CREATE_PROPOSAL_ACCOUNTS,
+            vec![("content", Def("ProposalContent")), ("spend", Def("TreasurySpend"))],
+            CREATE_PROPOSAL_DOCS,
+        ),
+        ix(68, "get_treasury_balance", &["treasury_assets", "vault"], vec![], "Returns the vault balance as return data."),
+        ix(69, "set_bridge_mode", &["bridge_config:w", "bridge_admin:s", "mint:w"], vec![("mode", Def("BridgeMode"))], ""),
+        ix(
+            70,
+            "create_lookup_table",
+            &["lookup_table:w", "lookup_table_authority", "admin:ws", "system_program", "lookup_table_program"],
+            vec![("recent_slot", U64)],
+            "",
+        ),
+        ix(
+            71,
+            "extend_lookup_table",
+            &[
+                "lookup_table:w",
+                "lookup_table_authority",
+                "governance:s",
+                "authority_config",
+                "payer:ws",
+                "system_program",
+                "lookup_table_program",
+            ],
+            vec![("addresses", list(Key))],
+            "",
+        ),
+        ix(
+            72,
+            "start_migration",
+            &[
+                "migration:w",
+                "governance:s",
+                "authority_config",
+                "payer:ws",
+                "legacy_mint:w",
+                "legacy_mint_authority:s",
+                "new_mint:w",
+                "migration_authority",
+                "legacy_token_program",
+                "token_2022_program",
+                "system_program",
+            ],
+            vec![("cutoff_at", I64), ("params", Def("Token2022MintParams"))],
+            "",
+        ),
+        ix(
+            73,
+            "migrate_tokens",
+            &[
+                "migration:w",
+                "holder:s",
+                "legacy_token:w",
+                "legacy_mint:w",
+                "new_token:w",
+                "new_mint:w",
+                "migration_authority",
+                "legacy_token_program",
+                "token_2022_program",
+            ],
+            vec![("amount", U64)],
+            "",
+        ),
+        ix(
+            74,
+            "request_unstake",
+            &["stake:w", "staker:s", "mint", "pool_state:w", "staking_config", "stake_summary:w?"],
+            vec![("amount", U64)],
+            "",
+        ),
+        ix(
+            75,
+            "withdraw_unstaked",
+            &["stake:w", "pool:w", "pool_authority", "staker_token:w", "staker:s", "token_program", "mint"],
+            vec![],
+            "",
+        ),
+        ix(
+            76,
+            "sync_stake_summary",
+            &["stake_summary:w", "owner:ws", "system_program"],
+            vec![],
+            "Each of the owner's stake positions follows as a (stake, mint) pair of remaining accounts.",
+        ),
+        ix(
+            77,
+            "register_chain",
+            &["chain_registry:w", "authority:s", "authority_config", "payer:ws", "system_program"],
+            vec![("chain", Def("ChainInfo"))],
+            "",
+        ),
+        ix(
+            78,
+            "set_market_maker",
+            &["market_maker:w", "admin:ws", "maker", "collateral_vault", "mint", "system_program"],
+            vec![("collateral_ratio_bps", U16), ("approved", Bool)],
+            "",
+        ),
+        ix(
+            79,
+            "withdraw_collateral",
+            &["market_maker:w", "maker:s", "collateral_vault:w", "destination:w", "vault_authority", "token_program", "mint"],
+            vec![("amount", U64)],
+            "",
+        ),
+        ix(
+            80,
+            "fast_mint",
+            &["market_maker:w", "maker:ws", "collateral_vault", "bridge_config:w", "chain_registry", "record:w", "system_program"],
+            vec![("amount", U64), ("target_chain_id", U16), ("destination", Bytes)],
+            "",
+        ),
+        ix(
+            81,
+            "reconcile_fast_mint",
+            &[
+                "market_maker:w",
+                "record:w",
+                "collateral_vault:w",
+                "bridge_vault:w",
+                "vault_authority",
+                "bridge_config",
+                "instructions_sysvar",
+                "token_program",
+                "mint",
+            ],
+            vec![],
+            "",
+        ),
+        ix(
+            82,
+            "register_consultant",
+            &["consultant_profile:w", "consultant:ws", "system_program"],
+            vec![("skills_hash", arr(U8, 32)), ("hourly_rate", U64)],
+            "",
+        ),
+        ix(
+            83,
+            "update_consultant",
+            &["consultant_profile:w", "consultant:s"],
+            vec![("skills_hash", arr(U8, 32)), ("hourly_rate", U64), ("available", Bool)],
+            "",
+        ),
+        ix(84, "deactivate_consultant", &["consultant_profile:w", "consultant:s"], vec![], ""),
+        ix(
+            85,
+            "open_engagement",
+            &[
+                "engagement:w",
+                "client:ws",
+                "consultant",
+                "consultant_profile",
+                "client_token:w",
+                "vault:w",
+                "mint",
+                "token_program",
+                "system_program",
+            ],
+            vec![("engagement_id", U64), ("milestones", list(U64)), ("duration_seconds", I64)],
+            "Escrows the sum of the milestones, up to eight, which the client approves in order.",
+        ),
+        ix(
+            86,
+            "complete_engagement",
+            &[
+                "engagement:w",
+                "client:ws",
+                "consultant:s",
+                "vault:w",
+                "consultant_token:w",
+                "fee_vault:w",
+                "mint",
+                "token_program",
+                "program_config",
+                "client_reputation:w",
+                "consultant_reputation:w",
+                "system_program",
+            ],
+            vec![],
+            "Pays out the escrow left after approved milestones and credits the engagement to both reputations; \
+             the client pays to create either.",
+        ),
+        ix(
+            87,
+            "cancel_engagement",
+            &["engagement:w", "client", "consultant", "vault:w", "client_token:w", "mint", "token_program"],
+            vec![],
+            "Either the client or the consultant must sign.",
+        ),
+        ix(
+            88,
+            "update_token_metadata",
+            &["metadata:w", "mint", "update_authority:s", "token_metadata_program"],
+            vec![("name", Str), ("symbol", Str), ("uri", Str)],
+            "",
+        ),
+        ix(
+            89,
+            "set_outflow_caps",
+            &["bridge_config:w", "governance:s", "authority_config"],
+            vec![("daily_outflow_cap", U64), ("chain_outflow_caps", list(Def("ChainOutflowCap")))],
+            "",
+        ),
+        ix(90, "pause_bridge", &["bridge_config:w", "authority:s", "authority_config"], vec![], ""),
+        ix(91, "unpause_bridge", &["bridge_config:w", "authority:s", "authority_config"], vec![], ""),
+        ix(SET_PAUSE_FLAGS_TAG, "set_pause_flags", CONFIG_UPDATE_ACCOUNTS, vec![("pause_flags", U8)], ""),
+        ix(
+            93,
+            "cancel_proposal",
+            &["proposal:w", "authority:s", "authority_config"],
+            vec![],
+            "A proposal holding a deposit takes the deposit release accounts as remaining accounts.",
+        ),
+        ix(
+            94,
+            "initialize_multisig",
+            &["multisig:w", "admin:ws", "authority_config", "system_program"],
+            vec![("owners", list(Key)), ("threshold", U8)],
+            "",
+        ),
+        ix(
+            95,
+            "propose_admin_action",
+            &["multisig:w", "admin_action:w", "owner:ws", "system_program"],
+            vec![("instruction", Def("ProposalInstruction"))],
+            "",
+        ),
+        ix(96, "approve_admin_action", &["multisig", "admin_action:w", "owner:s"], vec![], ""),
+        ix(
+            97,
+            "execute_admin_action",
+            &["multisig:w", "admin_action:w", "multisig_authority"],
+            vec![],
+            "Every account the action references follows as remaining accounts.",
+        ),
+        ix(
+            98,
+            "nominate_authority",
+            AUTHORITY_TRANSFER_ACCOUNTS,
+            vec![("role", Def("AuthorityRole")), ("nominee", Key)],
+            AUTHORITY_TRANSFER_DOCS,
+        ),
+        ix(99, "accept_authority", AUTHORITY_TRANSFER_ACCOUNTS, vec![("role", Def("AuthorityRole"))], AUTHORITY_TRANSFER_DOCS),
+        ix(
+            100,
+            "mint_stake_receipt",
+            &[
+                "stake:w",
+                "staker:ws",
+                "mint",
+                "receipt_mint:w",
+                "receipt_account:w",
+                "metadata:w",
+                "receipt_authority",
+                "token_program",
+                "system_program",
+                "rent",
+                "token_metadata_program",
+            ],
+            vec![("transferable", Bool)],
+            "",
+        ),
+        ix(
+            101,
+            "burn_stake_receipt",
+            &["stake:w", "receipt_mint:w", "holder_token:w", "holder:s", "receipt_authority", "token_program"],
+            vec![],
+            "",
+        ),
+        ix(
+            102,
+            "refresh_stake_receipt",
+            &["stake", "receipt_mint", "metadata:w", "receipt_authority", "token_metadata_program"],
+            vec![],
+            "",
+        ),
+        ix(
+            103,
+            "create_distribution",
+            &["distribution:w", "bitmap:w", "creator:ws", "creator_token:w", "vault:w", "mint", "token_program", "system_program"],
+            vec![("distribution_id", U64), ("merkle_root", arr(U8, 32)), ("total_amount", U64), ("num_recipients", U32)],
+            "",
+        ),
+        ix(
+            104,
+            "claim_airdrop",
+            &["distribution:w", "bitmap:w", "recipient:s", "recipient_token:w", "vault:w", "mint", "token_program"],
+            vec![("index", U64), ("amount", U64), ("proof", list(arr(U8, 32)))],
+            "",
+        ),
+        ix(
+            105,
+            "batch_transfer",
+            &["source:w", "owner:s", "token_program", "mint", "program_config", "fee_vault:w", "denylist"],
+            vec![("amounts", list(U64))],
+            "One writable recipient token account per amount follows as remaining accounts.",
+        ),
+        ix(
+            106,
+            "set_evm_guardian_set",
+            &["bridge_config:w", "bridge_admin:s"],
+            vec![("evm_guardian_threshold", U8), ("evm_guardians", list(arr(U8, 20)))],
+            "",
+        ),
+        ix(
+            107,
+            "attest_foreign_asset",
+            &[
+                "wrapped_asset:w",
+                "mint:w",
+                "authority:s",
+                "authority_config",
+                "payer:ws",
+                "chain_registry",
+                "vault_authority",
+                "token_program",
+                "system_program",
+            ],
+            vec![("source_chain_id", U16), ("foreign_token", arr(U8, 32)), ("decimals", U8)],
+            "",
+        ),
+        ix(
+            108,
+            "complete_inbound_transfer",
+            &[
+                "wrapped_asset",
+                "mint:w",
+                "recipient_token:w",
+                "vault_authority",
+                "bridge_config",
+                "chain_registry",
+                "instructions_sysvar",
+                "token_program",
+                "receipt:w",
+                "payer:ws",
+                "system_program",
+            ],
+            vec![("amount", U64), ("nonce", U64), ("source_chain_id", U16)],
+            "For Wormhole chains, instructions_sysvar is the posted VAA.",
+        ),
+        ix(
+            109,
+            "tag_legacy_account",
+            &["account:w", "admin:s", "program_config:w", "payer:ws", "system_program"],
+            vec![("discriminator", arr(U8, DISCRIMINATOR_LEN))],
+            "Retags an account written before discriminators; an untagged program config is retagged first.",
+        ),
+        ix(110, "create_burn_proposal", CREATE_PROPOSAL_ACCOUNTS, vec![("content", Def("ProposalContent")), ("burn", Def("TokenBurn"))], CREATE_PROPOSAL_DOCS),
+        ix(
+            111,
+            "fund_rewards",
+            &["pool_state:w", "admin:s", "admin_token:w", "rewards_vault:w", "mint", "token_program"],
+            vec![("amount", U64), ("reward_rate_per_second", U64), ("rewards_end_at", I64)],
+            "",
+        ),
+        ix(
+            112,
+            "slash",
+            &[
+                "stake:w",
+                "pool_state:w",
+                "staker",
+                "mint",
+                "pool:w",
+                "pool_authority",
+                "rewards_vault:w",
+                "token_program",
+                "governance:s",
+                "authority_config",
+                "stake_summary:w?",
+            ],
+            vec![("bps", U16), ("reason_hash", arr(U8, 32))],
+            "The slashed tokens move from the stake pool into the rewards vault.",
+        ),
+        ix(
+            113,
+            "create_parameter_change_proposal",
+            CREATE_PROPOSAL_ACCOUNTS,
+            vec![("content", Def("ProposalContent")), ("change", Def("ParameterChange"))],
+            CREATE_PROPOSAL_DOCS,
+        ),
+        ix(
+            114,
+            "create_upgrade_proposal",
+            CREATE_PROPOSAL_ACCOUNTS,
+            vec![("content", Def("ProposalContent")), ("upgrade", Def("CodeUpgrade"))],
+            CREATE_PROPOSAL_DOCS,
+        ),
+        ix(
+            115,
+            "update_vote",
+            &["vote:w", "voter:s", "proposal:w"],
+            vec![("proposal_id", U64), ("choice", Def("VoteChoice"))],
+            "Moves the vote's recorded weight to the new choice; only while voting is open.",
+        ),
+        ix(
+            116,
+            "extend_proposal_body",
+            &["proposal:w", "proposer:ws", "system_program"],
+            vec![("proposal_id", U64), ("chunk", Bytes)],
+            "Appends a chunk of the proposal's full text behind its account, which grows at the proposer's expense.",
+        ),
+        ix(
+            117,
+            "transfer_with_ata_create",
+            &[
+                "source:w",
+                "destination:w",
+                "owner:ws",
+                "token_program",
+                "mint",
+                "program_config",
+                "fee_vault:w",
+                "denylist",
+                "recipient",
+                "system_program",
+                "associated_token_program",
+            ],
+            vec![("amount", U64)],
+            "Creates the recipient's associated token account idempotently, paid by the owner, then transfers into it.",
+        ),
+        ix(
+            118,
+            "approve_delegate",
+            &["source:w", "delegate", "owner:s", "token_program", "mint"],
+            vec![("amount", U64)],
+            "Replaces any earlier allowance; the delegate then signs transfer_tokens as its trailing account.",
+        ),
+        ix(119, "revoke_delegate", &["source:w", "owner:s", "token_program", "mint"], vec![], ""),
+        ix(120, "get_stake_info", &["stake", "pool_state"], vec![], "Returns a Borsh StakeInfo as return data."),
+        ix(121, "get_proposal_state", &["proposal"], vec![("proposal_id", U64)], "Returns a Borsh ProposalState as return data."),
+        ix(122, "get_bridge_config", &["bridge_config"], vec![], "Returns the Borsh BridgeConfig as return data."),
+        ix(
+            123,
+            "set_oracle_bond_config",
+            &["oracle_bond_config:w", "admin:ws", "bond_vault", "mint", "system_program"],
+            vec![("min_bond", U64), ("withdrawal_delay_seconds", I64)],
+            "The first call creates the config and fixes the bond mint and vault.",
+        ),
+        ix(
+            124,
+            "register_oracle",
+            &[
+                "oracle_operator:w",
+                "operator:ws",
+                "operator_token:w",
+                "bond_vault:w",
+                "oracle_bond_config",
+                "token_program",
+                "mint",
+                "system_program",
+            ],
+            vec![("amount", U64)],
+            "Adds to the operator's bond; it becomes active once the bond covers the minimum.",
+        ),
+        ix(125, "deregister_oracle", &["oracle_operator:w", "operator:s", "oracle_bond_config"], vec![], ""),
+        ix(
+            126,
+            "withdraw_oracle_bond",
+            &[
+                "oracle_operator:w",
+                "operator:ws",
+                "destination:w",
+                "bond_vault:w",
+                "bond_authority",
+                "oracle_bond_config",
+                "token_program",
+                "mint",
+            ],
+            vec![],
+            "Only after the withdrawal delay; closes the operator account.",
+        ),
+        ix(
+            127,
+            "slash_oracle",
+            &[
+                "oracle_operator:w",
+                "bond_vault:w",
+                "treasury_vault:w",
+                "bond_authority",
+                "oracle_bond_config",
+                "token_program",
+                "mint",
+                "governance:s",
+                "authority_config",
+            ],
+            vec![("bps", U16), ("reason_hash", arr(U8, 32))],
+            "",
+        ),
+        ix(
+            128,
+            "set_arbitration_council",
+            &["arbitration_config:w", "governance:s", "authority_config", "payer:ws", "system_program"],
+            vec![("council", list(Key)), ("dispute_bond_bps", U16), ("loser_slash_bps", U16)],
+            "",
+        ),
+        ix(
+            129,
+            "open_dispute",
+            &[
+                "engagement:w",
+                "dispute:w",
+                "opener:ws",
+                "client",
+                "consultant",
+                "vault:w",
+                "opener_token:w",
+                "arbitration_config",
+                "mint",
+                "token_program",
+                "system_program",
+            ],
+            vec![("reason_hash", arr(U8, 32))],
+            "The opener is the client or the consultant and posts the dispute bond into the vault.",
+        ),
+        ix(
+            130,
+            "resolve_dispute",
+            &[
+                "engagement:w",
+                "dispute:w",
+                "arbiter:ws",
+                "client",
+                "consultant",
+                "vault:w",
+                "client_token:w",
+                "consultant_token:w",
+                "fee_vault:w",
+                "arbitration_config",
+                "mint",
+                "token_program",
+                "client_reputation:w",
+                "consultant_reputation:w",
+                "system_program",
+            ],
+            vec![("consultant_bps", U16)],
+            "Pays the consultant consultant_bps of the escrow and the client the rest, and records the lost dispute.",
+        ),
+        ix(131, "get_reputation", &["reputation"], vec![], "Returns a Borsh Reputation as return data."),
+        ix(
+            132,
+            "claim_relayer_fee",
+            &["receipt:w", "relayer:s", "relayer_fee_vault:w", "relayer_token:w", "relayer_fee_authority", "mint", "token_program"],
+            vec![],
+            "The relayer must be the payer recorded in the release receipt.",
+        ),
+        ix(
+            133,
+            "set_transfer_timeout",
+            &["bridge_config:w", "governance:s", "authority_config"],
+            vec![("transfer_timeout_seconds", I64)],
+            "",
+        ),
+        ix(
+            134,
+            "complete_bridge_transfer",
+            &["bridge_transfer:w", "bridge_config", "instructions_sysvar", "chain_registry"],
+            vec![],
+            "Guardians of the target chain sign the completion message in precompile instructions earlier in the transaction. For Wormhole chains, instructions_sysvar is the posted VAA.",
+        ),
+        ix(
+            135,
+            "cancel_bridge_transfer",
+            &[
+                "bridge_transfer:w",
+                "sender:s",
+                "sender_token:w",
+                "bridge_vault:w",
+                "vault_authority",
+                "relayer_fee_vault:w",
+                "relayer_fee_authority",
+                "bridge_config",
+                "mint:w",
+                "token_program",
+            ],
+            vec![],
+            "Refunds the amount and the fee of a pending transfer once the transfer timeout has passed.",
+        ),
+        ix(
+            136,
+            "set_voting_delegate",
+            &["stake:w", "staker:s", "mint"],
+            vec![("delegate", Key)],
+            "The default key returns the stake's voting weight to the staker.",
+        ),
+        ix(
+            137,
+            "lock_for_voting",
+            &["vote_escrow:w", "owner:ws", "owner_token:w", "vault:w", "mint", "token_program", "system_program"],
+            vec![("amount", U64), ("lock_period_in_days", U64)],
+            "Locks for one to four years. Locking again adds to the lock and can only move its unlock time later.",
+        ),
+        ix(
+            138,
+            "withdraw_expired_lock",
+            &["vote_escrow:w", "owner:s", "owner_token:w", "vault:w", "vault_authority", "mint", "token_program"],
+            vec![],
+            "",
+        ),
+        ix(
+            139,
+            "cancel_stream",
+            &["stream:w", "vault:w", "payer:s", "payer_token:w", "vault_authority", "token_program"],
+            vec![],
+            "Pays each recipient what it has accrued and refunds the rest to the payer. \
+             The recipient token accounts follow as remaining accounts.",
+        ),
+        ix(
+            140,
+            "approve_milestone",
+            &[
+                "engagement:w",
+                "client:s",
+                "consultant",
+                "vault:w",
+                "consultant_token:w",
+                "fee_vault:w",
+                "mint",
+                "token_program",
+                "program_config",
+            ],
+            vec![("milestone_index", U8)],
+            "Releases the next milestone to the consultant, less the protocol fee.",
+        ),
+        ix(
+            141,
+            "burn_from_treasury",
+            &["treasury_assets", "governance:s", "program_config:w", "vault:w", "mint:w", "treasury_authority", "token_program"],
+            vec![("amount", U64)],
+            "Governance only; adds the amount to the program config's total_burned.",
+        ),
+        ix(
+            142,
+            "transfer_with_memo",
+            &[
+                "source:w",
+                "destination:w",
+                "owner:ws",
+                "token_program",
+                "mint",
+                "program_config",
+                "fee_vault:w",
+                "denylist",
+                "payment_receipt?:w",
+                "system_program?",
+            ],
+            vec![("reference", arr(U8, 32)), ("amount", U64)],
+            "Emits PaymentReferenced; passing the receipt PDA for the destination, owner and reference also records a PaymentReceipt, once per reference.",
+        ),
+        ix(
+            143,
+            "add_to_denylist",
+            DENYLIST_ACCOUNTS,
+            vec![("address", Key)],
+            "Transfers, stakes and bridge locks touching a denylisted wallet or token account fail. The first add creates the denylist PDA.",
+        ),
+        ix(144, "remove_from_denylist", DENYLIST_ACCOUNTS, vec![("address", Key)], ""),
+        ix(
+            145,
+            "migrate_account",
+            &["account:w", "admin:s", "program_config:w", "payer:ws", "system_program"],
+            vec![("version", U8)],
+            "Grows an account at layout `version` to its current layout; an unversioned program config is migrated first.",
+        ),
+        ix(
+            146,
+            "set_upgrade_authority",
+            &["program_data:w", "current_authority:s", "governance_authority", "bpf_loader_upgradeable"],
+            vec![],
+            "Hands the program's upgrade authority to the governance authority PDA, which then upgrades only by executing a CodeUpgrade proposal.",
+        ),
+        ix(
+            147,
+            "set_multisig_owners",
+            &["multisig:w", "multisig_authority:s"],
+            vec![("owners", list(Key)), ("threshold", U8)],
+            "Only runs as an executed admin action. Admin actions proposed before the change can no longer be approved or executed.",
+        ),
+    ]
+}
+
+fn account_json(spec: &str) -> Value {
+    let (name, flags) = spec.split_once(':').unwrap_or((spec, ""));
+    let (name, flags) = match name.strip_suffix('?') {
+        Some(name) => (name, format!("{flags}?")),
+        None => (name, flags.to_string()),
+    };
+    let mut account = Map::new();
+    account.insert("name".into(), json!(name));
+    for (flag, key) in [('w', "writable"), ('s', "signer"), ('?', "optional")] {
+        if flags.contains(flag) {
+            account.insert(key.into(), json!(true));
+        }
+    }
+    Value::Object(account)
+}
+
+fn instruction_json(ix: &Ix) -> Value {
+    let mut accounts: Vec<Value> = ix.accounts.iter().map(|spec| account_json(spec)).collect();
+    if !UNGATED_TAGS.contains(&ix.tag) {
+        accounts.push(account_json("pause_config"));
+    }
+    let docs: Vec<&str> = if ix.docs.is_empty() { Vec::new() } else { vec![ix.docs] };
+    json!({
+        "name": ix.name,
+        "docs": docs,
+        "discriminator": [ix.tag, INSTRUCTION_VERSION],
+        "accounts": accounts,
+        "args": fields_json(&ix.args),
+    })
+}
+
+/// The program's IDL as JSON.
+/// Every instruction behind the pause gate, with whether it takes a token program.
+#[cfg(test)]
+pub(crate) fn gated_instructions() -> Vec<(u8, bool)> {
+    instructions()
+        .into_iter()
+        .filter(|ix| !UNGATED_TAGS.contains(&ix.tag))
+        .map(|ix| (ix.tag, ix.accounts.iter().any(|account| account.starts_with("token_program"))))
+        .collect()
+}
+
+pub fn idl() -> Value {
+    let layouts = account_layouts();
+    let events = event_types();
+    let mut types: Vec<Value> = arg_types().iter().map(TypeDef::to_json).collect();
+    types.extend(layouts.iter().map(|(def, ..)| def.to_json()));
+    types.extend(events.iter().map(|(_, def)| def.to_json()));
+
+    json!({
+        "address": "",
+        "metadata": {
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "spec": "0.1.0",
+            "description": "Gadder Gold Token program; see the idl module docs for where this differs from Anchor.",
+        },
+        "instructions": instructions().iter().map(instruction_json).collect::<Vec<_>>(),
+        "accounts": layouts.iter().map(|(def, _, discriminator)| json!({ "name": def.name, "discriminator": discriminator })).collect::<Vec<_>>(),
+        "events": events.iter().map(|(discriminator, def)| json!({ "name": def.name, "discriminator": discriminator })).collect::<Vec<_>>(),
+        "errors": GgtError::ALL
+            .iter()
+            .map(|error| json!({ "code": *error as u32, "name": format!("{error:?}"), "msg": error.message() }))
+            .collect::<Vec<_>>(),
+        "types": types,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn idl_matches_the_program() {
+        let mut types = arg_types();
+        let layouts = account_layouts();
+        for (def, len, discriminator) in &layouts {
+            if let Some(size) = def.size(&types) {
+                assert_eq!(discriminator.len() + size, *len, "{} layout does not match its Pack::LEN", def.name);
+            }
+        }
+        types.extend(layouts.into_iter().map(|(def, ..)| def));
+        types.extend(event_types().into_iter().map(|(_, def)| def));
+        let mut names = HashSet::new();
+        assert!(types.iter().all(|def| names.insert(def.name)), "type names must be unique");
+
+        let mut tags = HashSet::new();
+        assert!(instructions().iter().all(|ix| tags.insert(ix.tag)), "instruction tags must be unique");
+        assert!(UNGATED_TAGS.iter().all(|tag| tags.contains(tag)));
+
+        for (code, error) in GgtError::ALL.iter().enumerate() {
+            assert_eq!(*error as u32, code as u32);
+        }
+
+        let idl = idl();
+        let transfer = &idl["instructions"].as_array().unwrap()[1];
+        assert_eq!(transfer["discriminator"], json!([1, INSTRUCTION_VERSION]));
+        assert_eq!(transfer["accounts"].as_array().unwrap().last().unwrap()["name"], "pause_config");
+        assert_eq!(transfer["accounts"][8], json!({ "name": "delegate", "signer": true, "optional": true }));
+    }
+}
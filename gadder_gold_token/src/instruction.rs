@@ -0,0 +1,536 @@
+//! Instruction builders for off-chain Rust clients. Each returns an
+//! `Instruction` whose data follows the `[tag][version][payload]` layout and
+//! whose account metas are in the order the handler reads them. Accounts at a
+//! fixed PDA are derived here; gated instructions also get the program config
+//! PDA appended as their last account.
+//!
+//! Depend on this crate with the `no-entrypoint` feature to use the builders
+//! without linking the program entrypoint.
+
+use solana_program::{
+    bpf_loader_upgradeable,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+use mpl_token_metadata::accounts::Metadata;
+use crate::{
+    address_book::address_book_address,
+    bridge_transfer::bridge_transfer_address,
+    config::config_address,
+    denylist::denylist_address,
+    cross_chain_bridge_contract::CrossChainBridge,
+    governance_contract::{
+        governance_authority_address, governance_config_address, proposal_address, proposal_counter_address, proposal_deposit_authority,
+        vote_address, ProposalContent, VoteChoice,
+    },
+    staking_contract::{rewards_vault_authority, stake_address},
+    token_program::{associated_token_address, ASSOCIATED_TOKEN_PROGRAM_ID},
+    wormhole, TokenContract, TokenParams, INSTRUCTION_VERSION,
+};
+
+fn instruction_data(tag: u8, payload: &[u8]) -> Vec<u8> {
+    [&[tag, INSTRUCTION_VERSION][..], payload].concat()
+}
+
+/// Appends the program config PDA every gated instruction ends with.
+fn gated(program_id: &Pubkey, tag: u8, payload: &[u8], mut accounts: Vec<AccountMeta>) -> Instruction {
+    accounts.push(AccountMeta::new_readonly(config_address(program_id).0, false));
+    Instruction { program_id: *program_id, accounts, data: instruction_data(tag, payload) }
+}
+
+/// Creates the GGT mint at its PDA as described by `params`, with
+/// `authority` as mint authority, payer and metadata update authority.
+pub fn initialize_token(program_id: &Pubkey, authority: &Pubkey, token_program: &Pubkey, params: &TokenParams) -> Instruction {
+    let payload = borsh::to_vec(params).unwrap();
+    let mint = TokenContract::mint_address(program_id).0;
+    let accounts = vec![
+        AccountMeta::new(mint, false),
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new(Metadata::find_pda(&mint).0, false),
+        AccountMeta::new(mint, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+    ];
+    gated(program_id, 0, &payload, accounts)
+}
+
+/// Replaces the GGT metadata's name, symbol and URI. The update authority
+/// itself moves with `nominate_authority` and `accept_authority`.
+pub fn update_token_metadata(program_id: &Pubkey, mint: &Pubkey, update_authority: &Pubkey, name: &str, symbol: &str, uri: &str) -> Instruction {
+    let payload = borsh::to_vec(&(name, symbol, uri)).unwrap();
+    let accounts = vec![
+        AccountMeta::new(Metadata::find_pda(mint).0, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*update_authority, true),
+        AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+    ];
+    gated(program_id, 88, &payload, accounts)
+}
+
+/// Transfers `amount` from `source` to `destination`, with the protocol fee
+/// going to `fee_vault`.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer(
+    program_id: &Pubkey,
+    source: &Pubkey,
+    destination: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    fee_vault: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*source, false),
+        AccountMeta::new(*destination, false),
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(config_address(program_id).0, false),
+        AccountMeta::new(*fee_vault, false),
+        AccountMeta::new_readonly(denylist_address(program_id).0, false),
+    ];
+    gated(program_id, 1, &amount.to_le_bytes(), accounts)
+}
+
+/// Like [`transfer`], but signed by `delegate`, which spends the allowance
+/// `owner` granted it with [`approve_delegate`].
+#[allow(clippy::too_many_arguments)]
+pub fn delegated_transfer(
+    program_id: &Pubkey,
+    source: &Pubkey,
+    destination: &Pubkey,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+    mint: &Pubkey,
+    fee_vault: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*source, false),
+        AccountMeta::new(*destination, false),
+        AccountMeta::new_readonly(*owner, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(config_address(program_id).0, false),
+        AccountMeta::new(*fee_vault, false),
+        AccountMeta::new_readonly(denylist_address(program_id).0, false),
+        AccountMeta::new_readonly(*delegate, true),
+    ];
+    gated(program_id, 1, &amount.to_le_bytes(), accounts)
+}
+
+/// Lets `delegate` move up to `amount` from `owner`'s `source` account.
+pub fn approve_delegate(
+    program_id: &Pubkey,
+    source: &Pubkey,
+    delegate: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*source, false),
+        AccountMeta::new_readonly(*delegate, false),
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(*mint, false),
+    ];
+    gated(program_id, 118, &amount.to_le_bytes(), accounts)
+}
+
+/// Clears the allowance on `owner`'s `source` account.
+pub fn revoke_delegate(program_id: &Pubkey, source: &Pubkey, owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*source, false),
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(*mint, false),
+    ];
+    gated(program_id, 119, &[], accounts)
+}
+
+/// Like [`transfer`], but pays into `recipient`'s associated token account,
+/// creating it first at `owner`'s expense if it does not exist yet.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_with_ata_create(
+    program_id: &Pubkey,
+    source: &Pubkey,
+    owner: &Pubkey,
+    recipient: &Pubkey,
+    mint: &Pubkey,
+    fee_vault: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*source, false),
+        AccountMeta::new(associated_token_address(recipient, mint, token_program).0, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(config_address(program_id).0, false),
+        AccountMeta::new(*fee_vault, false),
+        AccountMeta::new_readonly(denylist_address(program_id).0, false),
+        AccountMeta::new_readonly(*recipient, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+    ];
+    gated(program_id, 117, &amount.to_le_bytes(), accounts)
+}
+
+/// Pays `amounts[i]` from `source` to `recipients[i]`, with one protocol fee
+/// on the total going to `fee_vault`.
+#[allow(clippy::too_many_arguments)]
+pub fn batch_transfer(
+    program_id: &Pubkey,
+    source: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    fee_vault: &Pubkey,
+    token_program: &Pubkey,
+    recipients: &[Pubkey],
+    amounts: &[u64],
+) -> Instruction {
+    let payload = borsh::to_vec(amounts).unwrap();
+    let mut accounts = vec![
+        AccountMeta::new(*source, false),
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(config_address(program_id).0, false),
+        AccountMeta::new(*fee_vault, false),
+        AccountMeta::new_readonly(denylist_address(program_id).0, false),
+    ];
+    accounts.extend(recipients.iter().map(|recipient| AccountMeta::new(*recipient, false)));
+    gated(program_id, 105, &payload, accounts)
+}
+
+/// Stakes `amount` from `staker_token` into `pool`, locked for `lock_period_in_days`.
+#[allow(clippy::too_many_arguments)]
+pub fn stake(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    staker_token: &Pubkey,
+    pool: &Pubkey,
+    pool_state: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+    lock_period_in_days: u64,
+) -> Instruction {
+    let payload = [amount.to_le_bytes(), lock_period_in_days.to_le_bytes()].concat();
+    let accounts = vec![
+        AccountMeta::new(stake_address(program_id, staker, mint).0, false),
+        AccountMeta::new(*staker_token, false),
+        AccountMeta::new(*pool, false),
+        AccountMeta::new_readonly(*staker, true),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new(*pool_state, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(denylist_address(program_id).0, false),
+    ];
+    gated(program_id, 3, &payload, accounts)
+}
+
+/// Pays `staker` the rewards its stake in `mint` has earned, from
+/// `rewards_vault` into `staker_token`.
+pub fn claim_rewards(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    staker_token: &Pubkey,
+    rewards_vault: &Pubkey,
+    pool_state: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(stake_address(program_id, staker, mint).0, false),
+        AccountMeta::new(*pool_state, false),
+        AccountMeta::new(*rewards_vault, false),
+        AccountMeta::new_readonly(rewards_vault_authority(program_id).0, false),
+        AccountMeta::new(*staker_token, false),
+        AccountMeta::new_readonly(*staker, true),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(*mint, false),
+    ];
+    gated(program_id, 12, &[], accounts)
+}
+
+/// Opens proposal `proposal_id`, which must be the next ID in the proposal
+/// counter, at its derived address; the proposer pays for the account. Only
+/// `content`'s hash and URI go on-chain. The proposer's stake in `mint` must
+/// meet the governance minimum, and `pool_state` is the staking pool votes are
+/// snapshotted against. When governance requires a deposit, pass the
+/// proposer's token account, the deposit vault and the token program as `deposit`.
+pub fn create_proposal(
+    program_id: &Pubkey,
+    proposal_id: u64,
+    proposer: &Pubkey,
+    mint: &Pubkey,
+    pool_state: &Pubkey,
+    content: &ProposalContent,
+    deposit: Option<(&Pubkey, &Pubkey, &Pubkey)>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(proposal_address(program_id, proposal_id).0, false),
+        AccountMeta::new(*proposer, true),
+        AccountMeta::new_readonly(governance_config_address(program_id).0, false),
+        AccountMeta::new_readonly(stake_address(program_id, proposer, mint).0, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*pool_state, false),
+        AccountMeta::new(proposal_counter_address(program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some((proposer_token, deposit_vault, token_program)) = deposit {
+        accounts.push(AccountMeta::new(*proposer_token, false));
+        accounts.push(AccountMeta::new(*deposit_vault, false));
+        accounts.push(AccountMeta::new_readonly(*token_program, false));
+    }
+    gated(program_id, 5, &borsh::to_vec(content).unwrap(), accounts)
+}
+
+/// Votes `choice` on proposal `proposal_id` with the weight of `stake`, the
+/// voter's own stake in `mint` or one delegated to them, plus their
+/// `vote_escrow` lock if given and every stake in `delegated`. The voter pays
+/// for the vote record.
+#[allow(clippy::too_many_arguments)]
+pub fn vote_on_proposal(
+    program_id: &Pubkey,
+    proposal_id: u64,
+    voter: &Pubkey,
+    stake: &Pubkey,
+    mint: &Pubkey,
+    vote_escrow: Option<&Pubkey>,
+    delegated: &[Pubkey],
+    choice: VoteChoice,
+) -> Instruction {
+    let proposal = proposal_address(program_id, proposal_id).0;
+    let mut accounts = vec![
+        AccountMeta::new(vote_address(program_id, &proposal, voter).0, false),
+        AccountMeta::new(*voter, true),
+        AccountMeta::new(proposal, false),
+        AccountMeta::new_readonly(*stake, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*mint, false),
+    ];
+    if let Some(vote_escrow) = vote_escrow {
+        accounts.push(AccountMeta::new_readonly(*vote_escrow, false));
+    }
+    accounts.extend(delegated.iter().map(|stake| AccountMeta::new_readonly(*stake, false)));
+    gated(program_id, 7, &borsh::to_vec(&(proposal_id, choice)).unwrap(), accounts)
+}
+
+/// Permissionless crank folding ballot page `page_index` of proposal
+/// `proposal_id` into its totals once voting has ended.
+pub fn tally_ballot_page(program_id: &Pubkey, proposal_id: u64, page: &Pubkey, page_index: u16) -> Instruction {
+    let accounts = vec![AccountMeta::new(proposal_address(program_id, proposal_id).0, false), AccountMeta::new(*page, false)];
+    gated(program_id, 24, &page_index.to_le_bytes(), accounts)
+}
+
+/// Settles proposal `proposal_id` against the quorum of `pool_state`, the
+/// staking pool in the governance config. A proposal holding a deposit needs
+/// `deposit`: the deposit vault, the token account it is paid out to (the
+/// proposer's on a refund, the treasury's on a slash), the mint and the token program.
+pub fn finalize_proposal(
+    program_id: &Pubkey,
+    proposal_id: u64,
+    pool_state: &Pubkey,
+    deposit: Option<(&Pubkey, &Pubkey, &Pubkey, &Pubkey)>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(proposal_address(program_id, proposal_id).0, false),
+        AccountMeta::new_readonly(governance_config_address(program_id).0, false),
+        AccountMeta::new_readonly(*pool_state, false),
+    ];
+    if let Some((deposit_vault, destination, mint, token_program)) = deposit {
+        accounts.extend([
+            AccountMeta::new(*deposit_vault, false),
+            AccountMeta::new_readonly(proposal_deposit_authority(program_id).0, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ]);
+    }
+    gated(program_id, 13, &proposal_id.to_le_bytes(), accounts)
+}
+
+/// Executes passed proposal `proposal_id`, signed by the admin or governance
+/// `authority`. `accounts` are those its kind takes, followed by the
+/// governance authority PDA and the accounts of its instructions if it has any.
+pub fn execute_proposal(program_id: &Pubkey, proposal_id: u64, authority: &Pubkey, accounts: &[AccountMeta]) -> Instruction {
+    let mut metas = vec![
+        AccountMeta::new(proposal_address(program_id, proposal_id).0, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(config_address(program_id).0, false),
+    ];
+    metas.extend_from_slice(accounts);
+    gated(program_id, 6, &proposal_id.to_le_bytes(), metas)
+}
+
+/// Hands the program's upgrade authority from `current_authority`, which
+/// signs, to the governance authority PDA.
+pub fn set_upgrade_authority(program_id: &Pubkey, current_authority: &Pubkey) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(bpf_loader_upgradeable::get_program_data_address(program_id), false),
+        AccountMeta::new_readonly(*current_authority, true),
+        AccountMeta::new_readonly(governance_authority_address(program_id).0, false),
+        AccountMeta::new_readonly(bpf_loader_upgradeable::id(), false),
+    ];
+    gated(program_id, 146, &[], accounts)
+}
+
+/// Locks `amount` for bridging to `target_chain_id`. A `destination`, if
+/// given, must match the chain's address format; senders whose address book
+/// is in strict mode must give one of its entries. The chain's relayer fee
+/// goes to `relayer_fee_vault` and the rest is bridged. `sequence` is the
+/// bridge config's current `outbound_sequence`, which names the lock's
+/// `BridgeTransfer` record. Set `via_wormhole` for chains registered with
+/// `SignatureScheme::Wormhole` to append the core bridge accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn lock_for_bridge(
+    program_id: &Pubkey,
+    sender: &Pubkey,
+    sender_token: &Pubkey,
+    bridge_vault: &Pubkey,
+    relayer_fee_vault: &Pubkey,
+    bridge_config: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    sequence: u64,
+    amount: u64,
+    target_chain_id: u16,
+    destination: Option<Vec<u8>>,
+    via_wormhole: bool,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*sender, true),
+        AccountMeta::new(*sender_token, false),
+        AccountMeta::new(*bridge_vault, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new(*bridge_config, false),
+        AccountMeta::new(*mint, false),
+        AccountMeta::new_readonly(address_book_address(program_id, sender).0, false),
+        AccountMeta::new_readonly(CrossChainBridge::chain_registry_address(program_id).0, false),
+        AccountMeta::new(*relayer_fee_vault, false),
+        AccountMeta::new(bridge_transfer_address(program_id, sender, sequence).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(denylist_address(program_id).0, false),
+    ];
+    if via_wormhole {
+        let emitter = wormhole::emitter_address(program_id).0;
+        accounts.extend([
+            AccountMeta::new(wormhole::bridge_address(), false),
+            AccountMeta::new(wormhole::message_address(program_id, sequence).0, false),
+            AccountMeta::new_readonly(emitter, false),
+            AccountMeta::new(wormhole::sequence_address(&emitter), false),
+            AccountMeta::new(wormhole::fee_collector_address(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(wormhole::WORMHOLE_PROGRAM_ID, false),
+        ]);
+    }
+    match destination {
+        Some(destination) => {
+            let payload = borsh::to_vec(&(amount, target_chain_id, destination)).unwrap();
+            gated(program_id, 57, &payload, accounts)
+        }
+        None => {
+            let payload = [&amount.to_le_bytes()[..], &target_chain_id.to_le_bytes()].concat();
+            gated(program_id, 8, &payload, accounts)
+        }
+    }
+}
+
+/// Read-only query for a stake position; simulate it and decode the return
+/// data as a `StakeInfo`.
+pub fn get_stake_info(program_id: &Pubkey, staker: &Pubkey, mint: &Pubkey, pool_state: &Pubkey) -> Instruction {
+    let accounts =
+        vec![AccountMeta::new_readonly(stake_address(program_id, staker, mint).0, false), AccountMeta::new_readonly(*pool_state, false)];
+    Instruction { program_id: *program_id, accounts, data: instruction_data(120, &[]) }
+}
+
+/// Read-only query for a proposal; the return data decodes as a `ProposalState`.
+pub fn get_proposal_state(program_id: &Pubkey, proposal_id: u64) -> Instruction {
+    let accounts = vec![AccountMeta::new_readonly(proposal_address(program_id, proposal_id).0, false)];
+    Instruction { program_id: *program_id, accounts, data: instruction_data(121, &proposal_id.to_le_bytes()) }
+}
+
+/// Read-only query for the bridge config; the return data decodes as a `BridgeConfig`.
+pub fn get_bridge_config(program_id: &Pubkey, bridge_config: &Pubkey) -> Instruction {
+    let accounts = vec![AccountMeta::new_readonly(*bridge_config, false)];
+    Instruction { program_id: *program_id, accounts, data: instruction_data(122, &[]) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builders_match_dispatcher_layout() {
+        let program_id = Pubkey::new_unique();
+        let (sender, sender_token, vault, bridge_config, mint) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (token_program, fee_vault) = (spl_token::id(), Pubkey::new_unique());
+
+        let ix = lock_for_bridge(&program_id, &sender, &sender_token, &vault, &fee_vault, &bridge_config, &mint, &token_program, 0, 500, 2, None, false);
+        assert_eq!(ix.data[..2], [8, INSTRUCTION_VERSION]);
+        assert_eq!(ix.data[2..10], 500u64.to_le_bytes());
+        assert_eq!(ix.data[10..], 2u16.to_le_bytes());
+        assert_eq!(ix.accounts.len(), 13);
+        assert_eq!(ix.accounts[9].pubkey, bridge_transfer_address(&program_id, &sender, 0).0);
+        assert_eq!(ix.accounts[11].pubkey, denylist_address(&program_id).0);
+        assert_eq!(ix.accounts.last().unwrap().pubkey, config_address(&program_id).0);
+
+        let ix = lock_for_bridge(&program_id, &sender, &sender_token, &vault, &fee_vault, &bridge_config, &mint, &token_program, 0, 500, 2, Some(vec![0x11; 20]), true);
+        assert_eq!(ix.data[0], 57);
+        assert_eq!(ix.accounts.len(), 21);
+        assert_eq!(ix.accounts[19].pubkey, wormhole::WORMHOLE_PROGRAM_ID);
+        let (amount, chain, destination): (u64, u16, Vec<u8>) = borsh::from_slice(&ix.data[2..]).unwrap();
+        assert_eq!((amount, chain, destination), (500, 2, vec![0x11; 20]));
+
+        let ix = stake(&program_id, &sender, &sender_token, &vault, &bridge_config, &mint, &token_program, 500, 30);
+        assert_eq!(ix.accounts[0].pubkey, stake_address(&program_id, &sender, &mint).0);
+        assert!(ix.accounts[3].is_signer && !ix.accounts[3].is_writable);
+        assert_eq!(ix.data[10..], 30u64.to_le_bytes());
+
+        let ix = claim_rewards(&program_id, &sender, &sender_token, &vault, &bridge_config, &mint, &token_program);
+        assert_eq!(ix.data, vec![12, INSTRUCTION_VERSION]);
+        assert_eq!(ix.accounts[3].pubkey, rewards_vault_authority(&program_id).0);
+        assert!(ix.accounts[5].is_signer && !ix.accounts[5].is_writable);
+        assert_eq!(ix.accounts.len(), 9);
+
+        let proposal = proposal_address(&program_id, 4).0;
+        let ix = vote_on_proposal(&program_id, 4, &sender, &vault, &mint, None, &[fee_vault], VoteChoice::Yes);
+        assert_eq!(ix.accounts[0].pubkey, vote_address(&program_id, &proposal, &sender).0);
+        assert!(ix.accounts[1].is_signer && ix.accounts[1].is_writable);
+        assert_eq!(ix.accounts[6].pubkey, fee_vault);
+        assert_eq!(ix.accounts.len(), 8);
+        assert_eq!(borsh::from_slice::<(u64, VoteChoice)>(&ix.data[2..]).unwrap(), (4, VoteChoice::Yes));
+
+        let ix = finalize_proposal(&program_id, 4, &bridge_config, Some((&vault, &sender_token, &mint, &token_program)));
+        assert_eq!(ix.accounts[1].pubkey, governance_config_address(&program_id).0);
+        assert_eq!(ix.accounts[4].pubkey, proposal_deposit_authority(&program_id).0);
+        assert_eq!(ix.accounts.len(), 9);
+
+        let ix = execute_proposal(&program_id, 4, &sender, &[AccountMeta::new(vault, false)]);
+        assert_eq!((ix.data[0], &ix.data[2..]), (6, &4u64.to_le_bytes()[..]));
+        assert_eq!(ix.accounts[2].pubkey, config_address(&program_id).0);
+        assert_eq!(ix.accounts[3].pubkey, vault);
+        assert_eq!(ix.accounts.len(), 5);
+
+        let ix = set_upgrade_authority(&program_id, &sender);
+        assert_eq!(ix.data, vec![146, INSTRUCTION_VERSION]);
+        assert_eq!(ix.accounts[2].pubkey, governance_authority_address(&program_id).0);
+        assert!(ix.accounts[1].is_signer && !ix.accounts[2].is_signer);
+        assert_eq!(ix.accounts.len(), 5);
+    }
+}
@@ -0,0 +1,396 @@
+//! Deferred fee invoicing for enterprise clients. A client given a credit limit
+//! by governance accrues protocol fees on an `Invoice` instead of paying them per
+//! transfer. Each billing period's fees become due after a grace period, and are
+//! settled by the client or by a crank pulling from a delegated token account;
+//! an overdue invoice suspends deferred transfers until it is paid.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use crate::{
+    config::{self, AuthorityRole},
+    error::GgtError,
+    token_program, TokenContract, FEE_VAULT_SEED,
+};
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer, assert_token_account_mint};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const INVOICE_SEED: &[u8] = b"invoice";
+pub const BILLING_PERIOD_SECONDS: i64 = 30 * 86_400;
+/// Time a client has to pay a closed period's fees before the invoice is overdue.
+pub const PAYMENT_GRACE_SECONDS: i64 = 15 * 86_400;
+
+pub fn invoice_address(program_id: &Pubkey, client: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[INVOICE_SEED, client.as_ref()], program_id)
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct Invoice {
+    pub client: Pubkey,
+    /// Most the client may owe at once, billed and unbilled; zero disables deferral.
+    pub credit_limit: u64,
+    /// Fees accrued in the current billing period.
+    pub accrued: u64,
+    /// Fees billed for closed periods and not yet paid.
+    pub outstanding: u64,
+    pub period_ends_at: i64,
+    /// Deadline for paying `outstanding`; zero while nothing is billed.
+    pub due_at: i64,
+    pub suspended: bool,
+    pub is_initialized: bool,
+}
+
+impl Invoice {
+    /// Closes every billing period that ended by `now`, billing its fees.
+    pub fn roll_period(&mut self, now: i64) -> ProgramResult {
+        if now < self.period_ends_at {
+            return Ok(());
+        }
+        if self.accrued > 0 {
+            if self.outstanding == 0 {
+                self.due_at = self.period_ends_at.checked_add(PAYMENT_GRACE_SECONDS).ok_or(GgtError::MathOverflow)?;
+            }
+            self.outstanding = self.outstanding.checked_add(self.accrued).ok_or(GgtError::MathOverflow)?;
+            self.accrued = 0;
+        }
+        let closed_periods = (now - self.period_ends_at) / BILLING_PERIOD_SECONDS + 1;
+        self.period_ends_at = closed_periods
+            .checked_mul(BILLING_PERIOD_SECONDS)
+            .and_then(|span| self.period_ends_at.checked_add(span))
+            .ok_or(GgtError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn is_overdue(&self, now: i64) -> bool {
+        self.outstanding > 0 && now >= self.due_at
+    }
+
+    /// Defers `fee` onto the current period if the client is in good standing.
+    pub fn accrue(&mut self, fee: u64, now: i64) -> ProgramResult {
+        self.roll_period(now)?;
+        if self.suspended || self.is_overdue(now) {
+            return Err(GgtError::InvoiceOverdue.into());
+        }
+        let owed = self
+            .outstanding
+            .checked_add(self.accrued)
+            .and_then(|owed| owed.checked_add(fee))
+            .ok_or(GgtError::MathOverflow)?;
+        if owed > self.credit_limit {
+            msg!("Deferred fees {} would exceed the credit limit {}", owed, self.credit_limit);
+            return Err(GgtError::CreditLimitExceeded.into());
+        }
+        self.accrued = self.accrued.checked_add(fee).ok_or(GgtError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Applies a payment to billed fees first, then to the current period.
+    /// Lifts a suspension once nothing billed remains.
+    fn apply_payment(&mut self, amount: u64) {
+        let to_outstanding = amount.min(self.outstanding);
+        self.outstanding -= to_outstanding;
+        self.accrued -= (amount - to_outstanding).min(self.accrued);
+        if self.outstanding == 0 {
+            self.due_at = 0;
+            self.suspended = false;
+        }
+    }
+}
+
+impl Sealed for Invoice {}
+
+impl IsInitialized for Invoice {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for Invoice {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [51, 194, 250, 114, 6, 104, 18, 164];
+}
+
+impl Pack for Invoice {
+    const LEN: usize = 83; // discriminator (8) + version (1) + Pubkey (32) + u64 (8) * 3 + i64 (8) * 2 + bool (1) * 2
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        let mut cursor = 0;
+        dst[cursor..cursor + 32].copy_from_slice(self.client.as_ref());
+        cursor += 32;
+        dst[cursor..cursor + 8].copy_from_slice(&self.credit_limit.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.accrued.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.outstanding.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.period_ends_at.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.due_at.to_le_bytes());
+        cursor += 8;
+        dst[cursor] = self.suspended as u8;
+        cursor += 1;
+        dst[cursor] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(Invoice {
+            client: Pubkey::new_from_array(src[0..32].try_into().unwrap()),
+            credit_limit: u64::from_le_bytes(src[32..40].try_into().unwrap()),
+            accrued: u64::from_le_bytes(src[40..48].try_into().unwrap()),
+            outstanding: u64::from_le_bytes(src[48..56].try_into().unwrap()),
+            period_ends_at: i64::from_le_bytes(src[56..64].try_into().unwrap()),
+            due_at: i64::from_le_bytes(src[64..72].try_into().unwrap()),
+            suspended: src[72] != 0,
+            is_initialized: src[73] != 0,
+        })
+    }
+}
+
+fn load_invoice(program_id: &Pubkey, invoice_acc: &AccountInfo) -> Result<Invoice, ProgramError> {
+    assert_owned_by(invoice_acc, program_id)?;
+    let invoice = Invoice::unpack(&invoice_acc.try_borrow_data()?)?;
+    assert_pda(invoice_acc, invoice_address(program_id, &invoice.client))?;
+    Ok(invoice)
+}
+
+/// Defers a protocol fee owed by `client` onto their invoice.
+pub(crate) fn accrue_fee(program_id: &Pubkey, invoice_acc: &AccountInfo, client: &Pubkey, fee: u64) -> ProgramResult {
+    let mut invoice = load_invoice(program_id, invoice_acc)?;
+    if invoice.client != *client {
+        return Err(ProgramError::IllegalOwner);
+    }
+    invoice.accrue(fee, Clock::get()?.unix_timestamp)?;
+    pack_state(&invoice, invoice_acc)
+}
+
+/// Sets the credit limit governance approved for `client`, opening their invoice
+/// on first use. A zero limit stops further deferral without forgiving fees owed.
+pub struct SetCreditLimitAccounts<'a, 'info> {
+    pub invoice_acc: &'a AccountInfo<'info>,
+    pub governance_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub invoice_bump: u8,
+}
+
+impl<'a, 'info> SetCreditLimitAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], client: &Pubkey) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let invoice_acc = next_account_info(account_info_iter)?;
+        let governance_acc = next_account_info(account_info_iter)?;
+        let program_config_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        config::check_authority(program_id, program_config_acc, &[AuthorityRole::Governance], governance_acc)?;
+        let invoice_bump = assert_pda(invoice_acc, invoice_address(program_id, client))?;
+        Ok(Self { invoice_acc, governance_acc, system_program_acc, invoice_bump })
+    }
+}
+
+pub fn set_credit_limit(program_id: &Pubkey, accounts: &[AccountInfo], client: Pubkey, credit_limit: u64) -> ProgramResult {
+    let SetCreditLimitAccounts { invoice_acc, governance_acc, system_program_acc, invoice_bump } =
+        SetCreditLimitAccounts::try_from(program_id, accounts, &client)?;
+
+    let mut invoice = if invoice_acc.lamports() > 0 {
+        load_invoice(program_id, invoice_acc)?
+    } else {
+        invoke_signed(
+            &system_instruction::create_account(
+                governance_acc.key,
+                invoice_acc.key,
+                Rent::get()?.minimum_balance(Invoice::LEN),
+                Invoice::LEN as u64,
+                program_id,
+            ),
+            &[governance_acc.clone(), invoice_acc.clone(), system_program_acc.clone()],
+            &[&[INVOICE_SEED, client.as_ref(), &[invoice_bump]]],
+        )?;
+        Invoice {
+            client,
+            credit_limit: 0,
+            accrued: 0,
+            outstanding: 0,
+            period_ends_at: Clock::get()?.unix_timestamp + BILLING_PERIOD_SECONDS,
+            due_at: 0,
+            suspended: false,
+            is_initialized: true,
+        }
+    };
+    invoice.credit_limit = credit_limit;
+    pack_state(&invoice, invoice_acc)?;
+    msg!("Credit limit for {} set to {}", client, credit_limit);
+    Ok(())
+}
+
+pub struct PayInvoiceAccounts<'a, 'info> {
+    pub invoice_acc: &'a AccountInfo<'info>,
+    pub payer_token_acc: &'a AccountInfo<'info>,
+    pub payer_acc: &'a AccountInfo<'info>,
+    pub fee_vault_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> PayInvoiceAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            invoice_acc: next_account_info(account_info_iter)?,
+            payer_token_acc: next_account_info(account_info_iter)?,
+            payer_acc: next_account_info(account_info_iter)?,
+            fee_vault_acc: next_account_info(account_info_iter)?,
+            token_program_acc: next_account_info(account_info_iter)?,
+            mint_acc: next_account_info(account_info_iter)?,
+        };
+        assert_signer(ctx.payer_acc)?;
+        token_program::check_token_program(ctx.token_program_acc, ctx.mint_acc)?;
+        TokenContract::check_fee_vault(program_id, ctx.fee_vault_acc, ctx.mint_acc.key)?;
+        Ok(ctx)
+    }
+}
+
+/// Pays up to `amount` of the invoice into the fee vault, billed fees first.
+pub fn pay_invoice(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let PayInvoiceAccounts { invoice_acc, payer_token_acc, payer_acc, fee_vault_acc, token_program_acc, mint_acc } =
+        PayInvoiceAccounts::try_from(program_id, accounts)?;
+    let mut invoice = load_invoice(program_id, invoice_acc)?;
+    invoice.roll_period(Clock::get()?.unix_timestamp)?;
+    let payment = amount.min(invoice.outstanding.saturating_add(invoice.accrued));
+    if payment > 0 {
+        token_program::transfer(token_program_acc, payer_token_acc, mint_acc, fee_vault_acc, payer_acc, payment, &[])?;
+    }
+    invoice.apply_payment(payment);
+    pack_state(&invoice, invoice_acc)?;
+    msg!("Paid {} towards invoice", payment);
+    Ok(())
+}
+
+pub struct SettleInvoiceAccounts<'a, 'info> {
+    pub invoice_acc: &'a AccountInfo<'info>,
+    pub client_token_acc: &'a AccountInfo<'info>,
+    pub fee_vault_acc: &'a AccountInfo<'info>,
+    pub fee_authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub fee_authority_bump: u8,
+}
+
+impl<'a, 'info> SettleInvoiceAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let invoice_acc = next_account_info(account_info_iter)?;
+        let client_token_acc = next_account_info(account_info_iter)?;
+        let fee_vault_acc = next_account_info(account_info_iter)?;
+        let fee_authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+
+        let fee_authority_bump = assert_pda(fee_authority_acc, TokenContract::fee_vault_authority(program_id))?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        TokenContract::check_fee_vault(program_id, fee_vault_acc, mint_acc.key)?;
+        Ok(Self { invoice_acc, client_token_acc, fee_vault_acc, fee_authority_acc, token_program_acc, mint_acc, fee_authority_bump })
+    }
+}
+
+/// Keeper crank: bills closed periods and collects billed fees from the client's
+/// token account, which must have delegated to the fee vault PDA. Suspends the
+/// invoice if it is still overdue afterwards.
+pub fn settle_invoice(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let SettleInvoiceAccounts { invoice_acc, client_token_acc, fee_vault_acc, fee_authority_acc, token_program_acc, mint_acc, fee_authority_bump } =
+        SettleInvoiceAccounts::try_from(program_id, accounts)?;
+    let mut invoice = load_invoice(program_id, invoice_acc)?;
+    let client_token = assert_token_account_mint(client_token_acc, mint_acc.key)?;
+    if client_token.owner != invoice.client {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    invoice.roll_period(now)?;
+    let allowance = if client_token.delegate == COption::Some(*fee_authority_acc.key) { client_token.delegated_amount } else { 0 };
+    let collected = invoice.outstanding.min(allowance).min(client_token.amount);
+    if collected > 0 {
+        token_program::transfer(
+            token_program_acc,
+            client_token_acc,
+            mint_acc,
+            fee_vault_acc,
+            fee_authority_acc,
+            collected,
+            &[&[FEE_VAULT_SEED, &[fee_authority_bump]]],
+        )?;
+        invoice.apply_payment(collected);
+    }
+    if invoice.is_overdue(now) {
+        invoice.suspended = true;
+        msg!("Invoice for {} is overdue by {}, suspended", invoice.client, invoice.outstanding);
+    }
+    pack_state(&invoice, invoice_acc)?;
+    msg!("Collected {} in invoiced fees", collected);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invoice(credit_limit: u64) -> Invoice {
+        Invoice {
+            client: Pubkey::new_unique(),
+            credit_limit,
+            accrued: 0,
+            outstanding: 0,
+            period_ends_at: BILLING_PERIOD_SECONDS,
+            due_at: 0,
+            suspended: false,
+            is_initialized: true,
+        }
+    }
+
+    #[test]
+    fn test_invoice_bills_periods_and_blocks_overdue_clients() {
+        let mut invoice = invoice(100);
+        invoice.accrue(60, 10).unwrap();
+        assert_eq!(invoice.accrue(41, 20), Err(GgtError::CreditLimitExceeded.into()));
+        invoice.accrue(40, 20).unwrap();
+
+        // The first period closes: its fees are billed and due after the grace period.
+        let due_at = BILLING_PERIOD_SECONDS + PAYMENT_GRACE_SECONDS;
+        invoice.roll_period(BILLING_PERIOD_SECONDS).unwrap();
+        assert_eq!((invoice.accrued, invoice.outstanding, invoice.due_at), (0, 100, due_at));
+        assert_eq!(invoice.period_ends_at, 2 * BILLING_PERIOD_SECONDS);
+        assert_eq!(invoice.accrue(1, BILLING_PERIOD_SECONDS), Err(GgtError::CreditLimitExceeded.into()));
+
+        invoice.apply_payment(30);
+        invoice.accrue(10, BILLING_PERIOD_SECONDS + 1).unwrap();
+        assert!(!invoice.is_overdue(due_at - 1));
+        assert_eq!(invoice.accrue(1, due_at), Err(GgtError::InvoiceOverdue.into()));
+
+        invoice.suspended = true;
+        invoice.apply_payment(75);
+        assert_eq!((invoice.outstanding, invoice.accrued, invoice.due_at, invoice.suspended), (0, 5, 0, false));
+        invoice.accrue(1, due_at).unwrap();
+    }
+
+    #[test]
+    fn test_invoice_reports_overflow_at_boundaries() {
+        let mut invoice = invoice(u64::MAX);
+        invoice.accrue(u64::MAX, 10).unwrap();
+        assert_eq!(invoice.accrue(1, 20), Err(GgtError::MathOverflow.into()));
+
+        invoice.roll_period(BILLING_PERIOD_SECONDS).unwrap();
+        invoice.accrued = 1;
+        assert_eq!(invoice.roll_period(2 * BILLING_PERIOD_SECONDS), Err(GgtError::MathOverflow.into()));
+    }
+}
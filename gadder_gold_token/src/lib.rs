@@ -2,10 +2,10 @@
 
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    entrypoint,
+    clock::Clock,
     entrypoint::ProgramResult,
     msg,
-    program::{invoke, invoke_signed},
+    program::{invoke, invoke_signed, set_return_data},
     program_error::ProgramError,
     program_option::COption,
     program_pack::{Pack},
@@ -14,137 +14,825 @@ use solana_program::{
     sysvar::Sysvar,
     system_instruction,
 };
-use spl_token::{
-    instruction as token_instruction,
-    state::{Account as TokenAccount, Mint},
+use spl_token::state::Mint;
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use mpl_token_metadata::{
+    accounts::Metadata,
+    instructions::{CreateMetadataAccountV3, CreateMetadataAccountV3InstructionArgs, UpdateMetadataAccountV2, UpdateMetadataAccountV2InstructionArgs},
 };
-use mpl_token_metadata::instructions::{CreateMetadataAccountV3, CreateMetadataAccountV3InstructionArgs};
 
+pub mod error;
+pub mod events;
+pub mod instruction;
+mod config;
+mod denylist;
+mod discriminator;
+mod dispute;
 mod ai_contract;
+mod airdrop;
+mod address_book;
+mod bridge_transfer;
+mod engagement;
+mod invoice;
+mod lookup_table;
+mod market_maker;
+mod migration;
+mod multisig;
+mod oracle_operator;
+mod payment_receipt;
+mod reputation;
 mod governance_contract;
 mod staking_contract;
+mod stake_receipt;
+mod state;
 mod cross_chain_bridge_contract;
+mod treasury_contract;
+mod streaming_contract;
+mod vesting;
+mod vote_escrow;
+mod wrapped_asset;
+mod wormhole;
+mod profiling;
+mod token_program;
+mod validation;
+mod zero_copy;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod test_utils;
+#[cfg(test)]
+mod pack_proptests;
+#[cfg(any(test, feature = "client"))]
+pub mod client;
+#[cfg(feature = "idl")]
+pub mod idl;
+
+use error::GgtError;
+use crate::validation::{assert_pda, assert_signer, assert_token_account_mint};
 
 pub const ADMIN_PUBKEY: Pubkey = Pubkey::new_from_array([0xAA; 32]);
 pub const GOVERNANCE_PUBKEY: Pubkey = Pubkey::new_from_array([0xBB; 32]);
 pub const BRIDGE_ADMIN_PUBKEY: Pubkey = Pubkey::new_from_array([0xCC; 32]);
 
-pub struct TokenContract;
+/// Instruction layout version written by current clients, right after the tag byte.
+pub const INSTRUCTION_VERSION: u8 = 1;
+/// Oldest layout still accepted. Raise it only once clients have moved off it,
+/// so old clients keep working across an upgrade that bumps `INSTRUCTION_VERSION`.
+pub const MIN_SUPPORTED_INSTRUCTION_VERSION: u8 = 1;
+pub const GET_VERSION_TAG: u8 = 14;
+pub const SET_PAUSE_FLAGS_TAG: u8 = 92;
+pub const MINT_SEED: &[u8] = b"ggt-mint";
+pub const FEE_VAULT_SEED: &[u8] = b"fee_vault";
+pub const FREEZE_AUTHORITY_SEED: &[u8] = b"freeze_authority";
+/// Most recipients one `batch_transfer` pays, keeping it within the CPI budget.
+pub const MAX_BATCH_RECIPIENTS: usize = 32;
 
-impl TokenContract {
-    pub fn initialize_token(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+/// Mint PDA, authority and token program, followed by the accounts
+/// `create_token_metadata` expects.
+pub struct InitializeTokenAccounts<'a, 'info> {
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub metadata_accs: &'a [AccountInfo<'info>],
+    pub mint_bump: u8,
+}
+
+impl<'a, 'info> InitializeTokenAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
         let account_info_iter = &mut accounts.iter();
         let mint_acc = next_account_info(account_info_iter)?;
         let authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(authority_acc)?;
+        if !token_program::is_supported(token_program_acc.key) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mint_bump = assert_pda(mint_acc, TokenContract::mint_address(program_id))?;
+        Ok(Self { mint_acc, authority_acc, token_program_acc, metadata_accs: account_info_iter.as_slice(), mint_bump })
+    }
+}
+
+pub struct TokenMetadataAccounts<'a, 'info> {
+    pub metadata_pda_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub mint_authority: &'a AccountInfo<'info>,
+    pub payer_acc: &'a AccountInfo<'info>,
+    pub update_authority: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub rent_sysvar: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> TokenMetadataAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            metadata_pda_acc: next_account_info(account_info_iter)?,
+            mint_acc: next_account_info(account_info_iter)?,
+            mint_authority: next_account_info(account_info_iter)?,
+            payer_acc: next_account_info(account_info_iter)?,
+            update_authority: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+            rent_sysvar: next_account_info(account_info_iter)?,
+        };
+        let _token_metadata_program = next_account_info(account_info_iter)?;
+        Ok(ctx)
+    }
+}
+
+/// The token metadata program follows `update_authority_acc`; it is only checked.
+pub struct UpdateTokenMetadataAccounts<'a, 'info> {
+    pub metadata_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub update_authority_acc: &'a AccountInfo<'info>,
+    pub metadata: Metadata,
+}
+
+impl<'a, 'info> UpdateTokenMetadataAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let metadata_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let update_authority_acc = next_account_info(account_info_iter)?;
+        let token_metadata_program = next_account_info(account_info_iter)?;
+
+        if token_metadata_program.key != &mpl_token_metadata::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if metadata_acc.owner != &mpl_token_metadata::ID {
+            return Err(ProgramError::IllegalOwner);
+        }
+        if *metadata_acc.key != Metadata::find_pda(mint_acc.key).0 {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let metadata = Metadata::safe_deserialize(&metadata_acc.try_borrow_data()?).map_err(|_| ProgramError::InvalidAccountData)?;
+        assert_signer(update_authority_acc)?;
+        if metadata.update_authority != *update_authority_acc.key {
+            msg!("Metadata update authority is {}", metadata.update_authority);
+            return Err(ProgramError::IllegalOwner);
+        }
+        Ok(Self { metadata_acc, mint_acc, update_authority_acc, metadata })
+    }
+}
+
+/// The compliance authority and program config sit between
+/// `freeze_authority_acc` and `token_program_acc`; they are only checked.
+pub struct SetFrozenAccounts<'a, 'info> {
+    pub token_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub freeze_authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub freeze_authority_bump: u8,
+}
+
+impl<'a, 'info> SetFrozenAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let token_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let freeze_authority_acc = next_account_info(account_info_iter)?;
+        let compliance_acc = next_account_info(account_info_iter)?;
+        let config_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+
+        config::check_compliance_authority(program_id, config_acc, compliance_acc)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        let freeze_authority_bump = assert_pda(freeze_authority_acc, TokenContract::freeze_authority(program_id))?;
+        Ok(Self { token_acc, mint_acc, freeze_authority_acc, token_program_acc, freeze_authority_bump })
+    }
+}
+
+/// Accounts for `transfer_tokens`. With the optional trailing delegate, the
+/// delegate signs and moves the tokens as `authority_acc`; otherwise the owner
+/// does. `source_token` is the unpacked source account. Neither side of the
+/// transfer may be on the denylist in `denylist_acc`.
+pub struct TransferAccounts<'a, 'info> {
+    pub source_acc: &'a AccountInfo<'info>,
+    pub dest_acc: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub config_acc: &'a AccountInfo<'info>,
+    pub fee_vault_acc: &'a AccountInfo<'info>,
+    pub denylist_acc: &'a AccountInfo<'info>,
+    pub delegate_acc: Option<&'a AccountInfo<'info>>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub source_token: spl_token_2022::state::Account,
+}
+
+impl<'a, 'info> TransferAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let source_acc = next_account_info(account_info_iter)?;
+        let dest_acc = next_account_info(account_info_iter)?;
+        let owner_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let config_acc = next_account_info(account_info_iter)?;
+        let fee_vault_acc = next_account_info(account_info_iter)?;
+        let denylist_acc = next_account_info(account_info_iter)?;
+        let delegate_acc = next_account_info(account_info_iter).ok();
+
+        let authority_acc = delegate_acc.unwrap_or(owner_acc);
+        assert_signer(authority_acc)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        let source_token = assert_token_account_mint(source_acc, mint_acc.key)?;
+        if source_token.owner != *owner_acc.key {
+            msg!("{} does not own {}", owner_acc.key, source_acc.key);
+            return Err(ProgramError::IllegalOwner);
+        }
+        Ok(Self {
+            source_acc,
+            dest_acc,
+            owner_acc,
+            token_program_acc,
+            mint_acc,
+            config_acc,
+            fee_vault_acc,
+            denylist_acc,
+            delegate_acc,
+            authority_acc,
+            source_token,
+        })
+    }
+}
+
+/// Accounts for `approve_delegate`. The owner of `source_acc` signs.
+pub struct ApproveDelegateAccounts<'a, 'info> {
+    pub source_acc: &'a AccountInfo<'info>,
+    pub delegate_acc: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ApproveDelegateAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let source_acc = next_account_info(account_info_iter)?;
+        let delegate_acc = next_account_info(account_info_iter)?;
+        let RevokeDelegateAccounts { owner_acc, token_program_acc, mint_acc, .. } =
+            RevokeDelegateAccounts::check(source_acc, account_info_iter)?;
+        Ok(Self { source_acc, delegate_acc, owner_acc, token_program_acc, mint_acc })
+    }
+}
+
+/// Accounts for `revoke_delegate`. The owner of `source_acc` signs.
+pub struct RevokeDelegateAccounts<'a, 'info> {
+    pub source_acc: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> RevokeDelegateAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let source_acc = next_account_info(account_info_iter)?;
+        Self::check(source_acc, account_info_iter)
+    }
+
+    /// Reads the owner, token program and mint that follow `source_acc`.
+    fn check(
+        source_acc: &'a AccountInfo<'info>,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'info>>,
+    ) -> Result<Self, ProgramError> {
+        let owner_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        assert_signer(owner_acc)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        if assert_token_account_mint(source_acc, mint_acc.key)?.owner != *owner_acc.key {
+            msg!("Only the owner of {} can manage its delegate", source_acc.key);
+            return Err(ProgramError::IllegalOwner);
+        }
+        Ok(Self { source_acc, owner_acc, token_program_acc, mint_acc })
+    }
+}
+
+/// The accounts of a `transfer` without a delegate, where `dest_acc` is the
+/// recipient's associated token account, followed by the recipient wallet, the
+/// system program and the associated token account program. The owner pays
+/// for the account, so it signs and is writable.
+pub struct TransferWithAtaCreateAccounts<'a, 'info> {
+    pub transfer_accs: &'a [AccountInfo<'info>],
+    pub dest_acc: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub recipient_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub associated_token_program_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> TransferWithAtaCreateAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let _source_acc = next_account_info(account_info_iter)?;
+        let dest_acc = next_account_info(account_info_iter)?;
+        let owner_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let _config_acc = next_account_info(account_info_iter)?;
+        let _fee_vault_acc = next_account_info(account_info_iter)?;
+        let _denylist_acc = next_account_info(account_info_iter)?;
+        let recipient_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+        let associated_token_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(owner_acc)?;
+        assert_pda(dest_acc, token_program::associated_token_address(recipient_acc.key, mint_acc.key, token_program_acc.key))?;
+        Ok(Self {
+            transfer_accs: &accounts[..8],
+            dest_acc,
+            owner_acc,
+            token_program_acc,
+            mint_acc,
+            recipient_acc,
+            system_program_acc,
+            associated_token_program_acc,
+        })
+    }
+}
+
+/// The `transfer_tokens` accounts, without a delegate, optionally followed by
+/// the receipt PDA for the reference and the system program. The owner signs
+/// and pays for the receipt.
+pub struct TransferWithMemoAccounts<'a, 'info> {
+    pub transfer_accs: &'a [AccountInfo<'info>],
+    pub dest_acc: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub receipt_acc: Option<&'a AccountInfo<'info>>,
+    pub system_program_acc: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> TransferWithMemoAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let _source_acc = next_account_info(account_info_iter)?;
+        let dest_acc = next_account_info(account_info_iter)?;
+        let owner_acc = next_account_info(account_info_iter)?;
         let _token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let _config_acc = next_account_info(account_info_iter)?;
+        let _fee_vault_acc = next_account_info(account_info_iter)?;
+        let _denylist_acc = next_account_info(account_info_iter)?;
+        let receipt_acc = next_account_info(account_info_iter).ok();
+        let system_program_acc = next_account_info(account_info_iter).ok();
+
+        assert_signer(owner_acc)?;
+        Ok(Self { transfer_accs: &accounts[..8], dest_acc, owner_acc, mint_acc, receipt_acc, system_program_acc })
+    }
+}
 
-        if !authority_acc.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+/// Neither side of the transfer may be on the denylist in `denylist_acc`.
+pub struct InvoicedTransferAccounts<'a, 'info> {
+    pub source_acc: &'a AccountInfo<'info>,
+    pub dest_acc: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub config_acc: &'a AccountInfo<'info>,
+    pub invoice_acc: &'a AccountInfo<'info>,
+    pub denylist_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> InvoicedTransferAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            source_acc: next_account_info(account_info_iter)?,
+            dest_acc: next_account_info(account_info_iter)?,
+            owner_acc: next_account_info(account_info_iter)?,
+            token_program_acc: next_account_info(account_info_iter)?,
+            mint_acc: next_account_info(account_info_iter)?,
+            config_acc: next_account_info(account_info_iter)?,
+            invoice_acc: next_account_info(account_info_iter)?,
+            denylist_acc: next_account_info(account_info_iter)?,
+        };
+        assert_signer(ctx.owner_acc)?;
+        token_program::check_token_program(ctx.token_program_acc, ctx.mint_acc)?;
+        assert_token_account_mint(ctx.source_acc, ctx.mint_acc.key)?;
+        Ok(ctx)
+    }
+}
+
+/// Recipient token accounts follow `denylist_acc`, one per amount. No party
+/// to the transfer may be on the denylist.
+pub struct BatchTransferAccounts<'a, 'info> {
+    pub source_acc: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub config_acc: &'a AccountInfo<'info>,
+    pub fee_vault_acc: &'a AccountInfo<'info>,
+    pub denylist_acc: &'a AccountInfo<'info>,
+    pub recipient_accs: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> BatchTransferAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let source_acc = next_account_info(account_info_iter)?;
+        let owner_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let config_acc = next_account_info(account_info_iter)?;
+        let fee_vault_acc = next_account_info(account_info_iter)?;
+        let denylist_acc = next_account_info(account_info_iter)?;
+        let recipient_accs = account_info_iter.as_slice();
+
+        assert_signer(owner_acc)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        assert_token_account_mint(source_acc, mint_acc.key)?;
+        Ok(Self { source_acc, owner_acc, token_program_acc, mint_acc, config_acc, fee_vault_acc, denylist_acc, recipient_accs })
+    }
+}
+
+pub struct WithdrawFeesAccounts<'a, 'info> {
+    pub fee_vault_acc: &'a AccountInfo<'info>,
+    pub treasury_vault_acc: &'a AccountInfo<'info>,
+    pub fee_authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub fee_authority_bump: u8,
+}
+
+impl<'a, 'info> WithdrawFeesAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let fee_vault_acc = next_account_info(account_info_iter)?;
+        let treasury_vault_acc = next_account_info(account_info_iter)?;
+        let fee_authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+
+        let fee_authority_bump = assert_pda(fee_authority_acc, TokenContract::fee_vault_authority(program_id))?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        TokenContract::check_fee_vault(program_id, fee_vault_acc, mint_acc.key)?;
+        let treasury_vault = assert_token_account_mint(treasury_vault_acc, mint_acc.key)?;
+        if treasury_vault.owner != treasury_contract::Treasury::treasury_authority(program_id).0 {
+            msg!("Fees can only be withdrawn to a treasury vault");
+            return Err(ProgramError::IllegalOwner);
+        }
+        Ok(Self { fee_vault_acc, treasury_vault_acc, fee_authority_acc, token_program_acc, mint_acc, fee_authority_bump })
+    }
+}
+
+/// Accounts for `burn_tokens`. `owner_acc` must own `token_account` and sign;
+/// delegates cannot burn.
+pub struct BurnAccounts<'a, 'info> {
+    pub token_account: &'a AccountInfo<'info>,
+    pub mint_account: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> BurnAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            token_account: next_account_info(account_info_iter)?,
+            mint_account: next_account_info(account_info_iter)?,
+            owner_acc: next_account_info(account_info_iter)?,
+            token_program_acc: next_account_info(account_info_iter)?,
+        };
+        assert_signer(ctx.owner_acc)?;
+        if assert_token_account_mint(ctx.token_account, ctx.mint_account.key)?.owner != *ctx.owner_acc.key {
+            msg!("Only the owner of {} can burn from it", ctx.token_account.key);
+            return Err(ProgramError::IllegalOwner);
+        }
+        token_program::check_token_program(ctx.token_program_acc, ctx.mint_account)?;
+        Ok(ctx)
+    }
+}
+
+/// Mint settings carried by `initialize_token`. An empty payload launches GGT
+/// with `TokenParams::default()`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct TokenParams {
+    pub decimals: u8,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    /// Pass `TokenContract::freeze_authority` to let the compliance authority
+    /// freeze accounts, or `None` to make the mint unfreezable.
+    pub freeze_authority: Option<Pubkey>,
+}
+
+impl Default for TokenParams {
+    fn default() -> Self {
+        TokenParams {
+            decimals: 9,
+            name: "Gadder Gold".to_string(),
+            symbol: "GGT".to_string(),
+            uri: "http://example.com/metadata".to_string(),
+            freeze_authority: None,
+        }
+    }
+}
+
+/// Rejects metadata fields longer than the token metadata program stores.
+fn check_metadata_fields(name: &str, symbol: &str, uri: &str) -> ProgramResult {
+    if name.len() > mpl_token_metadata::MAX_NAME_LENGTH
+        || symbol.len() > mpl_token_metadata::MAX_SYMBOL_LENGTH
+        || uri.len() > mpl_token_metadata::MAX_URI_LENGTH
+    {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    Ok(())
+}
+
+pub struct TokenContract;
+
+impl TokenContract {
+    /// Creates the GGT mint at its PDA, and its metadata, as described by
+    /// `params`. The mint can only be created once.
+    pub fn initialize_token(program_id: &Pubkey, accounts: &[AccountInfo], params: TokenParams) -> ProgramResult {
+        let InitializeTokenAccounts { mint_acc, authority_acc, token_program_acc, metadata_accs, mint_bump } =
+            InitializeTokenAccounts::try_from(program_id, accounts)?;
+        check_metadata_fields(&params.name, &params.symbol, &params.uri)?;
+        if mint_acc.lamports() > 0 {
+            msg!("GGT mint already exists");
+            return Err(ProgramError::AccountAlreadyInitialized);
         }
 
-        let decimals = 9u8;
         let mint_data = Mint {
             mint_authority: COption::Some(*authority_acc.key),
             supply: 0,
-            decimals,
+            decimals: params.decimals,
             is_initialized: true,
-            freeze_authority: COption::None,
+            freeze_authority: params.freeze_authority.into(),
         };
         let rent = Rent::get()?;
         let space = Mint::LEN;
         let lamports = rent.minimum_balance(space);
 
-        invoke(
+        invoke_signed(
             &system_instruction::create_account(
                 authority_acc.key,
                 mint_acc.key,
                 lamports,
                 space as u64,
-                &spl_token::id(),
+                token_program_acc.key,
             ),
             &[authority_acc.clone(), mint_acc.clone()],
+            &[&[MINT_SEED, program_id.as_ref(), &[mint_bump]]],
         )?;
 
         Mint::pack(mint_data, &mut mint_acc.try_borrow_mut_data()?)?;
 
-        let metadata_accounts = accounts[3..].to_vec();
-        Self::create_token_metadata(
-            program_id,
-            &metadata_accounts,
-            "Gadder Gold",
-            "GGT",
-            "http://example.com/metadata",
-        )?;
-        msg!("Token initialized with metadata!");
+        Self::create_token_metadata(program_id, metadata_accs, &params.name, &params.symbol, &params.uri)?;
+        msg!("Token {} initialized with {} decimals", params.symbol, params.decimals);
         Ok(())
     }
 
-    pub fn transfer_tokens(_program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
-        let account_info_iter = &mut accounts.iter();
-        let source_acc = next_account_info(account_info_iter)?;
-        let dest_acc = next_account_info(account_info_iter)?;
-        let owner_acc = next_account_info(account_info_iter)?;
-        let token_program_acc = next_account_info(account_info_iter)?;
-        let delegate_acc = next_account_info(account_info_iter).ok();
+    pub fn mint_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[MINT_SEED, program_id.as_ref()], program_id)
+    }
+
+    pub fn fee_vault_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[FEE_VAULT_SEED], program_id)
+    }
 
-        if !owner_acc.is_signer && delegate_acc.map_or(true, |d| !d.is_signer) {
-            return Err(ProgramError::MissingRequiredSignature);
+    /// Checks that `fee_vault_acc` is a token account of `mint` owned by the fee vault PDA.
+    pub(crate) fn check_fee_vault(program_id: &Pubkey, fee_vault_acc: &AccountInfo, mint: &Pubkey) -> ProgramResult {
+        let fee_vault = token_program::unpack_account(fee_vault_acc)?;
+        if fee_vault.owner != Self::fee_vault_authority(program_id).0 || fee_vault.mint != *mint {
+            msg!("Fee vault is not owned by the fee vault PDA");
+            return Err(ProgramError::IllegalOwner);
         }
+        Ok(())
+    }
+
+    pub fn freeze_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[FREEZE_AUTHORITY_SEED], program_id)
+    }
+
+    /// Freezes (`freeze == true`) or thaws a GGT token account. Only the compliance
+    /// authority in the program config may call it; the mint's freeze authority
+    /// must be the freeze authority PDA.
+    pub fn set_account_frozen(program_id: &Pubkey, accounts: &[AccountInfo], freeze: bool) -> ProgramResult {
+        let SetFrozenAccounts { token_acc, mint_acc, freeze_authority_acc, token_program_acc, freeze_authority_bump } =
+            SetFrozenAccounts::try_from(program_id, accounts)?;
 
-        let source_token_acc = TokenAccount::unpack(&source_acc.try_borrow_data()?)?;
+        token_program::set_frozen(
+            token_program_acc,
+            token_acc,
+            mint_acc,
+            freeze_authority_acc,
+            freeze,
+            &[&[FREEZE_AUTHORITY_SEED, &[freeze_authority_bump]]],
+        )?;
+        msg!("{} token account {}", if freeze { "Froze" } else { "Thawed" }, token_acc.key);
+        Ok(())
+    }
+
+    /// Transfers `amount` minus the configured protocol fee to `dest`; the fee goes
+    /// to a token account owned by the fee vault PDA. Returns the net amount and fee.
+    pub fn transfer_tokens(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> Result<(u64, u64), ProgramError> {
+        let TransferAccounts {
+            source_acc,
+            dest_acc,
+            owner_acc,
+            token_program_acc,
+            mint_acc,
+            config_acc,
+            fee_vault_acc,
+            denylist_acc,
+            delegate_acc,
+            authority_acc,
+            source_token,
+        } = TransferAccounts::try_from(accounts)?;
+        denylist::check_transfer_parties(program_id, denylist_acc, source_acc, &[owner_acc.key, authority_acc.key], &[dest_acc])?;
         if let Some(delegate) = delegate_acc {
-            if source_token_acc.delegate != COption::Some(*delegate.key) || source_token_acc.delegated_amount < amount {
+            if source_token.delegate != COption::Some(*delegate.key) || source_token.delegated_amount < amount {
                 return Err(ProgramError::InsufficientFunds);
             }
         }
 
-        let ix = token_instruction::transfer(
-            token_program_acc.key,
-            source_acc.key,
-            dest_acc.key,
-            owner_acc.key,
-            &[],
-            amount,
+        let fee = config::load_config(program_id, config_acc)?.transfer_fee(amount);
+        if fee > 0 {
+            Self::check_fee_vault(program_id, fee_vault_acc, mint_acc.key)?;
+        }
+        let net = amount.checked_sub(fee).ok_or(GgtError::MathOverflow)?;
+
+        for (destination, value) in [(dest_acc, net), (fee_vault_acc, fee)] {
+            if value == 0 {
+                continue;
+            }
+            token_program::transfer(token_program_acc, source_acc, mint_acc, destination, authority_acc, value, &[])?;
+        }
+        events::emit(&events::TransferEvent {
+            source: *source_acc.key,
+            destination: *dest_acc.key,
+            authority: *authority_acc.key,
+            amount: net,
+            fee,
+        });
+        msg!("Transferred {} tokens with fee {}!", net, fee);
+        Ok((net, fee))
+    }
+
+    /// Creates the recipient's associated token account if it does not exist yet,
+    /// paid by the sender, then transfers to it as `transfer_tokens` does.
+    pub fn transfer_with_ata_create(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+        let TransferWithAtaCreateAccounts {
+            transfer_accs,
+            dest_acc,
+            owner_acc,
+            token_program_acc,
+            mint_acc,
+            recipient_acc,
+            system_program_acc,
+            associated_token_program_acc,
+        } = TransferWithAtaCreateAccounts::try_from(accounts)?;
+        token_program::create_associated_token_account_idempotent(
+            associated_token_program_acc,
+            owner_acc,
+            dest_acc,
+            recipient_acc,
+            mint_acc,
+            system_program_acc,
+            token_program_acc,
         )?;
+        Self::transfer_tokens(program_id, transfer_accs, amount)?;
+        Ok(())
+    }
 
-        invoke(&ix, &[source_acc.clone(), dest_acc.clone(), owner_acc.clone(), token_program_acc.clone()])?;
-        msg!("Transferred {} tokens!", amount);
+    /// Transfers as `transfer_tokens` does and tags the payment with
+    /// `reference` in a `PaymentReferenced` event, recording it in a
+    /// `PaymentReceipt` PDA as well when the receipt accounts are passed.
+    pub fn transfer_with_memo(program_id: &Pubkey, accounts: &[AccountInfo], reference: [u8; 32], amount: u64) -> ProgramResult {
+        let TransferWithMemoAccounts { transfer_accs, dest_acc, owner_acc, mint_acc, receipt_acc, system_program_acc } =
+            TransferWithMemoAccounts::try_from(accounts)?;
+        let (net, fee) = Self::transfer_tokens(program_id, transfer_accs, amount)?;
+        if let Some(receipt_acc) = receipt_acc {
+            let system_program_acc = system_program_acc.ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let receipt = payment_receipt::PaymentReceipt {
+                reference,
+                sender: *owner_acc.key,
+                recipient: *dest_acc.key,
+                mint: *mint_acc.key,
+                amount: net,
+                fee,
+                paid_at: Clock::get()?.unix_timestamp,
+                is_initialized: true,
+            };
+            payment_receipt::record_receipt(program_id, receipt_acc, owner_acc, system_program_acc, receipt)?;
+        }
+        events::emit(&events::PaymentReferenced { reference, sender: *owner_acc.key, recipient: *dest_acc.key, amount: net, fee });
+        msg!("Payment {} received by {}", Pubkey::new_from_array(reference), dest_acc.key);
         Ok(())
     }
 
-    pub fn burn_tokens(_program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
-        let account_info_iter = &mut accounts.iter();
-        let token_account = next_account_info(account_info_iter)?;
-        let mint_account = next_account_info(account_info_iter)?;
-        let burn_authority = next_account_info(account_info_iter)?;
-        let token_program_acc = next_account_info(account_info_iter)?;
+    /// Lets `delegate` move up to `amount` of the owner's tokens, replacing any
+    /// earlier allowance.
+    pub fn approve_delegate(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+        let ApproveDelegateAccounts { source_acc, delegate_acc, owner_acc, token_program_acc, mint_acc } =
+            ApproveDelegateAccounts::try_from(accounts)?;
+        token_program::approve(token_program_acc, source_acc, mint_acc, delegate_acc, owner_acc, amount)?;
+        events::emit(&events::DelegateApproved {
+            source: *source_acc.key,
+            owner: *owner_acc.key,
+            delegate: *delegate_acc.key,
+            amount,
+        });
+        msg!("Approved {} to spend {} tokens!", delegate_acc.key, amount);
+        Ok(())
+    }
 
-        if burn_authority.key != &ADMIN_PUBKEY && burn_authority.key != &GOVERNANCE_PUBKEY {
-            msg!("Unauthorized burn attempt!");
-            return Err(ProgramError::IllegalOwner);
+    /// Clears whatever allowance the source account has granted.
+    pub fn revoke_delegate(accounts: &[AccountInfo]) -> ProgramResult {
+        let RevokeDelegateAccounts { source_acc, owner_acc, token_program_acc, mint_acc } = RevokeDelegateAccounts::try_from(accounts)?;
+        token_program::revoke(token_program_acc, source_acc, mint_acc, owner_acc)?;
+        events::emit(&events::DelegateRevoked { source: *source_acc.key, owner: *owner_acc.key });
+        msg!("Revoked the delegate of {}!", source_acc.key);
+        Ok(())
+    }
+
+    /// Transfers the full `amount` to `dest` for an enterprise client, deferring the
+    /// protocol fee onto the owner's invoice instead of taking it from the transfer.
+    pub fn transfer_tokens_on_invoice(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+        let InvoicedTransferAccounts { source_acc, dest_acc, owner_acc, token_program_acc, mint_acc, config_acc, invoice_acc, denylist_acc } =
+            InvoicedTransferAccounts::try_from(accounts)?;
+        denylist::check_transfer_parties(program_id, denylist_acc, source_acc, &[owner_acc.key], &[dest_acc])?;
+        let fee = config::load_config(program_id, config_acc)?.transfer_fee(amount);
+        invoice::accrue_fee(program_id, invoice_acc, owner_acc.key, fee)?;
+
+        token_program::transfer(token_program_acc, source_acc, mint_acc, dest_acc, owner_acc, amount, &[])?;
+        events::emit(&events::TransferEvent {
+            source: *source_acc.key,
+            destination: *dest_acc.key,
+            authority: *owner_acc.key,
+            amount,
+            fee,
+        });
+        msg!("Transferred {} tokens with fee {} invoiced!", amount, fee);
+        Ok(())
+    }
+
+    /// Pays `amounts[i]` to the i-th recipient account in full and charges one
+    /// protocol fee on the total, on top of it. Any failed leg fails the whole
+    /// instruction, so either every recipient is paid or none is.
+    pub fn batch_transfer(program_id: &Pubkey, accounts: &[AccountInfo], amounts: Vec<u64>) -> ProgramResult {
+        let BatchTransferAccounts { source_acc, owner_acc, token_program_acc, mint_acc, config_acc, fee_vault_acc, denylist_acc, recipient_accs } =
+            BatchTransferAccounts::try_from(accounts)?;
+        if amounts.is_empty() || amounts.len() > MAX_BATCH_RECIPIENTS || amounts.len() != recipient_accs.len() {
+            return Err(ProgramError::InvalidArgument);
         }
-        if !burn_authority.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+        let recipients: Vec<&AccountInfo> = recipient_accs.iter().collect();
+        denylist::check_transfer_parties(program_id, denylist_acc, source_acc, &[owner_acc.key], &recipients)?;
+        if amounts.contains(&0) {
+            return Err(ProgramError::InvalidArgument);
         }
+        let total = amounts.iter().try_fold(0u64, |sum, amount| sum.checked_add(*amount)).ok_or(GgtError::MathOverflow)?;
 
-        let ix = token_instruction::burn(
-            token_program_acc.key,
-            token_account.key,
-            mint_account.key,
-            burn_authority.key,
-            &[],
+        let fee = config::load_config(program_id, config_acc)?.transfer_fee(total);
+        if fee > 0 {
+            Self::check_fee_vault(program_id, fee_vault_acc, mint_acc.key)?;
+        }
+        for (recipient_acc, amount) in recipient_accs.iter().zip(&amounts) {
+            token_program::transfer(token_program_acc, source_acc, mint_acc, recipient_acc, owner_acc, *amount, &[])?;
+        }
+        if fee > 0 {
+            token_program::transfer(token_program_acc, source_acc, mint_acc, fee_vault_acc, owner_acc, fee, &[])?;
+        }
+        events::emit(&events::BatchTransferEvent {
+            source: *source_acc.key,
+            authority: *owner_acc.key,
+            recipients: recipient_accs.iter().map(|acc| *acc.key).collect(),
+            amounts,
+            fee,
+        });
+        msg!("Batch transferred {} tokens to {} recipients with fee {}!", total, recipient_accs.len(), fee);
+        Ok(())
+    }
+
+    /// Sweeps collected transfer fees into a treasury vault. Permissionless, since
+    /// the destination must be owned by the treasury PDA.
+    pub fn withdraw_fees(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+        let WithdrawFeesAccounts { fee_vault_acc, treasury_vault_acc, fee_authority_acc, token_program_acc, mint_acc, fee_authority_bump } =
+            WithdrawFeesAccounts::try_from(program_id, accounts)?;
+
+        token_program::transfer(
+            token_program_acc,
+            fee_vault_acc,
+            mint_acc,
+            treasury_vault_acc,
+            fee_authority_acc,
             amount,
+            &[&[FEE_VAULT_SEED, &[fee_authority_bump]]],
         )?;
+        msg!("Withdrew {} in fees to the treasury", amount);
+        Ok(())
+    }
 
-        invoke(
-            &ix,
-            &[
-                token_account.clone(),
-                mint_account.clone(),
-                burn_authority.clone(),
-                token_program_acc.clone(),
-            ],
-        )?;
+    /// Burns `amount` from a token account, signed by its owner. Governance
+    /// burns what it holds through a proposal carrying a `TokenBurn`.
+    pub fn burn_tokens(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+        let BurnAccounts { token_account, mint_account, owner_acc, token_program_acc } = BurnAccounts::try_from(accounts)?;
+
+        token_program::burn(token_program_acc, token_account, mint_account, owner_acc, amount, &[])?;
         msg!("Burned {} tokens!", amount);
         Ok(())
     }
@@ -156,15 +844,8 @@ impl TokenContract {
         symbol: &str,
         uri: &str,
     ) -> ProgramResult {
-        let account_info_iter = &mut accounts.iter();
-        let metadata_pda_acc = next_account_info(account_info_iter)?;
-        let mint_acc = next_account_info(account_info_iter)?;
-        let mint_authority = next_account_info(account_info_iter)?;
-        let payer_acc = next_account_info(account_info_iter)?;
-        let update_authority = next_account_info(account_info_iter)?;
-        let system_program = next_account_info(account_info_iter)?;
-        let rent_sysvar = next_account_info(account_info_iter)?;
-        let _token_metadata_program = next_account_info(account_info_iter)?;
+        let TokenMetadataAccounts { metadata_pda_acc, mint_acc, mint_authority, payer_acc, update_authority, system_program, rent_sysvar } =
+            TokenMetadataAccounts::try_from(accounts)?;
 
         let ix = CreateMetadataAccountV3 {
             metadata: *metadata_pda_acc.key,
@@ -205,26 +886,150 @@ impl TokenContract {
         msg!("Created token metadata for mint {}", mint_acc.key);
         Ok(())
     }
+
+    /// Replaces the name, symbol and URI of the GGT metadata, keeping its other
+    /// fields. The metadata's update authority must sign; the role itself moves
+    /// through `nominate_authority` and `accept_authority`.
+    pub fn update_token_metadata(accounts: &[AccountInfo], name: String, symbol: String, uri: String) -> ProgramResult {
+        let UpdateTokenMetadataAccounts { metadata_acc, mint_acc, update_authority_acc, metadata } =
+            UpdateTokenMetadataAccounts::try_from(accounts)?;
+        check_metadata_fields(&name, &symbol, &uri)?;
+
+        let ix = UpdateMetadataAccountV2 { metadata: *metadata_acc.key, update_authority: *update_authority_acc.key }.instruction(
+            UpdateMetadataAccountV2InstructionArgs {
+                data: Some(mpl_token_metadata::types::DataV2 {
+                    name,
+                    symbol,
+                    uri,
+                    seller_fee_basis_points: metadata.seller_fee_basis_points,
+                    creators: metadata.creators,
+                    collection: metadata.collection,
+                    uses: metadata.uses,
+                }),
+                new_update_authority: None,
+                primary_sale_happened: None,
+                is_mutable: None,
+            },
+        );
+        invoke(&ix, &[metadata_acc.clone(), update_authority_acc.clone()])?;
+        msg!("Updated token metadata for mint {}", mint_acc.key);
+        Ok(())
+    }
 }
 
-entrypoint!(process_instruction);
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
 
-fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
-    if data.is_empty() {
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if data.len() < 2 {
         return Err(ProgramError::InvalidInstructionData);
     }
 
-    let (tag, rest) = data.split_at(1);
+    let (header, rest) = data.split_at(2);
+    let (tag, version) = (header[0], header[1]);
+    if tag == GET_VERSION_TAG {
+        // Answered for any version byte so clients can negotiate before knowing what is supported.
+        set_return_data(&[MIN_SUPPORTED_INSTRUCTION_VERSION, INSTRUCTION_VERSION]);
+        return Ok(());
+    }
+    if !(MIN_SUPPORTED_INSTRUCTION_VERSION..=INSTRUCTION_VERSION).contains(&version) {
+        msg!("Unsupported instruction version {} for tag {}", version, tag);
+        return Err(GgtError::UnsupportedInstructionVersion.into());
+    }
+
+    profiling::profiled(tag, || match tag {
+        33 => config::initialize_program_config(program_id, accounts),
+        34 => {
+            let paused = rest.first().cloned().unwrap_or(0) == 1;
+            config::set_paused(program_id, accounts, paused)
+        }
+        35 => {
+            let slots = parse_amount(rest)?;
+            config::drill_pause(program_id, accounts, slots)
+        }
+        36 => {
+            let fee_bps = parse_u16(rest)?;
+            config::set_transfer_fee(program_id, accounts, fee_bps)
+        }
+        // Compliance actions stay available while the program is paused.
+        48 => {
+            let compliance_authority: Pubkey = parse_args(rest)?;
+            config::set_compliance_authority(program_id, accounts, compliance_authority)
+        }
+        49 => TokenContract::set_account_frozen(program_id, accounts, true),
+        50 => TokenContract::set_account_frozen(program_id, accounts, false),
+        143 => denylist::add_to_denylist(program_id, accounts, parse_args(rest)?),
+        144 => denylist::remove_from_denylist(program_id, accounts, parse_args(rest)?),
+        // Read-only queries skip the pause gate, like `get_version`.
+        68 => treasury_contract::Treasury::get_treasury_balance(program_id, accounts),
+        120 => staking_contract::StakingContract::new().get_stake_info(program_id, accounts),
+        121 => governance_contract::GovernanceContract::get_proposal_state(program_id, accounts, parse_amount(rest)?),
+        122 => cross_chain_bridge_contract::CrossChainBridge::get_bridge_config(program_id, accounts),
+        131 => reputation::get_reputation(program_id, accounts),
+        SET_PAUSE_FLAGS_TAG => {
+            let pause_flags = rest.first().cloned().ok_or(ProgramError::InvalidInstructionData)?;
+            config::set_pause_flags(program_id, accounts, pause_flags)
+        }
+        // Roles can change hands while paused.
+        98 => {
+            let (role, nominee): (config::AuthorityRole, Pubkey) = parse_args(rest)?;
+            config::nominate_authority(program_id, accounts, role, nominee)
+        }
+        99 => config::accept_authority(program_id, accounts, parse_args(rest)?),
+        // The admin multisig stays available while paused, so it can lift the pause.
+        94 => {
+            let (owners, threshold): (Vec<Pubkey>, u8) = parse_args(rest)?;
+            multisig::initialize_multisig(program_id, accounts, owners, threshold)
+        }
+        95 => multisig::propose_admin_action(program_id, accounts, parse_args(rest)?),
+        96 => multisig::approve_admin_action(program_id, accounts),
+        97 => multisig::execute_admin_action(program_id, accounts),
+        147 => {
+            let (owners, threshold): (Vec<Pubkey>, u8) = parse_args(rest)?;
+            multisig::set_multisig_owners(program_id, accounts, owners, threshold)
+        }
+        // Retags the program config itself, which the pause gate could not read untagged.
+        109 => discriminator::tag_legacy_account(program_id, accounts, parse_args(rest)?),
+        // Likewise for a program config written before layout versions.
+        145 => discriminator::migrate_account(program_id, accounts, parse_args(rest)?),
+        _ => {
+            // Every other instruction takes the program config PDA as its last account.
+            let (config_acc, accounts) = accounts.split_last().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            config::check_not_paused(program_id, config_acc, pause_flag(program_id, tag, accounts))?;
+            dispatch(program_id, accounts, tag, rest)
+        }
+    })
+}
+
+/// The `PAUSE_*` bit that stops `tag`, or zero if only the global pause does.
+fn pause_flag(program_id: &Pubkey, tag: u8, accounts: &[AccountInfo]) -> u8 {
+    match tag {
+        1 | 2 | 17 | 18 | 20 | 30 | 31 | 32 | 37 | 38 | 39 | 40 | 61 | 64 | 65 | 66 | 72 | 73 | 85 | 86 | 87 | 103 | 104 | 105 | 117
+        | 118 | 119 | 129 | 130 | 139 | 140 | 142 => config::PAUSE_TRANSFERS,
+        3 | 4 | 12 | 26 | 42 | 43 | 44 | 47 | 51 | 58 | 59 | 74 | 75 | 76 | 100 | 101 | 111 | 112 | 124 | 126 | 127 | 137 | 138 => {
+            config::PAUSE_STAKING
+        }
+        6 if accounts.first().is_some_and(|proposal_acc| governance_contract::only_sets_pause_flags(program_id, proposal_acc)) => 0,
+        6 | 28 | 29 | 141 => config::PAUSE_GOVERNANCE_EXECUTION,
+        8 | 9 | 57 | 79 | 80 | 81 | 107 | 108 | 132 | 134 | 135 => config::PAUSE_BRIDGE,
+        _ => 0,
+    }
+}
 
-    match tag[0] {
-        0 => TokenContract::initialize_token(program_id, accounts),
+fn dispatch(program_id: &Pubkey, accounts: &[AccountInfo], tag: u8, rest: &[u8]) -> ProgramResult {
+    match tag {
+        0 => {
+            let params = if rest.is_empty() { TokenParams::default() } else { parse_args(rest)? };
+            TokenContract::initialize_token(program_id, accounts, params)
+        }
         1 => {
             let amount = parse_amount(rest)?;
-            TokenContract::transfer_tokens(program_id, accounts, amount)
+            TokenContract::transfer_tokens(program_id, accounts, amount)?;
+            Ok(())
         }
         2 => {
             let amount = parse_amount(rest)?;
-            TokenContract::burn_tokens(program_id, accounts, amount)
+            TokenContract::burn_tokens(accounts, amount)
         }
         3 => {
             let amount = parse_amount(rest)?;
@@ -238,33 +1043,372 @@ fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8
             staking_contract.unstake_tokens(program_id, accounts, amount)
         }
         5 => {
-            let description = String::from_utf8_lossy(rest);
-            governance_contract::GovernanceContract::create_proposal(program_id, accounts, &description)
+            let content: governance_contract::ProposalContent = parse_args(rest)?;
+            let kind = governance_contract::ProposalKind::Text;
+            governance_contract::GovernanceContract::create_proposal(program_id, accounts, content, kind, Vec::new())
         }
         6 => {
             let proposal_id = parse_amount(rest)?;
             governance_contract::GovernanceContract::execute_proposal(program_id, accounts, proposal_id)
         }
         7 => {
-            let proposal_id = parse_amount(rest)?;
-            let vote = rest.get(8).cloned().unwrap_or(0) == 1;
-            governance_contract::GovernanceContract::vote_on_proposal(program_id, accounts, proposal_id, vote)
+            let (proposal_id, choice): (u64, governance_contract::VoteChoice) = parse_args(rest)?;
+            governance_contract::GovernanceContract::vote_on_proposal(program_id, accounts, proposal_id, choice)
         }
         8 => {
             let amount = parse_amount(rest)?;
-            let target_chain = String::from_utf8_lossy(&rest[8..]).to_string();
-            cross_chain_bridge_contract::CrossChainBridge::lock_tokens_for_bridge(program_id, accounts, amount, &target_chain)
+            let target_chain_id = parse_u16(&rest[8..])?;
+            cross_chain_bridge_contract::CrossChainBridge::lock_tokens_for_bridge(program_id, accounts, amount, target_chain_id, None)
         }
         9 => {
             let amount = parse_amount(rest)?;
-            let target_chain_address = String::from_utf8_lossy(&rest[8..]).to_string();
-            let dummy_signature = vec![0u8; 64];
-            cross_chain_bridge_contract::CrossChainBridge::release_tokens_on_target_chain(program_id, accounts, amount, &target_chain_address, &dummy_signature)
+            let nonce = parse_amount(&rest[8..])?;
+            let source_chain_id = parse_u16(&rest[16..])?;
+            cross_chain_bridge_contract::CrossChainBridge::release_tokens_on_target_chain(program_id, accounts, amount, nonce, source_chain_id)
+        }
+        11 => staking_contract::StakingContract::new().initialize_pool(program_id, accounts),
+        12 => {
+            let mut staking_contract = staking_contract::StakingContract::new();
+            staking_contract.claim_rewards(program_id, accounts)
+        }
+        13 => {
+            let proposal_id = parse_amount(rest)?;
+            governance_contract::GovernanceContract::finalize_proposal(program_id, accounts, proposal_id)
+        }
+        15 => {
+            let (guardian_threshold, guardians): (u8, Vec<Pubkey>) = parse_args(rest)?;
+            cross_chain_bridge_contract::CrossChainBridge::initialize_bridge_config(program_id, accounts, guardian_threshold, guardians)
+        }
+        16 => {
+            let (guardian_threshold, guardians): (u8, Vec<Pubkey>) = parse_args(rest)?;
+            cross_chain_bridge_contract::CrossChainBridge::set_guardian_set(program_id, accounts, guardian_threshold, guardians)
+        }
+        106 => {
+            let (evm_guardian_threshold, evm_guardians): (u8, Vec<[u8; 20]>) = parse_args(rest)?;
+            cross_chain_bridge_contract::CrossChainBridge::set_evm_guardian_set(program_id, accounts, evm_guardian_threshold, evm_guardians)
+        }
+        17 => {
+            let (fee, sla_seconds, requirements): (u64, i64, String) = parse_args(rest)?;
+            ai_contract::request_match(program_id, accounts, fee, sla_seconds, &requirements)
+        }
+        18 => ai_contract::reclaim_match_fee(program_id, accounts),
+        19 => ai_contract::expire_match_requests(program_id, accounts),
+        20 => {
+            let consultant: Pubkey = parse_args(rest)?;
+            ai_contract::fulfill_match(program_id, accounts, consultant)
+        }
+        21 => {
+            let oracles: Vec<Pubkey> = parse_args(rest)?;
+            ai_contract::set_oracles(program_id, accounts, oracles)
+        }
+        22 => governance_contract::GovernanceContract::open_ballot_page(program_id, accounts),
+        23 => {
+            let vote = rest.first().cloned().unwrap_or(0) == 1;
+            governance_contract::GovernanceContract::cast_ballot(program_id, accounts, vote)
+        }
+        24 => {
+            let page_index = parse_u16(rest)?;
+            governance_contract::GovernanceContract::tally_page(program_id, accounts, page_index)
+        }
+        25 => {
+            let (evm_address, signature, recovery_id): ([u8; 20], [u8; 64], u8) = parse_args(rest)?;
+            ai_contract::link_evm_address(program_id, accounts, evm_address, signature, recovery_id)
+        }
+        26 => staking_contract::StakingContract::new().init_stake_account(program_id, accounts),
+        27 => {
+            let max_holding = parse_amount(rest)?;
+            treasury_contract::Treasury::set_treasury_asset(program_id, accounts, max_holding)
+        }
+        28 => {
+            let amount = parse_amount(rest)?;
+            treasury_contract::Treasury::treasury_spend(program_id, accounts, amount)
+        }
+        29 => {
+            let (amount_in, amount_out): (u64, u64) = parse_args(rest)?;
+            treasury_contract::Treasury::treasury_swap(program_id, accounts, amount_in, amount_out)
+        }
+        30 => {
+            let (recipients, schedule): (Vec<(Pubkey, u64)>, streaming_contract::StreamSchedule) = parse_args(rest)?;
+            streaming_contract::Streaming::create_stream(program_id, accounts, recipients, schedule)
+        }
+        31 => {
+            let recipient_index = rest.first().cloned().ok_or(ProgramError::InvalidInstructionData)?;
+            streaming_contract::Streaming::withdraw_stream(program_id, accounts, recipient_index)
+        }
+        32 => streaming_contract::Streaming::liquidate_underfunded(program_id, accounts),
+        37 => {
+            let amount = parse_amount(rest)?;
+            TokenContract::withdraw_fees(program_id, accounts, amount)
+        }
+        38 => {
+            let (beneficiary, total_amount, cliff_seconds, duration_seconds): (Pubkey, u64, i64, i64) = parse_args(rest)?;
+            vesting::Vesting::create_vesting_schedule(program_id, accounts, beneficiary, total_amount, cliff_seconds, duration_seconds)
+        }
+        39 => vesting::Vesting::claim_vested(program_id, accounts),
+        40 => vesting::Vesting::revoke_vesting(program_id, accounts),
+        41 => {
+            let (partner_ratio_bps, boost_bps): (u64, u64) = parse_args(rest)?;
+            staking_contract::StakingContract::new().initialize_co_staking_pool(program_id, accounts, partner_ratio_bps, boost_bps)
+        }
+        42 => {
+            let (ggt_amount, partner_amount): (u64, u64) = parse_args(rest)?;
+            staking_contract::StakingContract::new().co_stake(program_id, accounts, ggt_amount, partner_amount)
+        }
+        43 => {
+            let ggt_amount = parse_amount(rest)?;
+            staking_contract::StakingContract::new().co_unstake(program_id, accounts, ggt_amount)
+        }
+        44 => staking_contract::StakingContract::new().claim_co_stake_rewards(program_id, accounts),
+        45 => {
+            let params: governance_contract::GovernanceParams = parse_args(rest)?;
+            governance_contract::GovernanceContract::initialize_governance_config(program_id, accounts, params)
+        }
+        46 => {
+            let (content, params): (governance_contract::ProposalContent, governance_contract::GovernanceParams) = parse_args(rest)?;
+            let kind = governance_contract::ProposalKind::GovernanceRules(params);
+            governance_contract::GovernanceContract::create_proposal(program_id, accounts, content, kind, Vec::new())
+        }
+        47 => staking_contract::StakingContract::new().convert_legacy_stake(program_id, accounts),
+        51 => staking_contract::StakingContract::new().roll_reward_epoch(program_id, accounts),
+        52 => {
+            let economics: staking_contract::StakingEconomics = parse_args(rest)?;
+            staking_contract::StakingContract::new().initialize_staking_config(program_id, accounts, economics)
+        }
+        53 => {
+            let economics: staking_contract::StakingEconomics = parse_args(rest)?;
+            staking_contract::StakingContract::new().set_staking_economics(program_id, accounts, economics)
+        }
+        54 => {
+            let (chain, destination, evm_proof): (String, Vec<u8>, Option<address_book::EvmProof>) = parse_args(rest)?;
+            address_book::register_destination(program_id, accounts, chain, destination, evm_proof)
+        }
+        55 => {
+            let (chain, destination): (String, Vec<u8>) = parse_args(rest)?;
+            address_book::remove_destination(program_id, accounts, chain, destination)
+        }
+        56 => address_book::set_strict_mode(program_id, accounts, parse_args(rest)?),
+        57 => {
+            let (amount, target_chain_id, destination): (u64, u16, Vec<u8>) = parse_args(rest)?;
+            cross_chain_bridge_contract::CrossChainBridge::lock_tokens_for_bridge(
+                program_id,
+                accounts,
+                amount,
+                target_chain_id,
+                Some(&destination),
+            )
+        }
+        58 => {
+            let lock_period_in_days = parse_amount(rest)?;
+            staking_contract::StakingContract::new().extend_lock(program_id, accounts, lock_period_in_days)
+        }
+        59 => {
+            let amount = parse_amount(rest)?;
+            staking_contract::StakingContract::new().add_to_stake(program_id, accounts, amount)
+        }
+        60 => {
+            let members: Vec<ai_contract::SplitMember> = parse_args(rest)?;
+            ai_contract::set_payout_split(program_id, accounts, members)
+        }
+        61 => {
+            let amount = parse_amount(rest)?;
+            ai_contract::distribute_payout(program_id, accounts, amount)
+        }
+        62 => {
+            let (content, instructions): (governance_contract::ProposalContent, Vec<governance_contract::ProposalInstruction>) = parse_args(rest)?;
+            let kind = governance_contract::ProposalKind::Text;
+            governance_contract::GovernanceContract::create_proposal(program_id, accounts, content, kind, instructions)
+        }
+        63 => {
+            let (client, credit_limit): (Pubkey, u64) = parse_args(rest)?;
+            invoice::set_credit_limit(program_id, accounts, client, credit_limit)
+        }
+        64 => {
+            let amount = parse_amount(rest)?;
+            TokenContract::transfer_tokens_on_invoice(program_id, accounts, amount)
+        }
+        65 => {
+            let amount = parse_amount(rest)?;
+            invoice::pay_invoice(program_id, accounts, amount)
+        }
+        66 => invoice::settle_invoice(program_id, accounts),
+        67 => {
+            let (content, spend): (governance_contract::ProposalContent, governance_contract::TreasurySpend) = parse_args(rest)?;
+            let kind = governance_contract::ProposalKind::TreasurySpend(spend);
+            governance_contract::GovernanceContract::create_proposal(program_id, accounts, content, kind, Vec::new())
+        }
+        69 => {
+            let mode: cross_chain_bridge_contract::BridgeMode = parse_args(rest)?;
+            cross_chain_bridge_contract::CrossChainBridge::set_bridge_mode(program_id, accounts, mode)
+        }
+        70 => {
+            let recent_slot = parse_amount(rest)?;
+            lookup_table::create_lookup_table(program_id, accounts, recent_slot)
+        }
+        71 => {
+            let addresses: Vec<Pubkey> = parse_args(rest)?;
+            lookup_table::extend_lookup_table(program_id, accounts, addresses)
+        }
+        72 => {
+            let (cutoff_at, params): (i64, migration::Token2022MintParams) = parse_args(rest)?;
+            migration::start_migration(program_id, accounts, cutoff_at, params)
+        }
+        73 => {
+            let amount = parse_amount(rest)?;
+            migration::migrate_tokens(program_id, accounts, amount)
+        }
+        74 => {
+            let amount = parse_amount(rest)?;
+            staking_contract::StakingContract::new().request_unstake(program_id, accounts, amount)
+        }
+        75 => staking_contract::StakingContract::new().withdraw_unstaked(program_id, accounts),
+        76 => staking_contract::StakingContract::new().sync_stake_summary(program_id, accounts),
+        77 => {
+            let chain: cross_chain_bridge_contract::ChainInfo = parse_args(rest)?;
+            cross_chain_bridge_contract::CrossChainBridge::register_chain(program_id, accounts, chain)
+        }
+        78 => {
+            let (collateral_ratio_bps, approved): (u16, bool) = parse_args(rest)?;
+            market_maker::set_market_maker(program_id, accounts, collateral_ratio_bps, approved)
+        }
+        79 => {
+            let amount = parse_amount(rest)?;
+            market_maker::withdraw_collateral(program_id, accounts, amount)
+        }
+        80 => {
+            let (amount, target_chain_id, destination): (u64, u16, Vec<u8>) = parse_args(rest)?;
+            market_maker::fast_mint(program_id, accounts, amount, target_chain_id, destination)
+        }
+        81 => market_maker::reconcile_fast_mint(program_id, accounts),
+        82 => {
+            let (skills_hash, hourly_rate): ([u8; 32], u64) = parse_args(rest)?;
+            ai_contract::register_consultant(program_id, accounts, skills_hash, hourly_rate)
+        }
+        83 => {
+            let (skills_hash, hourly_rate, available): ([u8; 32], u64, bool) = parse_args(rest)?;
+            ai_contract::update_consultant(program_id, accounts, skills_hash, hourly_rate, available)
+        }
+        84 => ai_contract::deactivate_consultant(program_id, accounts),
+        85 => {
+            let (engagement_id, milestones, duration_seconds): (u64, Vec<u64>, i64) = parse_args(rest)?;
+            engagement::open_engagement(program_id, accounts, engagement_id, milestones, duration_seconds)
+        }
+        86 => engagement::complete_engagement(program_id, accounts),
+        87 => engagement::cancel_engagement(program_id, accounts),
+        88 => {
+            let (name, symbol, uri): (String, String, String) = parse_args(rest)?;
+            TokenContract::update_token_metadata(accounts, name, symbol, uri)
+        }
+        89 => {
+            let (daily_outflow_cap, chain_outflow_caps): (u64, Vec<cross_chain_bridge_contract::ChainOutflowCap>) = parse_args(rest)?;
+            cross_chain_bridge_contract::CrossChainBridge::set_outflow_caps(program_id, accounts, daily_outflow_cap, chain_outflow_caps)
+        }
+        90 => cross_chain_bridge_contract::CrossChainBridge::pause_bridge(program_id, accounts),
+        91 => cross_chain_bridge_contract::CrossChainBridge::unpause_bridge(program_id, accounts),
+        93 => governance_contract::GovernanceContract::cancel_proposal(program_id, accounts),
+        100 => stake_receipt::mint_stake_receipt(program_id, accounts, parse_args(rest)?),
+        101 => stake_receipt::burn_stake_receipt(program_id, accounts),
+        102 => stake_receipt::refresh_stake_receipt(program_id, accounts),
+        103 => {
+            let (distribution_id, merkle_root, total_amount, num_recipients): (u64, [u8; 32], u64, u32) = parse_args(rest)?;
+            airdrop::create_distribution(program_id, accounts, distribution_id, merkle_root, total_amount, num_recipients)
+        }
+        104 => {
+            let (index, amount, proof): (u64, u64, Vec<[u8; 32]>) = parse_args(rest)?;
+            airdrop::claim_airdrop(program_id, accounts, index, amount, proof)
+        }
+        105 => TokenContract::batch_transfer(program_id, accounts, parse_args(rest)?),
+        107 => {
+            let (source_chain_id, foreign_token, decimals): (u16, [u8; 32], u8) = parse_args(rest)?;
+            wrapped_asset::attest_foreign_asset(program_id, accounts, source_chain_id, foreign_token, decimals)
+        }
+        108 => {
+            let (amount, nonce, source_chain_id): (u64, u64, u16) = parse_args(rest)?;
+            wrapped_asset::complete_inbound_transfer(program_id, accounts, amount, nonce, source_chain_id)
+        }
+        110 => {
+            let (content, burn): (governance_contract::ProposalContent, governance_contract::TokenBurn) = parse_args(rest)?;
+            let kind = governance_contract::ProposalKind::TokenBurn(burn);
+            governance_contract::GovernanceContract::create_proposal(program_id, accounts, content, kind, Vec::new())
+        }
+        111 => {
+            let (amount, reward_rate_per_second, rewards_end_at): (u64, u64, i64) = parse_args(rest)?;
+            staking_contract::StakingContract::new().fund_rewards(program_id, accounts, amount, reward_rate_per_second, rewards_end_at)
+        }
+        112 => {
+            let (bps, reason_hash): (u16, [u8; 32]) = parse_args(rest)?;
+            staking_contract::StakingContract::new().slash(program_id, accounts, bps, reason_hash)
+        }
+        113 => {
+            let (content, change): (governance_contract::ProposalContent, governance_contract::ParameterChange) = parse_args(rest)?;
+            let kind = governance_contract::ProposalKind::ParameterChange(change);
+            governance_contract::GovernanceContract::create_proposal(program_id, accounts, content, kind, Vec::new())
+        }
+        114 => {
+            let (content, upgrade): (governance_contract::ProposalContent, governance_contract::CodeUpgrade) = parse_args(rest)?;
+            let kind = governance_contract::ProposalKind::CodeUpgrade(upgrade);
+            governance_contract::GovernanceContract::create_proposal(program_id, accounts, content, kind, Vec::new())
+        }
+        115 => {
+            let (proposal_id, choice): (u64, governance_contract::VoteChoice) = parse_args(rest)?;
+            governance_contract::GovernanceContract::update_vote(program_id, accounts, proposal_id, choice)
+        }
+        116 => {
+            let (proposal_id, chunk): (u64, Vec<u8>) = parse_args(rest)?;
+            governance_contract::GovernanceContract::extend_proposal_body(program_id, accounts, proposal_id, &chunk)
+        }
+        117 => {
+            let amount = parse_amount(rest)?;
+            TokenContract::transfer_with_ata_create(program_id, accounts, amount)
+        }
+        118 => {
+            let amount = parse_amount(rest)?;
+            TokenContract::approve_delegate(accounts, amount)
+        }
+        119 => TokenContract::revoke_delegate(accounts),
+        123 => {
+            let (min_bond, withdrawal_delay_seconds): (u64, i64) = parse_args(rest)?;
+            oracle_operator::set_oracle_bond_config(program_id, accounts, min_bond, withdrawal_delay_seconds)
+        }
+        124 => {
+            let amount = parse_amount(rest)?;
+            oracle_operator::register_oracle(program_id, accounts, amount)
+        }
+        125 => oracle_operator::deregister_oracle(program_id, accounts),
+        126 => oracle_operator::withdraw_oracle_bond(program_id, accounts),
+        127 => {
+            let (bps, reason_hash): (u16, [u8; 32]) = parse_args(rest)?;
+            oracle_operator::slash_oracle(program_id, accounts, bps, reason_hash)
+        }
+        128 => {
+            let (council, dispute_bond_bps, loser_slash_bps): (Vec<Pubkey>, u16, u16) = parse_args(rest)?;
+            dispute::set_arbitration_council(program_id, accounts, council, dispute_bond_bps, loser_slash_bps)
+        }
+        129 => dispute::open_dispute(program_id, accounts, parse_args(rest)?),
+        130 => dispute::resolve_dispute(program_id, accounts, parse_u16(rest)?),
+        132 => cross_chain_bridge_contract::CrossChainBridge::claim_relayer_fee(program_id, accounts),
+        133 => cross_chain_bridge_contract::CrossChainBridge::set_transfer_timeout(program_id, accounts, parse_args(rest)?),
+        134 => bridge_transfer::complete_bridge_transfer(program_id, accounts),
+        135 => bridge_transfer::cancel_bridge_transfer(program_id, accounts),
+        136 => {
+            let delegate: Pubkey = parse_args(rest)?;
+            staking_contract::StakingContract::new().set_voting_delegate(program_id, accounts, delegate)
+        }
+        137 => {
+            let (amount, lock_period_in_days): (u64, u64) = parse_args(rest)?;
+            vote_escrow::lock_for_voting(program_id, accounts, amount, lock_period_in_days)
+        }
+        138 => vote_escrow::withdraw_expired_lock(program_id, accounts),
+        139 => streaming_contract::Streaming::cancel_stream(program_id, accounts),
+        140 => {
+            let milestone_index = rest.first().cloned().ok_or(ProgramError::InvalidInstructionData)?;
+            engagement::approve_milestone(program_id, accounts, milestone_index)
         }
-        10 => {
-            let client_requirements = String::from_utf8_lossy(rest).to_string();
-            ai_contract::match_consultant(program_id, accounts, &client_requirements)
+        141 => treasury_contract::Treasury::burn_from_treasury(program_id, accounts, parse_amount(rest)?),
+        142 => {
+            let (reference, amount): ([u8; 32], u64) = parse_args(rest)?;
+            TokenContract::transfer_with_memo(program_id, accounts, reference, amount)
         }
+        146 => governance_contract::GovernanceContract::set_upgrade_authority(program_id, accounts),
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
@@ -276,4 +1420,700 @@ fn parse_amount(data: &[u8]) -> Result<u64, ProgramError> {
     let mut bytes = [0u8; 8];
     bytes.copy_from_slice(&data[..8]);
     Ok(u64::from_le_bytes(bytes))
-}
\ No newline at end of file
+}
+
+fn parse_u16(data: &[u8]) -> Result<u16, ProgramError> {
+    if data.len() < 2 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    Ok(u16::from_le_bytes([data[0], data[1]]))
+}
+
+/// Decodes Borsh-encoded arguments for instructions with variable-length payloads.
+fn parse_args<T: borsh::BorshDeserialize>(data: &[u8]) -> Result<T, ProgramError> {
+    borsh::from_slice(data).map_err(|_| ProgramError::InvalidInstructionData)
+}
+
+/// Publishes the Borsh encoding of a read-only query's result as return data.
+pub(crate) fn set_borsh_return_data<T: borsh::BorshSerialize>(value: &T) {
+    set_return_data(&borsh::to_vec(value).expect("serializing into a Vec cannot fail"));
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_token::state::Account as TokenAccount;
+
+    #[test]
+    fn test_dispatcher_rejects_unknown_instruction_version() {
+        let program_id = Pubkey::new_unique();
+        let mut data = vec![1, INSTRUCTION_VERSION + 1];
+        data.extend_from_slice(&500u64.to_le_bytes());
+        assert_eq!(
+            process_instruction(&program_id, &[], &data),
+            Err(GgtError::UnsupportedInstructionVersion.into())
+        );
+        assert_eq!(process_instruction(&program_id, &[], &[1]), Err(ProgramError::InvalidInstructionData));
+    }
+
+    #[test]
+    fn test_get_version_accepts_any_version_byte() {
+        let program_id = Pubkey::new_unique();
+        assert!(process_instruction(&program_id, &[], &[GET_VERSION_TAG, 0]).is_ok());
+        assert!(process_instruction(&program_id, &[], &[GET_VERSION_TAG, u8::MAX]).is_ok());
+    }
+
+    fn config_data(paused: bool, pause_until_slot: u64) -> Vec<u8> {
+        let mut data = vec![0u8; config::ProgramConfig::LEN];
+        let config = config::ProgramConfig { paused, pause_until_slot, fee_bps: 0, compliance_authority: Pubkey::default(), pause_flags: 0, is_initialized: true, ..Default::default() };
+        config::ProgramConfig::pack(config, &mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_pause_blocks_every_gated_instruction() {
+        let _bank = test_utils::SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        let mut lamports = 0u64;
+        let mut data = config_data(true, 0);
+        let config_acc = test_utils::account_info(&config_key, false, false, &mut lamports, &mut data, &program_id);
+
+        let gated = idl::gated_instructions();
+        // The IDL lists every tag the dispatcher handles behind the gate.
+        for tag in (0..=u8::MAX).filter(|tag| gated.iter().all(|(listed, _)| listed != tag)) {
+            assert_eq!(dispatch(&program_id, &[], tag, &[0u8; 64]), Err(ProgramError::InvalidInstructionData), "tag {} is missing from the IDL", tag);
+        }
+        for (tag, moves_tokens) in gated {
+            let mut data = vec![tag, INSTRUCTION_VERSION];
+            data.extend_from_slice(&[0u8; 64]);
+            assert_eq!(
+                process_instruction(&program_id, std::slice::from_ref(&config_acc), &data),
+                Err(GgtError::ProgramPaused.into()),
+                "tag {} bypassed the pause gate",
+                tag
+            );
+            // Anything that moves tokens can also be stopped by its group's flag,
+            // bar initialize_token, which runs once before there is anything to pause.
+            assert!(!moves_tokens || tag == 0 || pause_flag(&program_id, tag, &[]) != 0, "tag {} moves tokens outside every pause group", tag);
+        }
+    }
+
+    #[test]
+    fn test_pause_flags_stop_only_their_group() {
+        let _bank = test_utils::SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        let (audit_log_key, _) = config::audit_log_address(&program_id);
+        let (governance_authority, _) = governance_contract::governance_authority_address(&program_id);
+        let mut lamports = [0u64; 5];
+        let [l0, l1, l2, l3, l4] = &mut lamports;
+        let mut data = config_data(false, 0);
+        let mut audit_log_data = vec![0u8; config::AuditLog::LEN];
+        config::AuditLog::pack(config::AuditLog { entries: vec![], next_index: 0, is_initialized: true }, &mut audit_log_data).unwrap();
+        let (mut d1, mut d2) = (vec![], vec![]);
+        let config_acc = test_utils::account_info(&config_key, false, true, l0, &mut data, &program_id);
+        let governance_acc = test_utils::account_info(&GOVERNANCE_PUBKEY, true, false, l1, &mut d1, &program_id);
+        let authority_acc = test_utils::account_info(&governance_authority, true, false, l2, &mut d2, &program_id);
+        let audit_log_acc = test_utils::account_info(&audit_log_key, false, true, l3, &mut audit_log_data, &program_id);
+        test_utils::fund_rent_exempt(&[&config_acc, &audit_log_acc]);
+        let set_flags = [SET_PAUSE_FLAGS_TAG, INSTRUCTION_VERSION, config::PAUSE_STAKING | config::PAUSE_GOVERNANCE_EXECUTION];
+
+        assert_eq!(
+            process_instruction(&program_id, &[config_acc.clone(), governance_acc, audit_log_acc.clone()], &set_flags),
+            Err(ProgramError::IllegalOwner)
+        );
+        let by_authority = [config_acc.clone(), authority_acc, audit_log_acc.clone()];
+        assert_eq!(
+            process_instruction(&program_id, &by_authority, &[SET_PAUSE_FLAGS_TAG, INSTRUCTION_VERSION, 1 << 7]),
+            Err(ProgramError::InvalidArgument)
+        );
+        process_instruction(&program_id, &by_authority, &set_flags).unwrap();
+
+        let gated = |tag: u8| {
+            let mut data = vec![tag, INSTRUCTION_VERSION];
+            data.extend_from_slice(&[0u8; 64]);
+            process_instruction(&program_id, std::slice::from_ref(&config_acc), &data)
+        };
+        assert_eq!(gated(3), Err(GgtError::ProgramPaused.into()));
+        assert_eq!(gated(6), Err(GgtError::ProgramPaused.into()));
+        assert_ne!(gated(1), Err(GgtError::ProgramPaused.into()));
+        assert_ne!(gated(8), Err(GgtError::ProgramPaused.into()));
+
+        // A proposal that only lifts the flags still executes.
+        let lift = governance_contract::ProposalInstruction { program_id, accounts: vec![], data: vec![SET_PAUSE_FLAGS_TAG, INSTRUCTION_VERSION, 0] };
+        let proposal = governance_contract::Proposal {
+            uri: "ipfs://lift-the-staking-pause".to_string(),
+            content_hash: [0; 32],
+            proposer: Pubkey::new_unique(),
+            status: governance_contract::ProposalStatus::Passed,
+            timestamp: 0,
+            voting_ends_at: 0,
+            yes_weight: 1,
+            no_weight: 0,
+            abstain_weight: 0,
+            escrow_weight: 0,
+            ballot_pages: 0,
+            pages_tallied: 0,
+            kind: governance_contract::ProposalKind::Text,
+            deposit: 0,
+            snapshot_epoch: 0,
+            snapshot_reward_index: 0,
+            executable_at: 0,
+            is_initialized: true,
+            instructions: vec![lift],
+        };
+        let (proposal_key, mut proposal_data) = (Pubkey::new_unique(), vec![0u8; governance_contract::Proposal::LEN]);
+        proposal.pack_into_slice(&mut proposal_data);
+        let proposal_acc = test_utils::account_info(&proposal_key, false, true, l4, &mut proposal_data, &program_id);
+        assert_eq!(pause_flag(&program_id, 6, &[proposal_acc]), 0);
+        let log = config::AuditLog::unpack(&audit_log_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(log.entries[0].action, config::AuditAction::SetPauseFlags);
+    }
+
+    #[test]
+    fn test_drill_pause_lifts_after_slots() {
+        let bank = test_utils::SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        let (audit_log_key, _) = config::audit_log_address(&program_id);
+        let (admin_key, _) = multisig::multisig_authority_address(&program_id);
+        let (mut l0, mut l1, mut l2) = (0u64, 0u64, 0u64);
+        let mut data = config_data(false, 0);
+        let mut admin_data = vec![];
+        let mut audit_log_data = vec![0u8; config::AuditLog::LEN];
+        config::AuditLog::pack(config::AuditLog { entries: vec![], next_index: 0, is_initialized: true }, &mut audit_log_data).unwrap();
+        let config_acc = test_utils::account_info(&config_key, false, true, &mut l0, &mut data, &program_id);
+        let admin_acc = test_utils::account_info(&admin_key, true, false, &mut l1, &mut admin_data, &program_id);
+        let audit_log_acc = test_utils::account_info(&audit_log_key, false, true, &mut l2, &mut audit_log_data, &program_id);
+        test_utils::fund_rent_exempt(&[&config_acc, &audit_log_acc]);
+        let crank = [19u8, INSTRUCTION_VERSION];
+
+        bank.set_slot(1_000);
+        let mut drill = vec![35u8, INSTRUCTION_VERSION];
+        drill.extend_from_slice(&10u64.to_le_bytes());
+        process_instruction(&program_id, &[config_acc.clone(), admin_acc, audit_log_acc.clone()], &drill).unwrap();
+
+        bank.set_slot(1_009);
+        assert_eq!(
+            process_instruction(&program_id, std::slice::from_ref(&config_acc), &crank),
+            Err(GgtError::ProgramPaused.into())
+        );
+        bank.set_slot(1_010);
+        process_instruction(&program_id, std::slice::from_ref(&config_acc), &crank).unwrap();
+
+        let log = config::AuditLog::unpack(&audit_log_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(log.entries.len(), 1);
+        assert_eq!((log.entries[0].action, log.entries[0].slot, log.entries[0].detail), (config::AuditAction::DrillPause, 1_000, 10));
+    }
+
+    #[test]
+    fn test_governance_role_moves_only_once_nominee_accepts() {
+        let bank = test_utils::SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        let (audit_log_key, _) = config::audit_log_address(&program_id);
+        let (nominee_key, stranger_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut lamports = [0u64; 5];
+        let [l0, l1, l2, l3, l4] = &mut lamports;
+        let mut data = config_data(false, 0);
+        let (mut d1, mut d2, mut d3) = (vec![], vec![], vec![]);
+        let mut audit_log_data = vec![0u8; config::AuditLog::LEN];
+        config::AuditLog::pack(config::AuditLog { entries: vec![], next_index: 0, is_initialized: true }, &mut audit_log_data).unwrap();
+        let config_acc = test_utils::account_info(&config_key, false, true, l0, &mut data, &program_id);
+        let governance_acc = test_utils::account_info(&GOVERNANCE_PUBKEY, true, false, l1, &mut d1, &program_id);
+        let nominee_acc = test_utils::account_info(&nominee_key, true, false, l2, &mut d2, &program_id);
+        let stranger_acc = test_utils::account_info(&stranger_key, true, false, l3, &mut d3, &program_id);
+        let audit_log_acc = test_utils::account_info(&audit_log_key, false, true, l4, &mut audit_log_data, &program_id);
+        test_utils::fund_rent_exempt(&[&config_acc, &audit_log_acc]);
+        let nominate = [[98u8, INSTRUCTION_VERSION].as_slice(), &borsh::to_vec(&(config::AuthorityRole::Governance, nominee_key)).unwrap()].concat();
+        let accept = [99u8, INSTRUCTION_VERSION, config::AuthorityRole::Governance as u8];
+        let check = |signer_acc| config::check_authority(&program_id, &config_acc, &[config::AuthorityRole::Governance], signer_acc);
+
+        bank.set_unix_timestamp(1_000);
+        process_instruction(&program_id, &[config_acc.clone(), governance_acc.clone(), audit_log_acc.clone()], &nominate).unwrap();
+        assert_eq!(
+            process_instruction(&program_id, &[config_acc.clone(), stranger_acc, audit_log_acc.clone()], &accept),
+            Err(ProgramError::IllegalOwner)
+        );
+        bank.set_unix_timestamp(1_000 + config::NOMINATION_TTL_SECONDS);
+        assert_eq!(
+            process_instruction(&program_id, &[config_acc.clone(), nominee_acc.clone(), audit_log_acc.clone()], &accept),
+            Err(GgtError::NominationExpired.into())
+        );
+        assert_eq!(check(&nominee_acc), Err(ProgramError::IllegalOwner));
+
+        process_instruction(&program_id, &[config_acc.clone(), governance_acc.clone(), audit_log_acc.clone()], &nominate).unwrap();
+        process_instruction(&program_id, &[config_acc.clone(), nominee_acc.clone(), audit_log_acc.clone()], &accept).unwrap();
+        check(&nominee_acc).unwrap();
+        assert_eq!(check(&governance_acc), Err(ProgramError::IllegalOwner));
+        let log = config::AuditLog::unpack(&audit_log_acc.try_borrow_data().unwrap()).unwrap();
+        let actions: Vec<_> = log.entries.iter().map(|entry| entry.action).collect();
+        assert_eq!(actions, [config::AuditAction::NominateAuthority, config::AuditAction::NominateAuthority, config::AuditAction::AcceptAuthority]);
+    }
+
+    #[test]
+    fn test_transfer_takes_fee_and_withdraw_sweeps_to_treasury() {
+        let bank = test_utils::SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let mint = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        let (fee_authority_key, _) = TokenContract::fee_vault_authority(&program_id);
+        let (treasury_authority_key, _) = treasury_contract::Treasury::treasury_authority(&program_id);
+        let (source_key, dest_key, owner_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (fee_vault_key, treasury_vault_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let token_account = |owner: Pubkey, amount: u64| {
+            let mut data = vec![0u8; TokenAccount::LEN];
+            let account = TokenAccount {
+                mint,
+                owner,
+                amount,
+                state: spl_token::state::AccountState::Initialized,
+                ..TokenAccount::default()
+            };
+            TokenAccount::pack(account, &mut data).unwrap();
+            data
+        };
+        let dest_owner_key = Pubkey::new_unique();
+        let (denylist_key, mut denylist_data) = test_utils::denylist(&program_id, &[dest_owner_key]);
+        let mut lamports = [0u64; 10];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9] = &mut lamports;
+        let mut source_data = token_account(owner_key, 1_000);
+        let mut dest_data = token_account(dest_owner_key, 0);
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { decimals: 9, is_initialized: true, ..Mint::default() }, &mut mint_data).unwrap();
+        let mut fee_vault_data = token_account(fee_authority_key, 0);
+        let mut treasury_vault_data = token_account(treasury_authority_key, 0);
+        let mut config_data = vec![0u8; config::ProgramConfig::LEN];
+        config::ProgramConfig::pack(
+            config::ProgramConfig {
+                paused: false,
+                pause_until_slot: 0,
+                fee_bps: 250,
+                compliance_authority: Pubkey::default(),
+                pause_flags: 0,
+                is_initialized: true,
+                ..Default::default()
+            },
+            &mut config_data,
+        )
+        .unwrap();
+        let (mut d2, mut d3, mut d4) = (vec![], vec![], vec![]);
+        let source_acc = test_utils::account_info(&source_key, false, true, l0, &mut source_data, &token_program_id);
+        let dest_acc = test_utils::account_info(&dest_key, false, true, l1, &mut dest_data, &token_program_id);
+        let owner_acc = test_utils::account_info(&owner_key, true, false, l2, &mut d2, &program_id);
+        let token_program_acc = test_utils::account_info(&token_program_id, false, false, l3, &mut d3, &program_id);
+        let config_acc = test_utils::account_info(&config_key, false, false, l4, &mut config_data, &program_id);
+        let fee_vault_acc = test_utils::account_info(&fee_vault_key, false, true, l5, &mut fee_vault_data, &token_program_id);
+        let treasury_vault_acc =
+            test_utils::account_info(&treasury_vault_key, false, true, l6, &mut treasury_vault_data, &token_program_id);
+        let fee_authority_acc = test_utils::account_info(&fee_authority_key, false, false, l7, &mut d4, &program_id);
+        let mint_acc = test_utils::account_info(&mint, false, false, l8, &mut mint_data, &token_program_id);
+        let denylist_acc = test_utils::account_info(&denylist_key, false, false, l9, &mut denylist_data, &program_id);
+        bank.set_balance(&source_key, 1_000);
+
+        let accounts = [
+            source_acc,
+            dest_acc,
+            owner_acc,
+            token_program_acc.clone(),
+            mint_acc.clone(),
+            config_acc.clone(),
+            fee_vault_acc.clone(),
+            denylist_acc.clone(),
+        ];
+        TokenContract::transfer_tokens(&program_id, &accounts, 400).unwrap();
+        assert_eq!(bank.balance(&dest_key), 390);
+        assert_eq!(bank.balance(&fee_vault_key), 10);
+
+        // Once the denylist exists, paying into an account whose owner is on it fails.
+        **denylist_acc.try_borrow_mut_lamports().unwrap() = 1;
+        assert_eq!(TokenContract::transfer_tokens(&program_id, &accounts, 100), Err(GgtError::AddressDenylisted.into()));
+
+        let accounts = [fee_vault_acc, treasury_vault_acc, fee_authority_acc, token_program_acc, mint_acc];
+        TokenContract::withdraw_fees(&program_id, &accounts, 10).unwrap();
+        assert_eq!(bank.balance(&treasury_vault_key), 10);
+    }
+
+    #[test]
+    fn test_batch_transfer_pays_every_recipient_and_one_fee() {
+        let bank = test_utils::SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let mint = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        let (fee_authority_key, _) = TokenContract::fee_vault_authority(&program_id);
+        let (source_key, owner_key, fee_vault_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (alice_key, bob_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (denylist_key, mut denylist_data) = test_utils::denylist(&program_id, &[bob_key]);
+        let mut lamports = [0u64; 9];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8] = &mut lamports;
+        let mut source_data = test_utils::token_account_data(&mint, &owner_key, 1_000);
+        let mut fee_vault_data = test_utils::token_account_data(&mint, &fee_authority_key, 0);
+        let (mut alice_data, mut bob_data) =
+            (test_utils::token_account_data(&mint, &alice_key, 0), test_utils::token_account_data(&mint, &bob_key, 0));
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { decimals: 9, is_initialized: true, ..Mint::default() }, &mut mint_data).unwrap();
+        let mut config_data = vec![0u8; config::ProgramConfig::LEN];
+        config::ProgramConfig::pack(config::ProgramConfig { fee_bps: 100, is_initialized: true, ..Default::default() }, &mut config_data)
+            .unwrap();
+        let (mut d0, mut d1) = (vec![], vec![]);
+        let source_acc = test_utils::account_info(&source_key, false, true, l0, &mut source_data, &token_program_id);
+        let owner_acc = test_utils::account_info(&owner_key, true, false, l1, &mut d0, &program_id);
+        let token_program_acc = test_utils::account_info(&token_program_id, false, false, l2, &mut d1, &program_id);
+        let mint_acc = test_utils::account_info(&mint, false, false, l3, &mut mint_data, &token_program_id);
+        let config_acc = test_utils::account_info(&config_key, false, false, l4, &mut config_data, &program_id);
+        let fee_vault_acc = test_utils::account_info(&fee_vault_key, false, true, l5, &mut fee_vault_data, &token_program_id);
+        let alice_acc = test_utils::account_info(&alice_key, false, true, l6, &mut alice_data, &token_program_id);
+        let bob_acc = test_utils::account_info(&bob_key, false, true, l7, &mut bob_data, &token_program_id);
+        let denylist_acc = test_utils::account_info(&denylist_key, false, false, l8, &mut denylist_data, &program_id);
+        bank.set_balance(&source_key, 1_000);
+
+        let accounts = [source_acc, owner_acc, token_program_acc, mint_acc, config_acc, fee_vault_acc, denylist_acc.clone(), alice_acc, bob_acc];
+        assert_eq!(TokenContract::batch_transfer(&program_id, &accounts, vec![300]), Err(ProgramError::InvalidArgument));
+        TokenContract::batch_transfer(&program_id, &accounts, vec![300, 500]).unwrap();
+        assert_eq!((bank.balance(&alice_key), bank.balance(&bob_key), bank.balance(&fee_vault_key)), (300, 500, 8));
+        assert_eq!(bank.balance(&source_key), 192);
+        assert!(TokenContract::batch_transfer(&program_id, &accounts, vec![100, 100]).is_err());
+
+        // Once the denylist exists, a batch paying any listed recipient fails as a whole.
+        **denylist_acc.try_borrow_mut_lamports().unwrap() = 1;
+        assert_eq!(TokenContract::batch_transfer(&program_id, &accounts, vec![1, 1]), Err(GgtError::AddressDenylisted.into()));
+    }
+
+    #[test]
+    fn test_invoiced_transfer_rejects_a_denylisted_destination() {
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let mint = Pubkey::new_unique();
+        let (config_key, mut config_data) = test_utils::program_config(&program_id);
+        let (source_key, dest_key, owner_key, dest_owner_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (invoice_key, _) = invoice::invoice_address(&program_id, &owner_key);
+        let (denylist_key, mut denylist_data) = test_utils::denylist(&program_id, &[dest_owner_key]);
+        let mut lamports = [0u64, 0, 0, 0, 0, 0, 0, 1];
+        let [l0, l1, l2, l3, l4, l5, l6, l7] = &mut lamports;
+        let mut source_data = test_utils::token_account_data(&mint, &owner_key, 1_000);
+        let mut dest_data = test_utils::token_account_data(&mint, &dest_owner_key, 0);
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { decimals: 9, is_initialized: true, ..Mint::default() }, &mut mint_data).unwrap();
+        let (mut d0, mut d1, mut d2) = (vec![], vec![], vec![]);
+        let accounts = [
+            test_utils::account_info(&source_key, false, true, l0, &mut source_data, &token_program_id),
+            test_utils::account_info(&dest_key, false, true, l1, &mut dest_data, &token_program_id),
+            test_utils::account_info(&owner_key, true, false, l2, &mut d0, &program_id),
+            test_utils::account_info(&token_program_id, false, false, l3, &mut d1, &program_id),
+            test_utils::account_info(&mint, false, false, l4, &mut mint_data, &token_program_id),
+            test_utils::account_info(&config_key, false, false, l5, &mut config_data, &program_id),
+            test_utils::account_info(&invoice_key, false, true, l6, &mut d2, &program_id),
+            test_utils::account_info(&denylist_key, false, false, l7, &mut denylist_data, &program_id),
+        ];
+
+        assert_eq!(TokenContract::transfer_tokens_on_invoice(&program_id, &accounts, 100), Err(GgtError::AddressDenylisted.into()));
+    }
+
+    #[test]
+    fn test_delegate_signs_for_the_transfers_it_was_approved_for() {
+        let bank = test_utils::SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let mint = Pubkey::new_unique();
+        let (config_key, mut config_data) = test_utils::program_config(&program_id);
+        let (source_key, dest_key, owner_key, delegate_key, fee_vault_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (denylist_key, mut denylist_data) = test_utils::denylist(&program_id, &[]);
+        let mut lamports = [0u64; 9];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8] = &mut lamports;
+        let mut source_data = test_utils::token_account_data(&mint, &owner_key, 1_000);
+        let mut source = TokenAccount::unpack(&source_data).unwrap();
+        (source.delegate, source.delegated_amount) = (COption::Some(delegate_key), 500);
+        TokenAccount::pack(source, &mut source_data).unwrap();
+        let mut dest_data = test_utils::token_account_data(&mint, &Pubkey::new_unique(), 0);
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { decimals: 9, is_initialized: true, ..Mint::default() }, &mut mint_data).unwrap();
+        let (mut d0, mut d1, mut d2, mut d3) = (vec![], vec![], vec![], vec![]);
+        let source_acc = test_utils::account_info(&source_key, false, true, l0, &mut source_data, &token_program_id);
+        let dest_acc = test_utils::account_info(&dest_key, false, true, l1, &mut dest_data, &token_program_id);
+        let owner_acc = test_utils::account_info(&owner_key, true, false, l2, &mut d0, &program_id);
+        let token_program_acc = test_utils::account_info(&token_program_id, false, false, l3, &mut d1, &program_id);
+        let mint_acc = test_utils::account_info(&mint, false, false, l4, &mut mint_data, &token_program_id);
+        let config_acc = test_utils::account_info(&config_key, false, false, l5, &mut config_data, &program_id);
+        let fee_vault_acc = test_utils::account_info(&fee_vault_key, false, true, l6, &mut d2, &token_program_id);
+        let delegate_acc = test_utils::account_info(&delegate_key, true, false, l7, &mut d3, &program_id);
+        let denylist_acc = test_utils::account_info(&denylist_key, false, false, l8, &mut denylist_data, &program_id);
+        bank.set_balance(&source_key, 1_000);
+
+        let approve_accounts = [source_acc.clone(), delegate_acc.clone(), owner_acc.clone(), token_program_acc.clone(), mint_acc.clone()];
+        TokenContract::approve_delegate(&approve_accounts, 500).unwrap();
+        assert_eq!(
+            bank.events::<events::DelegateApproved>(),
+            vec![events::DelegateApproved { source: source_key, owner: owner_key, delegate: delegate_key, amount: 500 }]
+        );
+
+        // The owner does not sign; the transfer CPI must be authorized by the delegate.
+        let mut owner_acc = owner_acc;
+        owner_acc.is_signer = false;
+        let mut accounts =
+            [source_acc, dest_acc, owner_acc, token_program_acc, mint_acc, config_acc, fee_vault_acc, denylist_acc, delegate_acc];
+        TokenContract::transfer_tokens(&program_id, &accounts, 300).unwrap();
+        assert_eq!((bank.balance(&source_key), bank.balance(&dest_key)), (700, 300));
+        assert_eq!(bank.events::<events::TransferEvent>()[0].authority, delegate_key);
+
+        accounts[8].is_signer = false;
+        assert_eq!(TokenContract::transfer_tokens(&program_id, &accounts, 100), Err(ProgramError::MissingRequiredSignature));
+        accounts[8].is_signer = true;
+        assert_eq!(TokenContract::transfer_tokens(&program_id, &accounts, 600), Err(ProgramError::InsufficientFunds));
+    }
+
+    #[test]
+    fn test_transfer_with_ata_create_pays_the_recipients_associated_account() {
+        let bank = test_utils::SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let (ata_program_id, system_program_id) = (token_program::ASSOCIATED_TOKEN_PROGRAM_ID, solana_program::system_program::id());
+        let mint = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        let (source_key, owner_key, recipient_key, fee_vault_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (ata_key, _) = token_program::associated_token_address(&recipient_key, &mint, &token_program_id);
+        let (denylist_key, mut denylist_data) = test_utils::denylist(&program_id, &[]);
+        let mut lamports = [0u64; 11];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10] = &mut lamports;
+        let mut source_data = test_utils::token_account_data(&mint, &owner_key, 1_000);
+        let mut ata_data = test_utils::token_account_data(&mint, &recipient_key, 0);
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { decimals: 9, is_initialized: true, ..Mint::default() }, &mut mint_data).unwrap();
+        let (config_key, mut config_data) = (config_key, test_utils::program_config(&program_id).1);
+        let (mut d0, mut d1, mut d2, mut d3, mut d4, mut d5) = (vec![], vec![], vec![], vec![], vec![], vec![]);
+        let source_acc = test_utils::account_info(&source_key, false, true, l0, &mut source_data, &token_program_id);
+        let ata_acc = test_utils::account_info(&ata_key, false, true, l1, &mut ata_data, &token_program_id);
+        let owner_acc = test_utils::account_info(&owner_key, true, true, l2, &mut d0, &program_id);
+        let token_program_acc = test_utils::account_info(&token_program_id, false, false, l3, &mut d1, &program_id);
+        let mint_acc = test_utils::account_info(&mint, false, false, l4, &mut mint_data, &token_program_id);
+        let config_acc = test_utils::account_info(&config_key, false, false, l5, &mut config_data, &program_id);
+        let fee_vault_acc = test_utils::account_info(&fee_vault_key, false, true, l6, &mut d2, &token_program_id);
+        let recipient_acc = test_utils::account_info(&recipient_key, false, false, l7, &mut d3, &program_id);
+        let system_program_acc = test_utils::account_info(&system_program_id, false, false, l8, &mut d4, &program_id);
+        let ata_program_acc = test_utils::account_info(&ata_program_id, false, false, l9, &mut d5, &program_id);
+        let denylist_acc = test_utils::account_info(&denylist_key, false, false, l10, &mut denylist_data, &program_id);
+        bank.set_balance(&source_key, 1_000);
+
+        let mut accounts = [
+            source_acc,
+            ata_acc,
+            owner_acc,
+            token_program_acc,
+            mint_acc,
+            config_acc,
+            fee_vault_acc,
+            denylist_acc,
+            recipient_acc,
+            system_program_acc,
+            ata_program_acc,
+        ];
+        TokenContract::transfer_with_ata_create(&program_id, &accounts, 400).unwrap();
+        assert_eq!((bank.balance(&source_key), bank.balance(&ata_key)), (600, 400));
+
+        // Only the recipient's associated token account is accepted as the destination.
+        accounts.swap(1, 6);
+        assert_eq!(TokenContract::transfer_with_ata_create(&program_id, &accounts, 400), Err(ProgramError::InvalidSeeds));
+    }
+
+    #[test]
+    fn test_transfer_with_memo_emits_the_reference_and_records_one_receipt() {
+        let bank = test_utils::SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let system_program_id = solana_program::system_program::id();
+        let mint = Pubkey::new_unique();
+        let (config_key, mut config_data) = test_utils::program_config(&program_id);
+        let (source_key, owner_key, dest_key, fee_vault_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let reference = [7u8; 32];
+        let (receipt_key, _) = payment_receipt::payment_receipt_address(&program_id, &dest_key, &owner_key, &reference);
+        let (denylist_key, mut denylist_data) = test_utils::denylist(&program_id, &[]);
+        let mut lamports = [0, 0, 10_000_000, 0, 0, 0, 0, 0, 0, 0];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9] = &mut lamports;
+        let mut source_data = test_utils::token_account_data(&mint, &owner_key, 1_000);
+        let mut dest_data = test_utils::token_account_data(&mint, &dest_key, 0);
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { decimals: 9, is_initialized: true, ..Mint::default() }, &mut mint_data).unwrap();
+        let mut receipt_data = vec![0u8; payment_receipt::PaymentReceipt::LEN];
+        let (mut d0, mut d1, mut d2, mut d3) = (vec![], vec![], vec![], vec![]);
+        let source_acc = test_utils::account_info(&source_key, false, true, l0, &mut source_data, &token_program_id);
+        let dest_acc = test_utils::account_info(&dest_key, false, true, l1, &mut dest_data, &token_program_id);
+        let owner_acc = test_utils::account_info(&owner_key, true, true, l2, &mut d0, &system_program_id);
+        let token_program_acc = test_utils::account_info(&token_program_id, false, false, l3, &mut d1, &program_id);
+        let mint_acc = test_utils::account_info(&mint, false, false, l4, &mut mint_data, &token_program_id);
+        let config_acc = test_utils::account_info(&config_key, false, false, l5, &mut config_data, &program_id);
+        let fee_vault_acc = test_utils::account_info(&fee_vault_key, false, true, l6, &mut d2, &token_program_id);
+        let receipt_acc = test_utils::account_info(&receipt_key, false, true, l7, &mut receipt_data, &program_id);
+        let system_program_acc = test_utils::account_info(&system_program_id, false, false, l8, &mut d3, &system_program_id);
+        let denylist_acc = test_utils::account_info(&denylist_key, false, false, l9, &mut denylist_data, &program_id);
+        bank.set_balance(&source_key, 1_000);
+        bank.set_unix_timestamp(1_234);
+
+        let accounts = [
+            source_acc,
+            dest_acc,
+            owner_acc,
+            token_program_acc,
+            mint_acc,
+            config_acc,
+            fee_vault_acc,
+            denylist_acc,
+            receipt_acc.clone(),
+            system_program_acc,
+        ];
+        TokenContract::transfer_with_memo(&program_id, &accounts[..8], [1u8; 32], 100).unwrap();
+        TokenContract::transfer_with_memo(&program_id, &accounts, reference, 400).unwrap();
+        assert_eq!(bank.balance(&dest_key), 500);
+        let referenced = bank.events::<events::PaymentReferenced>();
+        assert_eq!(referenced.len(), 2);
+        assert_eq!(referenced[1], events::PaymentReferenced { reference, sender: owner_key, recipient: dest_key, amount: 400, fee: 0 });
+        let receipt = payment_receipt::PaymentReceipt::unpack(&receipt_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((receipt.sender, receipt.recipient, receipt.amount, receipt.paid_at), (owner_key, dest_key, 400, 1_234));
+
+        // A reference already receipted cannot be paid against again.
+        assert_eq!(TokenContract::transfer_with_memo(&program_id, &accounts, reference, 400), Err(ProgramError::AccountAlreadyInitialized));
+        // The receipt is keyed by the payer too, so one derived for another payer is refused.
+        let (mut other_lamports, mut other_data) = (0u64, vec![0u8; payment_receipt::PaymentReceipt::LEN]);
+        let other_key = payment_receipt::payment_receipt_address(&program_id, &dest_key, &Pubkey::new_unique(), &reference).0;
+        let mut front_run = accounts.clone();
+        front_run[8] = test_utils::account_info(&other_key, false, true, &mut other_lamports, &mut other_data, &program_id);
+        assert_eq!(TokenContract::transfer_with_memo(&program_id, &front_run, reference, 1), Err(ProgramError::InvalidSeeds));
+    }
+
+    #[test]
+    fn test_compliance_authority_freezes_accounts_while_paused() {
+        let _bank = test_utils::SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let (config_key, _) = config::config_address(&program_id);
+        let (audit_log_key, _) = config::audit_log_address(&program_id);
+        let (freeze_authority_key, _) = TokenContract::freeze_authority(&program_id);
+        let (admin_key, _) = multisig::multisig_authority_address(&program_id);
+        let (compliance_key, token_key, mint_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut lamports = [0u64; 8];
+        let [l0, l1, l2, l3, l4, l5, l6, l7] = &mut lamports;
+        let mut data = config_data(true, 0);
+        let mut audit_log_data = vec![0u8; config::AuditLog::LEN];
+        config::AuditLog::pack(config::AuditLog { entries: vec![], next_index: 0, is_initialized: true }, &mut audit_log_data).unwrap();
+        let (mut d1, mut d3, mut d4, mut d5, mut d6, mut d7) = (vec![], vec![], vec![], vec![], vec![], vec![]);
+        let config_acc = test_utils::account_info(&config_key, false, true, l0, &mut data, &program_id);
+        let admin_acc = test_utils::account_info(&admin_key, true, false, l1, &mut d1, &program_id);
+        let audit_log_acc = test_utils::account_info(&audit_log_key, false, true, l2, &mut audit_log_data, &program_id);
+        test_utils::fund_rent_exempt(&[&config_acc, &audit_log_acc]);
+        let token_acc = test_utils::account_info(&token_key, false, true, l3, &mut d3, &token_program_id);
+        let mint_acc = test_utils::account_info(&mint_key, false, false, l4, &mut d4, &token_program_id);
+        let freeze_authority_acc = test_utils::account_info(&freeze_authority_key, false, false, l5, &mut d5, &program_id);
+        let compliance_acc = test_utils::account_info(&compliance_key, true, false, l6, &mut d6, &program_id);
+        let token_program_acc = test_utils::account_info(&token_program_id, false, false, l7, &mut d7, &program_id);
+        let freeze_accounts = [token_acc, mint_acc, freeze_authority_acc, compliance_acc, config_acc.clone(), token_program_acc];
+        let freeze = [49u8, INSTRUCTION_VERSION];
+
+        assert_eq!(process_instruction(&program_id, &freeze_accounts, &freeze), Err(ProgramError::IllegalOwner));
+
+        let mut set_authority = vec![48u8, INSTRUCTION_VERSION];
+        set_authority.extend_from_slice(compliance_key.as_ref());
+        process_instruction(&program_id, &[config_acc.clone(), admin_acc, audit_log_acc.clone()], &set_authority).unwrap();
+        process_instruction(&program_id, &freeze_accounts, &freeze).unwrap();
+        process_instruction(&program_id, &freeze_accounts, &[50u8, INSTRUCTION_VERSION]).unwrap();
+
+        let log = config::AuditLog::unpack(&audit_log_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(log.entries[0].action, config::AuditAction::SetComplianceAuthority);
+    }
+
+    #[test]
+    fn test_initialize_token_with_custom_params() {
+        let _bank = test_utils::SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (token_program_id, system_program_id, metadata_program_id) =
+            (spl_token::id(), solana_program::system_program::id(), mpl_token_metadata::ID);
+        let rent_key = solana_program::sysvar::rent::id();
+        let (mint_key, authority_key) = (TokenContract::mint_address(&program_id).0, Pubkey::new_unique());
+        let (metadata_key, _) = Metadata::find_pda(&mint_key);
+        let (config_key, mut config_data) = test_utils::program_config(&program_id);
+        let mut lamports = [0u64, 1_000_000_000, 0, 0, 0, 0, 0, 0];
+        let [l0, l1, l2, l3, l4, l5, l6, l7] = &mut lamports;
+        let mut mint_data = vec![0u8; Mint::LEN];
+        let (mut d1, mut d2, mut d3, mut d4, mut d5, mut d6) = (vec![], vec![], vec![], vec![], vec![], vec![]);
+        let mint_acc = test_utils::account_info(&mint_key, false, true, l0, &mut mint_data, &token_program_id);
+        let authority_acc = test_utils::account_info(&authority_key, true, true, l1, &mut d1, &system_program_id);
+        let token_program_acc = test_utils::account_info(&token_program_id, false, false, l2, &mut d2, &program_id);
+        let metadata_acc = test_utils::account_info(&metadata_key, false, true, l3, &mut d3, &system_program_id);
+        let system_program_acc = test_utils::account_info(&system_program_id, false, false, l4, &mut d4, &program_id);
+        let rent_acc = test_utils::account_info(&rent_key, false, false, l5, &mut d5, &program_id);
+        let metadata_program_acc = test_utils::account_info(&metadata_program_id, false, false, l6, &mut d6, &program_id);
+        let config_acc = test_utils::account_info(&config_key, false, false, l7, &mut config_data, &program_id);
+        let accounts = [
+            mint_acc.clone(),
+            authority_acc.clone(),
+            token_program_acc,
+            metadata_acc,
+            mint_acc.clone(),
+            authority_acc.clone(),
+            authority_acc.clone(),
+            authority_acc,
+            system_program_acc,
+            rent_acc,
+            metadata_program_acc,
+            config_acc,
+        ];
+        let params = TokenParams { decimals: 6, name: "Gadder Gold Devnet".to_string(), symbol: "dGGT".to_string(), ..TokenParams::default() };
+        let long_symbol = TokenParams { symbol: "GGT".repeat(4), ..params.clone() };
+
+        let data = instruction::initialize_token(&program_id, &authority_key, &token_program_id, &long_symbol).data;
+        assert_eq!(process_instruction(&program_id, &accounts, &data), Err(ProgramError::InvalidInstructionData));
+
+        let data = instruction::initialize_token(&program_id, &authority_key, &token_program_id, &params).data;
+        process_instruction(&program_id, &accounts, &data).unwrap();
+        let mint = Mint::unpack(&mint_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((mint.decimals, mint.mint_authority, mint.freeze_authority), (6, COption::Some(authority_key), COption::None));
+
+        // The mint lives at one PDA, so a second launch fails instead of minting a new token.
+        assert_eq!(process_instruction(&program_id, &accounts, &data), Err(ProgramError::AccountAlreadyInitialized));
+    }
+
+    #[test]
+    fn test_update_token_metadata_requires_update_authority() {
+        let _bank = test_utils::SimulatedBank::start();
+        let (metadata_program_id, token_program_id) = (mpl_token_metadata::ID, spl_token::id());
+        let (mint_key, authority_key, other_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (metadata_key, _) = Metadata::find_pda(&mint_key);
+        let mut metadata_data = borsh::to_vec(&(
+            4u8, // Key::MetadataV1
+            authority_key,
+            mint_key,
+            "Gadder Gold",
+            "GGT",
+            "http://example.com/metadata",
+            0u16,
+            None::<u8>,
+            false,
+            true,
+            None::<u8>,
+        ))
+        .unwrap();
+        let mut lamports = [0u64; 5];
+        let [l0, l1, l2, l3, l4] = &mut lamports;
+        let (mut d1, mut d2, mut d3, mut d4) = (vec![], vec![], vec![], vec![]);
+        let metadata_acc = test_utils::account_info(&metadata_key, false, true, l0, &mut metadata_data, &metadata_program_id);
+        let mint_acc = test_utils::account_info(&mint_key, false, false, l1, &mut d1, &token_program_id);
+        let authority_acc = test_utils::account_info(&authority_key, true, false, l2, &mut d2, &metadata_program_id);
+        let other_acc = test_utils::account_info(&other_key, true, false, l3, &mut d3, &metadata_program_id);
+        let metadata_program_acc = test_utils::account_info(&metadata_program_id, false, false, l4, &mut d4, &metadata_program_id);
+        let update = |accounts: &[AccountInfo], name: &str| {
+            TokenContract::update_token_metadata(accounts, name.to_string(), "GGT".to_string(), "https://gadder.gold/ggt.json".to_string())
+        };
+
+        let impostor = [metadata_acc.clone(), mint_acc.clone(), other_acc, metadata_program_acc.clone()];
+        assert_eq!(update(&impostor, "Gadder Gold"), Err(ProgramError::IllegalOwner));
+        let other_mint = [metadata_acc.clone(), authority_acc.clone(), authority_acc.clone(), metadata_program_acc.clone()];
+        assert_eq!(update(&other_mint, "Gadder Gold"), Err(ProgramError::InvalidSeeds));
+
+        let accounts = [metadata_acc, mint_acc, authority_acc, metadata_program_acc];
+        assert_eq!(update(&accounts, &"G".repeat(33)), Err(ProgramError::InvalidInstructionData));
+        update(&accounts, "Gadder Gold").unwrap();
+    }
+}
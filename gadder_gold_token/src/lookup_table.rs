@@ -0,0 +1,213 @@
+//! Address lookup tables owned by the program. Tables are created under the
+//! lookup table authority PDA and seeded with the program's singleton PDAs and
+//! the programs it calls, so large instructions such as proposal execution
+//! and batch transfers fit in a v0 transaction. Only governance extends them.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    address_lookup_table::{instruction as lookup_table_instruction, program as lookup_table_program},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use crate::{
+    ai_contract, config, cross_chain_bridge_contract::CrossChainBridge, governance_contract, multisig, staking_contract,
+    treasury_contract::Treasury, TokenContract,
+};
+use crate::validation::{assert_pda, assert_signer};
+
+pub const LOOKUP_TABLE_AUTHORITY_SEED: &[u8] = b"lookup_table_authority";
+
+pub fn lookup_table_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LOOKUP_TABLE_AUTHORITY_SEED], program_id)
+}
+
+/// Addresses every new table starts with: singleton PDAs and called programs.
+pub fn common_addresses(program_id: &Pubkey) -> Vec<Pubkey> {
+    vec![
+        *program_id,
+        config::config_address(program_id).0,
+        config::audit_log_address(program_id).0,
+        TokenContract::fee_vault_authority(program_id).0,
+        TokenContract::freeze_authority(program_id).0,
+        Treasury::treasury_authority(program_id).0,
+        CrossChainBridge::vault_authority(program_id).0,
+        governance_contract::governance_config_address(program_id).0,
+        governance_contract::governance_authority_address(program_id).0,
+        staking_contract::staking_config_address(program_id).0,
+        ai_contract::escrow_authority(program_id).0,
+        spl_token::id(),
+        spl_token_2022::id(),
+        system_program::id(),
+    ]
+}
+
+pub struct CreateLookupTableAccounts<'a, 'info> {
+    pub lookup_table_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub admin_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub lookup_table_program_acc: &'a AccountInfo<'info>,
+    pub authority_bump: u8,
+}
+
+impl<'a, 'info> CreateLookupTableAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let lookup_table_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let admin_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+        let lookup_table_program_acc = next_account_info(account_info_iter)?;
+
+        multisig::check_admin_signer(program_id, admin_acc)?;
+        if *lookup_table_program_acc.key != lookup_table_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let authority_bump = assert_pda(authority_acc, lookup_table_authority(program_id))?;
+        Ok(Self { lookup_table_acc, authority_acc, admin_acc, system_program_acc, lookup_table_program_acc, authority_bump })
+    }
+}
+
+/// Creates a lookup table for `recent_slot` owned by the lookup table authority
+/// and seeds it with `common_addresses`. The admin pays for the table.
+pub fn create_lookup_table(program_id: &Pubkey, accounts: &[AccountInfo], recent_slot: u64) -> ProgramResult {
+    let CreateLookupTableAccounts { lookup_table_acc, authority_acc, admin_acc, system_program_acc, lookup_table_program_acc, authority_bump } =
+        CreateLookupTableAccounts::try_from(program_id, accounts)?;
+    let authority = *authority_acc.key;
+
+    let (create_ix, table) = lookup_table_instruction::create_lookup_table(authority, *admin_acc.key, recent_slot);
+    if *lookup_table_acc.key != table {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let account_infos = [
+        lookup_table_acc.clone(),
+        authority_acc.clone(),
+        admin_acc.clone(),
+        system_program_acc.clone(),
+        lookup_table_program_acc.clone(),
+    ];
+    let signer_seeds: &[&[u8]] = &[LOOKUP_TABLE_AUTHORITY_SEED, &[authority_bump]];
+    invoke_signed(&create_ix, &account_infos, &[signer_seeds])?;
+    let extend_ix = lookup_table_instruction::extend_lookup_table(table, authority, Some(*admin_acc.key), common_addresses(program_id));
+    invoke_signed(&extend_ix, &account_infos, &[signer_seeds])?;
+    msg!("Created lookup table {}", table);
+    Ok(())
+}
+
+/// The governance signer and the program config it is checked against sit
+/// between `authority_acc` and `payer_acc`; neither is kept.
+pub struct ExtendLookupTableAccounts<'a, 'info> {
+    pub lookup_table_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub payer_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub lookup_table_program_acc: &'a AccountInfo<'info>,
+    pub authority_bump: u8,
+}
+
+impl<'a, 'info> ExtendLookupTableAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let lookup_table_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let governance_acc = next_account_info(account_info_iter)?;
+        let program_config_acc = next_account_info(account_info_iter)?;
+        let payer_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+        let lookup_table_program_acc = next_account_info(account_info_iter)?;
+
+        governance_contract::check_governance_signer(program_id, program_config_acc, governance_acc)?;
+        assert_signer(payer_acc)?;
+        if *lookup_table_program_acc.key != lookup_table_program::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if lookup_table_acc.owner != &lookup_table_program::id() {
+            return Err(ProgramError::IllegalOwner);
+        }
+        let authority_bump = assert_pda(authority_acc, lookup_table_authority(program_id))?;
+        Ok(Self { lookup_table_acc, authority_acc, payer_acc, system_program_acc, lookup_table_program_acc, authority_bump })
+    }
+}
+
+/// Appends `addresses` (mints, vaults, pools) to one of the program's lookup tables.
+pub fn extend_lookup_table(program_id: &Pubkey, accounts: &[AccountInfo], addresses: Vec<Pubkey>) -> ProgramResult {
+    let ExtendLookupTableAccounts { lookup_table_acc, authority_acc, payer_acc, system_program_acc, lookup_table_program_acc, authority_bump } =
+        ExtendLookupTableAccounts::try_from(program_id, accounts)?;
+    let authority = *authority_acc.key;
+    if addresses.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let count = addresses.len();
+    let ix = lookup_table_instruction::extend_lookup_table(*lookup_table_acc.key, authority, Some(*payer_acc.key), addresses);
+    invoke_signed(
+        &ix,
+        &[
+            lookup_table_acc.clone(),
+            authority_acc.clone(),
+            payer_acc.clone(),
+            system_program_acc.clone(),
+            lookup_table_program_acc.clone(),
+        ],
+        &[&[LOOKUP_TABLE_AUTHORITY_SEED, &[authority_bump]]],
+    )?;
+    msg!("Extended lookup table {} with {} addresses", lookup_table_acc.key, count);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{account_info, SimulatedBank};
+    use crate::ADMIN_PUBKEY;
+
+    #[test]
+    fn test_create_lookup_table_checks_authority_and_address() {
+        let _bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (authority, _) = lookup_table_authority(&program_id);
+        let (table, _) = lookup_table_instruction::derive_lookup_table_address(&authority, 42);
+        let (alt_program_id, system_program_id) = (lookup_table_program::id(), system_program::id());
+        let mut lamports = [0u64; 7];
+        let [l0, l1, l2, l3, l4, l5, l6] = &mut lamports;
+        let (mut d0, mut d1, mut d2, mut d3, mut d4, mut d5) = (vec![], vec![], vec![], vec![], vec![], vec![]);
+        let (program_config_key, mut program_config_data) = crate::test_utils::program_config(&program_id);
+        let (rogue_key, (admin_key, _)) = (Pubkey::new_unique(), multisig::multisig_authority_address(&program_id));
+        let accounts = [
+            account_info(&table, false, true, l0, &mut d0, &system_program_id),
+            account_info(&authority, false, false, l1, &mut d1, &program_id),
+            account_info(&admin_key, true, true, l2, &mut d2, &system_program_id),
+            account_info(&system_program_id, false, false, l3, &mut d3, &system_program_id),
+            account_info(&alt_program_id, false, false, l4, &mut d4, &system_program_id),
+        ];
+        let rogue_acc = account_info(&rogue_key, true, true, l5, &mut d5, &system_program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, l6, &mut program_config_data, &program_id);
+
+        create_lookup_table(&program_id, &accounts, 42).unwrap();
+        assert_eq!(create_lookup_table(&program_id, &accounts, 43), Err(ProgramError::InvalidSeeds));
+        let mut not_admin = accounts.clone();
+        not_admin[2] = rogue_acc.clone();
+        assert_eq!(create_lookup_table(&program_id, &not_admin, 42), Err(ProgramError::IllegalOwner));
+        // The configured admin key alone no longer stands in for the multisig.
+        let (mut admin_lamports, mut admin_data) = (0u64, vec![]);
+        not_admin[2] = account_info(&ADMIN_PUBKEY, true, true, &mut admin_lamports, &mut admin_data, &system_program_id);
+        assert_eq!(create_lookup_table(&program_id, &not_admin, 42), Err(ProgramError::IllegalOwner));
+
+        let common = common_addresses(&program_id);
+        let mut unique = common.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), common.len());
+
+        let extend_accounts =
+            [accounts[0].clone(), accounts[1].clone(), rogue_acc, program_config_acc, accounts[2].clone(), accounts[3].clone(), accounts[4].clone()];
+        assert_eq!(
+            extend_lookup_table(&program_id, &extend_accounts, vec![Pubkey::new_unique()]),
+            Err(ProgramError::IllegalOwner)
+        );
+    }
+}
@@ -0,0 +1,633 @@
+//! Fast bridge path for approved market makers. A market maker keeps GGT in a
+//! collateral vault held by the bridge vault authority and can have wrapped
+//! GGT minted on a destination chain straight from a `FastMint` event, without
+//! the guardian wait of a regular lock. Outstanding fast mints may never exceed
+//! what the collateral covers at the maker's collateral ratio. Once guardians
+//! attest that the destination chain minted a transfer, anyone can submit the
+//! attestation to reconcile it: the collateral for it moves into the bridge
+//! vault, or is burned in burn/mint mode, and the exposure is released.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke_signed, set_return_data},
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use crate::cross_chain_bridge_contract::{check_bridge_admin, BridgeMode, CrossChainBridge, BRIDGE_VAULT_SEED};
+use crate::{
+    error::GgtError,
+    events, token_program,
+};
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const MARKET_MAKER_SEED: &[u8] = b"market_maker";
+pub const FAST_MINT_SEED: &[u8] = b"fast_mint";
+/// Prefix of the payload guardians sign to reconcile a fast mint.
+pub const RECONCILE_DOMAIN: &[u8] = b"ggt-fast-mint-reconcile";
+
+pub fn market_maker_address(program_id: &Pubkey, maker: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MARKET_MAKER_SEED, maker.as_ref()], program_id)
+}
+
+/// Record of the fast mint `maker` opened at bridge `sequence`.
+pub fn fast_mint_address(program_id: &Pubkey, maker: &Pubkey, sequence: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FAST_MINT_SEED, maker.as_ref(), &sequence.to_le_bytes()], program_id)
+}
+
+/// What guardians sign once the destination chain has minted `transfer_id`.
+pub fn reconcile_message(transfer_id: &[u8; 32]) -> Vec<u8> {
+    [RECONCILE_DOMAIN, &transfer_id[..]].concat()
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct MarketMaker {
+    pub maker: Pubkey,
+    /// Token account owned by the bridge vault authority holding the maker's collateral.
+    pub collateral_vault: Pubkey,
+    /// Collateral required per unit outstanding, in basis points; at least 100%.
+    pub collateral_ratio_bps: u16,
+    /// Fast-minted on destination chains and not yet reconciled.
+    pub outstanding: u64,
+    pub approved: bool,
+    pub is_initialized: bool,
+}
+
+impl MarketMaker {
+    pub fn required_collateral(&self, outstanding: u64) -> u64 {
+        let required = outstanding as u128 * self.collateral_ratio_bps as u128 / 10_000;
+        required.min(u64::MAX as u128) as u64
+    }
+}
+
+impl Sealed for MarketMaker {}
+
+impl IsInitialized for MarketMaker {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for MarketMaker {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [151, 123, 40, 58, 68, 211, 104, 56];
+}
+
+impl Pack for MarketMaker {
+    const LEN: usize = 85; // discriminator (8) + version (1) + Pubkey (32) * 2 + u16 (2) + u64 (8) + bool (1) * 2
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..32].copy_from_slice(self.maker.as_ref());
+        dst[32..64].copy_from_slice(self.collateral_vault.as_ref());
+        dst[64..66].copy_from_slice(&self.collateral_ratio_bps.to_le_bytes());
+        dst[66..74].copy_from_slice(&self.outstanding.to_le_bytes());
+        dst[74] = self.approved as u8;
+        dst[75] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(MarketMaker {
+            maker: Pubkey::new_from_array(src[0..32].try_into().unwrap()),
+            collateral_vault: Pubkey::new_from_array(src[32..64].try_into().unwrap()),
+            collateral_ratio_bps: u16::from_le_bytes(src[64..66].try_into().unwrap()),
+            outstanding: u64::from_le_bytes(src[66..74].try_into().unwrap()),
+            approved: src[74] != 0,
+            is_initialized: src[75] != 0,
+        })
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct FastMint {
+    pub maker: Pubkey,
+    pub sequence: u64,
+    pub target_chain_id: u16,
+    pub amount: u64,
+    pub reconciled: bool,
+    pub is_initialized: bool,
+}
+
+impl Sealed for FastMint {}
+
+impl IsInitialized for FastMint {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for FastMint {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [32, 111, 208, 43, 213, 248, 74, 155];
+}
+
+impl Pack for FastMint {
+    const LEN: usize = 61; // discriminator (8) + version (1) + Pubkey (32) + u64 (8) + u16 (2) + u64 (8) + bool (1) * 2
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..32].copy_from_slice(self.maker.as_ref());
+        dst[32..40].copy_from_slice(&self.sequence.to_le_bytes());
+        dst[40..42].copy_from_slice(&self.target_chain_id.to_le_bytes());
+        dst[42..50].copy_from_slice(&self.amount.to_le_bytes());
+        dst[50] = self.reconciled as u8;
+        dst[51] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(FastMint {
+            maker: Pubkey::new_from_array(src[0..32].try_into().unwrap()),
+            sequence: u64::from_le_bytes(src[32..40].try_into().unwrap()),
+            target_chain_id: u16::from_le_bytes(src[40..42].try_into().unwrap()),
+            amount: u64::from_le_bytes(src[42..50].try_into().unwrap()),
+            reconciled: src[50] != 0,
+            is_initialized: src[51] != 0,
+        })
+    }
+}
+
+fn load_market_maker(program_id: &Pubkey, market_maker_acc: &AccountInfo, maker: &Pubkey) -> Result<MarketMaker, ProgramError> {
+    assert_owned_by(market_maker_acc, program_id)?;
+    assert_pda(market_maker_acc, market_maker_address(program_id, maker))?;
+    MarketMaker::unpack(&market_maker_acc.try_borrow_data()?)
+}
+
+fn collateral_balance(market_maker: &MarketMaker, collateral_vault_acc: &AccountInfo) -> Result<u64, ProgramError> {
+    if *collateral_vault_acc.key != market_maker.collateral_vault {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(token_program::unpack_account(collateral_vault_acc)?.amount)
+}
+
+/// The bridge admin signer follows `market_maker_acc` and pays for it.
+pub struct SetMarketMakerAccounts<'a, 'info> {
+    pub market_maker_acc: &'a AccountInfo<'info>,
+    pub admin_acc: &'a AccountInfo<'info>,
+    pub maker_acc: &'a AccountInfo<'info>,
+    pub collateral_vault_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub market_maker_bump: u8,
+}
+
+impl<'a, 'info> SetMarketMakerAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let market_maker_acc = next_account_info(account_info_iter)?;
+        let admin_acc = next_account_info(account_info_iter)?;
+        let maker_acc = next_account_info(account_info_iter)?;
+        let collateral_vault_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        check_bridge_admin(program_id, admin_acc)?;
+        CrossChainBridge::check_vault(program_id, collateral_vault_acc, mint_acc)?;
+        let market_maker_bump = assert_pda(market_maker_acc, market_maker_address(program_id, maker_acc.key))?;
+        Ok(Self { market_maker_acc, admin_acc, maker_acc, collateral_vault_acc, system_program_acc, market_maker_bump })
+    }
+}
+
+pub struct WithdrawCollateralAccounts<'a, 'info> {
+    pub market_maker_acc: &'a AccountInfo<'info>,
+    pub maker_acc: &'a AccountInfo<'info>,
+    pub collateral_vault_acc: &'a AccountInfo<'info>,
+    pub destination_acc: &'a AccountInfo<'info>,
+    pub vault_authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub vault_authority_bump: u8,
+}
+
+impl<'a, 'info> WithdrawCollateralAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let market_maker_acc = next_account_info(account_info_iter)?;
+        let maker_acc = next_account_info(account_info_iter)?;
+        let collateral_vault_acc = next_account_info(account_info_iter)?;
+        let destination_acc = next_account_info(account_info_iter)?;
+        let vault_authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(maker_acc)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        let vault_authority_bump = assert_pda(vault_authority_acc, CrossChainBridge::vault_authority(program_id))?;
+        Ok(Self {
+            market_maker_acc,
+            maker_acc,
+            collateral_vault_acc,
+            destination_acc,
+            vault_authority_acc,
+            token_program_acc,
+            mint_acc,
+            vault_authority_bump,
+        })
+    }
+}
+
+pub struct FastMintAccounts<'a, 'info> {
+    pub market_maker_acc: &'a AccountInfo<'info>,
+    pub maker_acc: &'a AccountInfo<'info>,
+    pub collateral_vault_acc: &'a AccountInfo<'info>,
+    pub bridge_config_acc: &'a AccountInfo<'info>,
+    pub registry_acc: &'a AccountInfo<'info>,
+    pub record_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> FastMintAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            market_maker_acc: next_account_info(account_info_iter)?,
+            maker_acc: next_account_info(account_info_iter)?,
+            collateral_vault_acc: next_account_info(account_info_iter)?,
+            bridge_config_acc: next_account_info(account_info_iter)?,
+            registry_acc: next_account_info(account_info_iter)?,
+            record_acc: next_account_info(account_info_iter)?,
+            system_program_acc: next_account_info(account_info_iter)?,
+        };
+        assert_signer(ctx.maker_acc)?;
+        assert_owned_by(ctx.bridge_config_acc, program_id)?;
+        CrossChainBridge::check_registry(program_id, ctx.registry_acc)?;
+        Ok(ctx)
+    }
+}
+
+pub struct ReconcileFastMintAccounts<'a, 'info> {
+    pub market_maker_acc: &'a AccountInfo<'info>,
+    pub record_acc: &'a AccountInfo<'info>,
+    pub collateral_vault_acc: &'a AccountInfo<'info>,
+    pub bridge_vault_acc: &'a AccountInfo<'info>,
+    pub vault_authority_acc: &'a AccountInfo<'info>,
+    pub bridge_config_acc: &'a AccountInfo<'info>,
+    pub instructions_sysvar_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub vault_authority_bump: u8,
+}
+
+impl<'a, 'info> ReconcileFastMintAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let market_maker_acc = next_account_info(account_info_iter)?;
+        let record_acc = next_account_info(account_info_iter)?;
+        let collateral_vault_acc = next_account_info(account_info_iter)?;
+        let bridge_vault_acc = next_account_info(account_info_iter)?;
+        let vault_authority_acc = next_account_info(account_info_iter)?;
+        let bridge_config_acc = next_account_info(account_info_iter)?;
+        let instructions_sysvar_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        let vault_authority_bump = assert_pda(vault_authority_acc, CrossChainBridge::vault_authority(program_id))?;
+        assert_owned_by(record_acc, program_id)?;
+        assert_owned_by(bridge_config_acc, program_id)?;
+        Ok(Self {
+            market_maker_acc,
+            record_acc,
+            collateral_vault_acc,
+            bridge_vault_acc,
+            vault_authority_acc,
+            bridge_config_acc,
+            instructions_sysvar_acc,
+            token_program_acc,
+            mint_acc,
+            vault_authority_bump,
+        })
+    }
+}
+
+/// Approves `maker` for fast mints at `collateral_ratio_bps`, or suspends it
+/// with `approved = false`. The first call creates the maker's account and
+/// fixes its collateral vault.
+pub fn set_market_maker(program_id: &Pubkey, accounts: &[AccountInfo], collateral_ratio_bps: u16, approved: bool) -> ProgramResult {
+    let SetMarketMakerAccounts { market_maker_acc, admin_acc, maker_acc, collateral_vault_acc, system_program_acc, market_maker_bump } =
+        SetMarketMakerAccounts::try_from(program_id, accounts)?;
+    if collateral_ratio_bps < 10_000 {
+        msg!("Collateral ratio must be at least 100%");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut market_maker = if market_maker_acc.lamports() == 0 {
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_acc.key,
+                market_maker_acc.key,
+                Rent::get()?.minimum_balance(MarketMaker::LEN),
+                MarketMaker::LEN as u64,
+                program_id,
+            ),
+            &[admin_acc.clone(), market_maker_acc.clone(), system_program_acc.clone()],
+            &[&[MARKET_MAKER_SEED, maker_acc.key.as_ref(), &[market_maker_bump]]],
+        )?;
+        MarketMaker {
+            maker: *maker_acc.key,
+            collateral_vault: *collateral_vault_acc.key,
+            collateral_ratio_bps,
+            outstanding: 0,
+            approved,
+            is_initialized: true,
+        }
+    } else {
+        let market_maker = load_market_maker(program_id, market_maker_acc, maker_acc.key)?;
+        if market_maker.collateral_vault != *collateral_vault_acc.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        market_maker
+    };
+    market_maker.collateral_ratio_bps = collateral_ratio_bps;
+    market_maker.approved = approved;
+    pack_state(&market_maker, market_maker_acc)?;
+    msg!("Market maker {} approved: {} at {} bps", maker_acc.key, approved, collateral_ratio_bps);
+    Ok(())
+}
+
+/// Returns collateral to the maker as long as what stays behind still covers
+/// the outstanding fast mints.
+pub fn withdraw_collateral(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let WithdrawCollateralAccounts {
+        market_maker_acc,
+        maker_acc,
+        collateral_vault_acc,
+        destination_acc,
+        vault_authority_acc,
+        token_program_acc,
+        mint_acc,
+        vault_authority_bump,
+    } = WithdrawCollateralAccounts::try_from(program_id, accounts)?;
+    let market_maker = load_market_maker(program_id, market_maker_acc, maker_acc.key)?;
+    let collateral = collateral_balance(&market_maker, collateral_vault_acc)?;
+    let remaining = collateral.checked_sub(amount).ok_or(ProgramError::InsufficientFunds)?;
+    if remaining < market_maker.required_collateral(market_maker.outstanding) {
+        msg!("Withdrawal would leave {} against {} outstanding", remaining, market_maker.outstanding);
+        return Err(GgtError::CollateralRatioBreached.into());
+    }
+
+    token_program::transfer(
+        token_program_acc,
+        collateral_vault_acc,
+        mint_acc,
+        destination_acc,
+        vault_authority_acc,
+        amount,
+        &[&[BRIDGE_VAULT_SEED, &[vault_authority_bump]]],
+    )?;
+    msg!("Withdrew {} collateral", amount);
+    Ok(())
+}
+
+/// Opens a fast mint of `amount` to `destination` on `target_chain_id`. Takes
+/// the next bridge sequence, so the transfer ID is unique across regular locks
+/// and fast mints, and returns it as return data. The maker pays for the record.
+pub fn fast_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    target_chain_id: u16,
+    destination: Vec<u8>,
+) -> ProgramResult {
+    let FastMintAccounts { market_maker_acc, maker_acc, collateral_vault_acc, bridge_config_acc, registry_acc, record_acc, system_program_acc } =
+        FastMintAccounts::try_from(program_id, accounts)?;
+    let mut market_maker = load_market_maker(program_id, market_maker_acc, maker_acc.key)?;
+    if !market_maker.approved {
+        return Err(GgtError::MarketMakerNotApproved.into());
+    }
+    let chain = CrossChainBridge::registered_chain(registry_acc, target_chain_id)?;
+    if !chain.address_format.accepts(&destination) {
+        msg!("Destination does not match the {:?} address format of {}", chain.address_format, chain.name);
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let outstanding = market_maker.outstanding.checked_add(amount).ok_or(GgtError::MathOverflow)?;
+    let collateral = collateral_balance(&market_maker, collateral_vault_acc)?;
+    if amount == 0 || collateral < market_maker.required_collateral(outstanding) {
+        msg!("Collateral {} does not cover {} outstanding", collateral, outstanding);
+        return Err(GgtError::CollateralRatioBreached.into());
+    }
+
+    let mut config = CrossChainBridge::load_config(bridge_config_acc)?;
+    let sequence = config.outbound_sequence;
+    config.outbound_sequence = sequence.checked_add(1).ok_or(GgtError::MathOverflow)?;
+    pack_state(&config, bridge_config_acc)?;
+
+    let record_bump = assert_pda(record_acc, fast_mint_address(program_id, maker_acc.key, sequence))?;
+    invoke_signed(
+        &system_instruction::create_account(
+            maker_acc.key,
+            record_acc.key,
+            Rent::get()?.minimum_balance(FastMint::LEN),
+            FastMint::LEN as u64,
+            program_id,
+        ),
+        &[maker_acc.clone(), record_acc.clone(), system_program_acc.clone()],
+        &[&[FAST_MINT_SEED, maker_acc.key.as_ref(), &sequence.to_le_bytes(), &[record_bump]]],
+    )?;
+    let record = FastMint { maker: *maker_acc.key, sequence, target_chain_id, amount, reconciled: false, is_initialized: true };
+    pack_state(&record, record_acc)?;
+    market_maker.outstanding = outstanding;
+    pack_state(&market_maker, market_maker_acc)?;
+
+    let collateral_mint = token_program::unpack_account(collateral_vault_acc)?.mint;
+    let transfer_id = CrossChainBridge::transfer_id(maker_acc.key, &collateral_mint, sequence, target_chain_id, amount);
+    events::emit(&events::FastMint { transfer_id, maker: *maker_acc.key, sequence, target_chain_id, destination, amount });
+    set_return_data(&transfer_id);
+    msg!("Fast mint of {} to {} (sequence {})", amount, chain.name, sequence);
+    Ok(())
+}
+
+/// Settles a fast mint once guardians have signed `reconcile_message` for its
+/// transfer ID. Anyone may submit the attestation.
+pub fn reconcile_fast_mint(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let ReconcileFastMintAccounts {
+        market_maker_acc,
+        record_acc,
+        collateral_vault_acc,
+        bridge_vault_acc,
+        vault_authority_acc,
+        bridge_config_acc,
+        instructions_sysvar_acc,
+        token_program_acc,
+        mint_acc,
+        vault_authority_bump,
+    } = ReconcileFastMintAccounts::try_from(program_id, accounts)?;
+    let mut record = FastMint::unpack(&record_acc.try_borrow_data()?)?;
+    assert_pda(record_acc, fast_mint_address(program_id, &record.maker, record.sequence))?;
+    if record.reconciled {
+        return Err(GgtError::BridgeMessageAlreadyProcessed.into());
+    }
+    let mut market_maker = load_market_maker(program_id, market_maker_acc, &record.maker)?;
+    collateral_balance(&market_maker, collateral_vault_acc)?;
+
+    let config = CrossChainBridge::load_config(bridge_config_acc)?;
+    let collateral_mint = token_program::unpack_account(collateral_vault_acc)?.mint;
+    let transfer_id = CrossChainBridge::transfer_id(&record.maker, &collateral_mint, record.sequence, record.target_chain_id, record.amount);
+    CrossChainBridge::verify_guardian_signatures(&config, instructions_sysvar_acc, &reconcile_message(&transfer_id))?;
+
+    let signer_seeds: &[&[u8]] = &[BRIDGE_VAULT_SEED, &[vault_authority_bump]];
+    match config.mode {
+        BridgeMode::LockRelease => {
+            CrossChainBridge::check_vault(program_id, bridge_vault_acc, mint_acc)?;
+            token_program::transfer(
+                token_program_acc,
+                collateral_vault_acc,
+                mint_acc,
+                bridge_vault_acc,
+                vault_authority_acc,
+                record.amount,
+                &[signer_seeds],
+            )?;
+        }
+        BridgeMode::BurnMint => {
+            token_program::burn(token_program_acc, collateral_vault_acc, mint_acc, vault_authority_acc, record.amount, &[signer_seeds])?
+        }
+    }
+
+    market_maker.outstanding = market_maker.outstanding.saturating_sub(record.amount);
+    record.reconciled = true;
+    let (maker, amount) = (record.maker, record.amount);
+    pack_state(&record, record_acc)?;
+    pack_state(&market_maker, market_maker_acc)?;
+    events::emit(&events::FastMintReconciled { transfer_id, maker, amount });
+    msg!("Reconciled fast mint of {} for {}", amount, maker);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::new_ed25519_instruction;
+    use crate::cross_chain_bridge_contract::{AddressFormat, BridgeConfig, ChainInfo, ChainRegistry, SignatureScheme};
+    use crate::multisig::multisig_authority_address;
+    use crate::test_utils::{account_info, fund_rent_exempt, instructions_sysvar_data, token_account_data, SimulatedBank};
+    use solana_program::instruction::Instruction;
+    use spl_token::state::Mint;
+
+    #[test]
+    fn test_fast_mint_is_capped_by_collateral_and_reconciled_by_guardians() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (token_program_id, system_program_id) = (spl_token::id(), Pubkey::default());
+        let (vault_authority, _) = CrossChainBridge::vault_authority(&program_id);
+        let (registry_key, _) = CrossChainBridge::chain_registry_address(&program_id);
+        let (maker_key, mint_key, collateral_key, bridge_vault_key, config_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (market_maker_key, _) = market_maker_address(&program_id, &maker_key);
+        let (record_key, _) = fast_mint_address(&program_id, &maker_key, 0);
+        let guardian = Pubkey::new_unique();
+
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { decimals: 9, is_initialized: true, ..Mint::default() }, &mut mint_data).unwrap();
+        let mut collateral_data = token_account_data(&mint_key, &vault_authority, 1_500);
+        let mut bridge_vault_data = token_account_data(&mint_key, &vault_authority, 0);
+        let mut config_data = vec![0u8; BridgeConfig::LEN];
+        let config = BridgeConfig {
+            guardians: vec![guardian],
+            guardian_threshold: 1,
+            evm_guardians: Vec::new(),
+            evm_guardian_threshold: 0,
+            outbound_sequence: 0,
+            mode: BridgeMode::LockRelease,
+            daily_outflow_cap: 0,
+            chain_outflow_caps: Vec::new(),
+            transfer_timeout_seconds: 0,
+            paused: false,
+            is_initialized: true,
+        };
+        BridgeConfig::pack(config, &mut config_data).unwrap();
+        let mut registry_data = vec![0u8; ChainRegistry::LEN];
+        let ethereum = ChainInfo {
+            chain_id: 2,
+            name: "Ethereum".to_string(),
+            address_format: AddressFormat::Evm,
+            decimals: 8,
+            signature_scheme: SignatureScheme::Ed25519,
+            fee_flat: 0,
+            fee_bps: 0,
+            wormhole_chain_id: 0,
+            wormhole_emitter: [0; 32],
+        };
+        ChainRegistry::pack(ChainRegistry { chains: vec![ethereum], is_initialized: true }, &mut registry_data).unwrap();
+        let (mut market_maker_data, mut record_data) = (vec![0u8; MarketMaker::LEN], vec![0u8; FastMint::LEN]);
+
+        let transfer_id = CrossChainBridge::transfer_id(&maker_key, &mint_key, 0, 2, 1_000);
+        let transaction = vec![
+            new_ed25519_instruction(&guardian, &[0u8; 64], &reconcile_message(&transfer_id)),
+            Instruction { program_id, accounts: vec![], data: vec![] },
+        ];
+        let mut sysvar_data = instructions_sysvar_data(&transaction, 1);
+        let sysvar_key = solana_instructions_sysvar::ID;
+
+        let (admin_key, _) = multisig_authority_address(&program_id);
+        let mut lamports = [0u64, 10_000_000, 10_000_000, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10, l11, l12] = &mut lamports;
+        let (mut d1, mut d2, mut d8, mut d9, mut d10) = (vec![], vec![], vec![], vec![], vec![]);
+        let market_maker_acc = account_info(&market_maker_key, false, true, l0, &mut market_maker_data, &program_id);
+        let admin_acc = account_info(&admin_key, true, true, l1, &mut d1, &system_program_id);
+        let maker_acc = account_info(&maker_key, true, true, l2, &mut d2, &system_program_id);
+        let collateral_acc = account_info(&collateral_key, false, true, l3, &mut collateral_data, &token_program_id);
+        let mint_acc = account_info(&mint_key, false, true, l4, &mut mint_data, &token_program_id);
+        let config_acc = account_info(&config_key, false, true, l5, &mut config_data, &program_id);
+        let registry_acc = account_info(&registry_key, false, false, l6, &mut registry_data, &program_id);
+        let record_acc = account_info(&record_key, false, true, l7, &mut record_data, &program_id);
+        fund_rent_exempt(&[&config_acc]);
+        let system_program_acc = account_info(&system_program_id, false, false, l8, &mut d8, &system_program_id);
+        let bridge_vault_acc = account_info(&bridge_vault_key, false, true, l9, &mut bridge_vault_data, &token_program_id);
+        let authority_acc = account_info(&vault_authority, false, false, l10, &mut d9, &program_id);
+        let sysvar_acc = account_info(&sysvar_key, false, false, l11, &mut sysvar_data, &program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, l12, &mut d10, &program_id);
+        bank.set_balance(&collateral_key, 1_500);
+
+        let approve = [
+            market_maker_acc.clone(),
+            admin_acc,
+            maker_acc.clone(),
+            collateral_acc.clone(),
+            mint_acc.clone(),
+            system_program_acc.clone(),
+        ];
+        set_market_maker(&program_id, &approve, 15_000, true).unwrap();
+
+        let mint_accounts = [
+            market_maker_acc.clone(),
+            maker_acc,
+            collateral_acc.clone(),
+            config_acc.clone(),
+            registry_acc,
+            record_acc.clone(),
+            system_program_acc,
+        ];
+        // 1_500 collateral at 150% covers at most 1_000 outstanding.
+        assert_eq!(
+            fast_mint(&program_id, &mint_accounts, 1_001, 2, vec![0x11; 20]),
+            Err(GgtError::CollateralRatioBreached.into())
+        );
+        fast_mint(&program_id, &mint_accounts, 1_000, 2, vec![0x11; 20]).unwrap();
+        assert_eq!(bank.return_data(), transfer_id.to_vec());
+        assert_eq!(MarketMaker::unpack(&market_maker_acc.try_borrow_data().unwrap()).unwrap().outstanding, 1_000);
+
+        let reconcile_accounts = [
+            market_maker_acc.clone(),
+            record_acc.clone(),
+            collateral_acc,
+            bridge_vault_acc,
+            authority_acc,
+            config_acc,
+            sysvar_acc,
+            token_program_acc,
+            mint_acc,
+        ];
+        reconcile_fast_mint(&program_id, &reconcile_accounts).unwrap();
+        assert_eq!((bank.balance(&collateral_key), bank.balance(&bridge_vault_key)), (500, 1_000));
+        assert_eq!(MarketMaker::unpack(&market_maker_acc.try_borrow_data().unwrap()).unwrap().outstanding, 0);
+        assert_eq!(
+            reconcile_fast_mint(&program_id, &reconcile_accounts),
+            Err(GgtError::BridgeMessageAlreadyProcessed.into())
+        );
+    }
+}
@@ -0,0 +1,509 @@
+//! End-of-life migration of GGT from the legacy spl-token mint to a Token-2022
+//! mint. Governance starts it once: the legacy mint authority is revoked so
+//! its supply can only shrink, and the program deploys the Token-2022 mint at
+//! a PDA with the migration authority PDA as its mint authority. Holders then
+//! call `migrate_tokens` to burn legacy GGT and receive new GGT 1:1 until the
+//! final cutoff. The migration account tracks what moved in each epoch.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use spl_token_2022::{
+    extension::{transfer_fee::instruction::initialize_transfer_fee_config, ExtensionType},
+    instruction::initialize_mint2,
+    state::Mint,
+};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use crate::{error::GgtError, events, governance_contract, token_program};
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer, assert_token_account_mint};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const MIGRATION_SEED: &[u8] = b"migration";
+pub const MIGRATION_MINT_SEED: &[u8] = b"migration_mint";
+pub const MIGRATION_AUTHORITY_SEED: &[u8] = b"migration_authority";
+/// Length of the windows migration volume is reported over.
+pub const MIGRATION_EPOCH_SECONDS: i64 = 86_400;
+
+/// Address of the singleton `MigrationState` account.
+pub fn migration_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MIGRATION_SEED], program_id)
+}
+
+/// Address the Token-2022 GGT mint is deployed at.
+pub fn migration_mint_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MIGRATION_MINT_SEED], program_id)
+}
+
+/// Mint authority of the Token-2022 mint.
+pub fn migration_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MIGRATION_AUTHORITY_SEED], program_id)
+}
+
+/// Extensions the Token-2022 mint is deployed with. A `transfer_fee_bps` of zero
+/// leaves out the transfer-fee extension; otherwise governance holds its
+/// config and withdraw authorities.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Token2022MintParams {
+    pub transfer_fee_bps: u16,
+    pub maximum_fee: u64,
+}
+
+impl Token2022MintParams {
+    fn extensions(&self) -> Vec<ExtensionType> {
+        if self.transfer_fee_bps > 0 {
+            vec![ExtensionType::TransferFeeConfig]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct MigrationState {
+    pub legacy_mint: Pubkey,
+    pub new_mint: Pubkey,
+    pub started_at: i64,
+    /// No migrations are accepted from this timestamp on.
+    pub cutoff_at: i64,
+    /// Epoch of the last migration, counted from `started_at`.
+    pub epoch: u64,
+    pub epoch_migrated: u64,
+    pub total_migrated: u64,
+    pub is_initialized: bool,
+}
+
+impl MigrationState {
+    /// Adds `amount` to the stats for the epoch `now` falls in. Returns the
+    /// epoch it was counted in.
+    pub fn record(&mut self, now: i64, amount: u64) -> Result<u64, ProgramError> {
+        let epoch = (now.saturating_sub(self.started_at) / MIGRATION_EPOCH_SECONDS) as u64;
+        if epoch != self.epoch {
+            msg!("Migration epoch {} closed with {} migrated", self.epoch, self.epoch_migrated);
+            self.epoch = epoch;
+            self.epoch_migrated = 0;
+        }
+        self.epoch_migrated = self.epoch_migrated.checked_add(amount).ok_or(GgtError::MathOverflow)?;
+        self.total_migrated = self.total_migrated.checked_add(amount).ok_or(GgtError::MathOverflow)?;
+        Ok(epoch)
+    }
+}
+
+impl Sealed for MigrationState {}
+
+impl IsInitialized for MigrationState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for MigrationState {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [95, 146, 135, 64, 145, 25, 197, 115];
+}
+
+impl Pack for MigrationState {
+    const LEN: usize = 114; // discriminator (8) + version (1) + Pubkey (32) * 2 + i64 (8) * 2 + u64 (8) * 3 + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        let mut cursor = 0;
+        dst[cursor..cursor + 32].copy_from_slice(self.legacy_mint.as_ref());
+        cursor += 32;
+        dst[cursor..cursor + 32].copy_from_slice(self.new_mint.as_ref());
+        cursor += 32;
+        dst[cursor..cursor + 8].copy_from_slice(&self.started_at.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.cutoff_at.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.epoch.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.epoch_migrated.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.total_migrated.to_le_bytes());
+        cursor += 8;
+        dst[cursor] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let mut cursor = 0;
+        let legacy_mint = Pubkey::new_from_array(src[cursor..cursor + 32].try_into().unwrap());
+        cursor += 32;
+        let new_mint = Pubkey::new_from_array(src[cursor..cursor + 32].try_into().unwrap());
+        cursor += 32;
+        let started_at = i64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let cutoff_at = i64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let epoch = u64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let epoch_migrated = u64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let total_migrated = u64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let is_initialized = src[cursor] != 0;
+        Ok(MigrationState {
+            legacy_mint,
+            new_mint,
+            started_at,
+            cutoff_at,
+            epoch,
+            epoch_migrated,
+            total_migrated,
+            is_initialized,
+        })
+    }
+}
+
+/// The governance signer follows `migration_acc`, then the program config it is
+/// checked against; neither is kept.
+pub struct StartMigrationAccounts<'a, 'info> {
+    pub migration_acc: &'a AccountInfo<'info>,
+    pub payer_acc: &'a AccountInfo<'info>,
+    pub legacy_mint_acc: &'a AccountInfo<'info>,
+    pub legacy_mint_authority_acc: &'a AccountInfo<'info>,
+    pub new_mint_acc: &'a AccountInfo<'info>,
+    pub migration_authority_acc: &'a AccountInfo<'info>,
+    pub legacy_token_program_acc: &'a AccountInfo<'info>,
+    pub token_2022_program_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub migration_bump: u8,
+    pub new_mint_bump: u8,
+}
+
+impl<'a, 'info> StartMigrationAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let migration_acc = next_account_info(account_info_iter)?;
+        let governance_acc = next_account_info(account_info_iter)?;
+        let program_config_acc = next_account_info(account_info_iter)?;
+        let payer_acc = next_account_info(account_info_iter)?;
+        let legacy_mint_acc = next_account_info(account_info_iter)?;
+        let legacy_mint_authority_acc = next_account_info(account_info_iter)?;
+        let new_mint_acc = next_account_info(account_info_iter)?;
+        let migration_authority_acc = next_account_info(account_info_iter)?;
+        let legacy_token_program_acc = next_account_info(account_info_iter)?;
+        let token_2022_program_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        governance_contract::check_governance_signer(program_id, program_config_acc, governance_acc)?;
+        assert_signer(payer_acc)?;
+        assert_signer(legacy_mint_authority_acc)?;
+        token_program::check_token_program(legacy_token_program_acc, legacy_mint_acc)?;
+        if *token_2022_program_acc.key != spl_token_2022::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let migration_bump = assert_pda(migration_acc, migration_address(program_id))?;
+        let new_mint_bump = assert_pda(new_mint_acc, migration_mint_address(program_id))?;
+        assert_pda(migration_authority_acc, migration_authority(program_id))?;
+        Ok(Self {
+            migration_acc,
+            payer_acc,
+            legacy_mint_acc,
+            legacy_mint_authority_acc,
+            new_mint_acc,
+            migration_authority_acc,
+            legacy_token_program_acc,
+            token_2022_program_acc,
+            system_program_acc,
+            migration_bump,
+            new_mint_bump,
+        })
+    }
+}
+
+pub struct MigrateTokensAccounts<'a, 'info> {
+    pub migration_acc: &'a AccountInfo<'info>,
+    pub holder_acc: &'a AccountInfo<'info>,
+    pub legacy_token_acc: &'a AccountInfo<'info>,
+    pub legacy_mint_acc: &'a AccountInfo<'info>,
+    pub new_token_acc: &'a AccountInfo<'info>,
+    pub new_mint_acc: &'a AccountInfo<'info>,
+    pub migration_authority_acc: &'a AccountInfo<'info>,
+    pub legacy_token_program_acc: &'a AccountInfo<'info>,
+    pub token_2022_program_acc: &'a AccountInfo<'info>,
+    pub migration_authority_bump: u8,
+}
+
+impl<'a, 'info> MigrateTokensAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let migration_acc = next_account_info(account_info_iter)?;
+        let holder_acc = next_account_info(account_info_iter)?;
+        let legacy_token_acc = next_account_info(account_info_iter)?;
+        let legacy_mint_acc = next_account_info(account_info_iter)?;
+        let new_token_acc = next_account_info(account_info_iter)?;
+        let new_mint_acc = next_account_info(account_info_iter)?;
+        let migration_authority_acc = next_account_info(account_info_iter)?;
+        let legacy_token_program_acc = next_account_info(account_info_iter)?;
+        let token_2022_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(holder_acc)?;
+        assert_owned_by(migration_acc, program_id)?;
+        assert_pda(migration_acc, migration_address(program_id))?;
+        let migration_authority_bump = assert_pda(migration_authority_acc, migration_authority(program_id))?;
+        token_program::check_token_program(legacy_token_program_acc, legacy_mint_acc)?;
+        token_program::check_token_program(token_2022_program_acc, new_mint_acc)?;
+        assert_token_account_mint(legacy_token_acc, legacy_mint_acc.key)?;
+        assert_token_account_mint(new_token_acc, new_mint_acc.key)?;
+        Ok(Self {
+            migration_acc,
+            holder_acc,
+            legacy_token_acc,
+            legacy_mint_acc,
+            new_token_acc,
+            new_mint_acc,
+            migration_authority_acc,
+            legacy_token_program_acc,
+            token_2022_program_acc,
+            migration_authority_bump,
+        })
+    }
+}
+
+/// Starts the migration. Revokes the legacy mint authority, which must sign,
+/// deploys the Token-2022 mint with the legacy decimals and freeze authority,
+/// and opens migrations until `cutoff_at`. Runs once, from governance.
+pub fn start_migration(program_id: &Pubkey, accounts: &[AccountInfo], cutoff_at: i64, params: Token2022MintParams) -> ProgramResult {
+    let StartMigrationAccounts {
+        migration_acc,
+        payer_acc,
+        legacy_mint_acc,
+        legacy_mint_authority_acc,
+        new_mint_acc,
+        migration_authority_acc,
+        legacy_token_program_acc,
+        token_2022_program_acc,
+        system_program_acc,
+        migration_bump,
+        new_mint_bump,
+    } = StartMigrationAccounts::try_from(program_id, accounts)?;
+    if migration_acc.lamports() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if cutoff_at <= now || params.transfer_fee_bps > 10_000 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let legacy_mint = token_program::unpack_mint(legacy_mint_acc)?;
+    let rent = Rent::get()?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_acc.key,
+            migration_acc.key,
+            rent.minimum_balance(MigrationState::LEN),
+            MigrationState::LEN as u64,
+            program_id,
+        ),
+        &[payer_acc.clone(), migration_acc.clone(), system_program_acc.clone()],
+        &[&[MIGRATION_SEED, &[migration_bump]]],
+    )?;
+
+    let mint_len = ExtensionType::try_calculate_account_len::<Mint>(&params.extensions())?;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_acc.key,
+            new_mint_acc.key,
+            rent.minimum_balance(mint_len),
+            mint_len as u64,
+            token_2022_program_acc.key,
+        ),
+        &[payer_acc.clone(), new_mint_acc.clone(), system_program_acc.clone()],
+        &[&[MIGRATION_MINT_SEED, &[new_mint_bump]]],
+    )?;
+    if params.transfer_fee_bps > 0 {
+        let governance_authority = governance_contract::governance_authority_address(program_id).0;
+        let ix = initialize_transfer_fee_config(
+            token_2022_program_acc.key,
+            new_mint_acc.key,
+            Some(&governance_authority),
+            Some(&governance_authority),
+            params.transfer_fee_bps,
+            params.maximum_fee,
+        )?;
+        invoke(&ix, &[new_mint_acc.clone(), token_2022_program_acc.clone()])?;
+    }
+    let ix = initialize_mint2(
+        token_2022_program_acc.key,
+        new_mint_acc.key,
+        migration_authority_acc.key,
+        Option::<Pubkey>::from(legacy_mint.freeze_authority).as_ref(),
+        legacy_mint.decimals,
+    )?;
+    invoke(&ix, &[new_mint_acc.clone(), token_2022_program_acc.clone()])?;
+
+    token_program::set_mint_authority(legacy_token_program_acc, legacy_mint_acc, legacy_mint_authority_acc, None, &[])?;
+
+    let state = MigrationState {
+        legacy_mint: *legacy_mint_acc.key,
+        new_mint: *new_mint_acc.key,
+        started_at: now,
+        cutoff_at,
+        epoch: 0,
+        epoch_migrated: 0,
+        total_migrated: 0,
+        is_initialized: true,
+    };
+    pack_state(&state, migration_acc)?;
+    msg!("Migration to Token-2022 mint {} open until {}", new_mint_acc.key, cutoff_at);
+    Ok(())
+}
+
+/// Burns `amount` legacy GGT from the holder and mints the same amount of the
+/// Token-2022 GGT to them.
+pub fn migrate_tokens(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let MigrateTokensAccounts {
+        migration_acc,
+        holder_acc,
+        legacy_token_acc,
+        legacy_mint_acc,
+        new_token_acc,
+        new_mint_acc,
+        migration_authority_acc,
+        legacy_token_program_acc,
+        token_2022_program_acc,
+        migration_authority_bump,
+    } = MigrateTokensAccounts::try_from(program_id, accounts)?;
+    let mut state = MigrationState::unpack(&migration_acc.try_borrow_data()?)?;
+    if state.legacy_mint != *legacy_mint_acc.key || state.new_mint != *new_mint_acc.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if now >= state.cutoff_at {
+        msg!("Migration closed at {}", state.cutoff_at);
+        return Err(GgtError::MigrationClosed.into());
+    }
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let epoch = state.record(now, amount)?;
+    pack_state(&state, migration_acc)?;
+    token_program::burn(legacy_token_program_acc, legacy_token_acc, legacy_mint_acc, holder_acc, amount, &[])?;
+    token_program::mint_to(
+        token_2022_program_acc,
+        new_mint_acc,
+        new_token_acc,
+        migration_authority_acc,
+        amount,
+        &[&[MIGRATION_AUTHORITY_SEED, &[migration_authority_bump]]],
+    )?;
+    events::emit(&events::TokensMigrated {
+        holder: *holder_acc.key,
+        amount,
+        epoch,
+        epoch_migrated: state.epoch_migrated,
+        total_migrated: state.total_migrated,
+    });
+    msg!("Migrated {} GGT to Token-2022", amount);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{account_info, token_account_data, SimulatedBank};
+    use solana_program::program_option::COption;
+
+    #[test]
+    fn test_migration_swaps_one_to_one_until_cutoff() {
+        let bank = SimulatedBank::start();
+        bank.set_unix_timestamp(1_000);
+        let program_id = Pubkey::new_unique();
+        let (spl_token_id, token_2022_id, system_program_id) = (spl_token::id(), spl_token_2022::id(), Pubkey::default());
+        let (migration_key, _) = migration_address(&program_id);
+        let (new_mint_key, _) = migration_mint_address(&program_id);
+        let (authority_key, _) = migration_authority(&program_id);
+        let (governance_key, _) = governance_contract::governance_authority_address(&program_id);
+        let (legacy_mint_key, legacy_authority_key, holder_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (legacy_token_key, new_token_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let mint_data = |mint_authority: Pubkey| {
+            let mut data = vec![0u8; spl_token::state::Mint::LEN];
+            let mint = spl_token::state::Mint { mint_authority: COption::Some(mint_authority), decimals: 9, is_initialized: true, ..Default::default() };
+            spl_token::state::Mint::pack(mint, &mut data).unwrap();
+            data
+        };
+        let (mut legacy_mint_data, mut new_mint_data) = (mint_data(legacy_authority_key), mint_data(authority_key));
+        let mut migration_data = vec![0u8; MigrationState::LEN];
+        let mut legacy_token_data = token_account_data(&legacy_mint_key, &holder_key, 0);
+        let mut new_token_data = token_account_data(&new_mint_key, &holder_key, 0);
+        let (program_config_key, mut program_config_data) = crate::test_utils::program_config(&program_id);
+        let mut lamports = [0u64; 14];
+        lamports[2] = 100_000_000;
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10, l11, l12, l13] = &mut lamports;
+        let (mut d1, mut d2, mut d4, mut d6, mut d7, mut d8, mut d9, mut d10) =
+            (vec![], vec![], vec![], vec![], vec![], vec![], vec![], vec![]);
+        let migration_acc = account_info(&migration_key, false, true, l0, &mut migration_data, &program_id);
+        let governance_acc = account_info(&governance_key, true, false, l1, &mut d1, &system_program_id);
+        let payer_acc = account_info(&holder_key, true, true, l2, &mut d2, &system_program_id);
+        let legacy_mint_acc = account_info(&legacy_mint_key, false, true, l3, &mut legacy_mint_data, &spl_token_id);
+        let legacy_authority_acc = account_info(&legacy_authority_key, true, false, l4, &mut d4, &system_program_id);
+        let new_mint_acc = account_info(&new_mint_key, false, true, l5, &mut new_mint_data, &token_2022_id);
+        let authority_acc = account_info(&authority_key, false, false, l6, &mut d6, &program_id);
+        let spl_token_acc = account_info(&spl_token_id, false, false, l7, &mut d7, &system_program_id);
+        let token_2022_acc = account_info(&token_2022_id, false, false, l8, &mut d8, &system_program_id);
+        let system_program_acc = account_info(&system_program_id, false, false, l9, &mut d9, &system_program_id);
+        let legacy_token_acc = account_info(&legacy_token_key, false, true, l10, &mut legacy_token_data, &spl_token_id);
+        let new_token_acc = account_info(&new_token_key, false, true, l11, &mut new_token_data, &token_2022_id);
+        let rogue_acc = account_info(&legacy_token_key, true, false, l12, &mut d10, &system_program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, l13, &mut program_config_data, &program_id);
+
+        let params = Token2022MintParams { transfer_fee_bps: 50, maximum_fee: 1_000 };
+        let mut start_accounts = vec![
+            migration_acc.clone(),
+            governance_acc,
+            program_config_acc,
+            payer_acc.clone(),
+            legacy_mint_acc.clone(),
+            legacy_authority_acc,
+            new_mint_acc.clone(),
+            authority_acc.clone(),
+            spl_token_acc.clone(),
+            token_2022_acc.clone(),
+            system_program_acc,
+        ];
+        assert_eq!(start_migration(&program_id, &start_accounts, 500, params), Err(ProgramError::InvalidArgument));
+        start_migration(&program_id, &start_accounts, 1_000 + 3 * MIGRATION_EPOCH_SECONDS, params).unwrap();
+        assert_eq!(
+            start_migration(&program_id, &start_accounts, 1_000 + 3 * MIGRATION_EPOCH_SECONDS, params),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+        start_accounts[1] = rogue_acc;
+        assert_eq!(start_migration(&program_id, &start_accounts, 5_000, params), Err(ProgramError::IllegalOwner));
+
+        bank.set_balance(&legacy_token_key, 1_000);
+        let migrate_accounts = [
+            migration_acc.clone(),
+            payer_acc,
+            legacy_token_acc,
+            legacy_mint_acc,
+            new_token_acc,
+            new_mint_acc,
+            authority_acc,
+            spl_token_acc,
+            token_2022_acc,
+        ];
+        migrate_tokens(&program_id, &migrate_accounts, 300).unwrap();
+        bank.set_unix_timestamp(1_000 + MIGRATION_EPOCH_SECONDS + 1);
+        migrate_tokens(&program_id, &migrate_accounts, 200).unwrap();
+        assert_eq!(bank.balance(&legacy_token_key), 500);
+        assert_eq!(bank.balance(&new_token_key), 500);
+        let state = MigrationState::unpack(&migration_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((state.epoch, state.epoch_migrated, state.total_migrated), (1, 200, 500));
+        assert_eq!(bank.events::<events::TokensMigrated>().len(), 2);
+
+        bank.set_unix_timestamp(1_000 + 3 * MIGRATION_EPOCH_SECONDS);
+        assert_eq!(migrate_tokens(&program_id, &migrate_accounts, 100), Err(GgtError::MigrationClosed.into()));
+    }
+}
@@ -0,0 +1,569 @@
+//! M-of-n admin multisig. Admin-gated instructions (bridge admin, pausing)
+//! only accept the multisig authority PDA, which signs nothing but
+//! admin actions that enough owners approved. The deployer admin key only
+//! bootstraps the multisig, once; after that only an executed admin action
+//! can change its owners or threshold.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use crate::{
+    config::{self, AuthorityRole},
+    error::GgtError,
+    governance_contract::ProposalInstruction,
+};
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const MULTISIG_SEED: &[u8] = b"multisig";
+pub const MULTISIG_AUTHORITY_SEED: &[u8] = b"multisig_authority";
+pub const ADMIN_ACTION_SEED: &[u8] = b"admin_action";
+pub const MAX_MULTISIG_OWNERS: usize = 10;
+/// Space reserved in an admin action for its Borsh-encoded instruction.
+pub const MAX_ACTION_INSTRUCTION_LEN: usize = 512;
+
+/// Address of the singleton `Multisig` account.
+pub fn multisig_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MULTISIG_SEED], program_id)
+}
+
+/// PDA that signs executed admin actions; admin-gated instructions accept no
+/// other admin.
+pub fn multisig_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MULTISIG_AUTHORITY_SEED], program_id)
+}
+
+/// Address of the admin action proposed with multisig nonce `nonce`.
+pub fn admin_action_address(program_id: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ADMIN_ACTION_SEED, &nonce.to_le_bytes()], program_id)
+}
+
+/// Admin-gated instructions take the multisig authority PDA, so they only run
+/// as part of an executed admin action.
+pub(crate) fn check_admin_signer(program_id: &Pubkey, admin_acc: &AccountInfo) -> ProgramResult {
+    if *admin_acc.key != multisig_authority_address(program_id).0 {
+        return Err(ProgramError::IllegalOwner);
+    }
+    assert_signer(admin_acc)
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct Multisig {
+    pub owners: Vec<Pubkey>,
+    /// Approvals an admin action needs before it can execute.
+    pub threshold: u8,
+    /// Nonce the next proposed admin action is created at.
+    pub nonce: u64,
+    /// `nonce` when the current owners were set. Actions proposed before it
+    /// were approved by the previous owners and can no longer run.
+    pub owners_set_at: u64,
+    pub is_initialized: bool,
+}
+
+impl Multisig {
+    fn owner_index(&self, owner: &Pubkey) -> Result<usize, ProgramError> {
+        self.owners.iter().position(|o| o == owner).ok_or(ProgramError::IllegalOwner)
+    }
+
+    fn check_current(&self, action: &AdminAction) -> ProgramResult {
+        if action.nonce < self.owners_set_at {
+            msg!("Admin action {} was proposed before the owners changed at {}", action.nonce, self.owners_set_at);
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+}
+
+impl Sealed for Multisig {}
+
+impl IsInitialized for Multisig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for Multisig {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [224, 116, 121, 186, 68, 161, 79, 236];
+}
+
+impl Pack for Multisig {
+    const LEN: usize = 348; // discriminator (8) + version (1) + count (1) + Pubkey (32) * MAX_MULTISIG_OWNERS + u8 (1) + u64 (8) * 2 + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0] = self.owners.len() as u8;
+        for slot in 0..MAX_MULTISIG_OWNERS {
+            let at = 1 + slot * 32;
+            match self.owners.get(slot) {
+                Some(owner) => dst[at..at + 32].copy_from_slice(owner.as_ref()),
+                None => dst[at..at + 32].fill(0),
+            }
+        }
+        dst[321] = self.threshold;
+        dst[322..330].copy_from_slice(&self.nonce.to_le_bytes());
+        dst[330..338].copy_from_slice(&self.owners_set_at.to_le_bytes());
+        dst[338] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let count = src[0] as usize;
+        if count > MAX_MULTISIG_OWNERS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let owners = (0..count).map(|slot| Pubkey::new_from_array(src[1 + slot * 32..33 + slot * 32].try_into().unwrap())).collect();
+        Ok(Multisig {
+            owners,
+            threshold: src[321],
+            nonce: u64::from_le_bytes(src[322..330].try_into().unwrap()),
+            owners_set_at: u64::from_le_bytes(src[330..338].try_into().unwrap()),
+            is_initialized: src[338] != 0,
+        })
+    }
+}
+
+/// An instruction the multisig authority signs once `threshold` owners approved it.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct AdminAction {
+    pub nonce: u64,
+    pub proposer: Pubkey,
+    /// Bit `i` is set once `Multisig::owners[i]` approved.
+    pub approvals: u16,
+    pub executed: bool,
+    pub is_initialized: bool,
+    pub instruction: ProposalInstruction,
+}
+
+impl Sealed for AdminAction {}
+
+impl IsInitialized for AdminAction {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for AdminAction {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [21, 121, 247, 106, 40, 20, 114, 122];
+}
+
+impl Pack for AdminAction {
+    const LEN: usize = 53 + MAX_ACTION_INSTRUCTION_LEN; // discriminator (8) + version (1) + u64 (8) + Pubkey (32) + u16 (2) + bool (1) * 2 + instruction
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..8].copy_from_slice(&self.nonce.to_le_bytes());
+        dst[8..40].copy_from_slice(self.proposer.as_ref());
+        dst[40..42].copy_from_slice(&self.approvals.to_le_bytes());
+        dst[42] = self.executed as u8;
+        dst[43] = self.is_initialized as u8;
+        let instruction_dst = &mut dst[44..];
+        instruction_dst.fill(0);
+        borsh::to_writer(instruction_dst, &self.instruction).expect("admin action exceeds MAX_ACTION_INSTRUCTION_LEN");
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let instruction = borsh::BorshDeserialize::deserialize(&mut &src[44..]).map_err(|_| ProgramError::InvalidAccountData)?;
+        Ok(AdminAction {
+            nonce: u64::from_le_bytes(src[0..8].try_into().unwrap()),
+            proposer: Pubkey::new_from_array(src[8..40].try_into().unwrap()),
+            approvals: u16::from_le_bytes(src[40..42].try_into().unwrap()),
+            executed: src[42] != 0,
+            is_initialized: src[43] != 0,
+            instruction,
+        })
+    }
+}
+
+fn load_multisig(program_id: &Pubkey, multisig_acc: &AccountInfo) -> Result<Multisig, ProgramError> {
+    assert_pda(multisig_acc, multisig_address(program_id))?;
+    assert_owned_by(multisig_acc, program_id)?;
+    Multisig::unpack(&multisig_acc.try_borrow_data()?)
+}
+
+/// Accounts for `initialize_multisig`: the multisig PDA, the deployer admin
+/// paying for it, and the system program.
+pub struct InitializeMultisigAccounts<'a, 'info> {
+    pub multisig_acc: &'a AccountInfo<'info>,
+    pub admin_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub multisig_bump: u8,
+}
+
+impl<'a, 'info> InitializeMultisigAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let multisig_acc = next_account_info(account_info_iter)?;
+        let admin_acc = next_account_info(account_info_iter)?;
+        let program_config_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        config::check_authority(program_id, program_config_acc, &[AuthorityRole::Admin], admin_acc)?;
+        let multisig_bump = assert_pda(multisig_acc, multisig_address(program_id))?;
+        Ok(Self { multisig_acc, admin_acc, system_program_acc, multisig_bump })
+    }
+}
+
+/// Accounts for `propose_admin_action`: the multisig, the action PDA at the
+/// multisig's nonce, the proposing owner paying for it, and the system program.
+pub struct ProposeAdminActionAccounts<'a, 'info> {
+    pub multisig_acc: &'a AccountInfo<'info>,
+    pub action_acc: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ProposeAdminActionAccounts<'a, 'info> {
+    pub fn try_from(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            multisig_acc: next_account_info(account_info_iter)?,
+            action_acc: next_account_info(account_info_iter)?,
+            owner_acc: next_account_info(account_info_iter)?,
+            system_program_acc: next_account_info(account_info_iter)?,
+        };
+        assert_signer(ctx.owner_acc)?;
+        Ok(ctx)
+    }
+}
+
+pub struct ApproveAdminActionAccounts<'a, 'info> {
+    pub multisig_acc: &'a AccountInfo<'info>,
+    pub action_acc: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ApproveAdminActionAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            multisig_acc: next_account_info(account_info_iter)?,
+            action_acc: next_account_info(account_info_iter)?,
+            owner_acc: next_account_info(account_info_iter)?,
+        };
+        assert_signer(ctx.owner_acc)?;
+        assert_owned_by(ctx.action_acc, program_id)?;
+        Ok(ctx)
+    }
+}
+
+/// Accounts for `set_multisig_owners`: the multisig and the multisig authority
+/// PDA, which signs only when executing an approved admin action.
+pub struct SetMultisigOwnersAccounts<'a, 'info> {
+    pub multisig_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> SetMultisigOwnersAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self { multisig_acc: next_account_info(account_info_iter)?, authority_acc: next_account_info(account_info_iter)? };
+        check_admin_signer(program_id, ctx.authority_acc)?;
+        Ok(ctx)
+    }
+}
+
+/// Accounts for `execute_admin_action`: the multisig, the action and the
+/// multisig authority PDA, followed by every account the action references.
+pub struct ExecuteAdminActionAccounts<'a, 'info> {
+    pub multisig_acc: &'a AccountInfo<'info>,
+    pub action_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub remaining_accs: &'a [AccountInfo<'info>],
+    pub authority_bump: u8,
+}
+
+impl<'a, 'info> ExecuteAdminActionAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let multisig_acc = next_account_info(account_info_iter)?;
+        let action_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let authority_bump = assert_pda(authority_acc, multisig_authority_address(program_id))?;
+        assert_owned_by(action_acc, program_id)?;
+        Ok(Self { multisig_acc, action_acc, authority_acc, remaining_accs: account_info_iter.as_slice(), authority_bump })
+    }
+}
+
+/// Creates the multisig with `owners`, `threshold` of whom must approve each
+/// admin action.
+pub fn initialize_multisig(program_id: &Pubkey, accounts: &[AccountInfo], owners: Vec<Pubkey>, threshold: u8) -> ProgramResult {
+    let InitializeMultisigAccounts { multisig_acc, admin_acc, system_program_acc, multisig_bump } =
+        InitializeMultisigAccounts::try_from(program_id, accounts)?;
+    if multisig_acc.lamports() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    check_owners(&owners, threshold)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            admin_acc.key,
+            multisig_acc.key,
+            Rent::get()?.minimum_balance(Multisig::LEN),
+            Multisig::LEN as u64,
+            program_id,
+        ),
+        &[admin_acc.clone(), multisig_acc.clone(), system_program_acc.clone()],
+        &[&[MULTISIG_SEED, &[multisig_bump]]],
+    )?;
+    let owner_count = owners.len();
+    pack_state(&Multisig { owners, threshold, nonce: 0, owners_set_at: 0, is_initialized: true }, multisig_acc)?;
+    msg!("Multisig initialized: {} of {}", threshold, owner_count);
+    Ok(())
+}
+
+/// Owners must be distinct and fit the account, and `threshold` must be
+/// between one and their number.
+fn check_owners(owners: &[Pubkey], threshold: u8) -> ProgramResult {
+    let distinct = owners.iter().enumerate().all(|(i, owner)| !owners[..i].contains(owner));
+    if owners.len() > MAX_MULTISIG_OWNERS || !distinct || threshold == 0 || threshold as usize > owners.len() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Replaces the multisig's owners and threshold. Only the multisig authority
+/// can sign this, so the change is itself an admin action the current owners
+/// approved. Actions still pending from before the change lapse, since their
+/// approvals index the old owner list.
+pub fn set_multisig_owners(program_id: &Pubkey, accounts: &[AccountInfo], owners: Vec<Pubkey>, threshold: u8) -> ProgramResult {
+    let SetMultisigOwnersAccounts { multisig_acc, authority_acc: _ } = SetMultisigOwnersAccounts::try_from(program_id, accounts)?;
+    let mut multisig = load_multisig(program_id, multisig_acc)?;
+    check_owners(&owners, threshold)?;
+
+    let owner_count = owners.len();
+    multisig.owners = owners;
+    multisig.threshold = threshold;
+    multisig.owners_set_at = multisig.nonce;
+    pack_state(&multisig, multisig_acc)?;
+    msg!("Multisig owners set: {} of {}", threshold, owner_count);
+    Ok(())
+}
+
+/// Proposes `instruction` as the next admin action. The proposing owner's
+/// approval is recorded with it.
+pub fn propose_admin_action(program_id: &Pubkey, accounts: &[AccountInfo], instruction: ProposalInstruction) -> ProgramResult {
+    let ProposeAdminActionAccounts { multisig_acc, action_acc, owner_acc, system_program_acc } = ProposeAdminActionAccounts::try_from(accounts)?;
+    let mut multisig = load_multisig(program_id, multisig_acc)?;
+    let owner_index = multisig.owner_index(owner_acc.key)?;
+    let nonce = multisig.nonce;
+    let action_bump = assert_pda(action_acc, admin_action_address(program_id, nonce))?;
+    let action = AdminAction {
+        nonce,
+        proposer: *owner_acc.key,
+        approvals: 1 << owner_index,
+        executed: false,
+        is_initialized: true,
+        instruction,
+    };
+    if borsh::to_vec(&action.instruction)?.len() > MAX_ACTION_INSTRUCTION_LEN {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            owner_acc.key,
+            action_acc.key,
+            Rent::get()?.minimum_balance(AdminAction::LEN),
+            AdminAction::LEN as u64,
+            program_id,
+        ),
+        &[owner_acc.clone(), action_acc.clone(), system_program_acc.clone()],
+        &[&[ADMIN_ACTION_SEED, &nonce.to_le_bytes(), &[action_bump]]],
+    )?;
+    pack_state(&action, action_acc)?;
+    multisig.nonce = nonce.checked_add(1).ok_or(GgtError::MathOverflow)?;
+    pack_state(&multisig, multisig_acc)?;
+    msg!("Proposed admin action {}", nonce);
+    Ok(())
+}
+
+/// Records the signing owner's approval of a pending admin action.
+pub fn approve_admin_action(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let ApproveAdminActionAccounts { multisig_acc, action_acc, owner_acc } = ApproveAdminActionAccounts::try_from(program_id, accounts)?;
+    let multisig = load_multisig(program_id, multisig_acc)?;
+    let bit = 1u16 << multisig.owner_index(owner_acc.key)?;
+    let mut action = AdminAction::unpack(&action_acc.try_borrow_data()?)?;
+    multisig.check_current(&action)?;
+    if action.executed {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if action.approvals & bit != 0 {
+        return Err(GgtError::AlreadyVoted.into());
+    }
+    action.approvals |= bit;
+    pack_state(&action, action_acc)?;
+    msg!("Approved admin action by {}", owner_acc.key);
+    Ok(())
+}
+
+/// Invokes an admin action approved by at least `threshold` owners, signed by
+/// the multisig authority. Anyone may execute it, once.
+pub fn execute_admin_action(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let ExecuteAdminActionAccounts { multisig_acc, action_acc, authority_acc, remaining_accs, authority_bump } =
+        ExecuteAdminActionAccounts::try_from(program_id, accounts)?;
+    let multisig = load_multisig(program_id, multisig_acc)?;
+    let mut action = AdminAction::unpack(&action_acc.try_borrow_data()?)?;
+    multisig.check_current(&action)?;
+    if action.executed {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if action.approvals.count_ones() < multisig.threshold as u32 {
+        msg!("Admin action {} has {} of {} approvals", action.nonce, action.approvals.count_ones(), multisig.threshold);
+        return Err(GgtError::ApprovalThresholdNotMet.into());
+    }
+    action.executed = true;
+    pack_state(&action.clone(), action_acc)?;
+
+    let mut account_infos = vec![authority_acc.clone()];
+    account_infos.extend(remaining_accs.iter().cloned());
+    invoke_signed(&Instruction::from(&action.instruction), &account_infos, &[&[MULTISIG_AUTHORITY_SEED, &[authority_bump]]])?;
+    msg!("Executed admin action {}", action.nonce);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::governance_contract::ProposalAccountMeta;
+    use crate::test_utils::{account_info, fund_rent_exempt, program_config, SimulatedBank};
+    use crate::ADMIN_PUBKEY;
+
+    #[test]
+    fn test_admin_action_executes_after_threshold_approvals() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (token_program_id, system_program_id) = (spl_token::id(), Pubkey::default());
+        let (multisig_key, _) = multisig_address(&program_id);
+        let (authority_key, _) = multisig_authority_address(&program_id);
+        let (action_key, _) = admin_action_address(&program_id, 0);
+        let owners = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let (vault_key, recipient_key, stranger_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let transfer = spl_token::instruction::transfer(&token_program_id, &vault_key, &recipient_key, &authority_key, &[], 250).unwrap();
+        let instruction = ProposalInstruction {
+            program_id: transfer.program_id,
+            accounts: transfer
+                .accounts
+                .iter()
+                .map(|meta| ProposalAccountMeta { pubkey: meta.pubkey, is_signer: meta.is_signer, is_writable: meta.is_writable })
+                .collect(),
+            data: transfer.data.clone(),
+        };
+
+        let (program_config_key, mut program_config_data) = program_config(&program_id);
+        let mut lamports = [0u64, 0, 10_000_000, 10_000_000, 0, 0, 0, 0, 0, 0, 0, 0];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10, l11] = &mut lamports;
+        let (mut multisig_data, mut action_data) = (vec![0u8; Multisig::LEN], vec![0u8; AdminAction::LEN]);
+        let (mut d2, mut d3, mut d4, mut d5, mut d6, mut d7, mut d8, mut d9, mut d10) =
+            (vec![], vec![], vec![], vec![], vec![], vec![], vec![], vec![], vec![]);
+        let multisig_acc = account_info(&multisig_key, false, true, l0, &mut multisig_data, &program_id);
+        let action_acc = account_info(&action_key, false, true, l1, &mut action_data, &program_id);
+        let admin_acc = account_info(&ADMIN_PUBKEY, true, true, l2, &mut d2, &system_program_id);
+        let owner_accs = [
+            account_info(&owners[0], true, true, l3, &mut d3, &system_program_id),
+            account_info(&owners[1], true, false, l4, &mut d4, &system_program_id),
+        ];
+        let stranger_acc = account_info(&stranger_key, true, false, l5, &mut d5, &system_program_id);
+        let system_program_acc = account_info(&system_program_id, false, false, l6, &mut d6, &system_program_id);
+        let authority_acc = account_info(&authority_key, false, false, l7, &mut d7, &program_id);
+        let vault_acc = account_info(&vault_key, false, true, l8, &mut d8, &token_program_id);
+        let recipient_acc = account_info(&recipient_key, false, true, l9, &mut d9, &token_program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, l10, &mut d10, &program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, l11, &mut program_config_data, &program_id);
+        bank.set_balance(&vault_key, 1_000);
+
+        let init_accounts = [multisig_acc.clone(), admin_acc, program_config_acc, system_program_acc.clone()];
+        assert_eq!(initialize_multisig(&program_id, &init_accounts, owners.to_vec(), 4), Err(ProgramError::InvalidArgument));
+        initialize_multisig(&program_id, &init_accounts, owners.to_vec(), 2).unwrap();
+
+        let propose_accounts = [multisig_acc.clone(), action_acc.clone(), owner_accs[0].clone(), system_program_acc];
+        propose_admin_action(&program_id, &propose_accounts, instruction.clone()).unwrap();
+        assert_eq!(Multisig::unpack(&multisig_acc.try_borrow_data().unwrap()).unwrap().nonce, 1);
+
+        let execute_accounts = [multisig_acc.clone(), action_acc.clone(), authority_acc, vault_acc, recipient_acc, token_program_acc];
+        assert_eq!(execute_admin_action(&program_id, &execute_accounts), Err(GgtError::ApprovalThresholdNotMet.into()));
+
+        let approve_accounts = [multisig_acc.clone(), action_acc.clone()];
+        assert_eq!(
+            approve_admin_action(&program_id, &[approve_accounts.as_slice(), &[stranger_acc]].concat()),
+            Err(ProgramError::IllegalOwner)
+        );
+        assert_eq!(
+            approve_admin_action(&program_id, &[approve_accounts.as_slice(), &[owner_accs[0].clone()]].concat()),
+            Err(GgtError::AlreadyVoted.into())
+        );
+        approve_admin_action(&program_id, &[approve_accounts.as_slice(), &[owner_accs[1].clone()]].concat()).unwrap();
+
+        execute_admin_action(&program_id, &execute_accounts).unwrap();
+        assert_eq!((bank.balance(&vault_key), bank.balance(&recipient_key)), (750, 250));
+        let action = AdminAction::unpack(&action_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((action.approvals, action.executed, action.instruction), (0b11, true, instruction));
+        assert_eq!(execute_admin_action(&program_id, &execute_accounts), Err(ProgramError::InvalidArgument));
+    }
+
+    #[test]
+    fn test_set_multisig_owners_needs_the_multisig_authority_and_retires_pending_actions() {
+        let _bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let system_program_id = Pubkey::default();
+        let (multisig_key, _) = multisig_address(&program_id);
+        let (authority_key, _) = multisig_authority_address(&program_id);
+        let (action_key, _) = admin_action_address(&program_id, 0);
+        let owners = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let new_owners = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let instruction = ProposalInstruction { program_id, accounts: vec![], data: vec![] };
+
+        let mut multisig_data = vec![0u8; Multisig::LEN];
+        Multisig { owners: owners.to_vec(), threshold: 2, nonce: 1, owners_set_at: 0, is_initialized: true }.pack_into_slice(&mut multisig_data);
+        let mut action_data = vec![0u8; AdminAction::LEN];
+        AdminAction { nonce: 0, proposer: owners[0], approvals: 0b01, executed: false, is_initialized: true, instruction }
+            .pack_into_slice(&mut action_data);
+        let mut lamports = [0u64; 5];
+        let [l0, l1, l2, l3, l4] = &mut lamports;
+        let (mut d2, mut d3, mut d4) = (vec![], vec![], vec![]);
+        let multisig_acc = account_info(&multisig_key, false, true, l0, &mut multisig_data, &program_id);
+        let action_acc = account_info(&action_key, false, true, l1, &mut action_data, &program_id);
+        let authority_acc = account_info(&authority_key, true, false, l2, &mut d2, &program_id);
+        let owner_acc = account_info(&owners[0], true, false, l3, &mut d3, &system_program_id);
+        let new_owner_acc = account_info(&new_owners[0], true, false, l4, &mut d4, &system_program_id);
+        fund_rent_exempt(&[&multisig_acc, &action_acc]);
+
+        // An owner cannot change the owners on their own, only the authority an admin action signs with.
+        assert_eq!(
+            set_multisig_owners(&program_id, &[multisig_acc.clone(), owner_acc.clone()], new_owners.to_vec(), 1),
+            Err(ProgramError::IllegalOwner)
+        );
+        let mut unsigned = authority_acc.clone();
+        unsigned.is_signer = false;
+        assert_eq!(
+            set_multisig_owners(&program_id, &[multisig_acc.clone(), unsigned], new_owners.to_vec(), 1),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        let set_accounts = [multisig_acc.clone(), authority_acc];
+        assert_eq!(set_multisig_owners(&program_id, &set_accounts, new_owners.to_vec(), 3), Err(ProgramError::InvalidArgument));
+        assert_eq!(set_multisig_owners(&program_id, &set_accounts, vec![new_owners[0]; 2], 1), Err(ProgramError::InvalidArgument));
+        set_multisig_owners(&program_id, &set_accounts, new_owners.to_vec(), 1).unwrap();
+        let multisig = Multisig::unpack(&multisig_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((multisig.owners, multisig.threshold, multisig.owners_set_at), (new_owners.to_vec(), 1, 1));
+
+        // The action pending from before the change was approved by the old owners, so it lapses.
+        assert_eq!(
+            approve_admin_action(&program_id, &[multisig_acc.clone(), action_acc.clone(), new_owner_acc]),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            execute_admin_action(&program_id, &[multisig_acc, action_acc, set_accounts[1].clone()]),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+}
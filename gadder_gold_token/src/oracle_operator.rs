@@ -0,0 +1,632 @@
+//! Bonded oracle operators. An operator locks at least the configured minimum
+//! of GGT in the oracle bond vault to register, and must stay active to
+//! fulfill match requests. Governance can slash a bond into the treasury; an
+//! operator whose bond falls below the minimum is deactivated until it tops
+//! the bond back up. Deregistering starts the withdrawal delay, after which
+//! the operator takes the bond back and its account is closed.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use crate::treasury_contract::Treasury;
+use crate::{
+    config::{self, AuthorityRole},
+    error::GgtError,
+    events, multisig, token_program,
+};
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer, assert_token_account_mint};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const ORACLE_BOND_CONFIG_SEED: &[u8] = b"oracle_bond_config";
+pub const ORACLE_OPERATOR_SEED: &[u8] = b"oracle_operator";
+pub const ORACLE_BOND_SEED: &[u8] = b"oracle_bond";
+
+pub fn oracle_bond_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ORACLE_BOND_CONFIG_SEED], program_id)
+}
+
+pub fn oracle_operator_address(program_id: &Pubkey, operator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ORACLE_OPERATOR_SEED, operator.as_ref()], program_id)
+}
+
+/// Owner of the token account holding every operator's bond.
+pub fn oracle_bond_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ORACLE_BOND_SEED], program_id)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OracleBondConfig {
+    pub mint: Pubkey,
+    /// Token account owned by the oracle bond authority holding the bonds.
+    pub bond_vault: Pubkey,
+    pub min_bond: u64,
+    /// How long a deregistered operator waits before withdrawing its bond.
+    pub withdrawal_delay_seconds: i64,
+    pub is_initialized: bool,
+}
+
+impl Sealed for OracleBondConfig {}
+
+impl IsInitialized for OracleBondConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for OracleBondConfig {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [108, 110, 104, 249, 203, 101, 146, 24];
+}
+
+impl Pack for OracleBondConfig {
+    const LEN: usize = 90; // discriminator (8) + version (1) + Pubkey (32) * 2 + u64 (8) + i64 (8) + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..32].copy_from_slice(self.mint.as_ref());
+        dst[32..64].copy_from_slice(self.bond_vault.as_ref());
+        dst[64..72].copy_from_slice(&self.min_bond.to_le_bytes());
+        dst[72..80].copy_from_slice(&self.withdrawal_delay_seconds.to_le_bytes());
+        dst[80] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(OracleBondConfig {
+            mint: Pubkey::new_from_array(src[0..32].try_into().unwrap()),
+            bond_vault: Pubkey::new_from_array(src[32..64].try_into().unwrap()),
+            min_bond: u64::from_le_bytes(src[64..72].try_into().unwrap()),
+            withdrawal_delay_seconds: i64::from_le_bytes(src[72..80].try_into().unwrap()),
+            is_initialized: src[80] != 0,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OracleOperator {
+    pub operator: Pubkey,
+    pub bond: u64,
+    pub active: bool,
+    /// When a deregistered operator may withdraw its bond; zero while registered.
+    pub withdrawable_at: i64,
+    pub is_initialized: bool,
+}
+
+impl Sealed for OracleOperator {}
+
+impl IsInitialized for OracleOperator {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for OracleOperator {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [70, 133, 202, 0, 56, 123, 208, 146];
+}
+
+impl Pack for OracleOperator {
+    const LEN: usize = 59; // discriminator (8) + version (1) + Pubkey (32) + u64 (8) + bool (1) + i64 (8) + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..32].copy_from_slice(self.operator.as_ref());
+        dst[32..40].copy_from_slice(&self.bond.to_le_bytes());
+        dst[40] = self.active as u8;
+        dst[41..49].copy_from_slice(&self.withdrawable_at.to_le_bytes());
+        dst[49] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(OracleOperator {
+            operator: Pubkey::new_from_array(src[0..32].try_into().unwrap()),
+            bond: u64::from_le_bytes(src[32..40].try_into().unwrap()),
+            active: src[40] != 0,
+            withdrawable_at: i64::from_le_bytes(src[41..49].try_into().unwrap()),
+            is_initialized: src[49] != 0,
+        })
+    }
+}
+
+fn load_bond_config(program_id: &Pubkey, bond_config_acc: &AccountInfo) -> Result<OracleBondConfig, ProgramError> {
+    assert_owned_by(bond_config_acc, program_id)?;
+    assert_pda(bond_config_acc, oracle_bond_config_address(program_id))?;
+    OracleBondConfig::unpack(&bond_config_acc.try_borrow_data()?)
+}
+
+fn load_operator(program_id: &Pubkey, operator_record_acc: &AccountInfo, operator: &Pubkey) -> Result<OracleOperator, ProgramError> {
+    assert_owned_by(operator_record_acc, program_id)?;
+    assert_pda(operator_record_acc, oracle_operator_address(program_id, operator))?;
+    OracleOperator::unpack(&operator_record_acc.try_borrow_data()?)
+}
+
+/// Checks `operator_record_acc` is the record of `operator` and that it is
+/// active, for instructions only bonded operators may submit.
+pub fn check_active_operator(program_id: &Pubkey, operator_record_acc: &AccountInfo, operator: &Pubkey) -> ProgramResult {
+    if !load_operator(program_id, operator_record_acc, operator)?.active {
+        return Err(GgtError::OracleNotActive.into());
+    }
+    Ok(())
+}
+
+fn check_bond_vault(config: &OracleBondConfig, bond_vault_acc: &AccountInfo, mint_acc: &AccountInfo) -> ProgramResult {
+    if *bond_vault_acc.key != config.bond_vault || *mint_acc.key != config.mint {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// The admin signer follows `bond_config_acc` and pays for it.
+pub struct SetOracleBondConfigAccounts<'a, 'info> {
+    pub bond_config_acc: &'a AccountInfo<'info>,
+    pub admin_acc: &'a AccountInfo<'info>,
+    pub bond_vault_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub bond_config_bump: u8,
+}
+
+impl<'a, 'info> SetOracleBondConfigAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let bond_config_acc = next_account_info(account_info_iter)?;
+        let admin_acc = next_account_info(account_info_iter)?;
+        let bond_vault_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        multisig::check_admin_signer(program_id, admin_acc)?;
+        if assert_token_account_mint(bond_vault_acc, mint_acc.key)?.owner != oracle_bond_authority(program_id).0 {
+            msg!("Bond vault {} is not owned by the oracle bond PDA", bond_vault_acc.key);
+            return Err(ProgramError::IllegalOwner);
+        }
+        let bond_config_bump = assert_pda(bond_config_acc, oracle_bond_config_address(program_id))?;
+        Ok(Self { bond_config_acc, admin_acc, bond_vault_acc, mint_acc, system_program_acc, bond_config_bump })
+    }
+}
+
+pub struct RegisterOracleAccounts<'a, 'info> {
+    pub operator_record_acc: &'a AccountInfo<'info>,
+    pub operator_acc: &'a AccountInfo<'info>,
+    pub operator_token_acc: &'a AccountInfo<'info>,
+    pub bond_vault_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub config: OracleBondConfig,
+    pub operator_record_bump: u8,
+}
+
+impl<'a, 'info> RegisterOracleAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let operator_record_acc = next_account_info(account_info_iter)?;
+        let operator_acc = next_account_info(account_info_iter)?;
+        let operator_token_acc = next_account_info(account_info_iter)?;
+        let bond_vault_acc = next_account_info(account_info_iter)?;
+        let bond_config_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(operator_acc)?;
+        let config = load_bond_config(program_id, bond_config_acc)?;
+        check_bond_vault(&config, bond_vault_acc, mint_acc)?;
+        let operator_record_bump = assert_pda(operator_record_acc, oracle_operator_address(program_id, operator_acc.key))?;
+        Ok(Self {
+            operator_record_acc,
+            operator_acc,
+            operator_token_acc,
+            bond_vault_acc,
+            token_program_acc,
+            mint_acc,
+            system_program_acc,
+            config,
+            operator_record_bump,
+        })
+    }
+}
+
+pub struct DeregisterOracleAccounts<'a, 'info> {
+    pub operator_record_acc: &'a AccountInfo<'info>,
+    pub operator_acc: &'a AccountInfo<'info>,
+    pub config: OracleBondConfig,
+}
+
+impl<'a, 'info> DeregisterOracleAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let operator_record_acc = next_account_info(account_info_iter)?;
+        let operator_acc = next_account_info(account_info_iter)?;
+        let bond_config_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(operator_acc)?;
+        let config = load_bond_config(program_id, bond_config_acc)?;
+        Ok(Self { operator_record_acc, operator_acc, config })
+    }
+}
+
+pub struct WithdrawOracleBondAccounts<'a, 'info> {
+    pub operator_record_acc: &'a AccountInfo<'info>,
+    pub operator_acc: &'a AccountInfo<'info>,
+    pub destination_acc: &'a AccountInfo<'info>,
+    pub bond_vault_acc: &'a AccountInfo<'info>,
+    pub bond_authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub bond_authority_bump: u8,
+}
+
+impl<'a, 'info> WithdrawOracleBondAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let operator_record_acc = next_account_info(account_info_iter)?;
+        let operator_acc = next_account_info(account_info_iter)?;
+        let destination_acc = next_account_info(account_info_iter)?;
+        let bond_vault_acc = next_account_info(account_info_iter)?;
+        let bond_authority_acc = next_account_info(account_info_iter)?;
+        let bond_config_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(operator_acc)?;
+        check_bond_vault(&load_bond_config(program_id, bond_config_acc)?, bond_vault_acc, mint_acc)?;
+        let bond_authority_bump = assert_pda(bond_authority_acc, oracle_bond_authority(program_id))?;
+        Ok(Self {
+            operator_record_acc,
+            operator_acc,
+            destination_acc,
+            bond_vault_acc,
+            bond_authority_acc,
+            token_program_acc,
+            mint_acc,
+            bond_authority_bump,
+        })
+    }
+}
+
+/// The governance signer's program config follows it and is only checked.
+pub struct SlashOracleAccounts<'a, 'info> {
+    pub operator_record_acc: &'a AccountInfo<'info>,
+    pub bond_vault_acc: &'a AccountInfo<'info>,
+    pub treasury_vault_acc: &'a AccountInfo<'info>,
+    pub bond_authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub config: OracleBondConfig,
+    pub bond_authority_bump: u8,
+}
+
+impl<'a, 'info> SlashOracleAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let operator_record_acc = next_account_info(account_info_iter)?;
+        let bond_vault_acc = next_account_info(account_info_iter)?;
+        let treasury_vault_acc = next_account_info(account_info_iter)?;
+        let bond_authority_acc = next_account_info(account_info_iter)?;
+        let bond_config_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let governance_acc = next_account_info(account_info_iter)?;
+        let program_config_acc = next_account_info(account_info_iter)?;
+
+        config::check_authority(program_id, program_config_acc, &[AuthorityRole::Governance], governance_acc)?;
+        let config = load_bond_config(program_id, bond_config_acc)?;
+        check_bond_vault(&config, bond_vault_acc, mint_acc)?;
+        if assert_token_account_mint(treasury_vault_acc, mint_acc.key)?.owner != Treasury::treasury_authority(program_id).0 {
+            msg!("Treasury vault {} is not owned by the treasury authority PDA", treasury_vault_acc.key);
+            return Err(ProgramError::IllegalOwner);
+        }
+        let bond_authority_bump = assert_pda(bond_authority_acc, oracle_bond_authority(program_id))?;
+        Ok(Self {
+            operator_record_acc,
+            bond_vault_acc,
+            treasury_vault_acc,
+            bond_authority_acc,
+            token_program_acc,
+            mint_acc,
+            config,
+            bond_authority_bump,
+        })
+    }
+}
+
+/// Sets the minimum bond and withdrawal delay. The first call creates the
+/// config and fixes the bond mint and vault.
+pub fn set_oracle_bond_config(program_id: &Pubkey, accounts: &[AccountInfo], min_bond: u64, withdrawal_delay_seconds: i64) -> ProgramResult {
+    let SetOracleBondConfigAccounts { bond_config_acc, admin_acc, bond_vault_acc, mint_acc, system_program_acc, bond_config_bump } =
+        SetOracleBondConfigAccounts::try_from(program_id, accounts)?;
+    if min_bond == 0 || withdrawal_delay_seconds < 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut config = if bond_config_acc.lamports() == 0 {
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_acc.key,
+                bond_config_acc.key,
+                Rent::get()?.minimum_balance(OracleBondConfig::LEN),
+                OracleBondConfig::LEN as u64,
+                program_id,
+            ),
+            &[admin_acc.clone(), bond_config_acc.clone(), system_program_acc.clone()],
+            &[&[ORACLE_BOND_CONFIG_SEED, &[bond_config_bump]]],
+        )?;
+        OracleBondConfig { mint: *mint_acc.key, bond_vault: *bond_vault_acc.key, min_bond, withdrawal_delay_seconds, is_initialized: true }
+    } else {
+        let config = load_bond_config(program_id, bond_config_acc)?;
+        check_bond_vault(&config, bond_vault_acc, mint_acc)?;
+        config
+    };
+    config.min_bond = min_bond;
+    config.withdrawal_delay_seconds = withdrawal_delay_seconds;
+    pack_state(&config, bond_config_acc)?;
+    msg!("Oracle bond set to {} with a {}s withdrawal delay", min_bond, withdrawal_delay_seconds);
+    Ok(())
+}
+
+/// Locks `amount` more of the operator's GGT as its bond, creating its record
+/// on the first call. The operator becomes active once its bond covers the
+/// minimum, including after a slash took it below.
+pub fn register_oracle(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let RegisterOracleAccounts {
+        operator_record_acc,
+        operator_acc,
+        operator_token_acc,
+        bond_vault_acc,
+        token_program_acc,
+        mint_acc,
+        system_program_acc,
+        config,
+        operator_record_bump,
+    } = RegisterOracleAccounts::try_from(program_id, accounts)?;
+
+    let is_new = operator_record_acc.lamports() == 0;
+    let mut record = if is_new {
+        OracleOperator { operator: *operator_acc.key, bond: 0, active: false, withdrawable_at: 0, is_initialized: true }
+    } else {
+        load_operator(program_id, operator_record_acc, operator_acc.key)?
+    };
+    if record.withdrawable_at != 0 {
+        msg!("Operator is deregistering; withdraw the bond before registering again");
+        return Err(GgtError::OracleNotActive.into());
+    }
+    record.bond = record.bond.checked_add(amount).ok_or(GgtError::MathOverflow)?;
+    if record.bond < config.min_bond {
+        msg!("Bond {} is below the minimum of {}", record.bond, config.min_bond);
+        return Err(GgtError::OracleBondTooLow.into());
+    }
+
+    if is_new {
+        invoke_signed(
+            &system_instruction::create_account(
+                operator_acc.key,
+                operator_record_acc.key,
+                Rent::get()?.minimum_balance(OracleOperator::LEN),
+                OracleOperator::LEN as u64,
+                program_id,
+            ),
+            &[operator_acc.clone(), operator_record_acc.clone(), system_program_acc.clone()],
+            &[&[ORACLE_OPERATOR_SEED, operator_acc.key.as_ref(), &[operator_record_bump]]],
+        )?;
+    }
+    token_program::transfer(token_program_acc, operator_token_acc, mint_acc, bond_vault_acc, operator_acc, amount, &[])?;
+    record.active = true;
+    let bond = record.bond;
+    pack_state(&record, operator_record_acc)?;
+    events::emit(&events::OracleRegistered { operator: *operator_acc.key, bond });
+    msg!("Oracle {} bonded {}", operator_acc.key, bond);
+    Ok(())
+}
+
+/// Deactivates the operator and starts its withdrawal delay.
+pub fn deregister_oracle(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let DeregisterOracleAccounts { operator_record_acc, operator_acc, config } = DeregisterOracleAccounts::try_from(program_id, accounts)?;
+    let mut record = load_operator(program_id, operator_record_acc, operator_acc.key)?;
+    if record.withdrawable_at != 0 {
+        return Err(GgtError::OracleNotActive.into());
+    }
+    let withdrawable_at = Clock::get()?.unix_timestamp.checked_add(config.withdrawal_delay_seconds).ok_or(GgtError::MathOverflow)?;
+    record.active = false;
+    record.withdrawable_at = withdrawable_at;
+    pack_state(&record, operator_record_acc)?;
+    events::emit(&events::OracleDeregistered { operator: *operator_acc.key, withdrawable_at });
+    msg!("Oracle {} deregistered; bond withdrawable at {}", operator_acc.key, withdrawable_at);
+    Ok(())
+}
+
+/// Returns a deregistered operator's bond once the delay has passed and closes
+/// its record, refunding the rent to the operator.
+pub fn withdraw_oracle_bond(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let WithdrawOracleBondAccounts {
+        operator_record_acc,
+        operator_acc,
+        destination_acc,
+        bond_vault_acc,
+        bond_authority_acc,
+        token_program_acc,
+        mint_acc,
+        bond_authority_bump,
+    } = WithdrawOracleBondAccounts::try_from(program_id, accounts)?;
+    let record = load_operator(program_id, operator_record_acc, operator_acc.key)?;
+    if record.withdrawable_at == 0 || Clock::get()?.unix_timestamp < record.withdrawable_at {
+        return Err(GgtError::TimelockNotElapsed.into());
+    }
+
+    if record.bond > 0 {
+        token_program::transfer(
+            token_program_acc,
+            bond_vault_acc,
+            mint_acc,
+            destination_acc,
+            bond_authority_acc,
+            record.bond,
+            &[&[ORACLE_BOND_SEED, &[bond_authority_bump]]],
+        )?;
+    }
+    let lamports = operator_record_acc.lamports();
+    **operator_acc.try_borrow_mut_lamports()? = operator_acc.lamports().checked_add(lamports).ok_or(GgtError::MathOverflow)?;
+    **operator_record_acc.try_borrow_mut_lamports()? = 0;
+    operator_record_acc.try_borrow_mut_data()?.fill(0);
+    msg!("Oracle {} withdrew its bond of {}", operator_acc.key, record.bond);
+    Ok(())
+}
+
+/// Moves `bps` of an operator's bond into the treasury. Governance only.
+pub fn slash_oracle(program_id: &Pubkey, accounts: &[AccountInfo], bps: u16, reason_hash: [u8; 32]) -> ProgramResult {
+    let SlashOracleAccounts {
+        operator_record_acc,
+        bond_vault_acc,
+        treasury_vault_acc,
+        bond_authority_acc,
+        token_program_acc,
+        mint_acc,
+        config,
+        bond_authority_bump,
+    } = SlashOracleAccounts::try_from(program_id, accounts)?;
+    if bps == 0 || bps > 10_000 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    assert_owned_by(operator_record_acc, program_id)?;
+    let mut record = OracleOperator::unpack(&operator_record_acc.try_borrow_data()?)?;
+    assert_pda(operator_record_acc, oracle_operator_address(program_id, &record.operator))?;
+
+    let amount = (record.bond as u128 * bps as u128 / 10_000) as u64;
+    if amount > 0 {
+        token_program::transfer(
+            token_program_acc,
+            bond_vault_acc,
+            mint_acc,
+            treasury_vault_acc,
+            bond_authority_acc,
+            amount,
+            &[&[ORACLE_BOND_SEED, &[bond_authority_bump]]],
+        )?;
+    }
+    record.bond -= amount;
+    if record.bond < config.min_bond {
+        record.active = false;
+    }
+    let (operator, active) = (record.operator, record.active);
+    pack_state(&record, operator_record_acc)?;
+    events::emit(&events::OracleSlashed { operator, amount, active, reason_hash });
+    msg!("Slashed {} from oracle {}", amount, operator);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multisig::multisig_authority_address;
+    use crate::test_utils::{account_info, program_config, token_account_data, SimulatedBank};
+    use crate::GOVERNANCE_PUBKEY;
+    use spl_token::state::Mint;
+
+    #[test]
+    fn test_bonded_oracle_registers_is_slashed_and_withdraws_after_the_delay() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (token_program_id, system_program_id) = (spl_token::id(), Pubkey::default());
+        let (bond_authority, _) = oracle_bond_authority(&program_id);
+        let (treasury_authority, _) = Treasury::treasury_authority(&program_id);
+        let (bond_config_key, _) = oracle_bond_config_address(&program_id);
+        let (operator_key, mint_key, operator_token_key, bond_vault_key, treasury_vault_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (record_key, _) = oracle_operator_address(&program_id, &operator_key);
+
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { decimals: 9, is_initialized: true, ..Mint::default() }, &mut mint_data).unwrap();
+        let mut operator_token_data = token_account_data(&mint_key, &operator_key, 1_000);
+        let mut bond_vault_data = token_account_data(&mint_key, &bond_authority, 0);
+        let mut treasury_vault_data = token_account_data(&mint_key, &treasury_authority, 0);
+        let (mut bond_config_data, mut record_data) = (vec![0u8; OracleBondConfig::LEN], vec![0u8; OracleOperator::LEN]);
+        let (program_config_key, mut program_config_data) = program_config(&program_id);
+        let (admin_key, _) = multisig_authority_address(&program_id);
+
+        let mut lamports = [0u64, 10_000_000, 10_000_000, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10, l11, l12] = &mut lamports;
+        let (mut d1, mut d2, mut d8, mut d9, mut d10, mut d11) = (vec![], vec![], vec![], vec![], vec![], vec![]);
+        let bond_config_acc = account_info(&bond_config_key, false, true, l0, &mut bond_config_data, &program_id);
+        let admin_acc = account_info(&admin_key, true, true, l1, &mut d1, &system_program_id);
+        let operator_acc = account_info(&operator_key, true, true, l2, &mut d2, &system_program_id);
+        let record_acc = account_info(&record_key, false, true, l3, &mut record_data, &program_id);
+        let operator_token_acc = account_info(&operator_token_key, false, true, l4, &mut operator_token_data, &token_program_id);
+        let bond_vault_acc = account_info(&bond_vault_key, false, true, l5, &mut bond_vault_data, &token_program_id);
+        let treasury_vault_acc = account_info(&treasury_vault_key, false, true, l6, &mut treasury_vault_data, &token_program_id);
+        let mint_acc = account_info(&mint_key, false, false, l7, &mut mint_data, &token_program_id);
+        let system_program_acc = account_info(&system_program_id, false, false, l8, &mut d8, &system_program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, l9, &mut d9, &program_id);
+        let bond_authority_acc = account_info(&bond_authority, false, false, l10, &mut d10, &program_id);
+        let governance_acc = account_info(&GOVERNANCE_PUBKEY, true, false, l11, &mut d11, &system_program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, l12, &mut program_config_data, &program_id);
+        bank.set_balance(&operator_token_key, 1_000);
+        bank.set_unix_timestamp(100);
+
+        let configure = [
+            bond_config_acc.clone(),
+            admin_acc,
+            bond_vault_acc.clone(),
+            mint_acc.clone(),
+            system_program_acc.clone(),
+        ];
+        set_oracle_bond_config(&program_id, &configure, 500, 3_600).unwrap();
+
+        let register = [
+            record_acc.clone(),
+            operator_acc.clone(),
+            operator_token_acc.clone(),
+            bond_vault_acc.clone(),
+            bond_config_acc.clone(),
+            token_program_acc.clone(),
+            mint_acc.clone(),
+            system_program_acc,
+        ];
+        assert_eq!(register_oracle(&program_id, &register, 499), Err(GgtError::OracleBondTooLow.into()));
+        register_oracle(&program_id, &register, 600).unwrap();
+        check_active_operator(&program_id, &record_acc, &operator_key).unwrap();
+        assert_eq!(bank.balance(&bond_vault_key), 600);
+
+        // Losing a quarter of 600 leaves 450, under the 500 minimum.
+        let slash = [
+            record_acc.clone(),
+            bond_vault_acc.clone(),
+            treasury_vault_acc,
+            bond_authority_acc.clone(),
+            bond_config_acc.clone(),
+            token_program_acc.clone(),
+            mint_acc.clone(),
+            governance_acc,
+            program_config_acc,
+        ];
+        slash_oracle(&program_id, &slash, 2_500, [7; 32]).unwrap();
+        assert_eq!((bank.balance(&bond_vault_key), bank.balance(&treasury_vault_key)), (450, 150));
+        assert_eq!(check_active_operator(&program_id, &record_acc, &operator_key), Err(GgtError::OracleNotActive.into()));
+
+        deregister_oracle(&program_id, &[record_acc.clone(), operator_acc.clone(), bond_config_acc.clone()]).unwrap();
+        let withdraw = [
+            record_acc.clone(),
+            operator_acc.clone(),
+            operator_token_acc,
+            bond_vault_acc,
+            bond_authority_acc,
+            bond_config_acc,
+            token_program_acc,
+            mint_acc,
+        ];
+        bank.set_unix_timestamp(3_699);
+        assert_eq!(withdraw_oracle_bond(&program_id, &withdraw), Err(GgtError::TimelockNotElapsed.into()));
+        bank.set_unix_timestamp(3_700);
+        withdraw_oracle_bond(&program_id, &withdraw).unwrap();
+        assert_eq!((bank.balance(&operator_token_key), bank.balance(&bond_vault_key)), (850, 0));
+        // The operator gets back the rent it paid for its record.
+        assert_eq!((record_acc.lamports(), operator_acc.lamports()), (0, 10_000_000));
+    }
+}
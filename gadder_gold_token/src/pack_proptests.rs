@@ -0,0 +1,257 @@
+//! Property tests for the hand-rolled `Pack` layouts. Every account type gets
+//! the same two properties: unpacking arbitrary bytes never panics, and a
+//! value unpacked from a layout packs back to bytes that unpack to the same
+//! layout again, which catches a field read from a different offset than it
+//! is written to. Stake, Vote and Proposal also round-trip typed values,
+//! Proposal up to its longest URI and instruction list.
+
+use proptest::{collection, prelude::*, sample::Index};
+use solana_program::{program_error::ProgramError, program_pack::Pack, pubkey::Pubkey};
+use crate::discriminator::{Discriminator, DISCRIMINATOR_LEN, HEADER_LEN};
+use crate::governance_contract::{
+    Proposal, ProposalAccountMeta, ProposalInstruction, ProposalKind, ProposalStatus, TokenBurn, TreasurySpend, Vote, VoteChoice,
+    MAX_INSTRUCTIONS_LEN, MAX_URI_LEN,
+};
+use crate::staking_contract::Stake;
+
+fn packed<T: Pack>(value: &T) -> Vec<u8> {
+    let mut data = vec![0u8; T::LEN];
+    value.pack_into_slice(&mut data);
+    data
+}
+
+/// A zeroed layout of `T` behind its header, with `edits` applied past it.
+fn edited<T: Pack + Discriminator>(edits: &[(Index, u8)]) -> Vec<u8> {
+    let mut data = vec![0u8; T::LEN];
+    data[..DISCRIMINATOR_LEN].copy_from_slice(&T::DISCRIMINATOR);
+    data[DISCRIMINATOR_LEN] = T::VERSION;
+    for (index, byte) in edits {
+        data[HEADER_LEN + index.index(T::LEN - HEADER_LEN)] = *byte;
+    }
+    data
+}
+
+fn check_malformed<T: Pack + Discriminator>(body: &[u8], tag: &[u8; DISCRIMINATOR_LEN], len: usize) -> Result<(), TestCaseError> {
+    let mut data = T::DISCRIMINATOR.to_vec();
+    data.push(T::VERSION);
+    data.extend_from_slice(body);
+    // Whatever the body holds, unpacking returns rather than panics.
+    let _ = T::unpack_from_slice(&data);
+    if *tag != T::DISCRIMINATOR {
+        data[..DISCRIMINATOR_LEN].copy_from_slice(tag);
+        prop_assert!(T::unpack_from_slice(&data).is_err());
+    }
+    if len != T::LEN {
+        data.resize(len, 0);
+        prop_assert_eq!(T::unpack_unchecked(&data).err(), Some(ProgramError::InvalidAccountData));
+    }
+    Ok(())
+}
+
+fn check_repack<T: Pack>(data: &[u8]) -> Result<(), TestCaseError> {
+    let Ok(value) = T::unpack_from_slice(data) else { return Ok(()) };
+    let once = packed(&value);
+    let reread = T::unpack_from_slice(&once);
+    prop_assert!(reread.is_ok(), "packed layout failed to unpack: {:?}", reread.err());
+    prop_assert_eq!(packed(&reread.unwrap()), once);
+    Ok(())
+}
+
+macro_rules! pack_properties {
+    ($($name:ident: $ty:ty,)*) => {$(
+        mod $name {
+            use super::*;
+
+            proptest! {
+                #[test]
+                fn malformed_layouts_are_rejected_without_panicking(
+                    body in collection::vec(any::<u8>(), <$ty>::LEN - HEADER_LEN),
+                    tag in any::<[u8; DISCRIMINATOR_LEN]>(),
+                    len in 0..<$ty>::LEN * 2,
+                ) {
+                    check_malformed::<$ty>(&body, &tag, len)?;
+                }
+
+                #[test]
+                fn unpacked_layouts_repack_unchanged(edits in collection::vec((any::<Index>(), any::<u8>()), 0..12)) {
+                    check_repack::<$ty>(&edited::<$ty>(&edits))?;
+                }
+            }
+        }
+    )*};
+}
+
+pack_properties! {
+    address_book: crate::address_book::AddressBook,
+    match_request: crate::ai_contract::MatchRequest,
+    oracle_registry: crate::ai_contract::OracleRegistry,
+    consultant_profile: crate::ai_contract::ConsultantProfile,
+    payout_split: crate::ai_contract::PayoutSplit,
+    distribution: crate::airdrop::Distribution,
+    program_config: crate::config::ProgramConfig,
+    audit_log: crate::config::AuditLog,
+    bridge_config: crate::cross_chain_bridge_contract::BridgeConfig,
+    chain_registry: crate::cross_chain_bridge_contract::ChainRegistry,
+    bridge_receipt: crate::cross_chain_bridge_contract::BridgeReceipt,
+    bridge_transfer: crate::bridge_transfer::BridgeTransfer,
+    outflow_window: crate::cross_chain_bridge_contract::OutflowWindow,
+    engagement: crate::engagement::Engagement,
+    arbitration_config: crate::dispute::ArbitrationConfig,
+    dispute: crate::dispute::Dispute,
+    reputation: crate::reputation::Reputation,
+    payment_receipt: crate::payment_receipt::PaymentReceipt,
+    denylist: crate::denylist::Denylist,
+    governance_config: crate::governance_contract::GovernanceConfig,
+    proposal: crate::governance_contract::Proposal,
+    vote: crate::governance_contract::Vote,
+    vote_escrow: crate::vote_escrow::VoteEscrow,
+    ballot_page: crate::governance_contract::BallotPage,
+    proposal_counter: crate::governance_contract::ProposalCounter,
+    invoice: crate::invoice::Invoice,
+    market_maker: crate::market_maker::MarketMaker,
+    fast_mint: crate::market_maker::FastMint,
+    migration_state: crate::migration::MigrationState,
+    multisig: crate::multisig::Multisig,
+    admin_action: crate::multisig::AdminAction,
+    oracle_bond_config: crate::oracle_operator::OracleBondConfig,
+    oracle_operator: crate::oracle_operator::OracleOperator,
+    staking_config: crate::staking_contract::StakingConfig,
+    stake: crate::staking_contract::Stake,
+    staking_pool: crate::staking_contract::StakingPool,
+    co_staking_pool: crate::staking_contract::CoStakingPool,
+    co_stake: crate::staking_contract::CoStake,
+    stake_summary: crate::staking_contract::StakeSummary,
+    payment_stream: crate::streaming_contract::PaymentStream,
+    treasury_assets: crate::treasury_contract::TreasuryAssets,
+    vesting_schedule: crate::vesting::VestingSchedule,
+    wrapped_asset: crate::wrapped_asset::WrappedAsset,
+}
+
+fn pubkey() -> impl Strategy<Value = Pubkey> {
+    any::<[u8; 32]>().prop_map(Pubkey::new_from_array)
+}
+
+prop_compose! {
+    fn stake()(
+        (amount, lock_until, reward_per_token_paid, rewards_owed) in (any::<u64>(), any::<i64>(), any::<u128>(), any::<u64>()),
+        (staked_at, pending_unstake, claimable_at, voting_delegate) in (any::<i64>(), any::<u64>(), any::<i64>(), pubkey()),
+        (delegated_at, has_receipt, is_initialized) in (any::<i64>(), any::<bool>(), any::<bool>()),
+    ) -> Stake {
+        Stake {
+            amount,
+            lock_until,
+            reward_per_token_paid,
+            rewards_owed,
+            staked_at,
+            pending_unstake,
+            claimable_at,
+            voting_delegate,
+            delegated_at,
+            has_receipt,
+            is_initialized,
+        }
+    }
+}
+
+prop_compose! {
+    fn vote()(
+        proposal in pubkey(),
+        voter in pubkey(),
+        choice in prop_oneof![Just(VoteChoice::No), Just(VoteChoice::Yes), Just(VoteChoice::Abstain)],
+        weight in any::<u64>(),
+        is_initialized in any::<bool>(),
+    ) -> Vote {
+        Vote { proposal, voter, choice, weight, is_initialized }
+    }
+}
+
+fn proposal_instruction() -> impl Strategy<Value = ProposalInstruction> {
+    let meta = (pubkey(), any::<bool>(), any::<bool>())
+        .prop_map(|(pubkey, is_signer, is_writable)| ProposalAccountMeta { pubkey, is_signer, is_writable });
+    (pubkey(), collection::vec(meta, 0..8), collection::vec(any::<u8>(), 0..128))
+        .prop_map(|(program_id, accounts, data)| ProposalInstruction { program_id, accounts, data })
+}
+
+fn proposal_status() -> impl Strategy<Value = ProposalStatus> {
+    prop_oneof![
+        Just(ProposalStatus::Active),
+        Just(ProposalStatus::Passed),
+        Just(ProposalStatus::Rejected),
+        Just(ProposalStatus::Executed),
+        Just(ProposalStatus::Cancelled),
+    ]
+}
+
+fn proposal_kind() -> impl Strategy<Value = ProposalKind> {
+    prop_oneof![
+        Just(ProposalKind::Text),
+        (pubkey(), pubkey(), any::<u64>())
+            .prop_map(|(vault, recipient, amount)| ProposalKind::TreasurySpend(TreasurySpend { vault, recipient, amount })),
+        (pubkey(), any::<u64>()).prop_map(|(token_account, amount)| ProposalKind::TokenBurn(TokenBurn { token_account, amount })),
+    ]
+}
+
+prop_compose! {
+    fn proposal()(
+        uri in collection::vec(proptest::char::range('!', '~'), 0..=MAX_URI_LEN).prop_map(String::from_iter),
+        (content_hash, proposer, status) in (any::<[u8; 32]>(), pubkey(), proposal_status()),
+        (timestamp, voting_ends_at, yes_weight, no_weight, abstain_weight) in (any::<i64>(), any::<i64>(), any::<u64>(), any::<u64>(), any::<u64>()),
+        escrow_weight in any::<u64>(),
+        (ballot_pages, pages_tallied, kind, deposit) in (any::<u16>(), any::<u16>(), proposal_kind(), any::<u64>()),
+        (snapshot_epoch, snapshot_reward_index, executable_at, is_initialized) in (any::<u64>(), any::<u128>(), any::<i64>(), any::<bool>()),
+        instructions in collection::vec(proposal_instruction(), 0..4)
+            .prop_filter("fits MAX_INSTRUCTIONS_LEN", |ixs| borsh::object_length(ixs).unwrap() <= MAX_INSTRUCTIONS_LEN),
+    ) -> Proposal {
+        Proposal {
+            uri,
+            content_hash,
+            proposer,
+            status,
+            timestamp,
+            voting_ends_at,
+            yes_weight,
+            no_weight,
+            abstain_weight,
+            escrow_weight,
+            ballot_pages,
+            pages_tallied,
+            kind,
+            deposit,
+            snapshot_epoch,
+            snapshot_reward_index,
+            executable_at,
+            is_initialized,
+            instructions,
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn stake_round_trips(stake in stake()) {
+        let data = packed(&stake);
+        let unpacked = Stake::unpack_from_slice(&data).unwrap();
+        prop_assert_eq!((unpacked.amount, unpacked.reward_per_token_paid), (stake.amount, stake.reward_per_token_paid));
+        prop_assert_eq!(unpacked.voting_delegate, stake.voting_delegate);
+        prop_assert_eq!(packed(&unpacked), data);
+    }
+
+    #[test]
+    fn vote_round_trips(vote in vote()) {
+        let data = packed(&vote);
+        let unpacked = Vote::unpack_from_slice(&data).unwrap();
+        prop_assert_eq!((unpacked.proposal, unpacked.voter), (vote.proposal, vote.voter));
+        prop_assert_eq!((unpacked.choice, unpacked.weight, unpacked.is_initialized), (vote.choice, vote.weight, vote.is_initialized));
+        prop_assert_eq!(packed(&unpacked), data);
+    }
+
+    #[test]
+    fn proposal_round_trips_up_to_its_longest_uri(proposal in proposal()) {
+        let data = packed(&proposal);
+        let unpacked = Proposal::unpack_from_slice(&data).unwrap();
+        prop_assert_eq!(&unpacked.uri, &proposal.uri);
+        prop_assert_eq!(&unpacked.instructions, &proposal.instructions);
+        prop_assert_eq!((unpacked.kind, unpacked.snapshot_reward_index), (proposal.kind, proposal.snapshot_reward_index));
+        prop_assert_eq!(packed(&unpacked), data);
+    }
+}
@@ -0,0 +1,105 @@
+//! On-chain receipts for referenced payments. `transfer_with_memo` tags a
+//! transfer with a 32-byte reference, typically an invoice id the merchant
+//! generated, and may record it in a PDA keyed by the receiving token
+//! account, the payer and that reference so the merchant can look a payment
+//! up without scanning transaction logs. Keying by both parties means no one
+//! else can take a reference's receipt first. Each payer can receipt a
+//! reference to a recipient once.
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use crate::state::{create_state_account, pack_state};
+use crate::validation::assert_pda;
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const PAYMENT_RECEIPT_SEED: &[u8] = b"payment_receipt";
+
+pub fn payment_receipt_address(program_id: &Pubkey, recipient: &Pubkey, sender: &Pubkey, reference: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PAYMENT_RECEIPT_SEED, recipient.as_ref(), sender.as_ref(), reference], program_id)
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct PaymentReceipt {
+    pub reference: [u8; 32],
+    /// Owner of the paying token account.
+    pub sender: Pubkey,
+    /// Token account that received the payment.
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    /// Amount `recipient` received, after the protocol fee.
+    pub amount: u64,
+    pub fee: u64,
+    pub paid_at: i64,
+    pub is_initialized: bool,
+}
+
+impl Sealed for PaymentReceipt {}
+
+impl IsInitialized for PaymentReceipt {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for PaymentReceipt {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [168, 198, 209, 4, 60, 235, 126, 109];
+}
+
+impl Pack for PaymentReceipt {
+    const LEN: usize = 162; // discriminator (8) + version (1) + [u8; 32] (32) + Pubkey (32) * 3 + u64 (8) * 2 + i64 (8) + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..32].copy_from_slice(&self.reference);
+        dst[32..64].copy_from_slice(self.sender.as_ref());
+        dst[64..96].copy_from_slice(self.recipient.as_ref());
+        dst[96..128].copy_from_slice(self.mint.as_ref());
+        dst[128..136].copy_from_slice(&self.amount.to_le_bytes());
+        dst[136..144].copy_from_slice(&self.fee.to_le_bytes());
+        dst[144..152].copy_from_slice(&self.paid_at.to_le_bytes());
+        dst[152] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(PaymentReceipt {
+            reference: src[0..32].try_into().unwrap(),
+            sender: Pubkey::new_from_array(src[32..64].try_into().unwrap()),
+            recipient: Pubkey::new_from_array(src[64..96].try_into().unwrap()),
+            mint: Pubkey::new_from_array(src[96..128].try_into().unwrap()),
+            amount: u64::from_le_bytes(src[128..136].try_into().unwrap()),
+            fee: u64::from_le_bytes(src[136..144].try_into().unwrap()),
+            paid_at: i64::from_le_bytes(src[144..152].try_into().unwrap()),
+            is_initialized: src[152] != 0,
+        })
+    }
+}
+
+/// Creates the receipt PDA for `receipt`'s recipient, sender and reference,
+/// paid by `payer_acc`. Fails if that receipt already exists.
+pub(crate) fn record_receipt<'a>(
+    program_id: &Pubkey,
+    receipt_acc: &AccountInfo<'a>,
+    payer_acc: &AccountInfo<'a>,
+    system_program_acc: &AccountInfo<'a>,
+    receipt: PaymentReceipt,
+) -> ProgramResult {
+    let bump = assert_pda(receipt_acc, payment_receipt_address(program_id, &receipt.recipient, &receipt.sender, &receipt.reference))?;
+    if receipt_acc.lamports() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    create_state_account(
+        program_id,
+        payer_acc,
+        receipt_acc,
+        system_program_acc,
+        PaymentReceipt::LEN,
+        &[&[PAYMENT_RECEIPT_SEED, receipt.recipient.as_ref(), receipt.sender.as_ref(), &receipt.reference, &[bump]]],
+    )?;
+    pack_state(&receipt, receipt_acc)
+}
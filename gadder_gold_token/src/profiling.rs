@@ -0,0 +1,24 @@
+//! Per-instruction compute sampling for finding hotspots on devnet.
+//!
+//! With the `profiling` feature each instruction logs a `profile` event via
+//! `sol_log_data`: the tag, whether it succeeded and the compute units it
+//! consumed. Without the feature `profiled` just runs the handler.
+
+use solana_program::entrypoint::ProgramResult;
+#[cfg(feature = "profiling")]
+use solana_program::{compute_units::sol_remaining_compute_units, log::sol_log_data};
+
+#[cfg(feature = "profiling")]
+pub fn profiled(tag: u8, handler: impl FnOnce() -> ProgramResult) -> ProgramResult {
+    let before = sol_remaining_compute_units();
+    let result = handler();
+    let consumed = before.saturating_sub(sol_remaining_compute_units());
+    sol_log_data(&[b"profile", &[tag, result.is_ok() as u8], &consumed.to_le_bytes()]);
+    result
+}
+
+#[cfg(not(feature = "profiling"))]
+#[inline(always)]
+pub fn profiled(_tag: u8, handler: impl FnOnce() -> ProgramResult) -> ProgramResult {
+    handler()
+}
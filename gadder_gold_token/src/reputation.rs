@@ -0,0 +1,208 @@
+//! Marketplace reputation of a client or consultant wallet, kept in one PDA
+//! per wallet whichever side of an engagement it is on. Completing an
+//! engagement credits both parties; a dispute ruled against a party counts as
+//! a lost dispute. The off-chain matcher reads the record, including its
+//! score, through `get_reputation`.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use crate::error::GgtError;
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const REPUTATION_SEED: &[u8] = b"reputation";
+/// Each lost dispute weighs as much as this many completed engagements.
+pub const DISPUTE_LOSS_WEIGHT: u64 = 3;
+/// Completed engagements after which the score reflects the track record in full.
+pub const FULL_CONFIDENCE_ENGAGEMENTS: u64 = 20;
+pub const MAX_REPUTATION_SCORE: u16 = 10_000;
+
+pub fn reputation_address(program_id: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REPUTATION_SEED, wallet.as_ref()], program_id)
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct Reputation {
+    pub wallet: Pubkey,
+    pub completed_engagements: u32,
+    pub disputes_lost: u32,
+    /// Escrow of every engagement the wallet settled, completed or ruled on.
+    pub total_volume: u64,
+    /// Out of `MAX_REPUTATION_SCORE`; see [`Reputation::compute_score`].
+    pub score: u16,
+    pub updated_at: i64,
+    pub is_initialized: bool,
+}
+
+impl Reputation {
+    /// Share of the track record without lost disputes, each of which counts
+    /// `DISPUTE_LOSS_WEIGHT` times, scaled down until the wallet has completed
+    /// `FULL_CONFIDENCE_ENGAGEMENTS` engagements.
+    pub fn compute_score(&self) -> u16 {
+        let completed = self.completed_engagements as u64;
+        let record = completed + self.disputes_lost as u64 * DISPUTE_LOSS_WEIGHT;
+        if record == 0 {
+            return 0;
+        }
+        let ratio = completed * MAX_REPUTATION_SCORE as u64 / record;
+        (ratio * completed.min(FULL_CONFIDENCE_ENGAGEMENTS) / FULL_CONFIDENCE_ENGAGEMENTS) as u16
+    }
+
+    pub fn record_completion(&mut self, volume: u64, now: i64) -> ProgramResult {
+        self.completed_engagements = self.completed_engagements.checked_add(1).ok_or(GgtError::MathOverflow)?;
+        self.record_settlement(volume, now)
+    }
+
+    pub fn record_lost_dispute(&mut self, volume: u64, now: i64) -> ProgramResult {
+        self.disputes_lost = self.disputes_lost.checked_add(1).ok_or(GgtError::MathOverflow)?;
+        self.record_settlement(volume, now)
+    }
+
+    pub fn record_settlement(&mut self, volume: u64, now: i64) -> ProgramResult {
+        self.total_volume = self.total_volume.checked_add(volume).ok_or(GgtError::MathOverflow)?;
+        self.score = self.compute_score();
+        self.updated_at = now;
+        Ok(())
+    }
+}
+
+impl Sealed for Reputation {}
+
+impl IsInitialized for Reputation {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for Reputation {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [55, 148, 90, 71, 68, 183, 193, 28];
+}
+
+impl Pack for Reputation {
+    const LEN: usize = 68; // discriminator (8) + version (1) + Pubkey (32) + u32 (4) * 2 + u64 (8) + u16 (2) + i64 (8) + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..32].copy_from_slice(self.wallet.as_ref());
+        dst[32..36].copy_from_slice(&self.completed_engagements.to_le_bytes());
+        dst[36..40].copy_from_slice(&self.disputes_lost.to_le_bytes());
+        dst[40..48].copy_from_slice(&self.total_volume.to_le_bytes());
+        dst[48..50].copy_from_slice(&self.score.to_le_bytes());
+        dst[50..58].copy_from_slice(&self.updated_at.to_le_bytes());
+        dst[58] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(Reputation {
+            wallet: Pubkey::new_from_array(src[0..32].try_into().unwrap()),
+            completed_engagements: u32::from_le_bytes(src[32..36].try_into().unwrap()),
+            disputes_lost: u32::from_le_bytes(src[36..40].try_into().unwrap()),
+            total_volume: u64::from_le_bytes(src[40..48].try_into().unwrap()),
+            score: u16::from_le_bytes(src[48..50].try_into().unwrap()),
+            updated_at: i64::from_le_bytes(src[50..58].try_into().unwrap()),
+            is_initialized: src[58] != 0,
+        })
+    }
+}
+
+/// Applies `update` to `wallet`'s reputation in `reputation_acc`, which
+/// `payer_acc` creates if the wallet has none yet.
+pub(crate) fn update_reputation<'a>(
+    program_id: &Pubkey,
+    reputation_acc: &AccountInfo<'a>,
+    wallet: &Pubkey,
+    payer_acc: &AccountInfo<'a>,
+    system_program_acc: &AccountInfo<'a>,
+    update: impl FnOnce(&mut Reputation, i64) -> ProgramResult,
+) -> ProgramResult {
+    let bump = assert_pda(reputation_acc, reputation_address(program_id, wallet))?;
+    let mut reputation = if reputation_acc.lamports() == 0 {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_acc.key,
+                reputation_acc.key,
+                Rent::get()?.minimum_balance(Reputation::LEN),
+                Reputation::LEN as u64,
+                program_id,
+            ),
+            &[payer_acc.clone(), reputation_acc.clone(), system_program_acc.clone()],
+            &[&[REPUTATION_SEED, wallet.as_ref(), &[bump]]],
+        )?;
+        Reputation { wallet: *wallet, is_initialized: true, ..Reputation::default() }
+    } else {
+        assert_owned_by(reputation_acc, program_id)?;
+        Reputation::unpack(&reputation_acc.try_borrow_data()?)?
+    };
+    update(&mut reputation, Clock::get()?.unix_timestamp)?;
+    pack_state(&reputation, reputation_acc)
+}
+
+pub struct GetReputationAccounts<'a, 'info> {
+    pub reputation_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> GetReputationAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let reputation_acc = next_account_info(account_info_iter)?;
+
+        assert_owned_by(reputation_acc, program_id)?;
+        Ok(Self { reputation_acc })
+    }
+}
+
+/// Read-only query: publishes the wallet's `Reputation` as Borsh return data.
+pub fn get_reputation(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let GetReputationAccounts { reputation_acc } = GetReputationAccounts::try_from(program_id, accounts)?;
+    let reputation = Reputation::unpack(&reputation_acc.try_borrow_data()?)?;
+    assert_pda(reputation_acc, reputation_address(program_id, &reputation.wallet))?;
+    msg!("Reputation of {}: {}", reputation.wallet, reputation.score);
+    crate::set_borsh_return_data(&reputation);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{account_info, SimulatedBank};
+
+    #[test]
+    fn test_score_weighs_lost_disputes_and_grows_with_experience() {
+        let score = |completed_engagements, disputes_lost| Reputation { completed_engagements, disputes_lost, ..Reputation::default() }.compute_score();
+        assert_eq!(score(0, 0), 0);
+        // A clean record is trusted in full only after 20 engagements.
+        assert_eq!((score(5, 0), score(20, 0), score(40, 0)), (2_500, MAX_REPUTATION_SCORE, MAX_REPUTATION_SCORE));
+        // One lost dispute counts as three engagements against 17 completed.
+        assert_eq!(score(17, 1), 7_225);
+        assert_eq!(score(0, 2), 0);
+
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let (reputation_key, _) = reputation_address(&program_id, &wallet);
+        let mut reputation = Reputation { wallet, is_initialized: true, ..Reputation::default() };
+        reputation.record_completion(400, 7).unwrap();
+        reputation.record_lost_dispute(100, 9).unwrap();
+        let mut data = vec![0u8; Reputation::LEN];
+        Reputation::pack(reputation.clone(), &mut data).unwrap();
+        let mut lamports = 0u64;
+        let reputation_acc = account_info(&reputation_key, false, false, &mut lamports, &mut data, &program_id);
+        get_reputation(&program_id, &[reputation_acc]).unwrap();
+        let returned: Reputation = borsh::from_slice(&bank.return_data()).unwrap();
+        assert_eq!(returned, reputation);
+        assert_eq!((returned.total_volume, returned.score, returned.updated_at), (500, 125, 9));
+    }
+}
@@ -0,0 +1,441 @@
+//! Stake receipt NFTs. A staker can mint a receipt for an open position: a
+//! zero-decimal token with supply one under a mint derived from the stake
+//! account, whose metadata URI carries the staked amount and unlock date so
+//! wallets and marketplaces can display the position. Receipts are frozen in
+//! the staker's receipt account unless minted transferable. While a receipt is
+//! outstanding the position cannot be unstaken; whoever holds it burns it to
+//! release the position.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use mpl_token_metadata::{
+    accounts::Metadata,
+    instructions::{CreateMetadataAccountV3, CreateMetadataAccountV3InstructionArgs, UpdateMetadataAccountV2, UpdateMetadataAccountV2InstructionArgs},
+    types::DataV2,
+};
+use spl_token::{
+    instruction::{initialize_account3, initialize_mint2},
+    state::{Account as TokenAccount, Mint},
+};
+use crate::staking_contract::{check_stake_account, PodStake, Stake};
+use crate::token_program;
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer};
+use crate::zero_copy::{load, load_mut};
+
+pub const STAKE_RECEIPT_SEED: &[u8] = b"stake_receipt";
+pub const STAKE_RECEIPT_ACCOUNT_SEED: &[u8] = b"stake_receipt_account";
+pub const STAKE_RECEIPT_AUTHORITY_SEED: &[u8] = b"stake_receipt_authority";
+pub const STAKE_RECEIPT_NAME: &str = "GGT Stake Receipt";
+pub const STAKE_RECEIPT_SYMBOL: &str = "sGGT";
+/// Receipt metadata URIs append `?amount=<raw amount>&unlock=<unix time>`.
+pub const STAKE_RECEIPT_URI: &str = "https://gadder.gold/stake-receipt";
+
+/// Receipt mint for the position at `stake`.
+pub fn receipt_mint_address(program_id: &Pubkey, stake: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_RECEIPT_SEED, stake.as_ref()], program_id)
+}
+
+/// Token account the receipt for `receipt_mint` is minted into, owned by the staker.
+pub fn receipt_account_address(program_id: &Pubkey, receipt_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_RECEIPT_ACCOUNT_SEED, receipt_mint.as_ref()], program_id)
+}
+
+/// Mint, freeze and metadata update authority of every receipt.
+pub fn receipt_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_RECEIPT_AUTHORITY_SEED], program_id)
+}
+
+fn receipt_data(stake: &Stake) -> DataV2 {
+    DataV2 {
+        name: STAKE_RECEIPT_NAME.to_string(),
+        symbol: STAKE_RECEIPT_SYMBOL.to_string(),
+        uri: format!("{}?amount={}&unlock={}", STAKE_RECEIPT_URI, stake.amount, stake.lock_until),
+        seller_fee_basis_points: 0,
+        creators: None,
+        collection: None,
+        uses: None,
+    }
+}
+
+fn check_metadata(receipt_mint_acc: &AccountInfo, metadata_acc: &AccountInfo, metadata_program_acc: &AccountInfo) -> ProgramResult {
+    if *metadata_program_acc.key != mpl_token_metadata::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if *metadata_acc.key != Metadata::find_pda(receipt_mint_acc.key).0 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(())
+}
+
+fn update_metadata<'a>(metadata_acc: &AccountInfo<'a>, authority_acc: &AccountInfo<'a>, stake: &Stake, authority_bump: u8) -> ProgramResult {
+    let ix = UpdateMetadataAccountV2 { metadata: *metadata_acc.key, update_authority: *authority_acc.key }.instruction(
+        UpdateMetadataAccountV2InstructionArgs {
+            data: Some(receipt_data(stake)),
+            new_update_authority: None,
+            primary_sale_happened: None,
+            is_mutable: None,
+        },
+    );
+    invoke_signed(&ix, &[metadata_acc.clone(), authority_acc.clone()], &[&[STAKE_RECEIPT_AUTHORITY_SEED, &[authority_bump]]])
+}
+
+pub struct MintStakeReceiptAccounts<'a, 'info> {
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub staker_auth: &'a AccountInfo<'info>,
+    pub receipt_mint_acc: &'a AccountInfo<'info>,
+    pub receipt_account_acc: &'a AccountInfo<'info>,
+    pub metadata_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub rent_sysvar_acc: &'a AccountInfo<'info>,
+    pub receipt_mint_bump: u8,
+    pub receipt_account_bump: u8,
+    pub authority_bump: u8,
+}
+
+impl<'a, 'info> MintStakeReceiptAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let staking_acc = next_account_info(account_info_iter)?;
+        let staker_auth = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let receipt_mint_acc = next_account_info(account_info_iter)?;
+        let receipt_account_acc = next_account_info(account_info_iter)?;
+        let metadata_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+        let rent_sysvar_acc = next_account_info(account_info_iter)?;
+        let metadata_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(staker_auth)?;
+        check_stake_account(program_id, staking_acc, staker_auth.key, mint_acc.key)?;
+        if *token_program_acc.key != spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let receipt_mint_bump = assert_pda(receipt_mint_acc, receipt_mint_address(program_id, staking_acc.key))?;
+        let receipt_account_bump = assert_pda(receipt_account_acc, receipt_account_address(program_id, receipt_mint_acc.key))?;
+        let authority_bump = assert_pda(authority_acc, receipt_authority(program_id))?;
+        check_metadata(receipt_mint_acc, metadata_acc, metadata_program_acc)?;
+        Ok(Self {
+            staking_acc,
+            staker_auth,
+            receipt_mint_acc,
+            receipt_account_acc,
+            metadata_acc,
+            authority_acc,
+            token_program_acc,
+            system_program_acc,
+            rent_sysvar_acc,
+            receipt_mint_bump,
+            receipt_account_bump,
+            authority_bump,
+        })
+    }
+}
+
+/// The holder signer owns `holder_token_acc` and receives its rent when it closes.
+pub struct BurnStakeReceiptAccounts<'a, 'info> {
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub receipt_mint_acc: &'a AccountInfo<'info>,
+    pub holder_token_acc: &'a AccountInfo<'info>,
+    pub holder_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub authority_bump: u8,
+}
+
+impl<'a, 'info> BurnStakeReceiptAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let staking_acc = next_account_info(account_info_iter)?;
+        let receipt_mint_acc = next_account_info(account_info_iter)?;
+        let holder_token_acc = next_account_info(account_info_iter)?;
+        let holder_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(holder_acc)?;
+        assert_owned_by(staking_acc, program_id)?;
+        assert_pda(receipt_mint_acc, receipt_mint_address(program_id, staking_acc.key))?;
+        let authority_bump = assert_pda(authority_acc, receipt_authority(program_id))?;
+        let holder_token = token_program::unpack_account(holder_token_acc)?;
+        if holder_token.mint != *receipt_mint_acc.key || holder_token.owner != *holder_acc.key {
+            return Err(ProgramError::IllegalOwner);
+        }
+        if holder_token.amount != 1 {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        Ok(Self { staking_acc, receipt_mint_acc, holder_token_acc, holder_acc, authority_acc, token_program_acc, authority_bump })
+    }
+}
+
+/// Accounts for `refresh_stake_receipt`; anyone may pass them.
+pub struct RefreshStakeReceiptAccounts<'a, 'info> {
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub metadata_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub authority_bump: u8,
+}
+
+impl<'a, 'info> RefreshStakeReceiptAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let staking_acc = next_account_info(account_info_iter)?;
+        let receipt_mint_acc = next_account_info(account_info_iter)?;
+        let metadata_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let metadata_program_acc = next_account_info(account_info_iter)?;
+
+        assert_owned_by(staking_acc, program_id)?;
+        assert_pda(receipt_mint_acc, receipt_mint_address(program_id, staking_acc.key))?;
+        check_metadata(receipt_mint_acc, metadata_acc, metadata_program_acc)?;
+        let authority_bump = assert_pda(authority_acc, receipt_authority(program_id))?;
+        Ok(Self { staking_acc, metadata_acc, authority_acc, authority_bump })
+    }
+}
+
+/// Mints a receipt for the caller's open position into their receipt account.
+/// Unless `transferable`, the receipt account is frozen so the receipt stays
+/// with the staker. A position burned free of an earlier receipt reuses its
+/// mint and metadata.
+pub fn mint_stake_receipt(program_id: &Pubkey, accounts: &[AccountInfo], transferable: bool) -> ProgramResult {
+    let MintStakeReceiptAccounts {
+        staking_acc,
+        staker_auth,
+        receipt_mint_acc,
+        receipt_account_acc,
+        metadata_acc,
+        authority_acc,
+        token_program_acc,
+        system_program_acc,
+        rent_sysvar_acc,
+        receipt_mint_bump,
+        receipt_account_bump,
+        authority_bump,
+    } = MintStakeReceiptAccounts::try_from(program_id, accounts)?;
+
+    let mut stake = Stake::unpack(&staking_acc.try_borrow_data()?)?;
+    if stake.amount == 0 {
+        msg!("No open position to issue a receipt for");
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if stake.has_receipt {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let rent = Rent::get()?;
+    let authority_seeds: &[&[u8]] = &[STAKE_RECEIPT_AUTHORITY_SEED, &[authority_bump]];
+
+    if receipt_mint_acc.lamports() == 0 {
+        invoke_signed(
+            &system_instruction::create_account(
+                staker_auth.key,
+                receipt_mint_acc.key,
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                token_program_acc.key,
+            ),
+            &[staker_auth.clone(), receipt_mint_acc.clone(), system_program_acc.clone()],
+            &[&[STAKE_RECEIPT_SEED, staking_acc.key.as_ref(), &[receipt_mint_bump]]],
+        )?;
+        let ix = initialize_mint2(token_program_acc.key, receipt_mint_acc.key, authority_acc.key, Some(authority_acc.key), 0)?;
+        invoke(&ix, &[receipt_mint_acc.clone(), token_program_acc.clone()])?;
+
+        let ix = CreateMetadataAccountV3 {
+            metadata: *metadata_acc.key,
+            mint: *receipt_mint_acc.key,
+            mint_authority: *authority_acc.key,
+            payer: *staker_auth.key,
+            update_authority: (*authority_acc.key, true),
+            system_program: solana_program::system_program::id(),
+            rent: Some(*rent_sysvar_acc.key),
+        }
+        .instruction(CreateMetadataAccountV3InstructionArgs { data: receipt_data(&stake), is_mutable: true, collection_details: None });
+        invoke_signed(
+            &ix,
+            &[
+                metadata_acc.clone(),
+                receipt_mint_acc.clone(),
+                authority_acc.clone(),
+                staker_auth.clone(),
+                system_program_acc.clone(),
+                rent_sysvar_acc.clone(),
+            ],
+            &[authority_seeds],
+        )?;
+    } else {
+        update_metadata(metadata_acc, authority_acc, &stake, authority_bump)?;
+    }
+
+    if receipt_account_acc.lamports() == 0 {
+        invoke_signed(
+            &system_instruction::create_account(
+                staker_auth.key,
+                receipt_account_acc.key,
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                token_program_acc.key,
+            ),
+            &[staker_auth.clone(), receipt_account_acc.clone(), system_program_acc.clone()],
+            &[&[STAKE_RECEIPT_ACCOUNT_SEED, receipt_mint_acc.key.as_ref(), &[receipt_account_bump]]],
+        )?;
+        let ix = initialize_account3(token_program_acc.key, receipt_account_acc.key, receipt_mint_acc.key, staker_auth.key)?;
+        invoke(&ix, &[receipt_account_acc.clone(), receipt_mint_acc.clone(), token_program_acc.clone()])?;
+    }
+    token_program::mint_to(token_program_acc, receipt_mint_acc, receipt_account_acc, authority_acc, 1, &[authority_seeds])?;
+    if !transferable {
+        token_program::set_frozen(token_program_acc, receipt_account_acc, receipt_mint_acc, authority_acc, true, &[authority_seeds])?;
+    }
+
+    stake.has_receipt = true;
+    pack_state(&stake, staking_acc)?;
+    msg!("Minted stake receipt {} for {}", receipt_mint_acc.key, staking_acc.key);
+    Ok(())
+}
+
+/// Burns the receipt for a position, signed by whoever holds it, and closes
+/// the holder's token account. The position can then be unstaken.
+pub fn burn_stake_receipt(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let BurnStakeReceiptAccounts { staking_acc, receipt_mint_acc, holder_token_acc, holder_acc, authority_acc, token_program_acc, authority_bump } =
+        BurnStakeReceiptAccounts::try_from(program_id, accounts)?;
+
+    if load::<PodStake>(&staking_acc.try_borrow_data()?)?.has_receipt == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if token_program::unpack_account(holder_token_acc)?.is_frozen() {
+        let authority_seeds: &[&[u8]] = &[STAKE_RECEIPT_AUTHORITY_SEED, &[authority_bump]];
+        token_program::set_frozen(token_program_acc, holder_token_acc, receipt_mint_acc, authority_acc, false, &[authority_seeds])?;
+    }
+    token_program::burn(token_program_acc, holder_token_acc, receipt_mint_acc, holder_acc, 1, &[])?;
+    token_program::close_account(token_program_acc, holder_token_acc, holder_acc, holder_acc, &[])?;
+
+    load_mut::<PodStake>(&mut staking_acc.try_borrow_mut_data()?)?.has_receipt = 0;
+    msg!("Burned stake receipt {} for {}", receipt_mint_acc.key, staking_acc.key);
+    Ok(())
+}
+
+/// Rewrites a receipt's metadata from its position, after a top-up or a
+/// lock extension. Permissionless.
+pub fn refresh_stake_receipt(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let RefreshStakeReceiptAccounts { staking_acc, metadata_acc, authority_acc, authority_bump } =
+        RefreshStakeReceiptAccounts::try_from(program_id, accounts)?;
+
+    let stake = Stake::unpack(&staking_acc.try_borrow_data()?)?;
+    if !stake.has_receipt {
+        return Err(ProgramError::InvalidArgument);
+    }
+    update_metadata(metadata_acc, authority_acc, &stake, authority_bump)?;
+    msg!("Refreshed stake receipt for {}", staking_acc.key);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::GgtError;
+    use crate::staking_contract::{stake_address, staking_config_address, StakingConfig, StakingContract, StakingEconomics};
+    use crate::test_utils::{account_info, fund_rent_exempt, token_account_data, SimulatedBank};
+
+    #[test]
+    fn test_receipt_blocks_unstake_until_its_holder_burns_it() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_key = spl_token::id();
+        let (system_program_key, rent_key, metadata_program_key) =
+            (solana_program::system_program::id(), solana_program::sysvar::rent::id(), mpl_token_metadata::ID);
+        let (staker_key, mint_key, stranger_key, pool_state_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (staking_key, _) = stake_address(&program_id, &staker_key, &mint_key);
+        let (receipt_mint_key, _) = receipt_mint_address(&program_id, &staking_key);
+        let (receipt_account_key, _) = receipt_account_address(&program_id, &receipt_mint_key);
+        let (authority_key, _) = receipt_authority(&program_id);
+        let (metadata_key, _) = Metadata::find_pda(&receipt_mint_key);
+        let (config_key, _) = staking_config_address(&program_id);
+
+        let mut lamports = [0u64, 10_000_000, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10, l11, l12, l13] = &mut lamports;
+        let mut staking_data = vec![0u8; Stake::LEN];
+        Stake { amount: 1_000, lock_until: 5_000, staked_at: 1_000, is_initialized: true, ..Default::default() }
+            .pack_into_slice(&mut staking_data);
+        let mut receipt_mint_data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { decimals: 0, is_initialized: true, ..Default::default() }, &mut receipt_mint_data).unwrap();
+        let mut receipt_account_data = token_account_data(&receipt_mint_key, &staker_key, 0);
+        let mut config_data = vec![0u8; StakingConfig::LEN];
+        let economics = StakingEconomics {
+            penalty_bps_over_90_days: 0,
+            penalty_bps_over_30_days: 0,
+            penalty_bps: 0,
+            unstake_cooldown_seconds: 0,
+        };
+        StakingConfig::pack(StakingConfig { economics, last_econ_change: 0, is_initialized: true }, &mut config_data).unwrap();
+        let (mut d1, mut d2, mut d3, mut d4, mut d5, mut d6, mut d7, mut d8, mut d9, mut d10) =
+            (vec![], vec![], vec![], vec![], vec![], vec![], vec![], vec![], vec![], vec![]);
+
+        let staking_acc = account_info(&staking_key, false, true, l0, &mut staking_data, &program_id);
+        fund_rent_exempt(&[&staking_acc]);
+        let staker_acc = account_info(&staker_key, true, true, l1, &mut d1, &system_program_key);
+        let mint_acc = account_info(&mint_key, false, false, l2, &mut d2, &token_program_key);
+        let receipt_mint_acc = account_info(&receipt_mint_key, false, true, l3, &mut receipt_mint_data, &token_program_key);
+        let receipt_account_acc = account_info(&receipt_account_key, false, true, l4, &mut receipt_account_data, &token_program_key);
+        let metadata_acc = account_info(&metadata_key, false, true, l5, &mut d3, &system_program_key);
+        let authority_acc = account_info(&authority_key, false, false, l6, &mut d4, &program_id);
+        let token_program_acc = account_info(&token_program_key, false, false, l7, &mut d5, &system_program_key);
+        let system_program_acc = account_info(&system_program_key, false, false, l8, &mut d6, &system_program_key);
+        let rent_acc = account_info(&rent_key, false, false, l9, &mut d7, &system_program_key);
+        let metadata_program_acc = account_info(&metadata_program_key, false, false, l10, &mut d8, &system_program_key);
+        let stranger_acc = account_info(&stranger_key, true, true, l11, &mut d9, &system_program_key);
+        let pool_state_acc = account_info(&pool_state_key, false, true, l12, &mut d10, &program_id);
+        let config_acc = account_info(&config_key, false, false, l13, &mut config_data, &program_id);
+
+        let mint_accounts = [
+            staking_acc.clone(),
+            staker_acc.clone(),
+            mint_acc.clone(),
+            receipt_mint_acc.clone(),
+            receipt_account_acc.clone(),
+            metadata_acc.clone(),
+            authority_acc.clone(),
+            token_program_acc.clone(),
+            system_program_acc.clone(),
+            rent_acc.clone(),
+            metadata_program_acc.clone(),
+        ];
+        mint_stake_receipt(&program_id, &mint_accounts, false).unwrap();
+        assert_eq!(bank.balance(&receipt_account_key), 1);
+        assert!(Stake::unpack(&staking_acc.try_borrow_data().unwrap()).unwrap().has_receipt);
+        assert_eq!(mint_stake_receipt(&program_id, &mint_accounts, false), Err(ProgramError::AccountAlreadyInitialized));
+
+        bank.set_unix_timestamp(6_000);
+        let request_accounts = [staking_acc.clone(), staker_acc.clone(), mint_acc.clone(), pool_state_acc, config_acc];
+        assert_eq!(
+            StakingContract::new().request_unstake(&program_id, &request_accounts, 400),
+            Err(GgtError::StakeReceiptOutstanding.into())
+        );
+
+        let mut burn_accounts = [
+            staking_acc.clone(),
+            receipt_mint_acc.clone(),
+            receipt_account_acc.clone(),
+            stranger_acc,
+            authority_acc.clone(),
+            token_program_acc.clone(),
+        ];
+        assert_eq!(burn_stake_receipt(&program_id, &burn_accounts), Err(ProgramError::IllegalOwner));
+        burn_accounts[3] = staker_acc;
+        burn_stake_receipt(&program_id, &burn_accounts).unwrap();
+        assert_eq!(bank.balance(&receipt_account_key), 0);
+        assert!(!Stake::unpack(&staking_acc.try_borrow_data().unwrap()).unwrap().has_receipt);
+    }
+}
@@ -3,26 +3,209 @@ use solana_program::{
     clock::Clock,
     entrypoint::ProgramResult,
     msg,
-    program::invoke,
+    program::invoke_signed,
     program_error::ProgramError,
     program_pack::{Pack, Sealed, IsInitialized},
     pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
     sysvar::Sysvar,
 };
-use spl_token::instruction as token_instruction;
 use borsh_derive::{BorshDeserialize, BorshSerialize};
+use bytemuck::{Pod, Zeroable};
+use crate::{
+    config::{self, AuthorityRole},
+    denylist,
+    error::GgtError,
+    events, multisig, token_program,
+};
+use crate::state::pack_state;
+use crate::validation::{assert_ggt_mint, assert_owned_by, assert_pda, assert_signer, assert_token_account_mint};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN, HEADER_LEN};
+use crate::zero_copy::{load, ZeroCopy};
+
+pub const REWARDS_VAULT_SEED: &[u8] = b"rewards_vault";
+pub const STAKE_SEED: &[u8] = b"stake";
+pub const STAKE_POOL_SEED: &[u8] = b"stake_pool";
+pub const CO_STAKE_SEED: &[u8] = b"co_stake";
+pub const CO_STAKE_VAULT_SEED: &[u8] = b"co_stake_vault";
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+pub const STAKING_CONFIG_SEED: &[u8] = b"staking_config";
+pub const STAKE_SUMMARY_SEED: &[u8] = b"stake_summary";
+pub const EPOCH_SECONDS: i64 = 86_400;
+/// How long positions opened before an economics change may exit without penalty.
+pub const ECON_CHANGE_EXIT_WINDOW_SECONDS: i64 = 7 * 86_400;
 
 #[derive(Clone)]
-pub struct StakingContract {
+pub struct StakingContract;
+
+/// Stake account address for `staker` in `mint`.
+pub fn stake_address(program_id: &Pubkey, staker: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_SEED, staker.as_ref(), mint.as_ref()], program_id)
+}
+
+pub(crate) fn check_stake_account(program_id: &Pubkey, staking_acc: &AccountInfo, staker: &Pubkey, mint: &Pubkey) -> ProgramResult {
+    assert_owned_by(staking_acc, program_id)?;
+    assert_pda(staking_acc, stake_address(program_id, staker, mint))?;
+    Ok(())
+}
+
+/// Owner of the token accounts staking rewards are funded into and paid out of.
+pub fn rewards_vault_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REWARDS_VAULT_SEED], program_id)
+}
+
+/// Checks that `vault_acc` holds `mint` and is owned by the rewards vault PDA.
+fn check_rewards_vault(program_id: &Pubkey, vault_acc: &AccountInfo, mint: &Pubkey) -> ProgramResult {
+    if assert_token_account_mint(vault_acc, mint)?.owner != rewards_vault_authority(program_id).0 {
+        msg!("Rewards vault {} is not owned by the rewards vault PDA", vault_acc.key);
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
+
+/// Owner of the token account pooling everything staked in `mint`.
+pub fn stake_pool_authority(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_POOL_SEED, mint.as_ref()], program_id)
+}
+
+/// Checks that `pool_acc` holds `mint` and is owned by the stake pool PDA.
+pub(crate) fn check_stake_pool(program_id: &Pubkey, pool_acc: &AccountInfo, mint: &Pubkey) -> ProgramResult {
+    if assert_token_account_mint(pool_acc, mint)?.owner != stake_pool_authority(program_id, mint).0 {
+        msg!("Stake pool {} is not owned by the stake pool PDA", pool_acc.key);
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
+
+/// Dashboard summary address for `owner`.
+pub fn stake_summary_address(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_SUMMARY_SEED, owner.as_ref()], program_id)
+}
+
+fn check_stake_summary(program_id: &Pubkey, summary_acc: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    assert_owned_by(summary_acc, program_id)?;
+    assert_pda(summary_acc, stake_summary_address(program_id, owner))?;
+    Ok(())
+}
+
+/// Folds the change from `before` to `after` into the optional summary account.
+fn update_summary(summary_acc: Option<&AccountInfo>, before: &Stake, after: &Stake, now: i64) -> ProgramResult {
+    let Some(summary_acc) = summary_acc else {
+        return Ok(());
+    };
+    let mut summary = StakeSummary::unpack(&summary_acc.try_borrow_data()?)?;
+    summary.remove(before)?;
+    summary.add(after)?;
+    summary.updated_at = now;
+    pack_state(&summary, summary_acc)
+}
+
+/// Stake cannot leave a position while its receipt NFT is outstanding.
+fn check_no_receipt(stake: &Stake) -> ProgramResult {
+    if stake.has_receipt {
+        msg!("Burn the stake receipt before unstaking");
+        return Err(GgtError::StakeReceiptOutstanding.into());
+    }
+    Ok(())
+}
+
+pub fn staking_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKING_CONFIG_SEED], program_id)
+}
+
+pub(crate) fn load_staking_config(program_id: &Pubkey, config_acc: &AccountInfo) -> Result<StakingConfig, ProgramError> {
+    assert_owned_by(config_acc, program_id)?;
+    assert_pda(config_acc, staking_config_address(program_id))?;
+    StakingConfig::unpack(&config_acc.try_borrow_data()?)
+}
+
+/// Co-staking position address for `staker` in `pool`.
+pub fn co_stake_address(program_id: &Pubkey, pool: &Pubkey, staker: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CO_STAKE_SEED, pool.as_ref(), staker.as_ref()], program_id)
+}
+
+/// Authority over both vaults of the co-staking pool at `pool`.
+pub fn co_stake_vault_authority(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CO_STAKE_VAULT_SEED, pool.as_ref()], program_id)
+}
+
+fn load_co_staking_pool(
+    program_id: &Pubkey,
+    pool_acc: &AccountInfo,
+    (ggt_vault_acc, ggt_mint_acc): (&AccountInfo, &AccountInfo),
+    (partner_vault_acc, partner_mint_acc): (&AccountInfo, &AccountInfo),
+) -> Result<CoStakingPool, ProgramError> {
+    assert_owned_by(pool_acc, program_id)?;
+    let pool = CoStakingPool::unpack(&pool_acc.try_borrow_data()?)?;
+    if pool.ggt_vault != *ggt_vault_acc.key || pool.partner_vault != *partner_vault_acc.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if pool.ggt_mint != *ggt_mint_acc.key || pool.partner_mint != *partner_mint_acc.key {
+        msg!("Co-staking pool holds {} and {}", pool.ggt_mint, pool.partner_mint);
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(pool)
+}
+
+fn load_co_stake(program_id: &Pubkey, position_acc: &AccountInfo, pool: &Pubkey, staker: &Pubkey) -> Result<CoStake, ProgramError> {
+    assert_owned_by(position_acc, program_id)?;
+    assert_pda(position_acc, co_stake_address(program_id, pool, staker))?;
+    CoStake::unpack(&position_acc.try_borrow_data()?)
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct StakingPool {
     pub total_staked: u64,
+    /// Funded rewards not emitted yet, all held in the rewards vault.
     pub reward_pool: u64,
+    /// Penalties held in the rewards vault, waiting for stakers to be credited to.
     pub penalty_pool: u64,
+    pub reward_per_token_stored: u128,
+    pub last_update_time: i64,
+    /// Numbers the snapshots proposals are taken against; emission does not depend on it.
+    pub epoch: u64,
+    /// `roll_reward_epoch` may start the next epoch from here.
+    pub epoch_ends_at: i64,
+    /// Emissions schedule set by `fund_rewards`: this many tokens a second
+    /// until `rewards_end_at`.
+    pub reward_rate_per_second: u64,
+    pub rewards_end_at: i64,
+    pub is_initialized: bool,
+}
+
+/// Return data of `get_stake_info`: a stake position with its rewards accrued
+/// up to the query.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct StakeInfo {
+    pub amount: u64,
+    pub lock_until: i64,
+    pub claimable_rewards: u64,
+    pub pending_unstake: u64,
+    pub claimable_at: i64,
+    pub has_receipt: bool,
 }
 
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default)]
 pub struct Stake {
     pub amount: u64,
     pub lock_until: i64,
+    pub reward_per_token_paid: u128,
+    pub rewards_owed: u64,
+    /// When the position was opened; compared against `StakingConfig::last_econ_change`.
+    pub staked_at: i64,
+    /// Requested through `request_unstake`; no longer earns rewards.
+    pub pending_unstake: u64,
+    /// When `pending_unstake` may be withdrawn.
+    pub claimable_at: i64,
+    /// Votes with this stake's weight in place of the staker; the default
+    /// key means the staker votes themselves.
+    pub voting_delegate: Pubkey,
+    /// When `voting_delegate` last changed. The stake only votes on proposals
+    /// created after that.
+    pub delegated_at: i64,
+    /// A stake receipt NFT is outstanding; it must be burned before unstaking.
+    pub has_receipt: bool,
     pub is_initialized: bool,
 }
 
@@ -32,170 +215,2184 @@ impl IsInitialized for Stake {
     }
 }
 
-impl StakingContract {
-    pub fn new() -> Self {
-        StakingContract {
+/// Staking economics governance may change. Unstaking before `lock_until` pays
+/// a penalty tiered by the days left on the lock. With a non-zero
+/// `unstake_cooldown_seconds`, unlocked stake leaves penalty-free only through
+/// `request_unstake` and `withdraw_unstaked`; unstaking immediately pays
+/// `penalty_bps`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct StakingEconomics {
+    pub penalty_bps_over_90_days: u16,
+    pub penalty_bps_over_30_days: u16,
+    pub penalty_bps: u16,
+    pub unstake_cooldown_seconds: i64,
+}
+
+impl StakingEconomics {
+    pub const LEN: usize = 14; // u16 (2) * 3 + i64 (8)
+
+    pub(crate) fn validate(&self) -> ProgramResult {
+        let rates = [self.penalty_bps_over_90_days, self.penalty_bps_over_30_days, self.penalty_bps];
+        if rates.iter().any(|bps| *bps > 10_000) || self.unstake_cooldown_seconds < 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    pub fn early_unstake_penalty_bps(&self, remaining_days: i64) -> u16 {
+        if remaining_days > 90 {
+            self.penalty_bps_over_90_days
+        } else if remaining_days > 30 {
+            self.penalty_bps_over_30_days
+        } else {
+            self.penalty_bps
+        }
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[0..2].copy_from_slice(&self.penalty_bps_over_90_days.to_le_bytes());
+        dst[2..4].copy_from_slice(&self.penalty_bps_over_30_days.to_le_bytes());
+        dst[4..6].copy_from_slice(&self.penalty_bps.to_le_bytes());
+        dst[6..14].copy_from_slice(&self.unstake_cooldown_seconds.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Self {
+        StakingEconomics {
+            penalty_bps_over_90_days: u16::from_le_bytes(src[0..2].try_into().unwrap()),
+            penalty_bps_over_30_days: u16::from_le_bytes(src[2..4].try_into().unwrap()),
+            penalty_bps: u16::from_le_bytes(src[4..6].try_into().unwrap()),
+            unstake_cooldown_seconds: i64::from_le_bytes(src[6..14].try_into().unwrap()),
+        }
+    }
+}
+
+pub struct StakingConfig {
+    pub economics: StakingEconomics,
+    /// When governance last changed `economics`; zero if it never has.
+    pub last_econ_change: i64,
+    pub is_initialized: bool,
+}
+
+impl StakingConfig {
+    /// Whether a position opened at `staked_at` may still exit penalty-free
+    /// after the latest economics change.
+    pub fn in_exit_window(&self, staked_at: i64, now: i64) -> bool {
+        self.last_econ_change > 0 && staked_at < self.last_econ_change && now < self.last_econ_change + ECON_CHANGE_EXIT_WINDOW_SECONDS
+    }
+}
+
+impl Sealed for StakingConfig {}
+
+impl IsInitialized for StakingConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for StakingConfig {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [45, 134, 252, 82, 37, 57, 84, 25];
+}
+
+impl Pack for StakingConfig {
+    const LEN: usize = 32; // discriminator (8) + version (1) + StakingEconomics (14) + i64 (8) + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        self.economics.pack_into_slice(&mut dst[..StakingEconomics::LEN]);
+        dst[14..22].copy_from_slice(&self.last_econ_change.to_le_bytes());
+        dst[22] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(StakingConfig {
+            economics: StakingEconomics::unpack_from_slice(&src[..StakingEconomics::LEN]),
+            last_econ_change: i64::from_le_bytes(src[14..22].try_into().unwrap()),
+            is_initialized: src[22] != 0,
+        })
+    }
+}
+
+impl StakingPool {
+    /// An unfunded pool; it emits nothing until `fund_rewards` schedules emissions.
+    pub fn new(now: i64) -> Self {
+        StakingPool {
             total_staked: 0,
-            reward_pool: 15_000_000,
+            reward_pool: 0,
             penalty_pool: 0,
+            reward_per_token_stored: 0,
+            last_update_time: now,
+            epoch: 0,
+            epoch_ends_at: now + EPOCH_SECONDS,
+            reward_rate_per_second: 0,
+            rewards_end_at: now,
+            is_initialized: true,
         }
     }
 
-    pub fn stake_tokens(
-        &mut self,
-        _program_id: &Pubkey, // Prefixed with _ to suppress warning
-        accounts: &[AccountInfo],
-        amount: u64,
-        lock_period_in_days: u64,
-    ) -> ProgramResult {
+    /// Emits rewards for the time elapsed since the last update, up to the end of
+    /// the emissions schedule, into `reward_per_token_stored`. Time with nothing
+    /// staked emits nothing, leaving its share in the pool.
+    pub fn update_reward(&mut self, now: i64) -> ProgramResult {
+        let until = now.min(self.rewards_end_at);
+        if self.total_staked > 0 && until > self.last_update_time {
+            let elapsed = (until - self.last_update_time) as u128;
+            let emitted = (self.reward_rate_per_second as u128 * elapsed).min(self.reward_pool as u128) as u64;
+            let reward_per_token = (emitted as u128)
+                .checked_mul(REWARD_PRECISION)
+                .ok_or(GgtError::MathOverflow)?
+                / self.total_staked as u128;
+            self.reward_per_token_stored = self.reward_per_token_stored.checked_add(reward_per_token).ok_or(GgtError::MathOverflow)?;
+            self.reward_pool = self.reward_pool.checked_sub(emitted).ok_or(GgtError::MathOverflow)?;
+        }
+        self.last_update_time = self.last_update_time.max(until);
+        Ok(())
+    }
+
+    /// Closes the current epoch and starts the next at `now`. Stakers settle
+    /// lazily against the global index on their next interaction, so rolling
+    /// never touches stake accounts.
+    pub fn roll_epoch(&mut self, now: i64) -> ProgramResult {
+        if now < self.epoch_ends_at {
+            return Err(GgtError::EpochNotEnded.into());
+        }
+        self.update_reward(now)?;
+        self.epoch = self.epoch.checked_add(1).ok_or(GgtError::MathOverflow)?;
+        self.epoch_ends_at = now.checked_add(EPOCH_SECONDS).ok_or(GgtError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Adds `amount` of freshly funded rewards and replaces the schedule from
+    /// `now`, after emitting what the old one owed. Whatever the old schedule
+    /// left unemitted carries over, but the pool must cover the whole new one.
+    pub fn schedule_rewards(&mut self, now: i64, amount: u64, reward_rate_per_second: u64, rewards_end_at: i64) -> ProgramResult {
+        if rewards_end_at <= now {
+            return Err(ProgramError::InvalidArgument);
+        }
+        self.update_reward(now)?;
+        let reward_pool = self.reward_pool.checked_add(amount).ok_or(GgtError::MathOverflow)?;
+        let scheduled = reward_rate_per_second as u128 * (rewards_end_at - now) as u128;
+        if scheduled > reward_pool as u128 {
+            msg!("Schedule emits {} but the reward pool would hold {}", scheduled, reward_pool);
+            return Err(GgtError::RewardsUnderfunded.into());
+        }
+        self.reward_pool = reward_pool;
+        self.last_update_time = now;
+        self.reward_rate_per_second = reward_rate_per_second;
+        self.rewards_end_at = rewards_end_at;
+        Ok(())
+    }
+
+    pub fn earned(&self, stake: &Stake) -> Result<u64, ProgramError> {
+        let index_delta = self.reward_per_token_stored.checked_sub(stake.reward_per_token_paid).ok_or(GgtError::MathOverflow)?;
+        let pending = (stake.amount as u128).checked_mul(index_delta).ok_or(GgtError::MathOverflow)? / REWARD_PRECISION;
+        let pending = u64::try_from(pending).map_err(|_| GgtError::MathOverflow)?;
+        Ok(stake.rewards_owed.checked_add(pending).ok_or(GgtError::MathOverflow)?)
+    }
+
+    /// Moves everything accrued by `stake` into `rewards_owed` and checkpoints it at the current index.
+    pub fn settle(&self, stake: &mut Stake) -> ProgramResult {
+        stake.rewards_owed = self.earned(stake)?;
+        stake.reward_per_token_paid = self.reward_per_token_stored;
+        Ok(())
+    }
+
+    /// Confiscates `bps` of `stake`, staked and pending alike, into the penalty
+    /// pool after settling its rewards, and returns the amount taken.
+    pub fn slash(&mut self, stake: &mut Stake, bps: u16, now: i64) -> Result<u64, ProgramError> {
+        if bps == 0 || bps > 10_000 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        self.update_reward(now)?;
+        self.settle(stake)?;
+        let staked = (stake.amount as u128 * bps as u128 / 10_000) as u64;
+        let pending = (stake.pending_unstake as u128 * bps as u128 / 10_000) as u64;
+        stake.amount = stake.amount.checked_sub(staked).ok_or(GgtError::MathOverflow)?;
+        stake.pending_unstake = stake.pending_unstake.checked_sub(pending).ok_or(GgtError::MathOverflow)?;
+        self.total_staked = self.total_staked.checked_sub(staked).ok_or(GgtError::MathOverflow)?;
+        let slashed = staked.checked_add(pending).ok_or(GgtError::MathOverflow)?;
+        self.penalty_pool = self.penalty_pool.checked_add(slashed).ok_or(GgtError::MathOverflow)?;
+        Ok(slashed)
+    }
+
+    /// Credits the penalty pool to stakers through the reward index. Callers
+    /// move the penalized tokens into the rewards vault before crediting them.
+    pub fn redistribute_penalty(&mut self) -> ProgramResult {
+        if self.total_staked == 0 || self.penalty_pool == 0 {
+            return Ok(());
+        }
+        let reward_per_token = (self.penalty_pool as u128)
+            .checked_mul(REWARD_PRECISION)
+            .ok_or(GgtError::MathOverflow)?
+            / self.total_staked as u128;
+        self.reward_per_token_stored = self.reward_per_token_stored.checked_add(reward_per_token).ok_or(GgtError::MathOverflow)?;
+        self.penalty_pool = 0;
+        msg!("Redistributed penalty: {} per token (scaled)", reward_per_token);
+        Ok(())
+    }
+}
+
+impl Default for StakingContract {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The admin multisig authority follows `pool_state_acc`; it is only checked.
+pub struct InitializePoolAccounts<'a, 'info> {
+    pub pool_state_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> InitializePoolAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let pool_state_acc = next_account_info(account_info_iter)?;
+        let admin_acc = next_account_info(account_info_iter)?;
+
+        multisig::check_admin_signer(program_id, admin_acc)?;
+        Ok(Self { pool_state_acc })
+    }
+}
+
+pub struct InitializeStakingConfigAccounts<'a, 'info> {
+    pub config_acc: &'a AccountInfo<'info>,
+    pub admin_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub config_bump: u8,
+}
+
+impl<'a, 'info> InitializeStakingConfigAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let config_acc = next_account_info(account_info_iter)?;
+        let admin_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        multisig::check_admin_signer(program_id, admin_acc)?;
+        let config_bump = assert_pda(config_acc, staking_config_address(program_id))?;
+        Ok(Self { config_acc, admin_acc, system_program_acc, config_bump })
+    }
+}
+
+/// The governance signer follows `config_acc`, then the program config it is
+/// checked against; neither is kept.
+pub struct SetStakingEconomicsAccounts<'a, 'info> {
+    pub config_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> SetStakingEconomicsAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let config_acc = next_account_info(account_info_iter)?;
+        let governance_acc = next_account_info(account_info_iter)?;
+        let program_config_acc = next_account_info(account_info_iter)?;
+
+        config::check_authority(program_id, program_config_acc, &[AuthorityRole::Governance], governance_acc)?;
+        Ok(Self { config_acc })
+    }
+}
+
+pub struct InitStakeAccounts<'a, 'info> {
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub staker_auth: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub stake_bump: u8,
+}
+
+impl<'a, 'info> InitStakeAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let staking_acc = next_account_info(account_info_iter)?;
+        let staker_auth = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(staker_auth)?;
+        let stake_bump = assert_pda(staking_acc, stake_address(program_id, staker_auth.key, mint_acc.key))?;
+        Ok(Self { staking_acc, staker_auth, mint_acc, system_program_acc, stake_bump })
+    }
+}
+
+/// Accounts shared by `stake_tokens` and `add_to_stake`. Neither the staker
+/// nor their token account may be on the denylist.
+pub struct StakeAccounts<'a, 'info> {
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub staker_acc: &'a AccountInfo<'info>,
+    pub pool_acc: &'a AccountInfo<'info>,
+    pub staker_auth: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub pool_state_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub summary_acc: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> StakeAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
         let account_info_iter = &mut accounts.iter();
         let staking_acc = next_account_info(account_info_iter)?;
         let staker_acc = next_account_info(account_info_iter)?;
         let pool_acc = next_account_info(account_info_iter)?;
         let staker_auth = next_account_info(account_info_iter)?;
         let token_program_acc = next_account_info(account_info_iter)?;
+        let pool_state_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let denylist_acc = next_account_info(account_info_iter)?;
+        let summary_acc = next_account_info(account_info_iter).ok();
 
-        if !staker_auth.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+        assert_signer(staker_auth)?;
+        denylist::check_not_denylisted(program_id, denylist_acc, &[staker_auth.key, staker_acc.key])?;
+        // Every stake adds to the one staking pool, so only GGT can be staked.
+        assert_ggt_mint(program_id, mint_acc)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        check_stake_account(program_id, staking_acc, staker_auth.key, mint_acc.key)?;
+        assert_owned_by(pool_state_acc, program_id)?;
+        assert_token_account_mint(staker_acc, mint_acc.key)?;
+        check_stake_pool(program_id, pool_acc, mint_acc.key)?;
+        if let Some(summary_acc) = summary_acc {
+            check_stake_summary(program_id, summary_acc, staker_auth.key)?;
         }
+        Ok(Self { staking_acc, staker_acc, pool_acc, staker_auth, token_program_acc, pool_state_acc, mint_acc, summary_acc })
+    }
+}
 
-        if *token_program_acc.key != spl_token::id() {
-            return Err(ProgramError::InvalidAccountData);
+/// The staker signer and mint follow `staking_acc`; they only derive and authorize it.
+pub struct ExtendLockAccounts<'a, 'info> {
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub summary_acc: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> ExtendLockAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let staking_acc = next_account_info(account_info_iter)?;
+        let staker_auth = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let summary_acc = next_account_info(account_info_iter).ok();
+
+        assert_signer(staker_auth)?;
+        check_stake_account(program_id, staking_acc, staker_auth.key, mint_acc.key)?;
+        if let Some(summary_acc) = summary_acc {
+            check_stake_summary(program_id, summary_acc, staker_auth.key)?;
         }
+        Ok(Self { staking_acc, summary_acc })
+    }
+}
 
-        let stake_data = Stake {
-            amount,
-            lock_until: Clock::get()?.unix_timestamp + (lock_period_in_days as i64 * 86400),
-            is_initialized: true,
-        };
-        let mut staking_data = staking_acc.try_borrow_mut_data()?;
-        stake_data.pack_into_slice(&mut staking_data);
+pub struct SetVotingDelegateAccounts<'a, 'info> {
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub staker_auth: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> SetVotingDelegateAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let staking_acc = next_account_info(account_info_iter)?;
+        let staker_auth = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(staker_auth)?;
+        check_stake_account(program_id, staking_acc, staker_auth.key, mint_acc.key)?;
+        Ok(Self { staking_acc, staker_auth, mint_acc })
+    }
+}
+
+pub struct UnstakeAccounts<'a, 'info> {
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub pool_acc: &'a AccountInfo<'info>,
+    pub pool_authority_acc: &'a AccountInfo<'info>,
+    pub staker_acc: &'a AccountInfo<'info>,
+    pub staker_auth: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub pool_state_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub staking_config_acc: &'a AccountInfo<'info>,
+    pub rewards_vault_acc: &'a AccountInfo<'info>,
+    pub summary_acc: Option<&'a AccountInfo<'info>>,
+    pub pool_authority_bump: u8,
+}
+
+impl<'a, 'info> UnstakeAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let staking_acc = next_account_info(account_info_iter)?;
+        let pool_acc = next_account_info(account_info_iter)?;
+        let pool_authority_acc = next_account_info(account_info_iter)?;
+        let staker_acc = next_account_info(account_info_iter)?;
+        let staker_auth = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let pool_state_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let staking_config_acc = next_account_info(account_info_iter)?;
+        let rewards_vault_acc = next_account_info(account_info_iter)?;
+        let summary_acc = next_account_info(account_info_iter).ok();
+
+        assert_signer(staker_auth)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        check_stake_account(program_id, staking_acc, staker_auth.key, mint_acc.key)?;
+        assert_owned_by(pool_state_acc, program_id)?;
+        assert_token_account_mint(staker_acc, mint_acc.key)?;
+        check_rewards_vault(program_id, rewards_vault_acc, mint_acc.key)?;
+        if let Some(summary_acc) = summary_acc {
+            check_stake_summary(program_id, summary_acc, staker_auth.key)?;
+        }
+        let pool_authority_bump = assert_pda(pool_authority_acc, stake_pool_authority(program_id, mint_acc.key))?;
+        Ok(Self {
+            staking_acc,
+            pool_acc,
+            pool_authority_acc,
+            staker_acc,
+            staker_auth,
+            token_program_acc,
+            pool_state_acc,
+            mint_acc,
+            staking_config_acc,
+            rewards_vault_acc,
+            summary_acc,
+            pool_authority_bump,
+        })
+    }
+}
+
+pub struct StakeInfoAccounts<'a, 'info> {
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub pool_state_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> StakeInfoAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self { staking_acc: next_account_info(account_info_iter)?, pool_state_acc: next_account_info(account_info_iter)? };
+        assert_owned_by(ctx.staking_acc, program_id)?;
+        assert_owned_by(ctx.pool_state_acc, program_id)?;
+        Ok(ctx)
+    }
+}
+
+/// The staker signer sits between `staker_acc` and `token_program_acc`; it is only checked.
+pub struct ClaimRewardsAccounts<'a, 'info> {
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub pool_state_acc: &'a AccountInfo<'info>,
+    pub rewards_vault_acc: &'a AccountInfo<'info>,
+    pub vault_authority_acc: &'a AccountInfo<'info>,
+    pub staker_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub summary_acc: Option<&'a AccountInfo<'info>>,
+    pub vault_authority_bump: u8,
+}
+
+impl<'a, 'info> ClaimRewardsAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let staking_acc = next_account_info(account_info_iter)?;
+        let pool_state_acc = next_account_info(account_info_iter)?;
+        let rewards_vault_acc = next_account_info(account_info_iter)?;
+        let vault_authority_acc = next_account_info(account_info_iter)?;
+        let staker_acc = next_account_info(account_info_iter)?;
+        let staker_auth = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let summary_acc = next_account_info(account_info_iter).ok();
+
+        assert_signer(staker_auth)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        check_stake_account(program_id, staking_acc, staker_auth.key, mint_acc.key)?;
+        assert_owned_by(pool_state_acc, program_id)?;
+        assert_token_account_mint(staker_acc, mint_acc.key)?;
+        if let Some(summary_acc) = summary_acc {
+            check_stake_summary(program_id, summary_acc, staker_auth.key)?;
+        }
+        let vault_authority_bump = assert_pda(vault_authority_acc, rewards_vault_authority(program_id))?;
+        Ok(Self {
+            staking_acc,
+            pool_state_acc,
+            rewards_vault_acc,
+            vault_authority_acc,
+            staker_acc,
+            token_program_acc,
+            mint_acc,
+            summary_acc,
+            vault_authority_bump,
+        })
+    }
+}
+
+/// The staker signer and mint sit between `staking_acc` and `pool_state_acc`;
+/// they only derive and authorize the stake account.
+pub struct RequestUnstakeAccounts<'a, 'info> {
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub pool_state_acc: &'a AccountInfo<'info>,
+    pub staking_config_acc: &'a AccountInfo<'info>,
+    pub summary_acc: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> RequestUnstakeAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let staking_acc = next_account_info(account_info_iter)?;
+        let staker_auth = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let pool_state_acc = next_account_info(account_info_iter)?;
+        let staking_config_acc = next_account_info(account_info_iter)?;
+        let summary_acc = next_account_info(account_info_iter).ok();
+
+        assert_signer(staker_auth)?;
+        check_stake_account(program_id, staking_acc, staker_auth.key, mint_acc.key)?;
+        assert_owned_by(pool_state_acc, program_id)?;
+        if let Some(summary_acc) = summary_acc {
+            check_stake_summary(program_id, summary_acc, staker_auth.key)?;
+        }
+        Ok(Self { staking_acc, pool_state_acc, staking_config_acc, summary_acc })
+    }
+}
+
+pub struct WithdrawUnstakedAccounts<'a, 'info> {
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub pool_acc: &'a AccountInfo<'info>,
+    pub pool_authority_acc: &'a AccountInfo<'info>,
+    pub staker_acc: &'a AccountInfo<'info>,
+    pub staker_auth: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub pool_authority_bump: u8,
+}
+
+impl<'a, 'info> WithdrawUnstakedAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let staking_acc = next_account_info(account_info_iter)?;
+        let pool_acc = next_account_info(account_info_iter)?;
+        let pool_authority_acc = next_account_info(account_info_iter)?;
+        let staker_acc = next_account_info(account_info_iter)?;
+        let staker_auth = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(staker_auth)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        check_stake_account(program_id, staking_acc, staker_auth.key, mint_acc.key)?;
+        assert_token_account_mint(staker_acc, mint_acc.key)?;
+        let pool_authority_bump = assert_pda(pool_authority_acc, stake_pool_authority(program_id, mint_acc.key))?;
+        Ok(Self { staking_acc, pool_acc, pool_authority_acc, staker_acc, staker_auth, token_program_acc, mint_acc, pool_authority_bump })
+    }
+}
+
+/// `positions` holds `(stake account, mint)` pairs, each checked to be the
+/// owner's stake PDA for that mint.
+pub struct SyncStakeSummaryAccounts<'a, 'info> {
+    pub summary_acc: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub positions: &'a [AccountInfo<'info>],
+    pub summary_bump: u8,
+}
+
+impl<'a, 'info> SyncStakeSummaryAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let summary_acc = next_account_info(account_info_iter)?;
+        let owner_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+        let positions = account_info_iter.as_slice();
+
+        assert_signer(owner_acc)?;
+        let summary_bump = assert_pda(summary_acc, stake_summary_address(program_id, owner_acc.key))?;
+        if !positions.len().is_multiple_of(2) {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        for (i, pair) in positions.chunks(2).enumerate() {
+            check_stake_account(program_id, &pair[0], owner_acc.key, pair[1].key)?;
+            if positions[..i * 2].chunks(2).any(|seen| seen[0].key == pair[0].key) {
+                msg!("Stake account {} listed twice", pair[0].key);
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+        Ok(Self { summary_acc, owner_acc, system_program_acc, positions, summary_bump })
+    }
+}
+
+/// The admin multisig authority follows `pool_acc`, and the GGT mint follows
+/// the partner vault; both are only checked.
+pub struct InitializeCoStakingPoolAccounts<'a, 'info> {
+    pub pool_acc: &'a AccountInfo<'info>,
+    pub ggt_vault_acc: &'a AccountInfo<'info>,
+    pub partner_vault_acc: &'a AccountInfo<'info>,
+    pub rewards_vault_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> InitializeCoStakingPoolAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let pool_acc = next_account_info(account_info_iter)?;
+        let admin_acc = next_account_info(account_info_iter)?;
+        let ggt_vault_acc = next_account_info(account_info_iter)?;
+        let partner_vault_acc = next_account_info(account_info_iter)?;
+        let ggt_mint_acc = next_account_info(account_info_iter)?;
+        let rewards_vault_acc = next_account_info(account_info_iter)?;
+
+        multisig::check_admin_signer(program_id, admin_acc)?;
+        assert_owned_by(pool_acc, program_id)?;
+        // Co-stakers are paid from the GGT rewards vault, so the GGT side must be GGT.
+        assert_ggt_mint(program_id, ggt_mint_acc)?;
+        assert_token_account_mint(ggt_vault_acc, ggt_mint_acc.key)?;
+        check_rewards_vault(program_id, rewards_vault_acc, ggt_mint_acc.key)?;
+        Ok(Self { pool_acc, ggt_vault_acc, partner_vault_acc, rewards_vault_acc })
+    }
+}
+
+pub struct CoStakeAccounts<'a, 'info> {
+    pub position_acc: &'a AccountInfo<'info>,
+    pub pool_acc: &'a AccountInfo<'info>,
+    pub staker_auth: &'a AccountInfo<'info>,
+    pub staker_ggt_acc: &'a AccountInfo<'info>,
+    pub staker_partner_acc: &'a AccountInfo<'info>,
+    pub ggt_vault_acc: &'a AccountInfo<'info>,
+    pub partner_vault_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub ggt_mint_acc: &'a AccountInfo<'info>,
+    pub partner_mint_acc: &'a AccountInfo<'info>,
+    pub position_bump: u8,
+}
+
+impl<'a, 'info> CoStakeAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let position_acc = next_account_info(account_info_iter)?;
+        let pool_acc = next_account_info(account_info_iter)?;
+        let staker_auth = next_account_info(account_info_iter)?;
+        let staker_ggt_acc = next_account_info(account_info_iter)?;
+        let staker_partner_acc = next_account_info(account_info_iter)?;
+        let ggt_vault_acc = next_account_info(account_info_iter)?;
+        let partner_vault_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+        let ggt_mint_acc = next_account_info(account_info_iter)?;
+        let partner_mint_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(staker_auth)?;
+        token_program::check_token_program(token_program_acc, ggt_mint_acc)?;
+        token_program::check_token_program(token_program_acc, partner_mint_acc)?;
+        let position_bump = assert_pda(position_acc, co_stake_address(program_id, pool_acc.key, staker_auth.key))?;
+        Ok(Self {
+            position_acc,
+            pool_acc,
+            staker_auth,
+            staker_ggt_acc,
+            staker_partner_acc,
+            ggt_vault_acc,
+            partner_vault_acc,
+            token_program_acc,
+            system_program_acc,
+            ggt_mint_acc,
+            partner_mint_acc,
+            position_bump,
+        })
+    }
+}
+
+pub struct CoUnstakeAccounts<'a, 'info> {
+    pub position_acc: &'a AccountInfo<'info>,
+    pub pool_acc: &'a AccountInfo<'info>,
+    pub staker_auth: &'a AccountInfo<'info>,
+    pub staker_ggt_acc: &'a AccountInfo<'info>,
+    pub staker_partner_acc: &'a AccountInfo<'info>,
+    pub ggt_vault_acc: &'a AccountInfo<'info>,
+    pub partner_vault_acc: &'a AccountInfo<'info>,
+    pub vault_authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub ggt_mint_acc: &'a AccountInfo<'info>,
+    pub partner_mint_acc: &'a AccountInfo<'info>,
+    pub vault_authority_bump: u8,
+}
+
+impl<'a, 'info> CoUnstakeAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let position_acc = next_account_info(account_info_iter)?;
+        let pool_acc = next_account_info(account_info_iter)?;
+        let staker_auth = next_account_info(account_info_iter)?;
+        let staker_ggt_acc = next_account_info(account_info_iter)?;
+        let staker_partner_acc = next_account_info(account_info_iter)?;
+        let ggt_vault_acc = next_account_info(account_info_iter)?;
+        let partner_vault_acc = next_account_info(account_info_iter)?;
+        let vault_authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let ggt_mint_acc = next_account_info(account_info_iter)?;
+        let partner_mint_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(staker_auth)?;
+        token_program::check_token_program(token_program_acc, ggt_mint_acc)?;
+        token_program::check_token_program(token_program_acc, partner_mint_acc)?;
+        let vault_authority_bump = assert_pda(vault_authority_acc, co_stake_vault_authority(program_id, pool_acc.key))?;
+        Ok(Self {
+            position_acc,
+            pool_acc,
+            staker_auth,
+            staker_ggt_acc,
+            staker_partner_acc,
+            ggt_vault_acc,
+            partner_vault_acc,
+            vault_authority_acc,
+            token_program_acc,
+            ggt_mint_acc,
+            partner_mint_acc,
+            vault_authority_bump,
+        })
+    }
+}
+
+pub struct ClaimCoStakeRewardsAccounts<'a, 'info> {
+    pub position_acc: &'a AccountInfo<'info>,
+    pub pool_acc: &'a AccountInfo<'info>,
+    pub staker_auth: &'a AccountInfo<'info>,
+    pub staker_ggt_acc: &'a AccountInfo<'info>,
+    pub rewards_vault_acc: &'a AccountInfo<'info>,
+    pub vault_authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub ggt_mint_acc: &'a AccountInfo<'info>,
+    pub vault_authority_bump: u8,
+    pub pool: CoStakingPool,
+}
+
+impl<'a, 'info> ClaimCoStakeRewardsAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let position_acc = next_account_info(account_info_iter)?;
+        let pool_acc = next_account_info(account_info_iter)?;
+        let staker_auth = next_account_info(account_info_iter)?;
+        let staker_ggt_acc = next_account_info(account_info_iter)?;
+        let rewards_vault_acc = next_account_info(account_info_iter)?;
+        let vault_authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let ggt_mint_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(staker_auth)?;
+        token_program::check_token_program(token_program_acc, ggt_mint_acc)?;
+        let vault_authority_bump = assert_pda(vault_authority_acc, rewards_vault_authority(program_id))?;
+        assert_owned_by(pool_acc, program_id)?;
+        let pool = CoStakingPool::unpack(&pool_acc.try_borrow_data()?)?;
+        if pool.ggt_mint != *ggt_mint_acc.key || pool.rewards_vault != *rewards_vault_acc.key {
+            msg!("Co-staking rewards are paid from {}", pool.rewards_vault);
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(Self {
+            position_acc,
+            pool_acc,
+            staker_auth,
+            staker_ggt_acc,
+            rewards_vault_acc,
+            vault_authority_acc,
+            token_program_acc,
+            ggt_mint_acc,
+            vault_authority_bump,
+            pool,
+        })
+    }
+}
+
+pub struct ConvertLegacyStakeAccounts<'a, 'info> {
+    pub legacy_acc: &'a AccountInfo<'info>,
+    pub staker_auth: &'a AccountInfo<'info>,
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub pool_state_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub staker_acc: &'a AccountInfo<'info>,
+    pub pool_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub stake_bump: u8,
+}
+
+impl<'a, 'info> ConvertLegacyStakeAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let legacy_acc = next_account_info(account_info_iter)?;
+        let staker_auth = next_account_info(account_info_iter)?;
+        let staking_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let pool_state_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+        let staker_acc = next_account_info(account_info_iter)?;
+        let pool_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let denylist_acc = next_account_info(account_info_iter)?;
+
+        if !legacy_acc.is_signer || !staker_auth.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if legacy_acc.owner != program_id || pool_state_acc.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        denylist::check_not_denylisted(program_id, denylist_acc, &[staker_auth.key, staker_acc.key])?;
+        // Legacy stakes were GGT stakes, and the position must be derived with that mint.
+        assert_ggt_mint(program_id, mint_acc)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        let stake_bump = assert_pda(staking_acc, stake_address(program_id, staker_auth.key, mint_acc.key))?;
+        assert_token_account_mint(staker_acc, mint_acc.key)?;
+        check_stake_pool(program_id, pool_acc, mint_acc.key)?;
+        Ok(Self {
+            legacy_acc,
+            staker_auth,
+            staking_acc,
+            mint_acc,
+            pool_state_acc,
+            system_program_acc,
+            staker_acc,
+            pool_acc,
+            token_program_acc,
+            stake_bump,
+        })
+    }
+}
+
+pub struct RollRewardEpochAccounts<'a, 'info> {
+    pub pool_state_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> RollRewardEpochAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let pool_state_acc = next_account_info(account_info_iter)?;
+
+        assert_owned_by(pool_state_acc, program_id)?;
+        Ok(Self { pool_state_acc })
+    }
+}
+
+/// The governance signer follows `token_program_acc`, then the program config
+/// it is checked against; neither is kept. The staker does not sign.
+pub struct SlashAccounts<'a, 'info> {
+    pub staking_acc: &'a AccountInfo<'info>,
+    pub pool_state_acc: &'a AccountInfo<'info>,
+    pub staker_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub pool_acc: &'a AccountInfo<'info>,
+    pub pool_authority_acc: &'a AccountInfo<'info>,
+    pub rewards_vault_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub summary_acc: Option<&'a AccountInfo<'info>>,
+    pub pool_authority_bump: u8,
+}
+
+impl<'a, 'info> SlashAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let staking_acc = next_account_info(account_info_iter)?;
+        let pool_state_acc = next_account_info(account_info_iter)?;
+        let staker_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let pool_acc = next_account_info(account_info_iter)?;
+        let pool_authority_acc = next_account_info(account_info_iter)?;
+        let rewards_vault_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let governance_acc = next_account_info(account_info_iter)?;
+        let program_config_acc = next_account_info(account_info_iter)?;
+        let summary_acc = next_account_info(account_info_iter).ok();
+
+        config::check_authority(program_id, program_config_acc, &[AuthorityRole::Governance], governance_acc)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        check_stake_account(program_id, staking_acc, staker_acc.key, mint_acc.key)?;
+        assert_owned_by(pool_state_acc, program_id)?;
+        check_stake_pool(program_id, pool_acc, mint_acc.key)?;
+        check_rewards_vault(program_id, rewards_vault_acc, mint_acc.key)?;
+        if let Some(summary_acc) = summary_acc {
+            check_stake_summary(program_id, summary_acc, staker_acc.key)?;
+        }
+        let pool_authority_bump = assert_pda(pool_authority_acc, stake_pool_authority(program_id, mint_acc.key))?;
+        Ok(Self {
+            staking_acc,
+            pool_state_acc,
+            staker_acc,
+            mint_acc,
+            pool_acc,
+            pool_authority_acc,
+            rewards_vault_acc,
+            token_program_acc,
+            summary_acc,
+            pool_authority_bump,
+        })
+    }
+}
+
+/// The admin is the multisig authority and also owns `admin_token_acc`.
+pub struct FundRewardsAccounts<'a, 'info> {
+    pub pool_state_acc: &'a AccountInfo<'info>,
+    pub admin_acc: &'a AccountInfo<'info>,
+    pub admin_token_acc: &'a AccountInfo<'info>,
+    pub rewards_vault_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> FundRewardsAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let pool_state_acc = next_account_info(account_info_iter)?;
+        let admin_acc = next_account_info(account_info_iter)?;
+        let admin_token_acc = next_account_info(account_info_iter)?;
+        let rewards_vault_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+
+        multisig::check_admin_signer(program_id, admin_acc)?;
+        assert_owned_by(pool_state_acc, program_id)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        check_rewards_vault(program_id, rewards_vault_acc, mint_acc.key)?;
+        Ok(Self { pool_state_acc, admin_acc, admin_token_acc, rewards_vault_acc, mint_acc, token_program_acc })
+    }
+}
+
+impl StakingContract {
+    pub fn new() -> Self {
+        StakingContract
+    }
+
+    pub fn initialize_pool(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let InitializePoolAccounts { pool_state_acc } = InitializePoolAccounts::try_from(program_id, accounts)?;
+
+        let existing = StakingPool::unpack_unchecked(&pool_state_acc.try_borrow_data()?)?;
+        if existing.is_initialized {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let pool = StakingPool::new(Clock::get()?.unix_timestamp);
+        pack_state(&pool, pool_state_acc)?;
+        msg!("Staking pool initialized");
+        Ok(())
+    }
+
+    pub fn initialize_staking_config(&self, program_id: &Pubkey, accounts: &[AccountInfo], economics: StakingEconomics) -> ProgramResult {
+        let InitializeStakingConfigAccounts { config_acc, admin_acc, system_program_acc, config_bump } =
+            InitializeStakingConfigAccounts::try_from(program_id, accounts)?;
+        if config_acc.lamports() > 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        economics.validate()?;
+
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_acc.key,
+                config_acc.key,
+                Rent::get()?.minimum_balance(StakingConfig::LEN),
+                StakingConfig::LEN as u64,
+                program_id,
+            ),
+            &[admin_acc.clone(), config_acc.clone(), system_program_acc.clone()],
+            &[&[STAKING_CONFIG_SEED, &[config_bump]]],
+        )?;
+        let config = StakingConfig { economics, last_econ_change: 0, is_initialized: true };
+        pack_state(&config, config_acc)?;
+        msg!("Staking config initialized");
+        Ok(())
+    }
+
+    /// Replaces the staking economics. Positions opened before the change may
+    /// unstake without penalty for `ECON_CHANGE_EXIT_WINDOW_SECONDS`.
+    pub fn set_staking_economics(&self, program_id: &Pubkey, accounts: &[AccountInfo], economics: StakingEconomics) -> ProgramResult {
+        let SetStakingEconomicsAccounts { config_acc } = SetStakingEconomicsAccounts::try_from(program_id, accounts)?;
+        economics.validate()?;
+        let mut config = load_staking_config(program_id, config_acc)?;
+        if config.economics == economics {
+            return Ok(());
+        }
+        config.economics = economics;
+        config.last_econ_change = Clock::get()?.unix_timestamp;
+        let changed_at = config.last_econ_change;
+        pack_state(&config, config_acc)?;
+        msg!("Staking economics changed; penalty-free exit window open until {}", changed_at + ECON_CHANGE_EXIT_WINDOW_SECONDS);
+        Ok(())
+    }
+
+    /// Creates the caller's stake PDA for `mint`, funded by the staker.
+    pub fn init_stake_account(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let InitStakeAccounts { staking_acc, staker_auth, mint_acc, system_program_acc, stake_bump } =
+            InitStakeAccounts::try_from(program_id, accounts)?;
+        let stake_key = *staking_acc.key;
+        if staking_acc.lamports() > 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                staker_auth.key,
+                staking_acc.key,
+                rent.minimum_balance(Stake::LEN),
+                Stake::LEN as u64,
+                program_id,
+            ),
+            &[staker_auth.clone(), staking_acc.clone(), system_program_acc.clone()],
+            &[&[STAKE_SEED, staker_auth.key.as_ref(), mint_acc.key.as_ref(), &[stake_bump]]],
+        )?;
+
+        let stake = Stake {
+            amount: 0,
+            lock_until: 0,
+            reward_per_token_paid: 0,
+            rewards_owed: 0,
+            staked_at: 0,
+            pending_unstake: 0,
+            claimable_at: 0,
+            voting_delegate: Pubkey::default(),
+            delegated_at: 0,
+            has_receipt: false,
+            is_initialized: true,
+        };
+        pack_state(&stake, staking_acc)?;
+        msg!("Initialized stake account {}", stake_key);
+        Ok(())
+    }
+
+    pub fn stake_tokens(
+        &mut self,
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        lock_period_in_days: u64,
+    ) -> ProgramResult {
+        let StakeAccounts {
+            staking_acc,
+            staker_acc,
+            pool_acc,
+            staker_auth,
+            token_program_acc,
+            pool_state_acc,
+            mint_acc,
+            summary_acc,
+        } = StakeAccounts::try_from(program_id, accounts)?;
+
+        let existing = Stake::unpack_unchecked(&staking_acc.try_borrow_data()?)?;
+        if existing.is_initialized && existing.amount > 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut pool = StakingPool::unpack(&pool_state_acc.try_borrow_data()?)?;
+        pool.update_reward(now)?;
+
+        let stake_data = Stake {
+            amount,
+            lock_until: i64::try_from(lock_period_in_days)
+                .ok()
+                .and_then(|days| days.checked_mul(86400))
+                .and_then(|lock| now.checked_add(lock))
+                .ok_or(GgtError::MathOverflow)?,
+            reward_per_token_paid: pool.reward_per_token_stored,
+            rewards_owed: existing.rewards_owed,
+            staked_at: now,
+            // Tokens still cooling down from an earlier unstake stay withdrawable.
+            pending_unstake: existing.pending_unstake,
+            claimable_at: existing.claimable_at,
+            voting_delegate: existing.voting_delegate,
+            delegated_at: existing.delegated_at,
+            has_receipt: false,
+            is_initialized: true,
+        };
+        pack_state(&stake_data, staking_acc)?;
+        update_summary(summary_acc, &existing, &stake_data, now)?;
+
+        token_program::transfer(token_program_acc, staker_acc, mint_acc, pool_acc, staker_auth, amount, &[])?;
+        events::emit(&events::StakeEvent {
+            staker: *staker_auth.key,
+            mint: *mint_acc.key,
+            amount,
+            lock_until: stake_data.lock_until,
+        });
+
+        pool.total_staked = pool.total_staked.checked_add(amount).ok_or(GgtError::MathOverflow)?;
+        pack_state(&pool, pool_state_acc)?;
+        msg!("Staked {} tokens for {} days", amount, lock_period_in_days);
+        Ok(())
+    }
+
+    /// Adds `amount` to an open position. Rewards accrued so far are settled
+    /// first; the lock is kept, and the position counts as opened now, so a
+    /// top-up accepts the current staking economics.
+    pub fn add_to_stake(&mut self, program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+        let StakeAccounts {
+            staking_acc,
+            staker_acc,
+            pool_acc,
+            staker_auth,
+            token_program_acc,
+            pool_state_acc,
+            mint_acc,
+            summary_acc,
+        } = StakeAccounts::try_from(program_id, accounts)?;
+
+        let mut stake_data = Stake::unpack(&staking_acc.try_borrow_data()?)?;
+        let before = stake_data.clone();
+        if stake_data.amount == 0 {
+            msg!("No open position; use stake_tokens");
+            return Err(ProgramError::UninitializedAccount);
+        }
+        let now = Clock::get()?.unix_timestamp;
+        let mut pool = StakingPool::unpack(&pool_state_acc.try_borrow_data()?)?;
+        pool.update_reward(now)?;
+        pool.settle(&mut stake_data)?;
+        stake_data.amount = stake_data.amount.checked_add(amount).ok_or(GgtError::MathOverflow)?;
+        stake_data.staked_at = now;
+        pool.total_staked = pool.total_staked.checked_add(amount).ok_or(GgtError::MathOverflow)?;
+        let lock_until = stake_data.lock_until;
+        pack_state(&stake_data, staking_acc)?;
+        update_summary(summary_acc, &before, &stake_data, now)?;
+        pack_state(&pool, pool_state_acc)?;
+
+        token_program::transfer(token_program_acc, staker_acc, mint_acc, pool_acc, staker_auth, amount, &[])?;
+        events::emit(&events::StakeEvent { staker: *staker_auth.key, mint: *mint_acc.key, amount, lock_until });
+        msg!("Added {} tokens to stake", amount);
+        Ok(())
+    }
+
+    /// Relocks a position for `lock_period_in_days` from now. The new lock may not
+    /// end before the current one.
+    pub fn extend_lock(&mut self, program_id: &Pubkey, accounts: &[AccountInfo], lock_period_in_days: u64) -> ProgramResult {
+        let ExtendLockAccounts { staking_acc, summary_acc } = ExtendLockAccounts::try_from(program_id, accounts)?;
+
+        let mut stake_data = Stake::unpack(&staking_acc.try_borrow_data()?)?;
+        let before = stake_data.clone();
+        let now = Clock::get()?.unix_timestamp;
+        let lock_until = (lock_period_in_days as i64)
+            .checked_mul(86400)
+            .and_then(|seconds| seconds.checked_add(now))
+            .ok_or(ProgramError::InvalidArgument)?;
+        if stake_data.amount == 0 || lock_until < stake_data.lock_until {
+            msg!("Lock can only move forward from {}", stake_data.lock_until);
+            return Err(ProgramError::InvalidArgument);
+        }
+        stake_data.lock_until = lock_until;
+        pack_state(&stake_data, staking_acc)?;
+        update_summary(summary_acc, &before, &stake_data, now)?;
+        msg!("Extended lock until {}", lock_until);
+        Ok(())
+    }
+
+    /// Points the stake's voting weight at `delegate`, or back at the staker
+    /// if it is the default key. The tokens stay in the stake.
+    pub fn set_voting_delegate(&self, program_id: &Pubkey, accounts: &[AccountInfo], delegate: Pubkey) -> ProgramResult {
+        let SetVotingDelegateAccounts { staking_acc, staker_auth, mint_acc } = SetVotingDelegateAccounts::try_from(program_id, accounts)?;
+        let mut stake = Stake::unpack(&staking_acc.try_borrow_data()?)?;
+        if stake.voting_delegate == delegate {
+            return Err(ProgramError::InvalidArgument);
+        }
+        stake.voting_delegate = delegate;
+        stake.delegated_at = Clock::get()?.unix_timestamp;
+        pack_state(&stake, staking_acc)?;
+        events::emit(&events::VotingDelegateSet { staker: *staker_auth.key, mint: *mint_acc.key, delegate });
+        msg!("Voting weight of {} delegated to {}", staker_auth.key, delegate);
+        Ok(())
+    }
+
+    pub fn unstake_tokens(
+        &mut self,
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        let UnstakeAccounts {
+            staking_acc,
+            pool_acc,
+            pool_authority_acc,
+            staker_acc,
+            staker_auth,
+            token_program_acc,
+            pool_state_acc,
+            mint_acc,
+            staking_config_acc,
+            rewards_vault_acc,
+            summary_acc,
+            pool_authority_bump,
+        } = UnstakeAccounts::try_from(program_id, accounts)?;
+        let config = load_staking_config(program_id, staking_config_acc)?;
+
+        let mut stake_data = Stake::unpack(&staking_acc.try_borrow_data()?)?;
+        let before = stake_data.clone();
+        if stake_data.amount < amount {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        check_no_receipt(&stake_data)?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let cooldown = config.economics.unstake_cooldown_seconds;
+        let penalty_bps = if current_time >= stake_data.lock_until && cooldown == 0 {
+            0
+        } else if config.in_exit_window(stake_data.staked_at, current_time) {
+            msg!("Economics changed at {}; unstaking without penalty", config.last_econ_change);
+            0
+        } else {
+            if current_time >= stake_data.lock_until {
+                msg!("Skipping the {}s unstake cooldown", cooldown);
+            }
+            config.economics.early_unstake_penalty_bps((stake_data.lock_until - current_time) / 86400)
+        };
+
+        let penalty_amount = (amount as u128 * penalty_bps as u128 / 10_000) as u64;
+        let final_amount = amount.saturating_sub(penalty_amount);
+
+        let mut pool = StakingPool::unpack(&pool_state_acc.try_borrow_data()?)?;
+        pool.update_reward(current_time)?;
+        pool.settle(&mut stake_data)?;
+
+        stake_data.amount = stake_data.amount.checked_sub(amount).ok_or(GgtError::MathOverflow)?;
+        pack_state(&stake_data, staking_acc)?;
+        update_summary(summary_acc, &before, &stake_data, current_time)?;
+
+        pool.total_staked = pool.total_staked.checked_sub(amount).ok_or(GgtError::MathOverflow)?;
+        pool.penalty_pool = pool.penalty_pool.checked_add(penalty_amount).ok_or(GgtError::MathOverflow)?;
+
+        let pool_seeds: &[&[u8]] = &[STAKE_POOL_SEED, mint_acc.key.as_ref(), &[pool_authority_bump]];
+        token_program::transfer(token_program_acc, pool_acc, mint_acc, staker_acc, pool_authority_acc, final_amount, &[pool_seeds])?;
+        if penalty_amount > 0 {
+            // The penalty is paid out as rewards, so it must sit where claims are paid from.
+            token_program::transfer(token_program_acc, pool_acc, mint_acc, rewards_vault_acc, pool_authority_acc, penalty_amount, &[pool_seeds])?;
+        }
+        events::emit(&events::UnstakeEvent {
+            staker: *staker_auth.key,
+            mint: *mint_acc.key,
+            amount: final_amount,
+            penalty: penalty_amount,
+        });
+
+        pool.redistribute_penalty()?;
+        pack_state(&pool, pool_state_acc)?;
+        msg!("Unstaked {} tokens with penalty {}", final_amount, penalty_amount);
+        Ok(())
+    }
+
+    /// Starts the unstake cooldown for `amount` of an unlocked position. The
+    /// amount stops earning rewards now and can be withdrawn penalty-free with
+    /// `withdraw_unstaked` once the cooldown has passed. A new request adds to
+    /// the pending amount and restarts the cooldown.
+    pub fn request_unstake(&mut self, program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+        let RequestUnstakeAccounts { staking_acc, pool_state_acc, staking_config_acc, summary_acc } =
+            RequestUnstakeAccounts::try_from(program_id, accounts)?;
+        let config = load_staking_config(program_id, staking_config_acc)?;
+
+        let mut stake_data = Stake::unpack(&staking_acc.try_borrow_data()?)?;
+        let before = stake_data.clone();
+        if amount == 0 || stake_data.amount < amount {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        check_no_receipt(&stake_data)?;
+        let now = Clock::get()?.unix_timestamp;
+        if now < stake_data.lock_until && !config.in_exit_window(stake_data.staked_at, now) {
+            msg!("Stake is locked until {}", stake_data.lock_until);
+            return Err(GgtError::StakeLocked.into());
+        }
+
+        let mut pool = StakingPool::unpack(&pool_state_acc.try_borrow_data()?)?;
+        pool.update_reward(now)?;
+        pool.settle(&mut stake_data)?;
+        stake_data.amount = stake_data.amount.checked_sub(amount).ok_or(GgtError::MathOverflow)?;
+        stake_data.pending_unstake = stake_data.pending_unstake.checked_add(amount).ok_or(GgtError::MathOverflow)?;
+        stake_data.claimable_at = now.checked_add(config.economics.unstake_cooldown_seconds).ok_or(GgtError::MathOverflow)?;
+        pool.total_staked = pool.total_staked.checked_sub(amount).ok_or(GgtError::MathOverflow)?;
+        let claimable_at = stake_data.claimable_at;
+        pack_state(&stake_data, staking_acc)?;
+        update_summary(summary_acc, &before, &stake_data, now)?;
+        pack_state(&pool, pool_state_acc)?;
+        msg!("Requested unstake of {}; claimable at {}", amount, claimable_at);
+        Ok(())
+    }
+
+    /// Pays out the pending unstake once its cooldown has passed.
+    pub fn withdraw_unstaked(&mut self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let WithdrawUnstakedAccounts {
+            staking_acc,
+            pool_acc,
+            pool_authority_acc,
+            staker_acc,
+            staker_auth,
+            token_program_acc,
+            mint_acc,
+            pool_authority_bump,
+        } = WithdrawUnstakedAccounts::try_from(program_id, accounts)?;
+
+        let mut stake_data = Stake::unpack(&staking_acc.try_borrow_data()?)?;
+        let amount = stake_data.pending_unstake;
+        if amount == 0 {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        if Clock::get()?.unix_timestamp < stake_data.claimable_at {
+            msg!("Unstake cooldown ends at {}", stake_data.claimable_at);
+            return Err(GgtError::CooldownNotElapsed.into());
+        }
+        stake_data.pending_unstake = 0;
+        stake_data.claimable_at = 0;
+        pack_state(&stake_data, staking_acc)?;
+
+        token_program::transfer(
+            token_program_acc,
+            pool_acc,
+            mint_acc,
+            staker_acc,
+            pool_authority_acc,
+            amount,
+            &[&[STAKE_POOL_SEED, mint_acc.key.as_ref(), &[pool_authority_bump]]],
+        )?;
+        events::emit(&events::UnstakeEvent { staker: *staker_auth.key, mint: *mint_acc.key, amount, penalty: 0 });
+        msg!("Withdrew {} unstaked tokens", amount);
+        Ok(())
+    }
+
+    pub fn claim_rewards(&mut self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let ClaimRewardsAccounts {
+            staking_acc,
+            pool_state_acc,
+            rewards_vault_acc,
+            vault_authority_acc,
+            staker_acc,
+            token_program_acc,
+            mint_acc,
+            summary_acc,
+            vault_authority_bump,
+        } = ClaimRewardsAccounts::try_from(program_id, accounts)?;
+
+        let mut stake_data = Stake::unpack(&staking_acc.try_borrow_data()?)?;
+        let before = stake_data.clone();
+        let mut pool = StakingPool::unpack(&pool_state_acc.try_borrow_data()?)?;
+        let now = Clock::get()?.unix_timestamp;
+        pool.update_reward(now)?;
+        pool.settle(&mut stake_data)?;
+
+        let reward = stake_data.rewards_owed;
+        if reward == 0 {
+            msg!("No rewards to claim");
+            return Ok(());
+        }
+        stake_data.rewards_owed = 0;
+        pack_state(&stake_data, staking_acc)?;
+        update_summary(summary_acc, &before, &stake_data, now)?;
+        pack_state(&pool, pool_state_acc)?;
+
+        token_program::transfer(
+            token_program_acc,
+            rewards_vault_acc,
+            mint_acc,
+            staker_acc,
+            vault_authority_acc,
+            reward,
+            &[&[REWARDS_VAULT_SEED, &[vault_authority_bump]]],
+        )?;
+
+        msg!("Claimed {} reward tokens", reward);
+        Ok(())
+    }
+
+    /// Admin sets up a pool where GGT must be staked alongside `partner_ratio_bps`
+    /// of a partner token. Co-stakers earn rewards on `boost_bps` more weight than
+    /// their GGT alone would carry.
+    pub fn initialize_co_staking_pool(
+        &self,
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        partner_ratio_bps: u64,
+        boost_bps: u64,
+    ) -> ProgramResult {
+        let InitializeCoStakingPoolAccounts { pool_acc, ggt_vault_acc, partner_vault_acc, rewards_vault_acc } =
+            InitializeCoStakingPoolAccounts::try_from(program_id, accounts)?;
+        if CoStakingPool::unpack_unchecked(&pool_acc.try_borrow_data()?)?.rewards.is_initialized {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        if partner_ratio_bps == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let (vault_authority, _) = co_stake_vault_authority(program_id, pool_acc.key);
+        let ggt_vault = token_program::unpack_account(ggt_vault_acc)?;
+        let partner_vault = token_program::unpack_account(partner_vault_acc)?;
+        if ggt_vault.owner != vault_authority || partner_vault.owner != vault_authority {
+            msg!("Co-staking vaults must be owned by the pool's vault authority PDA");
+            return Err(ProgramError::IllegalOwner);
+        }
+        if ggt_vault.mint == partner_vault.mint {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let pool = CoStakingPool {
+            ggt_mint: ggt_vault.mint,
+            partner_mint: partner_vault.mint,
+            ggt_vault: *ggt_vault_acc.key,
+            partner_vault: *partner_vault_acc.key,
+            partner_ratio_bps,
+            boost_bps,
+            rewards: StakingPool::new(Clock::get()?.unix_timestamp),
+            rewards_vault: *rewards_vault_acc.key,
+        };
+        pack_state(&pool, pool_acc)?;
+        msg!("Co-staking pool initialized");
+        Ok(())
+    }
+
+    /// Stakes `ggt_amount` together with exactly the partner amount the pool ratio
+    /// requires, creating the staker's position PDA on first use.
+    pub fn co_stake(
+        &self,
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ggt_amount: u64,
+        partner_amount: u64,
+    ) -> ProgramResult {
+        let CoStakeAccounts {
+            position_acc,
+            pool_acc,
+            staker_auth,
+            staker_ggt_acc,
+            staker_partner_acc,
+            ggt_vault_acc,
+            partner_vault_acc,
+            token_program_acc,
+            system_program_acc,
+            ggt_mint_acc,
+            partner_mint_acc,
+            position_bump,
+        } = CoStakeAccounts::try_from(program_id, accounts)?;
+        let mut pool = load_co_staking_pool(program_id, pool_acc, (ggt_vault_acc, ggt_mint_acc), (partner_vault_acc, partner_mint_acc))?;
+        if ggt_amount == 0 || partner_amount != pool.required_partner_amount(ggt_amount)? {
+            return Err(GgtError::CoStakeRatioMismatch.into());
+        }
+
+        if position_acc.lamports() == 0 {
+            invoke_signed(
+                &system_instruction::create_account(
+                    staker_auth.key,
+                    position_acc.key,
+                    Rent::get()?.minimum_balance(CoStake::LEN),
+                    CoStake::LEN as u64,
+                    program_id,
+                ),
+                &[staker_auth.clone(), position_acc.clone(), system_program_acc.clone()],
+                &[&[CO_STAKE_SEED, pool_acc.key.as_ref(), staker_auth.key.as_ref(), &[position_bump]]],
+            )?;
+        }
+        let mut position = CoStake::unpack_unchecked(&position_acc.try_borrow_data()?)?;
+
+        pool.rewards.update_reward(Clock::get()?.unix_timestamp)?;
+        pool.rewards.settle(&mut position.weight)?;
+        let weight = pool.boosted_weight(ggt_amount)?;
+        position.ggt_amount = position.ggt_amount.checked_add(ggt_amount).ok_or(GgtError::MathOverflow)?;
+        position.partner_amount = position.partner_amount.checked_add(partner_amount).ok_or(GgtError::MathOverflow)?;
+        position.weight.amount = position.weight.amount.checked_add(weight).ok_or(GgtError::MathOverflow)?;
+        position.weight.is_initialized = true;
+        pool.rewards.total_staked = pool.rewards.total_staked.checked_add(weight).ok_or(GgtError::MathOverflow)?;
+        pack_state(&position, position_acc)?;
+        pack_state(&pool, pool_acc)?;
+
+        for (source, mint, vault, amount) in
+            [(staker_ggt_acc, ggt_mint_acc, ggt_vault_acc, ggt_amount), (staker_partner_acc, partner_mint_acc, partner_vault_acc, partner_amount)]
+        {
+            token_program::transfer(token_program_acc, source, mint, vault, staker_auth, amount, &[])?;
+        }
+        msg!("Co-staked {} GGT with {} partner tokens", ggt_amount, partner_amount);
+        Ok(())
+    }
+
+    /// Withdraws `ggt_amount` of GGT and the same share of the position's partner
+    /// tokens, so the remaining position keeps the ratio it was opened at.
+    pub fn co_unstake(&self, program_id: &Pubkey, accounts: &[AccountInfo], ggt_amount: u64) -> ProgramResult {
+        let CoUnstakeAccounts {
+            position_acc,
+            pool_acc,
+            staker_auth,
+            staker_ggt_acc,
+            staker_partner_acc,
+            ggt_vault_acc,
+            partner_vault_acc,
+            vault_authority_acc,
+            token_program_acc,
+            ggt_mint_acc,
+            partner_mint_acc,
+            vault_authority_bump,
+        } = CoUnstakeAccounts::try_from(program_id, accounts)?;
+        let mut pool = load_co_staking_pool(program_id, pool_acc, (ggt_vault_acc, ggt_mint_acc), (partner_vault_acc, partner_mint_acc))?;
+        let mut position = load_co_stake(program_id, position_acc, pool_acc.key, staker_auth.key)?;
+        if ggt_amount == 0 || ggt_amount > position.ggt_amount {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        pool.rewards.update_reward(Clock::get()?.unix_timestamp)?;
+        pool.rewards.settle(&mut position.weight)?;
+        let partner_amount = (position.partner_amount as u128 * ggt_amount as u128 / position.ggt_amount as u128) as u64;
+        let weight = (position.weight.amount as u128 * ggt_amount as u128 / position.ggt_amount as u128) as u64;
+        position.ggt_amount = position.ggt_amount.checked_sub(ggt_amount).ok_or(GgtError::MathOverflow)?;
+        position.partner_amount = position.partner_amount.checked_sub(partner_amount).ok_or(GgtError::MathOverflow)?;
+        position.weight.amount = position.weight.amount.checked_sub(weight).ok_or(GgtError::MathOverflow)?;
+        pool.rewards.total_staked = pool.rewards.total_staked.checked_sub(weight).ok_or(GgtError::MathOverflow)?;
+        pack_state(&position, position_acc)?;
+        pack_state(&pool, pool_acc)?;
+
+        let vault_seeds: &[&[u8]] = &[CO_STAKE_VAULT_SEED, pool_acc.key.as_ref(), &[vault_authority_bump]];
+        for (vault, mint, destination, amount) in
+            [(ggt_vault_acc, ggt_mint_acc, staker_ggt_acc, ggt_amount), (partner_vault_acc, partner_mint_acc, staker_partner_acc, partner_amount)]
+        {
+            token_program::transfer(token_program_acc, vault, mint, destination, vault_authority_acc, amount, &[vault_seeds])?;
+        }
+        msg!("Co-unstaked {} GGT and {} partner tokens", ggt_amount, partner_amount);
+        Ok(())
+    }
+
+    /// Pays a co-staker's boosted rewards from the shared rewards vault.
+    pub fn claim_co_stake_rewards(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let ClaimCoStakeRewardsAccounts {
+            position_acc,
+            pool_acc,
+            staker_auth,
+            staker_ggt_acc,
+            rewards_vault_acc,
+            vault_authority_acc,
+            token_program_acc,
+            ggt_mint_acc,
+            vault_authority_bump,
+            mut pool,
+        } = ClaimCoStakeRewardsAccounts::try_from(program_id, accounts)?;
+        let mut position = load_co_stake(program_id, position_acc, pool_acc.key, staker_auth.key)?;
+        pool.rewards.update_reward(Clock::get()?.unix_timestamp)?;
+        pool.rewards.settle(&mut position.weight)?;
+
+        let reward = position.weight.rewards_owed;
+        if reward == 0 {
+            msg!("No rewards to claim");
+            return Ok(());
+        }
+        position.weight.rewards_owed = 0;
+        pack_state(&position, position_acc)?;
+        pack_state(&pool, pool_acc)?;
+
+        let vault_seeds: &[&[u8]] = &[REWARDS_VAULT_SEED, &[vault_authority_bump]];
+        token_program::transfer(token_program_acc, rewards_vault_acc, ggt_mint_acc, staker_ggt_acc, vault_authority_acc, reward, &[vault_seeds])?;
+        msg!("Claimed {} co-staking reward tokens", reward);
+        Ok(())
+    }
+
+    /// Moves a pre-PDA 17-byte stake account into the staker's stake PDA for `mint`,
+    /// checkpointed at the current reward index, and closes the old account to the
+    /// staker. Legacy accounts store no owner, so the old account's keypair must sign.
+    /// Legacy stakes paid into a vault the program never checked, so the staker
+    /// deposits the amount into the stake pool again as part of the conversion.
+    pub fn convert_legacy_stake(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let ConvertLegacyStakeAccounts {
+            legacy_acc,
+            staker_auth,
+            staking_acc,
+            mint_acc,
+            pool_state_acc,
+            system_program_acc,
+            staker_acc,
+            pool_acc,
+            token_program_acc,
+            stake_bump,
+        } = ConvertLegacyStakeAccounts::try_from(program_id, accounts)?;
+        let legacy = LegacyStake::unpack_from_slice(&legacy_acc.try_borrow_data()?)?;
+        let stake_key = *staking_acc.key;
+        let mut existing = Stake::default();
+        if staking_acc.lamports() == 0 {
+            invoke_signed(
+                &system_instruction::create_account(
+                    staker_auth.key,
+                    staking_acc.key,
+                    Rent::get()?.minimum_balance(Stake::LEN),
+                    Stake::LEN as u64,
+                    program_id,
+                ),
+                &[staker_auth.clone(), staking_acc.clone(), system_program_acc.clone()],
+                &[&[STAKE_SEED, staker_auth.key.as_ref(), mint_acc.key.as_ref(), &[stake_bump]]],
+            )?;
+        } else {
+            check_stake_account(program_id, staking_acc, staker_auth.key, mint_acc.key)?;
+            existing = Stake::unpack_unchecked(&staking_acc.try_borrow_data()?)?;
+            if existing.amount > 0 {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+        }
+
+        token_program::transfer(token_program_acc, staker_acc, mint_acc, pool_acc, staker_auth, legacy.amount, &[])?;
+        let mut pool = StakingPool::unpack(&pool_state_acc.try_borrow_data()?)?;
+        pool.update_reward(Clock::get()?.unix_timestamp)?;
+        // Legacy stakes were only ever counted in memory, never in the pool account.
+        pool.total_staked = pool.total_staked.checked_add(legacy.amount).ok_or(GgtError::MathOverflow)?;
+        let stake = Stake {
+            amount: legacy.amount,
+            lock_until: legacy.lock_until,
+            reward_per_token_paid: pool.reward_per_token_stored,
+            rewards_owed: 0,
+            // Legacy positions predate every economics change.
+            staked_at: 0,
+            pending_unstake: existing.pending_unstake,
+            claimable_at: existing.claimable_at,
+            voting_delegate: Pubkey::default(),
+            delegated_at: 0,
+            has_receipt: false,
+            is_initialized: true,
+        };
+        pack_state(&stake, staking_acc)?;
+        pack_state(&pool, pool_state_acc)?;
+
+        let lamports = legacy_acc.lamports();
+        **staker_auth.try_borrow_mut_lamports()? = staker_auth.lamports().checked_add(lamports).ok_or(GgtError::MathOverflow)?;
+        **legacy_acc.try_borrow_mut_lamports()? = 0;
+        legacy_acc.try_borrow_mut_data()?.fill(0);
+        msg!("Converted legacy stake of {} into {}", legacy.amount, stake_key);
+        Ok(())
+    }
+
+    /// Permissionless crank rolling a staking or co-staking pool into its next epoch.
+    pub fn roll_reward_epoch(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let RollRewardEpochAccounts { pool_state_acc } = RollRewardEpochAccounts::try_from(program_id, accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        let epoch = if pool_state_acc.data_len() == CoStakingPool::LEN {
+            let mut pool = CoStakingPool::unpack(&pool_state_acc.try_borrow_data()?)?;
+            pool.rewards.roll_epoch(now)?;
+            let epoch = pool.rewards.epoch;
+            pack_state(&pool, pool_state_acc)?;
+            epoch
+        } else {
+            let mut pool = StakingPool::unpack(&pool_state_acc.try_borrow_data()?)?;
+            pool.roll_epoch(now)?;
+            let epoch = pool.epoch;
+            pack_state(&pool, pool_state_acc)?;
+            epoch
+        };
+        msg!("Rolled reward epoch to {}", epoch);
+        Ok(())
+    }
+
+    /// Admin moves `amount` into the rewards vault for a staking or co-staking
+    /// pool and sets it to emit `reward_rate_per_second` until `rewards_end_at`.
+    /// The pool's unemitted rewards plus `amount` must cover the schedule.
+    pub fn fund_rewards(
+        &self,
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        reward_rate_per_second: u64,
+        rewards_end_at: i64,
+    ) -> ProgramResult {
+        let FundRewardsAccounts { pool_state_acc, admin_acc, admin_token_acc, rewards_vault_acc, mint_acc, token_program_acc } =
+            FundRewardsAccounts::try_from(program_id, accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        if pool_state_acc.data_len() == CoStakingPool::LEN {
+            let mut pool = CoStakingPool::unpack(&pool_state_acc.try_borrow_data()?)?;
+            pool.rewards.schedule_rewards(now, amount, reward_rate_per_second, rewards_end_at)?;
+            pack_state(&pool, pool_state_acc)?;
+        } else {
+            let mut pool = StakingPool::unpack(&pool_state_acc.try_borrow_data()?)?;
+            pool.schedule_rewards(now, amount, reward_rate_per_second, rewards_end_at)?;
+            pack_state(&pool, pool_state_acc)?;
+        }
+        if amount > 0 {
+            token_program::transfer(token_program_acc, admin_token_acc, mint_acc, rewards_vault_acc, admin_acc, amount, &[])?;
+        }
+        msg!("Funded {} rewards emitting {} a second until {}", amount, reward_rate_per_second, rewards_end_at);
+        Ok(())
+    }
+
+    /// Governance confiscates `bps` of a staker's position into the penalty
+    /// pool, where it is shared among the remaining stakers. The slashed
+    /// tokens move from the stake pool into the rewards vault the shares are
+    /// claimed from. `reason_hash` ties the slash to its off-chain record in
+    /// the emitted event.
+    pub fn slash(&self, program_id: &Pubkey, accounts: &[AccountInfo], bps: u16, reason_hash: [u8; 32]) -> ProgramResult {
+        let SlashAccounts {
+            staking_acc,
+            pool_state_acc,
+            staker_acc,
+            mint_acc,
+            pool_acc,
+            pool_authority_acc,
+            rewards_vault_acc,
+            token_program_acc,
+            summary_acc,
+            pool_authority_bump,
+        } = SlashAccounts::try_from(program_id, accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        let mut stake = Stake::unpack(&staking_acc.try_borrow_data()?)?;
+        let before = stake.clone();
+        let mut pool = StakingPool::unpack(&pool_state_acc.try_borrow_data()?)?;
+        let amount = pool.slash(&mut stake, bps, now)?;
+        if amount > 0 {
+            let pool_seeds: &[&[u8]] = &[STAKE_POOL_SEED, mint_acc.key.as_ref(), &[pool_authority_bump]];
+            token_program::transfer(token_program_acc, pool_acc, mint_acc, rewards_vault_acc, pool_authority_acc, amount, &[pool_seeds])?;
+        }
+        pool.redistribute_penalty()?;
+        pack_state(&stake, staking_acc)?;
+        update_summary(summary_acc, &before, &stake, now)?;
+        pack_state(&pool, pool_state_acc)?;
+        events::emit(&events::StakeSlashed { staker: *staker_acc.key, mint: *mint_acc.key, amount, reason_hash });
+        msg!("Slashed {} staked tokens from {}", amount, staker_acc.key);
+        Ok(())
+    }
+
+    /// Creates the caller's stake summary if needed and rebuilds it from the
+    /// listed positions. Stake instructions keep it current from then on when
+    /// it is passed as their optional last account; a call made without it
+    /// leaves the summary stale, and one it no longer adds up for fails with
+    /// `MathOverflow`, until the next sync.
+    pub fn sync_stake_summary(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let SyncStakeSummaryAccounts { summary_acc, owner_acc, system_program_acc, positions, summary_bump } =
+            SyncStakeSummaryAccounts::try_from(program_id, accounts)?;
+
+        if summary_acc.lamports() == 0 {
+            let rent = Rent::get()?;
+            invoke_signed(
+                &system_instruction::create_account(
+                    owner_acc.key,
+                    summary_acc.key,
+                    rent.minimum_balance(StakeSummary::LEN),
+                    StakeSummary::LEN as u64,
+                    program_id,
+                ),
+                &[owner_acc.clone(), summary_acc.clone(), system_program_acc.clone()],
+                &[&[STAKE_SUMMARY_SEED, owner_acc.key.as_ref(), &[summary_bump]]],
+            )?;
+        } else {
+            assert_owned_by(summary_acc, program_id)?;
+        }
+
+        let mut summary = StakeSummary { owner: *owner_acc.key, is_initialized: true, ..Default::default() };
+        for pair in positions.chunks(2) {
+            summary.add(&Stake::unpack(&pair[0].try_borrow_data()?)?)?;
+        }
+        summary.updated_at = Clock::get()?.unix_timestamp;
+        let (total_staked, positions) = (summary.total_staked, summary.positions);
+        pack_state(&summary, summary_acc)?;
+        msg!("Stake summary: {} staked across {} positions", total_staked, positions);
+        Ok(())
+    }
+
+    /// Read-only query: publishes the position's `StakeInfo` as Borsh return
+    /// data, accruing the pool in memory so the rewards are current.
+    pub fn get_stake_info(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let StakeInfoAccounts { staking_acc, pool_state_acc } = StakeInfoAccounts::try_from(program_id, accounts)?;
+        let stake = Stake::unpack(&staking_acc.try_borrow_data()?)?;
+        let mut pool = StakingPool::unpack(&pool_state_acc.try_borrow_data()?)?;
+        pool.update_reward(Clock::get()?.unix_timestamp)?;
+        let info = StakeInfo {
+            amount: stake.amount,
+            lock_until: stake.lock_until,
+            claimable_rewards: pool.earned(&stake)?,
+            pending_unstake: stake.pending_unstake,
+            claimable_at: stake.claimable_at,
+            has_receipt: stake.has_receipt,
+        };
+        crate::set_borsh_return_data(&info);
+        Ok(())
+    }
+
+    pub fn get_staked_amount(&self, staking_acc: &AccountInfo) -> Result<u64, ProgramError> {
+        Ok(load::<PodStake>(&staking_acc.try_borrow_data()?)?.amount)
+    }
+}
+
+impl Discriminator for Stake {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [150, 197, 176, 29, 55, 132, 112, 149];
+}
+
+/// Zero-copy view of a `Stake` account after its discriminator.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct PodStake {
+    pub amount: u64,
+    pub lock_until: i64,
+    pub reward_per_token_paid: u128,
+    pub rewards_owed: u64,
+    pub staked_at: i64,
+    pub pending_unstake: u64,
+    pub claimable_at: i64,
+    pub voting_delegate: [u8; 32],
+    pub delegated_at: i64,
+    pub has_receipt: u8,
+    pub is_initialized: u8,
+}
+
+impl IsInitialized for PodStake {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized != 0
+    }
+}
+
+impl ZeroCopy for PodStake {
+    type Account = Stake;
+}
+
+impl From<&Stake> for PodStake {
+    fn from(stake: &Stake) -> Self {
+        PodStake {
+            amount: stake.amount,
+            lock_until: stake.lock_until,
+            reward_per_token_paid: stake.reward_per_token_paid,
+            rewards_owed: stake.rewards_owed,
+            staked_at: stake.staked_at,
+            pending_unstake: stake.pending_unstake,
+            claimable_at: stake.claimable_at,
+            voting_delegate: stake.voting_delegate.to_bytes(),
+            delegated_at: stake.delegated_at,
+            has_receipt: stake.has_receipt as u8,
+            is_initialized: stake.is_initialized as u8,
+        }
+    }
+}
+
+impl From<&PodStake> for Stake {
+    fn from(pod: &PodStake) -> Self {
+        Stake {
+            amount: pod.amount,
+            lock_until: pod.lock_until,
+            reward_per_token_paid: pod.reward_per_token_paid,
+            rewards_owed: pod.rewards_owed,
+            staked_at: pod.staked_at,
+            pending_unstake: pod.pending_unstake,
+            claimable_at: pod.claimable_at,
+            voting_delegate: Pubkey::new_from_array(pod.voting_delegate),
+            delegated_at: pod.delegated_at,
+            has_receipt: pod.has_receipt != 0,
+            is_initialized: pod.is_initialized != 0,
+        }
+    }
+}
+
+impl Pack for Stake {
+    const LEN: usize = HEADER_LEN + size_of::<PodStake>(); // 115
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst.copy_from_slice(bytemuck::bytes_of(&PodStake::from(self)));
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(Stake::from(bytemuck::from_bytes::<PodStake>(&src[..size_of::<PodStake>()])))
+    }
+}
+
+impl Sealed for Stake {}
+
+/// Stake layout written before rewards were tracked per account.
+pub struct LegacyStake {
+    pub amount: u64,
+    pub lock_until: i64,
+}
+
+impl LegacyStake {
+    pub const LEN: usize = 17; // u64 (8) + i64 (8) + bool (1)
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN || src[16] == 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(LegacyStake {
+            amount: u64::from_le_bytes(src[0..8].try_into().unwrap()),
+            lock_until: i64::from_le_bytes(src[8..16].try_into().unwrap()),
+        })
+    }
+}
+
+impl IsInitialized for StakingPool {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Sealed for StakingPool {}
+
+impl Discriminator for StakingPool {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [203, 19, 214, 220, 220, 154, 24, 102];
+}
+
+/// Zero-copy view of a `StakingPool` account after its discriminator.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct PodStakingPool {
+    pub total_staked: u64,
+    pub reward_pool: u64,
+    pub penalty_pool: u64,
+    pub reward_per_token_stored: u128,
+    pub last_update_time: i64,
+    pub epoch: u64,
+    pub epoch_ends_at: i64,
+    pub reward_rate_per_second: u64,
+    pub rewards_end_at: i64,
+    pub is_initialized: u8,
+}
+
+impl IsInitialized for PodStakingPool {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized != 0
+    }
+}
+
+impl ZeroCopy for PodStakingPool {
+    type Account = StakingPool;
+}
+
+impl From<&StakingPool> for PodStakingPool {
+    fn from(pool: &StakingPool) -> Self {
+        PodStakingPool {
+            total_staked: pool.total_staked,
+            reward_pool: pool.reward_pool,
+            penalty_pool: pool.penalty_pool,
+            reward_per_token_stored: pool.reward_per_token_stored,
+            last_update_time: pool.last_update_time,
+            epoch: pool.epoch,
+            epoch_ends_at: pool.epoch_ends_at,
+            reward_rate_per_second: pool.reward_rate_per_second,
+            rewards_end_at: pool.rewards_end_at,
+            is_initialized: pool.is_initialized as u8,
+        }
+    }
+}
+
+impl From<&PodStakingPool> for StakingPool {
+    fn from(pod: &PodStakingPool) -> Self {
+        StakingPool {
+            total_staked: pod.total_staked,
+            reward_pool: pod.reward_pool,
+            penalty_pool: pod.penalty_pool,
+            reward_per_token_stored: pod.reward_per_token_stored,
+            last_update_time: pod.last_update_time,
+            epoch: pod.epoch,
+            epoch_ends_at: pod.epoch_ends_at,
+            reward_rate_per_second: pod.reward_rate_per_second,
+            rewards_end_at: pod.rewards_end_at,
+            is_initialized: pod.is_initialized != 0,
+        }
+    }
+}
+
+impl Pack for StakingPool {
+    const LEN: usize = HEADER_LEN + size_of::<PodStakingPool>(); // 90
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst.copy_from_slice(bytemuck::bytes_of(&PodStakingPool::from(self)));
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(StakingPool::from(bytemuck::from_bytes::<PodStakingPool>(&src[..size_of::<PodStakingPool>()])))
+    }
+}
+
+/// A pool staking GGT alongside a partner mint at a fixed ratio. Rewards accrue
+/// through `rewards` exactly like the single-mint pool, but over boosted weight.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoStakingPool {
+    pub ggt_mint: Pubkey,
+    pub partner_mint: Pubkey,
+    pub ggt_vault: Pubkey,
+    pub partner_vault: Pubkey,
+    /// Partner tokens required per GGT staked, in basis points.
+    pub partner_ratio_bps: u64,
+    /// Extra reward weight a co-staked GGT carries, in basis points.
+    pub boost_bps: u64,
+    pub rewards: StakingPool,
+    /// The GGT rewards vault co-stakers claim from.
+    pub rewards_vault: Pubkey,
+}
+
+impl CoStakingPool {
+    /// Partner amount that must accompany `ggt_amount`, rounded up.
+    pub fn required_partner_amount(&self, ggt_amount: u64) -> Result<u64, ProgramError> {
+        let required = (ggt_amount as u128 * self.partner_ratio_bps as u128).div_ceil(10_000);
+        u64::try_from(required).map_err(|_| GgtError::MathOverflow.into())
+    }
+
+    pub fn boosted_weight(&self, ggt_amount: u64) -> Result<u64, ProgramError> {
+        let weight = ggt_amount as u128 * (10_000 + self.boost_bps as u128) / 10_000;
+        u64::try_from(weight).map_err(|_| GgtError::MathOverflow.into())
+    }
+}
 
-        let ix = token_instruction::transfer(
-            token_program_acc.key,
-            staker_acc.key,
-            pool_acc.key,
-            staker_auth.key,
-            &[],
-            amount,
-        )?;
-        invoke(&ix, &[staker_acc.clone(), pool_acc.clone(), staker_auth.clone(), token_program_acc.clone()])?;
+impl Sealed for CoStakingPool {}
 
-        self.total_staked += amount;
-        msg!("Staked {} tokens for {} days", amount, lock_period_in_days);
-        Ok(())
+impl IsInitialized for CoStakingPool {
+    fn is_initialized(&self) -> bool {
+        self.rewards.is_initialized
     }
+}
 
-    pub fn unstake_tokens(
-        &mut self,
-        _program_id: &Pubkey, // Prefixed with _ to suppress warning
-        accounts: &[AccountInfo],
-        amount: u64,
-    ) -> ProgramResult {
-        let account_info_iter = &mut accounts.iter();
-        let staking_acc = next_account_info(account_info_iter)?;
-        let pool_acc = next_account_info(account_info_iter)?;
-        let staker_acc = next_account_info(account_info_iter)?;
-        let staker_auth = next_account_info(account_info_iter)?;
-        let token_program_acc = next_account_info(account_info_iter)?;
+impl Discriminator for CoStakingPool {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [57, 86, 108, 104, 174, 46, 152, 49];
+}
 
-        if !staker_auth.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+impl Pack for CoStakingPool {
+    const LEN: usize = 275; // discriminator (8) + version (1) + Pubkey (32) * 4 + u64 (8) * 2 + StakingPool (90) + Pubkey (32)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        let mut cursor = 0;
+        for key in [&self.ggt_mint, &self.partner_mint, &self.ggt_vault, &self.partner_vault] {
+            dst[cursor..cursor + 32].copy_from_slice(key.as_ref());
+            cursor += 32;
         }
+        dst[cursor..cursor + 8].copy_from_slice(&self.partner_ratio_bps.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.boost_bps.to_le_bytes());
+        cursor += 8;
+        self.rewards.pack_into_slice(&mut dst[cursor..cursor + StakingPool::LEN]);
+        cursor += StakingPool::LEN;
+        dst[cursor..cursor + 32].copy_from_slice(self.rewards_vault.as_ref());
+    }
 
-        if *token_program_acc.key != spl_token::id() {
-            return Err(ProgramError::InvalidAccountData);
-        }
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let key_at = |offset: usize| Pubkey::new_from_array(src[offset..offset + 32].try_into().unwrap());
+        Ok(CoStakingPool {
+            ggt_mint: key_at(0),
+            partner_mint: key_at(32),
+            ggt_vault: key_at(64),
+            partner_vault: key_at(96),
+            partner_ratio_bps: u64::from_le_bytes(src[128..136].try_into().unwrap()),
+            boost_bps: u64::from_le_bytes(src[136..144].try_into().unwrap()),
+            rewards: StakingPool::unpack_from_slice(&src[144..144 + StakingPool::LEN])?,
+            rewards_vault: key_at(144 + StakingPool::LEN),
+        })
+    }
+}
 
-        let mut stake_data = Stake::unpack(&staking_acc.try_borrow_data()?)?;
-        if stake_data.amount < amount {
-            return Err(ProgramError::InsufficientFunds);
-        }
+/// A staker's position in a co-staking pool. `weight` tracks boosted weight
+/// and reward checkpoints using the single-mint `Stake` layout.
+pub struct CoStake {
+    pub ggt_amount: u64,
+    pub partner_amount: u64,
+    pub weight: Stake,
+}
 
-        let current_time = Clock::get()?.unix_timestamp;
-        let penalty = if current_time < stake_data.lock_until {
-            let remaining_days = (stake_data.lock_until - current_time) / 86400;
-            if remaining_days > 90 {
-                10
-            } else if remaining_days > 30 {
-                7
-            } else {
-                5
-            }
-        } else {
-            0
-        };
+impl Sealed for CoStake {}
 
-        let penalty_amount = (amount * penalty) / 100;
-        let final_amount = amount.saturating_sub(penalty_amount);
+impl IsInitialized for CoStake {
+    fn is_initialized(&self) -> bool {
+        self.weight.is_initialized
+    }
+}
 
-        stake_data.amount -= amount;
-        let mut staking_data = staking_acc.try_borrow_mut_data()?;
-        stake_data.pack_into_slice(&mut staking_data);
+impl Discriminator for CoStake {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [111, 189, 53, 175, 147, 86, 221, 2];
+}
 
-        self.total_staked = self.total_staked.saturating_sub(amount);
-        self.penalty_pool += penalty_amount;
+impl Pack for CoStake {
+    const LEN: usize = 140; // discriminator (8) + version (1) + u64 (8) * 2 + Stake (115)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..8].copy_from_slice(&self.ggt_amount.to_le_bytes());
+        dst[8..16].copy_from_slice(&self.partner_amount.to_le_bytes());
+        self.weight.pack_into_slice(&mut dst[16..]);
+    }
 
-        let ix = token_instruction::transfer(
-            token_program_acc.key,
-            pool_acc.key,
-            staker_acc.key,
-            staker_auth.key,
-            &[],
-            final_amount,
-        )?;
-        invoke(&ix, &[pool_acc.clone(), staker_acc.clone(), staker_auth.clone(), token_program_acc.clone()])?;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(CoStake {
+            ggt_amount: u64::from_le_bytes(src[0..8].try_into().unwrap()),
+            partner_amount: u64::from_le_bytes(src[8..16].try_into().unwrap()),
+            weight: Stake::unpack_from_slice(&src[16..])?,
+        })
+    }
+}
 
-        self.redistribute_penalty();
-        msg!("Unstaked {} tokens with penalty {}", final_amount, penalty_amount);
-        Ok(())
+/// Per-owner totals across single-mint stake positions, so a wallet can render
+/// a dashboard from one account. Amounts pending unstake are not counted, and
+/// `pending_rewards` is what the positions had settled as of `updated_at`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct StakeSummary {
+    pub owner: Pubkey,
+    pub total_staked: u64,
+    /// Unlock time averaged over `total_staked`.
+    pub weighted_unlock_at: i64,
+    /// Sum of `amount * lock_until`; keeps `weighted_unlock_at` exact across updates.
+    pub lock_weight: i128,
+    pub pending_rewards: u64,
+    /// Positions with a non-zero staked amount.
+    pub positions: u32,
+    pub updated_at: i64,
+    pub is_initialized: bool,
+}
+
+impl StakeSummary {
+    fn add(&mut self, stake: &Stake) -> ProgramResult {
+        self.total_staked = self.total_staked.checked_add(stake.amount).ok_or(GgtError::MathOverflow)?;
+        self.lock_weight = self.lock_weight.checked_add(Self::lock_weight_of(stake)?).ok_or(GgtError::MathOverflow)?;
+        self.pending_rewards = self.pending_rewards.checked_add(stake.rewards_owed).ok_or(GgtError::MathOverflow)?;
+        if stake.amount > 0 {
+            self.positions = self.positions.checked_add(1).ok_or(GgtError::MathOverflow)?;
+        }
+        self.refresh_unlock()
     }
 
-    pub fn redistribute_penalty(&mut self) {
-        if self.total_staked == 0 || self.penalty_pool == 0 {
-            return;
+    fn remove(&mut self, stake: &Stake) -> ProgramResult {
+        self.total_staked = self.total_staked.checked_sub(stake.amount).ok_or(GgtError::MathOverflow)?;
+        self.lock_weight = self.lock_weight.checked_sub(Self::lock_weight_of(stake)?).ok_or(GgtError::MathOverflow)?;
+        self.pending_rewards = self.pending_rewards.checked_sub(stake.rewards_owed).ok_or(GgtError::MathOverflow)?;
+        if stake.amount > 0 {
+            self.positions = self.positions.checked_sub(1).ok_or(GgtError::MathOverflow)?;
         }
-        let reward_per_token = self.penalty_pool / self.total_staked;
-        self.reward_pool += self.penalty_pool;
-        self.penalty_pool = 0;
-        msg!("Redistributed penalty: {} per token", reward_per_token);
+        self.refresh_unlock()
     }
 
-    pub fn get_staked_amount(&self, staking_acc: &AccountInfo) -> Result<u64, ProgramError> {
-        let stake_data = Stake::unpack(&staking_acc.try_borrow_data()?)?;
-        Ok(stake_data.amount)
+    fn lock_weight_of(stake: &Stake) -> Result<i128, ProgramError> {
+        (stake.amount as i128).checked_mul(stake.lock_until as i128).ok_or_else(|| GgtError::MathOverflow.into())
+    }
+
+    fn refresh_unlock(&mut self) -> ProgramResult {
+        self.weighted_unlock_at = if self.total_staked == 0 {
+            0
+        } else {
+            let average = self.lock_weight.checked_div(self.total_staked as i128).ok_or(GgtError::MathOverflow)?;
+            i64::try_from(average).map_err(|_| GgtError::MathOverflow)?
+        };
+        Ok(())
     }
 }
 
-impl Pack for Stake {
-    const LEN: usize = 17; // u64 (8) + i64 (8) + bool (1)
+impl Sealed for StakeSummary {}
+
+impl IsInitialized for StakeSummary {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for StakeSummary {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [246, 241, 236, 1, 143, 51, 140, 34];
+}
+
+impl Pack for StakeSummary {
+    const LEN: usize = 94; // discriminator (8) + version (1) + Pubkey (32) + u64 (8) + i64 (8) + i128 (16) + u64 (8) + u32 (4) + i64 (8) + bool (1)
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let mut cursor = 0;
-        dst[cursor..cursor + 8].copy_from_slice(&self.amount.to_le_bytes());
-        cursor += 8;
-        dst[cursor..cursor + 8].copy_from_slice(&self.lock_until.to_le_bytes());
-        cursor += 8;
-        dst[cursor] = self.is_initialized as u8;
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..32].copy_from_slice(self.owner.as_ref());
+        dst[32..40].copy_from_slice(&self.total_staked.to_le_bytes());
+        dst[40..48].copy_from_slice(&self.weighted_unlock_at.to_le_bytes());
+        dst[48..64].copy_from_slice(&self.lock_weight.to_le_bytes());
+        dst[64..72].copy_from_slice(&self.pending_rewards.to_le_bytes());
+        dst[72..76].copy_from_slice(&self.positions.to_le_bytes());
+        dst[76..84].copy_from_slice(&self.updated_at.to_le_bytes());
+        dst[84] = self.is_initialized as u8;
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        if src.len() < Self::LEN {
-            return Err(ProgramError::InvalidAccountData);
-        }
-        let amount = u64::from_le_bytes(src[0..8].try_into().unwrap());
-        let lock_until = i64::from_le_bytes(src[8..16].try_into().unwrap());
-        let is_initialized = src[16] != 0;
-        Ok(Stake { amount, lock_until, is_initialized })
+        let src = read_discriminator::<Self>(src)?;
+        Ok(StakeSummary {
+            owner: Pubkey::new_from_array(src[0..32].try_into().unwrap()),
+            total_staked: u64::from_le_bytes(src[32..40].try_into().unwrap()),
+            weighted_unlock_at: i64::from_le_bytes(src[40..48].try_into().unwrap()),
+            lock_weight: i128::from_le_bytes(src[48..64].try_into().unwrap()),
+            pending_rewards: u64::from_le_bytes(src[64..72].try_into().unwrap()),
+            positions: u32::from_le_bytes(src[72..76].try_into().unwrap()),
+            updated_at: i64::from_le_bytes(src[76..84].try_into().unwrap()),
+            is_initialized: src[84] != 0,
+        })
     }
 }
 
-impl Sealed for Stake {}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{account_info, denylist, fund_rent_exempt, program_config, token_account_data, SimulatedBank};
+    use crate::GOVERNANCE_PUBKEY;
     use solana_program::pubkey::Pubkey;
 
+    /// A pool funded to emit one token a second for as long as any test runs.
+    fn funded_pool(total_staked: u64) -> StakingPool {
+        StakingPool { total_staked, reward_pool: 15_000_000, reward_rate_per_second: 1, rewards_end_at: i64::MAX, ..StakingPool::new(0) }
+    }
+
     #[test]
     fn test_stake_tokens() {
         let mut staking_contract = StakingContract::new();
@@ -269,7 +2466,22 @@ mod tests {
             0,
         );
 
-        let accounts = vec![staking_acc, staker_acc, pool_acc, staker_auth, token_program_acc];
+        let pool_state_key = Pubkey::new_unique();
+        let mut pool_state_lamports = 0u64;
+        let mut pool_state_data = vec![0u8; StakingPool::LEN];
+        StakingPool::pack(StakingPool::new(0), &mut pool_state_data).unwrap();
+        let pool_state_acc = AccountInfo::new(
+            &pool_state_key,
+            false,
+            true,
+            &mut pool_state_lamports,
+            &mut pool_state_data,
+            &program_id,
+            false,
+            0,
+        );
+
+        let accounts = vec![staking_acc, staker_acc, pool_acc, staker_auth, token_program_acc, pool_state_acc];
         let res = staking_contract.stake_tokens(&program_id, &accounts, 500, 30);
         assert!(res.is_err()); // Expect Err due to stubbed invoke in test env
     }
@@ -293,6 +2505,14 @@ mod tests {
         let stake_data = Stake {
             amount: 500,
             lock_until: 0, // Already unlocked
+            reward_per_token_paid: 0,
+            rewards_owed: 0,
+            staked_at: 0,
+            pending_unstake: 0,
+            claimable_at: 0,
+            voting_delegate: Pubkey::default(),
+            delegated_at: 0,
+            has_receipt: false,
             is_initialized: true,
         };
         let mut staking_data = vec![0u8; Stake::LEN];
@@ -353,8 +2573,22 @@ mod tests {
             0,
         );
 
-        staking_contract.total_staked = 500;
-        let accounts = vec![staking_acc, pool_acc, staker_acc, staker_auth, token_program_acc];
+        let pool_state_key = Pubkey::new_unique();
+        let mut pool_state_lamports = 0u64;
+        let mut pool_state_data = vec![0u8; StakingPool::LEN];
+        StakingPool::pack(StakingPool { total_staked: 500, ..StakingPool::new(0) }, &mut pool_state_data).unwrap();
+        let pool_state_acc = AccountInfo::new(
+            &pool_state_key,
+            false,
+            true,
+            &mut pool_state_lamports,
+            &mut pool_state_data,
+            &program_id,
+            false,
+            0,
+        );
+
+        let accounts = vec![staking_acc, pool_acc, staker_acc, staker_auth, token_program_acc, pool_state_acc];
         let res = staking_contract.unstake_tokens(&program_id, &accounts, 500);
         assert!(res.is_err()); // Expect Err due to stubbed invoke in test env
     }
@@ -369,6 +2603,14 @@ mod tests {
         let stake_data = Stake {
             amount: 500,
             lock_until: 0,
+            reward_per_token_paid: 0,
+            rewards_owed: 0,
+            staked_at: 0,
+            pending_unstake: 0,
+            claimable_at: 0,
+            voting_delegate: Pubkey::default(),
+            delegated_at: 0,
+            has_receipt: false,
             is_initialized: true,
         };
         let mut staking_data = vec![0u8; Stake::LEN];
@@ -389,6 +2631,33 @@ mod tests {
         assert_eq!(amount, 500);
     }
 
+    #[test]
+    fn test_get_stake_info_returns_rewards_accrued_up_to_the_query() {
+        let bank = SimulatedBank::start();
+        bank.set_unix_timestamp(100);
+        let program_id = Pubkey::new_unique();
+        let (staking_key, pool_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut l0, mut l1) = (0u64, 0u64);
+        let stake = Stake { amount: 500, lock_until: 3_600, rewards_owed: 7, is_initialized: true, ..Stake::default() };
+        let mut staking_data = vec![0u8; Stake::LEN];
+        stake.pack_into_slice(&mut staking_data);
+        let mut pool_data = vec![0u8; StakingPool::LEN];
+        StakingPool::pack(funded_pool(500), &mut pool_data).unwrap();
+        let accounts = [
+            account_info(&staking_key, false, false, &mut l0, &mut staking_data, &program_id),
+            account_info(&pool_key, false, false, &mut l1, &mut pool_data, &program_id),
+        ];
+
+        // Queries skip the pause gate, so no program config follows the accounts.
+        crate::process_instruction(&program_id, &accounts, &[120, crate::INSTRUCTION_VERSION]).unwrap();
+        let info: StakeInfo = borsh::from_slice(&bank.return_data()).unwrap();
+        assert_eq!(
+            info,
+            StakeInfo { amount: 500, lock_until: 3_600, claimable_rewards: 107, pending_unstake: 0, claimable_at: 0, has_receipt: false }
+        );
+        assert_eq!(StakingPool::unpack(&accounts[1].try_borrow_data().unwrap()).unwrap().last_update_time, 0);
+    }
+
     #[test]
     fn test_unstake_tokens_with_penalty() {
         let mut staking_contract = StakingContract::new();
@@ -408,6 +2677,14 @@ mod tests {
         let stake_data = Stake {
             amount: 500,
             lock_until: i64::MAX, // Far in the future for 10% penalty
+            reward_per_token_paid: 0,
+            rewards_owed: 0,
+            staked_at: 0,
+            pending_unstake: 0,
+            claimable_at: 0,
+            voting_delegate: Pubkey::default(),
+            delegated_at: 0,
+            has_receipt: false,
             is_initialized: true,
         };
         let mut staking_data = vec![0u8; Stake::LEN];
@@ -468,9 +2745,934 @@ mod tests {
             0,
         );
 
-        staking_contract.total_staked = 500;
-        let accounts = vec![staking_acc, pool_acc, staker_acc, staker_auth, token_program_acc];
+        let pool_state_key = Pubkey::new_unique();
+        let mut pool_state_lamports = 0u64;
+        let mut pool_state_data = vec![0u8; StakingPool::LEN];
+        StakingPool::pack(StakingPool { total_staked: 500, ..StakingPool::new(0) }, &mut pool_state_data).unwrap();
+        let pool_state_acc = AccountInfo::new(
+            &pool_state_key,
+            false,
+            true,
+            &mut pool_state_lamports,
+            &mut pool_state_data,
+            &program_id,
+            false,
+            0,
+        );
+
+        let accounts = vec![staking_acc, pool_acc, staker_acc, staker_auth, token_program_acc, pool_state_acc];
         let res = staking_contract.unstake_tokens(&program_id, &accounts, 500);
         assert!(res.is_err()); // Expect Err due to stubbed invoke
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_reward_accrual_is_proportional_to_stake() {
+        let mut pool = funded_pool(400);
+        let small = Stake { amount: 100, is_initialized: true, ..Default::default() };
+        let large = Stake { amount: 300, is_initialized: true, ..Default::default() };
+
+        pool.update_reward(400).unwrap();
+        assert_eq!(pool.reward_pool, 15_000_000 - 400);
+        assert_eq!(pool.earned(&small), Ok(100));
+        assert_eq!(pool.earned(&large), Ok(300));
+    }
+
+    #[test]
+    fn test_reward_accrual_capped_by_reward_pool() {
+        let mut pool = StakingPool { reward_pool: 50, ..funded_pool(10) };
+        let stake = Stake { amount: 10, is_initialized: true, ..Default::default() };
+
+        pool.update_reward(1_000).unwrap();
+        assert_eq!(pool.reward_pool, 0);
+        assert_eq!(pool.earned(&stake), Ok(50));
+    }
+
+    #[test]
+    fn test_emission_follows_the_funded_schedule() {
+        let mut pool = StakingPool { total_staked: 100, ..StakingPool::new(0) };
+        let stake = Stake { amount: 100, is_initialized: true, ..Default::default() };
+
+        pool.update_reward(1_000).unwrap();
+        assert_eq!(pool.earned(&stake), Ok(0));
+        assert_eq!(pool.schedule_rewards(1_000, 999, 1, 2_000), Err(GgtError::RewardsUnderfunded.into()));
+        assert_eq!(pool.schedule_rewards(1_000, 1_000, 1, 1_000), Err(ProgramError::InvalidArgument));
+        pool.schedule_rewards(1_000, 1_000, 1, 2_000).unwrap();
+
+        pool.update_reward(1_400).unwrap();
+        assert_eq!((pool.earned(&stake), pool.reward_pool), (Ok(400), 600));
+        // Topping up mid-schedule carries the unemitted 600 into the new rate.
+        pool.schedule_rewards(1_400, 600, 2, 2_000).unwrap();
+        pool.update_reward(5_000).unwrap();
+        assert_eq!((pool.earned(&stake), pool.reward_pool), (Ok(1_600), 0));
+
+        // Rolling epochs only advances the counter.
+        assert_eq!(pool.roll_epoch(EPOCH_SECONDS - 1), Err(GgtError::EpochNotEnded.into()));
+        pool.roll_epoch(EPOCH_SECONDS + 500).unwrap();
+        assert_eq!((pool.epoch, pool.epoch_ends_at), (1, 2 * EPOCH_SECONDS + 500));
+        assert_eq!(pool.earned(&stake), Ok(1_600));
+    }
+
+    #[test]
+    fn test_settle_checkpoints_stake_and_penalty_redistribution() {
+        let mut pool = funded_pool(200);
+        let mut stake = Stake { amount: 200, is_initialized: true, ..Default::default() };
+
+        pool.update_reward(100).unwrap();
+        pool.settle(&mut stake).unwrap();
+        assert_eq!(stake.rewards_owed, 100);
+        assert_eq!(pool.earned(&stake), Ok(100));
+
+        pool.penalty_pool = 50;
+        pool.redistribute_penalty().unwrap();
+        assert_eq!(pool.penalty_pool, 0);
+        assert_eq!(pool.earned(&stake), Ok(150));
+    }
+
+    #[test]
+    fn test_reward_math_reports_overflow_at_boundaries() {
+        let overflow = ProgramError::from(GgtError::MathOverflow);
+
+        let mut pool = StakingPool { reward_per_token_stored: u128::MAX, ..funded_pool(1) };
+        assert_eq!(pool.update_reward(1), Err(overflow.clone()));
+        pool.penalty_pool = 1;
+        assert_eq!(pool.redistribute_penalty(), Err(overflow.clone()));
+
+        let pool = StakingPool { reward_per_token_stored: 2 * REWARD_PRECISION, ..StakingPool::new(0) };
+        let whale = Stake { amount: u64::MAX, is_initialized: true, ..Default::default() };
+        assert_eq!(pool.earned(&whale), Err(overflow.clone()));
+        let owed = Stake { amount: 1, rewards_owed: u64::MAX - 2, is_initialized: true, ..Default::default() };
+        assert_eq!(pool.earned(&owed), Ok(u64::MAX));
+        let owed = Stake { rewards_owed: u64::MAX, ..owed };
+        assert_eq!(pool.earned(&owed), Err(overflow.clone()));
+
+        let co_pool = CoStakingPool {
+            ggt_mint: Pubkey::new_unique(),
+            partner_mint: Pubkey::new_unique(),
+            ggt_vault: Pubkey::new_unique(),
+            partner_vault: Pubkey::new_unique(),
+            partner_ratio_bps: 10_000,
+            boost_bps: 1,
+            rewards: StakingPool::new(0),
+            rewards_vault: Pubkey::new_unique(),
+        };
+        assert_eq!(co_pool.required_partner_amount(u64::MAX), Ok(u64::MAX));
+        assert_eq!(co_pool.boosted_weight(u64::MAX), Err(overflow.clone()));
+        assert_eq!(co_pool.boosted_weight(u64::MAX / 2), Ok(u64::MAX / 2 + u64::MAX / 2 / 10_000));
+    }
+
+    #[test]
+    fn test_fund_stake_and_claim_rewards_with_simulated_bank() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_key = spl_token::id();
+        let (vault_authority_key, _) = rewards_vault_authority(&program_id);
+        let staker_key = Pubkey::new_unique();
+        let pool_key = Pubkey::new_unique();
+        let staker_auth_key = Pubkey::new_unique();
+        let pool_state_key = Pubkey::new_unique();
+        let rewards_vault_key = Pubkey::new_unique();
+        let mint_key = crate::TokenContract::mint_address(&program_id).0;
+        let (staking_key, _) = stake_address(&program_id, &staker_auth_key, &mint_key);
+
+        let (mut l0, mut l1, mut l2, mut l3, mut l4, mut l5, mut l6, mut l7) = (0u64, 0u64, 0u64, 0u64, 0u64, 0u64, 0u64, 0u64);
+        let mut staking_data = vec![0u8; Stake::LEN];
+        let mut pool_state_data = vec![0u8; StakingPool::LEN];
+        StakingPool::pack(StakingPool::new(0), &mut pool_state_data).unwrap();
+        let (mut d2, mut d3) = (vec![], vec![]);
+
+        let staking_acc = account_info(&staking_key, false, true, &mut l0, &mut staking_data, &program_id);
+        let mut staker_data = token_account_data(&mint_key, &staker_auth_key, 0);
+        let staker_acc = account_info(&staker_key, false, true, &mut l1, &mut staker_data, &token_program_key);
+        let (pool_authority_key, _) = stake_pool_authority(&program_id, &mint_key);
+        let mut pool_data = token_account_data(&mint_key, &pool_authority_key, 0);
+        let pool_acc = account_info(&pool_key, false, true, &mut l2, &mut pool_data, &token_program_key);
+        let staker_auth = account_info(&staker_auth_key, true, false, &mut l3, &mut d2, &program_id);
+        let token_program_acc = account_info(&token_program_key, false, false, &mut l4, &mut d3, &program_id);
+        let pool_state_acc = account_info(&pool_state_key, false, true, &mut l5, &mut pool_state_data, &program_id);
+        fund_rent_exempt(&[&staking_acc, &pool_state_acc]);
+        let mut rewards_vault_data = token_account_data(&mint_key, &vault_authority_key, 0);
+        let rewards_vault_acc = account_info(&rewards_vault_key, false, true, &mut l6, &mut rewards_vault_data, &token_program_key);
+        let mut vault_authority_data = vec![];
+        let vault_authority_acc = account_info(&vault_authority_key, false, false, &mut l7, &mut vault_authority_data, &program_id);
+        let (mut mint_lamports, mut mint_data) = (0u64, vec![0u8; spl_token::state::Mint::LEN]);
+        let mint = spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() };
+        spl_token::state::Mint::pack(mint, &mut mint_data).unwrap();
+        let mint_acc = account_info(&mint_key, false, false, &mut mint_lamports, &mut mint_data, &token_program_key);
+
+        let (admin_token_key, (admin_key, _)) = (Pubkey::new_unique(), multisig::multisig_authority_address(&program_id));
+        let (mut admin_lamports, mut admin_token_lamports, mut admin_data) = (0u64, 0u64, vec![]);
+        let admin_acc = account_info(&admin_key, true, false, &mut admin_lamports, &mut admin_data, &program_id);
+        let mut admin_token_data = token_account_data(&mint_key, &admin_key, 1_000);
+        let admin_token_acc =
+            account_info(&admin_token_key, false, true, &mut admin_token_lamports, &mut admin_token_data, &token_program_key);
+
+        bank.set_balance(&staker_key, 1_000);
+        bank.set_balance(&admin_token_key, 1_000);
+        bank.set_unix_timestamp(1_000);
+
+        let mut staking_contract = StakingContract::new();
+        let fund_accounts = vec![
+            pool_state_acc.clone(),
+            admin_acc,
+            admin_token_acc,
+            rewards_vault_acc.clone(),
+            mint_acc.clone(),
+            token_program_acc.clone(),
+        ];
+        assert_eq!(
+            staking_contract.fund_rewards(&program_id, &fund_accounts, 1_000, 2, 2_000),
+            Err(GgtError::RewardsUnderfunded.into())
+        );
+        staking_contract.fund_rewards(&program_id, &fund_accounts, 1_000, 1, 2_000).unwrap();
+        assert_eq!((bank.balance(&admin_token_key), bank.balance(&rewards_vault_key)), (0, 1_000));
+        let pool = StakingPool::unpack(&pool_state_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((pool.reward_pool, pool.reward_rate_per_second, pool.rewards_end_at), (1_000, 1, 2_000));
+
+        let (denylist_key, mut denylist_data) = denylist(&program_id, &[]);
+        let mut denylist_lamports = 0u64;
+        let denylist_acc = account_info(&denylist_key, false, false, &mut denylist_lamports, &mut denylist_data, &program_id);
+        let stake_accounts = vec![
+            staking_acc.clone(),
+            staker_acc.clone(),
+            pool_acc.clone(),
+            staker_auth.clone(),
+            token_program_acc.clone(),
+            pool_state_acc.clone(),
+            mint_acc.clone(),
+            denylist_acc,
+        ];
+        staking_contract.stake_tokens(&program_id, &stake_accounts, 500, 0).unwrap();
+        assert_eq!(bank.balance(&staker_key), 500);
+        let stake_event = events::StakeEvent { staker: staker_auth_key, mint: mint_key, amount: 500, lock_until: 1_000 };
+        assert_eq!(bank.events::<events::StakeEvent>(), vec![stake_event]);
+        assert_eq!(bank.balance(&pool_key), 500);
+
+        bank.set_unix_timestamp(1_100);
+        let claim_accounts = vec![
+            staking_acc,
+            pool_state_acc,
+            rewards_vault_acc,
+            vault_authority_acc,
+            staker_acc,
+            staker_auth,
+            token_program_acc,
+            mint_acc,
+        ];
+        staking_contract.claim_rewards(&program_id, &claim_accounts).unwrap();
+        assert_eq!(bank.balance(&rewards_vault_key), 900);
+        assert_eq!(bank.balance(&staker_key), 600);
+    }
+
+    #[test]
+    fn test_add_to_stake_settles_rewards_and_extend_lock_only_moves_forward() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_key = spl_token::id();
+        let (staker_key, pool_key, staker_auth_key, pool_state_key, mint_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), crate::TokenContract::mint_address(&program_id).0);
+        let (staking_key, _) = stake_address(&program_id, &staker_auth_key, &mint_key);
+
+        let mut lamports = [0u64; 7];
+        let [l0, l1, l2, l3, l4, l5, l6] = &mut lamports;
+        let mut staking_data = vec![0u8; Stake::LEN];
+        let mut pool_state_data = vec![0u8; StakingPool::LEN];
+        StakingPool::pack(funded_pool(0), &mut pool_state_data).unwrap();
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        let mint = spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() };
+        spl_token::state::Mint::pack(mint, &mut mint_data).unwrap();
+        let (mut d2, mut d3) = (vec![], vec![]);
+        let staking_acc = account_info(&staking_key, false, true, l0, &mut staking_data, &program_id);
+        let mut staker_data = token_account_data(&mint_key, &staker_auth_key, 0);
+        let staker_acc = account_info(&staker_key, false, true, l1, &mut staker_data, &token_program_key);
+        let (pool_authority_key, _) = stake_pool_authority(&program_id, &mint_key);
+        let mut pool_data = token_account_data(&mint_key, &pool_authority_key, 0);
+        let pool_acc = account_info(&pool_key, false, true, l2, &mut pool_data, &token_program_key);
+        let staker_auth = account_info(&staker_auth_key, true, false, l3, &mut d2, &program_id);
+        let token_program_acc = account_info(&token_program_key, false, false, l4, &mut d3, &program_id);
+        let pool_state_acc = account_info(&pool_state_key, false, true, l5, &mut pool_state_data, &program_id);
+        fund_rent_exempt(&[&staking_acc, &pool_state_acc]);
+        let mint_acc = account_info(&mint_key, false, false, l6, &mut mint_data, &token_program_key);
+        let (denylist_key, mut denylist_data) = denylist(&program_id, &[]);
+        let mut denylist_lamports = 0u64;
+        let denylist_acc = account_info(&denylist_key, false, false, &mut denylist_lamports, &mut denylist_data, &program_id);
+        let accounts = vec![
+            staking_acc.clone(),
+            staker_acc,
+            pool_acc,
+            staker_auth.clone(),
+            token_program_acc,
+            pool_state_acc.clone(),
+            mint_acc.clone(),
+            denylist_acc,
+        ];
+        bank.set_balance(&staker_key, 1_000);
+
+        let mut staking_contract = StakingContract::new();
+        bank.set_unix_timestamp(1_000);
+        assert_eq!(staking_contract.add_to_stake(&program_id, &accounts, 300), Err(ProgramError::UninitializedAccount));
+        // Staking again while an earlier unstake cools down keeps it withdrawable.
+        Stake { pending_unstake: 250, claimable_at: 5_000, is_initialized: true, ..Default::default() }
+            .pack_into_slice(&mut staking_acc.try_borrow_mut_data().unwrap());
+        staking_contract.stake_tokens(&program_id, &accounts, 500, 30).unwrap();
+        let stake = Stake::unpack(&staking_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((stake.amount, stake.pending_unstake, stake.claimable_at), (500, 250, 5_000));
+
+        bank.set_unix_timestamp(1_100);
+        staking_contract.add_to_stake(&program_id, &accounts, 300).unwrap();
+        let stake = Stake::unpack(&staking_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((stake.amount, stake.rewards_owed, stake.lock_until), (800, 100, 1_000 + 30 * 86_400));
+        assert_eq!(StakingPool::unpack(&pool_state_acc.try_borrow_data().unwrap()).unwrap().total_staked, 800);
+        assert_eq!(bank.balance(&pool_key), 800);
+
+        let lock_accounts = [staking_acc.clone(), staker_auth, mint_acc];
+        assert_eq!(staking_contract.extend_lock(&program_id, &lock_accounts, 10), Err(ProgramError::InvalidArgument));
+        staking_contract.extend_lock(&program_id, &lock_accounts, 60).unwrap();
+        assert_eq!(Stake::unpack(&staking_acc.try_borrow_data().unwrap()).unwrap().lock_until, 1_100 + 60 * 86_400);
+    }
+
+    #[test]
+    fn test_economics_change_opens_penalty_free_exit_window() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_key = spl_token::id();
+        let (config_key, _) = staking_config_address(&program_id);
+        let (staker_key, pool_key, staker_auth_key, pool_state_key, mint_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (staking_key, _) = stake_address(&program_id, &staker_auth_key, &mint_key);
+        let economics = StakingEconomics {
+            penalty_bps_over_90_days: 1_000,
+            penalty_bps_over_30_days: 700,
+            penalty_bps: 500,
+            unstake_cooldown_seconds: 0,
+        };
+
+        let (program_config_key, mut program_config_data) = program_config(&program_id);
+        let mut lamports = [0u64, 0, 0, 0, 0, 0, 0, 1, 0, 0];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9] = &mut lamports;
+        let lock_until = 1_000 + 60 * 86_400;
+        let mut staking_data = vec![0u8; Stake::LEN];
+        Stake { amount: 1_000, lock_until, staked_at: 1_000, is_initialized: true, ..Default::default() }
+            .pack_into_slice(&mut staking_data);
+        let mut pool_state_data = vec![0u8; StakingPool::LEN];
+        StakingPool::pack(StakingPool { total_staked: 1_000, ..StakingPool::new(1_000) }, &mut pool_state_data).unwrap();
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        let mint = spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() };
+        spl_token::state::Mint::pack(mint, &mut mint_data).unwrap();
+        let mut config_data = vec![0u8; StakingConfig::LEN];
+        StakingConfig::pack(StakingConfig { economics, last_econ_change: 0, is_initialized: true }, &mut config_data).unwrap();
+        let (mut d2, mut d3, mut d4) = (vec![], vec![], vec![]);
+
+        let staking_acc = account_info(&staking_key, false, true, l0, &mut staking_data, &program_id);
+        let (pool_authority_key, _) = stake_pool_authority(&program_id, &mint_key);
+        let mut pool_data = token_account_data(&mint_key, &pool_authority_key, 0);
+        let pool_acc = account_info(&pool_key, false, true, l1, &mut pool_data, &token_program_key);
+        let (mut pool_authority_lamports, mut pool_authority_data) = (0u64, vec![]);
+        let pool_authority_acc =
+            account_info(&pool_authority_key, false, false, &mut pool_authority_lamports, &mut pool_authority_data, &program_id);
+        let mut staker_data = token_account_data(&mint_key, &staker_auth_key, 0);
+        let staker_acc = account_info(&staker_key, false, true, l2, &mut staker_data, &token_program_key);
+        let staker_auth = account_info(&staker_auth_key, true, false, l3, &mut d2, &program_id);
+        let token_program_acc = account_info(&token_program_key, false, false, l4, &mut d3, &program_id);
+        let pool_state_acc = account_info(&pool_state_key, false, true, l5, &mut pool_state_data, &program_id);
+        let mint_acc = account_info(&mint_key, false, false, l6, &mut mint_data, &token_program_key);
+        let config_acc = account_info(&config_key, false, true, l7, &mut config_data, &program_id);
+        let governance_acc = account_info(&GOVERNANCE_PUBKEY, true, false, l8, &mut d4, &program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, l9, &mut program_config_data, &program_id);
+        let rewards_vault_key = Pubkey::new_unique();
+        let mut rewards_vault_data = token_account_data(&mint_key, &rewards_vault_authority(&program_id).0, 0);
+        let mut rewards_vault_lamports = 0u64;
+        let rewards_vault_acc =
+            account_info(&rewards_vault_key, false, true, &mut rewards_vault_lamports, &mut rewards_vault_data, &token_program_key);
+        fund_rent_exempt(&[&staking_acc, &pool_state_acc, &config_acc]);
+        bank.set_balance(&pool_key, 1_000);
+
+        let mut staking_contract = StakingContract::new();
+        bank.set_unix_timestamp(2_000);
+        let raised = StakingEconomics { penalty_bps_over_30_days: 1_500, ..economics };
+        staking_contract.set_staking_economics(&program_id, &[config_acc.clone(), governance_acc, program_config_acc], raised).unwrap();
+        assert_eq!(StakingConfig::unpack(&config_acc.try_borrow_data().unwrap()).unwrap().last_econ_change, 2_000);
+
+        let accounts = vec![
+            staking_acc,
+            pool_acc,
+            pool_authority_acc,
+            staker_acc,
+            staker_auth,
+            token_program_acc,
+            pool_state_acc.clone(),
+            mint_acc,
+            config_acc,
+            rewards_vault_acc,
+        ];
+        bank.set_unix_timestamp(3_000);
+        staking_contract.unstake_tokens(&program_id, &accounts, 500).unwrap();
+        assert_eq!(bank.balance(&staker_key), 500);
+
+        // Once the window closes the raised curve applies, and the penalty
+        // moves into the rewards vault the remaining stake is credited from.
+        bank.set_unix_timestamp(2_000 + ECON_CHANGE_EXIT_WINDOW_SECONDS);
+        staking_contract.unstake_tokens(&program_id, &accounts, 100).unwrap();
+        assert_eq!((bank.balance(&staker_key), bank.balance(&rewards_vault_key), bank.balance(&pool_key)), (585, 15, 400));
+        let pool = StakingPool::unpack(&pool_state_acc.try_borrow_data().unwrap()).unwrap();
+        let remaining = Stake { amount: 400, is_initialized: true, ..Default::default() };
+        assert_eq!((pool.penalty_pool, pool.earned(&remaining)), (0, Ok(15)));
+
+        let config = StakingConfig { economics: raised, last_econ_change: 2_000, is_initialized: true };
+        assert!(!config.in_exit_window(2_500, 3_000));
+    }
+
+    #[test]
+    fn test_request_unstake_waits_out_cooldown_or_pays_penalty() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_key = spl_token::id();
+        let (config_key, _) = staking_config_address(&program_id);
+        let (staker_key, pool_key, staker_auth_key, pool_state_key, mint_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (staking_key, _) = stake_address(&program_id, &staker_auth_key, &mint_key);
+        let economics = StakingEconomics {
+            penalty_bps_over_90_days: 1_000,
+            penalty_bps_over_30_days: 700,
+            penalty_bps: 500,
+            unstake_cooldown_seconds: 3_600,
+        };
+
+        let mut lamports = [0u64, 0, 0, 0, 0, 0, 0, 1];
+        let [l0, l1, l2, l3, l4, l5, l6, l7] = &mut lamports;
+        let mut staking_data = vec![0u8; Stake::LEN];
+        Stake { amount: 1_000, lock_until: 5_000, staked_at: 1_000, is_initialized: true, ..Default::default() }
+            .pack_into_slice(&mut staking_data);
+        let mut pool_state_data = vec![0u8; StakingPool::LEN];
+        StakingPool::pack(StakingPool { total_staked: 1_000, ..StakingPool::new(0) }, &mut pool_state_data).unwrap();
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        let mint = spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() };
+        spl_token::state::Mint::pack(mint, &mut mint_data).unwrap();
+        let mut config_data = vec![0u8; StakingConfig::LEN];
+        StakingConfig::pack(StakingConfig { economics, last_econ_change: 0, is_initialized: true }, &mut config_data).unwrap();
+        let (mut d2, mut d3) = (vec![], vec![]);
+
+        let staking_acc = account_info(&staking_key, false, true, l0, &mut staking_data, &program_id);
+        let (pool_authority_key, _) = stake_pool_authority(&program_id, &mint_key);
+        let mut pool_data = token_account_data(&mint_key, &pool_authority_key, 0);
+        let pool_acc = account_info(&pool_key, false, true, l1, &mut pool_data, &token_program_key);
+        let (mut pool_authority_lamports, mut pool_authority_data) = (0u64, vec![]);
+        let pool_authority_acc =
+            account_info(&pool_authority_key, false, false, &mut pool_authority_lamports, &mut pool_authority_data, &program_id);
+        let mut staker_data = token_account_data(&mint_key, &staker_auth_key, 0);
+        let staker_acc = account_info(&staker_key, false, true, l2, &mut staker_data, &token_program_key);
+        let staker_auth = account_info(&staker_auth_key, true, false, l3, &mut d2, &program_id);
+        let token_program_acc = account_info(&token_program_key, false, false, l4, &mut d3, &program_id);
+        let pool_state_acc = account_info(&pool_state_key, false, true, l5, &mut pool_state_data, &program_id);
+        let mint_acc = account_info(&mint_key, false, false, l6, &mut mint_data, &token_program_key);
+        let config_acc = account_info(&config_key, false, true, l7, &mut config_data, &program_id);
+        fund_rent_exempt(&[&staking_acc, &pool_state_acc, &config_acc]);
+        bank.set_balance(&pool_key, 1_000);
+
+        let request_accounts =
+            [staking_acc.clone(), staker_auth.clone(), mint_acc.clone(), pool_state_acc.clone(), config_acc.clone()];
+        let withdraw_accounts = [
+            staking_acc.clone(),
+            pool_acc.clone(),
+            pool_authority_acc.clone(),
+            staker_acc.clone(),
+            staker_auth.clone(),
+            token_program_acc.clone(),
+            mint_acc.clone(),
+        ];
+        let mut staking_contract = StakingContract::new();
+        bank.set_unix_timestamp(4_000);
+        assert_eq!(
+            staking_contract.request_unstake(&program_id, &request_accounts, 400),
+            Err(GgtError::StakeLocked.into())
+        );
+
+        bank.set_unix_timestamp(6_000);
+        staking_contract.request_unstake(&program_id, &request_accounts, 400).unwrap();
+        let stake = Stake::unpack(&staking_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((stake.amount, stake.pending_unstake, stake.claimable_at), (600, 400, 9_600));
+        assert_eq!(StakingPool::unpack(&pool_state_acc.try_borrow_data().unwrap()).unwrap().total_staked, 600);
+        assert_eq!(
+            staking_contract.withdraw_unstaked(&program_id, &withdraw_accounts),
+            Err(GgtError::CooldownNotElapsed.into())
+        );
+
+        bank.set_unix_timestamp(9_600);
+        staking_contract.withdraw_unstaked(&program_id, &withdraw_accounts).unwrap();
+        assert_eq!(bank.balance(&staker_key), 400);
+        assert_eq!(Stake::unpack(&staking_acc.try_borrow_data().unwrap()).unwrap().pending_unstake, 0);
+
+        // Skipping the cooldown costs the flat penalty even after the lock.
+        let rewards_vault_key = Pubkey::new_unique();
+        let mut rewards_vault_data = token_account_data(&mint_key, &rewards_vault_authority(&program_id).0, 0);
+        let mut rewards_vault_lamports = 0u64;
+        let rewards_vault_acc =
+            account_info(&rewards_vault_key, false, true, &mut rewards_vault_lamports, &mut rewards_vault_data, &token_program_key);
+        let accounts = vec![
+            staking_acc,
+            pool_acc,
+            pool_authority_acc,
+            staker_acc,
+            staker_auth,
+            token_program_acc,
+            pool_state_acc,
+            mint_acc,
+            config_acc,
+            rewards_vault_acc,
+        ];
+        staking_contract.unstake_tokens(&program_id, &accounts, 200).unwrap();
+        assert_eq!((bank.balance(&staker_key), bank.balance(&rewards_vault_key)), (590, 10));
+    }
+
+    #[test]
+    fn test_stake_summary_tracks_positions() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_key = spl_token::id();
+        let (staker_key, pool_key, staker_auth_key, pool_state_key, mint_key, other_mint_key) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            crate::TokenContract::mint_address(&program_id).0,
+            crate::migration::migration_mint_address(&program_id).0,
+            Pubkey::new_unique(),
+        );
+        let system_program_key = Pubkey::default();
+        let (staking_key, _) = stake_address(&program_id, &staker_auth_key, &mint_key);
+        let (other_staking_key, _) = stake_address(&program_id, &staker_auth_key, &other_mint_key);
+        let (summary_key, _) = stake_summary_address(&program_id, &staker_auth_key);
+
+        let mut lamports = [0u64, 0, 0, 10_000_000, 0, 0, 0, 0, 0, 0, 0];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10] = &mut lamports;
+        let mut staking_data = vec![0u8; Stake::LEN];
+        Stake { amount: 1_000, lock_until: 5_000, rewards_owed: 50, is_initialized: true, ..Default::default() }
+            .pack_into_slice(&mut staking_data);
+        let mut other_staking_data = vec![0u8; Stake::LEN];
+        Stake { amount: 3_000, lock_until: 9_000, is_initialized: true, ..Default::default() }
+            .pack_into_slice(&mut other_staking_data);
+        let mut pool_state_data = vec![0u8; StakingPool::LEN];
+        StakingPool::pack(StakingPool { total_staked: 4_000, ..StakingPool::new(1_000) }, &mut pool_state_data).unwrap();
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        let mint = spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() };
+        spl_token::state::Mint::pack(mint, &mut mint_data).unwrap();
+        let mut summary_data = vec![0u8; StakeSummary::LEN];
+        let (mut d1, mut d2, mut d3, mut d4) = (vec![], vec![], vec![], vec![]);
+
+        let staking_acc = account_info(&staking_key, false, true, l0, &mut staking_data, &program_id);
+        let mut staker_data = token_account_data(&mint_key, &staker_auth_key, 0);
+        let staker_acc = account_info(&staker_key, false, true, l1, &mut staker_data, &token_program_key);
+        let (pool_authority_key, _) = stake_pool_authority(&program_id, &mint_key);
+        let mut pool_data = token_account_data(&mint_key, &pool_authority_key, 0);
+        let pool_acc = account_info(&pool_key, false, true, l2, &mut pool_data, &token_program_key);
+        let staker_auth = account_info(&staker_auth_key, true, true, l3, &mut d1, &program_id);
+        let token_program_acc = account_info(&token_program_key, false, false, l4, &mut d2, &program_id);
+        let pool_state_acc = account_info(&pool_state_key, false, true, l5, &mut pool_state_data, &program_id);
+        let mint_acc = account_info(&mint_key, false, false, l6, &mut mint_data, &token_program_key);
+        let summary_acc = account_info(&summary_key, false, true, l7, &mut summary_data, &program_id);
+        let system_program_acc = account_info(&system_program_key, false, false, l8, &mut d3, &system_program_key);
+        let other_staking_acc = account_info(&other_staking_key, false, true, l9, &mut other_staking_data, &program_id);
+        fund_rent_exempt(&[&staking_acc, &pool_state_acc, &other_staking_acc]);
+        let other_mint_acc = account_info(&other_mint_key, false, false, l10, &mut d4, &token_program_key);
+        bank.set_balance(&staker_key, 1_000);
+
+        let mut staking_contract = StakingContract::new();
+        let summary_prefix = [summary_acc.clone(), staker_auth.clone(), system_program_acc];
+        let duplicated = [&summary_prefix[..], &[staking_acc.clone(), mint_acc.clone(), staking_acc.clone(), mint_acc.clone()]].concat();
+        let both = [&summary_prefix[..], &[staking_acc.clone(), mint_acc.clone(), other_staking_acc, other_mint_acc]].concat();
+        bank.set_unix_timestamp(1_000);
+        assert_eq!(staking_contract.sync_stake_summary(&program_id, &duplicated), Err(ProgramError::InvalidArgument));
+        staking_contract.sync_stake_summary(&program_id, &both).unwrap();
+        let summary = StakeSummary::unpack(&summary_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((summary.total_staked, summary.weighted_unlock_at, summary.positions), (4_000, 8_000, 2));
+        assert_eq!((summary.pending_rewards, summary.owner), (50, staker_auth_key));
+
+        let (denylist_key, mut denylist_data) = denylist(&program_id, &[]);
+        let mut denylist_lamports = 0u64;
+        let denylist_acc = account_info(&denylist_key, false, false, &mut denylist_lamports, &mut denylist_data, &program_id);
+        let accounts = vec![
+            staking_acc.clone(),
+            staker_acc,
+            pool_acc,
+            staker_auth.clone(),
+            token_program_acc,
+            pool_state_acc,
+            mint_acc.clone(),
+            denylist_acc,
+            summary_acc.clone(),
+        ];
+        staking_contract.add_to_stake(&program_id, &accounts, 1_000).unwrap();
+        let stake = Stake::unpack(&staking_acc.try_borrow_data().unwrap()).unwrap();
+        let summary = StakeSummary::unpack(&summary_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((summary.total_staked, summary.weighted_unlock_at, summary.positions), (5_000, 7_400, 2));
+        assert_eq!(summary.pending_rewards, stake.rewards_owed);
+
+        // A summary that no longer covers a position fails instead of clamping.
+        let mut stale = StakeSummary { total_staked: 500, ..summary };
+        assert_eq!(stale.remove(&stake), Err(GgtError::MathOverflow.into()));
+    }
+
+    #[test]
+    fn test_init_stake_account_derives_pda() {
+        let _bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (staker_key, mint_key, system_program_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::default());
+        let (stake_key, _) = stake_address(&program_id, &staker_key, &mint_key);
+        let foreign_key = Pubkey::new_unique();
+        let (mut l0, mut l1, mut l2, mut l3, mut l4) = (0u64, 10_000_000u64, 0u64, 0u64, 0u64);
+        let mut stake_data = vec![0u8; Stake::LEN];
+        let mut foreign_data = vec![0u8; Stake::LEN];
+        let (mut d1, mut d2, mut d3) = (vec![], vec![], vec![]);
+        let stake_acc = account_info(&stake_key, false, true, &mut l0, &mut stake_data, &program_id);
+        let foreign_acc = account_info(&foreign_key, false, true, &mut l4, &mut foreign_data, &program_id);
+        let staker_acc = account_info(&staker_key, true, true, &mut l1, &mut d1, &program_id);
+        let mint_acc = account_info(&mint_key, false, false, &mut l2, &mut d2, &program_id);
+        let system_program_acc = account_info(&system_program_key, false, false, &mut l3, &mut d3, &program_id);
+        let staking_contract = StakingContract::new();
+
+        let accounts = [foreign_acc, staker_acc.clone(), mint_acc.clone(), system_program_acc.clone()];
+        assert_eq!(staking_contract.init_stake_account(&program_id, &accounts), Err(ProgramError::InvalidSeeds));
+
+        let accounts = [stake_acc.clone(), staker_acc, mint_acc, system_program_acc];
+        staking_contract.init_stake_account(&program_id, &accounts).unwrap();
+        let stake = Stake::unpack(&stake_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(stake.amount, 0);
+        assert_eq!(stake_acc.lamports(), Rent::default().minimum_balance(Stake::LEN));
+        assert_eq!(
+            staking_contract.init_stake_account(&program_id, &accounts),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+    }
+
+    #[test]
+    fn test_stake_accounts_run_the_shared_prologue() {
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let (staker_key, mint_key, pool_key, pool_state_key) =
+            (Pubkey::new_unique(), crate::TokenContract::mint_address(&program_id).0, Pubkey::new_unique(), Pubkey::new_unique());
+        let (stake_key, _) = stake_address(&program_id, &staker_key, &mint_key);
+        let staker_token_key = Pubkey::new_unique();
+        let mut stake_data = vec![0u8; Stake::LEN];
+        let mut staker_token_data = token_account_data(&mint_key, &staker_key, 0);
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        let mint = spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() };
+        spl_token::state::Mint::pack(mint, &mut mint_data).unwrap();
+        let (mut l0, mut l1, mut l2, mut l3, mut l4, mut l5, mut l6) = (0u64, 0u64, 0u64, 0u64, 0u64, 0u64, 0u64);
+        let (mut d3, mut d4, mut d5) = (vec![], vec![], vec![0u8; StakingPool::LEN]);
+        let staking_acc = account_info(&stake_key, false, true, &mut l0, &mut stake_data, &program_id);
+        let staker_acc = account_info(&staker_token_key, false, true, &mut l1, &mut staker_token_data, &token_program_id);
+        let (pool_authority_key, _) = stake_pool_authority(&program_id, &mint_key);
+        let mut pool_data = token_account_data(&mint_key, &pool_authority_key, 0);
+        let pool_acc = account_info(&pool_key, false, true, &mut l2, &mut pool_data, &token_program_id);
+        let staker_auth = account_info(&staker_key, false, false, &mut l3, &mut d3, &program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, &mut l4, &mut d4, &program_id);
+        let pool_state_acc = account_info(&pool_state_key, false, true, &mut l5, &mut d5, &program_id);
+        let mint_acc = account_info(&mint_key, false, false, &mut l6, &mut mint_data, &token_program_id);
+        let (denylist_key, mut denylist_data) = denylist(&program_id, &[staker_key]);
+        let mut denylist_lamports = 0u64;
+        let denylist_acc = account_info(&denylist_key, false, false, &mut denylist_lamports, &mut denylist_data, &program_id);
+
+        let mut accounts =
+            vec![staking_acc, staker_acc, pool_acc, staker_auth, token_program_acc, pool_state_acc, mint_acc, denylist_acc.clone()];
+        assert!(matches!(StakeAccounts::try_from(&program_id, &accounts), Err(ProgramError::MissingRequiredSignature)));
+        accounts[3].is_signer = true;
+        let ctx = StakeAccounts::try_from(&program_id, &accounts).unwrap();
+        assert_eq!(*ctx.staking_acc.key, stake_key);
+        assert_eq!(*ctx.pool_acc.key, pool_key);
+        assert!(matches!(StakeAccounts::try_from(&program_id, &accounts[..7]), Err(ProgramError::NotEnoughAccountKeys)));
+
+        // A denylisted staker cannot open or grow a position.
+        **denylist_acc.try_borrow_mut_lamports().unwrap() = 1;
+        assert!(matches!(StakeAccounts::try_from(&program_id, &accounts), Err(e) if e == GgtError::AddressDenylisted.into()));
+        **denylist_acc.try_borrow_mut_lamports().unwrap() = 0;
+
+        // Only GGT feeds the staking pool.
+        let other_mint_key = Pubkey::new_unique();
+        let (mut other_mint_lamports, mut other_mint_data) = (0u64, vec![]);
+        let mut other_mint_accounts = accounts.clone();
+        other_mint_accounts[6] = account_info(&other_mint_key, false, false, &mut other_mint_lamports, &mut other_mint_data, &token_program_id);
+        assert!(matches!(StakeAccounts::try_from(&program_id, &other_mint_accounts), Err(ProgramError::InvalidArgument)));
+
+        // Stakes must land in the PDA-owned pool that unstaking pays out of.
+        let (mut staker_pool_lamports, mut staker_pool_data) = (0u64, token_account_data(&mint_key, &staker_key, 0));
+        accounts[2] = account_info(&pool_key, false, true, &mut staker_pool_lamports, &mut staker_pool_data, &token_program_id);
+        assert!(matches!(StakeAccounts::try_from(&program_id, &accounts), Err(ProgramError::IllegalOwner)));
+    }
+
+    #[test]
+    fn test_governance_slashes_a_share_of_a_stake_into_the_penalty_pool() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (staker_key, mint_key, pool_state_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (staking_key, _) = stake_address(&program_id, &staker_key, &mint_key);
+        let (pool_key, rewards_vault_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (pool_authority_key, _) = stake_pool_authority(&program_id, &mint_key);
+        let (program_config_key, mut program_config_data) = program_config(&program_id);
+        let mut staking_data = vec![0u8; Stake::LEN];
+        Stake { amount: 400, pending_unstake: 100, is_initialized: true, ..Default::default() }.pack_into_slice(&mut staking_data);
+        let mut pool_state_data = vec![0u8; StakingPool::LEN];
+        StakingPool::pack(StakingPool { total_staked: 1_000, ..StakingPool::new(0) }, &mut pool_state_data).unwrap();
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() }, &mut mint_data)
+            .unwrap();
+        let mut pool_data = token_account_data(&mint_key, &pool_authority_key, 0);
+        let mut rewards_vault_data = token_account_data(&mint_key, &rewards_vault_authority(&program_id).0, 0);
+        let mut lamports = [0u64; 10];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9] = &mut lamports;
+        let (mut d2, mut d4, mut d6, mut d8) = (vec![], vec![], vec![], vec![]);
+        let token_program_key = spl_token::id();
+        let staking_acc = account_info(&staking_key, false, true, l0, &mut staking_data, &program_id);
+        let pool_state_acc = account_info(&pool_state_key, false, true, l1, &mut pool_state_data, &program_id);
+        let staker_acc = account_info(&staker_key, false, false, l2, &mut d2, &program_id);
+        let mint_acc = account_info(&mint_key, false, false, l3, &mut mint_data, &token_program_key);
+        let pool_acc = account_info(&pool_key, false, true, l4, &mut pool_data, &token_program_key);
+        let pool_authority_acc = account_info(&pool_authority_key, false, false, l5, &mut d4, &program_id);
+        let rewards_vault_acc = account_info(&rewards_vault_key, false, true, l6, &mut rewards_vault_data, &token_program_key);
+        let token_program_acc = account_info(&token_program_key, false, false, l7, &mut d6, &program_id);
+        let governance_acc = account_info(&GOVERNANCE_PUBKEY, true, false, l8, &mut d8, &program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, l9, &mut program_config_data, &program_id);
+        fund_rent_exempt(&[&staking_acc, &pool_state_acc]);
+        bank.set_balance(&pool_key, 1_100);
+        bank.set_unix_timestamp(1_000);
+
+        let mut accounts = vec![
+            staking_acc.clone(),
+            pool_state_acc.clone(),
+            staker_acc.clone(),
+            mint_acc,
+            pool_acc,
+            pool_authority_acc,
+            rewards_vault_acc,
+            token_program_acc,
+            governance_acc.clone(),
+            program_config_acc,
+        ];
+        let staking_contract = StakingContract::new();
+        let reason_hash = [7u8; 32];
+        assert_eq!(staking_contract.slash(&program_id, &accounts, 10_001, reason_hash), Err(ProgramError::InvalidArgument));
+        accounts[8] = staker_acc;
+        assert!(staking_contract.slash(&program_id, &accounts, 5_000, reason_hash).is_err());
+        accounts[8] = governance_acc;
+
+        staking_contract.slash(&program_id, &accounts, 5_000, reason_hash).unwrap();
+        let stake = Stake::unpack(&staking_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((stake.amount, stake.pending_unstake), (200, 50));
+        let pool = StakingPool::unpack(&pool_state_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((pool.total_staked, pool.penalty_pool), (800, 0));
+        assert_eq!(pool.reward_per_token_stored, 250 * REWARD_PRECISION / 800);
+        // The credited share is backed by tokens in the vault rewards are claimed from.
+        assert_eq!((bank.balance(&pool_key), bank.balance(&rewards_vault_key)), (850, 250));
+        let slashed = events::StakeSlashed { staker: staker_key, mint: mint_key, amount: 250, reason_hash };
+        assert_eq!(bank.events::<events::StakeSlashed>(), vec![slashed]);
+    }
+
+    #[test]
+    fn test_co_stake_enforces_ratio_and_withdraws_proportionally() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_key = spl_token::id();
+        let system_program_key = Pubkey::default();
+        let (pool_key, staker_key, staker_ggt_key, staker_partner_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (ggt_vault_key, partner_vault_key, rewards_vault_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (position_key, _) = co_stake_address(&program_id, &pool_key, &staker_key);
+        let (vault_authority_key, _) = co_stake_vault_authority(&program_id, &pool_key);
+        let (rewards_authority_key, _) = rewards_vault_authority(&program_id);
+        let (admin_key, _) = multisig::multisig_authority_address(&program_id);
+        let (ggt_mint_key, partner_mint_key) = (crate::TokenContract::mint_address(&program_id).0, Pubkey::new_unique());
+        let other_rewards_key = Pubkey::new_unique();
+        let mut lamports = [0u64; 16];
+        lamports[2] = 10_000_000;
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10, l11, l12, l13, l14, l15] = &mut lamports;
+        let mut pool_data = vec![0u8; CoStakingPool::LEN];
+        let mut position_data = vec![0u8; CoStake::LEN];
+        let mut ggt_vault_data = token_account_data(&ggt_mint_key, &vault_authority_key, 0);
+        let mut partner_vault_data = token_account_data(&partner_mint_key, &vault_authority_key, 0);
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        let mint = spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() };
+        spl_token::state::Mint::pack(mint, &mut mint_data).unwrap();
+        let mut partner_mint_data = mint_data.clone();
+        let mut rewards_vault_data = token_account_data(&ggt_mint_key, &rewards_authority_key, 0);
+        let mut other_rewards_data = rewards_vault_data.clone();
+        let (mut d1, mut d2, mut d3, mut d4, mut d7, mut d8, mut d9, mut d11) = (vec![], vec![], vec![], vec![], vec![], vec![], vec![], vec![]);
+        let pool_acc = account_info(&pool_key, false, true, l0, &mut pool_data, &program_id);
+        let admin_acc = account_info(&admin_key, true, false, l1, &mut d1, &program_id);
+        let staker_acc = account_info(&staker_key, true, true, l2, &mut d2, &program_id);
+        let staker_ggt_acc = account_info(&staker_ggt_key, false, true, l3, &mut d3, &token_program_key);
+        let staker_partner_acc = account_info(&staker_partner_key, false, true, l4, &mut d4, &token_program_key);
+        let ggt_vault_acc = account_info(&ggt_vault_key, false, true, l5, &mut ggt_vault_data, &token_program_key);
+        let partner_vault_acc = account_info(&partner_vault_key, false, true, l6, &mut partner_vault_data, &token_program_key);
+        let token_program_acc = account_info(&token_program_key, false, false, l7, &mut d7, &program_id);
+        let system_program_acc = account_info(&system_program_key, false, false, l8, &mut d8, &program_id);
+        let vault_authority_acc = account_info(&vault_authority_key, false, false, l9, &mut d9, &program_id);
+        let rewards_vault_acc = account_info(&rewards_vault_key, false, true, l10, &mut rewards_vault_data, &token_program_key);
+        let other_rewards_acc = account_info(&other_rewards_key, false, true, l15, &mut other_rewards_data, &token_program_key);
+        let rewards_authority_acc = account_info(&rewards_authority_key, false, false, l11, &mut d11, &program_id);
+        let position_acc = account_info(&position_key, false, true, l12, &mut position_data, &program_id);
+        let ggt_mint_acc = account_info(&ggt_mint_key, false, false, l13, &mut mint_data, &token_program_key);
+        let partner_mint_acc = account_info(&partner_mint_key, false, false, l14, &mut partner_mint_data, &token_program_key);
+        fund_rent_exempt(&[&pool_acc]);
+        bank.set_balance(&staker_ggt_key, 1_000);
+        bank.set_balance(&staker_partner_key, 1_000);
+        bank.set_balance(&rewards_vault_key, 1_000);
+        bank.set_balance(&other_rewards_key, 1_000);
+        bank.set_unix_timestamp(1_000);
+        let staking_contract = StakingContract::new();
+
+        let init_accounts = [
+            pool_acc.clone(),
+            admin_acc,
+            ggt_vault_acc.clone(),
+            partner_vault_acc.clone(),
+            ggt_mint_acc.clone(),
+            rewards_vault_acc.clone(),
+        ];
+        // The GGT side of the pool must hold GGT.
+        let mut partner_as_ggt = init_accounts.clone();
+        partner_as_ggt.swap(2, 3);
+        partner_as_ggt[4] = partner_mint_acc.clone();
+        assert_eq!(staking_contract.initialize_co_staking_pool(&program_id, &partner_as_ggt, 5_000, 2_500), Err(ProgramError::InvalidArgument));
+        staking_contract.initialize_co_staking_pool(&program_id, &init_accounts, 5_000, 2_500).unwrap();
+        assert_eq!(CoStakingPool::unpack(&pool_acc.try_borrow_data().unwrap()).unwrap().rewards_vault, rewards_vault_key);
+        let mut pool = CoStakingPool::unpack(&pool_acc.try_borrow_data().unwrap()).unwrap();
+        pool.rewards.schedule_rewards(1_000, 1_000, 1, 2_000).unwrap();
+        CoStakingPool::pack(pool, &mut pool_acc.try_borrow_mut_data().unwrap()).unwrap();
+
+        let stake_accounts = [
+            position_acc.clone(),
+            pool_acc.clone(),
+            staker_acc.clone(),
+            staker_ggt_acc.clone(),
+            staker_partner_acc.clone(),
+            ggt_vault_acc.clone(),
+            partner_vault_acc.clone(),
+            token_program_acc.clone(),
+            system_program_acc,
+            ggt_mint_acc.clone(),
+            partner_mint_acc.clone(),
+        ];
+        assert_eq!(
+            staking_contract.co_stake(&program_id, &stake_accounts, 400, 100),
+            Err(GgtError::CoStakeRatioMismatch.into())
+        );
+        // The mints must be the pool's, in the pool's order.
+        let mut swapped_mints = stake_accounts.clone();
+        swapped_mints.swap(9, 10);
+        assert_eq!(staking_contract.co_stake(&program_id, &swapped_mints, 400, 200), Err(ProgramError::InvalidArgument));
+        staking_contract.co_stake(&program_id, &stake_accounts, 400, 200).unwrap();
+        assert_eq!((bank.balance(&ggt_vault_key), bank.balance(&partner_vault_key)), (400, 200));
+        let pool = CoStakingPool::unpack(&pool_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(pool.rewards.total_staked, 500);
+
+        bank.set_unix_timestamp(1_100);
+        let claim_accounts = [
+            position_acc.clone(),
+            pool_acc.clone(),
+            staker_acc.clone(),
+            staker_ggt_acc.clone(),
+            rewards_vault_acc,
+            rewards_authority_acc,
+            token_program_acc.clone(),
+            ggt_mint_acc.clone(),
+        ];
+        // Only the pool's own rewards vault pays out, not any account the rewards authority owns.
+        let mut other_vault = claim_accounts.clone();
+        other_vault[4] = other_rewards_acc;
+        assert_eq!(staking_contract.claim_co_stake_rewards(&program_id, &other_vault), Err(ProgramError::InvalidArgument));
+        staking_contract.claim_co_stake_rewards(&program_id, &claim_accounts).unwrap();
+        assert_eq!((bank.balance(&staker_ggt_key), bank.balance(&other_rewards_key)), (700, 1_000));
+
+        let unstake_accounts = [
+            position_acc.clone(),
+            pool_acc.clone(),
+            staker_acc,
+            staker_ggt_acc,
+            staker_partner_acc,
+            ggt_vault_acc,
+            partner_vault_acc,
+            vault_authority_acc,
+            token_program_acc,
+            ggt_mint_acc,
+            partner_mint_acc,
+        ];
+        staking_contract.co_unstake(&program_id, &unstake_accounts, 100).unwrap();
+        assert_eq!((bank.balance(&ggt_vault_key), bank.balance(&partner_vault_key)), (300, 150));
+        let position = CoStake::unpack(&position_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((position.ggt_amount, position.partner_amount, position.weight.amount), (300, 150, 375));
+    }
+
+    #[test]
+    fn test_convert_legacy_stake_moves_into_pda_and_closes_old_account() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_key = spl_token::id();
+        let (legacy_key, staker_key, mint_key, pool_state_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), crate::TokenContract::mint_address(&program_id).0, Pubkey::new_unique());
+        let (staker_token_key, pool_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let system_program_key = Pubkey::default();
+        let (stake_key, _) = stake_address(&program_id, &staker_key, &mint_key);
+        let (pool_authority_key, _) = stake_pool_authority(&program_id, &mint_key);
+        let (mut l0, mut l1, mut l2, mut l3, mut l4, mut l5) = (2_000_000u64, 10_000_000u64, 0u64, 0u64, 0u64, 0u64);
+        let (mut l7, mut l8, mut l9, mut l10) = (0u64, 0u64, 0u64, 0u64);
+        let mut legacy_data = vec![0u8; LegacyStake::LEN];
+        legacy_data[0..8].copy_from_slice(&700u64.to_le_bytes());
+        legacy_data[8..16].copy_from_slice(&5_000i64.to_le_bytes());
+        legacy_data[16] = 1;
+        let mut stake_data = vec![0u8; Stake::LEN];
+        let mut pool_state_data = vec![0u8; StakingPool::LEN];
+        StakingPool::pack(StakingPool { total_staked: 300, ..StakingPool::new(0) }, &mut pool_state_data).unwrap();
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        let mint = spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() };
+        spl_token::state::Mint::pack(mint, &mut mint_data).unwrap();
+        let mut staker_token_data = token_account_data(&mint_key, &staker_key, 0);
+        let mut pool_data = token_account_data(&mint_key, &pool_authority_key, 0);
+        let (denylist_key, mut denylist_data) = denylist(&program_id, &[]);
+        let (mut d1, mut d5, mut d9) = (vec![], vec![], vec![]);
+        let legacy_acc = account_info(&legacy_key, true, true, &mut l0, &mut legacy_data, &program_id);
+        let staker_acc = account_info(&staker_key, true, true, &mut l1, &mut d1, &program_id);
+        let stake_acc = account_info(&stake_key, false, true, &mut l2, &mut stake_data, &program_id);
+        let mint_acc = account_info(&mint_key, false, false, &mut l3, &mut mint_data, &token_program_key);
+        let pool_state_acc = account_info(&pool_state_key, false, true, &mut l4, &mut pool_state_data, &program_id);
+        let system_program_acc = account_info(&system_program_key, false, false, &mut l5, &mut d5, &program_id);
+        let staker_token_acc = account_info(&staker_token_key, false, true, &mut l7, &mut staker_token_data, &token_program_key);
+        let pool_acc = account_info(&pool_key, false, true, &mut l8, &mut pool_data, &token_program_key);
+        let token_program_acc = account_info(&token_program_key, false, false, &mut l9, &mut d9, &program_id);
+        let denylist_acc = account_info(&denylist_key, false, false, &mut l10, &mut denylist_data, &program_id);
+        fund_rent_exempt(&[&pool_state_acc]);
+        bank.set_unix_timestamp(100);
+        bank.set_balance(&staker_token_key, 700);
+
+        let accounts = [
+            legacy_acc.clone(),
+            staker_acc.clone(),
+            stake_acc.clone(),
+            mint_acc,
+            pool_state_acc.clone(),
+            system_program_acc,
+            staker_token_acc.clone(),
+            pool_acc,
+            token_program_acc,
+            denylist_acc,
+        ];
+        // The position is only created for GGT, at the address derived with it.
+        let other_mint_key = Pubkey::new_unique();
+        let (mut l6, mut d6) = (0u64, vec![]);
+        let mut other_mint = accounts.clone();
+        other_mint[3] = account_info(&other_mint_key, false, false, &mut l6, &mut d6, &token_program_key);
+        assert_eq!(StakingContract::new().convert_legacy_stake(&program_id, &other_mint), Err(ProgramError::InvalidArgument));
+        // The legacy amount is paid into the stake pool, not only credited to it.
+        let mut wrong_pool = accounts.clone();
+        wrong_pool[7] = staker_token_acc;
+        assert_eq!(StakingContract::new().convert_legacy_stake(&program_id, &wrong_pool), Err(ProgramError::IllegalOwner));
+        StakingContract::new().convert_legacy_stake(&program_id, &accounts).unwrap();
+
+        let stake = Stake::unpack(&stake_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((stake.amount, stake.lock_until), (700, 5_000));
+        assert_eq!(StakingPool::unpack(&pool_state_acc.try_borrow_data().unwrap()).unwrap().total_staked, 1_000);
+        assert_eq!((bank.balance(&staker_token_key), bank.balance(&pool_key)), (0, 700));
+        assert_eq!(legacy_acc.lamports(), 0);
+        assert_eq!(staker_acc.lamports(), 12_000_000 - Rent::default().minimum_balance(Stake::LEN));
+        assert_eq!(
+            StakingContract::new().convert_legacy_stake(&program_id, &accounts),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Writing program state into accounts. A packed layout only fits an account
+//! sized for it, and state in an account that is not rent exempt can be
+//! collected away, so every write goes through `pack_state`, and accounts the
+//! program sets up itself are created by `create_state_account` at exactly
+//! the size and balance their layout needs.
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+
+/// Checks that `acc` has room for a `len`-byte layout and holds enough
+/// lamports to stay rent exempt at its current size.
+pub fn assert_state_account(acc: &AccountInfo, len: usize) -> ProgramResult {
+    if acc.data_len() < len {
+        msg!("Account {} holds {} bytes, {} needed", acc.key, acc.data_len(), len);
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    if !Rent::get()?.is_exempt(acc.lamports(), acc.data_len()) {
+        msg!("Account {} is not rent exempt", acc.key);
+        return Err(ProgramError::AccountNotRentExempt);
+    }
+    Ok(())
+}
+
+/// Packs `state` into the start of `acc` once `assert_state_account` passes.
+pub fn pack_state<T: Pack>(state: &T, acc: &AccountInfo) -> ProgramResult {
+    assert_state_account(acc, T::LEN)?;
+    state.pack_into_slice(&mut acc.try_borrow_mut_data()?[..T::LEN]);
+    Ok(())
+}
+
+/// Creates `new_acc` owned by the program with `space` bytes, funded by
+/// `payer_acc` to the rent-exempt minimum. `signer_seeds` signs for a PDA;
+/// pass none when `new_acc` is a keypair that signed the transaction.
+/// Anyone can send lamports to an address before it is created, which makes
+/// `create_account` fail, so an already funded `new_acc` is topped up,
+/// allocated and assigned instead.
+pub fn create_state_account<'a>(
+    program_id: &Pubkey,
+    payer_acc: &AccountInfo<'a>,
+    new_acc: &AccountInfo<'a>,
+    system_program_acc: &AccountInfo<'a>,
+    space: usize,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let rent_exempt = Rent::get()?.minimum_balance(space);
+    if new_acc.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(payer_acc.key, new_acc.key, rent_exempt, space as u64, program_id),
+            &[payer_acc.clone(), new_acc.clone(), system_program_acc.clone()],
+            signer_seeds,
+        );
+    }
+    let shortfall = rent_exempt.saturating_sub(new_acc.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer_acc.key, new_acc.key, shortfall),
+            &[payer_acc.clone(), new_acc.clone(), system_program_acc.clone()],
+        )?;
+    }
+    invoke_signed(&system_instruction::allocate(new_acc.key, space as u64), &[new_acc.clone(), system_program_acc.clone()], signer_seeds)?;
+    invoke_signed(&system_instruction::assign(new_acc.key, program_id), &[new_acc.clone(), system_program_acc.clone()], signer_seeds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::staking_contract::Stake;
+    use crate::test_utils::{account_info, SimulatedBank};
+
+    #[test]
+    fn test_pack_state_rejects_short_and_rent_paying_accounts() {
+        let _bank = SimulatedBank::start();
+        let (program_id, key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let exempt = Rent::default().minimum_balance(Stake::LEN);
+        let stake = Stake { amount: 500, is_initialized: true, ..Default::default() };
+        let (mut short_lamports, mut short_data) = (exempt, vec![0u8; Stake::LEN - 1]);
+        let (mut lamports, mut data) = (exempt - 1, vec![0u8; Stake::LEN]);
+        let short_acc = account_info(&key, false, true, &mut short_lamports, &mut short_data, &program_id);
+        let acc = account_info(&key, false, true, &mut lamports, &mut data, &program_id);
+
+        assert_eq!(pack_state(&stake, &short_acc), Err(ProgramError::AccountDataTooSmall));
+        assert_eq!(pack_state(&stake, &acc), Err(ProgramError::AccountNotRentExempt));
+        **acc.try_borrow_mut_lamports().unwrap() = exempt;
+        pack_state(&stake, &acc).unwrap();
+        assert_eq!(Stake::unpack(&acc.try_borrow_data().unwrap()).unwrap().amount, 500);
+    }
+}
@@ -0,0 +1,644 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use spl_token::{instruction as token_instruction, state::Account as TokenAccount};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use crate::error::GgtError;
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const STREAM_VAULT_SEED: &[u8] = b"stream_vault";
+pub const MAX_STREAM_RECIPIENTS: usize = 4;
+/// Share of the remaining vault balance paid to whoever liquidates an underfunded stream.
+pub const LIQUIDATION_INCENTIVE_BPS: u64 = 100;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct StreamRecipient {
+    pub token_account: Pubkey,
+    pub rate_per_second: u64,
+    pub withdrawn: u64,
+}
+
+/// Escrow and time bounds of a new stream. A zero `starts_at` starts it on
+/// creation. A zero `ends_at` leaves it open-ended, funded by the payer
+/// topping up the vault; otherwise `deposit` must cover every recipient
+/// until `ends_at`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct StreamSchedule {
+    pub deposit: u64,
+    pub starts_at: i64,
+    pub ends_at: i64,
+}
+
+/// A payment stream or subscription paying each recipient a fixed rate out of a
+/// PDA-owned vault, either escrowed up front for a fixed term or kept topped
+/// up by the payer.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct PaymentStream {
+    pub payer: Pubkey,
+    pub vault: Pubkey,
+    pub recipients: Vec<StreamRecipient>,
+    pub started_at: i64,
+    /// Accrual stops here; zero for open-ended streams.
+    pub ends_at: i64,
+    pub closed: bool,
+    pub is_initialized: bool,
+}
+
+impl PaymentStream {
+    /// Amount `recipient` has earned but not yet withdrawn at `now`.
+    pub fn accrued(&self, recipient: &StreamRecipient, now: i64) -> u64 {
+        let until = if self.ends_at == 0 { now } else { now.min(self.ends_at) };
+        let elapsed = until.saturating_sub(self.started_at).max(0) as u64;
+        recipient.rate_per_second.saturating_mul(elapsed).saturating_sub(recipient.withdrawn)
+    }
+
+    pub fn total_accrued(&self, now: i64) -> u64 {
+        self.recipients.iter().fold(0u64, |total, r| total.saturating_add(self.accrued(r, now)))
+    }
+}
+
+impl Sealed for PaymentStream {}
+
+impl IsInitialized for PaymentStream {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for PaymentStream {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [124, 85, 193, 22, 93, 1, 143, 75];
+}
+
+impl Pack for PaymentStream {
+    const LEN: usize = 284; // discriminator (8) + version (1) + Pubkey (32) * 2 + count (1) + (Pubkey (32) + u64 (8) * 2) * MAX_STREAM_RECIPIENTS + i64 (8) * 2 + bool (1) * 2
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        let mut cursor = 0;
+        dst[cursor..cursor + 32].copy_from_slice(self.payer.as_ref());
+        cursor += 32;
+        dst[cursor..cursor + 32].copy_from_slice(self.vault.as_ref());
+        cursor += 32;
+        dst[cursor] = self.recipients.len() as u8;
+        cursor += 1;
+        for slot in 0..MAX_STREAM_RECIPIENTS {
+            let recipient = self.recipients.get(slot).copied().unwrap_or(StreamRecipient {
+                token_account: Pubkey::default(),
+                rate_per_second: 0,
+                withdrawn: 0,
+            });
+            dst[cursor..cursor + 32].copy_from_slice(recipient.token_account.as_ref());
+            cursor += 32;
+            dst[cursor..cursor + 8].copy_from_slice(&recipient.rate_per_second.to_le_bytes());
+            cursor += 8;
+            dst[cursor..cursor + 8].copy_from_slice(&recipient.withdrawn.to_le_bytes());
+            cursor += 8;
+        }
+        dst[cursor..cursor + 8].copy_from_slice(&self.started_at.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.ends_at.to_le_bytes());
+        cursor += 8;
+        dst[cursor] = self.closed as u8;
+        cursor += 1;
+        dst[cursor] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let mut cursor = 0;
+        let payer = Pubkey::new_from_array(src[cursor..cursor + 32].try_into().unwrap());
+        cursor += 32;
+        let vault = Pubkey::new_from_array(src[cursor..cursor + 32].try_into().unwrap());
+        cursor += 32;
+        let count = src[cursor] as usize;
+        cursor += 1;
+        if count > MAX_STREAM_RECIPIENTS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut recipients = Vec::with_capacity(count);
+        for slot in 0..MAX_STREAM_RECIPIENTS {
+            if slot < count {
+                recipients.push(StreamRecipient {
+                    token_account: Pubkey::new_from_array(src[cursor..cursor + 32].try_into().unwrap()),
+                    rate_per_second: u64::from_le_bytes(src[cursor + 32..cursor + 40].try_into().unwrap()),
+                    withdrawn: u64::from_le_bytes(src[cursor + 40..cursor + 48].try_into().unwrap()),
+                });
+            }
+            cursor += 48;
+        }
+        let started_at = i64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let ends_at = i64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let closed = src[cursor] != 0;
+        cursor += 1;
+        let is_initialized = src[cursor] != 0;
+        Ok(PaymentStream {
+            payer,
+            vault,
+            recipients,
+            started_at,
+            ends_at,
+            closed,
+            is_initialized,
+        })
+    }
+}
+
+pub struct Streaming;
+
+/// `payer_token_acc` and `token_program_acc` are only needed to escrow a deposit.
+pub struct CreateStreamAccounts<'a, 'info> {
+    pub stream_acc: &'a AccountInfo<'info>,
+    pub payer_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub payer_token_acc: Option<&'a AccountInfo<'info>>,
+    pub token_program_acc: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> CreateStreamAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            stream_acc: next_account_info(account_info_iter)?,
+            payer_acc: next_account_info(account_info_iter)?,
+            vault_acc: next_account_info(account_info_iter)?,
+            payer_token_acc: next_account_info(account_info_iter).ok(),
+            token_program_acc: next_account_info(account_info_iter).ok(),
+        };
+        assert_signer(ctx.payer_acc)?;
+        assert_owned_by(ctx.stream_acc, program_id)?;
+        let vault = TokenAccount::unpack(&ctx.vault_acc.try_borrow_data()?)?;
+        if vault.owner != Streaming::vault_authority(program_id, ctx.stream_acc.key).0 {
+            msg!("Stream vault is not owned by this stream's vault authority PDA");
+            return Err(ProgramError::IllegalOwner);
+        }
+        if vault.amount != 0 {
+            msg!("Stream vault must start empty");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(ctx)
+    }
+}
+
+/// Accounts for paying out of a stream vault: `destination_acc` is the recipient
+/// for withdrawals and the caller for liquidations, whose recipient token
+/// accounts follow in `recipient_accs`.
+pub struct StreamPayoutAccounts<'a, 'info> {
+    pub stream_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub destination_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub recipient_accs: &'a [AccountInfo<'info>],
+    pub authority_bump: u8,
+}
+
+impl<'a, 'info> StreamPayoutAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let stream_acc = next_account_info(account_info_iter)?;
+        let vault_acc = next_account_info(account_info_iter)?;
+        let destination_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+
+        assert_owned_by(stream_acc, program_id)?;
+        let authority_bump = assert_pda(authority_acc, Streaming::vault_authority(program_id, stream_acc.key))?;
+        Ok(Self {
+            stream_acc,
+            vault_acc,
+            destination_acc,
+            authority_acc,
+            token_program_acc,
+            recipient_accs: account_info_iter.as_slice(),
+            authority_bump,
+        })
+    }
+
+    fn pay(&self, destination_acc: &AccountInfo<'info>, amount: u64) -> ProgramResult {
+        Streaming::pay(self.stream_acc, self.token_program_acc, self.vault_acc, self.authority_acc, self.authority_bump, destination_acc, amount)
+    }
+}
+
+/// Recipient token accounts follow in stream order in `recipient_accs`.
+pub struct CancelStreamAccounts<'a, 'info> {
+    pub stream_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub payer_acc: &'a AccountInfo<'info>,
+    pub payer_token_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub recipient_accs: &'a [AccountInfo<'info>],
+    pub authority_bump: u8,
+}
+
+impl<'a, 'info> CancelStreamAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let stream_acc = next_account_info(account_info_iter)?;
+        let vault_acc = next_account_info(account_info_iter)?;
+        let payer_acc = next_account_info(account_info_iter)?;
+        let payer_token_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(payer_acc)?;
+        assert_owned_by(stream_acc, program_id)?;
+        if TokenAccount::unpack(&payer_token_acc.try_borrow_data()?)?.owner != *payer_acc.key {
+            return Err(ProgramError::IllegalOwner);
+        }
+        let authority_bump = assert_pda(authority_acc, Streaming::vault_authority(program_id, stream_acc.key))?;
+        Ok(Self {
+            stream_acc,
+            vault_acc,
+            payer_acc,
+            payer_token_acc,
+            authority_acc,
+            token_program_acc,
+            recipient_accs: account_info_iter.as_slice(),
+            authority_bump,
+        })
+    }
+}
+
+impl Streaming {
+    /// Authority over the vault of the stream stored at `stream`.
+    pub fn vault_authority(program_id: &Pubkey, stream: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[STREAM_VAULT_SEED, stream.as_ref()], program_id)
+    }
+
+    fn pay<'info>(
+        stream_acc: &AccountInfo<'info>,
+        token_program_acc: &AccountInfo<'info>,
+        vault_acc: &AccountInfo<'info>,
+        authority_acc: &AccountInfo<'info>,
+        authority_bump: u8,
+        destination_acc: &AccountInfo<'info>,
+        amount: u64,
+    ) -> ProgramResult {
+        if amount == 0 {
+            return Ok(());
+        }
+        let ix = token_instruction::transfer(token_program_acc.key, vault_acc.key, destination_acc.key, authority_acc.key, &[], amount)?;
+        invoke_signed(
+            &ix,
+            &[vault_acc.clone(), destination_acc.clone(), authority_acc.clone(), token_program_acc.clone()],
+            &[&[STREAM_VAULT_SEED, stream_acc.key.as_ref(), &[authority_bump]]],
+        )
+    }
+
+    fn load_open_stream(stream_acc: &AccountInfo) -> Result<PaymentStream, ProgramError> {
+        let stream = PaymentStream::unpack(&stream_acc.try_borrow_data()?)?;
+        if stream.closed {
+            return Err(GgtError::StreamClosed.into());
+        }
+        Ok(stream)
+    }
+
+    /// Opens a stream paying each `(token_account, rate_per_second)` from `vault`
+    /// on `schedule`, escrowing its deposit from the payer's token account.
+    pub fn create_stream(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        recipients: Vec<(Pubkey, u64)>,
+        schedule: StreamSchedule,
+    ) -> ProgramResult {
+        let CreateStreamAccounts { stream_acc, payer_acc, vault_acc, payer_token_acc, token_program_acc } =
+            CreateStreamAccounts::try_from(program_id, accounts)?;
+        if PaymentStream::unpack_unchecked(&stream_acc.try_borrow_data()?)?.is_initialized {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        if recipients.is_empty() || recipients.len() > MAX_STREAM_RECIPIENTS {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let now = Clock::get()?.unix_timestamp;
+        let started_at = if schedule.starts_at == 0 { now } else { schedule.starts_at };
+        if schedule.ends_at != 0 {
+            let duration = schedule.ends_at.checked_sub(started_at).filter(|d| *d > 0).ok_or(ProgramError::InvalidArgument)?;
+            let rate = recipients.iter().try_fold(0u64, |total, (_, rate)| total.checked_add(*rate)).ok_or(GgtError::MathOverflow)?;
+            let obligation = rate.checked_mul(duration as u64).ok_or(GgtError::MathOverflow)?;
+            if schedule.deposit < obligation {
+                msg!("Deposit {} does not cover the {} the stream pays out", schedule.deposit, obligation);
+                return Err(ProgramError::InsufficientFunds);
+            }
+        }
+        if schedule.deposit > 0 {
+            let (Some(payer_token_acc), Some(token_program_acc)) = (payer_token_acc, token_program_acc) else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            let ix = token_instruction::transfer(token_program_acc.key, payer_token_acc.key, vault_acc.key, payer_acc.key, &[], schedule.deposit)?;
+            invoke(&ix, &[payer_token_acc.clone(), vault_acc.clone(), payer_acc.clone(), token_program_acc.clone()])?;
+        }
+
+        let stream = PaymentStream {
+            payer: *payer_acc.key,
+            vault: *vault_acc.key,
+            recipients: recipients
+                .into_iter()
+                .map(|(token_account, rate_per_second)| StreamRecipient { token_account, rate_per_second, withdrawn: 0 })
+                .collect(),
+            started_at,
+            ends_at: schedule.ends_at,
+            closed: false,
+            is_initialized: true,
+        };
+        pack_state(&stream, stream_acc)?;
+        msg!("Stream created with a deposit of {}", schedule.deposit);
+        Ok(())
+    }
+
+    /// Pays a recipient everything accrued so far. Anyone may crank it since funds
+    /// can only go to the recorded token account.
+    pub fn withdraw_stream(program_id: &Pubkey, accounts: &[AccountInfo], recipient_index: u8) -> ProgramResult {
+        let ctx = StreamPayoutAccounts::try_from(program_id, accounts)?;
+        let (stream_acc, recipient_acc) = (ctx.stream_acc, ctx.destination_acc);
+        let mut stream = Self::load_open_stream(stream_acc)?;
+        if stream.vault != *ctx.vault_acc.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let now = Clock::get()?.unix_timestamp;
+        let recipient = *stream.recipients.get(recipient_index as usize).ok_or(ProgramError::InvalidArgument)?;
+        if recipient.token_account != *recipient_acc.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let amount = stream.accrued(&recipient, now);
+        stream.recipients[recipient_index as usize].withdrawn = recipient.withdrawn.saturating_add(amount);
+        pack_state(&stream, stream_acc)?;
+
+        ctx.pay(recipient_acc, amount)?;
+        msg!("Withdrew {} from stream", amount);
+        Ok(())
+    }
+
+    /// Closes the stream early on the payer's behalf: each recipient is paid what
+    /// it has accrued so far and the rest of the vault goes back to the payer.
+    /// Recipient token accounts follow in stream order. A vault that cannot
+    /// cover what has accrued must be liquidated instead.
+    pub fn cancel_stream(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let CancelStreamAccounts {
+            stream_acc,
+            vault_acc,
+            payer_acc,
+            payer_token_acc,
+            authority_acc,
+            token_program_acc,
+            recipient_accs,
+            authority_bump,
+        } = CancelStreamAccounts::try_from(program_id, accounts)?;
+        let mut stream = Self::load_open_stream(stream_acc)?;
+        if stream.vault != *vault_acc.key || stream.payer != *payer_acc.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let now = Clock::get()?.unix_timestamp;
+        let balance = TokenAccount::unpack(&vault_acc.try_borrow_data()?)?.amount;
+        let owed = stream.total_accrued(now);
+        let Some(refund) = balance.checked_sub(owed) else {
+            msg!("Vault holds {} of the {} accrued; liquidate the stream instead", balance, owed);
+            return Err(ProgramError::InsufficientFunds);
+        };
+
+        let mut payouts = Vec::with_capacity(stream.recipients.len());
+        let recipient_iter = &mut recipient_accs.iter();
+        for recipient in stream.recipients.iter() {
+            let recipient_acc = next_account_info(recipient_iter)?;
+            if recipient.token_account != *recipient_acc.key {
+                return Err(ProgramError::InvalidArgument);
+            }
+            payouts.push((recipient_acc, stream.accrued(recipient, now)));
+        }
+
+        for (slot, (_, accrued)) in payouts.iter().enumerate() {
+            stream.recipients[slot].withdrawn = stream.recipients[slot].withdrawn.saturating_add(*accrued);
+        }
+        stream.closed = true;
+        pack_state(&stream, stream_acc)?;
+
+        for (recipient_acc, accrued) in payouts {
+            Self::pay(stream_acc, token_program_acc, vault_acc, authority_acc, authority_bump, recipient_acc, accrued)?;
+        }
+        Self::pay(stream_acc, token_program_acc, vault_acc, authority_acc, authority_bump, payer_token_acc, refund)?;
+        msg!("Cancelled stream: {} paid out, {} refunded", owed, refund);
+        Ok(())
+    }
+
+    /// Closes a stream whose vault can no longer cover accrued obligations. The caller
+    /// earns `LIQUIDATION_INCENTIVE_BPS` of the vault; the rest is split pro-rata by
+    /// what each recipient is owed. Recipient token accounts follow in stream order.
+    pub fn liquidate_underfunded(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let ctx = StreamPayoutAccounts::try_from(program_id, accounts)?;
+        let (stream_acc, vault_acc, caller_token_acc) = (ctx.stream_acc, ctx.vault_acc, ctx.destination_acc);
+        let mut stream = Self::load_open_stream(stream_acc)?;
+        if stream.vault != *vault_acc.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let now = Clock::get()?.unix_timestamp;
+        let balance = TokenAccount::unpack(&vault_acc.try_borrow_data()?)?.amount;
+        let owed = stream.total_accrued(now);
+        if owed <= balance {
+            return Err(GgtError::StreamSolvent.into());
+        }
+
+        let incentive = (balance as u128 * LIQUIDATION_INCENTIVE_BPS as u128 / 10_000) as u64;
+        let remaining = balance.checked_sub(incentive).ok_or(GgtError::MathOverflow)?;
+        let mut payouts = Vec::with_capacity(stream.recipients.len());
+        let recipient_iter = &mut ctx.recipient_accs.iter();
+        for recipient in stream.recipients.iter() {
+            let recipient_acc = next_account_info(recipient_iter)?;
+            if recipient.token_account != *recipient_acc.key {
+                return Err(ProgramError::InvalidArgument);
+            }
+            let share = (remaining as u128 * stream.accrued(recipient, now) as u128 / owed as u128) as u64;
+            payouts.push((recipient_acc, share));
+        }
+
+        for (slot, (_, share)) in payouts.iter().enumerate() {
+            stream.recipients[slot].withdrawn = stream.recipients[slot].withdrawn.saturating_add(*share);
+        }
+        stream.closed = true;
+        pack_state(&stream, stream_acc)?;
+
+        ctx.pay(caller_token_acc, incentive)?;
+        for (recipient_acc, share) in payouts {
+            ctx.pay(recipient_acc, share)?;
+        }
+        msg!("Liquidated stream: {} owed, {} available", owed, balance);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{account_info, fund_rent_exempt, SimulatedBank};
+    use solana_program::program_option::COption;
+    use spl_token::state::AccountState;
+
+    #[test]
+    fn test_liquidate_underfunded_stream_pro_rata() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let (stream_key, payer_key, vault_key, caller_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (authority_key, _) = Streaming::vault_authority(&program_id, &stream_key);
+        let (alice_key, bob_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut lamports = [0u64; 8];
+        let [l0, l1, l2, l3, l4, l5, l6, l7] = &mut lamports;
+        let mut stream_data = vec![0u8; PaymentStream::LEN];
+        let mut vault_data = vec![0u8; TokenAccount::LEN];
+        let vault = TokenAccount {
+            mint: Pubkey::new_unique(),
+            owner: authority_key,
+            amount: 0,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
+        TokenAccount::pack(vault, &mut vault_data).unwrap();
+        let (mut d1, mut d3, mut d4, mut d5, mut d6, mut d7) = (vec![], vec![], vec![], vec![], vec![], vec![]);
+        let stream_acc = account_info(&stream_key, false, true, l0, &mut stream_data, &program_id);
+        fund_rent_exempt(&[&stream_acc]);
+        let payer_acc = account_info(&payer_key, true, false, l1, &mut d1, &program_id);
+        let vault_acc = account_info(&vault_key, false, true, l2, &mut vault_data, &token_program_id);
+        let caller_acc = account_info(&caller_key, false, true, l3, &mut d3, &token_program_id);
+        let authority_acc = account_info(&authority_key, false, false, l4, &mut d4, &program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, l5, &mut d5, &program_id);
+        let alice_acc = account_info(&alice_key, false, true, l6, &mut d6, &token_program_id);
+        let bob_acc = account_info(&bob_key, false, true, l7, &mut d7, &token_program_id);
+
+        bank.set_unix_timestamp(1_000);
+        let create_accounts = [stream_acc.clone(), payer_acc, vault_acc.clone()];
+        Streaming::create_stream(&program_id, &create_accounts, vec![(alice_key, 2), (bob_key, 1)], StreamSchedule::default()).unwrap();
+
+        // The payer tops the open-ended stream up once.
+        bank.set_balance(&vault_key, 300);
+        TokenAccount::pack(TokenAccount { amount: 300, ..vault }, &mut vault_acc.try_borrow_mut_data().unwrap()).unwrap();
+
+        let accounts = vec![stream_acc.clone(), vault_acc, caller_acc, authority_acc, token_program_acc, alice_acc, bob_acc];
+        bank.set_unix_timestamp(1_050);
+        assert_eq!(Streaming::liquidate_underfunded(&program_id, &accounts), Err(GgtError::StreamSolvent.into()));
+
+        bank.set_unix_timestamp(1_200);
+        Streaming::liquidate_underfunded(&program_id, &accounts).unwrap();
+        assert_eq!(bank.balance(&caller_key), 3);
+        assert_eq!(bank.balance(&alice_key), 198);
+        assert_eq!(bank.balance(&bob_key), 99);
+        assert!(PaymentStream::unpack(&stream_acc.try_borrow_data().unwrap()).unwrap().closed);
+        assert_eq!(Streaming::liquidate_underfunded(&program_id, &accounts), Err(GgtError::StreamClosed.into()));
+    }
+
+    #[test]
+    fn test_scheduled_stream_escrows_and_cancel_splits_the_rest() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let (stream_key, payer_key, payer_token_key, vault_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (authority_key, _) = Streaming::vault_authority(&program_id, &stream_key);
+        let (alice_key, bob_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let token_account = |owner| {
+            let mut data = vec![0u8; TokenAccount::LEN];
+            let account = TokenAccount { owner, state: AccountState::Initialized, ..TokenAccount::default() };
+            TokenAccount::pack(account, &mut data).unwrap();
+            data
+        };
+        let mut lamports = [0u64; 8];
+        let [l0, l1, l2, l3, l4, l5, l6, l7] = &mut lamports;
+        let mut stream_data = vec![0u8; PaymentStream::LEN];
+        let (mut vault_data, mut payer_token_data) = (token_account(authority_key), token_account(payer_key));
+        let (mut d1, mut d4, mut d5, mut d6, mut d7) = (vec![], vec![], vec![], vec![], vec![]);
+        let stream_acc = account_info(&stream_key, false, true, l0, &mut stream_data, &program_id);
+        fund_rent_exempt(&[&stream_acc]);
+        let payer_acc = account_info(&payer_key, true, false, l1, &mut d1, &program_id);
+        let vault_acc = account_info(&vault_key, false, true, l2, &mut vault_data, &token_program_id);
+        let payer_token_acc = account_info(&payer_token_key, false, true, l3, &mut payer_token_data, &token_program_id);
+        let authority_acc = account_info(&authority_key, false, false, l4, &mut d4, &program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, l5, &mut d5, &program_id);
+        let alice_acc = account_info(&alice_key, false, true, l6, &mut d6, &token_program_id);
+        let bob_acc = account_info(&bob_key, false, true, l7, &mut d7, &token_program_id);
+        bank.set_balance(&payer_token_key, 500);
+
+        bank.set_unix_timestamp(900);
+        let recipients = vec![(alice_key, 2), (bob_key, 1)];
+        let create_accounts = [stream_acc.clone(), payer_acc.clone(), vault_acc.clone(), payer_token_acc.clone(), token_program_acc.clone()];
+        let short = StreamSchedule { deposit: 299, starts_at: 1_000, ends_at: 1_100 };
+        assert_eq!(
+            Streaming::create_stream(&program_id, &create_accounts, recipients.clone(), short),
+            Err(ProgramError::InsufficientFunds)
+        );
+        let schedule = StreamSchedule { deposit: 300, ..short };
+        Streaming::create_stream(&program_id, &create_accounts, recipients, schedule).unwrap();
+        assert_eq!((bank.balance(&payer_token_key), bank.balance(&vault_key)), (200, 300));
+
+        // Nothing accrues before the start, and accrual stops at the end.
+        let stream = PaymentStream::unpack(&stream_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((stream.total_accrued(950), stream.total_accrued(1_050), stream.total_accrued(5_000)), (0, 150, 300));
+
+        bank.set_unix_timestamp(1_050);
+        let withdraw_accounts = [stream_acc.clone(), vault_acc.clone(), alice_acc.clone(), authority_acc.clone(), token_program_acc.clone()];
+        Streaming::withdraw_stream(&program_id, &withdraw_accounts, 0).unwrap();
+        assert_eq!(bank.balance(&alice_key), 100);
+
+        bank.set_unix_timestamp(1_060);
+        let cancel_accounts = [stream_acc.clone(), vault_acc, payer_acc, payer_token_acc, authority_acc, token_program_acc, alice_acc, bob_acc];
+        Streaming::cancel_stream(&program_id, &cancel_accounts).unwrap();
+        assert_eq!((bank.balance(&alice_key), bank.balance(&bob_key)), (120, 60));
+        assert_eq!((bank.balance(&payer_token_key), bank.balance(&vault_key)), (320, 0));
+        assert!(PaymentStream::unpack(&stream_acc.try_borrow_data().unwrap()).unwrap().closed);
+        assert_eq!(Streaming::cancel_stream(&program_id, &cancel_accounts), Err(GgtError::StreamClosed.into()));
+    }
+
+    #[test]
+    fn test_create_stream_rejects_another_streams_vault() {
+        let _bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let (stream_a_key, stream_b_key, payer_key, vault_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let authority_a = Streaming::vault_authority(&program_id, &stream_a_key).0;
+        let token_account = |owner, amount| {
+            let mut data = vec![0u8; TokenAccount::LEN];
+            let account = TokenAccount { owner, amount, state: AccountState::Initialized, ..TokenAccount::default() };
+            TokenAccount::pack(account, &mut data).unwrap();
+            data
+        };
+        let mut lamports = [0u64; 3];
+        let [l0, l1, l2] = &mut lamports;
+        let mut stream_b_data = vec![0u8; PaymentStream::LEN];
+        let (mut d1, mut vault_data) = (vec![], token_account(authority_a, 500));
+        let stream_b_acc = account_info(&stream_b_key, false, true, l0, &mut stream_b_data, &program_id);
+        let payer_acc = account_info(&payer_key, true, false, l1, &mut d1, &program_id);
+        let vault_acc = account_info(&vault_key, false, true, l2, &mut vault_data, &token_program_id);
+
+        // Stream B cannot claim the vault stream A funded.
+        let accounts = [stream_b_acc, payer_acc, vault_acc.clone()];
+        assert_eq!(
+            Streaming::create_stream(&program_id, &accounts, vec![(payer_key, 1)], StreamSchedule::default()),
+            Err(ProgramError::IllegalOwner)
+        );
+
+        // Nor can a stream adopt its own vault once someone has filled it.
+        let stream_b_authority = Streaming::vault_authority(&program_id, &stream_b_key).0;
+        TokenAccount::pack(
+            TokenAccount { owner: stream_b_authority, amount: 500, state: AccountState::Initialized, ..TokenAccount::default() },
+            &mut vault_acc.try_borrow_mut_data().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            Streaming::create_stream(&program_id, &accounts, vec![(payer_key, 1)], StreamSchedule::default()),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
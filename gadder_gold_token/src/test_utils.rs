@@ -0,0 +1,465 @@
+//! Dev-mode bank simulator for unit tests and the fuzz targets, plus packed
+//! account fixtures the compute-unit benchmark in `cu-bench/` loads.
+//!
+//! Installs syscall stubs that apply spl-token and Token-2022 CPIs to an
+//! in-memory ledger and apply system `CreateAccount`, `Transfer` and
+//! `Assign`, so handler tests can assert balances and emitted events after
+//! the fact without spinning up program-test. The ledger is thread-local and
+//! only active while a `SimulatedBank` is alive; other tests keep the default
+//! stub behaviour (CPIs are no-ops and sysvars are unavailable).
+
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program_error::{ProgramError, UNSUPPORTED_SYSVAR},
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program,
+};
+use spl_token::{
+    instruction::TokenInstruction,
+    state::{Account as TokenAccount, AccountState},
+};
+use crate::config::{config_address, ProgramConfig};
+use crate::denylist::{denylist_address, Denylist};
+use crate::events::{self, Event};
+use crate::governance_contract::{
+    governance_config_address, proposal_address, BallotPage, GovernanceConfig, GovernanceParams, Proposal, ProposalAccountMeta,
+    ProposalInstruction, ProposalKind, ProposalStatus,
+};
+use crate::staking_contract::{Stake, StakingPool};
+use std::{cell::RefCell, collections::HashMap, sync::Once};
+
+/// Addresses the fixtures below are checked against, for crates outside this one.
+pub use crate::governance_contract::governance_authority_address;
+pub use crate::staking_contract::{rewards_vault_authority, stake_address, stake_pool_authority};
+
+const SUCCESS: u64 = 0;
+
+#[derive(Default)]
+struct Ledger {
+    balances: HashMap<Pubkey, u64>,
+    clock: Clock,
+    return_data: Vec<u8>,
+    logged_data: Vec<Vec<u8>>,
+}
+
+thread_local! {
+    static LEDGER: RefCell<Option<Ledger>> = const { RefCell::new(None) };
+}
+
+static INSTALL_STUBS: Once = Once::new();
+
+/// Handle to the simulated bank for the current test thread.
+pub struct SimulatedBank;
+
+impl SimulatedBank {
+    pub fn start() -> Self {
+        INSTALL_STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(BankStubs));
+        });
+        LEDGER.with(|l| *l.borrow_mut() = Some(Ledger::default()));
+        SimulatedBank
+    }
+
+    pub fn set_balance(&self, token_account: &Pubkey, amount: u64) {
+        with_ledger(|l| {
+            l.balances.insert(*token_account, amount);
+        });
+    }
+
+    pub fn balance(&self, token_account: &Pubkey) -> u64 {
+        with_ledger(|l| l.balances.get(token_account).copied().unwrap_or(0))
+    }
+
+    pub fn set_unix_timestamp(&self, unix_timestamp: i64) {
+        with_ledger(|l| l.clock.unix_timestamp = unix_timestamp);
+    }
+
+    pub fn set_slot(&self, slot: u64) {
+        with_ledger(|l| l.clock.slot = slot);
+    }
+
+    /// Data most recently passed to `set_return_data`.
+    pub fn return_data(&self) -> Vec<u8> {
+        with_ledger(|l| l.return_data.clone())
+    }
+
+    /// Events of type `E` emitted so far, in order.
+    pub fn events<E: Event>(&self) -> Vec<E> {
+        with_ledger(|l| l.logged_data.iter().filter_map(|data| events::decode(data)).collect())
+    }
+}
+
+impl Drop for SimulatedBank {
+    fn drop(&mut self) {
+        LEDGER.with(|l| *l.borrow_mut() = None);
+    }
+}
+
+fn with_ledger<R>(f: impl FnOnce(&mut Ledger) -> R) -> R {
+    LEDGER.with(|l| f(l.borrow_mut().as_mut().expect("SimulatedBank not started")))
+}
+
+fn is_active() -> bool {
+    LEDGER.with(|l| l.borrow().is_some())
+}
+
+fn debit(ledger: &mut Ledger, key: &Pubkey, amount: u64) -> ProgramResult {
+    let balance = ledger.balances.entry(*key).or_insert(0);
+    *balance = balance.checked_sub(amount).ok_or(ProgramError::InsufficientFunds)?;
+    Ok(())
+}
+
+fn credit(ledger: &mut Ledger, key: &Pubkey, amount: u64) -> ProgramResult {
+    let balance = ledger.balances.entry(*key).or_insert(0);
+    *balance = balance.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+/// Mirrors the ledger balance into the account data when it holds a packed token account.
+fn sync_token_account(ledger: &Ledger, key: &Pubkey, account_infos: &[AccountInfo]) {
+    let Some(info) = account_infos.iter().find(|a| a.key == key) else {
+        return;
+    };
+    let Ok(mut data) = info.try_borrow_mut_data() else {
+        return;
+    };
+    if data.len() != TokenAccount::LEN {
+        return;
+    }
+    if let Ok(mut token_account) = TokenAccount::unpack(&data) {
+        token_account.amount = ledger.balances.get(key).copied().unwrap_or(0);
+        TokenAccount::pack(token_account, &mut data).unwrap();
+    }
+}
+
+fn apply_token_instruction(ledger: &mut Ledger, ix: &Instruction, account_infos: &[AccountInfo]) -> ProgramResult {
+    // Token-2022 extension instructions have no spl-token equivalent and touch no balances.
+    let Ok(token_ix) = TokenInstruction::unpack(&ix.data) else {
+        return Ok(());
+    };
+    let touched = match token_ix {
+        TokenInstruction::Transfer { amount } => {
+            debit(ledger, &ix.accounts[0].pubkey, amount)?;
+            credit(ledger, &ix.accounts[1].pubkey, amount)?;
+            vec![ix.accounts[0].pubkey, ix.accounts[1].pubkey]
+        }
+        TokenInstruction::TransferChecked { amount, .. } => {
+            debit(ledger, &ix.accounts[0].pubkey, amount)?;
+            credit(ledger, &ix.accounts[2].pubkey, amount)?;
+            vec![ix.accounts[0].pubkey, ix.accounts[2].pubkey]
+        }
+        TokenInstruction::MintTo { amount } | TokenInstruction::MintToChecked { amount, .. } => {
+            credit(ledger, &ix.accounts[1].pubkey, amount)?;
+            vec![ix.accounts[1].pubkey]
+        }
+        TokenInstruction::Burn { amount } | TokenInstruction::BurnChecked { amount, .. } => {
+            debit(ledger, &ix.accounts[0].pubkey, amount)?;
+            vec![ix.accounts[0].pubkey]
+        }
+        _ => vec![],
+    };
+    for key in touched.iter() {
+        sync_token_account(ledger, key, account_infos);
+    }
+    Ok(())
+}
+
+/// Funds the new account of a system `CreateAccount` from the payer, moves
+/// the lamports of a `Transfer` and hands over the account of an `Assign`.
+/// Tests preallocate account data, so `CreateAccount` and `Allocate` never
+/// resize it.
+fn apply_system_instruction(ix: &Instruction, account_infos: &[AccountInfo]) -> ProgramResult {
+    let find = |key: &Pubkey| account_infos.iter().find(|a| a.key == key).ok_or(ProgramError::NotEnoughAccountKeys);
+    let move_lamports = |from: &AccountInfo, to: &AccountInfo, lamports: u64| -> ProgramResult {
+        let remaining = from.lamports().checked_sub(lamports).ok_or(ProgramError::InsufficientFunds)?;
+        **from.try_borrow_mut_lamports()? = remaining;
+        **to.try_borrow_mut_lamports()? += lamports;
+        Ok(())
+    };
+    match ix.data.get(..4) {
+        Some([0, 0, 0, 0]) if ix.data.len() >= 12 => {
+            let new_account = find(&ix.accounts[1].pubkey)?;
+            if new_account.lamports() > 0 {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            move_lamports(find(&ix.accounts[0].pubkey)?, new_account, u64::from_le_bytes(ix.data[4..12].try_into().unwrap()))
+        }
+        Some([1, 0, 0, 0]) if ix.data.len() >= 36 => {
+            find(&ix.accounts[0].pubkey)?.assign(&Pubkey::new_from_array(ix.data[4..36].try_into().unwrap()));
+            Ok(())
+        }
+        Some([2, 0, 0, 0]) if ix.data.len() >= 12 => {
+            let lamports = u64::from_le_bytes(ix.data[4..12].try_into().unwrap());
+            move_lamports(find(&ix.accounts[0].pubkey)?, find(&ix.accounts[1].pubkey)?, lamports)
+        }
+        _ => Ok(()),
+    }
+}
+
+struct BankStubs;
+
+impl SyscallStubs for BankStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if !is_active() {
+            return Ok(());
+        }
+        // The runtime refuses a CPI that asks for write access the caller was not given.
+        for meta in instruction.accounts.iter().filter(|meta| meta.is_writable) {
+            if account_infos.iter().any(|a| *a.key == meta.pubkey && !a.is_writable) {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+        // Nor one that asks for a signature the caller neither had nor signed for as a PDA.
+        for meta in instruction.accounts.iter().filter(|meta| meta.is_signer) {
+            if signers_seeds.is_empty() && account_infos.iter().any(|a| *a.key == meta.pubkey && !a.is_signer) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+        }
+        with_ledger(|ledger| {
+            if crate::token_program::is_supported(&instruction.program_id) {
+                apply_token_instruction(ledger, instruction, account_infos)
+            } else if instruction.program_id == system_program::id() {
+                apply_system_instruction(instruction, account_infos)
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        if is_active() {
+            with_ledger(|l| l.return_data = data.to_vec());
+        }
+    }
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        if is_active() {
+            with_ledger(|l| l.logged_data.push(fields.concat()));
+        }
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        if !is_active() {
+            return UNSUPPORTED_SYSVAR;
+        }
+        let clock = with_ledger(|l| l.clock.clone());
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        if !is_active() {
+            return UNSUPPORTED_SYSVAR;
+        }
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+}
+
+/// Builds an `AccountInfo` over caller-owned storage, mirroring the positional
+/// arguments of `AccountInfo::new` used throughout the module tests.
+pub fn account_info<'a>(
+    key: &'a Pubkey,
+    is_signer: bool,
+    is_writable: bool,
+    lamports: &'a mut u64,
+    data: &'a mut [u8],
+    owner: &'a Pubkey,
+) -> AccountInfo<'a> {
+    AccountInfo::new(key, is_signer, is_writable, lamports, data, owner, false, 0)
+}
+
+/// Config PDA of `program_id` with the data of an initialized, unpaused config.
+pub fn program_config(program_id: &Pubkey) -> (Pubkey, Vec<u8>) {
+    let mut data = vec![0u8; ProgramConfig::LEN];
+    let config = ProgramConfig { paused: false, pause_until_slot: 0, fee_bps: 0, compliance_authority: Pubkey::default(), pause_flags: 0, is_initialized: true, ..Default::default() };
+    ProgramConfig::pack(config, &mut data).unwrap();
+    (config_address(program_id).0, data)
+}
+
+/// Like [`program_config`], with `governance` holding the governance role and
+/// a transfer fee of `fee_bps`.
+pub fn program_config_with(program_id: &Pubkey, governance: &Pubkey, fee_bps: u16) -> (Pubkey, Vec<u8>) {
+    let mut data = vec![0u8; ProgramConfig::LEN];
+    ProgramConfig::pack(ProgramConfig { fee_bps, governance: *governance, is_initialized: true, ..Default::default() }, &mut data).unwrap();
+    (config_address(program_id).0, data)
+}
+
+/// Tops `accounts` up to the rent-exempt minimum for their size, as state
+/// accounts the program writes must be.
+pub fn fund_rent_exempt(accounts: &[&AccountInfo]) {
+    for acc in accounts {
+        let minimum = Rent::default().minimum_balance(acc.data_len());
+        let mut lamports = acc.try_borrow_mut_lamports().unwrap();
+        **lamports = (**lamports).max(minimum);
+    }
+}
+
+/// Denylist PDA of `program_id` with the data of a denylist holding `entries`.
+/// Handlers treat it as empty until it has lamports.
+pub fn denylist(program_id: &Pubkey, entries: &[Pubkey]) -> (Pubkey, Vec<u8>) {
+    let mut data = vec![0u8; Denylist::LEN];
+    Denylist::pack(Denylist { entries: entries.to_vec(), is_initialized: true }, &mut data).unwrap();
+    (denylist_address(program_id).0, data)
+}
+
+/// Packs an initialized spl-token account holding `amount` of `mint`.
+pub fn token_account_data(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![0u8; TokenAccount::LEN];
+    let account = TokenAccount { mint: *mint, owner: *owner, amount, state: AccountState::Initialized, ..TokenAccount::default() };
+    TokenAccount::pack(account, &mut data).unwrap();
+    data
+}
+
+/// Packs a stake position of `amount` opened at `staked_at`, never delegated.
+pub fn stake_data(amount: u64, staked_at: i64) -> Vec<u8> {
+    let mut data = vec![0u8; Stake::LEN];
+    Stake::pack(Stake { amount, staked_at, is_initialized: true, ..Stake::default() }, &mut data).unwrap();
+    data
+}
+
+/// Like [`stake_data`], with `rewards_owed` settled and waiting to be claimed.
+pub fn stake_data_with_rewards(amount: u64, staked_at: i64, rewards_owed: u64) -> Vec<u8> {
+    let mut data = vec![0u8; Stake::LEN];
+    Stake::pack(Stake { amount, staked_at, rewards_owed, is_initialized: true, ..Stake::default() }, &mut data).unwrap();
+    data
+}
+
+/// Packs a staking pool holding `total_staked`, with no rewards scheduled.
+pub fn staking_pool_data(total_staked: u64) -> Vec<u8> {
+    let pool = StakingPool {
+        total_staked,
+        reward_pool: 0,
+        penalty_pool: 0,
+        reward_per_token_stored: 0,
+        last_update_time: 0,
+        epoch: 0,
+        epoch_ends_at: 0,
+        reward_rate_per_second: 0,
+        rewards_end_at: 0,
+        is_initialized: true,
+    };
+    let mut data = vec![0u8; StakingPool::LEN];
+    StakingPool::pack(pool, &mut data).unwrap();
+    data
+}
+
+/// Governance config PDA of `program_id` measuring quorum against
+/// `staking_pool`, with no deposit and no timelock.
+pub fn governance_config(program_id: &Pubkey, staking_pool: &Pubkey, quorum_bps: u16, approval_threshold_bps: u16) -> (Pubkey, Vec<u8>) {
+    let params = GovernanceParams {
+        quorum_bps,
+        approval_threshold_bps,
+        voting_period_seconds: 86_400,
+        min_stake_to_propose: 0,
+        proposal_deposit: 0,
+        timelock_seconds: 0,
+    };
+    let mut data = vec![0u8; GovernanceConfig::LEN];
+    GovernanceConfig::pack(GovernanceConfig { params, staking_pool: *staking_pool, is_initialized: true }, &mut data).unwrap();
+    (governance_config_address(program_id).0, data)
+}
+
+/// PDA of active text proposal `proposal_id`, snapshotted at `timestamp`,
+/// with `ballot_pages` opened and `instructions` to invoke once executed.
+pub fn proposal(
+    program_id: &Pubkey,
+    proposal_id: u64,
+    timestamp: i64,
+    voting_ends_at: i64,
+    ballot_pages: u16,
+    instructions: &[Instruction],
+) -> (Pubkey, Vec<u8>) {
+    let instructions = instructions
+        .iter()
+        .map(|ix| ProposalInstruction {
+            program_id: ix.program_id,
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|meta| ProposalAccountMeta { pubkey: meta.pubkey, is_signer: meta.is_signer, is_writable: meta.is_writable })
+                .collect(),
+            data: ix.data.clone(),
+        })
+        .collect();
+    let proposal = Proposal {
+        uri: "ipfs://proposal".to_string(),
+        content_hash: [0; 32],
+        proposer: Pubkey::default(),
+        status: ProposalStatus::Active,
+        timestamp,
+        voting_ends_at,
+        yes_weight: 0,
+        no_weight: 0,
+        abstain_weight: 0,
+        escrow_weight: 0,
+        ballot_pages,
+        pages_tallied: 0,
+        kind: ProposalKind::Text,
+        deposit: 0,
+        snapshot_epoch: 0,
+        snapshot_reward_index: 0,
+        executable_at: 0,
+        is_initialized: true,
+        instructions,
+    };
+    let mut data = vec![0u8; Proposal::LEN];
+    proposal.pack_into_slice(&mut data);
+    (proposal_address(program_id, proposal_id).0, data)
+}
+
+/// Packs ballot page `page_index` of `proposal`, with a ballot for each of
+/// `ballots`' voter and weight; bit `i` of `in_favor` is ballot `i`'s choice.
+pub fn ballot_page_data(proposal: &Pubkey, page_index: u16, ballots: &[(Pubkey, u64)], in_favor: u32) -> Vec<u8> {
+    let page = BallotPage {
+        proposal: *proposal,
+        page_index,
+        voters: ballots.iter().map(|(voter, _)| *voter).collect(),
+        in_favor,
+        weights: ballots.iter().map(|(_, weight)| *weight).collect(),
+        escrow_weight: 0,
+        tallied: false,
+        is_initialized: true,
+    };
+    let mut data = vec![0u8; BallotPage::LEN];
+    page.pack_into_slice(&mut data);
+    data
+}
+
+/// Serializes `instructions` the way the runtime lays out the instructions sysvar,
+/// with `current_index` as the executing instruction.
+pub fn instructions_sysvar_data(instructions: &[Instruction], current_index: u16) -> Vec<u8> {
+    let mut data = (instructions.len() as u16).to_le_bytes().to_vec();
+    let mut body = Vec::new();
+    let mut offsets = Vec::new();
+    let table_len = 2 + 2 * instructions.len();
+    for ix in instructions {
+        offsets.push((table_len + body.len()) as u16);
+        body.extend_from_slice(&(ix.accounts.len() as u16).to_le_bytes());
+        for meta in ix.accounts.iter() {
+            body.push(meta.is_signer as u8 | (meta.is_writable as u8) << 1);
+            body.extend_from_slice(meta.pubkey.as_ref());
+        }
+        body.extend_from_slice(ix.program_id.as_ref());
+        body.extend_from_slice(&(ix.data.len() as u16).to_le_bytes());
+        body.extend_from_slice(&ix.data);
+    }
+    for offset in offsets {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(&body);
+    data.extend_from_slice(&current_index.to_le_bytes());
+    data
+}
+
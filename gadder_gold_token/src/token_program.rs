@@ -0,0 +1,302 @@
+//! Token program abstraction. GGT may be minted under either spl-token or
+//! Token-2022, so handlers validate the passed token program against the
+//! mint's owner and move tokens with `transfer_checked`, which Token-2022
+//! requires for mints carrying the transfer-fee extension.
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey,
+    pubkey::Pubkey,
+};
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    instruction as token_instruction,
+    state::{Account as TokenAccount, Mint},
+};
+
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+/// `CreateIdempotent` in the associated token account program's instruction enum.
+const CREATE_ATA_IDEMPOTENT: u8 = 1;
+
+pub fn is_supported(token_program_id: &Pubkey) -> bool {
+    *token_program_id == spl_token::id() || *token_program_id == spl_token_2022::id()
+}
+
+/// Checks that `token_program_acc` is a supported token program and owns `mint_acc`.
+pub fn check_token_program(token_program_acc: &AccountInfo, mint_acc: &AccountInfo) -> ProgramResult {
+    if !is_supported(token_program_acc.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if mint_acc.owner != token_program_acc.key {
+        msg!("Mint {} is not owned by token program {}", mint_acc.key, token_program_acc.key);
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
+
+/// Base state of a token account under either program, ignoring any extensions.
+pub fn unpack_account(token_acc: &AccountInfo) -> Result<TokenAccount, ProgramError> {
+    Ok(StateWithExtensions::<TokenAccount>::unpack(&token_acc.try_borrow_data()?)?.base)
+}
+
+/// Base state of a mint under either program, ignoring any extensions.
+pub fn unpack_mint(mint_acc: &AccountInfo) -> Result<Mint, ProgramError> {
+    Ok(StateWithExtensions::<Mint>::unpack(&mint_acc.try_borrow_data()?)?.base)
+}
+
+pub fn mint_decimals(mint_acc: &AccountInfo) -> Result<u8, ProgramError> {
+    Ok(unpack_mint(mint_acc)?.decimals)
+}
+
+/// Moves `amount` of the mint's tokens; `signer_seeds` is empty unless `authority` is a PDA.
+pub fn transfer<'a>(
+    token_program_acc: &AccountInfo<'a>,
+    source: &AccountInfo<'a>,
+    mint_acc: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    check_token_program(token_program_acc, mint_acc)?;
+    let ix = token_instruction::transfer_checked(
+        token_program_acc.key,
+        source.key,
+        mint_acc.key,
+        destination.key,
+        authority.key,
+        &[],
+        amount,
+        mint_decimals(mint_acc)?,
+    )?;
+    invoke_signed(
+        &ix,
+        &[source.clone(), mint_acc.clone(), destination.clone(), authority.clone(), token_program_acc.clone()],
+        signer_seeds,
+    )
+}
+
+pub fn mint_to<'a>(
+    token_program_acc: &AccountInfo<'a>,
+    mint_acc: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    mint_authority: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    check_token_program(token_program_acc, mint_acc)?;
+    let ix = token_instruction::mint_to_checked(
+        token_program_acc.key,
+        mint_acc.key,
+        destination.key,
+        mint_authority.key,
+        &[],
+        amount,
+        mint_decimals(mint_acc)?,
+    )?;
+    invoke_signed(
+        &ix,
+        &[mint_acc.clone(), destination.clone(), mint_authority.clone(), token_program_acc.clone()],
+        signer_seeds,
+    )
+}
+
+pub fn burn<'a>(
+    token_program_acc: &AccountInfo<'a>,
+    token_acc: &AccountInfo<'a>,
+    mint_acc: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    check_token_program(token_program_acc, mint_acc)?;
+    let ix = token_instruction::burn_checked(
+        token_program_acc.key,
+        token_acc.key,
+        mint_acc.key,
+        authority.key,
+        &[],
+        amount,
+        mint_decimals(mint_acc)?,
+    )?;
+    invoke_signed(&ix, &[token_acc.clone(), mint_acc.clone(), authority.clone(), token_program_acc.clone()], signer_seeds)
+}
+
+/// Lets `delegate` move up to `amount` of `source`'s tokens on `owner`'s behalf.
+pub fn approve<'a>(
+    token_program_acc: &AccountInfo<'a>,
+    source: &AccountInfo<'a>,
+    mint_acc: &AccountInfo<'a>,
+    delegate: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    check_token_program(token_program_acc, mint_acc)?;
+    let ix = token_instruction::approve_checked(
+        token_program_acc.key,
+        source.key,
+        mint_acc.key,
+        delegate.key,
+        owner.key,
+        &[],
+        amount,
+        mint_decimals(mint_acc)?,
+    )?;
+    invoke(&ix, &[source.clone(), mint_acc.clone(), delegate.clone(), owner.clone(), token_program_acc.clone()])
+}
+
+pub fn revoke<'a>(
+    token_program_acc: &AccountInfo<'a>,
+    source: &AccountInfo<'a>,
+    mint_acc: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+) -> ProgramResult {
+    check_token_program(token_program_acc, mint_acc)?;
+    let ix = token_instruction::revoke(token_program_acc.key, source.key, owner.key, &[])?;
+    invoke(&ix, &[source.clone(), owner.clone(), token_program_acc.clone()])
+}
+
+/// Hands the mint authority to `new_authority`, or revokes it for good with `None`.
+pub fn set_mint_authority<'a>(
+    token_program_acc: &AccountInfo<'a>,
+    mint_acc: &AccountInfo<'a>,
+    current_authority: &AccountInfo<'a>,
+    new_authority: Option<&Pubkey>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    check_token_program(token_program_acc, mint_acc)?;
+    let ix = token_instruction::set_authority(
+        token_program_acc.key,
+        mint_acc.key,
+        new_authority,
+        token_instruction::AuthorityType::MintTokens,
+        current_authority.key,
+        &[],
+    )?;
+    invoke_signed(&ix, &[mint_acc.clone(), current_authority.clone(), token_program_acc.clone()], signer_seeds)
+}
+
+pub fn set_frozen<'a>(
+    token_program_acc: &AccountInfo<'a>,
+    token_acc: &AccountInfo<'a>,
+    mint_acc: &AccountInfo<'a>,
+    freeze_authority: &AccountInfo<'a>,
+    freeze: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    check_token_program(token_program_acc, mint_acc)?;
+    let ix = if freeze {
+        token_instruction::freeze_account(token_program_acc.key, token_acc.key, mint_acc.key, freeze_authority.key, &[])?
+    } else {
+        token_instruction::thaw_account(token_program_acc.key, token_acc.key, mint_acc.key, freeze_authority.key, &[])?
+    };
+    invoke_signed(
+        &ix,
+        &[token_acc.clone(), mint_acc.clone(), freeze_authority.clone(), token_program_acc.clone()],
+        signer_seeds,
+    )
+}
+
+/// Associated token account of `owner` for `mint` under `token_program_id`.
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey, token_program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[owner.as_ref(), token_program_id.as_ref(), mint.as_ref()], &ASSOCIATED_TOKEN_PROGRAM_ID)
+}
+
+/// Creates `owner`'s associated token account for the mint, paid by `payer`,
+/// unless it already exists.
+pub fn create_associated_token_account_idempotent<'a>(
+    associated_token_program_acc: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    ata: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+    mint_acc: &AccountInfo<'a>,
+    system_program_acc: &AccountInfo<'a>,
+    token_program_acc: &AccountInfo<'a>,
+) -> ProgramResult {
+    check_token_program(token_program_acc, mint_acc)?;
+    if *associated_token_program_acc.key != ASSOCIATED_TOKEN_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let ix = Instruction {
+        program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer.key, true),
+            AccountMeta::new(*ata.key, false),
+            AccountMeta::new_readonly(*owner.key, false),
+            AccountMeta::new_readonly(*mint_acc.key, false),
+            AccountMeta::new_readonly(*system_program_acc.key, false),
+            AccountMeta::new_readonly(*token_program_acc.key, false),
+        ],
+        data: vec![CREATE_ATA_IDEMPOTENT],
+    };
+    invoke(
+        &ix,
+        &[
+            payer.clone(),
+            ata.clone(),
+            owner.clone(),
+            mint_acc.clone(),
+            system_program_acc.clone(),
+            token_program_acc.clone(),
+            associated_token_program_acc.clone(),
+        ],
+    )
+}
+
+/// Closes an emptied token account, returning its rent to `destination`.
+pub fn close_account<'a>(
+    token_program_acc: &AccountInfo<'a>,
+    token_acc: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if !is_supported(token_program_acc.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let ix = token_instruction::close_account(token_program_acc.key, token_acc.key, destination.key, owner.key, &[])?;
+    invoke_signed(&ix, &[token_acc.clone(), destination.clone(), owner.clone(), token_program_acc.clone()], signer_seeds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{account_info, SimulatedBank};
+    use solana_program::program_pack::Pack;
+
+    #[test]
+    fn test_transfer_accepts_either_token_program_owning_the_mint() {
+        let bank = SimulatedBank::start();
+        let (source_key, dest_key, authority_key, mint_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let foreign_program = Pubkey::new_unique();
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { decimals: 9, is_initialized: true, ..Mint::default() }, &mut mint_data).unwrap();
+        bank.set_balance(&source_key, 1_000);
+
+        for (token_program_id, mint_owner, expected) in [
+            (spl_token::id(), spl_token::id(), Ok(())),
+            (spl_token_2022::id(), spl_token_2022::id(), Ok(())),
+            (spl_token::id(), spl_token_2022::id(), Err(ProgramError::IllegalOwner)),
+            (foreign_program, foreign_program, Err(ProgramError::IncorrectProgramId)),
+        ] {
+            let mut lamports = [0u64; 5];
+            let [l0, l1, l2, l3, l4] = &mut lamports;
+            let (mut d0, mut d1, mut d2, mut d3) = (vec![], vec![], vec![], vec![]);
+            let mut mint_copy = mint_data.clone();
+            let token_program_acc = account_info(&token_program_id, false, false, l0, &mut d0, &token_program_id);
+            let source = account_info(&source_key, false, true, l1, &mut d1, &token_program_id);
+            let mint_acc = account_info(&mint_key, false, false, l2, &mut mint_copy, &mint_owner);
+            let dest = account_info(&dest_key, false, true, l3, &mut d2, &token_program_id);
+            let authority = account_info(&authority_key, true, false, l4, &mut d3, &token_program_id);
+            assert_eq!(transfer(&token_program_acc, &source, &mint_acc, &dest, &authority, 100, &[]), expected);
+        }
+        assert_eq!(bank.balance(&source_key), 800);
+        assert_eq!(bank.balance(&dest_key), 200);
+    }
+}
@@ -0,0 +1,516 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed, set_return_data},
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+use spl_token::{instruction as token_instruction, state::Account as TokenAccount};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use crate::{
+    config::{self, AuthorityRole},
+    error::GgtError,
+    events, governance_contract, token_program,
+};
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const TREASURY_SEED: &[u8] = b"treasury";
+pub const MAX_TREASURY_ASSETS: usize = 8;
+
+/// A mint the treasury may hold, the PDA-owned vault holding it and the most
+/// the treasury may hold of it, in base units.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TreasuryAsset {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub max_holding: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct TreasuryAssets {
+    pub assets: Vec<TreasuryAsset>,
+    pub is_initialized: bool,
+}
+
+impl TreasuryAssets {
+    fn find(&self, vault: &Pubkey) -> Result<&TreasuryAsset, ProgramError> {
+        self.assets
+            .iter()
+            .find(|asset| asset.vault == *vault)
+            .ok_or_else(|| GgtError::UnregisteredTreasuryAsset.into())
+    }
+}
+
+impl Sealed for TreasuryAssets {}
+
+impl IsInitialized for TreasuryAssets {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for TreasuryAssets {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [137, 72, 7, 214, 12, 199, 82, 78];
+}
+
+impl Pack for TreasuryAssets {
+    const LEN: usize = 587; // discriminator (8) + version (1) + count (1) + (Pubkey (32) * 2 + u64 (8)) * MAX_TREASURY_ASSETS + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        let mut cursor = 0;
+        dst[cursor] = self.assets.len() as u8;
+        cursor += 1;
+        for slot in 0..MAX_TREASURY_ASSETS {
+            let asset = self.assets.get(slot).copied().unwrap_or(TreasuryAsset {
+                mint: Pubkey::default(),
+                vault: Pubkey::default(),
+                max_holding: 0,
+            });
+            dst[cursor..cursor + 32].copy_from_slice(asset.mint.as_ref());
+            cursor += 32;
+            dst[cursor..cursor + 32].copy_from_slice(asset.vault.as_ref());
+            cursor += 32;
+            dst[cursor..cursor + 8].copy_from_slice(&asset.max_holding.to_le_bytes());
+            cursor += 8;
+        }
+        dst[cursor] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let mut cursor = 0;
+        let count = src[cursor] as usize;
+        cursor += 1;
+        if count > MAX_TREASURY_ASSETS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut assets = Vec::with_capacity(count);
+        for slot in 0..MAX_TREASURY_ASSETS {
+            if slot < count {
+                assets.push(TreasuryAsset {
+                    mint: Pubkey::new_from_array(src[cursor..cursor + 32].try_into().unwrap()),
+                    vault: Pubkey::new_from_array(src[cursor + 32..cursor + 64].try_into().unwrap()),
+                    max_holding: u64::from_le_bytes(src[cursor + 64..cursor + 72].try_into().unwrap()),
+                });
+            }
+            cursor += 72;
+        }
+        let is_initialized = src[cursor] != 0;
+        Ok(TreasuryAssets { assets, is_initialized })
+    }
+}
+
+/// The governance signer and the program config it is checked against sit
+/// after `assets_acc`; neither is kept.
+pub struct SetTreasuryAssetAccounts<'a, 'info> {
+    pub assets_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> SetTreasuryAssetAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let assets_acc = next_account_info(account_info_iter)?;
+        let governance_acc = next_account_info(account_info_iter)?;
+        Treasury::check_governance(program_id, next_account_info(account_info_iter)?, governance_acc)?;
+        let vault_acc = next_account_info(account_info_iter)?;
+        assert_owned_by(assets_acc, program_id)?;
+        Ok(Self { assets_acc, vault_acc })
+    }
+}
+
+/// The governance signer and the program config it is checked against sit
+/// after `assets_acc`; neither is kept. The vault and treasury authority are
+/// validated by `Treasury::pay_out`.
+pub struct TreasurySpendAccounts<'a, 'info> {
+    pub assets_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub recipient_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> TreasurySpendAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let assets_acc = next_account_info(account_info_iter)?;
+        let governance_acc = next_account_info(account_info_iter)?;
+        Treasury::check_governance(program_id, next_account_info(account_info_iter)?, governance_acc)?;
+        Ok(Self {
+            assets_acc,
+            vault_acc: next_account_info(account_info_iter)?,
+            recipient_acc: next_account_info(account_info_iter)?,
+            authority_acc: next_account_info(account_info_iter)?,
+            token_program_acc: next_account_info(account_info_iter)?,
+        })
+    }
+}
+
+pub struct TreasuryBalanceAccounts<'a, 'info> {
+    pub assets_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> TreasuryBalanceAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self { assets_acc: next_account_info(account_info_iter)?, vault_acc: next_account_info(account_info_iter)? };
+        assert_owned_by(ctx.assets_acc, program_id)?;
+        Ok(ctx)
+    }
+}
+
+/// The governance signer and the program config it is checked against sit
+/// after `assets_acc`; neither is kept.
+pub struct TreasurySwapAccounts<'a, 'info> {
+    pub assets_acc: &'a AccountInfo<'info>,
+    pub vault_in_acc: &'a AccountInfo<'info>,
+    pub vault_out_acc: &'a AccountInfo<'info>,
+    pub counterparty_acc: &'a AccountInfo<'info>,
+    pub counterparty_receive_acc: &'a AccountInfo<'info>,
+    pub counterparty_pay_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub authority_bump: u8,
+}
+
+impl<'a, 'info> TreasurySwapAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let assets_acc = next_account_info(account_info_iter)?;
+        let governance_acc = next_account_info(account_info_iter)?;
+        let program_config_acc = next_account_info(account_info_iter)?;
+        let vault_in_acc = next_account_info(account_info_iter)?;
+        let vault_out_acc = next_account_info(account_info_iter)?;
+        let counterparty_acc = next_account_info(account_info_iter)?;
+        let counterparty_receive_acc = next_account_info(account_info_iter)?;
+        let counterparty_pay_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+
+        Treasury::check_governance(program_id, program_config_acc, governance_acc)?;
+        assert_signer(counterparty_acc)?;
+        if vault_in_acc.key == vault_out_acc.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        assert_owned_by(assets_acc, program_id)?;
+        let authority_bump = assert_pda(authority_acc, Treasury::treasury_authority(program_id))?;
+        Ok(Self {
+            assets_acc,
+            vault_in_acc,
+            vault_out_acc,
+            counterparty_acc,
+            counterparty_receive_acc,
+            counterparty_pay_acc,
+            authority_acc,
+            token_program_acc,
+            authority_bump,
+        })
+    }
+}
+
+/// The governance signer, or the governance authority PDA when run from an
+/// executed proposal, is checked against `program_config_acc`, which also
+/// keeps the running burn total.
+pub struct BurnFromTreasuryAccounts<'a, 'info> {
+    pub assets_acc: &'a AccountInfo<'info>,
+    pub program_config_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub authority_bump: u8,
+}
+
+impl<'a, 'info> BurnFromTreasuryAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let assets_acc = next_account_info(account_info_iter)?;
+        let governance_acc = next_account_info(account_info_iter)?;
+        let program_config_acc = next_account_info(account_info_iter)?;
+        let vault_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+
+        governance_contract::check_governance_signer(program_id, program_config_acc, governance_acc)?;
+        assert_owned_by(assets_acc, program_id)?;
+        let authority_bump = assert_pda(authority_acc, Treasury::treasury_authority(program_id))?;
+        Ok(Self { assets_acc, program_config_acc, vault_acc, mint_acc, authority_acc, token_program_acc, authority_bump })
+    }
+}
+
+pub struct Treasury;
+
+impl Treasury {
+    pub fn treasury_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[TREASURY_SEED], program_id)
+    }
+
+    fn check_governance(program_id: &Pubkey, program_config_acc: &AccountInfo, governance_acc: &AccountInfo) -> ProgramResult {
+        config::check_authority(program_id, program_config_acc, &[AuthorityRole::Governance], governance_acc)
+    }
+
+    fn load_assets(assets_acc: &AccountInfo) -> Result<TreasuryAssets, ProgramError> {
+        TreasuryAssets::unpack(&assets_acc.try_borrow_data()?)
+    }
+
+    fn vault_balance(vault_acc: &AccountInfo) -> Result<u64, ProgramError> {
+        Ok(TokenAccount::unpack(&vault_acc.try_borrow_data()?)?.amount)
+    }
+
+    /// Registers a treasury vault or updates its cap. Governance only.
+    pub fn set_treasury_asset(program_id: &Pubkey, accounts: &[AccountInfo], max_holding: u64) -> ProgramResult {
+        let SetTreasuryAssetAccounts { assets_acc, vault_acc } = SetTreasuryAssetAccounts::try_from(program_id, accounts)?;
+        let vault = TokenAccount::unpack(&vault_acc.try_borrow_data()?)?;
+        if vault.owner != Self::treasury_authority(program_id).0 {
+            msg!("Treasury vault is not owned by the treasury authority PDA");
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        let mut registry = TreasuryAssets::unpack_unchecked(&assets_acc.try_borrow_data()?)?;
+        registry.is_initialized = true;
+        let asset = TreasuryAsset { mint: vault.mint, vault: *vault_acc.key, max_holding };
+        match registry.assets.iter().position(|existing| existing.mint == vault.mint) {
+            Some(slot) => registry.assets[slot] = asset,
+            None if registry.assets.len() < MAX_TREASURY_ASSETS => registry.assets.push(asset),
+            None => return Err(ProgramError::InvalidArgument),
+        }
+        pack_state(&registry, assets_acc)?;
+        msg!("Treasury cap for {} set to {}", vault.mint, max_holding);
+        Ok(())
+    }
+
+    /// Pays `amount` out of a registered treasury vault. Governance only.
+    pub fn treasury_spend(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+        let TreasurySpendAccounts { assets_acc, vault_acc, recipient_acc, authority_acc, token_program_acc } = TreasurySpendAccounts::try_from(program_id, accounts)?;
+        Self::pay_out(program_id, assets_acc, vault_acc, recipient_acc, authority_acc, token_program_acc, amount)
+    }
+
+    /// Transfers `amount` from a registered vault, signed by the treasury authority.
+    /// Callers are responsible for authorizing the spend.
+    pub(crate) fn pay_out<'a>(
+        program_id: &Pubkey,
+        assets_acc: &AccountInfo<'a>,
+        vault_acc: &AccountInfo<'a>,
+        recipient_acc: &AccountInfo<'a>,
+        authority_acc: &AccountInfo<'a>,
+        token_program_acc: &AccountInfo<'a>,
+        amount: u64,
+    ) -> ProgramResult {
+        assert_owned_by(assets_acc, program_id)?;
+        Self::load_assets(assets_acc)?.find(vault_acc.key)?;
+        let bump = assert_pda(authority_acc, Self::treasury_authority(program_id))?;
+
+        let ix = token_instruction::transfer(token_program_acc.key, vault_acc.key, recipient_acc.key, authority_acc.key, &[], amount)?;
+        invoke_signed(
+            &ix,
+            &[vault_acc.clone(), recipient_acc.clone(), authority_acc.clone(), token_program_acc.clone()],
+            &[&[TREASURY_SEED, &[bump]]],
+        )?;
+        msg!("Treasury spent {} from {}", amount, vault_acc.key);
+        Ok(())
+    }
+
+    /// Read-only query: publishes the balance of a registered treasury vault as
+    /// little-endian `u64` return data.
+    pub fn get_treasury_balance(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let TreasuryBalanceAccounts { assets_acc, vault_acc } = TreasuryBalanceAccounts::try_from(program_id, accounts)?;
+        Self::load_assets(assets_acc)?.find(vault_acc.key)?;
+        let balance = Self::vault_balance(vault_acc)?;
+        set_return_data(&balance.to_le_bytes());
+        msg!("Treasury vault {} holds {}", vault_acc.key, balance);
+        Ok(())
+    }
+
+    /// Burns `amount` out of a registered treasury vault and adds it to the
+    /// program config's `total_burned`. Governance only, so the DAO's
+    /// buyback-and-burn policy can run from an executed proposal.
+    pub fn burn_from_treasury(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+        let BurnFromTreasuryAccounts { assets_acc, program_config_acc, vault_acc, mint_acc, authority_acc, token_program_acc, authority_bump } =
+            BurnFromTreasuryAccounts::try_from(program_id, accounts)?;
+        if amount == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if Self::load_assets(assets_acc)?.find(vault_acc.key)?.mint != *mint_acc.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let mut config = config::load_config(program_id, program_config_acc)?;
+        config.total_burned = config.total_burned.checked_add(amount).ok_or(GgtError::MathOverflow)?;
+        let total_burned = config.total_burned;
+        pack_state(&config, program_config_acc)?;
+
+        token_program::burn(token_program_acc, vault_acc, mint_acc, authority_acc, amount, &[&[TREASURY_SEED, &[authority_bump]]])?;
+        events::emit(&events::TreasuryBurned { vault: *vault_acc.key, mint: *mint_acc.key, amount, total_burned });
+        msg!("Treasury burned {} from {}, {} burned in total", amount, vault_acc.key, total_burned);
+        Ok(())
+    }
+
+    /// Swaps `amount_in` of one treasury asset for `amount_out` of another with a
+    /// signing counterparty, rejecting swaps that push the bought asset over its cap.
+    pub fn treasury_swap(program_id: &Pubkey, accounts: &[AccountInfo], amount_in: u64, amount_out: u64) -> ProgramResult {
+        let TreasurySwapAccounts {
+            assets_acc,
+            vault_in_acc,
+            vault_out_acc,
+            counterparty_acc,
+            counterparty_receive_acc,
+            counterparty_pay_acc,
+            authority_acc,
+            token_program_acc,
+            authority_bump,
+        } = TreasurySwapAccounts::try_from(program_id, accounts)?;
+        let registry = Self::load_assets(assets_acc)?;
+        registry.find(vault_in_acc.key)?;
+        let bought = registry.find(vault_out_acc.key)?;
+        let holding_after = Self::vault_balance(vault_out_acc)?
+            .checked_add(amount_out)
+            .ok_or(GgtError::MathOverflow)?;
+        if holding_after > bought.max_holding {
+            msg!("Swap would hold {} of {}, cap is {}", holding_after, bought.mint, bought.max_holding);
+            return Err(GgtError::TreasuryCapExceeded.into());
+        }
+
+        let pay = token_instruction::transfer(
+            token_program_acc.key,
+            vault_in_acc.key,
+            counterparty_receive_acc.key,
+            authority_acc.key,
+            &[],
+            amount_in,
+        )?;
+        invoke_signed(
+            &pay,
+            &[vault_in_acc.clone(), counterparty_receive_acc.clone(), authority_acc.clone(), token_program_acc.clone()],
+            &[&[TREASURY_SEED, &[authority_bump]]],
+        )?;
+        let receive = token_instruction::transfer(
+            token_program_acc.key,
+            counterparty_pay_acc.key,
+            vault_out_acc.key,
+            counterparty_acc.key,
+            &[],
+            amount_out,
+        )?;
+        invoke(
+            &receive,
+            &[counterparty_pay_acc.clone(), vault_out_acc.clone(), counterparty_acc.clone(), token_program_acc.clone()],
+        )?;
+        msg!("Treasury swapped {} for {}", amount_in, amount_out);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{account_info, fund_rent_exempt, program_config, token_account_data, SimulatedBank};
+    use crate::GOVERNANCE_PUBKEY;
+    use spl_token::state::Mint;
+
+    #[test]
+    fn test_treasury_swap_enforces_asset_cap() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let (authority_key, _) = Treasury::treasury_authority(&program_id);
+        let (ggt_mint, usdc_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (assets_key, ggt_vault_key, usdc_vault_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (counterparty_key, counterparty_ggt_key, counterparty_usdc_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (program_config_key, mut program_config_data) = program_config(&program_id);
+        let mut lamports = [0u64; 10];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9] = &mut lamports;
+        let mut assets_data = vec![0u8; TreasuryAssets::LEN];
+        let mut ggt_vault_data = token_account_data(&ggt_mint, &authority_key, 1_000);
+        let mut usdc_vault_data = token_account_data(&usdc_mint, &authority_key, 0);
+        let (mut d1, mut d4, mut d5, mut d6, mut d7, mut d8) = (vec![], vec![], vec![], vec![], vec![], vec![]);
+        let assets_acc = account_info(&assets_key, false, true, l0, &mut assets_data, &program_id);
+        let governance_acc = account_info(&GOVERNANCE_PUBKEY, true, false, l1, &mut d1, &program_id);
+        let ggt_vault_acc = account_info(&ggt_vault_key, false, true, l2, &mut ggt_vault_data, &token_program_id);
+        let usdc_vault_acc = account_info(&usdc_vault_key, false, true, l3, &mut usdc_vault_data, &token_program_id);
+        let counterparty_acc = account_info(&counterparty_key, true, false, l4, &mut d4, &program_id);
+        let counterparty_ggt_acc = account_info(&counterparty_ggt_key, false, true, l5, &mut d5, &token_program_id);
+        let counterparty_usdc_acc = account_info(&counterparty_usdc_key, false, true, l6, &mut d6, &token_program_id);
+        let authority_acc = account_info(&authority_key, false, false, l7, &mut d7, &program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, l8, &mut d8, &program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, l9, &mut program_config_data, &program_id);
+        fund_rent_exempt(&[&assets_acc]);
+        bank.set_balance(&ggt_vault_key, 1_000);
+        bank.set_balance(&counterparty_usdc_key, 1_000);
+
+        let set_ggt_accounts = [assets_acc.clone(), governance_acc.clone(), program_config_acc.clone(), ggt_vault_acc.clone()];
+        Treasury::set_treasury_asset(&program_id, &set_ggt_accounts, 1_000).unwrap();
+        let set_usdc_accounts = [assets_acc.clone(), governance_acc.clone(), program_config_acc.clone(), usdc_vault_acc.clone()];
+        Treasury::set_treasury_asset(&program_id, &set_usdc_accounts, 400).unwrap();
+
+        let accounts = vec![
+            assets_acc,
+            governance_acc,
+            program_config_acc,
+            ggt_vault_acc,
+            usdc_vault_acc,
+            counterparty_acc,
+            counterparty_ggt_acc,
+            counterparty_usdc_acc,
+            authority_acc,
+            token_program_acc,
+        ];
+        assert_eq!(
+            Treasury::treasury_swap(&program_id, &accounts, 1_000, 1_000),
+            Err(GgtError::TreasuryCapExceeded.into())
+        );
+
+        Treasury::treasury_swap(&program_id, &accounts, 400, 400).unwrap();
+        assert_eq!(bank.balance(&usdc_vault_key), 400);
+        assert_eq!(bank.balance(&ggt_vault_key), 600);
+        assert_eq!(
+            Treasury::treasury_swap(&program_id, &accounts, 1, 1),
+            Err(GgtError::TreasuryCapExceeded.into())
+        );
+    }
+
+    #[test]
+    fn test_burn_from_treasury_counts_total_burned() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let (authority_key, _) = Treasury::treasury_authority(&program_id);
+        let (assets_key, vault_key, mint_key, outsider_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (program_config_key, mut program_config_data) = program_config(&program_id);
+        let mut lamports = [0u64; 8];
+        let [l0, l1, l2, l3, l4, l5, l6, l7] = &mut lamports;
+        let mut assets_data = vec![0u8; TreasuryAssets::LEN];
+        let mut vault_data = token_account_data(&mint_key, &authority_key, 1_000);
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Mint::pack(Mint { supply: 1_000, decimals: 9, is_initialized: true, ..Mint::default() }, &mut mint_data).unwrap();
+        let (mut d1, mut d5, mut d6, mut d7) = (vec![], vec![], vec![], vec![]);
+        let assets_acc = account_info(&assets_key, false, true, l0, &mut assets_data, &program_id);
+        let governance_acc = account_info(&GOVERNANCE_PUBKEY, true, false, l1, &mut d1, &program_id);
+        let program_config_acc = account_info(&program_config_key, false, true, l2, &mut program_config_data, &program_id);
+        fund_rent_exempt(&[&assets_acc, &program_config_acc]);
+        let vault_acc = account_info(&vault_key, false, true, l3, &mut vault_data, &token_program_id);
+        let mint_acc = account_info(&mint_key, false, true, l4, &mut mint_data, &token_program_id);
+        let authority_acc = account_info(&authority_key, false, false, l5, &mut d5, &program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, l6, &mut d6, &program_id);
+        let outsider_acc = account_info(&outsider_key, true, false, l7, &mut d7, &program_id);
+        bank.set_balance(&vault_key, 1_000);
+
+        let set_accounts = [assets_acc.clone(), governance_acc.clone(), program_config_acc.clone(), vault_acc.clone()];
+        Treasury::set_treasury_asset(&program_id, &set_accounts, 1_000).unwrap();
+
+        let mut accounts = vec![assets_acc, outsider_acc, program_config_acc.clone(), vault_acc, mint_acc, authority_acc, token_program_acc];
+        assert_eq!(Treasury::burn_from_treasury(&program_id, &accounts, 100), Err(ProgramError::IllegalOwner));
+        accounts[1] = governance_acc;
+        Treasury::burn_from_treasury(&program_id, &accounts, 100).unwrap();
+        Treasury::burn_from_treasury(&program_id, &accounts, 150).unwrap();
+        assert_eq!(bank.balance(&vault_key), 750);
+        assert_eq!(config::load_config(&program_id, &program_config_acc).unwrap().total_burned, 250);
+        assert_eq!(bank.events::<events::TreasuryBurned>()[1].total_burned, 250);
+    }
+}
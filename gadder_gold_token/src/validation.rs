@@ -0,0 +1,92 @@
+//! Account checks shared by every instruction handler. Each helper fails with
+//! the same error the handlers used before they were factored out, so clients
+//! see no difference beyond the checks that were previously missing.
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use spl_token_2022::state::Account as TokenAccount;
+use crate::{migration, token_program, TokenContract};
+
+pub fn assert_signer(acc: &AccountInfo) -> ProgramResult {
+    if !acc.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+pub fn assert_owned_by(acc: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    if acc.owner != owner {
+        msg!("Account {} is owned by {}, expected {}", acc.key, acc.owner, owner);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// Checks that `acc` is the PDA `expected` (as returned by the `*_address`
+/// helpers) and returns its bump.
+pub fn assert_pda(acc: &AccountInfo, expected: (Pubkey, u8)) -> Result<u8, ProgramError> {
+    let (address, bump) = expected;
+    if *acc.key != address {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(bump)
+}
+
+/// Checks that `token_acc` is a token account of a supported token program
+/// holding `mint`, and returns its state.
+pub fn assert_token_account_mint(token_acc: &AccountInfo, mint: &Pubkey) -> Result<TokenAccount, ProgramError> {
+    if !token_program::is_supported(token_acc.owner) {
+        msg!("Token account {} is owned by {}", token_acc.key, token_acc.owner);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let account = token_program::unpack_account(token_acc)?;
+    if account.mint != *mint {
+        msg!("Token account {} holds mint {}, expected {}", token_acc.key, account.mint, mint);
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(account)
+}
+
+/// Checks that `mint_acc` is GGT: the legacy mint PDA or its Token-2022
+/// migration mint. Stake in any other mint carries no voting weight.
+pub fn assert_ggt_mint(program_id: &Pubkey, mint_acc: &AccountInfo) -> ProgramResult {
+    if *mint_acc.key != TokenContract::mint_address(program_id).0 && *mint_acc.key != migration::migration_mint_address(program_id).0 {
+        msg!("Mint {} is not the GGT mint", mint_acc.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{account_info, token_account_data};
+
+    #[test]
+    fn test_assertions_reject_mismatched_accounts() {
+        let program_id = Pubkey::new_unique();
+        let (mint, other_mint, key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let token_program_id = spl_token::id();
+        let mut data = token_account_data(&mint, &key, 0);
+        let (mut l0, mut l1) = (0u64, 0u64);
+        let mut d1 = vec![];
+        let token_acc = account_info(&key, false, true, &mut l0, &mut data, &token_program_id);
+        let state_acc = account_info(&key, true, true, &mut l1, &mut d1, &program_id);
+
+        assert_eq!(assert_signer(&token_acc), Err(ProgramError::MissingRequiredSignature));
+        assert_signer(&state_acc).unwrap();
+        assert_owned_by(&state_acc, &program_id).unwrap();
+        assert_eq!(assert_owned_by(&token_acc, &program_id), Err(ProgramError::IncorrectProgramId));
+        assert_eq!(assert_pda(&state_acc, (key, 7)), Ok(7));
+        assert_eq!(assert_pda(&state_acc, (mint, 7)), Err(ProgramError::InvalidSeeds));
+        assert_eq!(assert_token_account_mint(&token_acc, &mint).unwrap().owner, key);
+        assert_eq!(assert_token_account_mint(&token_acc, &other_mint), Err(ProgramError::InvalidAccountData));
+        assert_eq!(assert_token_account_mint(&state_acc, &mint), Err(ProgramError::IncorrectProgramId));
+        assert_eq!(assert_ggt_mint(&program_id, &state_acc), Err(ProgramError::InvalidArgument));
+    }
+}
@@ -0,0 +1,456 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use crate::{
+    config::{self, AuthorityRole},
+    error::GgtError,
+    token_program,
+    treasury_contract::Treasury,
+};
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer, assert_token_account_mint};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const VESTING_VAULT_SEED: &[u8] = b"vesting_vault";
+
+/// A team or investor allocation locked in a vault owned by a per-schedule PDA.
+/// Nothing unlocks before `start + cliff_seconds`; after that the allocation
+/// releases linearly until `start + duration_seconds`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct VestingSchedule {
+    pub beneficiary: Pubkey,
+    pub vault: Pubkey,
+    pub total_amount: u64,
+    pub claimed: u64,
+    pub start: i64,
+    pub cliff_seconds: i64,
+    pub duration_seconds: i64,
+    pub revoked: bool,
+    pub is_initialized: bool,
+}
+
+impl VestingSchedule {
+    /// Amount unlocked at `now`, including what has already been claimed.
+    pub fn vested(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.start);
+        if elapsed < self.cliff_seconds {
+            0
+        } else if elapsed >= self.duration_seconds {
+            self.total_amount
+        } else {
+            (self.total_amount as u128 * elapsed as u128 / self.duration_seconds as u128) as u64
+        }
+    }
+
+    pub fn claimable(&self, now: i64) -> u64 {
+        self.vested(now).saturating_sub(self.claimed)
+    }
+}
+
+impl Sealed for VestingSchedule {}
+
+impl IsInitialized for VestingSchedule {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for VestingSchedule {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [130, 200, 173, 148, 39, 75, 243, 147];
+}
+
+impl Pack for VestingSchedule {
+    const LEN: usize = 115; // discriminator (8) + version (1) + Pubkey (32) * 2 + u64 (8) * 2 + i64 (8) * 3 + bool (1) * 2
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        let mut cursor = 0;
+        dst[cursor..cursor + 32].copy_from_slice(self.beneficiary.as_ref());
+        cursor += 32;
+        dst[cursor..cursor + 32].copy_from_slice(self.vault.as_ref());
+        cursor += 32;
+        dst[cursor..cursor + 8].copy_from_slice(&self.total_amount.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.claimed.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.start.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.cliff_seconds.to_le_bytes());
+        cursor += 8;
+        dst[cursor..cursor + 8].copy_from_slice(&self.duration_seconds.to_le_bytes());
+        cursor += 8;
+        dst[cursor] = self.revoked as u8;
+        cursor += 1;
+        dst[cursor] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        let mut cursor = 0;
+        let beneficiary = Pubkey::new_from_array(src[cursor..cursor + 32].try_into().unwrap());
+        cursor += 32;
+        let vault = Pubkey::new_from_array(src[cursor..cursor + 32].try_into().unwrap());
+        cursor += 32;
+        let total_amount = u64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let claimed = u64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let start = i64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let cliff_seconds = i64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let duration_seconds = i64::from_le_bytes(src[cursor..cursor + 8].try_into().unwrap());
+        cursor += 8;
+        let revoked = src[cursor] != 0;
+        cursor += 1;
+        let is_initialized = src[cursor] != 0;
+        Ok(VestingSchedule {
+            beneficiary,
+            vault,
+            total_amount,
+            claimed,
+            start,
+            cliff_seconds,
+            duration_seconds,
+            revoked,
+            is_initialized,
+        })
+    }
+}
+
+pub struct CreateVestingAccounts<'a, 'info> {
+    pub schedule_acc: &'a AccountInfo<'info>,
+    pub funder_acc: &'a AccountInfo<'info>,
+    pub funder_token_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CreateVestingAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let ctx = Self {
+            schedule_acc: next_account_info(account_info_iter)?,
+            funder_acc: next_account_info(account_info_iter)?,
+            funder_token_acc: next_account_info(account_info_iter)?,
+            vault_acc: next_account_info(account_info_iter)?,
+            token_program_acc: next_account_info(account_info_iter)?,
+            mint_acc: next_account_info(account_info_iter)?,
+        };
+        assert_signer(ctx.funder_acc)?;
+        assert_owned_by(ctx.schedule_acc, program_id)?;
+        token_program::check_token_program(ctx.token_program_acc, ctx.mint_acc)?;
+        let vault = assert_token_account_mint(ctx.vault_acc, ctx.mint_acc.key)?;
+        if vault.owner != Vesting::vault_authority(program_id, ctx.schedule_acc.key).0 {
+            msg!("Vesting vault is not owned by the schedule's vault authority PDA");
+            return Err(ProgramError::IllegalOwner);
+        }
+        Ok(ctx)
+    }
+}
+
+pub struct ClaimVestedAccounts<'a, 'info> {
+    pub schedule_acc: &'a AccountInfo<'info>,
+    pub beneficiary_acc: &'a AccountInfo<'info>,
+    pub beneficiary_token_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub authority_bump: u8,
+}
+
+impl<'a, 'info> ClaimVestedAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let schedule_acc = next_account_info(account_info_iter)?;
+        let beneficiary_acc = next_account_info(account_info_iter)?;
+        let beneficiary_token_acc = next_account_info(account_info_iter)?;
+        let vault_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(beneficiary_acc)?;
+        assert_owned_by(schedule_acc, program_id)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        let authority_bump = assert_pda(authority_acc, Vesting::vault_authority(program_id, schedule_acc.key))?;
+        Ok(Self { schedule_acc, beneficiary_acc, beneficiary_token_acc, vault_acc, authority_acc, token_program_acc, mint_acc, authority_bump })
+    }
+}
+
+/// The governance signer sits after `schedule_acc`; it is only checked.
+pub struct RevokeVestingAccounts<'a, 'info> {
+    pub schedule_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub beneficiary_token_acc: &'a AccountInfo<'info>,
+    pub treasury_vault_acc: &'a AccountInfo<'info>,
+    pub authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub authority_bump: u8,
+}
+
+impl<'a, 'info> RevokeVestingAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let schedule_acc = next_account_info(account_info_iter)?;
+        let governance_acc = next_account_info(account_info_iter)?;
+        let program_config_acc = next_account_info(account_info_iter)?;
+        let vault_acc = next_account_info(account_info_iter)?;
+        let beneficiary_token_acc = next_account_info(account_info_iter)?;
+        let treasury_vault_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+
+        config::check_authority(program_id, program_config_acc, &[AuthorityRole::Governance], governance_acc)?;
+        assert_owned_by(schedule_acc, program_id)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        let authority_bump = assert_pda(authority_acc, Vesting::vault_authority(program_id, schedule_acc.key))?;
+        Ok(Self { schedule_acc, vault_acc, beneficiary_token_acc, treasury_vault_acc, authority_acc, token_program_acc, mint_acc, authority_bump })
+    }
+}
+
+pub struct Vesting;
+
+impl Vesting {
+    /// Authority over the vault of the schedule stored at `schedule`.
+    pub fn vault_authority(program_id: &Pubkey, schedule: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[VESTING_VAULT_SEED, schedule.as_ref()], program_id)
+    }
+
+    fn load_active_schedule(schedule_acc: &AccountInfo) -> Result<VestingSchedule, ProgramError> {
+        let schedule = VestingSchedule::unpack(&schedule_acc.try_borrow_data()?)?;
+        if schedule.revoked {
+            return Err(GgtError::VestingRevoked.into());
+        }
+        Ok(schedule)
+    }
+
+    /// Pays `amount` out of a schedule's vault, signed with `vault_seeds`.
+    fn pay_from_vault<'a>(
+        vault_acc: &AccountInfo<'a>,
+        destination_acc: &AccountInfo<'a>,
+        authority_acc: &AccountInfo<'a>,
+        token_program_acc: &AccountInfo<'a>,
+        mint_acc: &AccountInfo<'a>,
+        vault_seeds: &[&[u8]],
+        amount: u64,
+    ) -> ProgramResult {
+        if amount == 0 {
+            return Ok(());
+        }
+        token_program::transfer(token_program_acc, vault_acc, mint_acc, destination_acc, authority_acc, amount, &[vault_seeds])
+    }
+
+    /// Locks `total_amount` from the funder's token account in the schedule's vault,
+    /// starting the clock now.
+    pub fn create_vesting_schedule(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        beneficiary: Pubkey,
+        total_amount: u64,
+        cliff_seconds: i64,
+        duration_seconds: i64,
+    ) -> ProgramResult {
+        let CreateVestingAccounts { schedule_acc, funder_acc, funder_token_acc, vault_acc, token_program_acc, mint_acc } =
+            CreateVestingAccounts::try_from(program_id, accounts)?;
+        if VestingSchedule::unpack_unchecked(&schedule_acc.try_borrow_data()?)?.is_initialized {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        if total_amount == 0 || duration_seconds <= 0 || !(0..=duration_seconds).contains(&cliff_seconds) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let schedule = VestingSchedule {
+            beneficiary,
+            vault: *vault_acc.key,
+            total_amount,
+            claimed: 0,
+            start: Clock::get()?.unix_timestamp,
+            cliff_seconds,
+            duration_seconds,
+            revoked: false,
+            is_initialized: true,
+        };
+        pack_state(&schedule, schedule_acc)?;
+
+        token_program::transfer(token_program_acc, funder_token_acc, mint_acc, vault_acc, funder_acc, total_amount, &[])?;
+        msg!("Vesting schedule created for {}: {} tokens", beneficiary, total_amount);
+        Ok(())
+    }
+
+    /// Pays the beneficiary everything unlocked and not yet claimed.
+    pub fn claim_vested(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let ClaimVestedAccounts {
+            schedule_acc,
+            beneficiary_acc,
+            beneficiary_token_acc,
+            vault_acc,
+            authority_acc,
+            token_program_acc,
+            mint_acc,
+            authority_bump,
+        } = ClaimVestedAccounts::try_from(program_id, accounts)?;
+        let mut schedule = Self::load_active_schedule(schedule_acc)?;
+        if schedule.beneficiary != *beneficiary_acc.key {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if schedule.vault != *vault_acc.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let amount = schedule.claimable(Clock::get()?.unix_timestamp);
+        schedule.claimed = schedule.claimed.saturating_add(amount);
+        pack_state(&schedule, schedule_acc)?;
+
+        let vault_seeds: &[&[u8]] = &[VESTING_VAULT_SEED, schedule_acc.key.as_ref(), &[authority_bump]];
+        Self::pay_from_vault(vault_acc, beneficiary_token_acc, authority_acc, token_program_acc, mint_acc, vault_seeds, amount)?;
+        msg!("Claimed {} vested tokens", amount);
+        Ok(())
+    }
+
+    /// Governance ends a schedule: the beneficiary is paid what has already
+    /// unlocked and the unvested remainder returns to a treasury vault.
+    pub fn revoke_vesting(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let RevokeVestingAccounts {
+            schedule_acc,
+            vault_acc,
+            beneficiary_token_acc,
+            treasury_vault_acc,
+            authority_acc,
+            token_program_acc,
+            mint_acc,
+            authority_bump,
+        } = RevokeVestingAccounts::try_from(program_id, accounts)?;
+        let mut schedule = Self::load_active_schedule(schedule_acc)?;
+        if schedule.vault != *vault_acc.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        assert_token_account_mint(vault_acc, mint_acc.key)?;
+        let beneficiary_token = assert_token_account_mint(beneficiary_token_acc, mint_acc.key)?;
+        if beneficiary_token.owner != schedule.beneficiary {
+            return Err(ProgramError::IllegalOwner);
+        }
+        let treasury_vault = assert_token_account_mint(treasury_vault_acc, mint_acc.key)?;
+        if treasury_vault.owner != Treasury::treasury_authority(program_id).0 {
+            msg!("Unvested tokens can only be returned to a treasury vault");
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        let vested = schedule.vested(Clock::get()?.unix_timestamp);
+        let payout = vested.saturating_sub(schedule.claimed);
+        let unvested = schedule.total_amount.checked_sub(vested).ok_or(GgtError::MathOverflow)?;
+        schedule.claimed = vested;
+        schedule.total_amount = vested;
+        schedule.revoked = true;
+        pack_state(&schedule, schedule_acc)?;
+
+        let vault_seeds: &[&[u8]] = &[VESTING_VAULT_SEED, schedule_acc.key.as_ref(), &[authority_bump]];
+        Self::pay_from_vault(vault_acc, beneficiary_token_acc, authority_acc, token_program_acc, mint_acc, vault_seeds, payout)?;
+        Self::pay_from_vault(vault_acc, treasury_vault_acc, authority_acc, token_program_acc, mint_acc, vault_seeds, unvested)?;
+        msg!("Vesting revoked: {} paid out, {} returned to treasury", payout, unvested);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{account_info, fund_rent_exempt, program_config, token_account_data, SimulatedBank};
+    use crate::GOVERNANCE_PUBKEY;
+
+    #[test]
+    fn test_vesting_cliff_linear_claim_and_revoke() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let token_program_id = spl_token::id();
+        let governance_key = GOVERNANCE_PUBKEY;
+        let (schedule_key, funder_key, funder_token_key, vault_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (beneficiary_key, beneficiary_token_key, treasury_vault_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (authority_key, _) = Vesting::vault_authority(&program_id, &schedule_key);
+        let (treasury_authority, _) = Treasury::treasury_authority(&program_id);
+        let (program_config_key, mut program_config_data) = program_config(&program_id);
+        let mut lamports = [0u64; 12];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10, l11] = &mut lamports;
+        let mut schedule_data = vec![0u8; VestingSchedule::LEN];
+        let mint = Pubkey::new_unique();
+        let mut vault_data = token_account_data(&mint, &authority_key, 0);
+        let mut beneficiary_token_data = token_account_data(&mint, &beneficiary_key, 0);
+        let mut treasury_vault_data = token_account_data(&mint, &treasury_authority, 0);
+        let (mut d1, mut d2, mut d4, mut d5, mut d6) = (vec![], vec![], vec![], vec![], vec![]);
+        let schedule_acc = account_info(&schedule_key, false, true, l0, &mut schedule_data, &program_id);
+        fund_rent_exempt(&[&schedule_acc]);
+        let funder_acc = account_info(&funder_key, true, false, l1, &mut d1, &program_id);
+        let funder_token_acc = account_info(&funder_token_key, false, true, l2, &mut d2, &token_program_id);
+        let vault_acc = account_info(&vault_key, false, true, l3, &mut vault_data, &token_program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, l4, &mut d4, &program_id);
+        let beneficiary_acc = account_info(&beneficiary_key, true, false, l5, &mut d5, &program_id);
+        let authority_acc = account_info(&authority_key, false, false, l6, &mut d6, &program_id);
+        let beneficiary_token_acc = account_info(&beneficiary_token_key, false, true, l7, &mut beneficiary_token_data, &token_program_id);
+        let treasury_vault_acc = account_info(&treasury_vault_key, false, true, l8, &mut treasury_vault_data, &token_program_id);
+        let mut d9 = vec![];
+        let governance_acc = account_info(&governance_key, true, false, l9, &mut d9, &program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, l10, &mut program_config_data, &program_id);
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() }, &mut mint_data).unwrap();
+        let mint_acc = account_info(&mint, false, false, l11, &mut mint_data, &token_program_id);
+        bank.set_balance(&funder_token_key, 1_000);
+
+        bank.set_unix_timestamp(10_000);
+        let create_accounts = [schedule_acc.clone(), funder_acc, funder_token_acc, vault_acc.clone(), token_program_acc.clone(), mint_acc.clone()];
+        // The token program must own the mint.
+        let mut wrong_program = create_accounts.clone();
+        wrong_program[4] = governance_acc.clone();
+        assert_eq!(
+            Vesting::create_vesting_schedule(&program_id, &wrong_program, beneficiary_key, 1_000, 100, 400),
+            Err(ProgramError::IncorrectProgramId)
+        );
+        Vesting::create_vesting_schedule(&program_id, &create_accounts, beneficiary_key, 1_000, 100, 400).unwrap();
+        assert_eq!(bank.balance(&vault_key), 1_000);
+
+        let claim_accounts = [
+            schedule_acc.clone(),
+            beneficiary_acc,
+            beneficiary_token_acc.clone(),
+            vault_acc.clone(),
+            authority_acc.clone(),
+            token_program_acc.clone(),
+            mint_acc.clone(),
+        ];
+        bank.set_unix_timestamp(10_099);
+        Vesting::claim_vested(&program_id, &claim_accounts).unwrap();
+        assert_eq!(bank.balance(&beneficiary_token_key), 0);
+
+        bank.set_unix_timestamp(10_100);
+        Vesting::claim_vested(&program_id, &claim_accounts).unwrap();
+        assert_eq!(bank.balance(&beneficiary_token_key), 250);
+
+        bank.set_unix_timestamp(10_200);
+        let revoke_accounts = [
+            schedule_acc.clone(),
+            governance_acc,
+            program_config_acc,
+            vault_acc,
+            beneficiary_token_acc,
+            treasury_vault_acc,
+            authority_acc,
+            token_program_acc,
+            mint_acc,
+        ];
+        Vesting::revoke_vesting(&program_id, &revoke_accounts).unwrap();
+        assert_eq!(bank.balance(&beneficiary_token_key), 500);
+        assert_eq!(bank.balance(&treasury_vault_key), 500);
+        assert_eq!(bank.balance(&vault_key), 0);
+        assert_eq!(Vesting::claim_vested(&program_id, &claim_accounts), Err(GgtError::VestingRevoked.into()));
+    }
+}
@@ -0,0 +1,349 @@
+//! Vote-escrowed GGT. `lock_for_voting` locks tokens for one to four years in
+//! a `VoteEscrow` PDA per owner and mint, in exchange for voting weight on top
+//! of the owner's stake. The weight is boosted by the time left on the lock,
+//! one unit per token per remaining year, so it decays linearly to zero at
+//! unlock. The tokens cannot leave before then; `withdraw_expired_lock`
+//! returns them afterwards.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use crate::{error::GgtError, events, token_program};
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer, assert_token_account_mint};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const VOTE_ESCROW_SEED: &[u8] = b"vote_escrow";
+pub const VOTE_ESCROW_VAULT_SEED: &[u8] = b"vote_escrow_vault";
+pub const SECONDS_PER_YEAR: i64 = 365 * 86400;
+pub const MIN_LOCK_DAYS: u64 = 365;
+pub const MAX_LOCK_DAYS: u64 = 4 * 365;
+const MAX_LOCK_SECONDS: i64 = MAX_LOCK_DAYS as i64 * 86400;
+
+pub fn vote_escrow_address(program_id: &Pubkey, owner: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VOTE_ESCROW_SEED, owner.as_ref(), mint.as_ref()], program_id)
+}
+
+/// Owner of the token account holding everything locked in `mint`.
+pub fn vote_escrow_vault_authority(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VOTE_ESCROW_VAULT_SEED, mint.as_ref()], program_id)
+}
+
+fn check_vault(program_id: &Pubkey, vault_acc: &AccountInfo, mint: &Pubkey) -> ProgramResult {
+    if assert_token_account_mint(vault_acc, mint)?.owner != vote_escrow_vault_authority(program_id, mint).0 {
+        msg!("Vote-escrow vault {} is not owned by the vault PDA", vault_acc.key);
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct VoteEscrow {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub unlock_at: i64,
+    /// Last time tokens were added or the lock extended; the lock only
+    /// counts towards proposals created after that.
+    pub updated_at: i64,
+    pub is_initialized: bool,
+}
+
+impl VoteEscrow {
+    /// Voting weight at `at`: the locked amount for each year left on the lock.
+    pub fn weight_at(&self, at: i64) -> u64 {
+        let remaining = self.unlock_at.saturating_sub(at).clamp(0, MAX_LOCK_SECONDS);
+        (self.amount as u128 * remaining as u128 / SECONDS_PER_YEAR as u128) as u64
+    }
+}
+
+impl Sealed for VoteEscrow {}
+
+impl IsInitialized for VoteEscrow {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for VoteEscrow {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [242, 251, 110, 92, 198, 238, 144, 106];
+}
+
+impl Pack for VoteEscrow {
+    const LEN: usize = 98; // discriminator (8) + version (1) + Pubkey (32) * 2 + u64 (8) + i64 (8) * 2 + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..32].copy_from_slice(self.owner.as_ref());
+        dst[32..64].copy_from_slice(self.mint.as_ref());
+        dst[64..72].copy_from_slice(&self.amount.to_le_bytes());
+        dst[72..80].copy_from_slice(&self.unlock_at.to_le_bytes());
+        dst[80..88].copy_from_slice(&self.updated_at.to_le_bytes());
+        dst[88] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(VoteEscrow {
+            owner: Pubkey::new_from_array(src[0..32].try_into().unwrap()),
+            mint: Pubkey::new_from_array(src[32..64].try_into().unwrap()),
+            amount: u64::from_le_bytes(src[64..72].try_into().unwrap()),
+            unlock_at: i64::from_le_bytes(src[72..80].try_into().unwrap()),
+            updated_at: i64::from_le_bytes(src[80..88].try_into().unwrap()),
+            is_initialized: src[88] != 0,
+        })
+    }
+}
+
+/// Voting weight `voter`'s lock in `escrow_acc` adds to a proposal created at
+/// `snapshot`. Locks changed since then count for nothing.
+pub(crate) fn snapshot_escrow_weight(
+    program_id: &Pubkey,
+    escrow_acc: &AccountInfo,
+    voter: &Pubkey,
+    mint: &Pubkey,
+    snapshot: i64,
+) -> Result<u64, ProgramError> {
+    assert_owned_by(escrow_acc, program_id)?;
+    assert_pda(escrow_acc, vote_escrow_address(program_id, voter, mint))?;
+    let escrow = VoteEscrow::unpack(&escrow_acc.try_borrow_data()?)?;
+    if escrow.updated_at >= snapshot {
+        msg!("Lock changed at {}, after the proposal snapshot at {}", escrow.updated_at, snapshot);
+        return Ok(0);
+    }
+    Ok(escrow.weight_at(snapshot))
+}
+
+pub struct LockForVotingAccounts<'a, 'info> {
+    pub escrow_acc: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+    pub owner_token_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub escrow_bump: u8,
+}
+
+impl<'a, 'info> LockForVotingAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let escrow_acc = next_account_info(account_info_iter)?;
+        let owner_acc = next_account_info(account_info_iter)?;
+        let owner_token_acc = next_account_info(account_info_iter)?;
+        let vault_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(owner_acc)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        assert_token_account_mint(owner_token_acc, mint_acc.key)?;
+        check_vault(program_id, vault_acc, mint_acc.key)?;
+        let escrow_bump = assert_pda(escrow_acc, vote_escrow_address(program_id, owner_acc.key, mint_acc.key))?;
+        Ok(Self { escrow_acc, owner_acc, owner_token_acc, vault_acc, mint_acc, token_program_acc, system_program_acc, escrow_bump })
+    }
+}
+
+pub struct WithdrawExpiredLockAccounts<'a, 'info> {
+    pub escrow_acc: &'a AccountInfo<'info>,
+    pub owner_acc: &'a AccountInfo<'info>,
+    pub owner_token_acc: &'a AccountInfo<'info>,
+    pub vault_acc: &'a AccountInfo<'info>,
+    pub vault_authority_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub vault_authority_bump: u8,
+}
+
+impl<'a, 'info> WithdrawExpiredLockAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let escrow_acc = next_account_info(account_info_iter)?;
+        let owner_acc = next_account_info(account_info_iter)?;
+        let owner_token_acc = next_account_info(account_info_iter)?;
+        let vault_acc = next_account_info(account_info_iter)?;
+        let vault_authority_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(owner_acc)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        assert_token_account_mint(owner_token_acc, mint_acc.key)?;
+        check_vault(program_id, vault_acc, mint_acc.key)?;
+        assert_owned_by(escrow_acc, program_id)?;
+        assert_pda(escrow_acc, vote_escrow_address(program_id, owner_acc.key, mint_acc.key))?;
+        let vault_authority_bump = assert_pda(vault_authority_acc, vote_escrow_vault_authority(program_id, mint_acc.key))?;
+        Ok(Self {
+            escrow_acc,
+            owner_acc,
+            owner_token_acc,
+            vault_acc,
+            vault_authority_acc,
+            mint_acc,
+            token_program_acc,
+            vault_authority_bump,
+        })
+    }
+}
+
+/// Locks `amount` until `lock_period_in_days` from now, which must be between
+/// one and four years. Locking again adds to the existing lock, whose unlock time
+/// can only move later.
+pub fn lock_for_voting(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64, lock_period_in_days: u64) -> ProgramResult {
+    let LockForVotingAccounts {
+        escrow_acc,
+        owner_acc,
+        owner_token_acc,
+        vault_acc,
+        mint_acc,
+        token_program_acc,
+        system_program_acc,
+        escrow_bump,
+    } = LockForVotingAccounts::try_from(program_id, accounts)?;
+    if amount == 0 || !(MIN_LOCK_DAYS..=MAX_LOCK_DAYS).contains(&lock_period_in_days) {
+        msg!("Locks last between {} and {} days", MIN_LOCK_DAYS, MAX_LOCK_DAYS);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let now = Clock::get()?.unix_timestamp;
+
+    let mut escrow = if escrow_acc.lamports() == 0 {
+        invoke_signed(
+            &system_instruction::create_account(
+                owner_acc.key,
+                escrow_acc.key,
+                Rent::get()?.minimum_balance(VoteEscrow::LEN),
+                VoteEscrow::LEN as u64,
+                program_id,
+            ),
+            &[owner_acc.clone(), escrow_acc.clone(), system_program_acc.clone()],
+            &[&[VOTE_ESCROW_SEED, owner_acc.key.as_ref(), mint_acc.key.as_ref(), &[escrow_bump]]],
+        )?;
+        VoteEscrow { owner: *owner_acc.key, mint: *mint_acc.key, is_initialized: true, ..VoteEscrow::default() }
+    } else {
+        assert_owned_by(escrow_acc, program_id)?;
+        VoteEscrow::unpack(&escrow_acc.try_borrow_data()?)?
+    };
+    escrow.amount = escrow.amount.checked_add(amount).ok_or(GgtError::MathOverflow)?;
+    escrow.unlock_at = escrow.unlock_at.max(now + lock_period_in_days as i64 * 86400);
+    escrow.updated_at = now;
+    let (total, unlock_at) = (escrow.amount, escrow.unlock_at);
+    pack_state(&escrow, escrow_acc)?;
+
+    token_program::transfer(token_program_acc, owner_token_acc, mint_acc, vault_acc, owner_acc, amount, &[])?;
+    events::emit(&events::VoteEscrowLocked { owner: *owner_acc.key, mint: *mint_acc.key, amount: total, unlock_at });
+    msg!("Locked {} tokens for voting until {}", total, unlock_at);
+    Ok(())
+}
+
+/// Returns the whole lock to its owner once `unlock_at` has passed.
+pub fn withdraw_expired_lock(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let WithdrawExpiredLockAccounts {
+        escrow_acc,
+        owner_acc,
+        owner_token_acc,
+        vault_acc,
+        vault_authority_acc,
+        mint_acc,
+        token_program_acc,
+        vault_authority_bump,
+    } = WithdrawExpiredLockAccounts::try_from(program_id, accounts)?;
+    let mut escrow = VoteEscrow::unpack(&escrow_acc.try_borrow_data()?)?;
+    let amount = escrow.amount;
+    if amount == 0 {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    if Clock::get()?.unix_timestamp < escrow.unlock_at {
+        msg!("Lock expires at {}", escrow.unlock_at);
+        return Err(GgtError::VoteEscrowLocked.into());
+    }
+    escrow.amount = 0;
+    pack_state(&escrow, escrow_acc)?;
+
+    token_program::transfer(
+        token_program_acc,
+        vault_acc,
+        mint_acc,
+        owner_token_acc,
+        vault_authority_acc,
+        amount,
+        &[&[VOTE_ESCROW_VAULT_SEED, mint_acc.key.as_ref(), &[vault_authority_bump]]],
+    )?;
+    events::emit(&events::VoteEscrowWithdrawn { owner: *owner_acc.key, mint: *mint_acc.key, amount });
+    msg!("Withdrew {} tokens from an expired voting lock", amount);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{account_info, token_account_data, SimulatedBank};
+
+    #[test]
+    fn test_lock_weight_decays_and_unlocks_at_expiry() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (owner_key, mint_key, owner_token_key, vault_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (escrow_key, _) = vote_escrow_address(&program_id, &owner_key, &mint_key);
+        let (vault_authority_key, _) = vote_escrow_vault_authority(&program_id, &mint_key);
+        let (token_program_key, system_program_key) = (spl_token::id(), solana_program::system_program::id());
+        let (mut l0, mut l1, mut l2, mut l3, mut l4, mut l5, mut l6, mut l7) = (0u64, 10_000_000u64, 0u64, 0u64, 0u64, 0u64, 0u64, 0u64);
+        let mut escrow_data = vec![0u8; VoteEscrow::LEN];
+        let mut owner_token_data = token_account_data(&mint_key, &owner_key, 0);
+        let mut vault_data = token_account_data(&mint_key, &vault_authority_key, 0);
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(spl_token::state::Mint { decimals: 9, is_initialized: true, ..Default::default() }, &mut mint_data).unwrap();
+        let (mut d1, mut d5, mut d6, mut d7) = (vec![], vec![], vec![], vec![]);
+        let escrow_acc = account_info(&escrow_key, false, true, &mut l0, &mut escrow_data, &program_id);
+        let owner_acc = account_info(&owner_key, true, true, &mut l1, &mut d1, &system_program_key);
+        let owner_token_acc = account_info(&owner_token_key, false, true, &mut l2, &mut owner_token_data, &token_program_key);
+        let vault_acc = account_info(&vault_key, false, true, &mut l3, &mut vault_data, &token_program_key);
+        let mint_acc = account_info(&mint_key, false, false, &mut l4, &mut mint_data, &token_program_key);
+        let token_program_acc = account_info(&token_program_key, false, false, &mut l5, &mut d5, &program_id);
+        let system_program_acc = account_info(&system_program_key, false, false, &mut l6, &mut d6, &program_id);
+        let vault_authority_acc = account_info(&vault_authority_key, false, false, &mut l7, &mut d7, &program_id);
+        bank.set_balance(&owner_token_key, 1_000);
+        bank.set_unix_timestamp(1_000);
+
+        let lock_accounts = [
+            escrow_acc.clone(),
+            owner_acc.clone(),
+            owner_token_acc.clone(),
+            vault_acc.clone(),
+            mint_acc.clone(),
+            token_program_acc.clone(),
+            system_program_acc,
+        ];
+        assert_eq!(lock_for_voting(&program_id, &lock_accounts, 400, MIN_LOCK_DAYS - 1), Err(ProgramError::InvalidArgument));
+        lock_for_voting(&program_id, &lock_accounts, 400, MAX_LOCK_DAYS).unwrap();
+        assert_eq!((bank.balance(&owner_token_key), bank.balance(&vault_key)), (600, 400));
+
+        // Weight only counts for proposals created after the lock, and decays
+        // from four times the amount to nothing at unlock.
+        let weight = |snapshot| snapshot_escrow_weight(&program_id, &escrow_acc, &owner_key, &mint_key, snapshot).unwrap();
+        assert_eq!(weight(1_000), 0);
+        assert_eq!(weight(1_001), 1_599);
+        assert_eq!(weight(1_000 + 2 * SECONDS_PER_YEAR), 800);
+        assert_eq!(weight(1_000 + MAX_LOCK_SECONDS), 0);
+
+        let withdraw_accounts =
+            [escrow_acc.clone(), owner_acc, owner_token_acc, vault_acc, vault_authority_acc, mint_acc, token_program_acc];
+        assert_eq!(withdraw_expired_lock(&program_id, &withdraw_accounts), Err(GgtError::VoteEscrowLocked.into()));
+        bank.set_unix_timestamp(1_000 + MAX_LOCK_SECONDS);
+        withdraw_expired_lock(&program_id, &withdraw_accounts).unwrap();
+        assert_eq!((bank.balance(&owner_token_key), bank.balance(&vault_key)), (1_000, 0));
+        assert_eq!(VoteEscrow::unpack(&escrow_acc.try_borrow_data().unwrap()).unwrap().amount, 0);
+        assert_eq!(withdraw_expired_lock(&program_id, &withdraw_accounts), Err(ProgramError::InsufficientFunds));
+    }
+}
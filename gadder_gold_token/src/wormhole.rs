@@ -0,0 +1,331 @@
+//! Adapter for chains registered with `SignatureScheme::Wormhole`, which
+//! relay through the Wormhole core bridge instead of our guardian set. Locks
+//! to such a chain post their transfer as a Wormhole message from this
+//! program's emitter, and releases from it are proven by a posted VAA
+//! account, already verified by the core bridge, in place of the
+//! instructions sysvar. The core bridge instruction and account layouts are
+//! encoded here by hand so the program does not depend on the Wormhole SDK.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey,
+    pubkey::Pubkey,
+    system_instruction, system_program, sysvar,
+};
+use crate::bridge_transfer::BridgeTransfer;
+use crate::cross_chain_bridge_contract::ChainInfo;
+use crate::error::GgtError;
+use crate::validation::assert_pda;
+
+/// Mainnet Wormhole core bridge.
+pub const WORMHOLE_PROGRAM_ID: Pubkey = pubkey!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+/// Seed of this program's emitter, as the Wormhole SDK derives it.
+pub const WORMHOLE_EMITTER_SEED: &[u8] = b"emitter";
+pub const WORMHOLE_MESSAGE_SEED: &[u8] = b"wormhole_message";
+/// Core bridge `PostMessage` instruction index.
+const POST_MESSAGE: u8 = 1;
+/// Guardians observe a posted message only once its slot is finalized.
+const CONSISTENCY_FINALIZED: u8 = 1;
+const POSTED_VAA_MAGIC: &[u8] = b"vaa";
+/// Offsets into a posted VAA after its magic: version (1), consistency (1),
+/// timestamp (4), signature set (32), submission time (4), nonce (4),
+/// sequence (8), then the emitter and the length-prefixed payload.
+const VAA_EMITTER_CHAIN: usize = 3 + 54;
+const VAA_EMITTER_ADDRESS: usize = VAA_EMITTER_CHAIN + 2;
+const VAA_PAYLOAD: usize = VAA_EMITTER_ADDRESS + 32;
+/// Offset of the message fee in the core bridge's config account.
+const BRIDGE_FEE_OFFSET: usize = 16;
+
+pub fn emitter_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[WORMHOLE_EMITTER_SEED], program_id)
+}
+
+/// Message account the lock with `sequence` posts.
+pub fn message_address(program_id: &Pubkey, sequence: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[WORMHOLE_MESSAGE_SEED, &sequence.to_le_bytes()], program_id)
+}
+
+pub fn bridge_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"Bridge"], &WORMHOLE_PROGRAM_ID).0
+}
+
+pub fn fee_collector_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"fee_collector"], &WORMHOLE_PROGRAM_ID).0
+}
+
+pub fn sequence_address(emitter: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"Sequence", emitter.as_ref()], &WORMHOLE_PROGRAM_ID).0
+}
+
+/// Payload of the Wormhole message a lock posts: transfer ID, locked mint,
+/// bridged amount, target chain and the length-prefixed destination.
+/// Releases from a Wormhole chain carry the mint too, in `release_message`.
+pub fn transfer_payload(transfer: &BridgeTransfer) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(75 + transfer.destination.len());
+    payload.extend_from_slice(&transfer.transfer_id());
+    payload.extend_from_slice(transfer.mint.as_ref());
+    payload.extend_from_slice(&transfer.amount.to_le_bytes());
+    payload.extend_from_slice(&transfer.target_chain_id.to_le_bytes());
+    payload.push(transfer.destination.len() as u8);
+    payload.extend_from_slice(&transfer.destination);
+    payload
+}
+
+/// Core bridge accounts trailing `lock_tokens_for_bridge` when the target
+/// chain relays through Wormhole.
+pub struct PostMessageAccounts<'a, 'info> {
+    pub bridge_acc: &'a AccountInfo<'info>,
+    pub message_acc: &'a AccountInfo<'info>,
+    pub emitter_acc: &'a AccountInfo<'info>,
+    pub sequence_acc: &'a AccountInfo<'info>,
+    pub fee_collector_acc: &'a AccountInfo<'info>,
+    pub clock_acc: &'a AccountInfo<'info>,
+    pub rent_acc: &'a AccountInfo<'info>,
+    pub wormhole_program_acc: &'a AccountInfo<'info>,
+    pub emitter_bump: u8,
+}
+
+impl<'a, 'info> PostMessageAccounts<'a, 'info> {
+    pub fn try_from(
+        program_id: &Pubkey,
+        account_info_iter: &mut impl Iterator<Item = &'a AccountInfo<'info>>,
+    ) -> Result<Self, ProgramError> {
+        let bridge_acc = next_account_info(account_info_iter)?;
+        let message_acc = next_account_info(account_info_iter)?;
+        let emitter_acc = next_account_info(account_info_iter)?;
+        let sequence_acc = next_account_info(account_info_iter)?;
+        let fee_collector_acc = next_account_info(account_info_iter)?;
+        let clock_acc = next_account_info(account_info_iter)?;
+        let rent_acc = next_account_info(account_info_iter)?;
+        let wormhole_program_acc = next_account_info(account_info_iter)?;
+
+        if *wormhole_program_acc.key != WORMHOLE_PROGRAM_ID
+            || *bridge_acc.key != bridge_address()
+            || *fee_collector_acc.key != fee_collector_address()
+            || *sequence_acc.key != sequence_address(emitter_acc.key)
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let emitter_bump = assert_pda(emitter_acc, emitter_address(program_id))?;
+        Ok(Self {
+            bridge_acc,
+            message_acc,
+            emitter_acc,
+            sequence_acc,
+            fee_collector_acc,
+            clock_acc,
+            rent_acc,
+            wormhole_program_acc,
+            emitter_bump,
+        })
+    }
+}
+
+/// Builds the core bridge `PostMessage` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn post_message_instruction(
+    bridge: &Pubkey,
+    message: &Pubkey,
+    emitter: &Pubkey,
+    sequence: &Pubkey,
+    payer: &Pubkey,
+    fee_collector: &Pubkey,
+    nonce: u32,
+    payload: &[u8],
+) -> Instruction {
+    let mut data = Vec::with_capacity(10 + payload.len());
+    data.push(POST_MESSAGE);
+    data.extend_from_slice(&nonce.to_le_bytes());
+    data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    data.extend_from_slice(payload);
+    data.push(CONSISTENCY_FINALIZED);
+    Instruction {
+        program_id: WORMHOLE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*bridge, false),
+            AccountMeta::new(*message, true),
+            AccountMeta::new_readonly(*emitter, true),
+            AccountMeta::new(*sequence, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*fee_collector, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Pays the core bridge's message fee from `payer_acc` and posts `payload`
+/// from this program's emitter, in the message PDA for `sequence`.
+pub(crate) fn post_message<'info>(
+    program_id: &Pubkey,
+    accounts: &PostMessageAccounts<'_, 'info>,
+    payer_acc: &AccountInfo<'info>,
+    system_program_acc: &AccountInfo<'info>,
+    sequence: u64,
+    payload: &[u8],
+) -> ProgramResult {
+    let message_bump = assert_pda(accounts.message_acc, message_address(program_id, sequence))?;
+    let fee = accounts
+        .bridge_acc
+        .try_borrow_data()?
+        .get(BRIDGE_FEE_OFFSET..BRIDGE_FEE_OFFSET + 8)
+        .map(|fee| u64::from_le_bytes(fee.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if fee > 0 {
+        invoke(
+            &system_instruction::transfer(payer_acc.key, accounts.fee_collector_acc.key, fee),
+            &[payer_acc.clone(), accounts.fee_collector_acc.clone(), system_program_acc.clone()],
+        )?;
+    }
+    invoke_signed(
+        &post_message_instruction(
+            accounts.bridge_acc.key,
+            accounts.message_acc.key,
+            accounts.emitter_acc.key,
+            accounts.sequence_acc.key,
+            payer_acc.key,
+            accounts.fee_collector_acc.key,
+            sequence as u32,
+            payload,
+        ),
+        &[
+            accounts.bridge_acc.clone(),
+            accounts.message_acc.clone(),
+            accounts.emitter_acc.clone(),
+            accounts.sequence_acc.clone(),
+            payer_acc.clone(),
+            accounts.fee_collector_acc.clone(),
+            accounts.clock_acc.clone(),
+            system_program_acc.clone(),
+            accounts.rent_acc.clone(),
+            accounts.wormhole_program_acc.clone(),
+        ],
+        &[
+            &[WORMHOLE_EMITTER_SEED, &[accounts.emitter_bump]],
+            &[WORMHOLE_MESSAGE_SEED, &sequence.to_le_bytes(), &[message_bump]],
+        ],
+    )
+}
+
+/// Checks `posted_vaa_acc` is a VAA the core bridge verified, emitted by
+/// `chain`'s registered Wormhole emitter, whose payload is `message`.
+pub(crate) fn verify_posted_vaa(chain: &ChainInfo, posted_vaa_acc: &AccountInfo, message: &[u8]) -> ProgramResult {
+    if *posted_vaa_acc.owner != WORMHOLE_PROGRAM_ID {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let data = posted_vaa_acc.try_borrow_data()?;
+    let matches = data.get(..POSTED_VAA_MAGIC.len()) == Some(POSTED_VAA_MAGIC)
+        && data.get(VAA_EMITTER_CHAIN..VAA_EMITTER_ADDRESS) == Some(&chain.wormhole_chain_id.to_le_bytes()[..])
+        && data.get(VAA_EMITTER_ADDRESS..VAA_PAYLOAD) == Some(&chain.wormhole_emitter[..])
+        && data.get(VAA_PAYLOAD..VAA_PAYLOAD + 4) == Some(&(message.len() as u32).to_le_bytes()[..])
+        && data.get(VAA_PAYLOAD + 4..) == Some(message);
+    if !matches {
+        msg!("Posted VAA does not carry this message from the {} emitter", chain.name);
+        return Err(GgtError::WormholeVaaMismatch.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cross_chain_bridge_contract::{AddressFormat, CrossChainBridge, SignatureScheme};
+    use crate::test_utils::account_info;
+
+    fn posted_vaa(emitter_chain: u16, emitter: [u8; 32], payload: &[u8]) -> Vec<u8> {
+        let mut data = POSTED_VAA_MAGIC.to_vec();
+        data.resize(VAA_EMITTER_CHAIN, 0);
+        data.extend_from_slice(&emitter_chain.to_le_bytes());
+        data.extend_from_slice(&emitter);
+        data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        data.extend_from_slice(payload);
+        data
+    }
+
+    #[test]
+    fn test_posted_vaa_must_come_from_the_registered_emitter() {
+        let chain = ChainInfo {
+            chain_id: 1,
+            name: "ethereum".to_string(),
+            address_format: AddressFormat::Evm,
+            decimals: 18,
+            signature_scheme: SignatureScheme::Wormhole,
+            fee_flat: 0,
+            fee_bps: 0,
+            wormhole_chain_id: 2,
+            wormhole_emitter: [7; 32],
+        };
+        let message = b"release".to_vec();
+        let key = Pubkey::new_unique();
+        let check = |owner: &Pubkey, mut data: Vec<u8>| {
+            let mut lamports = 1u64;
+            let acc = account_info(&key, false, false, &mut lamports, &mut data, owner);
+            verify_posted_vaa(&chain, &acc, &message)
+        };
+        assert_eq!(check(&WORMHOLE_PROGRAM_ID, posted_vaa(2, [7; 32], &message)), Ok(()));
+        let mismatch = Err(GgtError::WormholeVaaMismatch.into());
+        assert_eq!(check(&WORMHOLE_PROGRAM_ID, posted_vaa(5, [7; 32], &message)), mismatch);
+        assert_eq!(check(&WORMHOLE_PROGRAM_ID, posted_vaa(2, [8; 32], &message)), mismatch);
+        assert_eq!(check(&WORMHOLE_PROGRAM_ID, posted_vaa(2, [7; 32], b"release 2")), mismatch);
+        // A look-alike account not written by the core bridge proves nothing.
+        assert_eq!(check(&Pubkey::new_unique(), posted_vaa(2, [7; 32], &message)), Err(ProgramError::IllegalOwner));
+
+        let ix = post_message_instruction(
+            &bridge_address(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &fee_collector_address(),
+            9,
+            &message,
+        );
+        assert_eq!(ix.data, [&[POST_MESSAGE, 9, 0, 0, 0, 7, 0, 0, 0][..], &message, &[CONSISTENCY_FINALIZED]].concat());
+        assert!(ix.accounts[1].is_signer && ix.accounts[2].is_signer && !ix.accounts[2].is_writable);
+    }
+
+    #[test]
+    fn test_wormhole_messages_name_the_mint_in_both_directions() {
+        let (ggt_mint, other_mint, recipient) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let transfer = BridgeTransfer {
+            sender: Pubkey::new_unique(),
+            sender_token: Pubkey::new_unique(),
+            mint: ggt_mint,
+            sequence: 3,
+            amount: 500,
+            fee: 0,
+            target_chain_id: 1,
+            destination: vec![0x11; 20],
+            status: crate::bridge_transfer::BridgeTransferStatus::Pending,
+            created_at: 0,
+            is_initialized: true,
+        };
+        let payload = transfer_payload(&transfer);
+        assert_eq!((&payload[..32], &payload[32..64]), (&transfer.transfer_id()[..], ggt_mint.as_ref()));
+
+        // A VAA releasing some other mint does not prove a release of GGT.
+        let chain = ChainInfo {
+            chain_id: 1,
+            name: "ethereum".to_string(),
+            address_format: AddressFormat::Evm,
+            decimals: 18,
+            signature_scheme: SignatureScheme::Wormhole,
+            fee_flat: 0,
+            fee_bps: 0,
+            wormhole_chain_id: 2,
+            wormhole_emitter: [7; 32],
+        };
+        let mut data = posted_vaa(2, [7; 32], &CrossChainBridge::release_message(&other_mint, 500, &recipient, 7, 1));
+        let (key, mut lamports) = (Pubkey::new_unique(), 1u64);
+        let acc = account_info(&key, false, false, &mut lamports, &mut data, &WORMHOLE_PROGRAM_ID);
+        let expected = CrossChainBridge::release_message(&ggt_mint, 500, &recipient, 7, 1);
+        assert_eq!(verify_posted_vaa(&chain, &acc, &expected), Err(GgtError::WormholeVaaMismatch.into()));
+    }
+}
@@ -0,0 +1,470 @@
+//! Wrapped assets for tokens bridged in from other chains. Attesting a foreign
+//! token creates an SPL mint derived from its chain and address, with the
+//! bridge vault authority as mint and freeze authority, and a record mapping
+//! the mint back to the foreign token. Inbound transfers of that token mint
+//! wrapped tokens to the recipient once guardians have signed them, the mirror
+//! of the outbound burn in `BurnMint` mode.
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use spl_token_2022::{instruction::initialize_mint2, state::Mint};
+use crate::cross_chain_bridge_contract::{check_bridge_admin_or_governance, BridgeReceipt, CrossChainBridge, BRIDGE_VAULT_SEED};
+use crate::{events, token_program};
+use crate::state::pack_state;
+use crate::validation::{assert_owned_by, assert_pda, assert_signer, assert_token_account_mint};
+use crate::discriminator::{read_discriminator, write_discriminator, Discriminator, DISCRIMINATOR_LEN};
+
+pub const WRAPPED_MINT_SEED: &[u8] = b"wrapped_mint";
+pub const WRAPPED_ASSET_SEED: &[u8] = b"wrapped_asset";
+/// Prefix of the payload guardians sign to release wrapped tokens.
+pub const WRAPPED_TRANSFER_DOMAIN: &[u8] = b"ggt-wrapped-transfer";
+/// Foreign tokens with more decimals are wrapped at this precision.
+pub const MAX_WRAPPED_DECIMALS: u8 = 9;
+
+/// Wrapped mint for `foreign_token` on `chain_id`. Foreign addresses shorter
+/// than 32 bytes are left-padded with zeros.
+pub fn wrapped_mint_address(program_id: &Pubkey, chain_id: u16, foreign_token: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[WRAPPED_MINT_SEED, &chain_id.to_le_bytes(), foreign_token], program_id)
+}
+
+/// Record of the foreign token behind `mint`.
+pub fn wrapped_asset_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[WRAPPED_ASSET_SEED, mint.as_ref()], program_id)
+}
+
+/// What guardians sign to release `amount` of the wrapped `foreign_token`, minted as `mint`, to `recipient`.
+pub fn wrapped_transfer_message(
+    foreign_token: &[u8; 32],
+    mint: &Pubkey,
+    amount: u64,
+    recipient: &Pubkey,
+    nonce: u64,
+    source_chain_id: u16,
+) -> Vec<u8> {
+    [
+        WRAPPED_TRANSFER_DOMAIN,
+        &foreign_token[..],
+        &CrossChainBridge::release_message(mint, amount, recipient, nonce, source_chain_id),
+    ]
+    .concat()
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct WrappedAsset {
+    pub chain_id: u16,
+    pub foreign_token: [u8; 32],
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub is_initialized: bool,
+}
+
+impl Sealed for WrappedAsset {}
+
+impl IsInitialized for WrappedAsset {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Discriminator for WrappedAsset {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [171, 107, 230, 24, 201, 198, 231, 192];
+}
+
+impl Pack for WrappedAsset {
+    const LEN: usize = 77; // discriminator (8) + version (1) + u16 (2) + [u8; 32] (32) + Pubkey (32) + u8 (1) + bool (1)
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = write_discriminator::<Self>(dst);
+        dst[0..2].copy_from_slice(&self.chain_id.to_le_bytes());
+        dst[2..34].copy_from_slice(&self.foreign_token);
+        dst[34..66].copy_from_slice(self.mint.as_ref());
+        dst[66] = self.decimals;
+        dst[67] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = read_discriminator::<Self>(src)?;
+        Ok(WrappedAsset {
+            chain_id: u16::from_le_bytes(src[0..2].try_into().unwrap()),
+            foreign_token: src[2..34].try_into().unwrap(),
+            mint: Pubkey::new_from_array(src[34..66].try_into().unwrap()),
+            decimals: src[66],
+            is_initialized: src[67] != 0,
+        })
+    }
+}
+
+fn load_wrapped_asset(program_id: &Pubkey, wrapped_asset_acc: &AccountInfo, mint: &Pubkey) -> Result<WrappedAsset, ProgramError> {
+    assert_owned_by(wrapped_asset_acc, program_id)?;
+    assert_pda(wrapped_asset_acc, wrapped_asset_address(program_id, mint))?;
+    WrappedAsset::unpack(&wrapped_asset_acc.try_borrow_data()?)
+}
+
+/// The authority is the bridge admin, or the governance signer or authority
+/// PDA, checked against the program config; neither is kept. The payer funds
+/// the mint and the record.
+pub struct AttestForeignAssetAccounts<'a, 'info> {
+    pub wrapped_asset_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub payer_acc: &'a AccountInfo<'info>,
+    pub registry_acc: &'a AccountInfo<'info>,
+    pub vault_authority_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> AttestForeignAssetAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let wrapped_asset_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let authority_acc = next_account_info(account_info_iter)?;
+        let program_config_acc = next_account_info(account_info_iter)?;
+        let payer_acc = next_account_info(account_info_iter)?;
+        let registry_acc = next_account_info(account_info_iter)?;
+        let vault_authority_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        check_bridge_admin_or_governance(program_id, program_config_acc, authority_acc)?;
+        assert_signer(payer_acc)?;
+        CrossChainBridge::check_registry(program_id, registry_acc)?;
+        assert_pda(vault_authority_acc, CrossChainBridge::vault_authority(program_id))?;
+        if !token_program::is_supported(token_program_acc.key) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Ok(Self {
+            wrapped_asset_acc,
+            mint_acc,
+            payer_acc,
+            registry_acc,
+            vault_authority_acc,
+            token_program_acc,
+            system_program_acc,
+        })
+    }
+}
+
+pub struct CompleteInboundTransferAccounts<'a, 'info> {
+    pub wrapped_asset_acc: &'a AccountInfo<'info>,
+    pub mint_acc: &'a AccountInfo<'info>,
+    pub recipient_token_acc: &'a AccountInfo<'info>,
+    pub vault_authority_acc: &'a AccountInfo<'info>,
+    pub bridge_config_acc: &'a AccountInfo<'info>,
+    pub registry_acc: &'a AccountInfo<'info>,
+    pub instructions_sysvar_acc: &'a AccountInfo<'info>,
+    pub token_program_acc: &'a AccountInfo<'info>,
+    pub receipt_acc: &'a AccountInfo<'info>,
+    pub payer_acc: &'a AccountInfo<'info>,
+    pub system_program_acc: &'a AccountInfo<'info>,
+    pub vault_authority_bump: u8,
+}
+
+impl<'a, 'info> CompleteInboundTransferAccounts<'a, 'info> {
+    pub fn try_from(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let wrapped_asset_acc = next_account_info(account_info_iter)?;
+        let mint_acc = next_account_info(account_info_iter)?;
+        let recipient_token_acc = next_account_info(account_info_iter)?;
+        let vault_authority_acc = next_account_info(account_info_iter)?;
+        let bridge_config_acc = next_account_info(account_info_iter)?;
+        let registry_acc = next_account_info(account_info_iter)?;
+        let instructions_sysvar_acc = next_account_info(account_info_iter)?;
+        let token_program_acc = next_account_info(account_info_iter)?;
+        let receipt_acc = next_account_info(account_info_iter)?;
+        let payer_acc = next_account_info(account_info_iter)?;
+        let system_program_acc = next_account_info(account_info_iter)?;
+
+        assert_signer(payer_acc)?;
+        token_program::check_token_program(token_program_acc, mint_acc)?;
+        assert_token_account_mint(recipient_token_acc, mint_acc.key)?;
+        let vault_authority_bump = assert_pda(vault_authority_acc, CrossChainBridge::vault_authority(program_id))?;
+        assert_owned_by(bridge_config_acc, program_id)?;
+        CrossChainBridge::check_registry(program_id, registry_acc)?;
+        Ok(Self {
+            wrapped_asset_acc,
+            mint_acc,
+            recipient_token_acc,
+            vault_authority_acc,
+            bridge_config_acc,
+            registry_acc,
+            instructions_sysvar_acc,
+            token_program_acc,
+            receipt_acc,
+            payer_acc,
+            system_program_acc,
+            vault_authority_bump,
+        })
+    }
+}
+
+/// Registers `foreign_token` on `source_chain_id` and creates its wrapped
+/// mint. The chain must be in the chain registry, and each foreign token can
+/// only be attested once.
+pub fn attest_foreign_asset(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    source_chain_id: u16,
+    foreign_token: [u8; 32],
+    decimals: u8,
+) -> ProgramResult {
+    let AttestForeignAssetAccounts {
+        wrapped_asset_acc,
+        mint_acc,
+        payer_acc,
+        registry_acc,
+        vault_authority_acc,
+        token_program_acc,
+        system_program_acc,
+    } = AttestForeignAssetAccounts::try_from(program_id, accounts)?;
+    let chain = CrossChainBridge::registered_chain(registry_acc, source_chain_id)?;
+    if decimals > MAX_WRAPPED_DECIMALS {
+        msg!("Wrapped mints carry at most {} decimals", MAX_WRAPPED_DECIMALS);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mint_bump = assert_pda(mint_acc, wrapped_mint_address(program_id, source_chain_id, &foreign_token))?;
+    let record_bump = assert_pda(wrapped_asset_acc, wrapped_asset_address(program_id, mint_acc.key))?;
+    if mint_acc.lamports() > 0 || wrapped_asset_acc.lamports() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let rent = Rent::get()?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_acc.key,
+            mint_acc.key,
+            rent.minimum_balance(Mint::LEN),
+            Mint::LEN as u64,
+            token_program_acc.key,
+        ),
+        &[payer_acc.clone(), mint_acc.clone(), system_program_acc.clone()],
+        &[&[WRAPPED_MINT_SEED, &source_chain_id.to_le_bytes(), &foreign_token, &[mint_bump]]],
+    )?;
+    let ix = initialize_mint2(token_program_acc.key, mint_acc.key, vault_authority_acc.key, Some(vault_authority_acc.key), decimals)?;
+    invoke(&ix, &[mint_acc.clone(), token_program_acc.clone()])?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_acc.key,
+            wrapped_asset_acc.key,
+            rent.minimum_balance(WrappedAsset::LEN),
+            WrappedAsset::LEN as u64,
+            program_id,
+        ),
+        &[payer_acc.clone(), wrapped_asset_acc.clone(), system_program_acc.clone()],
+        &[&[WRAPPED_ASSET_SEED, mint_acc.key.as_ref(), &[record_bump]]],
+    )?;
+    let record = WrappedAsset { chain_id: source_chain_id, foreign_token, mint: *mint_acc.key, decimals, is_initialized: true };
+    pack_state(&record, wrapped_asset_acc)?;
+    msg!("Attested wrapped asset {} from {}", mint_acc.key, chain.name);
+    Ok(())
+}
+
+/// Mints `amount` wrapped tokens to the recipient once guardians have signed
+/// `wrapped_transfer_message` for it. Shares the receipt namespace with
+/// `release_tokens_on_target_chain`, so a message nonce is only ever used once
+/// per source chain. Anyone may submit the attestation; the payer funds the receipt.
+pub fn complete_inbound_transfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    nonce: u64,
+    source_chain_id: u16,
+) -> ProgramResult {
+    let CompleteInboundTransferAccounts {
+        wrapped_asset_acc,
+        mint_acc,
+        recipient_token_acc,
+        vault_authority_acc,
+        bridge_config_acc,
+        registry_acc,
+        instructions_sysvar_acc,
+        token_program_acc,
+        receipt_acc,
+        payer_acc,
+        system_program_acc,
+        vault_authority_bump,
+    } = CompleteInboundTransferAccounts::try_from(program_id, accounts)?;
+    let asset = load_wrapped_asset(program_id, wrapped_asset_acc, mint_acc.key)?;
+    if asset.chain_id != source_chain_id {
+        msg!("Wrapped asset {} comes from chain {}", mint_acc.key, asset.chain_id);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let chain = CrossChainBridge::registered_chain(registry_acc, source_chain_id)?;
+    let config = CrossChainBridge::load_config(bridge_config_acc)?;
+    CrossChainBridge::check_not_paused(&config)?;
+    let message = wrapped_transfer_message(&asset.foreign_token, mint_acc.key, amount, recipient_token_acc.key, nonce, source_chain_id);
+    CrossChainBridge::verify_attestation(&config, &chain, instructions_sysvar_acc, &message)?;
+    // Relayer fees are paid in GGT, so wrapped transfers owe none.
+    let receipt = BridgeReceipt {
+        source_chain_id,
+        nonce,
+        amount,
+        recipient: *recipient_token_acc.key,
+        relayer: *payer_acc.key,
+        relayer_fee: 0,
+        fee_claimed: false,
+        is_initialized: true,
+    };
+    CrossChainBridge::create_receipt(program_id, receipt_acc, payer_acc, system_program_acc, receipt)?;
+
+    token_program::mint_to(
+        token_program_acc,
+        mint_acc,
+        recipient_token_acc,
+        vault_authority_acc,
+        amount,
+        &[&[BRIDGE_VAULT_SEED, &[vault_authority_bump]]],
+    )?;
+    events::emit(&events::WrappedRelease {
+        source_chain_id,
+        foreign_token: asset.foreign_token,
+        mint: *mint_acc.key,
+        nonce,
+        recipient: *recipient_token_acc.key,
+        amount,
+    });
+    msg!("Minted {} wrapped tokens to {} (nonce {}, chain {})", amount, recipient_token_acc.key, nonce, source_chain_id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::new_ed25519_instruction;
+    use crate::cross_chain_bridge_contract::{AddressFormat, BridgeConfig, BridgeMode, ChainInfo, ChainRegistry, SignatureScheme};
+    use crate::error::GgtError;
+    use crate::multisig;
+    use crate::test_utils::{account_info, instructions_sysvar_data, program_config, token_account_data, SimulatedBank};
+    use solana_program::instruction::Instruction;
+
+    #[test]
+    fn test_attested_asset_mints_wrapped_tokens_for_signed_inbound_transfers() {
+        let bank = SimulatedBank::start();
+        let program_id = Pubkey::new_unique();
+        let (token_program_id, system_program_id) = (spl_token::id(), Pubkey::default());
+        let (admin_key, _) = multisig::multisig_authority_address(&program_id);
+        let (vault_authority, _) = CrossChainBridge::vault_authority(&program_id);
+        let (registry_key, _) = CrossChainBridge::chain_registry_address(&program_id);
+        let foreign_token = [0x22; 32];
+        let (mint_key, _) = wrapped_mint_address(&program_id, 2, &foreign_token);
+        let (record_key, _) = wrapped_asset_address(&program_id, &mint_key);
+        let (receipt_key, _) = CrossChainBridge::receipt_address(&program_id, 2, 7);
+        let (payer_key, recipient_key, config_key, guardian) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        let mut config_data = vec![0u8; BridgeConfig::LEN];
+        let config = BridgeConfig {
+            guardians: vec![guardian],
+            guardian_threshold: 1,
+            evm_guardians: Vec::new(),
+            evm_guardian_threshold: 0,
+            outbound_sequence: 0,
+            mode: BridgeMode::LockRelease,
+            daily_outflow_cap: 0,
+            chain_outflow_caps: Vec::new(),
+            transfer_timeout_seconds: 0,
+            paused: false,
+            is_initialized: true,
+        };
+        BridgeConfig::pack(config, &mut config_data).unwrap();
+        let mut registry_data = vec![0u8; ChainRegistry::LEN];
+        let ethereum = ChainInfo {
+            chain_id: 2,
+            name: "Ethereum".to_string(),
+            address_format: AddressFormat::Evm,
+            decimals: 8,
+            signature_scheme: SignatureScheme::Ed25519,
+            fee_flat: 0,
+            fee_bps: 0,
+            wormhole_chain_id: 0,
+            wormhole_emitter: [0; 32],
+        };
+        ChainRegistry::pack(ChainRegistry { chains: vec![ethereum], is_initialized: true }, &mut registry_data).unwrap();
+        let (mut record_data, mut mint_data, mut receipt_data) =
+            (vec![0u8; WrappedAsset::LEN], vec![0u8; Mint::LEN], vec![0u8; BridgeReceipt::LEN]);
+        let mut recipient_data = token_account_data(&mint_key, &Pubkey::new_unique(), 0);
+
+        let transaction = vec![
+            new_ed25519_instruction(&guardian, &[0u8; 64], &wrapped_transfer_message(&foreign_token, &mint_key, 5_000, &recipient_key, 7, 2)),
+            Instruction { program_id, accounts: vec![], data: vec![] },
+        ];
+        let mut sysvar_data = instructions_sysvar_data(&transaction, 1);
+        let sysvar_key = solana_instructions_sysvar::ID;
+
+        let (program_config_key, mut program_config_data) = program_config(&program_id);
+        let mut lamports = [0u64, 0, 0, 0, 10_000_000, 0, 0, 0, 0, 0, 0, 0, 0];
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10, l11, l12] = &mut lamports;
+        let (mut d2, mut d4, mut d6, mut d7, mut d8) = (vec![], vec![], vec![], vec![], vec![]);
+        let record_acc = account_info(&record_key, false, true, l0, &mut record_data, &program_id);
+        let mint_acc = account_info(&mint_key, false, true, l1, &mut mint_data, &token_program_id);
+        let admin_acc = account_info(&admin_key, true, false, l2, &mut d2, &program_id);
+        let program_config_acc = account_info(&program_config_key, false, false, l3, &mut program_config_data, &program_id);
+        let payer_acc = account_info(&payer_key, true, true, l4, &mut d4, &system_program_id);
+        let registry_acc = account_info(&registry_key, false, false, l5, &mut registry_data, &program_id);
+        let authority_acc = account_info(&vault_authority, false, false, l6, &mut d6, &program_id);
+        let token_program_acc = account_info(&token_program_id, false, false, l7, &mut d7, &program_id);
+        let system_program_acc = account_info(&system_program_id, false, false, l8, &mut d8, &system_program_id);
+        let recipient_acc = account_info(&recipient_key, false, true, l9, &mut recipient_data, &token_program_id);
+        let config_acc = account_info(&config_key, false, false, l10, &mut config_data, &program_id);
+        let sysvar_acc = account_info(&sysvar_key, false, false, l11, &mut sysvar_data, &program_id);
+        let receipt_acc = account_info(&receipt_key, false, true, l12, &mut receipt_data, &program_id);
+
+        let attest_accounts = [
+            record_acc.clone(),
+            mint_acc.clone(),
+            admin_acc,
+            program_config_acc,
+            payer_acc.clone(),
+            registry_acc.clone(),
+            authority_acc.clone(),
+            token_program_acc.clone(),
+            system_program_acc.clone(),
+        ];
+        assert_eq!(
+            attest_foreign_asset(&program_id, &attest_accounts, 2, foreign_token, 18),
+            Err(ProgramError::InvalidArgument)
+        );
+        attest_foreign_asset(&program_id, &attest_accounts, 2, foreign_token, 8).unwrap();
+        assert_eq!(
+            attest_foreign_asset(&program_id, &attest_accounts, 2, foreign_token, 8),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+        let asset = WrappedAsset::unpack(&record_acc.try_borrow_data().unwrap()).unwrap();
+        assert_eq!((asset.chain_id, asset.foreign_token, asset.mint, asset.decimals), (2, foreign_token, mint_key, 8));
+        // The simulated token program does not initialize mints.
+        let mint = Mint { mint_authority: Some(vault_authority).into(), decimals: 8, is_initialized: true, ..Mint::default() };
+        Mint::pack(mint, &mut mint_acc.try_borrow_mut_data().unwrap()).unwrap();
+
+        let complete_accounts = [
+            record_acc,
+            mint_acc,
+            recipient_acc,
+            authority_acc,
+            config_acc,
+            registry_acc,
+            sysvar_acc,
+            token_program_acc,
+            receipt_acc,
+            payer_acc,
+            system_program_acc,
+        ];
+        complete_inbound_transfer(&program_id, &complete_accounts, 5_000, 7, 2).unwrap();
+        assert_eq!(bank.balance(&recipient_key), 5_000);
+        assert_eq!(bank.events::<events::WrappedRelease>()[0].foreign_token, foreign_token);
+        assert_eq!(
+            complete_inbound_transfer(&program_id, &complete_accounts, 5_000, 7, 2),
+            Err(GgtError::BridgeMessageAlreadyProcessed.into())
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! Zero-copy views of fixed-size accounts read on hot paths. A view is a
+//! `#[repr(C, packed)]` struct laying out, byte for byte, what the account's
+//! `Pack` impl writes after its discriminator, so the same account can be
+//! unpacked or viewed in place. Packed views have an alignment of one and
+//! can be borrowed from account data at any offset.
+//!
+//! Only accounts with a fixed layout get a view; `BridgeConfig` keeps its
+//! Borsh encoding because its guardian sets and outflow caps vary in length.
+//!
+//! Vote weights are read through `PodStake`. The `cu-bench` crate tracks the
+//! compute units of `vote_on_proposal` alongside `stake` and `claim_rewards`,
+//! which still unpack.
+
+use bytemuck::Pod;
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+};
+use crate::discriminator::{read_discriminator, Discriminator, HEADER_LEN};
+
+/// A view of `Account`'s packed layout after its header.
+pub trait ZeroCopy: Pod + IsInitialized {
+    type Account: Discriminator + Pack;
+}
+
+/// Borrows the view in `data` after checking its tag and that it is
+/// initialized, as `Pack::unpack` does.
+pub fn load<T: ZeroCopy>(data: &[u8]) -> Result<&T, ProgramError> {
+    let body = read_discriminator::<T::Account>(data)?;
+    let view: &T = bytemuck::from_bytes(&body[..size_of::<T>()]);
+    if !view.is_initialized() {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    Ok(view)
+}
+
+/// Mutable counterpart of [`load`]; writes land in the account directly.
+pub fn load_mut<T: ZeroCopy>(data: &mut [u8]) -> Result<&mut T, ProgramError> {
+    read_discriminator::<T::Account>(data)?;
+    let view: &mut T = bytemuck::from_bytes_mut(&mut data[HEADER_LEN..HEADER_LEN + size_of::<T>()]);
+    if !view.is_initialized() {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    Ok(view)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discriminator::DISCRIMINATOR_LEN;
+    use crate::error::GgtError;
+    use crate::staking_contract::{PodStake, PodStakingPool, Stake, StakingPool};
+
+    #[test]
+    fn test_views_read_and_write_the_packed_layout() {
+        let stake = Stake { amount: 500, lock_until: 42, reward_per_token_paid: u128::MAX - 1, has_receipt: true, is_initialized: true, ..Stake::default() };
+        let mut data = vec![0u8; Stake::LEN];
+        stake.pack_into_slice(&mut data);
+        let view = load::<PodStake>(&data).unwrap();
+        assert_eq!(({ view.amount }, { view.lock_until }, { view.reward_per_token_paid }, view.has_receipt), (500, 42, u128::MAX - 1, 1));
+
+        // Mutating the view at an odd offset writes straight into the account.
+        let mut shifted = [&[0u8][..], &data].concat();
+        load_mut::<PodStake>(&mut shifted[1..]).unwrap().amount = 700;
+        assert_eq!(Stake::unpack(&shifted[1..]).unwrap().amount, 700);
+
+        assert_eq!(load::<PodStakingPool>(&[0u8; StakingPool::LEN]).err(), Some(ProgramError::UninitializedAccount));
+        assert_eq!(load::<PodStakingPool>(&data[..StakingPool::LEN - 1]).err(), Some(ProgramError::InvalidAccountData));
+        let mut pool_data = vec![0u8; StakingPool::LEN];
+        pool_data[..DISCRIMINATOR_LEN].copy_from_slice(&Stake::DISCRIMINATOR);
+        assert_eq!(load::<PodStakingPool>(&pool_data).err(), Some(GgtError::AccountDiscriminatorMismatch.into()));
+    }
+}
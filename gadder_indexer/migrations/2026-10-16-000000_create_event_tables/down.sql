@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS engagements;
+DROP TABLE IF EXISTS bridge_transfers;
+DROP TABLE IF EXISTS votes;
+DROP TABLE IF EXISTS proposals;
+DROP TABLE IF EXISTS stake_events;
+DROP TABLE IF EXISTS indexer_cursors;
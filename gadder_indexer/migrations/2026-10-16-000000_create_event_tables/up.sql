@@ -0,0 +1,85 @@
+-- Tables the indexer fills from program events. Every row keeps the
+-- signature and slot of the transaction that last wrote it.
+
+CREATE TABLE indexer_cursors (
+    program_id TEXT PRIMARY KEY,
+    last_signature TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- One row per StakeEvent, UnstakeEvent or StakeSlashed.
+CREATE TABLE stake_events (
+    id BIGSERIAL PRIMARY KEY,
+    signature TEXT NOT NULL,
+    event_index INT NOT NULL,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMP,
+    kind TEXT NOT NULL,
+    staker TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    amount BIGINT NOT NULL,
+    penalty BIGINT NOT NULL DEFAULT 0,
+    lock_until BIGINT,
+    UNIQUE (signature, event_index)
+);
+CREATE INDEX stake_events_staker ON stake_events (staker, mint);
+
+CREATE TABLE proposals (
+    proposal TEXT PRIMARY KEY,
+    proposer TEXT NOT NULL,
+    voting_ends_at BIGINT NOT NULL,
+    content_hash TEXT NOT NULL,
+    uri TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMP
+);
+
+-- The current vote of each voter; VoteChanged overwrites the row VoteCast made.
+CREATE TABLE votes (
+    proposal TEXT NOT NULL,
+    voter TEXT NOT NULL,
+    choice TEXT NOT NULL,
+    weight BIGINT NOT NULL,
+    signature TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMP,
+    PRIMARY KEY (proposal, voter)
+);
+
+-- One row per BridgeLock, BridgeRelease or BridgeTransferCancelled.
+CREATE TABLE bridge_transfers (
+    id BIGSERIAL PRIMARY KEY,
+    signature TEXT NOT NULL,
+    event_index INT NOT NULL,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMP,
+    kind TEXT NOT NULL,
+    transfer_id TEXT,
+    account TEXT NOT NULL,
+    -- Target chain of a lock, source chain of a release; cancellations leave it empty.
+    chain_id INT,
+    sequence BIGINT NOT NULL,
+    destination TEXT,
+    amount BIGINT NOT NULL,
+    fee BIGINT NOT NULL DEFAULT 0,
+    UNIQUE (signature, event_index)
+);
+CREATE INDEX bridge_transfers_transfer_id ON bridge_transfers (transfer_id);
+
+-- One row per engagement, updated as milestones are approved.
+CREATE TABLE engagements (
+    engagement TEXT PRIMARY KEY,
+    client TEXT NOT NULL,
+    consultant TEXT NOT NULL,
+    engagement_id BIGINT NOT NULL,
+    milestones BIGINT[] NOT NULL,
+    milestones_approved INT NOT NULL DEFAULT 0,
+    released BIGINT NOT NULL DEFAULT 0,
+    fees BIGINT NOT NULL DEFAULT 0,
+    completed BOOLEAN NOT NULL DEFAULT FALSE,
+    signature TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMP
+);
@@ -0,0 +1,120 @@
+//! Picks the program's events out of a transaction's log messages. The
+//! runtime logs each `sol_log_data` call as `Program data: <base64>...`
+//! under whichever program is executing, so the decoder follows the
+//! `invoke` / `success` / `failed` lines to attribute each one, and ignores
+//! data logged by other programs even when our program invoked them.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use gadder_gold_token::events::{
+    decode, BridgeLock, BridgeRelease, BridgeTransferCancelled, EngagementCompleted, EngagementOpened, MilestoneApproved,
+    ProposalCreated, StakeEvent, StakeSlashed, UnstakeEvent, VoteCast, VoteChanged,
+};
+use solana_program::pubkey::Pubkey;
+
+/// The events the indexer stores. Other program events are skipped.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IndexedEvent {
+    Stake(StakeEvent),
+    Unstake(UnstakeEvent),
+    StakeSlashed(StakeSlashed),
+    ProposalCreated(ProposalCreated),
+    VoteCast(VoteCast),
+    VoteChanged(VoteChanged),
+    BridgeLock(BridgeLock),
+    BridgeRelease(BridgeRelease),
+    BridgeTransferCancelled(BridgeTransferCancelled),
+    EngagementOpened(EngagementOpened),
+    MilestoneApproved(MilestoneApproved),
+    EngagementCompleted(EngagementCompleted),
+}
+
+impl IndexedEvent {
+    /// Decodes one `sol_log_data` field, or `None` if it is not an indexed event.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        decode(data).map(Self::Stake)
+            .or_else(|| decode(data).map(Self::Unstake))
+            .or_else(|| decode(data).map(Self::StakeSlashed))
+            .or_else(|| decode(data).map(Self::ProposalCreated))
+            .or_else(|| decode(data).map(Self::VoteCast))
+            .or_else(|| decode(data).map(Self::VoteChanged))
+            .or_else(|| decode(data).map(Self::BridgeLock))
+            .or_else(|| decode(data).map(Self::BridgeRelease))
+            .or_else(|| decode(data).map(Self::BridgeTransferCancelled))
+            .or_else(|| decode(data).map(Self::EngagementOpened))
+            .or_else(|| decode(data).map(Self::MilestoneApproved))
+            .or_else(|| decode(data).map(Self::EngagementCompleted))
+    }
+}
+
+/// Events `program_id` logged in one transaction, in log order. The index of
+/// an event counts every `Program data` line the program logged, indexed or
+/// not, so it stays stable if more event types are indexed later.
+pub fn program_events(program_id: &Pubkey, logs: &[String]) -> Vec<(i32, IndexedEvent)> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    let mut index = 0;
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else { continue };
+        if let Some(fields) = rest.strip_prefix("data: ") {
+            if stack.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            // `emit` logs a single field; anything else is not one of ours.
+            if let Some(event) = STANDARD.decode(fields).ok().and_then(|data| IndexedEvent::decode(&data)) {
+                events.push((index, event));
+            }
+            index += 1;
+        } else if let Some((id, status)) = rest.split_once(' ') {
+            if status.starts_with("invoke [") {
+                stack.push(id);
+            } else if status == "success" || status.starts_with("failed") {
+                stack.pop();
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gadder_gold_token::events::{Event, TransferEvent, VoteChoice};
+
+    fn data_line<E: Event>(event: &E) -> String {
+        let mut data = E::discriminator().to_vec();
+        borsh::to_writer(&mut data, event).unwrap();
+        format!("Program data: {}", STANDARD.encode(data))
+    }
+
+    #[test]
+    fn test_program_events_decodes_only_our_program_data() {
+        let (program_id, other_id) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let stake = StakeEvent { staker: Pubkey::new_unique(), mint: Pubkey::new_unique(), amount: 500, lock_until: 30 };
+        let vote = VoteCast { proposal: Pubkey::new_unique(), voter: Pubkey::new_unique(), choice: VoteChoice::Yes, weight: 500 };
+        let transfer = TransferEvent {
+            source: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            amount: 1,
+            fee: 0,
+        };
+        let logs = [
+            format!("Program {} invoke [1]", program_id),
+            data_line(&transfer),
+            data_line(&stake),
+            format!("Program {} invoke [2]", other_id),
+            data_line(&vote),
+            format!("Program {} success", other_id),
+            "Program log: Staked".to_string(),
+            data_line(&vote),
+            format!("Program {} success", program_id),
+            data_line(&stake),
+        ];
+
+        assert_eq!(
+            program_events(&program_id, &logs),
+            vec![(1, IndexedEvent::Stake(stake)), (2, IndexedEvent::VoteCast(vote))]
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Off-chain indexer for the GGT program. It reads confirmed transactions
+//! that touched the program over RPC, decodes the typed events the program
+//! logs (see `gadder_gold_token::events`), and writes stakes, proposals,
+//! votes, bridge transfers and engagements into Postgres, so the web app can
+//! query tables instead of parsing logs itself.
+
+use std::fmt;
+
+pub mod decode;
+pub mod rpc;
+pub mod schema;
+pub mod store;
+
+#[derive(Debug)]
+pub enum Error {
+    Database(diesel::result::Error),
+    Rpc(String),
+    /// An amount or timestamp that does not fit the BIGINT column it goes in.
+    OutOfRange(u64),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Database(err) => write!(f, "database error: {}", err),
+            Error::Rpc(message) => write!(f, "rpc error: {}", message),
+            Error::OutOfRange(value) => write!(f, "{} does not fit in a BIGINT column", value),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<diesel::result::Error> for Error {
+    fn from(err: diesel::result::Error) -> Self {
+        Error::Database(err)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Error::Rpc(err.to_string())
+    }
+}
@@ -0,0 +1,62 @@
+//! Polls RPC for new transactions of the GGT program and stores their events.
+//!
+//! Configured through the environment (a `.env` file works too):
+//! `DATABASE_URL`, `RPC_URL`, `GGT_PROGRAM_ID`, and optionally `COMMITMENT`
+//! (default `confirmed`) and `POLL_INTERVAL_SECS` (default 5).
+
+use std::{env, str::FromStr, thread, time::Duration};
+
+use diesel::{pg::PgConnection, Connection};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use gadder_indexer::{
+    decode::program_events,
+    rpc::RpcClient,
+    store::{self, IndexedTransaction},
+    Error,
+};
+use solana_program::pubkey::Pubkey;
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+fn required(name: &str) -> String {
+    env::var(name).unwrap_or_else(|_| panic!("{} must be set", name))
+}
+
+/// Stores every transaction since the cursor; returns how many it stored.
+fn poll(conn: &mut PgConnection, rpc: &RpcClient, program_id: &Pubkey) -> Result<usize, Error> {
+    let until = store::cursor(conn, program_id)?;
+    let signatures = rpc.signatures_since(program_id, until.as_deref())?;
+    for info in &signatures {
+        let Some(logs) = rpc.transaction_logs(&info.signature)? else {
+            // Not served yet at this commitment; the next poll resumes here.
+            return Err(Error::Rpc(format!("transaction {} at slot {} is not available yet", info.signature, info.slot)));
+        };
+        let events = if logs.failed { Vec::new() } else { program_events(program_id, &logs.logs) };
+        let tx = IndexedTransaction { signature: info.signature.clone(), slot: logs.slot, block_time: logs.block_time, events };
+        store::apply(conn, program_id, &tx)?;
+        log::debug!("Indexed {} with {} events", tx.signature, tx.events.len());
+    }
+    Ok(signatures.len())
+}
+
+fn main() {
+    dotenv::dotenv().ok();
+    env_logger::init();
+
+    let program_id = Pubkey::from_str(&required("GGT_PROGRAM_ID")).expect("GGT_PROGRAM_ID is not a valid address");
+    let commitment = env::var("COMMITMENT").unwrap_or_else(|_| "confirmed".to_string());
+    let interval = env::var("POLL_INTERVAL_SECS").ok().and_then(|secs| secs.parse().ok()).unwrap_or(5);
+    let rpc = RpcClient::new(required("RPC_URL"), commitment);
+    let mut conn = PgConnection::establish(&required("DATABASE_URL")).expect("Error connecting to the database");
+    conn.run_pending_migrations(MIGRATIONS).expect("Error running indexer migrations");
+
+    log::info!("Indexing {}", program_id);
+    loop {
+        match poll(&mut conn, &rpc, &program_id) {
+            Ok(0) => {}
+            Ok(count) => log::info!("Indexed {} transactions", count),
+            Err(err) => log::error!("Poll failed: {}", err),
+        }
+        thread::sleep(Duration::from_secs(interval));
+    }
+}
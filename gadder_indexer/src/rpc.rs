@@ -0,0 +1,85 @@
+//! The two JSON-RPC calls the indexer needs, over a blocking HTTP client.
+
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+use crate::Error;
+
+/// `getSignaturesForAddress` returns at most this many signatures per call.
+const SIGNATURE_PAGE: usize = 1_000;
+
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+}
+
+pub struct TransactionLogs {
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Whether the transaction failed; its events were rolled back with it.
+    pub failed: bool,
+    pub logs: Vec<String>,
+}
+
+pub struct RpcClient {
+    http: reqwest::blocking::Client,
+    url: String,
+    commitment: String,
+}
+
+impl RpcClient {
+    pub fn new(url: String, commitment: String) -> Self {
+        RpcClient { http: reqwest::blocking::Client::new(), url, commitment }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value, Error> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let mut response: Value = self.http.post(&self.url).json(&request).send()?.error_for_status()?.json()?;
+        if let Some(error) = response.get("error") {
+            return Err(Error::Rpc(format!("{} failed: {}", method, error)));
+        }
+        Ok(response["result"].take())
+    }
+
+    /// Signatures of transactions that mention `address` after `until`, or
+    /// its whole history when `until` is `None`, oldest first.
+    pub fn signatures_since(&self, address: &Pubkey, until: Option<&str>) -> Result<Vec<SignatureInfo>, Error> {
+        let mut signatures = Vec::new();
+        let mut before: Option<String> = None;
+        loop {
+            let config = json!({ "limit": SIGNATURE_PAGE, "before": before, "until": until, "commitment": self.commitment });
+            let page = self.call("getSignaturesForAddress", json!([address.to_string(), config]))?;
+            let page = page.as_array().ok_or_else(|| Error::Rpc("getSignaturesForAddress returned no list".to_string()))?;
+            for entry in page {
+                let signature = entry["signature"].as_str().ok_or_else(|| Error::Rpc("signature missing".to_string()))?;
+                signatures.push(SignatureInfo { signature: signature.to_string(), slot: entry["slot"].as_u64().unwrap_or_default() });
+            }
+            if page.len() < SIGNATURE_PAGE {
+                break;
+            }
+            before = signatures.last().map(|info| info.signature.clone());
+        }
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    /// Slot, time, status and log messages of a transaction, or `None` if the
+    /// node no longer has it.
+    pub fn transaction_logs(&self, signature: &str) -> Result<Option<TransactionLogs>, Error> {
+        let config = json!({ "encoding": "json", "commitment": self.commitment, "maxSupportedTransactionVersion": 0 });
+        let transaction = self.call("getTransaction", json!([signature, config]))?;
+        if transaction.is_null() {
+            return Ok(None);
+        }
+        let meta = &transaction["meta"];
+        let logs = meta["logMessages"]
+            .as_array()
+            .map(|lines| lines.iter().filter_map(|line| line.as_str().map(str::to_string)).collect())
+            .unwrap_or_default();
+        Ok(Some(TransactionLogs {
+            slot: transaction["slot"].as_u64().unwrap_or_default(),
+            block_time: transaction["blockTime"].as_i64(),
+            failed: !meta["err"].is_null(),
+            logs,
+        }))
+    }
+}
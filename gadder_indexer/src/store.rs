@@ -0,0 +1,284 @@
+//! Writes decoded events into Postgres. A transaction's events and the
+//! cursor move past it are committed together, so a restart resumes at the
+//! first transaction that was not fully stored and never applies one twice.
+
+use chrono::{DateTime, NaiveDateTime};
+use diesel::{pg::PgConnection, prelude::*};
+use gadder_gold_token::events::VoteChoice;
+use solana_program::pubkey::Pubkey;
+use crate::{decode::IndexedEvent, schema::*, Error};
+
+/// One transaction's worth of events, as `program_events` returned them.
+pub struct IndexedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub events: Vec<(i32, IndexedEvent)>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = stake_events)]
+struct StakeEventRow<'a> {
+    signature: &'a str,
+    event_index: i32,
+    slot: i64,
+    block_time: Option<NaiveDateTime>,
+    kind: &'static str,
+    staker: String,
+    mint: String,
+    amount: i64,
+    penalty: i64,
+    lock_until: Option<i64>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = proposals)]
+struct ProposalRow<'a> {
+    proposal: String,
+    proposer: String,
+    voting_ends_at: i64,
+    content_hash: String,
+    uri: &'a str,
+    signature: &'a str,
+    slot: i64,
+    block_time: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable, AsChangeset)]
+#[diesel(table_name = votes)]
+struct VoteRow<'a> {
+    proposal: String,
+    voter: String,
+    choice: &'static str,
+    weight: i64,
+    signature: &'a str,
+    slot: i64,
+    block_time: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = bridge_transfers)]
+struct BridgeTransferRow<'a> {
+    signature: &'a str,
+    event_index: i32,
+    slot: i64,
+    block_time: Option<NaiveDateTime>,
+    kind: &'static str,
+    transfer_id: Option<String>,
+    account: String,
+    chain_id: Option<i32>,
+    sequence: i64,
+    destination: Option<String>,
+    amount: i64,
+    fee: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = engagements)]
+struct EngagementRow<'a> {
+    engagement: String,
+    client: String,
+    consultant: String,
+    engagement_id: i64,
+    milestones: Vec<i64>,
+    signature: &'a str,
+    slot: i64,
+    block_time: Option<NaiveDateTime>,
+}
+
+fn bigint(value: u64) -> Result<i64, Error> {
+    i64::try_from(value).map_err(|_| Error::OutOfRange(value))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn choice_name(choice: VoteChoice) -> &'static str {
+    match choice {
+        VoteChoice::No => "no",
+        VoteChoice::Yes => "yes",
+        VoteChoice::Abstain => "abstain",
+    }
+}
+
+/// The last transaction stored for `program_id`, where the next poll resumes.
+pub fn cursor(conn: &mut PgConnection, program_id: &Pubkey) -> Result<Option<String>, Error> {
+    Ok(indexer_cursors::table
+        .find(program_id.to_string())
+        .select(indexer_cursors::last_signature)
+        .first(conn)
+        .optional()?)
+}
+
+/// Stores `tx`'s events and moves the cursor for `program_id` past it.
+pub fn apply(conn: &mut PgConnection, program_id: &Pubkey, tx: &IndexedTransaction) -> Result<(), Error> {
+    conn.transaction(|conn| {
+        for (index, event) in &tx.events {
+            apply_event(conn, tx, *index, event)?;
+        }
+        let slot = bigint(tx.slot)?;
+        diesel::insert_into(indexer_cursors::table)
+            .values((
+                indexer_cursors::program_id.eq(program_id.to_string()),
+                indexer_cursors::last_signature.eq(&tx.signature),
+                indexer_cursors::slot.eq(slot),
+            ))
+            .on_conflict(indexer_cursors::program_id)
+            .do_update()
+            .set((
+                indexer_cursors::last_signature.eq(&tx.signature),
+                indexer_cursors::slot.eq(slot),
+                indexer_cursors::updated_at.eq(diesel::dsl::now),
+            ))
+            .execute(conn)?;
+        Ok(())
+    })
+}
+
+fn apply_event(conn: &mut PgConnection, tx: &IndexedTransaction, event_index: i32, event: &IndexedEvent) -> Result<(), Error> {
+    let signature = tx.signature.as_str();
+    let slot = bigint(tx.slot)?;
+    let block_time = tx.block_time.and_then(|time| DateTime::from_timestamp(time, 0)).map(|time| time.naive_utc());
+    let stake_row = |kind, staker: &Pubkey, mint: &Pubkey, amount, penalty, lock_until| -> Result<StakeEventRow, Error> {
+        Ok(StakeEventRow {
+            signature,
+            event_index,
+            slot,
+            block_time,
+            kind,
+            staker: staker.to_string(),
+            mint: mint.to_string(),
+            amount: bigint(amount)?,
+            penalty: bigint(penalty)?,
+            lock_until,
+        })
+    };
+    let vote_row = |proposal: &Pubkey, voter: &Pubkey, choice, weight| -> Result<VoteRow, Error> {
+        Ok(VoteRow {
+            proposal: proposal.to_string(),
+            voter: voter.to_string(),
+            choice: choice_name(choice),
+            weight: bigint(weight)?,
+            signature,
+            slot,
+            block_time,
+        })
+    };
+    let bridge_row = |kind, transfer_id: Option<&[u8; 32]>, account: &Pubkey, chain_id: Option<u16>, sequence, destination: Option<&[u8]>, amount, fee| {
+        Ok::<_, Error>(BridgeTransferRow {
+            signature,
+            event_index,
+            slot,
+            block_time,
+            kind,
+            transfer_id: transfer_id.map(|id| hex(id)),
+            account: account.to_string(),
+            chain_id: chain_id.map(i32::from),
+            sequence: bigint(sequence)?,
+            destination: destination.map(hex),
+            amount: bigint(amount)?,
+            fee: bigint(fee)?,
+        })
+    };
+
+    match event {
+        IndexedEvent::Stake(e) => insert_stake(conn, stake_row("stake", &e.staker, &e.mint, e.amount, 0, Some(e.lock_until))?),
+        IndexedEvent::Unstake(e) => insert_stake(conn, stake_row("unstake", &e.staker, &e.mint, e.amount, e.penalty, None)?),
+        IndexedEvent::StakeSlashed(e) => insert_stake(conn, stake_row("slash", &e.staker, &e.mint, e.amount, 0, None)?),
+        IndexedEvent::ProposalCreated(e) => {
+            let row = ProposalRow {
+                proposal: e.proposal.to_string(),
+                proposer: e.proposer.to_string(),
+                voting_ends_at: e.voting_ends_at,
+                content_hash: hex(&e.content_hash),
+                uri: &e.uri,
+                signature,
+                slot,
+                block_time,
+            };
+            diesel::insert_into(proposals::table).values(&row).on_conflict_do_nothing().execute(conn)?;
+            Ok(())
+        }
+        IndexedEvent::VoteCast(e) => upsert_vote(conn, vote_row(&e.proposal, &e.voter, e.choice, e.weight)?),
+        IndexedEvent::VoteChanged(e) => upsert_vote(conn, vote_row(&e.proposal, &e.voter, e.choice, e.weight)?),
+        IndexedEvent::BridgeLock(e) => insert_bridge(
+            conn,
+            bridge_row("lock", Some(&e.transfer_id), &e.sender, Some(e.target_chain_id), e.sequence, Some(&e.destination), e.amount, e.fee)?,
+        ),
+        IndexedEvent::BridgeRelease(e) => {
+            insert_bridge(conn, bridge_row("release", None, &e.recipient, Some(e.source_chain_id), e.nonce, None, e.amount, 0)?)
+        }
+        IndexedEvent::BridgeTransferCancelled(e) => {
+            insert_bridge(conn, bridge_row("cancel", Some(&e.transfer_id), &e.sender, None, e.sequence, None, e.amount, e.fee)?)
+        }
+        IndexedEvent::EngagementOpened(e) => {
+            let row = EngagementRow {
+                engagement: e.engagement.to_string(),
+                client: e.client.to_string(),
+                consultant: e.consultant.to_string(),
+                engagement_id: bigint(e.engagement_id)?,
+                milestones: e.milestones.iter().map(|&amount| bigint(amount)).collect::<Result<_, _>>()?,
+                signature,
+                slot,
+                block_time,
+            };
+            diesel::insert_into(engagements::table).values(&row).on_conflict_do_nothing().execute(conn)?;
+            Ok(())
+        }
+        IndexedEvent::MilestoneApproved(e) => {
+            let updated = diesel::update(engagements::table.find(e.engagement.to_string()))
+                .set((
+                    engagements::milestones_approved.eq(i32::from(e.milestone_index) + 1),
+                    engagements::released.eq(engagements::released + bigint(e.amount)?),
+                    engagements::fees.eq(engagements::fees + bigint(e.fee)?),
+                    engagements::signature.eq(signature),
+                    engagements::slot.eq(slot),
+                    engagements::block_time.eq(block_time),
+                ))
+                .execute(conn)?;
+            if updated == 0 {
+                log::warn!("Milestone approved on unindexed engagement {}", e.engagement);
+            }
+            Ok(())
+        }
+        IndexedEvent::EngagementCompleted(e) => {
+            let updated = diesel::update(engagements::table.find(e.engagement.to_string()))
+                .set((
+                    engagements::released.eq(engagements::released + bigint(e.amount)?),
+                    engagements::fees.eq(engagements::fees + bigint(e.fee)?),
+                    engagements::completed.eq(true),
+                    engagements::signature.eq(signature),
+                    engagements::slot.eq(slot),
+                    engagements::block_time.eq(block_time),
+                ))
+                .execute(conn)?;
+            if updated == 0 {
+                log::warn!("Completed engagement {} was never indexed as opened", e.engagement);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn insert_stake(conn: &mut PgConnection, row: StakeEventRow) -> Result<(), Error> {
+    diesel::insert_into(stake_events::table).values(&row).on_conflict_do_nothing().execute(conn)?;
+    Ok(())
+}
+
+fn insert_bridge(conn: &mut PgConnection, row: BridgeTransferRow) -> Result<(), Error> {
+    diesel::insert_into(bridge_transfers::table).values(&row).on_conflict_do_nothing().execute(conn)?;
+    Ok(())
+}
+
+/// `VoteChanged` replaces the voter's row; a `VoteCast` seen again after a
+/// change never reaches here, as the cursor has moved past it.
+fn upsert_vote(conn: &mut PgConnection, row: VoteRow) -> Result<(), Error> {
+    diesel::insert_into(votes::table)
+        .values(&row)
+        .on_conflict((votes::proposal, votes::voter))
+        .do_update()
+        .set(&row)
+        .execute(conn)?;
+    Ok(())
+}